@@ -0,0 +1,314 @@
+use serde::de::{
+    DeserializeSeed,
+    Deserializer,
+    Error as SerdeDeError,
+    IntoDeserializer,
+    MapAccess,
+    SeqAccess,
+    Visitor,
+};
+
+use crate::{
+    bson::{de::Error, document::IntoIter as DocumentIntoIter, Bson},
+    options::NumericCoercion,
+};
+
+/// A deserializer for an owned `Bson` value that converts BSON doubles into integers when the type
+/// being deserialized asks for one, according to a [`NumericCoercion`] mode. Conversions between
+/// `Int32`, `Int64` and floating point targets are already handled by serde's primitive visitors,
+/// which return an error if the value is out of range for the target type.
+///
+/// Nested documents and arrays are deserialized with the same coercion; every other value is
+/// handed to `bson::Deserializer` unchanged.
+pub(crate) struct CoercingDeserializer {
+    value: Bson,
+    mode: NumericCoercion,
+}
+
+impl CoercingDeserializer {
+    pub(crate) fn new(value: Bson, mode: NumericCoercion) -> Self {
+        Self { value, mode }
+    }
+
+    fn inner(self) -> bson::Deserializer {
+        bson::Deserializer::new(self.value)
+    }
+}
+
+fn coerce_double<'de, V>(value: f64, mode: NumericCoercion, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if !value.is_finite() {
+        return Err(Error::custom(format!(
+            "cannot convert double {} to an integer",
+            value
+        )));
+    }
+    let integer = match mode {
+        NumericCoercion::Exact if value.fract() != 0.0 => {
+            return Err(Error::custom(format!(
+                "cannot convert double {} with a fractional part to an integer",
+                value
+            )))
+        }
+        NumericCoercion::Exact => value,
+        NumericCoercion::Truncate => value.trunc(),
+    };
+    // 2^64 and -2^63 are exactly representable as doubles, unlike the integer bounds themselves.
+    if (0.0..18_446_744_073_709_551_616.0).contains(&integer) {
+        visitor.visit_u64(integer as u64)
+    } else if (-9_223_372_036_854_775_808.0..0.0).contains(&integer) {
+        visitor.visit_i64(integer as i64)
+    } else {
+        Err(Error::custom(format!(
+            "double {} is out of range for an integer",
+            value
+        )))
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.value {
+                    Bson::Double(value) => coerce_double(value, self.mode, visitor),
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! forward_to_inner {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for CoercingDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mode = self.mode;
+        match self.value {
+            Bson::Document(doc) => visitor.visit_map(CoercingMapAccess {
+                iter: doc.into_iter(),
+                value: None,
+                mode,
+            }),
+            Bson::Array(array) => visitor.visit_seq(CoercingSeqAccess {
+                iter: array.into_iter(),
+                mode,
+            }),
+            value => bson::Deserializer::new(value).deserialize_any(visitor),
+        }
+    }
+
+    deserialize_integer!(
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+    );
+
+    forward_to_inner!(
+        deserialize_bool,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_unit,
+        deserialize_identifier,
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Bson::Null | Bson::Undefined => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        // The BSON library's own types use newtype names starting with "$__" to request special
+        // handling from its deserializer.
+        if name.starts_with("$__") {
+            self.inner().deserialize_newtype_struct(name, visitor)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Bson::Array(_) => self.deserialize_any(visitor),
+            _ => self.inner().deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Bson::Document(_) => self.deserialize_any(visitor),
+            _ => self.inner().deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Bson::Document(_) => self.deserialize_any(visitor),
+            _ => self.inner().deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        bson::Deserializer::new(Bson::Null).is_human_readable()
+    }
+}
+
+struct CoercingMapAccess {
+    iter: DocumentIntoIter,
+    value: Option<Bson>,
+    mode: NumericCoercion,
+}
+
+impl<'de> MapAccess<'de> for CoercingMapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(IntoDeserializer::<Error>::into_deserializer(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::custom("value requested before key"))?;
+        seed.deserialize(CoercingDeserializer::new(value, self.mode))
+    }
+}
+
+struct CoercingSeqAccess {
+    iter: std::vec::IntoIter<Bson>,
+    mode: NumericCoercion,
+}
+
+impl<'de> SeqAccess<'de> for CoercingSeqAccess {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.iter
+            .next()
+            .map(|value| seed.deserialize(CoercingDeserializer::new(value, self.mode)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
@@ -1,10 +1,12 @@
+mod coerce;
+
 use std::{
     convert::TryFrom,
     io::{Read, Write},
     time::Duration,
 };
 
-use bson::RawBsonRef;
+use bson::{RawBsonRef, RawDocument};
 use serde::{de::Error as SerdeDeError, ser, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
@@ -13,6 +15,8 @@ use crate::{
     runtime::SyncLittleEndianRead,
 };
 
+pub(crate) use coerce::CoercingDeserializer;
+
 /// Coerce numeric types into an `i64` if it would be lossless to do so. If this Bson is not numeric
 /// or the conversion would be lossy (e.g. 1.5 -> 1), this returns `None`.
 pub(crate) fn get_int(val: &Bson) -> Option<i64> {
@@ -46,6 +50,36 @@ pub(crate) fn get_u64(val: &Bson) -> Option<u64> {
     }
 }
 
+/// Whether `a` and `b` are equal as compared by the server, which compares numbers by value
+/// regardless of their type (e.g. `Int32(1)` and `Double(1.0)` are equal).
+pub(crate) fn values_equal(a: &Bson, b: &Bson) -> bool {
+    match (a, b) {
+        (
+            Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_),
+            Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_),
+        ) => match (get_int(a), get_int(b)) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => a.as_f64() == b.as_f64(),
+            _ => false,
+        },
+        _ => a == b,
+    }
+}
+
+/// Gets the value at the dotted `path` (e.g. `"a.b"`) in `doc`, descending through embedded
+/// documents. A top-level field whose name is the whole path is preferred.
+pub(crate) fn get_dotted<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    if let Some(value) = doc.get(path) {
+        return Some(value);
+    }
+    let mut parts = path.split('.');
+    let mut value = doc.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_document()?.get(part)?;
+    }
+    Some(value)
+}
+
 pub(crate) fn to_bson_array(docs: &[Document]) -> Bson {
     Bson::Array(docs.iter().map(|doc| Bson::Document(doc.clone())).collect())
 }
@@ -180,6 +214,94 @@ where
         .ok_or_else(|| D::Error::custom(format!("could not deserialize u64 from {:?}", bson)))
 }
 
+/// Deserializes a `T` from the given raw document. If deserialization fails, the returned error
+/// includes the path to the offending field (e.g. `address.zip` or `items[2].qty`).
+pub(crate) fn from_raw_document_with_path<'a, T>(doc: &'a RawDocument) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    bson::from_slice(doc.as_bytes()).map_err(|error| {
+        // The raw deserializer doesn't track its position in the document, so the path is
+        // recovered by replaying the deserialization against an owned copy. The replay is only
+        // trusted if it fails in the same way, since types that borrow from the input can fail
+        // differently when deserialized from owned data.
+        let replayed = Document::try_from(doc).ok().and_then(|doc| {
+            serde_path_to_error::deserialize::<_, T>(bson::Deserializer::new(Bson::Document(doc)))
+                .err()
+        });
+        match replayed {
+            Some(replayed) if replayed.inner().to_string() == error.to_string() => {
+                error_with_path(replayed)
+            }
+            _ => error.into(),
+        }
+    })
+}
+
+/// Deserializes a `T` using the given BSON deserializer. If deserialization fails, the returned
+/// error includes the path to the offending field.
+pub(crate) fn from_deserializer_with_path<'de, D, T>(deserializer: D) -> Result<T>
+where
+    D: Deserializer<'de, Error = bson::de::Error>,
+    T: Deserialize<'de>,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(error_with_path)
+}
+
+fn error_with_path(error: serde_path_to_error::Error<bson::de::Error>) -> Error {
+    if error.path().iter().next().is_none() {
+        return error.into_inner().into();
+    }
+    let path = error.path().to_string();
+    ErrorKind::BsonDeserialization(bson::de::Error::custom(format!(
+        "error deserializing field `{}`: {}",
+        path,
+        error.into_inner()
+    )))
+    .into()
+}
+
+/// Returns an error if `doc` has more than `max_depth` levels of nested documents and arrays,
+/// counting `doc` itself as the first level. The document is walked with an explicit stack rather
+/// than recursively, so checking a maliciously deep document can't overflow the stack.
+pub(crate) fn check_document_depth(doc: &RawDocument, max_depth: u32) -> Result<()> {
+    type Values<'a> = Box<dyn Iterator<Item = bson::raw::Result<RawBsonRef<'a>>> + 'a>;
+    fn values(doc: &RawDocument) -> Values<'_> {
+        Box::new(doc.iter().map(|element| element.map(|(_, value)| value)))
+    }
+
+    let too_deep = || -> Error {
+        ErrorKind::BsonDeserialization(bson::de::Error::custom(format!(
+            "document exceeds the maximum nesting depth of {}",
+            max_depth
+        )))
+        .into()
+    };
+    if max_depth == 0 {
+        return Err(too_deep());
+    }
+
+    let mut stack: Vec<Values> = vec![values(doc)];
+    while let Some(current) = stack.last_mut() {
+        let nested: Values = match current.next() {
+            Some(value) => match value? {
+                RawBsonRef::Document(doc) => values(doc),
+                RawBsonRef::Array(array) => Box::new(array.into_iter()),
+                _ => continue,
+            },
+            None => {
+                stack.pop();
+                continue;
+            }
+        };
+        if stack.len() as u32 >= max_depth {
+            return Err(too_deep());
+        }
+        stack.push(nested);
+    }
+    Ok(())
+}
+
 /// The size in bytes of the provided document's entry in a BSON array at the given index.
 pub(crate) fn array_entry_size_bytes(index: usize, doc_len: usize) -> u64 {
     //   * type (1 byte)
@@ -237,7 +359,20 @@ pub(crate) fn serialize_result_error_as_string<S: Serializer, T: Serialize>(
 
 #[cfg(test)]
 mod test {
-    use crate::bson_util::num_decimal_digits;
+    use bson::{doc, Bson, RawDocumentBuf};
+    use serde::Deserialize;
+
+    use crate::{
+        bson_util::{
+            check_document_depth,
+            from_raw_document_with_path,
+            num_decimal_digits,
+            values_equal,
+        },
+        cursor::deserialize_document,
+        error::ErrorKind,
+        options::NumericCoercion,
+    };
 
     #[test]
     fn num_digits() {
@@ -248,4 +383,186 @@ mod test {
         assert_eq!(num_decimal_digits(100), 3);
         assert_eq!(num_decimal_digits(125), 3);
     }
+
+    #[test]
+    fn numbers_equal_by_value() {
+        assert!(values_equal(&Bson::Int32(1), &Bson::Double(1.0)));
+        assert!(values_equal(&Bson::Int64(1), &Bson::Int32(1)));
+        assert!(values_equal(&Bson::Double(1.5), &Bson::Double(1.5)));
+        assert!(!values_equal(&Bson::Int32(1), &Bson::Double(1.5)));
+        assert!(!values_equal(
+            &Bson::Int32(1),
+            &Bson::String("1".to_string())
+        ));
+        assert!(values_equal(
+            &Bson::String("a".to_string()),
+            &Bson::String("a".to_string())
+        ));
+    }
+
+    #[test]
+    fn deserialization_error_path() {
+        #[derive(Debug, Deserialize)]
+        struct Order {
+            #[allow(dead_code)]
+            items: Vec<Item>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Item {
+            #[allow(dead_code)]
+            tags: Vec<Tag>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Tag {
+            #[allow(dead_code)]
+            weight: i32,
+        }
+
+        let doc = RawDocumentBuf::from_document(&doc! {
+            "items": [
+                { "tags": [{ "weight": 1 }] },
+                { "tags": [{ "weight": 2 }, { "weight": "heavy" }] },
+            ]
+        })
+        .unwrap();
+        let error = from_raw_document_with_path::<Order>(&doc).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("items[1].tags[1].weight"), "{}", message);
+        assert!(message.contains("expected i32"), "{}", message);
+
+        let doc = RawDocumentBuf::from_document(&doc! { "items": [{}] }).unwrap();
+        let error = from_raw_document_with_path::<Order>(&doc).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("items[0]"), "{}", message);
+        assert!(message.contains("missing field `tags`"), "{}", message);
+    }
+
+    #[test]
+    fn numeric_coercion() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Stats {
+            count: u64,
+            small: i32,
+            total: i64,
+            ratio: f64,
+            limit: Option<u32>,
+            history: Vec<i16>,
+            nested: Nested,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Nested {
+            value: u8,
+        }
+
+        let doc = RawDocumentBuf::from_document(&doc! {
+            "count": 7_i32,
+            "small": 12_i64,
+            "total": 5.0,
+            "ratio": 3_i32,
+            "limit": 10.0,
+            "history": [1.0, 2_i32, 3_i64],
+            "nested": { "value": 255.0 },
+        })
+        .unwrap();
+        let expected = Stats {
+            count: 7,
+            small: 12,
+            total: 5,
+            ratio: 3.0,
+            limit: Some(10),
+            history: vec![1, 2, 3],
+            nested: Nested { value: 255 },
+        };
+        for mode in [NumericCoercion::Exact, NumericCoercion::Truncate] {
+            let stats: Stats = deserialize_document(&doc, false, Some(mode)).unwrap();
+            assert_eq!(stats, expected);
+            let stats: Stats = deserialize_document(&doc, true, Some(mode)).unwrap();
+            assert_eq!(stats, expected);
+        }
+        // Doubles aren't accepted for integer fields without coercion.
+        assert!(deserialize_document::<Stats>(&doc, false, None).is_err());
+
+        #[derive(Debug, Deserialize)]
+        struct Single {
+            n: i64,
+        }
+        let coerce = |n: bson::Bson, mode| {
+            let doc = RawDocumentBuf::from_document(&doc! { "n": n }).unwrap();
+            deserialize_document::<Single>(&doc, false, Some(mode)).map(|single| single.n)
+        };
+
+        let error = coerce(5.5.into(), NumericCoercion::Exact).unwrap_err();
+        assert!(error.to_string().contains("fractional"), "{}", error);
+        assert_eq!(coerce(5.9.into(), NumericCoercion::Truncate).unwrap(), 5);
+        assert_eq!(
+            coerce((-5.9).into(), NumericCoercion::Truncate).unwrap(),
+            -5
+        );
+        assert!(coerce(1e20.into(), NumericCoercion::Truncate).is_err());
+        assert!(coerce(f64::NAN.into(), NumericCoercion::Truncate).is_err());
+
+        // Out of range values are rejected for the target type.
+        let doc = RawDocumentBuf::from_document(&doc! { "value": 256.0 }).unwrap();
+        assert!(deserialize_document::<Nested>(&doc, false, Some(NumericCoercion::Exact)).is_err());
+        let doc = RawDocumentBuf::from_document(&doc! { "value": -1_i32 }).unwrap();
+        assert!(deserialize_document::<Nested>(&doc, false, Some(NumericCoercion::Exact)).is_err());
+    }
+
+    #[test]
+    fn document_depth() {
+        fn nested(depth: u32) -> RawDocumentBuf {
+            let mut value = Bson::Int32(1);
+            for i in 1..depth {
+                value = if i % 2 == 0 {
+                    Bson::Document(doc! { "x": value })
+                } else {
+                    Bson::Array(vec![value])
+                };
+            }
+            RawDocumentBuf::from_document(&doc! { "x": value }).unwrap()
+        }
+
+        // Each level of nesting alternates between an array and a document.
+        let doc = nested(5);
+        check_document_depth(&doc, 5).unwrap();
+        check_document_depth(&doc, 100).unwrap();
+        let error = check_document_depth(&doc, 4).unwrap_err();
+        assert!(
+            matches!(*error.kind, ErrorKind::BsonDeserialization(_)),
+            "{:?}",
+            error
+        );
+        assert!(check_document_depth(&doc, 0).is_err());
+
+        // Siblings don't add to the depth.
+        let doc = RawDocumentBuf::from_document(&doc! {
+            "a": { "b": 1 },
+            "c": [1, 2, { "d": 3 }],
+            "e": { "f": 4 },
+        })
+        .unwrap();
+        check_document_depth(&doc, 3).unwrap();
+        assert!(check_document_depth(&doc, 2).is_err());
+
+        // A document too deep to deserialize recursively is rejected without overflowing the
+        // stack.
+        let mut bytes = Vec::new();
+        let depth = 100_000;
+        for _ in 0..depth {
+            // An embedded document with the key "x", whose length is filled in below.
+            bytes.extend_from_slice(&[0, 0, 0, 0, 0x03, b'x', 0]);
+        }
+        bytes.extend_from_slice(&[5, 0, 0, 0, 0]);
+        for level in (0..depth).rev() {
+            let start = level * 7;
+            bytes.push(0);
+            let len = (bytes.len() - start) as i32;
+            bytes[start..start + 4].copy_from_slice(&len.to_le_bytes());
+        }
+        let doc = RawDocumentBuf::from_bytes(bytes).unwrap();
+        assert!(check_document_depth(&doc, 100).is_err());
+    }
 }
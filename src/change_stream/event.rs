@@ -2,7 +2,11 @@
 #[cfg(test)]
 use std::convert::TryInto;
 
-use crate::{cursor::CursorSpecification, options::ChangeStreamOptions};
+use crate::{
+    cursor::CursorSpecification,
+    error::{ErrorKind, Result},
+    options::ChangeStreamOptions,
+};
 
 #[cfg(test)]
 use bson::Bson;
@@ -15,7 +19,9 @@ use serde::{Deserialize, Serialize};
 /// When starting a new change stream,
 /// [`crate::options::ChangeStreamOptions::start_after`] and
 /// [`crate::options::ChangeStreamOptions::resume_after`] fields can be specified
-/// with instances of `ResumeToken`.
+/// with instances of `ResumeToken`. A token saved to external storage (e.g. via its `Serialize`
+/// implementation) can be turned back into a `ResumeToken` with
+/// [`ResumeToken::from_document`] or its `Deserialize` implementation.
 ///
 /// See the documentation
 /// [here](https://www.mongodb.com/docs/manual/changeStreams/#change-stream-resume-token) for more
@@ -24,6 +30,30 @@ use serde::{Deserialize, Serialize};
 pub struct ResumeToken(pub(crate) RawBson);
 
 impl ResumeToken {
+    /// Constructs a `ResumeToken` from a document previously obtained from a change stream, e.g.
+    /// one loaded from external storage.
+    ///
+    /// The contents of the document are not checked, since the format of resume tokens is
+    /// determined by the server; a malformed token is reported by the server when a change stream
+    /// is started with it.
+    pub fn from_document(doc: Document) -> Result<Self> {
+        Ok(ResumeToken(RawBson::Document(
+            RawDocumentBuf::from_document(&doc)?,
+        )))
+    }
+
+    /// Checks that this token is a document so that a token deserialized from some other BSON
+    /// value is reported before a change stream is started with it.
+    pub(crate) fn validate(&self) -> Result<()> {
+        match &self.0 {
+            RawBson::Document(_) => Ok(()),
+            other => Err(ErrorKind::InvalidArgument {
+                message: format!("invalid resume token {:?}: expected a document", other),
+            }
+            .into()),
+        }
+    }
+
     pub(crate) fn initial(
         options: Option<&ChangeStreamOptions>,
         spec: &CursorSpecification,
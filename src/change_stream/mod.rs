@@ -11,9 +11,7 @@ use std::{
     task::{Context, Poll},
 };
 
-#[cfg(test)]
-use bson::RawDocumentBuf;
-use bson::{Document, Timestamp};
+use bson::{Document, RawBsonRef, RawDocumentBuf, Timestamp};
 use derivative::Derivative;
 use futures_core::{future::BoxFuture, Stream};
 use serde::de::DeserializeOwned;
@@ -21,12 +19,13 @@ use serde::de::DeserializeOwned;
 use tokio::sync::oneshot;
 
 use crate::{
+    bson_util,
     change_stream::{
         event::{ChangeStreamEvent, ResumeToken},
         options::ChangeStreamOptions,
     },
     cursor::{stream_poll_next, BatchValue, CursorStream, NextInBatchFuture},
-    error::{ErrorKind, Result},
+    error::{Error, ErrorKind, Result},
     operation::AggregateTarget,
     ClientSession,
     Cursor,
@@ -162,7 +161,7 @@ where
     /// ```
     pub async fn next_if_any(&mut self) -> Result<Option<T>> {
         Ok(match NextInBatchFuture::new(self).await? {
-            BatchValue::Some { doc, .. } => Some(bson::from_slice(doc.as_bytes())?),
+            BatchValue::Some { doc, .. } => Some(bson_util::from_raw_document_with_path(&doc)?),
             BatchValue::Empty | BatchValue::Exhausted => None,
         })
     }
@@ -210,17 +209,129 @@ pub(crate) struct ChangeStreamData {
 
     /// The implicit session used to create the original cursor.
     pub(crate) implicit_session: Option<ClientSession>,
+
+    /// The fragments received so far of an event split by `$changeStreamSplitLargeEvent`.
+    pub(crate) split_event_fragments: Vec<RawDocumentBuf>,
 }
 
 impl ChangeStreamData {
+    /// Takes the data needed to resume. Any fragments received of a split event are discarded,
+    /// since the resumed stream starts over from the event's first fragment.
     fn take(&mut self) -> Self {
+        self.split_event_fragments.clear();
         Self {
             initial_operation_time: self.initial_operation_time,
             resume_token: self.resume_token.clone(),
             resume_attempted: self.resume_attempted,
             document_returned: self.document_returned,
             implicit_session: self.implicit_session.take(),
+            split_event_fragments: Vec::new(),
+        }
+    }
+
+    /// Updates the cached resume token from a value returned by the cursor, reassembling events
+    /// that were split by `$changeStreamSplitLargeEvent`. Returns `None` if the value is a
+    /// fragment of a split event whose remaining fragments have not been received yet.
+    fn process_batch_value(
+        &mut self,
+        batch_value: BatchValue,
+        batch_token: Option<&ResumeToken>,
+    ) -> Result<Option<BatchValue>> {
+        let batch_value = match batch_value {
+            BatchValue::Some { doc, is_last } => {
+                let split = doc.get("splitEvent")?.map(parse_split_event).transpose()?;
+                match split {
+                    Some((fragment, of)) => {
+                        match self.add_split_event_fragment(doc, fragment, of)? {
+                            Some(doc) => BatchValue::Some { doc, is_last },
+                            None => return Ok(None),
+                        }
+                    }
+                    None if !self.split_event_fragments.is_empty() => {
+                        return Err(self.unexpected_fragment_error("an unsplit event"));
+                    }
+                    None => BatchValue::Some { doc, is_last },
+                }
+            }
+            // The resume token must not move past a partially received event, so that resuming
+            // restarts the event from its first fragment.
+            BatchValue::Empty if !self.split_event_fragments.is_empty() => {
+                return Ok(Some(BatchValue::Empty))
+            }
+            BatchValue::Empty => BatchValue::Empty,
+            BatchValue::Exhausted => {
+                self.split_event_fragments.clear();
+                BatchValue::Exhausted
+            }
+        };
+        if let Some(token) = get_resume_token(&batch_value, batch_token)? {
+            self.resume_token = Some(token);
+        }
+        if matches!(batch_value, BatchValue::Some { .. }) {
+            self.document_returned = true;
+        }
+        Ok(Some(batch_value))
+    }
+
+    fn add_split_event_fragment(
+        &mut self,
+        doc: RawDocumentBuf,
+        fragment: i32,
+        of: i32,
+    ) -> Result<Option<RawDocumentBuf>> {
+        // A first fragment always starts a new event.
+        if fragment == 1 {
+            self.split_event_fragments.clear();
+        }
+        if fragment > of || fragment as usize != self.split_event_fragments.len() + 1 {
+            return Err(self.unexpected_fragment_error(&format!("fragment {} of {}", fragment, of)));
+        }
+        self.split_event_fragments.push(doc);
+        if fragment < of {
+            return Ok(None);
+        }
+
+        let fragments = std::mem::take(&mut self.split_event_fragments);
+        let mut merged = RawDocumentBuf::new();
+        // The resume token of the last fragment is the resume token of the whole event.
+        if let Some(id) = fragments[fragments.len() - 1].get("_id")? {
+            merged.append("_id", id.to_raw_bson());
+        }
+        for fragment in &fragments {
+            for element in fragment {
+                let (key, value) = element?;
+                if key != "_id" && key != "splitEvent" {
+                    merged.append(key, value.to_raw_bson());
+                }
+            }
+        }
+        Ok(Some(merged))
+    }
+
+    fn unexpected_fragment_error(&self, received: &str) -> Error {
+        ErrorKind::InvalidResponse {
+            message: format!(
+                "expected fragment {} of a split change event, but received {}",
+                self.split_event_fragments.len() + 1,
+                received
+            ),
         }
+        .into()
+    }
+}
+
+fn parse_split_event(split_event: RawBsonRef) -> Result<(i32, i32)> {
+    let fields = split_event.as_document().and_then(|split_event| {
+        let fragment = split_event.get("fragment").ok()??.as_i32()?;
+        let of = split_event.get("of").ok()??.as_i32()?;
+        Some((fragment, of))
+    });
+    match fields {
+        Some((fragment, of)) if fragment >= 1 => Ok((fragment, of)),
+        _ => Err(ErrorKind::InvalidResponse {
+            message: format!("invalid splitEvent in change event: {:?}", split_event),
+        }
+        .into()),
     }
 }
 
@@ -271,16 +382,15 @@ where
                     Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
                 }
             }
-            let out = self.cursor.poll_next_in_batch(cx);
-            match &out {
+            match self.cursor.poll_next_in_batch(cx) {
                 Poll::Ready(Ok(bv)) => {
-                    if let Some(token) =
-                        get_resume_token(bv, self.cursor.post_batch_resume_token())?
+                    match self
+                        .data
+                        .process_batch_value(bv, self.cursor.post_batch_resume_token())?
                     {
-                        self.data.resume_token = Some(token);
-                    }
-                    if matches!(bv, BatchValue::Some { .. }) {
-                        self.data.document_returned = true;
+                        Some(bv) => return Poll::Ready(Ok(bv)),
+                        // Keep polling for the remaining fragments of a split event.
+                        None => continue,
                     }
                 }
                 Poll::Ready(Err(e)) if e.is_resumable() && !self.data.resume_attempted => {
@@ -298,9 +408,8 @@ where
                     // Iterate the loop so the new future gets polled and can register wakers.
                     continue;
                 }
-                _ => {}
+                out => return out,
             }
-            return out;
         }
     }
 }
@@ -315,3 +424,84 @@ where
         stream_poll_next(Pin::into_inner(self), cx)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bson::{doc, RawBson, RawDocumentBuf};
+
+    use super::{event::ResumeToken, ChangeStreamData};
+    use crate::cursor::BatchValue;
+
+    fn fragment(fragment: i32, of: i32) -> BatchValue {
+        let doc = doc! {
+            "_id": { "_data": format!("token{}", fragment) },
+            format!("field{}", fragment): fragment,
+            "splitEvent": { "fragment": fragment, "of": of },
+        };
+        BatchValue::Some {
+            doc: RawDocumentBuf::from_document(&doc).unwrap(),
+            is_last: false,
+        }
+    }
+
+    fn token(fragment: i32) -> ResumeToken {
+        let doc = doc! { "_data": format!("token{}", fragment) };
+        ResumeToken(RawBson::Document(
+            RawDocumentBuf::from_document(&doc).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn split_event_restarts_after_resume() {
+        let mut data = ChangeStreamData {
+            resume_token: Some(token(0)),
+            ..ChangeStreamData::default()
+        };
+
+        for i in 1..=2 {
+            assert!(data
+                .process_batch_value(fragment(i, 3), None)
+                .unwrap()
+                .is_none());
+            assert_eq!(data.resume_token, Some(token(0)));
+        }
+
+        // A resume restarts the event from its first fragment.
+        let resumed = data.take();
+        assert_eq!(resumed.resume_token, Some(token(0)));
+        assert!(resumed.split_event_fragments.is_empty());
+        assert!(data.split_event_fragments.is_empty());
+
+        for i in 1..=2 {
+            assert!(data
+                .process_batch_value(fragment(i, 3), None)
+                .unwrap()
+                .is_none());
+        }
+        let merged = match data.process_batch_value(fragment(3, 3), None).unwrap() {
+            Some(BatchValue::Some { doc, .. }) => doc.to_document().unwrap(),
+            _ => panic!("expected a reassembled event"),
+        };
+        assert_eq!(
+            merged,
+            doc! {
+                "_id": { "_data": "token3" },
+                "field1": 1,
+                "field2": 2,
+                "field3": 3,
+            }
+        );
+        assert_eq!(data.resume_token, Some(token(3)));
+        assert!(data.split_event_fragments.is_empty());
+    }
+
+    #[test]
+    fn split_event_out_of_order() {
+        let mut data = ChangeStreamData::default();
+        assert!(data
+            .process_batch_value(fragment(1, 3), None)
+            .unwrap()
+            .is_none());
+        assert!(data.process_batch_value(fragment(3, 3), None).is_err());
+    }
+}
@@ -5,7 +5,8 @@ use std::time::Duration;
 use typed_builder::TypedBuilder;
 
 use crate::{
-    bson::Timestamp,
+    bson::{Bson, Timestamp},
+    bson_util,
     change_stream::event::ResumeToken,
     collation::Collation,
     concern::ReadConcern,
@@ -38,6 +39,11 @@ pub struct ChangeStreamOptions {
     /// collection is dropped and recreated or newly renamed, `start_after` should be set instead.
     /// `resume_after` and `start_after` cannot be set simultaneously.
     ///
+    /// A token saved from a previous change stream can be restored with
+    /// [`ResumeToken::from_document`]. If the event the token refers to is no longer in the oplog,
+    /// the server returns a `ChangeStreamHistoryLost` error; this error is not resumable and is
+    /// returned to the caller rather than retried.
+    ///
     /// For more information on resuming a change stream see the documentation [here](https://www.mongodb.com/docs/manual/changeStreams/#change-stream-resume-after)
     #[builder(default)]
     pub resume_after: Option<ResumeToken>,
@@ -64,12 +70,36 @@ pub struct ChangeStreamOptions {
     pub(crate) all_changes_for_cluster: Option<bool>,
 
     /// The maximum amount of time for the server to wait on new documents to satisfy a change
-    /// stream query.
+    /// stream query. This is sent as the `maxTimeMS` option of the `getMore` commands issued while
+    /// iterating the change stream, not of the initial `aggregate`.
+    ///
+    /// The driver doesn't time out reads from its connections, so this can safely be larger than
+    /// the `socketTimeoutMS` specified in a connection string.
     #[builder(default)]
     #[serde(skip_serializing)]
     pub max_await_time: Option<Duration>,
 
-    /// The number of documents to return per batch.
+    /// The maximum amount of time to allow the initial `aggregate` command that opens the change
+    /// stream to run. Use [`max_await_time`](ChangeStreamOptions::max_await_time) to limit the
+    /// time the server waits for new changes on the subsequent `getMore` commands.
+    ///
+    /// This options maps to the `maxTimeMS` MongoDB query option, so the duration will be sent
+    /// across the wire as an integer number of milliseconds.
+    #[builder(default)]
+    #[serde(
+        skip_serializing,
+        rename = "maxTimeMS",
+        deserialize_with = "bson_util::deserialize_duration_option_from_u64_millis",
+        default
+    )]
+    pub max_time: Option<Duration>,
+
+    /// The number of change events to return per batch. This is sent on both the initial
+    /// aggregate command and each subsequent `getMore`.
+    ///
+    /// A value of 0 is sent to the server as is on the initial aggregate, which returns an
+    /// empty first batch; it is omitted from `getMore` commands, so the server's default batch
+    /// size is used for those.
     #[builder(default)]
     #[serde(skip_serializing)]
     pub batch_size: Option<u32>,
@@ -94,6 +124,25 @@ pub struct ChangeStreamOptions {
     #[builder(default)]
     #[serde(skip_serializing)]
     pub selection_criteria: Option<SelectionCriteria>,
+
+    /// Tags the query with an arbitrary [`Bson`] value to help trace the operation through the
+    /// database profiler, currentOp and logs.
+    ///
+    /// On server versions 4.4+, this comment will also be attached to the `getMore` commands
+    /// issued while iterating the change stream.
+    #[builder(default)]
+    #[serde(skip_serializing)]
+    pub comment: Option<Bson>,
+
+    /// If `true`, a `$changeStreamSplitLargeEvent` stage is appended to the change stream's
+    /// pipeline, so that the server splits change events exceeding the 16MB BSON size limit into
+    /// fragments rather than failing the change stream. The driver reassembles the fragments and
+    /// returns them as a single event, whose resume token is that of the last fragment.
+    ///
+    /// This feature is only available on MongoDB 7.0+.
+    #[builder(default)]
+    #[serde(skip_serializing)]
+    pub split_large_events: Option<bool>,
 }
 
 impl ChangeStreamOptions {
@@ -102,6 +151,7 @@ impl ChangeStreamOptions {
             .batch_size(self.batch_size)
             .collation(self.collation.clone())
             .max_await_time(self.max_await_time)
+            .max_time(self.max_time)
             .read_concern(self.read_concern.clone())
             .selection_criteria(self.selection_criteria.clone())
             .build()
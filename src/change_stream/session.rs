@@ -2,6 +2,7 @@
 use serde::de::DeserializeOwned;
 
 use crate::{
+    bson_util,
     cursor::{BatchValue, NextInBatchFuture},
     error::Result,
     ClientSession,
@@ -10,7 +11,6 @@ use crate::{
 
 use super::{
     event::{ChangeStreamEvent, ResumeToken},
-    get_resume_token,
     ChangeStreamData,
     WatchArgs,
 };
@@ -138,18 +138,17 @@ where
                 (next, post_batch_token, client)
             };
             match next {
-                Ok(bv) => {
-                    if let Some(token) = get_resume_token(&bv, post_batch_token.as_ref())? {
-                        self.data.resume_token = Some(token);
+                Ok(bv) => match self
+                    .data
+                    .process_batch_value(bv, post_batch_token.as_ref())?
+                {
+                    Some(BatchValue::Some { doc, .. }) => {
+                        return Ok(Some(bson_util::from_raw_document_with_path(&doc)?));
                     }
-                    match bv {
-                        BatchValue::Some { doc, .. } => {
-                            self.data.document_returned = true;
-                            return Ok(Some(bson::from_slice(doc.as_bytes())?));
-                        }
-                        BatchValue::Empty | BatchValue::Exhausted => return Ok(None),
-                    }
-                }
+                    Some(BatchValue::Empty | BatchValue::Exhausted) => return Ok(None),
+                    // Keep iterating for the remaining fragments of a split event.
+                    None => continue,
+                },
                 Err(e) if e.is_resumable() && !self.data.resume_attempted => {
                     self.data.resume_attempted = true;
                     let args = self.args.clone();
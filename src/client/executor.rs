@@ -2,7 +2,7 @@ use bson::{doc, RawBsonRef, RawDocument, Timestamp};
 use lazy_static::lazy_static;
 use serde::de::DeserializeOwned;
 
-use std::{collections::HashSet, sync::Arc, time::Instant};
+use std::{borrow::BorrowMut, collections::HashSet, sync::Arc, time::Instant};
 
 use super::{session::TransactionState, Client, ClientSession};
 use crate::{
@@ -41,7 +41,8 @@ use crate::{
         Operation,
         Retryability,
     },
-    options::{ChangeStreamOptions, SelectionCriteria},
+    options::{ChangeStreamOptions, SelectionCriteria, ServerAddress, ServerApi},
+    results::OperationRetries,
     sdam::{
         HandshakePhase,
         SelectedServer,
@@ -82,31 +83,28 @@ impl Client {
     /// Server selection will performed using the criteria specified on the operation, if any, and
     /// an implicit session will be created if the operation and write concern are compatible with
     /// sessions and an explicit session is not provided.
+    ///
+    /// The operation may be passed by mutable reference so that the state it records while being
+    /// executed (e.g. how many statements fit in the command that was sent) can be inspected
+    /// afterwards.
     pub(crate) async fn execute_operation<T: Operation>(
         &self,
-        op: T,
+        mut op: impl BorrowMut<T>,
         session: impl Into<Option<&mut ClientSession>>,
     ) -> Result<T::O> {
-        self.execute_operation_with_details(op, session)
+        self.execute_operation_with_details(op.borrow_mut(), session)
             .await
             .map(|details| details.output.operation_output)
     }
 
     async fn execute_operation_with_details<T: Operation>(
         &self,
-        op: T,
+        op: &mut T,
         session: impl Into<Option<&mut ClientSession>>,
     ) -> Result<ExecutionDetails<T>> {
         Box::pin(async {
-            // TODO RUST-9: allow unacknowledged write concerns
-            if !op.is_acknowledged() {
-                return Err(ErrorKind::InvalidArgument {
-                    message: "Unacknowledged write concerns are not supported".to_string(),
-                }
-                .into());
-            }
             let mut implicit_session = None;
-            let session = match session.into() {
+            let mut session = match session.into() {
                 Some(session) => {
                     if !Arc::ptr_eq(&self.inner, &session.client().inner) {
                         return Err(ErrorKind::InvalidArgument {
@@ -130,11 +128,29 @@ impl Client {
                     Some(session)
                 }
                 None => {
-                    implicit_session = self.start_implicit_session(&op).await?;
+                    implicit_session = self.start_implicit_session(op).await?;
                     implicit_session.as_mut()
                 }
             };
-            let output = self.execute_operation_with_retry(op, session).await?;
+            let is_write = op.is_write();
+            let retry_hook = self.inner.retry_hook.read().unwrap().clone();
+            let command_name = retry_hook.as_ref().map(|_| op.name().to_string());
+            let mut retries = 0;
+            let result = self
+                .execute_operation_with_retry(op, session.as_deref_mut(), &mut retries)
+                .await;
+            if let (Some(hook), Some(command_name)) = (retry_hook, command_name) {
+                hook(&OperationRetries {
+                    command_name,
+                    retries,
+                    succeeded: result.is_ok(),
+                });
+            }
+            if let Some(session) = session {
+                // Failed writes may still have been partially applied, so they are recorded too.
+                session.record_operation(is_write);
+            }
+            let output = result?;
             Ok(ExecutionDetails {
                 output,
                 implicit_session,
@@ -146,12 +162,12 @@ impl Client {
     /// Execute the given operation, returning the cursor created by the operation.
     ///
     /// Server selection be will performed using the criteria specified on the operation, if any.
-    pub(crate) async fn execute_cursor_operation<Op, T>(&self, op: Op) -> Result<Cursor<T>>
+    pub(crate) async fn execute_cursor_operation<Op, T>(&self, mut op: Op) -> Result<Cursor<T>>
     where
         Op: Operation<O = CursorSpecification>,
     {
         Box::pin(async {
-            let mut details = self.execute_operation_with_details(op, None).await?;
+            let mut details = self.execute_operation_with_details(&mut op, None).await?;
             let pinned = self.pin_connection_for_cursor(
                 &details.output.operation_output,
                 &mut details.output.connection,
@@ -166,16 +182,46 @@ impl Client {
         .await
     }
 
+    /// Executes a cursor operation in `session`, which is then owned by the returned cursor and
+    /// used for its getMores. This allows a `Cursor` to be backed by a session with options that
+    /// implicit sessions don't have, e.g. a snapshot session.
+    pub(crate) async fn execute_cursor_operation_in_owned_session<Op, T>(
+        &self,
+        mut op: Op,
+        mut session: ClientSession,
+    ) -> Result<Cursor<T>>
+    where
+        Op: Operation<O = CursorSpecification>,
+    {
+        Box::pin(async {
+            let mut details = self
+                .execute_operation_with_details(&mut op, &mut session)
+                .await?;
+            let pinned = self.pin_connection_for_session(
+                &details.output.operation_output,
+                &mut details.output.connection,
+                &mut session,
+            )?;
+            Ok(Cursor::new(
+                self.clone(),
+                details.output.operation_output,
+                Some(session),
+                pinned,
+            ))
+        })
+        .await
+    }
+
     pub(crate) async fn execute_session_cursor_operation<Op, T>(
         &self,
-        op: Op,
+        mut op: Op,
         session: &mut ClientSession,
     ) -> Result<SessionCursor<T>>
     where
         Op: Operation<O = CursorSpecification>,
     {
         let mut details = self
-            .execute_operation_with_details(op, &mut *session)
+            .execute_operation_with_details(&mut op, &mut *session)
             .await?;
 
         let pinned = self.pin_connection_for_session(
@@ -240,10 +286,10 @@ impl Client {
             let mut implicit_session = resume_data
                 .as_mut()
                 .and_then(|rd| rd.implicit_session.take());
-            let op = ChangeStreamAggregate::new(&args, resume_data)?;
+            let mut op = ChangeStreamAggregate::new(&args, resume_data)?;
 
             let mut details = self
-                .execute_operation_with_details(op, implicit_session.as_mut())
+                .execute_operation_with_details(&mut op, implicit_session.as_mut())
                 .await?;
             if let Some(session) = implicit_session {
                 details.implicit_session = Some(session);
@@ -276,10 +322,10 @@ impl Client {
                 target,
                 options,
             };
-            let op = ChangeStreamAggregate::new(&args, resume_data)?;
+            let mut op = ChangeStreamAggregate::new(&args, resume_data)?;
 
             let mut details = self
-                .execute_operation_with_details(op, &mut *session)
+                .execute_operation_with_details(&mut op, &mut *session)
                 .await?;
             let (cursor_spec, cs_data) = details.output.operation_output;
             let pinned = self.pin_connection_for_session(
@@ -295,11 +341,13 @@ impl Client {
     }
 
     /// Selects a server and executes the given operation on it, optionally using a provided
-    /// session. Retries the operation upon failure if retryability is supported.
+    /// session. Retries the operation upon failure if retryability is supported, incrementing
+    /// `retries` for each retry attempt that is made.
     async fn execute_operation_with_retry<T: Operation>(
         &self,
-        mut op: T,
+        op: &mut T,
         mut session: Option<&mut ClientSession>,
+        retries: &mut u32,
     ) -> Result<ExecutionOutput<T>> {
         // If the current transaction has been committed/aborted and it is not being
         // re-committed/re-aborted, reset the transaction's state to TransactionState::None.
@@ -320,15 +368,21 @@ impl Client {
             .and_then(|s| s.transaction.pinned_mongos())
             .or_else(|| op.selection_criteria());
 
-        let server = match self.select_server(selection_criteria).await {
-            Ok(server) => server,
+        let server = match self
+            .select_server_and_criteria(selection_criteria, op.override_criteria())
+            .await
+        {
+            Ok((server, effective_criteria)) => {
+                self.notify_server_selected(&effective_criteria, &server.address);
+                server
+            }
             Err(mut err) => {
                 err.add_labels_and_update_pin(None, &mut session, None)?;
                 return Err(err);
             }
         };
 
-        let mut conn = match get_connection(&session, &op, &server.pool).await {
+        let mut conn = match get_connection(&session, op, &server.pool).await {
             Ok(c) => c,
             Err(mut err) => {
                 err.add_labels_and_update_pin(None, &mut session, None)?;
@@ -336,31 +390,29 @@ impl Client {
                     err.add_label(RETRYABLE_WRITE_ERROR);
                 }
 
-                let op_retry = match self.get_op_retryability(&op, &session) {
+                let op_retry = match self.get_op_retryability(op, &session) {
                     Retryability::Read => err.is_read_retryable(),
                     Retryability::Write => err.is_write_retryable(),
                     _ => false,
                 };
                 if err.is_pool_cleared() || op_retry {
-                    return self.execute_retry(&mut op, &mut session, None, err).await;
+                    let failed_address = server.address.clone();
+                    drop(server);
+                    return self
+                        .execute_retry(op, &mut session, None, err, &failed_address, retries)
+                        .await;
                 } else {
                     return Err(err);
                 }
             }
         };
 
-        let retryability = self.get_retryability(&conn, &op, &session)?;
+        let retryability = self.get_retryability(&conn, op, &session)?;
 
         let txn_number = get_txn_number(&mut session, retryability);
 
         match self
-            .execute_operation_on_connection(
-                &mut op,
-                &mut conn,
-                &mut session,
-                txn_number,
-                retryability,
-            )
+            .execute_operation_on_connection(op, &mut conn, &mut session, txn_number, retryability)
             .await
         {
             Ok(operation_output) => Ok(ExecutionOutput {
@@ -394,12 +446,13 @@ impl Client {
                 // release the connection to be processed by the connection pool
                 drop(conn);
                 // release the selected server to decrement its operation count
+                let failed_address = server.address.clone();
                 drop(server);
 
                 if retryability == Retryability::Read && err.is_read_retryable()
                     || retryability == Retryability::Write && err.is_write_retryable()
                 {
-                    self.execute_retry(&mut op, &mut session, txn_number, err)
+                    self.execute_retry(op, &mut session, txn_number, err, &failed_address, retries)
                         .await
                 } else {
                     Err(err)
@@ -414,11 +467,24 @@ impl Client {
         session: &mut Option<&mut ClientSession>,
         prior_txn_number: Option<i64>,
         first_error: Error,
+        failed_address: &ServerAddress,
+        retries: &mut u32,
     ) -> Result<ExecutionOutput<T>> {
         op.update_for_retry();
 
-        let server = match self.select_server(op.selection_criteria()).await {
-            Ok(server) => server,
+        let server = match self
+            .select_server_for_retry(
+                op.selection_criteria(),
+                op.override_criteria(),
+                failed_address,
+                &first_error,
+            )
+            .await
+        {
+            Ok((server, effective_criteria)) => {
+                self.notify_server_selected(&effective_criteria, &server.address);
+                server
+            }
             Err(_) => {
                 return Err(first_error);
             }
@@ -436,6 +502,7 @@ impl Client {
 
         let txn_number = prior_txn_number.or_else(|| get_txn_number(session, retryability));
 
+        *retries += 1;
         match self
             .execute_operation_on_connection(op, &mut conn, session, txn_number, retryability)
             .await
@@ -480,6 +547,16 @@ impl Client {
         let stream_description = connection.stream_description()?;
         let is_sharded = stream_description.initial_server_type == ServerType::Mongos;
         let mut cmd = op.build(stream_description)?;
+        if let Some(ref comment) = self.inner.options.default_comment {
+            if op.supports_default_comment(stream_description) {
+                cmd.set_comment(comment.clone());
+            }
+        }
+        if let Some(allow_disk_use) = self.inner.options.aggregate_allow_disk_use_default {
+            if op.supports_default_allow_disk_use(stream_description) {
+                cmd.set_allow_disk_use(allow_disk_use);
+            }
+        }
         self.inner.topology.update_command_with_read_pref(
             connection.address(),
             &mut cmd,
@@ -507,6 +584,8 @@ impl Client {
                         return Err(Error::new(
                             ErrorKind::IncompatibleServer {
                                 message: "Snapshot reads require MongoDB 5.0 or later".into(),
+                                driver_range: None,
+                                server_range: None,
                             },
                             labels,
                         ));
@@ -532,6 +611,7 @@ impl Client {
                     TransactionState::Starting => {
                         cmd.set_start_transaction();
                         cmd.set_autocommit();
+                        session.transaction.server_api = self.server_api.clone();
 
                         if let Some(ref options) = session.transaction.options {
                             if let Some(ref read_concern) = options.read_concern {
@@ -588,7 +668,12 @@ impl Client {
         let service_id = connection.service_id();
         let request_id = crate::cmap::conn::next_request_id();
 
-        if let Some(ref server_api) = self.inner.options.server_api {
+        // Connections are shared by every database and collection, so they're established with
+        // the server API from the client options, if any.
+        if let Some(ref server_api) = self.server_api {
+            check_server_api_compatible(server_api, self.inner.options.server_api.as_ref())?;
+        }
+        if let Some(server_api) = self.server_api() {
             cmd.set_server_api(server_api);
         }
 
@@ -623,9 +708,13 @@ impl Client {
             handler.handle_command_started_event(command_started_event);
         });
 
+        self.record_command_sent(connection.address(), &cmd_name);
+
         let start_time = Instant::now();
+        let mut response_to = None;
         let command_result = match connection.send_raw_command(raw_cmd, request_id).await {
             Ok(response) => {
+                response_to = Some(response.response_to);
                 async fn handle_response<T: Operation>(
                     client: &Client,
                     op: &T,
@@ -676,12 +765,16 @@ impl Client {
                     if ok == 1 {
                         if let Some(ref mut session) = session {
                             if is_sharded && session.in_transaction() {
+                                // Keep the most recent token rather than clearing it if a
+                                // response doesn't include one.
                                 let recovery_token = raw_doc
                                     .get("recoveryToken")?
                                     .and_then(RawBsonRef::as_document)
                                     .map(|d| bson::from_slice(d.as_bytes()))
                                     .transpose()?;
-                                session.transaction.recovery_token = recovery_token;
+                                if recovery_token.is_some() {
+                                    session.transaction.recovery_token = recovery_token;
+                                }
                             }
                         }
 
@@ -713,6 +806,7 @@ impl Client {
                         command_name: cmd_name,
                         failure: err.clone(),
                         request_id,
+                        response_to,
                         connection: connection_info,
                         service_id,
                     };
@@ -744,6 +838,7 @@ impl Client {
                         reply,
                         command_name: cmd_name.clone(),
                         request_id,
+                        response_to: response.response_to,
                         connection: connection_info,
                         service_id,
                     };
@@ -765,8 +860,12 @@ impl Client {
         }
     }
 
-    /// Start an implicit session if the operation and write concern are compatible with sessions.
+    /// Start an implicit session if implicit sessions are enabled and the operation and write
+    /// concern are compatible with sessions.
     async fn start_implicit_session<T: Operation>(&self, op: &T) -> Result<Option<ClientSession>> {
+        if self.inner.options.disable_implicit_sessions == Some(true) {
+            return Ok(None);
+        }
         match self.get_session_support_status().await? {
             SessionSupportStatus::Supported {
                 logical_session_timeout,
@@ -785,7 +884,7 @@ impl Client {
             (matches!(topology_type, TopologyType::Single) && server_type.is_available())
                 || server_type.is_data_bearing()
         }));
-        let _: SelectedServer = self.select_server(Some(&criteria)).await?;
+        let _: SelectedServer = self.select_server(Some(&criteria), |_, _| None).await?;
         Ok(())
     }
 
@@ -836,6 +935,17 @@ impl Client {
         {
             return Retryability::None;
         }
+        // Unacknowledged writes are sent without a session, so they can't be retried safely.
+        if !op.is_acknowledged() {
+            return Retryability::None;
+        }
+        // Likewise for writes run without an explicit session when implicit sessions are disabled.
+        if op.retryability() == Retryability::Write
+            && session.is_none()
+            && self.inner.options.disable_implicit_sessions == Some(true)
+        {
+            return Retryability::None;
+        }
         match op.retryability() {
             Retryability::Read if self.inner.options.retry_reads != Some(false) => {
                 Retryability::Read
@@ -912,6 +1022,28 @@ async fn get_connection<T: Operation>(
     }
 }
 
+/// Checks that the server API declared for a database or collection can be used on connections
+/// established with `handshake_api`. A connection established without a server API accepts any
+/// version; otherwise, the versions must match, while the other settings may differ per command.
+fn check_server_api_compatible(
+    server_api: &ServerApi,
+    handshake_api: Option<&ServerApi>,
+) -> Result<()> {
+    match handshake_api {
+        Some(handshake_api) if handshake_api.version != server_api.version => {
+            Err(ErrorKind::InvalidArgument {
+                message: format!(
+                    "the server API version {} declared for the database or collection is \
+                     incompatible with the version {} declared for the client",
+                    server_api.version, handshake_api.version
+                ),
+            }
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
 fn get_txn_number(
     session: &mut Option<&mut ClientSession>,
     retryability: Retryability,
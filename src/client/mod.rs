@@ -6,38 +6,74 @@ pub mod options;
 pub mod session;
 
 use std::{
-    sync::Arc,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+        RwLock,
+        Weak,
+    },
     time::{Duration, Instant},
 };
 
 use derivative::Derivative;
 
-#[cfg(test)]
-use crate::options::ServerAddress;
 use crate::{
-    bson::Document,
+    bson::{doc, Bson, Document, Timestamp},
     change_stream::{
         event::ChangeStreamEvent,
         options::ChangeStreamOptions,
         session::SessionChangeStream,
         ChangeStream,
     },
+    cmap::{Connection, Handshaker},
     concern::{ReadConcern, WriteConcern},
     db::Database,
-    error::{ErrorKind, Result},
-    event::command::CommandEventHandler,
-    operation::{AggregateTarget, ListDatabases},
+    error::{Error, ErrorKind, Result},
+    event::{cmap::PoolStats, command::CommandEventHandler, cursor::CursorEventHandler},
+    hello::{hello_command, run_hello, LEGACY_HELLO_COMMAND_NAME},
+    operation::{
+        AggregateTarget,
+        ListDatabases,
+        OverrideCriteriaFn,
+        SetFeatureCompatibilityVersion,
+    },
     options::{
+        AggregateOptions,
         ClientOptions,
+        CursorType,
         DatabaseOptions,
+        FindOneOptions,
+        FindOptions,
         ListDatabasesOptions,
         ReadPreference,
         SelectionCriteria,
+        ServerAddress,
+        ServerApi,
         SessionOptions,
+        SessionUser,
+    },
+    results::{
+        CompressionStats,
+        DatabaseSpecification,
+        FeatureCompatibilityVersion,
+        HelloResult,
+        OpCounts,
+        OperationRetries,
+        OplogEntry,
+    },
+    runtime,
+    sdam::{
+        server_selection,
+        SelectedServer,
+        SessionSupportStatus,
+        Topology,
+        TopologyState,
+        MIN_HEARTBEAT_FREQUENCY,
     },
-    results::DatabaseSpecification,
-    sdam::{server_selection, SelectedServer, SessionSupportStatus, Topology},
     ClientSession,
+    Cursor,
 };
 pub(crate) use executor::{HELLO_COMMAND_NAMES, REDACTED_COMMANDS};
 pub(crate) use session::{ClusterTime, SESSIONS_UNSUPPORTED_COMMANDS};
@@ -45,6 +81,8 @@ pub(crate) use session::{ClusterTime, SESSIONS_UNSUPPORTED_COMMANDS};
 use session::{ServerSession, ServerSessionPool};
 
 const DEFAULT_SERVER_SELECTION_TIMEOUT: Duration = Duration::from_secs(30);
+const SESSION_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+const SERVER_5_0_0_WIRE_VERSION: i32 = 13;
 
 /// This is the main entry point for the API. A `Client` is used to connect to a MongoDB cluster.
 /// By default, it will monitor the topology of the cluster, keeping track of any changes, such
@@ -95,14 +133,33 @@ const DEFAULT_SERVER_SELECTION_TIMEOUT: Duration = Duration::from_secs(30);
 #[derive(Clone, Debug)]
 pub struct Client {
     inner: Arc<ClientInner>,
+    /// A server API that overrides the one from the client options, set for the handles used by
+    /// databases and collections that declare their own.
+    server_api: Option<ServerApi>,
 }
 
+/// A callback invoked with the criteria and the address of the selected server each time a server
+/// is selected for an operation.
+type ServerSelectionHook = Arc<dyn Fn(&SelectionCriteria, &ServerAddress) + Send + Sync>;
+type RetryHook = Arc<dyn Fn(&OperationRetries) + Send + Sync>;
+
 #[derive(Derivative)]
 #[derivative(Debug)]
 struct ClientInner {
     topology: Topology,
     options: ClientOptions,
     session_pool: ServerSessionPool,
+    #[derivative(Debug = "ignore")]
+    server_selection_hook: RwLock<Option<ServerSelectionHook>>,
+    #[derivative(Debug = "ignore")]
+    retry_hook: RwLock<Option<RetryHook>>,
+    /// The counters are atomic so that operations only need the read lock to record a command,
+    /// except for the first command sent to each server.
+    operation_counts: RwLock<HashMap<ServerAddress, Arc<AtomicOpCounts>>>,
+    created_at: Instant,
+    /// Clients connected directly to each shard of a sharded cluster, keyed by the shard's host
+    /// string from the most recent `listShards`.
+    shard_clients: Mutex<HashMap<String, Client>>,
     #[cfg(feature = "csfle")]
     csfle: tokio::sync::RwLock<Option<csfle::ClientState>>,
 }
@@ -120,17 +177,52 @@ impl Client {
     }
 
     /// Creates a new `Client` connected to the cluster specified by `options`.
-    pub fn with_options(options: ClientOptions) -> Result<Self> {
+    pub fn with_options(mut options: ClientOptions) -> Result<Self> {
         options.validate()?;
+        options.compression_stats = Some(Default::default());
 
         let inner = Arc::new(ClientInner {
             topology: Topology::new(options.clone())?,
             session_pool: ServerSessionPool::new(),
+            server_selection_hook: RwLock::new(None),
+            retry_hook: RwLock::new(None),
+            operation_counts: Default::default(),
+            created_at: Instant::now(),
+            shard_clients: Default::default(),
             #[cfg(feature = "csfle")]
             csfle: Default::default(),
             options,
         });
-        Ok(Self { inner })
+        Self::start_session_keepalive(Arc::downgrade(&inner));
+        Ok(Self {
+            inner,
+            server_api: None,
+        })
+    }
+
+    /// Starts a task that periodically refreshes the idle sessions in this client's session pool,
+    /// stopping once the client has been dropped.
+    fn start_session_keepalive(inner: Weak<ClientInner>) {
+        runtime::execute(async move {
+            loop {
+                runtime::delay_for(SESSION_KEEPALIVE_INTERVAL).await;
+                let client = match inner.upgrade() {
+                    Some(inner) => Client {
+                        inner,
+                        server_api: None,
+                    },
+                    None => return,
+                };
+                if let SessionSupportStatus::Supported {
+                    logical_session_timeout: Some(logical_session_timeout),
+                } = client.inner.topology.session_support_status()
+                {
+                    client
+                        .refresh_pooled_sessions(logical_session_timeout)
+                        .await;
+                }
+            }
+        });
     }
 
     /// Creates a new `Client` connected to the cluster specified by `options` with auto-encryption
@@ -151,6 +243,12 @@ impl Client {
         }
     }
 
+    pub(crate) fn emit_cursor_event(&self, emit: impl FnOnce(&Arc<dyn CursorEventHandler>)) {
+        if let Some(ref handler) = self.inner.options.cursor_event_handler {
+            emit(handler);
+        }
+    }
+
     /// Gets the default selection criteria the `Client` uses for operations..
     pub fn selection_criteria(&self) -> Option<&SelectionCriteria> {
         self.inner.options.selection_criteria.as_ref()
@@ -166,6 +264,18 @@ impl Client {
         self.inner.options.write_concern.as_ref()
     }
 
+    pub(crate) fn default_insert_ordered(&self) -> Option<bool> {
+        self.inner.options.default_insert_ordered
+    }
+
+    pub(crate) fn options(&self) -> &ClientOptions {
+        &self.inner.options
+    }
+
+    pub(crate) fn default_w_timeout(&self) -> Option<Duration> {
+        self.inner.options.default_w_timeout
+    }
+
     /// Gets a handle to a database specified by `name` in the cluster the `Client` is connected to.
     /// The `Database` options (e.g. read preference and write concern) will default to those of the
     /// `Client`.
@@ -189,7 +299,18 @@ impl Client {
     /// Gets a handle to the default database specified in the `ClientOptions` or MongoDB connection
     /// string used to construct this `Client`.
     ///
-    /// If no default database was specified, `None` will be returned.
+    /// If no default database was specified, `None` will be returned. In particular, the
+    /// `authSource` connection string option does not set a default database.
+    ///
+    /// ```rust
+    /// # use mongodb::{error::Result, Client};
+    /// # async fn do_stuff() -> Result<()> {
+    /// let client = Client::with_uri_str("mongodb://localhost/mydb").await?;
+    /// let db = client.default_database().expect("the URI specifies a database");
+    /// assert_eq!(db.name(), "mydb");
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn default_database(&self) -> Option<Database> {
         self.inner
             .options
@@ -198,6 +319,46 @@ impl Client {
             .map(|db_name| self.database(db_name))
     }
 
+    /// Requests that the driver immediately check the state of every server in the deployment
+    /// rather than waiting for the next heartbeat, e.g. after the application sees a network
+    /// error. This lets the driver notice failovers and recovered servers sooner.
+    ///
+    /// Checks are still rate limited: each server is checked at most once per 500ms, and requests
+    /// made while a check is pending are coalesced into it, so calling this repeatedly is cheap.
+    pub fn request_immediate_check(&self) {
+        self.inner.topology.request_update();
+    }
+
+    /// Sets a callback that is invoked with the selection criteria and the address of the chosen
+    /// server each time the client selects a server, e.g. to log or check which servers
+    /// operations are routed to. The criteria reported are those the server was actually selected
+    /// with: operations without explicit criteria are reported with a primary read preference, and
+    /// operations whose criteria were overridden (e.g. aggregations writing with `$out` that are
+    /// routed to the primary) are reported with the criteria used instead. The callback is invoked
+    /// once per attempt, so a retried operation reports both the original and the retry
+    /// selection. It is called on the task running the operation and should return quickly.
+    ///
+    /// The callback applies to this `Client` and every handle cloned from it, and replaces any
+    /// previously set callback.
+    pub fn set_server_selection_hook(
+        &self,
+        hook: impl Fn(&SelectionCriteria, &ServerAddress) + Send + Sync + 'static,
+    ) {
+        *self.inner.server_selection_hook.write().unwrap() = Some(Arc::new(hook));
+    }
+
+    /// Sets a callback that is invoked once each operation run by the client completes, reporting
+    /// how many times the operation was retried (0 or 1) and whether it ultimately succeeded,
+    /// e.g. to emit retry-rate metrics. Operations that fail with an error that can't be retried
+    /// are reported with 0 retries, and their error is returned as usual. It is called on the
+    /// task running the operation and should return quickly.
+    ///
+    /// The callback applies to this `Client` and every handle cloned from it, and replaces any
+    /// previously set callback.
+    pub fn set_retry_hook(&self, hook: impl Fn(&OperationRetries) + Send + Sync + 'static) {
+        *self.inner.retry_hook.write().unwrap() = Some(Arc::new(hook));
+    }
+
     async fn list_databases_common(
         &self,
         filter: impl Into<Option<Document>>,
@@ -260,6 +421,416 @@ impl Client {
         }
     }
 
+    /// Runs a collectionless aggregation against the `admin` database, such as one beginning with
+    /// a `$currentOp` or `$listLocalSessions` stage, and returns a cursor over its results.
+    ///
+    /// Stages like these report on the state of the node they run on, so aggregations beginning
+    /// with them are not retried, since a retry could be executed by a different node.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
+    /// information on aggregations.
+    pub async fn aggregate_admin(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Cursor<Document>> {
+        self.database("admin").aggregate(pipeline, options).await
+    }
+
+    /// Gets the most recent entries of the in-memory log named `name` of the server the command is
+    /// sent to, via the `getLog` command (e.g. `"global"` or `"startupWarnings"`). This requires
+    /// the `getLog` privilege on the cluster.
+    ///
+    /// An error is returned if the server has no log named `name`. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/reference/command/getLog/) for more information.
+    pub async fn get_log(&self, name: impl AsRef<str>) -> Result<Vec<String>> {
+        let response = self
+            .database("admin")
+            .run_command(doc! { "getLog": name.as_ref() }, None)
+            .await?;
+        let invalid_response = || ErrorKind::InvalidResponse {
+            message: "Expected \"log\" array of strings in getLog response".to_string(),
+        };
+        response
+            .get_array("log")
+            .map_err(|_| invalid_response())?
+            .iter()
+            .map(|line| match line {
+                Bson::String(line) => Ok(line.clone()),
+                _ => Err(invalid_response().into()),
+            })
+            .collect()
+    }
+
+    /// Opens a tailable cursor over the oplog (`local.oplog.rs`) of a replica set member,
+    /// starting at the entry with timestamp `start_at` (or the first entry after it, if there is
+    /// no such entry). The cursor waits for new entries to be written rather than being exhausted
+    /// when it reaches the end of the oplog.
+    ///
+    /// An error is returned if the oplog has already rolled over past `start_at`, i.e. its oldest
+    /// entry is newer than `start_at`, as entries after `start_at` may have been lost. If the
+    /// oplog rolls over past the cursor's position while it is being iterated, the cursor returns
+    /// an error.
+    ///
+    /// This requires a connection to a replica set; the oplog can't be read via mongos.
+    /// [Change streams](https://www.mongodb.com/docs/manual/changeStreams/) should be preferred
+    /// over reading the oplog where possible, as the oplog's format is internal to the server.
+    pub async fn oplog_tail(&self, start_at: Timestamp) -> Result<Cursor<OplogEntry>> {
+        let oplog = self.database("local").collection::<OplogEntry>("oplog.rs");
+
+        let options = FindOneOptions::builder()
+            .sort(doc! { "$natural": 1 })
+            .build();
+        match oplog.find_one(None, options).await? {
+            Some(oldest) if oldest.ts > start_at => {
+                return Err(ErrorKind::InvalidArgument {
+                    message: format!(
+                        "the oplog has rolled over past {:?}: its oldest entry is at {:?}",
+                        start_at, oldest.ts
+                    ),
+                }
+                .into());
+            }
+            Some(_) => {}
+            None => {
+                return Err(ErrorKind::InvalidArgument {
+                    message: "oplog_tail requires a connection to a replica set member".to_string(),
+                }
+                .into());
+            }
+        }
+
+        let options = FindOptions::builder()
+            .cursor_type(CursorType::TailableAwait)
+            .build();
+        oplog
+            .find(doc! { "ts": { "$gte": start_at } }, options)
+            .await
+    }
+
+    /// Runs the `hello` command against the server at `address`, returning that server's
+    /// description of itself and of the topology. The legacy `isMaster` command is used instead if
+    /// the server doesn't support `hello`. The compressors configured via
+    /// [`ClientOptions::compressors`] are advertised in the command, as they are in the
+    /// handshake, so that the response reports which of them the server supports.
+    ///
+    /// `address` must be one of the servers in the client's topology; otherwise, this returns an
+    /// error once the server selection timeout elapses.
+    pub async fn hello(&self, address: ServerAddress) -> Result<HelloResult> {
+        let command = |name: &str| {
+            let mut command = doc! { name: 1 };
+            if let Some(ref credential) = self.inner.options.credential {
+                credential.append_needed_mechanism_negotiation(&mut command);
+            }
+            if let Some(ref compressors) = self.inner.options.compressors {
+                command.insert(
+                    "compression",
+                    compressors
+                        .iter()
+                        .map(|compressor| compressor.name())
+                        .collect::<Vec<&'static str>>(),
+                );
+            }
+            command
+        };
+        let criteria = SelectionCriteria::from_address(address);
+
+        let admin = self.database("admin");
+        let response = match admin.run_command(command("hello"), criteria.clone()).await {
+            // CommandNotFound
+            Err(error) if error.code() == Some(59) => {
+                admin
+                    .run_command(command(LEGACY_HELLO_COMMAND_NAME), criteria)
+                    .await?
+            }
+            result => result?,
+        };
+        Ok(bson::from_document(response)?)
+    }
+
+    /// Verifies that the deployment supports the stable API version configured via
+    /// [`ClientOptions::server_api`], so that an application can fail at startup rather than on
+    /// its first operation if it requests a version the server doesn't support. This is most
+    /// useful with [`ServerApi::strict`](crate::options::ServerApi::strict) set, since the server
+    /// then rejects any command outside of the requested version. Does nothing if no server API
+    /// is configured.
+    ///
+    /// An [`ErrorKind::IncompatibleServer`] error is returned if any server in the deployment is
+    /// too old to support the stable API at all (it requires MongoDB 5.0+) or if the server rejects
+    /// the requested version.
+    pub async fn verify_server_api(&self) -> Result<()> {
+        let server_api = match self.inner.options.server_api {
+            Some(ref server_api) => server_api,
+            None => return Ok(()),
+        };
+        let incompatible = |reason: String| ErrorKind::IncompatibleServer {
+            message: format!(
+                "stable API version {} was requested, but {}",
+                server_api.version, reason
+            ),
+            driver_range: None,
+            server_range: None,
+        };
+
+        // Selecting a server waits for the deployment to be discovered, after which every
+        // available server it contains must support the stable API.
+        let address = self.select_server(None, |_, _| None).await?.address.clone();
+        let mut unsupported: Vec<ServerAddress> = self
+            .inner
+            .topology
+            .watch()
+            .peek_latest()
+            .description
+            .servers
+            .values()
+            .filter(|server| {
+                server.is_available()
+                    && server.max_wire_version().ok().flatten().unwrap_or(0)
+                        < SERVER_5_0_0_WIRE_VERSION
+            })
+            .map(|server| server.address.clone())
+            .collect();
+        unsupported.sort_by_key(|address| address.to_string());
+        if let Some(address) = unsupported.first() {
+            return Err(incompatible(format!(
+                "the server at {} does not support the stable API, which requires MongoDB 5.0+",
+                address
+            ))
+            .into());
+        }
+
+        let criteria = SelectionCriteria::from_address(address);
+        match self
+            .database("admin")
+            .run_command(doc! { "ping": 1 }, criteria)
+            .await
+        {
+            // APIVersionError
+            Err(error) if error.code() == Some(322) => {
+                Err(incompatible(format!("the server rejected it: {}", error)).into())
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Gets the feature compatibility version of the deployment via the `getParameter` command.
+    ///
+    /// mongos does not report a feature compatibility version, so for a sharded cluster, this must
+    /// be called using a client connected directly to a shard or config server.
+    pub async fn get_fcv(&self) -> Result<FeatureCompatibilityVersion> {
+        let response = self
+            .database("admin")
+            .run_command(
+                doc! { "getParameter": 1, "featureCompatibilityVersion": 1 },
+                None,
+            )
+            .await?;
+        let version = response
+            .get_document("featureCompatibilityVersion")
+            .and_then(|fcv| fcv.get_str("version"))
+            .map_err(|_| ErrorKind::InvalidResponse {
+                message: "Expected \"featureCompatibilityVersion.version\" string in getParameter \
+                          response"
+                    .to_string(),
+            })?;
+        version.parse().map_err(|_| {
+            ErrorKind::InvalidResponse {
+                message: format!(
+                    "invalid feature compatibility version in getParameter response: {:?}",
+                    version
+                ),
+            }
+            .into()
+        })
+    }
+
+    /// Sets the feature compatibility version of the deployment via the
+    /// `setFeatureCompatibilityVersion` command, enabling or disabling the persisted features of
+    /// the corresponding server version. On MongoDB 7.0+, the command is sent with
+    /// `confirm: true`, as the server requires.
+    ///
+    /// The server returns an error if `version` is not supported by its binary version. See the
+    /// documentation [here](https://www.mongodb.com/docs/manual/reference/command/setFeatureCompatibilityVersion/)
+    /// for more information.
+    pub async fn set_fcv(&self, version: FeatureCompatibilityVersion) -> Result<()> {
+        let op = SetFeatureCompatibilityVersion::new(version);
+        self.execute_operation(op, None).await
+    }
+
+    /// Waits until a server that writes can be sent to has been discovered (e.g. the primary of a
+    /// replica set or any mongos of a sharded cluster) and returns its address. This can be used
+    /// by applications that must not issue writes until a primary is available.
+    ///
+    /// A server selection error is returned if no such server is discovered within `timeout`.
+    pub async fn wait_for_primary(&self, timeout: Duration) -> Result<ServerAddress> {
+        let (server, _) = self
+            .select_server_with_timeout(None, |_, _| None, timeout)
+            .await?;
+        Ok(server.address.clone())
+    }
+
+    /// Attempts to reach each server currently known to the client, and reports either the round
+    /// trip time of a `hello` command or the error that prevented it for each server, ordered by
+    /// address. This is intended for diagnostics, e.g. to check which members of a deployment
+    /// (including arbiters and hidden members) are reachable from the application's host.
+    ///
+    /// Each server is checked concurrently on a new connection dedicated to the check, so the
+    /// reported times are unaffected by the state of the client's connection pools. A server that
+    /// can't be reached is reported with the error from connecting to it, which may take up to
+    /// [`ClientOptions::connect_timeout`].
+    pub async fn connectivity_report(&self) -> Vec<(ServerAddress, Result<Duration>)> {
+        let mut addresses: Vec<ServerAddress> = self
+            .inner
+            .topology
+            .watch()
+            .peek_latest()
+            .servers
+            .keys()
+            .cloned()
+            .collect();
+        addresses.sort_by_key(|address| address.to_string());
+
+        let options = &self.inner.options;
+        let checks = addresses.into_iter().map(|address| async move {
+            let result = Self::check_connectivity(options, address.clone()).await;
+            (address, result)
+        });
+        futures_util::future::join_all(checks).await
+    }
+
+    /// Connects to the server at `address` and returns the round trip time of a `hello` sent once
+    /// the connection has been handshaken.
+    async fn check_connectivity(
+        options: &ClientOptions,
+        address: ServerAddress,
+    ) -> Result<Duration> {
+        let mut connection =
+            Connection::connect_monitoring(address, options.connect_timeout, options.tls_options())
+                .await?;
+        Handshaker::new(Some(options.clone().into()))
+            .handshake(&mut connection, None, &None)
+            .await?;
+        let command = hello_command(
+            options.server_api.as_ref(),
+            options.load_balanced,
+            Some(connection.stream_description()?.hello_ok),
+        );
+        let reply = run_hello(&mut connection, command, None, &None).await?;
+        Ok(reply.round_trip_time)
+    }
+
+    /// Returns a snapshot of the state of the connection pool for each server currently known to
+    /// the client. This can be polled to detect when operations are waiting on connection
+    /// establishment, e.g. to decide when to scale out; see
+    /// [`PoolSaturatedEvent`](crate::event::cmap::PoolSaturatedEvent) for more details.
+    pub fn pool_stats(&self) -> HashMap<ServerAddress, PoolStats> {
+        self.inner
+            .topology
+            .watch()
+            .peek_latest()
+            .servers
+            .iter()
+            .map(|(address, server)| (address.clone(), server.pool.stats()))
+            .collect()
+    }
+
+    /// Returns the number of bytes this client has sent to and received from the servers it is
+    /// connected to, both as sent over the network and as they would have been without
+    /// compression. This can be used to evaluate the effectiveness of the compressors specified
+    /// via [`ClientOptions::compressors`](options/struct.ClientOptions.html#structfield.
+    /// compressors). Traffic from the client's server monitors is not included.
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.inner
+            .options
+            .compression_stats
+            .as_ref()
+            .map(|stats| stats.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of commands this client has sent to each server, grouped by the kind of
+    /// operation. This can be used to check how operations are being distributed across the
+    /// deployment, e.g. when reading with a [`ReadPreference::Nearest`] preference. Commands sent
+    /// by the client's server monitors are not included.
+    ///
+    /// The counts for a server are kept even after it is removed from the topology, until
+    /// [`Client::reset_operation_counts`] is called.
+    pub fn operation_counts(&self) -> HashMap<ServerAddress, OpCounts> {
+        self.inner
+            .operation_counts
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(address, counts)| (address.clone(), counts.snapshot()))
+            .collect()
+    }
+
+    /// Resets the counts returned by [`Client::operation_counts`], discarding those for servers
+    /// that are no longer part of the topology.
+    pub fn reset_operation_counts(&self) {
+        self.inner.operation_counts.write().unwrap().clear();
+    }
+
+    pub(crate) fn record_command_sent(&self, address: &ServerAddress, command_name: &str) {
+        let counts = self
+            .inner
+            .operation_counts
+            .read()
+            .unwrap()
+            .get(address)
+            .cloned();
+        let counts = match counts {
+            Some(counts) => counts,
+            None => self
+                .inner
+                .operation_counts
+                .write()
+                .unwrap()
+                .entry(address.clone())
+                .or_default()
+                .clone(),
+        };
+        counts.record(command_name);
+    }
+
+    /// Gets a client connected directly to each of the shards with the given host strings from
+    /// the `listShards` command, which are either "<replica set name>/<host>,<host>,..." or a
+    /// single host. The clients have the same options as this one and are reused by later calls
+    /// with the same host strings; clients for host strings that aren't given are dropped, so
+    /// only the current shards' clients are kept.
+    pub(crate) fn shard_clients<'a>(
+        &self,
+        shard_hosts: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<Client>> {
+        let mut cached = self.inner.shard_clients.lock().unwrap();
+        let mut current = HashMap::new();
+        let mut clients = Vec::new();
+        for shard_host in shard_hosts {
+            let client = match cached.get(shard_host).cloned() {
+                Some(client) => client,
+                None => {
+                    let (repl_set_name, hosts) = match shard_host.split_once('/') {
+                        Some((repl_set_name, hosts)) => (Some(repl_set_name.to_string()), hosts),
+                        None => (None, shard_host),
+                    };
+                    let mut options = self.inner.options.clone();
+                    options.hosts = hosts
+                        .split(',')
+                        .map(ServerAddress::parse)
+                        .collect::<Result<_>>()?;
+                    options.repl_set_name = repl_set_name;
+                    options.direct_connection = None;
+                    options.original_srv_info = None;
+                    Client::with_options(options)?
+                }
+            };
+            current.insert(shard_host.to_string(), client.clone());
+            clients.push(client);
+        }
+        *cached = current;
+        Ok(clients)
+    }
+
     /// Starts a new `ClientSession`.
     pub async fn start_session(
         &self,
@@ -279,6 +850,97 @@ impl Client {
         }
     }
 
+    /// Returns the number of server sessions currently in use by this client, i.e. the number of
+    /// explicit sessions that haven't been dropped plus the number of implicit sessions in use by
+    /// in-progress operations and open cursors.
+    ///
+    /// Server sessions are returned to a pool rather than ended when they're no longer in use, and
+    /// reused by later sessions until they've been idle for close to the deployment's
+    /// `logicalSessionTimeoutMinutes`. Sessions are returned to the pool in the background, so a
+    /// session may be counted briefly after it's dropped.
+    pub fn active_session_count(&self) -> usize {
+        self.inner.session_pool.checked_out_count()
+    }
+
+    /// Refreshes the sessions with the given ids (as returned by
+    /// [`ClientSession::id`](crate::ClientSession::id)) via the `refreshSessions` command,
+    /// resetting the time after which the server times them out due to inactivity (the
+    /// deployment's `logicalSessionTimeoutMinutes`). This can be used to keep sessions alive that
+    /// are referred to outside of this client, e.g. by id in another process.
+    ///
+    /// The idle sessions in this client's session pool are refreshed automatically once they've
+    /// been unused for half of the timeout, so that they can be reused. Sessions that are in use
+    /// by a [`ClientSession`] are not; those that may be idle for long periods can be kept alive
+    /// with [`ClientSession::refresh`].
+    ///
+    /// Refreshing a session that has already timed out on the server starts a new session with
+    /// the same id, so the state of the old session (e.g. an in-progress transaction) is lost.
+    pub async fn refresh_sessions(&self, ids: impl IntoIterator<Item = Document>) -> Result<()> {
+        let ids: Vec<Document> = ids.into_iter().collect();
+        self.database("admin")
+            .run_command(doc! { "refreshSessions": ids.clone() }, None)
+            .await?;
+        self.inner.session_pool.mark_used(&ids).await;
+        Ok(())
+    }
+
+    /// Refreshes the idle sessions in the session pool that haven't been used or refreshed for
+    /// half of `logical_session_timeout`.
+    pub(crate) async fn refresh_pooled_sessions(&self, logical_session_timeout: Duration) {
+        let ids = self
+            .inner
+            .session_pool
+            .sessions_to_refresh(logical_session_timeout)
+            .await;
+        if !ids.is_empty() {
+            // A failed refresh is retried on the next pass.
+            let _: Result<()> = self.refresh_sessions(ids).await;
+        }
+    }
+
+    /// Kills the sessions of the given users via the `killAllSessions` command, aborting their
+    /// transactions and killing their in-progress operations and open cursors. If `users` is
+    /// empty, the sessions of all users are killed. This can be used to clean up sessions that are
+    /// holding on to server resources, e.g. stuck transactions.
+    ///
+    /// The idle server sessions in this client's session pool are discarded rather than reused.
+    /// Sessions of this client that are in use when they're killed can still be used, but any
+    /// transaction in progress on them is aborted.
+    pub async fn kill_all_sessions(
+        &self,
+        users: impl IntoIterator<Item = SessionUser>,
+    ) -> Result<()> {
+        let users: Vec<Document> = users
+            .into_iter()
+            .map(|user| doc! { "user": user.user, "db": user.db })
+            .collect();
+        // The pool is cleared first so that the command isn't run with one of the sessions being
+        // killed.
+        self.inner.session_pool.clear().await;
+        self.database("admin")
+            .run_command(doc! { "killAllSessions": users }, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Kills the sessions with the given ids (as returned by
+    /// [`ClientSession::id`](crate::ClientSession::id)) via the `killSessions` command, aborting
+    /// their transactions and killing their in-progress operations and open cursors.
+    ///
+    /// If any of the ids belong to idle server sessions in this client's session pool, those are
+    /// discarded rather than reused. Sessions of this client that are in use when they're killed
+    /// can still be used, but any transaction in progress on them is aborted.
+    pub async fn kill_sessions(&self, ids: impl IntoIterator<Item = Document>) -> Result<()> {
+        let ids: Vec<Document> = ids.into_iter().collect();
+        // The sessions are removed from the pool first so that the command isn't run with one of
+        // them.
+        self.inner.session_pool.remove(&ids).await;
+        self.database("admin")
+            .run_command(doc! { "killSessions": ids }, None)
+            .await?;
+        Ok(())
+    }
+
     /// Starts a new [`ChangeStream`] that receives events for all changes in the cluster. The
     /// stream does not observe changes from system collections or the "config", "local" or
     /// "admin" databases. Note that this method (`watch` on a cluster) is only supported in
@@ -343,6 +1005,8 @@ impl Client {
                 .session_pool
                 .check_in(session, logical_session_timeout)
                 .await;
+        } else {
+            self.inner.session_pool.discard(session);
         }
     }
 
@@ -385,53 +1049,164 @@ impl Client {
         &self,
         criteria: Option<&SelectionCriteria>,
     ) -> Result<ServerAddress> {
-        let server = self.select_server(criteria).await?;
+        let server = self.select_server(criteria, |_, _| None).await?;
         Ok(server.address.clone())
     }
 
+    /// Invokes the hook set via `set_server_selection_hook`, if any, for a server selected for an
+    /// operation with the given criteria.
+    fn notify_server_selected(&self, criteria: &SelectionCriteria, address: &ServerAddress) {
+        let hook = self.inner.server_selection_hook.read().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(criteria, address);
+        }
+    }
+
     /// Select a server using the provided criteria. If none is provided, a primary read preference
-    /// will be used instead.
-    async fn select_server(&self, criteria: Option<&SelectionCriteria>) -> Result<SelectedServer> {
-        let criteria =
-            criteria.unwrap_or(&SelectionCriteria::ReadPreference(ReadPreference::Primary));
+    /// will be used instead. The criteria are replaced by those returned by `override_criteria`
+    /// for the topology at the time of selection, if any.
+    async fn select_server(
+        &self,
+        criteria: Option<&SelectionCriteria>,
+        override_criteria: OverrideCriteriaFn,
+    ) -> Result<SelectedServer> {
+        let (server, _) = self
+            .select_server_and_criteria(criteria, override_criteria)
+            .await?;
+        Ok(server)
+    }
 
-        let start_time = Instant::now();
+    /// Select a server like `select_server`, also returning the criteria it was selected with
+    /// after any override was applied.
+    async fn select_server_and_criteria(
+        &self,
+        criteria: Option<&SelectionCriteria>,
+        override_criteria: OverrideCriteriaFn,
+    ) -> Result<(SelectedServer, SelectionCriteria)> {
         let timeout = self
             .inner
             .options
             .server_selection_timeout
             .unwrap_or(DEFAULT_SERVER_SELECTION_TIMEOUT);
+        self.select_server_with_timeout(criteria, override_criteria, timeout)
+            .await
+    }
 
+    /// Select a server to retry an operation on after it failed with `first_error` on the server at
+    /// `failed_address`.
+    ///
+    /// If the failure was a state change error (e.g. `NotWritablePrimary` after a stepdown), the
+    /// topology may not yet reflect the change, in which case the old address could be selected
+    /// again. When that happens, a topology check is requested and the selection is re-run once
+    /// the topology has been updated, still bounded by the server selection timeout. If an election
+    /// is in progress, this waits for the new primary to be discovered like any other selection.
+    ///
+    /// Note that the failed server's pool is only cleared for pre-4.2 servers or shutdown errors;
+    /// see `Topology::handle_application_error`.
+    ///
+    /// The criteria the server was selected with after any override was applied are returned
+    /// along with it.
+    async fn select_server_for_retry(
+        &self,
+        criteria: Option<&SelectionCriteria>,
+        override_criteria: OverrideCriteriaFn,
+        failed_address: &ServerAddress,
+        first_error: &Error,
+    ) -> Result<(SelectedServer, SelectionCriteria)> {
+        let timeout = self
+            .inner
+            .options
+            .server_selection_timeout
+            .unwrap_or(DEFAULT_SERVER_SELECTION_TIMEOUT);
+        let start_time = Instant::now();
+        let mut watcher = self.inner.topology.watch();
+
+        let (server, effective_criteria) = self
+            .select_server_with_timeout(criteria, override_criteria, timeout)
+            .await?;
+        if !first_error.is_state_change_error() || &server.address != failed_address {
+            return Ok((server, effective_criteria));
+        }
+        drop(server);
+
+        self.inner.topology.request_update();
+        let remaining = timeout.saturating_sub(start_time.elapsed());
+        watcher
+            .wait_for_update(std::cmp::min(MIN_HEARTBEAT_FREQUENCY * 2, remaining))
+            .await;
+        self.select_server_with_timeout(
+            criteria,
+            override_criteria,
+            timeout.saturating_sub(start_time.elapsed()),
+        )
+        .await
+    }
+
+    async fn select_server_with_timeout(
+        &self,
+        criteria: Option<&SelectionCriteria>,
+        override_criteria: OverrideCriteriaFn,
+        timeout: Duration,
+    ) -> Result<(SelectedServer, SelectionCriteria)> {
+        let criteria =
+            criteria.unwrap_or(&SelectionCriteria::ReadPreference(ReadPreference::Primary));
+
+        let start_time = Instant::now();
         let mut watcher = self.inner.topology.watch();
         loop {
             let state = watcher.observe_latest();
 
+            let override_criteria = override_criteria(criteria, &state.description);
+            let effective_criteria = override_criteria.as_ref().unwrap_or(criteria);
             if let Some(server) = server_selection::attempt_to_select_server(
-                criteria,
+                effective_criteria,
                 &state.description,
                 &state.servers,
             )? {
-                return Ok(server);
+                return Ok((server, effective_criteria.clone()));
             }
 
             self.inner.topology.request_update();
 
-            let change_occurred = start_time.elapsed() < timeout
-                && watcher
-                    .wait_for_update(timeout - start_time.elapsed())
-                    .await;
+            let discovery_deadline = self.initial_discovery_deadline(&state);
+            let deadline = match discovery_deadline {
+                Some(discovery_deadline) => discovery_deadline.min(start_time + timeout),
+                None => start_time + timeout,
+            };
+            let now = Instant::now();
+            let change_occurred = now < deadline && watcher.wait_for_update(deadline - now).await;
             if !change_occurred {
-                return Err(ErrorKind::ServerSelection {
-                    message: self
-                        .inner
-                        .topology
-                        .server_selection_timeout_error_message(criteria),
+                let mut message = self
+                    .inner
+                    .topology
+                    .server_selection_timeout_error_message(criteria);
+                if discovery_deadline.map_or(false, |d| Instant::now() >= d) {
+                    message = format!(
+                        "Initial topology discovery did not complete within the initial discovery \
+                         timeout of {:?}. {}",
+                        self.inner
+                            .options
+                            .initial_discovery_timeout
+                            .unwrap_or_default(),
+                        message
+                    );
                 }
-                .into());
+                return Err(ErrorKind::ServerSelection { message }.into());
             }
         }
     }
 
+    /// The time by which the initial discovery of the topology must complete per
+    /// `ClientOptions::initial_discovery_timeout`, or `None` if no such timeout was set or a server
+    /// has already been discovered.
+    fn initial_discovery_deadline(&self, state: &TopologyState) -> Option<Instant> {
+        let timeout = self.inner.options.initial_discovery_timeout?;
+        if state.initial_discovery_complete {
+            return None;
+        }
+        Some(self.inner.created_at + timeout)
+    }
+
     #[cfg(all(test, not(feature = "sync"), not(feature = "tokio-sync")))]
     pub(crate) async fn get_hosts(&self) -> Vec<String> {
         let watcher = self.inner.topology.watch();
@@ -458,6 +1233,22 @@ impl Client {
             .clone()
     }
 
+    /// Returns a handle to this client that declares `server_api` on its commands in place of the
+    /// server API from the client options.
+    pub(crate) fn with_server_api(&self, server_api: ServerApi) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            server_api: Some(server_api),
+        }
+    }
+
+    /// Gets the server API declared on commands sent via this handle, if any.
+    pub(crate) fn server_api(&self) -> Option<&ServerApi> {
+        self.server_api
+            .as_ref()
+            .or(self.inner.options.server_api.as_ref())
+    }
+
     #[cfg(feature = "csfle")]
     pub(crate) fn weak(&self) -> WeakClient {
         WeakClient {
@@ -476,12 +1267,52 @@ impl Client {
         .ok()
     }
 
-    #[cfg(test)]
+    pub(crate) fn topology_type(&self) -> crate::sdam::TopologyType {
+        self.inner.topology.topology_type()
+    }
+
+    #[cfg(any(test, feature = "test-util"))]
     pub(crate) fn topology(&self) -> &Topology {
         &self.inner.topology
     }
 }
 
+/// The counters of an `OpCounts`.
+#[derive(Debug, Default)]
+struct AtomicOpCounts {
+    query: AtomicU64,
+    insert: AtomicU64,
+    update: AtomicU64,
+    delete: AtomicU64,
+    get_more: AtomicU64,
+    command: AtomicU64,
+}
+
+impl AtomicOpCounts {
+    fn record(&self, command_name: &str) {
+        let count = match command_name {
+            "find" => &self.query,
+            "insert" => &self.insert,
+            "update" => &self.update,
+            "delete" => &self.delete,
+            "getMore" => &self.get_more,
+            _ => &self.command,
+        };
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> OpCounts {
+        OpCounts {
+            query: self.query.load(Ordering::Relaxed),
+            insert: self.insert.load(Ordering::Relaxed),
+            update: self.update.load(Ordering::Relaxed),
+            delete: self.delete.load(Ordering::Relaxed),
+            get_more: self.get_more.load(Ordering::Relaxed),
+            command: self.command.load(Ordering::Relaxed),
+        }
+    }
+}
+
 #[cfg(feature = "csfle")]
 #[derive(Clone, Debug)]
 pub(crate) struct WeakClient {
@@ -492,6 +1323,9 @@ pub(crate) struct WeakClient {
 impl WeakClient {
     #[allow(dead_code)]
     pub(crate) fn upgrade(&self) -> Option<Client> {
-        self.inner.upgrade().map(|inner| Client { inner })
+        self.inner.upgrade().map(|inner| Client {
+            inner,
+            server_api: None,
+        })
     }
 }
@@ -2,6 +2,7 @@ mod cluster_time;
 mod pool;
 #[cfg(test)]
 mod test;
+mod transaction_builder;
 
 use std::{
     collections::HashSet,
@@ -15,9 +16,10 @@ use uuid::Uuid;
 use crate::{
     bson::{doc, spec::BinarySubtype, Binary, Bson, Document, Timestamp},
     cmap::conn::PinnedConnectionHandle,
+    coll::Namespace,
     error::{ErrorKind, Result},
-    operation::{AbortTransaction, CommitTransaction, Operation},
-    options::{SessionOptions, TransactionOptions},
+    operation::{AbortTransaction, CommitTransaction, Find, Operation},
+    options::{FindOptions, ServerApi, SessionOptions, TransactionOptions},
     runtime,
     sdam::{ServerInfo, TransactionSupportStatus},
     selection_criteria::SelectionCriteria,
@@ -25,6 +27,7 @@ use crate::{
 };
 pub use cluster_time::ClusterTime;
 pub(super) use pool::ServerSessionPool;
+pub use transaction_builder::TransactionBuilder;
 
 use super::options::ServerAddress;
 
@@ -110,6 +113,7 @@ pub struct ClientSession {
     pub(crate) transaction: Transaction,
     pub(crate) snapshot_time: Option<Timestamp>,
     pub(crate) operation_time: Option<Timestamp>,
+    read_primary_pending: bool,
 }
 
 #[derive(Debug)]
@@ -118,6 +122,9 @@ pub(crate) struct Transaction {
     pub(crate) options: Option<TransactionOptions>,
     pub(crate) pinned: Option<TransactionPin>,
     pub(crate) recovery_token: Option<Document>,
+    /// The server API declared by the database or collection that ran the first operation of the
+    /// transaction, which is declared on commitTransaction and abortTransaction as well.
+    pub(crate) server_api: Option<ServerApi>,
 }
 
 impl Transaction {
@@ -125,6 +132,7 @@ impl Transaction {
         self.state = TransactionState::Starting;
         self.options = options;
         self.recovery_token = None;
+        self.server_api = None;
     }
 
     pub(crate) fn commit(&mut self, data_committed: bool) {
@@ -142,6 +150,7 @@ impl Transaction {
         self.options = None;
         self.pinned = None;
         self.recovery_token = None;
+        self.server_api = None;
     }
 
     pub(crate) fn pinned_mongos(&self) -> Option<&SelectionCriteria> {
@@ -164,6 +173,7 @@ impl Transaction {
             options: self.options.take(),
             pinned: self.pinned.take(),
             recovery_token: self.recovery_token.take(),
+            server_api: self.server_api.take(),
         }
     }
 }
@@ -175,6 +185,7 @@ impl Default for Transaction {
             options: None,
             pinned: None,
             recovery_token: None,
+            server_api: None,
         }
     }
 }
@@ -216,6 +227,7 @@ impl ClientSession {
             transaction: Default::default(),
             snapshot_time: None,
             operation_time: None,
+            read_primary_pending: false,
         }
     }
 
@@ -246,6 +258,17 @@ impl ClientSession {
         self.cluster_time.as_ref()
     }
 
+    /// The recovery token returned by mongos for the current or most recent transaction on a
+    /// sharded cluster. It is sent with `commitTransaction` and `abortTransaction` so that
+    /// the outcome of the transaction can be determined by a different mongos, e.g. when a commit
+    /// is retried after the mongos the transaction was run on became unavailable.
+    ///
+    /// This will be `None` if no transaction has been started on a sharded cluster, or if the
+    /// transaction has not yet executed an operation.
+    pub fn recovery_token(&self) -> Option<&Document> {
+        self.transaction.recovery_token.as_ref()
+    }
+
     /// The options used to create this session.
     pub fn options(&self) -> Option<&SessionOptions> {
         self.options.as_ref()
@@ -274,6 +297,95 @@ impl ClientSession {
         self.operation_time
     }
 
+    /// Ensures that the effects of operations up to `operation_time` are visible to reads using
+    /// this session, e.g. after a write made using another session or client whose operation time
+    /// was passed to this one. This advances the session's operation time to `operation_time` and
+    /// then sends a probe read with that `afterClusterTime`, which the server selected with the
+    /// client's read preference only answers once it has applied all writes up to that time.
+    /// This allows a secondary that is lagging behind to be detected before reading from it.
+    ///
+    /// The probe is a `find` on a nonexistent collection in the client's default database, or in
+    /// the `admin` database if none is set. If the server does not catch up within `timeout`, an
+    /// error with code 50 (`MaxTimeMSExpired`) is returned; a `timeout` of zero fails almost
+    /// immediately if the server is behind. Either way, later causally consistent reads using
+    /// this session will still wait until the writes are visible.
+    ///
+    /// Returns an error if the session is not causally consistent or is in a transaction.
+    pub async fn ensure_visible(
+        &mut self,
+        operation_time: Timestamp,
+        timeout: Duration,
+    ) -> Result<()> {
+        let options = self.options.as_ref();
+        if options.and_then(|opts| opts.snapshot).unwrap_or(false)
+            || !options
+                .and_then(|opts| opts.causal_consistency)
+                .unwrap_or(true)
+        {
+            return Err(ErrorKind::InvalidArgument {
+                message: "ensure_visible requires a causally consistent session".to_string(),
+            }
+            .into());
+        }
+        if self.in_transaction() {
+            return Err(ErrorKind::InvalidArgument {
+                message: "ensure_visible cannot be called in a transaction".to_string(),
+            }
+            .into());
+        }
+
+        self.advance_operation_time(operation_time);
+
+        let db = self
+            .client
+            .default_database()
+            .map_or_else(|| "admin".to_string(), |db| db.name().to_string());
+        let ns = Namespace {
+            db,
+            coll: "__mongodb_rust_driver_ensure_visible".to_string(),
+        };
+        let options = FindOptions::builder()
+            .limit(-1i64)
+            // A maxTimeMS of zero means no limit, so the shortest possible one is used instead.
+            .max_time(timeout.max(Duration::from_millis(1)))
+            .selection_criteria(self.client.selection_criteria().cloned())
+            .build();
+        let client = self.client.clone();
+        client
+            .execute_operation(Find::new(ns, None, Some(options)), self)
+            .await?;
+        Ok(())
+    }
+
+    /// Refreshes this session on the server via the `refreshSessions` command so that it isn't
+    /// timed out due to inactivity. This can be used to keep a session that is idle for long
+    /// periods (e.g. between the operations of a long-running transaction) alive. See
+    /// [`Client::refresh_sessions`] for more details.
+    pub async fn refresh(&mut self) -> Result<()> {
+        self.client
+            .refresh_sessions(vec![self.id().clone()])
+            .await?;
+        self.update_last_use();
+        Ok(())
+    }
+
+    /// Whether the next read performed using this session should be routed to the primary per
+    /// [`SessionOptions::read_primary_after_write`].
+    pub(crate) fn read_primary_pending(&self) -> bool {
+        self.read_primary_pending
+    }
+
+    /// Records that an operation was executed using this session, so that only the first read
+    /// following a write is routed to the primary.
+    pub(crate) fn record_operation(&mut self, is_write: bool) {
+        let enabled = self
+            .options
+            .as_ref()
+            .and_then(|opts| opts.read_primary_after_write)
+            .unwrap_or(false);
+        self.read_primary_pending = enabled && is_write;
+    }
+
     /// Mark this session (and the underlying server session) as dirty.
     pub(crate) fn mark_dirty(&mut self) {
         self.server_session.dirty = true;
@@ -427,6 +539,12 @@ impl ClientSession {
         }
     }
 
+    /// Returns a [`TransactionBuilder`] that can be used to configure and run a transaction on
+    /// this session, retrying it as needed. See [`TransactionBuilder::and_run`] for more details.
+    pub fn transaction(&mut self) -> TransactionBuilder<'_> {
+        TransactionBuilder::new(self)
+    }
+
     /// Commits the transaction that is currently active on this session.
     ///
     ///
@@ -471,8 +589,7 @@ impl ClientSession {
             TransactionState::InProgress => {
                 let commit_transaction = CommitTransaction::new(self.transaction.options.clone());
                 self.transaction.commit(true);
-                self.client
-                    .clone()
+                self.transaction_client()
                     .execute_operation(commit_transaction, self)
                     .await
             }
@@ -482,8 +599,7 @@ impl ClientSession {
                 let mut commit_transaction =
                     CommitTransaction::new(self.transaction.options.clone());
                 commit_transaction.update_for_retry();
-                self.client
-                    .clone()
+                self.transaction_client()
                     .execute_operation(commit_transaction, self)
                     .await
             }
@@ -552,8 +668,7 @@ impl ClientSession {
                 self.transaction.abort();
                 // Errors returned from running an abortTransaction command should be ignored.
                 let _result = self
-                    .client
-                    .clone()
+                    .transaction_client()
                     .execute_operation(abort_transaction, &mut *self)
                     .await;
                 Ok(())
@@ -561,6 +676,15 @@ impl ClientSession {
         }
     }
 
+    /// The client used to run commitTransaction and abortTransaction, which declares the same
+    /// server API as the operations in the transaction.
+    fn transaction_client(&self) -> Client {
+        match self.transaction.server_api {
+            Some(ref server_api) => self.client.with_server_api(server_api.clone()),
+            None => self.client.clone(),
+        }
+    }
+
     fn default_transaction_options(&self) -> Option<&TransactionOptions> {
         self.options
             .as_ref()
@@ -1,20 +1,27 @@
-use std::{collections::VecDeque, time::Duration};
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
 
 use tokio::sync::Mutex;
 
 use super::ServerSession;
-#[cfg(test)]
 use crate::bson::Document;
 
 #[derive(Debug)]
 pub(crate) struct ServerSessionPool {
     pool: Mutex<VecDeque<ServerSession>>,
+
+    /// The number of sessions that are currently checked out of the pool.
+    checked_out: AtomicUsize,
 }
 
 impl ServerSessionPool {
     pub(crate) fn new() -> Self {
         Self {
             pool: Default::default(),
+            checked_out: AtomicUsize::new(0),
         }
     }
 
@@ -25,6 +32,7 @@ impl ServerSessionPool {
         &self,
         logical_session_timeout: Option<Duration>,
     ) -> ServerSession {
+        self.checked_out.fetch_add(1, Ordering::SeqCst);
         let mut pool = self.pool.lock().await;
         while let Some(session) = pool.pop_front() {
             // If a session is about to expire within the next minute, remove it from pool.
@@ -39,15 +47,19 @@ impl ServerSessionPool {
     /// Checks in a server session to the pool. If it is about to expire or is dirty, it will be
     /// discarded.
     ///
-    /// This method will also clear out any expired session from the pool before checking in.
+    /// This method will also clear out any expired session from the pool before checking in. The
+    /// least recently used sessions are at the back of the pool, so pruning stops at the first
+    /// session that isn't about to expire. Sessions that are checked out are never pruned, however
+    /// close to expiring they are; their last use is checked again when they're checked back in.
     pub(crate) async fn check_in(
         &self,
         session: ServerSession,
         logical_session_timeout: Option<Duration>,
     ) {
+        self.checked_out.fetch_sub(1, Ordering::SeqCst);
         let mut pool = self.pool.lock().await;
         while let Some(pooled_session) = pool.pop_back() {
-            if session.is_about_to_expire(logical_session_timeout) {
+            if pooled_session.is_about_to_expire(logical_session_timeout) {
                 continue;
             }
             pool.push_back(pooled_session);
@@ -59,7 +71,51 @@ impl ServerSessionPool {
         }
     }
 
-    #[cfg(test)]
+    /// Discards a checked out server session rather than checking it back in to the pool.
+    pub(crate) fn discard(&self, _session: ServerSession) {
+        self.checked_out.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// The number of server sessions that are currently checked out of the pool.
+    pub(crate) fn checked_out_count(&self) -> usize {
+        self.checked_out.load(Ordering::SeqCst)
+    }
+
+    /// Resets the last use time of the pooled sessions with the given ids, e.g. after they were
+    /// refreshed on the server.
+    pub(crate) async fn mark_used(&self, ids: &[Document]) {
+        for session in self.pool.lock().await.iter_mut() {
+            if ids.contains(&session.id) {
+                session.last_use = Instant::now();
+            }
+        }
+    }
+
+    /// Gets the ids of the idle pooled sessions that haven't been used or refreshed for at least
+    /// half of the logical session timeout, and so should be refreshed to keep them from timing
+    /// out on the server. Checked out sessions are left to their owners.
+    pub(crate) async fn sessions_to_refresh(
+        &self,
+        logical_session_timeout: Duration,
+    ) -> Vec<Document> {
+        self.pool
+            .lock()
+            .await
+            .iter()
+            .filter(|session| session.last_use.elapsed() >= logical_session_timeout / 2)
+            .map(|session| session.id.clone())
+            .collect()
+    }
+
+    /// Removes the sessions with the given ids from the pool.
+    pub(crate) async fn remove(&self, ids: &[Document]) {
+        self.pool
+            .lock()
+            .await
+            .retain(|session| !ids.contains(&session.id));
+    }
+
+    /// Removes all sessions from the pool.
     pub(crate) async fn clear(&self) {
         self.pool.lock().await.clear();
     }
@@ -1,13 +1,28 @@
+use std::time::{Duration, Instant};
+
 use bson::{doc, Document};
 use futures::{future::BoxFuture, FutureExt};
-use tokio::sync::RwLockReadGuard;
+use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
     client::options::SessionOptions,
     coll::options::CollectionOptions,
     error::Result,
     options::ReadConcern,
-    test::{log_uncaptured, CommandEvent, EventClient, LOCK},
+    runtime,
+    sdam::ServerType,
+    selection_criteria::SelectionCriteria,
+    test::{
+        log_uncaptured,
+        CommandEvent,
+        EventClient,
+        FailPoint,
+        TestClient,
+        CLIENT_OPTIONS,
+        LOCK,
+        SERVERLESS,
+    },
+    Client,
     ClientSession,
     Collection,
 };
@@ -543,3 +558,87 @@ async fn cluster_time_sent_in_commands() {
     let (started, _) = client.get_successful_command_execution("find");
     started.command.get_document("$clusterTime").unwrap();
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn ensure_visible_waits_for_lagging_secondary() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let setup_client = TestClient::new().await;
+    if !setup_client.is_replica_set() || setup_client.server_version_lt(4, 0) || *SERVERLESS {
+        log_uncaptured(
+            "skipping ensure_visible_waits_for_lagging_secondary due to test configuration",
+        );
+        return;
+    }
+    let secondary = match setup_client
+        .topology_description()
+        .servers
+        .into_values()
+        .find(|server| server.server_type == ServerType::RsSecondary)
+    {
+        Some(server) => server.address,
+        None => {
+            log_uncaptured(
+                "skipping ensure_visible_waits_for_lagging_secondary due to test configuration",
+            );
+            return;
+        }
+    };
+    let coll = setup_client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // Stop replication to the secondary so that it falls behind the write below.
+    let fail_point: FailPoint = bson::from_document(doc! {
+        "configureFailPoint": "stopReplProducer",
+        "mode": "alwaysOn",
+    })
+    .unwrap();
+    let fp_guard = fail_point
+        .enable(
+            &setup_client,
+            SelectionCriteria::from_address(secondary.clone()),
+        )
+        .await
+        .unwrap();
+
+    let mut writer = setup_client.start_session(None).await.unwrap();
+    coll.insert_one_with_session(doc! { "_id": 1 }, None, &mut writer)
+        .await
+        .unwrap();
+    let operation_time = writer.operation_time().unwrap();
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.selection_criteria = Some(SelectionCriteria::from_address(secondary));
+    options.default_database = Some(function_name!().to_string());
+    let client = Client::with_options(options).unwrap();
+    let mut session = client.start_session(None).await.unwrap();
+
+    // The secondary can't catch up while replication is stopped.
+    let error = session
+        .ensure_visible(operation_time, Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some(50), "{:?}", error);
+
+    let start = Instant::now();
+    runtime::spawn(async move {
+        runtime::delay_for(Duration::from_secs(1)).await;
+        drop(fp_guard);
+    });
+    session
+        .ensure_visible(operation_time, Duration::from_secs(30))
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    let doc = client
+        .database(function_name!())
+        .collection::<Document>(function_name!())
+        .find_one_with_session(doc! { "_id": 1 }, None, &mut session)
+        .await
+        .unwrap();
+    assert_eq!(doc, Some(doc! { "_id": 1 }));
+}
@@ -1,16 +1,29 @@
 mod causal_consistency;
 
-use std::{future::Future, sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use bson::Document;
 use futures::stream::StreamExt;
 use tokio::sync::RwLockReadGuard;
 
 use crate::{
-    bson::{doc, Bson},
-    coll::options::{CountOptions, InsertManyOptions},
+    bson::{doc, spec::BinarySubtype, Binary, Bson},
+    coll::options::{CountOptions, InsertManyOptions, InsertOneOptions},
     error::Result,
-    options::{Acknowledgment, FindOptions, ReadConcern, ReadPreference, WriteConcern},
+    options::{
+        Acknowledgment,
+        FindOneOptions,
+        FindOptions,
+        ReadConcern,
+        ReadPreference,
+        SessionOptions,
+        SessionUser,
+        WriteConcern,
+    },
     runtime,
     sdam::ServerInfo,
     selection_criteria::SelectionCriteria,
@@ -348,6 +361,228 @@ async fn session_usage() {
     for_each_op!(function_name!(), session_usage_test)
 }
 
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn unacknowledged_write_session_usage() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    if client.is_standalone() {
+        log_uncaptured("skipping unacknowledged_write_session_usage due to standalone topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    let w_0 = WriteConcern::builder().w(Acknowledgment::Nodes(0)).build();
+    coll.insert_one(
+        doc! { "x": 2 },
+        InsertOneOptions::builder().write_concern(w_0).build(),
+    )
+    .await
+    .unwrap();
+
+    let inserts = client.get_command_started_events(&["insert"]);
+    assert_eq!(inserts.len(), 2);
+
+    let acknowledged = &inserts[0];
+    assert!(acknowledged.session_id().is_some());
+    assert!(acknowledged.txn_number().is_some());
+
+    let unacknowledged = &inserts[1];
+    assert!(unacknowledged.session_id().is_none());
+    assert!(unacknowledged.txn_number().is_none());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn disable_implicit_sessions() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.disable_implicit_sessions = Some(true);
+    let client = EventClient::with_options(options).await;
+    if client.is_standalone() {
+        log_uncaptured("skipping disable_implicit_sessions due to standalone topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    coll.find_one(None, None).await.unwrap();
+
+    for event in client.get_command_started_events(&["insert", "find"]) {
+        assert!(
+            event.session_id().is_none(),
+            "{} sent with an implicit session",
+            event.command_name
+        );
+        assert!(event.txn_number().is_none());
+    }
+
+    let mut session = client.start_session(None).await.unwrap();
+    coll.insert_one_with_session(doc! { "x": 2 }, None, &mut session)
+        .await
+        .unwrap();
+    let inserts = client.get_command_started_events(&["insert"]);
+    assert_eq!(inserts.last().unwrap().session_id(), Some(session.id()));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn kill_sessions() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if !client.is_replica_set() || client.server_version_lt(4, 0) {
+        log_uncaptured("skipping kill_sessions due to topology not supporting transactions");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let mut session = client.start_session(None).await.unwrap();
+    session.start_transaction(None).await.unwrap();
+    coll.insert_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+
+    let other_client = TestClient::new().await;
+    other_client
+        .kill_sessions(vec![session.id().clone()])
+        .await
+        .unwrap();
+
+    // The transaction was aborted when its session was killed.
+    assert!(session.commit_transaction().await.is_err());
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 0);
+
+    // Killing one of the client's own idle sessions discards it from the pool.
+    let id = session.id().clone();
+    drop(session);
+    runtime::delay_for(Duration::from_millis(250)).await;
+    assert!(client.is_session_checked_in(&id).await);
+    client.kill_sessions(vec![id.clone()]).await.unwrap();
+    assert!(!client.is_session_checked_in(&id).await);
+
+    other_client
+        .kill_all_sessions(vec![SessionUser::new(
+            "kill_sessions_nonexistent_user",
+            "admin",
+        )])
+        .await
+        .unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn refresh_sessions() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if client.is_standalone() {
+        log_uncaptured("skipping refresh_sessions due to standalone topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let mut session = client.start_session(None).await.unwrap();
+    coll.insert_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+
+    // Simulate the session having been idle for longer than the logical session timeout.
+    let timeout = Duration::from_secs(30 * 60);
+    let last_use = match Instant::now().checked_sub(timeout * 2) {
+        Some(last_use) => last_use,
+        None => {
+            log_uncaptured("skipping refresh_sessions due to the monotonic clock being too recent");
+            return;
+        }
+    };
+    session.server_session.last_use = last_use;
+    assert!(session.server_session.is_about_to_expire(Some(timeout)));
+
+    session.refresh().await.unwrap();
+    assert!(!session.server_session.is_about_to_expire(Some(timeout)));
+    coll.find_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Refreshing a session the server doesn't know about starts a new one.
+    client
+        .refresh_sessions(vec![doc! { "id": Binary {
+            subtype: BinarySubtype::Uuid,
+            bytes: uuid::Uuid::new_v4().as_bytes().to_vec(),
+        } }])
+        .await
+        .unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn pooled_sessions_refreshed() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    if client.is_standalone() {
+        log_uncaptured("skipping pooled_sessions_refreshed due to standalone topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let mut session = client.start_session(None).await.unwrap();
+    coll.insert_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+    let id = Bson::Document(session.id().clone());
+    let refreshed = |client: &EventClient| {
+        client
+            .get_command_started_events(&["refreshSessions"])
+            .iter()
+            .any(|event| {
+                event
+                    .command
+                    .get_array("refreshSessions")
+                    .unwrap()
+                    .contains(&id)
+            })
+    };
+
+    // Sessions are left to their owners while they're checked out.
+    client.refresh_pooled_sessions(Duration::ZERO).await;
+    assert!(!refreshed(&client));
+
+    drop(session);
+    runtime::delay_for(Duration::from_millis(250)).await;
+
+    // The session was used too recently to need refreshing.
+    client
+        .refresh_pooled_sessions(Duration::from_secs(30 * 60))
+        .await;
+    assert!(!refreshed(&client));
+
+    client.refresh_pooled_sessions(Duration::ZERO).await;
+    assert!(refreshed(&client));
+}
+
 /// Prose test 7 from sessions spec.
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
@@ -391,6 +626,51 @@ async fn implicit_session_returned_after_immediate_exhaust() {
     assert!(matches!(cursor.next().await, Some(Ok(_))));
 }
 
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn implicit_sessions_pooled() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    if client.is_standalone() {
+        log_uncaptured("skipping implicit_sessions_pooled due to standalone topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    runtime::delay_for(Duration::from_millis(250)).await;
+    client.clear_session_pool().await;
+
+    for i in 0..5 {
+        coll.insert_one(doc! { "x": i }, None).await.unwrap();
+        // Wait for the implicit session to be checked back in to the pool in the background.
+        runtime::delay_for(Duration::from_millis(100)).await;
+        coll.find_one(doc! { "x": i }, None).await.unwrap();
+        runtime::delay_for(Duration::from_millis(100)).await;
+    }
+
+    let lsids: Vec<Document> = client
+        .get_command_started_events(&["insert", "find"])
+        .into_iter()
+        .map(|event| event.command.get_document("lsid").unwrap().clone())
+        .collect();
+    assert_eq!(lsids.len(), 10);
+    assert!(lsids.iter().all(|lsid| lsid == &lsids[0]));
+    assert_eq!(client.active_session_count(), 0);
+
+    let session = client.start_session(None).await.unwrap();
+    assert_eq!(session.id(), &lsids[0]);
+    assert_eq!(client.active_session_count(), 1);
+
+    drop(session);
+    runtime::delay_for(Duration::from_millis(250)).await;
+    assert_eq!(client.active_session_count(), 0);
+    assert!(client.is_session_checked_in(&lsids[0]).await);
+}
+
 /// Prose test 8 from sessions spec.
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
@@ -552,3 +832,75 @@ async fn find_and_getmore_share_session() {
         run_test(&client, &coll, read_pref).await;
     }
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn read_primary_after_write() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.selection_criteria = Some(SelectionCriteria::ReadPreference(
+        ReadPreference::Secondary {
+            options: Default::default(),
+        },
+    ));
+    let client = EventClient::with_options(options).await;
+    if !client.is_replica_set() {
+        log_uncaptured("skipping read_primary_after_write due to topology not being a replica set");
+        return;
+    }
+    let primary = client
+        .wait_for_primary(Duration::from_secs(10))
+        .await
+        .unwrap();
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let session_options = SessionOptions::builder()
+        .read_primary_after_write(true)
+        .build();
+    let mut session = client.start_session(session_options).await.unwrap();
+
+    coll.insert_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+    // The first read after the write goes to the primary and observes the write.
+    let found = coll
+        .find_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+    assert!(found.is_some());
+    // Subsequent reads use the default read preference again.
+    coll.find_one_with_session(None, None, &mut session)
+        .await
+        .unwrap();
+
+    // A read preference specified on the operation itself takes precedence.
+    coll.insert_one_with_session(doc! { "x": 2 }, None, &mut session)
+        .await
+        .unwrap();
+    let options = FindOneOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(
+            ReadPreference::Secondary {
+                options: Default::default(),
+            },
+        ))
+        .build();
+    coll.find_one_with_session(None, options, &mut session)
+        .await
+        .unwrap();
+    // That read was still the first read after the write, so the next one isn't routed to the
+    // primary either.
+    coll.find_one_with_session(None, None, &mut session)
+        .await
+        .unwrap();
+
+    let finds = client.get_command_started_events(&["find"]);
+    let on_primary: Vec<bool> = finds
+        .iter()
+        .map(|event| event.connection.address == primary)
+        .collect();
+    assert_eq!(on_primary, vec![true, false, false, false]);
+}
@@ -0,0 +1,165 @@
+use std::time::{Duration, Instant};
+
+use futures_util::future::BoxFuture;
+
+use super::{ClientSession, TransactionState};
+use crate::{
+    concern::{ReadConcern, WriteConcern},
+    error::{Result, TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT},
+    options::TransactionOptions,
+    selection_criteria::SelectionCriteria,
+};
+
+/// The amount of time after which a transaction run via [`TransactionBuilder::and_run`] is no
+/// longer retried.
+const WITH_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The server error code returned when an operation exceeds its `maxTimeMS`.
+const MAX_TIME_MS_EXPIRED_CODE: i32 = 50;
+
+/// A builder for running a transaction on a [`ClientSession`], created via
+/// [`ClientSession::transaction`].
+///
+/// Options that are not set on the builder are inherited from the session's default transaction
+/// options and then from the client, as with [`ClientSession::start_transaction`]. The builder can
+/// be used to run any number of transactions; each call to [`TransactionBuilder::and_run`] starts
+/// a new transaction with the options that were set on the builder.
+#[derive(Debug)]
+pub struct TransactionBuilder<'a> {
+    session: &'a mut ClientSession,
+    options: TransactionOptions,
+}
+
+impl<'a> TransactionBuilder<'a> {
+    pub(super) fn new(session: &'a mut ClientSession) -> Self {
+        Self {
+            session,
+            options: TransactionOptions::default(),
+        }
+    }
+
+    /// Sets the read concern to use for the transaction.
+    pub fn read_concern(mut self, read_concern: impl Into<Option<ReadConcern>>) -> Self {
+        self.options.read_concern = read_concern.into();
+        self
+    }
+
+    /// Sets the write concern to use when committing or aborting the transaction.
+    pub fn write_concern(mut self, write_concern: impl Into<Option<WriteConcern>>) -> Self {
+        self.options.write_concern = write_concern.into();
+        self
+    }
+
+    /// Sets the selection criteria to use for all read operations in the transaction.
+    pub fn selection_criteria(
+        mut self,
+        selection_criteria: impl Into<Option<SelectionCriteria>>,
+    ) -> Self {
+        self.options.selection_criteria = selection_criteria.into();
+        self
+    }
+
+    /// Sets the maximum amount of time to allow a single commitTransaction to run.
+    pub fn max_commit_time(mut self, max_commit_time: impl Into<Option<Duration>>) -> Self {
+        self.options.max_commit_time = max_commit_time.into();
+        self
+    }
+
+    /// Starts a transaction, runs `callback` within it and commits it, returning the value
+    /// produced by `callback`.
+    ///
+    /// `callback` is passed the session and a mutable reference to `context`, and should pass the
+    /// session to every operation that is part of the transaction. If `callback` returns an error,
+    /// the transaction is aborted. The whole transaction is run again if an error with a
+    /// [`TRANSIENT_TRANSACTION_ERROR`] label is returned by `callback` or by the commit, and the
+    /// commit alone is retried if it returns an error with an
+    /// [`UNKNOWN_TRANSACTION_COMMIT_RESULT`] label. Retries stop once 120 seconds have passed
+    /// since this method was called, after which the last error is returned. Since `callback` may
+    /// be run more than once, it should not have side effects outside of the transaction.
+    ///
+    /// ```rust
+    /// # use mongodb::{bson::{doc, Document}, error::Result, Client, Collection};
+    /// # use futures::FutureExt;
+    /// use mongodb::options::{Acknowledgment, ReadConcern, WriteConcern};
+    ///
+    /// # async fn do_stuff() -> Result<()> {
+    /// # let client = Client::with_uri_str("mongodb://example.com").await?;
+    /// let coll: Collection<Document> = client.database("foo").collection("bar");
+    /// let mut session = client.start_session(None).await?;
+    /// session
+    ///     .transaction()
+    ///     .read_concern(ReadConcern::snapshot())
+    ///     .write_concern(WriteConcern::builder().w(Acknowledgment::Majority).build())
+    ///     .and_run(coll, |session, coll| {
+    ///         async move {
+    ///             coll.insert_one_with_session(doc! { "x": 1 }, None, session).await?;
+    ///             coll.delete_one_with_session(doc! { "y": 2 }, None, session).await?;
+    ///             Ok(())
+    ///         }
+    ///         .boxed()
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn and_run<R, C, F>(&mut self, mut context: C, mut callback: F) -> Result<R>
+    where
+        F: for<'b> FnMut(&'b mut ClientSession, &'b mut C) -> BoxFuture<'b, Result<R>>,
+    {
+        let start = Instant::now();
+        let timed_out = || start.elapsed() >= WITH_TRANSACTION_TIMEOUT;
+
+        'transaction: loop {
+            // Each attempt starts from the builder's options rather than from the state left
+            // behind by a previous transaction on this session.
+            self.session.start_transaction(self.options.clone()).await?;
+
+            let value = match callback(&mut *self.session, &mut context).await {
+                Ok(value) => value,
+                Err(error) => {
+                    if matches!(
+                        self.session.transaction.state,
+                        TransactionState::Starting | TransactionState::InProgress
+                    ) {
+                        // The abort is best-effort; the callback's error is the one reported.
+                        let _ = self.session.abort_transaction().await;
+                    }
+                    if error.contains_label(TRANSIENT_TRANSACTION_ERROR) && !timed_out() {
+                        continue 'transaction;
+                    }
+                    return Err(error);
+                }
+            };
+
+            // The callback may have committed or aborted the transaction itself.
+            if matches!(
+                self.session.transaction.state,
+                TransactionState::None
+                    | TransactionState::Aborted
+                    | TransactionState::Committed { .. }
+            ) {
+                return Ok(value);
+            }
+
+            loop {
+                match self.session.commit_transaction().await {
+                    Ok(()) => return Ok(value),
+                    Err(error) => {
+                        if timed_out() {
+                            return Err(error);
+                        }
+                        if error.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT)
+                            && error.code() != Some(MAX_TIME_MS_EXPIRED_CODE)
+                        {
+                            continue;
+                        }
+                        if error.contains_label(TRANSIENT_TRANSACTION_ERROR) {
+                            continue 'transaction;
+                        }
+                        return Err(error);
+                    }
+                }
+            }
+        }
+    }
+}
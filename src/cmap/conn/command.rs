@@ -5,7 +5,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use super::wire::Message;
 use crate::{
-    bson::Document,
+    bson::{Bson, Document},
     client::{options::ServerApi, ClusterTime, HELLO_COMMAND_NAMES, REDACTED_COMMANDS},
     error::{Error, ErrorKind, Result},
     hello::{HelloCommandResponse, HelloReply},
@@ -44,6 +44,10 @@ pub(crate) struct Command<T = Document> {
     #[serde(rename = "$db")]
     pub(crate) target_db: String,
 
+    comment: Option<Bson>,
+
+    allow_disk_use: Option<bool>,
+
     lsid: Option<Document>,
 
     #[serde(rename = "$clusterTime")]
@@ -72,6 +76,8 @@ impl<T> Command<T> {
             name,
             target_db,
             body,
+            comment: None,
+            allow_disk_use: None,
             lsid: None,
             cluster_time: None,
             server_api: None,
@@ -94,6 +100,8 @@ impl<T> Command<T> {
             name,
             target_db,
             body,
+            comment: None,
+            allow_disk_use: None,
             lsid: None,
             cluster_time: None,
             server_api: None,
@@ -106,6 +114,14 @@ impl<T> Command<T> {
         }
     }
 
+    pub(crate) fn set_comment(&mut self, comment: Bson) {
+        self.comment = Some(comment);
+    }
+
+    pub(crate) fn set_allow_disk_use(&mut self, allow_disk_use: bool) {
+        self.allow_disk_use = Some(allow_disk_use);
+    }
+
     pub(crate) fn set_session(&mut self, session: &ClientSession) {
         self.lsid = Some(session.id().clone())
     }
@@ -160,7 +176,16 @@ impl<T> Command<T> {
         inner.at_cluster_time = session.snapshot_time;
     }
 
+    /// Sets the `afterClusterTime` field of the read concern for this command, unless it already
+    /// specifies `atClusterTime`, which the server doesn't allow to be combined with it.
     pub(crate) fn set_after_cluster_time(&mut self, session: &ClientSession) {
+        if self
+            .read_concern
+            .as_ref()
+            .map_or(false, |rc| rc.at_cluster_time.is_some())
+        {
+            return;
+        }
         if let Some(operation_time) = session.operation_time {
             let inner = self.read_concern.get_or_insert(ReadConcernInternal {
                 level: None,
@@ -175,6 +200,11 @@ impl<T> Command<T> {
 #[derive(Debug, Clone)]
 pub(crate) struct RawCommandResponse {
     pub(crate) source: ServerAddress,
+
+    /// The `responseTo` field of the reply's wire protocol header, i.e. the request id of the
+    /// message this is a response to.
+    pub(crate) response_to: i32,
+
     raw: RawDocumentBuf,
 }
 
@@ -185,6 +215,7 @@ impl RawCommandResponse {
         doc.to_writer(&mut raw)?;
         Ok(Self {
             source,
+            response_to: 0,
             raw: RawDocumentBuf::from_bytes(raw)?,
         })
     }
@@ -202,9 +233,11 @@ impl RawCommandResponse {
     }
 
     pub(crate) fn new(source: ServerAddress, message: Message) -> Result<Self> {
+        let response_to = message.response_to;
         let raw = message.single_document_response()?;
         Ok(Self {
             source,
+            response_to,
             raw: RawDocumentBuf::from_bytes(raw)?,
         })
     }
@@ -18,8 +18,9 @@ use crate::{
     cmap::{
         options::{ConnectionOptions, StreamOptions},
         PoolGeneration,
+        PoolGenerationSubscriber,
     },
-    compression::Compressor,
+    compression::{CompressionStatsRecorder, Compressor},
     error::{load_balanced_mode_mismatch, Error, ErrorKind, Result},
     event::cmap::{
         CmapEventHandler,
@@ -68,6 +69,10 @@ pub(crate) struct Connection {
     /// The cached StreamDescription from the connection's handshake.
     pub(super) stream_description: Option<StreamDescription>,
 
+    /// How long the connection's handshake (the initial `hello`, which also negotiates
+    /// compression, followed by authentication) took. Set once the connection is established.
+    pub(super) handshake_duration: Duration,
+
     /// Marks the time when the connection was last checked into the pool. This is used
     /// to detect if the connection is idle.
     ready_and_available_time: Option<Instant>,
@@ -96,6 +101,13 @@ pub(crate) struct Connection {
     /// server's compressor list.
     pub(super) compressor: Option<Compressor>,
 
+    /// Records the number of bytes sent and received by this connection.
+    compression_stats: Option<Arc<CompressionStatsRecorder>>,
+
+    /// If set, commands in progress are interrupted once the pool is cleared past this
+    /// connection's generation.
+    interrupt_subscriber: Option<PoolGenerationSubscriber>,
+
     /// If the connection is pinned to a cursor or transaction, the channel sender to return this
     /// connection to the pin holder.
     pinned_sender: Option<mpsc::Sender<Connection>>,
@@ -126,8 +138,15 @@ impl Connection {
             ready_and_available_time: None,
             stream: AsyncStream::connect(stream_options).await?,
             address,
+            compression_stats: options
+                .as_ref()
+                .and_then(|options| options.compression_stats.clone()),
+            interrupt_subscriber: options
+                .as_ref()
+                .and_then(|options| options.interrupt_subscriber.clone()),
             handler: options.and_then(|options| options.event_handler),
             stream_description: None,
+            handshake_duration: Duration::ZERO,
             error: false,
             pinned_sender: None,
             compressor: None,
@@ -167,6 +186,8 @@ impl Connection {
                 connect_timeout,
                 tls_options,
                 event_handler: None,
+                compression_stats: None,
+                interrupt_subscriber: None,
             }),
         )
         .await
@@ -250,11 +271,14 @@ impl Connection {
         }
     }
 
-    /// Helper to create a `ConnectionReadyEvent` for the connection.
-    pub(super) fn ready_event(&self) -> ConnectionReadyEvent {
+    /// Helper to create a `ConnectionReadyEvent` for the connection, which took `duration` to
+    /// establish.
+    pub(super) fn ready_event(&self, duration: Duration) -> ConnectionReadyEvent {
         ConnectionReadyEvent {
             address: self.address.clone(),
             connection_id: self.id,
+            duration,
+            handshake_duration: self.handshake_duration,
         }
     }
 
@@ -264,6 +288,7 @@ impl Connection {
             address: self.address.clone(),
             connection_id: self.id,
             reason,
+            handshake_duration: None,
         }
     }
 
@@ -271,6 +296,36 @@ impl Connection {
         &mut self,
         message: Message,
         to_compress: bool,
+    ) -> Result<RawCommandResponse> {
+        let mut interrupt_subscriber = match self.interrupt_subscriber.clone() {
+            Some(subscriber) => subscriber,
+            None => return self.send_message_uninterrupted(message, to_compress).await,
+        };
+        let generation = self.generation.clone();
+        let result = tokio::select! {
+            result = self.send_message_uninterrupted(message, to_compress) => Some(result),
+            _ = interrupt_subscriber.wait_until_stale(&generation) => None,
+        };
+        match result {
+            Some(result) => result,
+            None => {
+                // The response to the command will never be read, so the connection can't be used
+                // again.
+                self.command_executing = false;
+                self.error = true;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "connection interrupted because its pool was cleared",
+                )
+                .into())
+            }
+        }
+    }
+
+    async fn send_message_uninterrupted(
+        &mut self,
+        message: Message,
+        to_compress: bool,
     ) -> Result<RawCommandResponse> {
         self.command_executing = true;
 
@@ -286,7 +341,10 @@ impl Connection {
         };
 
         self.error = write_result.is_err();
-        write_result?;
+        let sent_size = write_result?;
+        if let Some(ref stats) = self.compression_stats {
+            stats.record_sent(&sent_size);
+        }
 
         let response_message_result = Message::read_from(
             &mut self.stream,
@@ -298,7 +356,11 @@ impl Connection {
         self.command_executing = false;
         self.error = response_message_result.is_err();
 
-        RawCommandResponse::new(self.address.clone(), response_message_result?)
+        let (response_message, received_size) = response_message_result?;
+        if let Some(ref stats) = self.compression_stats {
+            stats.record_received(&received_size);
+        }
+        RawCommandResponse::new(self.address.clone(), response_message)
     }
 
     /// Executes a `Command` and returns a `CommandResponse` containing the result from the server.
@@ -394,6 +456,9 @@ impl Connection {
             ready_and_available_time: None,
             pinned_sender: self.pinned_sender.clone(),
             compressor: self.compressor.clone(),
+            compression_stats: self.compression_stats.clone(),
+            interrupt_subscriber: self.interrupt_subscriber.take(),
+            handshake_duration: self.handshake_duration,
         }
     }
 }
@@ -14,7 +14,7 @@ use crate::{
     runtime::{AsyncStream, SyncLittleEndianRead},
 };
 
-use crate::compression::{Compressor, Decoder};
+use crate::compression::{Compressor, CompressorId, Decoder, MessageSize};
 
 /// Represents an OP_MSG wire protocol operation.
 #[derive(Debug)]
@@ -76,11 +76,12 @@ impl Message {
         })
     }
 
-    /// Reads bytes from `reader` and deserializes them into a Message.
+    /// Reads bytes from `reader` and deserializes them into a Message, also returning the size of
+    /// the message.
     pub(crate) async fn read_from(
         reader: &mut AsyncStream,
         max_message_size_bytes: Option<i32>,
-    ) -> Result<Self> {
+    ) -> Result<(Self, MessageSize)> {
         let mut reader = BufReader::new(reader);
         let header = Header::read_from(&mut reader).await?;
         let max_len = max_message_size_bytes.unwrap_or(DEFAULT_MAX_MESSAGE_SIZE_BYTES);
@@ -92,7 +93,13 @@ impl Message {
         }
 
         if header.op_code == OpCode::Message {
-            return Self::read_from_op_msg(reader, &header).await;
+            let message = Self::read_from_op_msg(reader, &header).await?;
+            let size = MessageSize {
+                compressor: None,
+                uncompressed: header.length as usize,
+                wire: header.length as usize,
+            };
+            return Ok((message, size));
         }
         if header.op_code == OpCode::Compressed {
             return Self::read_from_op_compressed(reader, &header).await;
@@ -127,7 +134,7 @@ impl Message {
     async fn read_from_op_compressed(
         mut reader: BufReader<&mut AsyncStream>,
         header: &Header,
-    ) -> Result<Self> {
+    ) -> Result<(Self, MessageSize)> {
         let length_remaining = header.length - Header::LENGTH as i32;
         let mut buf = vec![0u8; length_remaining as usize];
         reader.read_exact(&mut buf).await?;
@@ -154,6 +161,7 @@ impl Message {
 
         // Get decoder
         let decoder = Decoder::from_u8(compressor_id)?;
+        let compressor_id = CompressorId::from_u8(compressor_id)?;
 
         // Decode message
         let decoded_message = decoder.decode(reader)?;
@@ -175,7 +183,13 @@ impl Message {
         let reader = decoded_message.as_slice();
         let length_remaining = decoded_message.len();
 
-        Self::read_op_common(reader, length_remaining as i32, header)
+        let message = Self::read_op_common(reader, length_remaining as i32, header)?;
+        let size = MessageSize {
+            compressor: Some(compressor_id),
+            uncompressed: Header::LENGTH + decoded_message.len(),
+            wire: header.length as usize,
+        };
+        Ok((message, size))
     }
 
     fn read_op_common(
@@ -220,8 +234,9 @@ impl Message {
         })
     }
 
-    /// Serializes the Message to bytes and writes them to `writer`.
-    pub(crate) async fn write_to(&self, stream: &mut AsyncStream) -> Result<()> {
+    /// Serializes the Message to bytes and writes them to `writer`, returning the size of the
+    /// message.
+    pub(crate) async fn write_to(&self, stream: &mut AsyncStream) -> Result<MessageSize> {
         let mut writer = BufWriter::new(stream);
         let mut sections_bytes = Vec::new();
 
@@ -255,15 +270,20 @@ impl Message {
 
         writer.flush().await?;
 
-        Ok(())
+        Ok(MessageSize {
+            compressor: None,
+            uncompressed: total_length,
+            wire: total_length,
+        })
     }
 
-    /// Serializes message to bytes, compresses those bytes, and writes the bytes.
+    /// Serializes message to bytes, compresses those bytes, and writes the bytes, returning the
+    /// size of the message.
     pub async fn write_compressed_to(
         &self,
         stream: &mut AsyncStream,
         compressor: &Compressor,
-    ) -> Result<()> {
+    ) -> Result<MessageSize> {
         let mut encoder = compressor.to_encoder()?;
         let compressor_id = compressor.id() as u8;
 
@@ -307,7 +327,11 @@ impl Message {
 
         writer.flush().await?;
 
-        Ok(())
+        Ok(MessageSize {
+            compressor: Some(compressor.id()),
+            uncompressed: Header::LENGTH + uncompressed_len,
+            wire: total_length,
+        })
     }
 }
 
@@ -11,7 +11,7 @@ use crate::{
     client::auth::{ClientFirst, FirstRound},
     cmap::{options::ConnectionPoolOptions, Command, Connection, StreamDescription},
     compression::Compressor,
-    error::{ErrorKind, Result},
+    error::{Error, ErrorKind, Result},
     event::sdam::SdamEventHandler,
     hello::{hello_command, run_hello, HelloReply},
     options::{AuthMechanism, ClientOptions, Credential, DriverInfo, ServerApi},
@@ -30,6 +30,9 @@ const RUNTIME_NAME: &str = "sync (with async-std)";
 #[cfg(feature = "tokio-sync")]
 const RUNTIME_NAME: &str = "sync (with tokio)";
 
+/// The maximum size in bytes of the client metadata document the server accepts in a handshake.
+const MAX_CLIENT_METADATA_BYTES: usize = 512;
+
 #[derive(Clone, Debug)]
 struct ClientMetadata {
     application: Option<AppMetadata>,
@@ -40,7 +43,8 @@ struct ClientMetadata {
 
 #[derive(Clone, Debug)]
 struct AppMetadata {
-    name: String,
+    name: Option<String>,
+    correlation_id: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -58,11 +62,24 @@ struct OsMetadata {
 }
 
 impl From<ClientMetadata> for Bson {
+    fn from(metadata: ClientMetadata) -> Self {
+        Bson::Document(metadata.into())
+    }
+}
+
+impl From<ClientMetadata> for Document {
     fn from(metadata: ClientMetadata) -> Self {
         let mut metadata_doc = Document::new();
 
         if let Some(application) = metadata.application {
-            metadata_doc.insert("application", doc! { "name": application.name });
+            let mut application_doc = Document::new();
+            if let Some(name) = application.name {
+                application_doc.insert("name", name);
+            }
+            if let Some(correlation_id) = application.correlation_id {
+                application_doc.insert("correlationId", correlation_id);
+            }
+            metadata_doc.insert("application", application_doc);
         }
 
         metadata_doc.insert(
@@ -76,7 +93,7 @@ impl From<ClientMetadata> for Bson {
         metadata_doc.insert("os", metadata.os);
         metadata_doc.insert("platform", metadata.platform);
 
-        Bson::Document(metadata_doc)
+        metadata_doc
     }
 }
 
@@ -161,24 +178,11 @@ impl Handshaker {
         );
 
         if let Some(options) = options {
-            if let Some(app_name) = options.app_name {
-                metadata.application = Some(AppMetadata { name: app_name });
-            }
-
-            if let Some(driver_info) = options.driver_info {
-                metadata.driver.name.push('|');
-                metadata.driver.name.push_str(&driver_info.name);
-
-                if let Some(ref version) = driver_info.version {
-                    metadata.driver.version.push('|');
-                    metadata.driver.version.push_str(version);
-                }
-
-                if let Some(ref driver_info_platform) = driver_info.platform {
-                    metadata.platform.push('|');
-                    metadata.platform.push_str(driver_info_platform);
-                }
-            }
+            metadata = client_metadata(
+                options.app_name,
+                options.correlation_id,
+                options.driver_info,
+            );
 
             if let Some(cred) = options.credential {
                 cred.append_needed_mechanism_negotiation(&mut command.body);
@@ -233,6 +237,8 @@ impl Handshaker {
                 message: "Driver attempted to initialize in load balancing mode, but the server \
                           does not support this mode."
                     .to_string(),
+                driver_range: None,
+                server_range: None,
             }
             .into());
         }
@@ -278,6 +284,67 @@ impl Handshaker {
     }
 }
 
+/// Builds the client metadata sent in the handshake from the user-provided options.
+fn client_metadata(
+    app_name: Option<String>,
+    correlation_id: Option<String>,
+    driver_info: Option<DriverInfo>,
+) -> ClientMetadata {
+    let mut metadata = BASE_CLIENT_METADATA.clone();
+
+    if app_name.is_some() || correlation_id.is_some() {
+        metadata.application = Some(AppMetadata {
+            name: app_name,
+            correlation_id,
+        });
+    }
+
+    if let Some(driver_info) = driver_info {
+        metadata.driver.name.push('|');
+        metadata.driver.name.push_str(&driver_info.name);
+
+        if let Some(ref version) = driver_info.version {
+            metadata.driver.version.push('|');
+            metadata.driver.version.push_str(version);
+        }
+
+        if let Some(ref driver_info_platform) = driver_info.platform {
+            metadata.platform.push('|');
+            metadata.platform.push_str(driver_info_platform);
+        }
+    }
+
+    metadata
+}
+
+/// Returns an error if the client metadata built from `options` would be larger than the server
+/// accepts. The server rejects handshakes with oversized metadata, so this is only checked when a
+/// `correlation_id` is set, since it is the only field whose size is entirely up to the user.
+pub(crate) fn validate_client_metadata(options: &ClientOptions) -> Result<()> {
+    if options.correlation_id.is_none() {
+        return Ok(());
+    }
+
+    let metadata = Document::from(client_metadata(
+        options.app_name.clone(),
+        options.correlation_id.clone(),
+        options.driver_info.clone(),
+    ));
+    let mut bytes = Vec::new();
+    metadata.to_writer(&mut bytes)?;
+
+    if bytes.len() > MAX_CLIENT_METADATA_BYTES {
+        return Err(Error::invalid_argument(format!(
+            "the client metadata sent in the handshake must not exceed {} bytes, but it would be \
+             {} bytes with the given correlation_id",
+            MAX_CLIENT_METADATA_BYTES,
+            bytes.len()
+        )));
+    }
+
+    Ok(())
+}
+
 /// The information returned from the server as part of the handshake.
 ///
 /// Also optionally includes the first round of speculative authentication
@@ -294,6 +361,7 @@ pub(crate) struct HandshakeResult {
 #[derive(Debug)]
 pub(crate) struct HandshakerOptions {
     app_name: Option<String>,
+    correlation_id: Option<String>,
     credential: Option<Credential>,
     compressors: Option<Vec<Compressor>>,
     driver_info: Option<DriverInfo>,
@@ -305,6 +373,7 @@ impl From<ConnectionPoolOptions> for HandshakerOptions {
     fn from(options: ConnectionPoolOptions) -> Self {
         Self {
             app_name: options.app_name,
+            correlation_id: options.correlation_id,
             compressors: options.compressors,
             credential: options.credential,
             driver_info: options.driver_info,
@@ -318,6 +387,7 @@ impl From<ClientOptions> for HandshakerOptions {
     fn from(options: ClientOptions) -> Self {
         Self {
             app_name: options.app_name,
+            correlation_id: options.correlation_id,
             compressors: options.compressors,
             credential: options.credential,
             driver_info: options.driver_info,
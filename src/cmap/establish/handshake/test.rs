@@ -1,7 +1,8 @@
-use super::Handshaker;
+use super::{validate_client_metadata, Handshaker};
 use crate::{
     bson::doc,
     cmap::options::ConnectionPoolOptions,
+    error::ErrorKind,
     options::{ClientOptions, DriverInfo},
 };
 
@@ -63,3 +64,52 @@ fn metadata_with_options() {
     assert_eq!(os.get_str("type"), Ok(std::env::consts::OS));
     assert_eq!(os.get_str("architecture"), Ok(std::env::consts::ARCH));
 }
+
+#[test]
+fn metadata_with_correlation_id() {
+    let correlation_id = "worker-7f3a";
+
+    let options = ClientOptions::builder()
+        .correlation_id(correlation_id.to_string())
+        .build();
+    validate_client_metadata(&options).unwrap();
+    let handshaker = Handshaker::new(Some(
+        ConnectionPoolOptions::from_client_options(&options).into(),
+    ));
+    let metadata = handshaker.command.body.get_document("client").unwrap();
+    assert_eq!(
+        metadata.get_document("application"),
+        Ok(&doc! { "correlationId": correlation_id })
+    );
+
+    let options = ClientOptions::builder()
+        .app_name("myspace 2.0".to_string())
+        .correlation_id(correlation_id.to_string())
+        .build();
+    let handshaker = Handshaker::new(Some(options.into()));
+    let metadata = handshaker.command.body.get_document("client").unwrap();
+    assert_eq!(
+        metadata.get_document("application"),
+        Ok(&doc! { "name": "myspace 2.0", "correlationId": correlation_id })
+    );
+}
+
+#[test]
+fn metadata_correlation_id_too_large() {
+    let options = ClientOptions::builder()
+        .correlation_id("x".repeat(512))
+        .build();
+    let error = validate_client_metadata(&options).unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+        "{:?}",
+        error
+    );
+
+    let error = crate::Client::with_options(options).unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+        "{:?}",
+        error
+    );
+}
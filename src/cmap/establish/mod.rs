@@ -2,6 +2,8 @@ pub(super) mod handshake;
 #[cfg(test)]
 mod test;
 
+use std::time::{Duration, Instant};
+
 use self::handshake::Handshaker;
 use super::{
     conn::{ConnectionGeneration, PendingConnection},
@@ -48,13 +50,16 @@ impl ConnectionEstablisher {
         let pool_gen = pending_connection.generation.clone();
         let mut connection = Connection::connect(pending_connection)
             .await
-            .map_err(|e| EstablishError::pre_hello(e, pool_gen.clone()))?;
+            .map_err(|e| EstablishError::pre_hello(e, pool_gen.clone(), None))?;
 
+        let handshake_start = Instant::now();
         let handshake = self
             .handshaker
             .handshake(&mut connection, None, &None)
             .await
-            .map_err(|e| EstablishError::pre_hello(e, pool_gen.clone()))?;
+            .map_err(|e| {
+                EstablishError::pre_hello(e, pool_gen.clone(), Some(handshake_start.elapsed()))
+            })?;
         let service_id = handshake.hello_reply.command_response.service_id;
 
         // If the handshake response had a `serviceId` field, this is a connection to a load
@@ -74,6 +79,7 @@ impl ConnectionEstablisher {
                     }
                     .into(),
                     connection.generation.clone(),
+                    handshake_start.elapsed(),
                 ));
             }
         }
@@ -87,8 +93,15 @@ impl ConnectionEstablisher {
                     handshake.first_round,
                 )
                 .await
-                .map_err(|e| EstablishError::post_hello(e, connection.generation.clone()))?
+                .map_err(|e| {
+                    EstablishError::post_hello(
+                        e,
+                        connection.generation.clone(),
+                        handshake_start.elapsed(),
+                    )
+                })?
         }
+        connection.handshake_duration = handshake_start.elapsed();
 
         Ok(connection)
     }
@@ -98,19 +111,32 @@ impl ConnectionEstablisher {
 pub(crate) struct EstablishError {
     pub(crate) cause: MongoError,
     pub(crate) handshake_phase: HandshakePhase,
+    /// How long the handshake ran before failing, or `None` if the connection failed before the
+    /// handshake started.
+    pub(crate) handshake_duration: Option<Duration>,
 }
 
 impl EstablishError {
-    fn pre_hello(cause: MongoError, generation: PoolGeneration) -> Self {
+    fn pre_hello(
+        cause: MongoError,
+        generation: PoolGeneration,
+        handshake_duration: Option<Duration>,
+    ) -> Self {
         Self {
             cause,
             handshake_phase: HandshakePhase::PreHello { generation },
+            handshake_duration,
         }
     }
-    fn post_hello(cause: MongoError, generation: ConnectionGeneration) -> Self {
+    fn post_hello(
+        cause: MongoError,
+        generation: ConnectionGeneration,
+        handshake_duration: Duration,
+    ) -> Self {
         Self {
             cause,
             handshake_phase: HandshakePhase::PostHello { generation },
+            handshake_duration: Some(handshake_duration),
         }
     }
 }
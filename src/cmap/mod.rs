@@ -18,11 +18,15 @@ use tokio::sync::oneshot;
 pub use self::conn::ConnectionInfo;
 pub(crate) use self::{
     conn::{Command, Connection, RawCommand, RawCommandResponse, StreamDescription},
-    establish::handshake::Handshaker,
+    establish::handshake::{validate_client_metadata, Handshaker},
     status::PoolGenerationSubscriber,
     worker::PoolGeneration,
 };
-use self::{connection_requester::ConnectionRequestResult, options::ConnectionPoolOptions};
+use self::{
+    connection_requester::ConnectionRequestResult,
+    options::ConnectionPoolOptions,
+    status::PoolStatsSubscriber,
+};
 use crate::{
     bson::oid::ObjectId,
     error::{Error, Result},
@@ -32,6 +36,7 @@ use crate::{
         ConnectionCheckoutFailedReason,
         ConnectionCheckoutStartedEvent,
         PoolCreatedEvent,
+        PoolStats,
     },
     options::ServerAddress,
     runtime::HttpClient,
@@ -55,6 +60,7 @@ pub(crate) struct ConnectionPool {
     manager: PoolManager,
     connection_requester: ConnectionRequester,
     generation_subscriber: PoolGenerationSubscriber,
+    stats_subscriber: PoolStatsSubscriber,
 
     #[derivative(Debug = "ignore")]
     event_handler: Option<Arc<dyn CmapEventHandler>>,
@@ -67,12 +73,13 @@ impl ConnectionPool {
         server_updater: TopologyUpdater,
         options: Option<ConnectionPoolOptions>,
     ) -> Self {
-        let (manager, connection_requester, generation_subscriber) = ConnectionPoolWorker::start(
-            address.clone(),
-            http_client,
-            server_updater,
-            options.clone(),
-        );
+        let (manager, connection_requester, generation_subscriber, stats_subscriber) =
+            ConnectionPoolWorker::start(
+                address.clone(),
+                http_client,
+                server_updater,
+                options.clone(),
+            );
 
         let event_handler = options
             .as_ref()
@@ -90,6 +97,7 @@ impl ConnectionPool {
             manager,
             connection_requester,
             generation_subscriber,
+            stats_subscriber,
             event_handler,
         }
     }
@@ -100,12 +108,14 @@ impl ConnectionPool {
         let handle = WorkerHandle::new_mocked();
         let (connection_requester, _) = connection_requester::channel(handle);
         let (_, generation_subscriber) = status::channel(PoolGeneration::normal());
+        let (_, stats_subscriber) = status::stats_channel();
 
         Self {
             address,
             manager,
             connection_requester,
             generation_subscriber,
+            stats_subscriber,
             event_handler: None,
         }
     }
@@ -175,6 +185,10 @@ impl ConnectionPool {
         self.generation_subscriber.generation()
     }
 
+    pub(crate) fn stats(&self) -> PoolStats {
+        self.stats_subscriber.stats()
+    }
+
     #[cfg(test)]
     pub(crate) fn sync_worker(&self) -> oneshot::Receiver<()> {
         self.manager.sync_worker()
@@ -8,10 +8,11 @@ use serde::de::{Deserializer, Error};
 use serde::Deserialize;
 use typed_builder::TypedBuilder;
 
+use super::status::PoolGenerationSubscriber;
 use crate::{
     bson_util,
     client::{auth::Credential, options::ServerApi},
-    compression::Compressor,
+    compression::{CompressionStatsRecorder, Compressor},
     event::cmap::{CmapEventHandler, ConnectionPoolOptions as EventOptions},
     options::{ClientOptions, DriverInfo, ServerAddress, TlsOptions},
 };
@@ -25,6 +26,11 @@ pub(crate) struct ConnectionPoolOptions {
     /// handshake that each connection makes when it's created.
     pub(crate) app_name: Option<String>,
 
+    /// The correlation id specified by the user, which is sent to the server alongside the
+    /// application name in the handshake that each connection makes when it's created.
+    #[serde(skip)]
+    pub(crate) correlation_id: Option<String>,
+
     /// The connect timeout passed to each underlying TcpStream when attempting to connect to the
     /// server.
     #[serde(skip)]
@@ -50,6 +56,11 @@ pub(crate) struct ConnectionPoolOptions {
     #[serde(skip)]
     pub(crate) compressors: Option<Vec<Compressor>>,
 
+    /// Records the number of bytes sent and received by the pool's connections.
+    #[derivative(PartialEq = "ignore")]
+    #[serde(skip)]
+    pub(crate) compression_stats: Option<Arc<CompressionStatsRecorder>>,
+
     /// Interval between background thread maintenance runs (e.g. ensure minPoolSize).
     #[cfg(test)]
     #[serde(rename = "backgroundThreadIntervalMS")]
@@ -70,6 +81,11 @@ pub(crate) struct ConnectionPoolOptions {
     /// The default is 10.
     pub(crate) max_pool_size: Option<u32>,
 
+    /// The maximum number of connections that the pool can be establishing concurrently.
+    ///
+    /// The default is 2.
+    pub(crate) max_connecting: Option<u32>,
+
     /// The minimum number of connections that the pool can have at a given time. This includes
     /// connections which are currently checked out of the pool. If fewer than `min_pool_size`
     /// connections are in the pool, connections will be added to the pool in the background.
@@ -77,6 +93,19 @@ pub(crate) struct ConnectionPoolOptions {
     /// The default is that no minimum is enforced
     pub(crate) min_pool_size: Option<u32>,
 
+    /// Interval between background thread maintenance runs, as specified by the user.
+    ///
+    /// The default is 500 ms.
+    #[serde(skip)]
+    pub(crate) maintenance_interval: Option<Duration>,
+
+    /// Whether clearing the pool also interrupts the commands in progress on connections that are
+    /// checked out of it.
+    ///
+    /// The default is not to interrupt in-use connections.
+    #[serde(skip)]
+    pub(crate) pool_clear_interrupt_in_use: Option<bool>,
+
     /// Whether to start the pool as "ready" or not.
     /// For tests only.
     #[cfg(test)]
@@ -103,15 +132,20 @@ impl ConnectionPoolOptions {
         Self {
             app_name: options.app_name.clone(),
             connect_timeout: options.connect_timeout,
+            correlation_id: options.correlation_id.clone(),
             driver_info: options.driver_info.clone(),
             max_idle_time: options.max_idle_time,
             min_pool_size: options.min_pool_size,
             max_pool_size: options.max_pool_size,
+            maintenance_interval: options.pool_maintenance_interval,
+            pool_clear_interrupt_in_use: options.pool_clear_interrupt_in_use,
+            max_connecting: options.max_connecting,
             server_api: options.server_api.clone(),
             tls_options: options.tls_options(),
             credential: options.credential.clone(),
             cmap_event_handler: options.cmap_event_handler.clone(),
             compressors: options.compressors.clone(),
+            compression_stats: options.compression_stats.clone(),
             #[cfg(test)]
             background_thread_interval: None,
             #[cfg(test)]
@@ -164,6 +198,12 @@ pub(crate) struct ConnectionOptions {
 
     #[derivative(Debug = "ignore")]
     pub(crate) event_handler: Option<Arc<dyn CmapEventHandler>>,
+
+    pub(crate) compression_stats: Option<Arc<CompressionStatsRecorder>>,
+
+    /// If set, the command in progress on the connection is interrupted once the pool is cleared
+    /// past the connection's generation.
+    pub(crate) interrupt_subscriber: Option<PoolGenerationSubscriber>,
 }
 
 impl From<ConnectionPoolOptions> for ConnectionOptions {
@@ -172,6 +212,8 @@ impl From<ConnectionPoolOptions> for ConnectionOptions {
             connect_timeout: pool_options.connect_timeout,
             tls_options: pool_options.tls_options,
             event_handler: pool_options.cmap_event_handler,
+            compression_stats: pool_options.compression_stats,
+            interrupt_subscriber: None,
         }
     }
 }
@@ -1,4 +1,7 @@
-use crate::cmap::PoolGeneration;
+use crate::{
+    cmap::{conn::ConnectionGeneration, PoolGeneration},
+    event::cmap::PoolStats,
+};
 
 /// Struct used to track the latest status of the pool.
 #[derive(Clone, Debug)]
@@ -45,4 +48,53 @@ impl PoolGenerationSubscriber {
     pub(crate) fn generation(&self) -> PoolGeneration {
         self.receiver.borrow().generation.clone()
     }
+
+    /// Waits until the pool has been cleared past `generation`, i.e. until connections of that
+    /// generation are stale. This never completes if the pool is closed first.
+    pub(super) async fn wait_until_stale(&mut self, generation: &ConnectionGeneration) {
+        loop {
+            if generation.is_stale(&self.receiver.borrow().generation) {
+                return;
+            }
+            if self.receiver.changed().await.is_err() {
+                return futures_util::future::pending().await;
+            }
+        }
+    }
+}
+
+/// Create a channel for publishing and receiving the pool's latest statistics.
+pub(super) fn stats_channel() -> (PoolStatsPublisher, PoolStatsSubscriber) {
+    let (sender, receiver) = tokio::sync::watch::channel(PoolStats::default());
+    (
+        PoolStatsPublisher { sender },
+        PoolStatsSubscriber { receiver },
+    )
+}
+
+/// Struct used to publish updates to the pool's statistics.
+#[derive(Debug)]
+pub(super) struct PoolStatsPublisher {
+    sender: tokio::sync::watch::Sender<PoolStats>,
+}
+
+impl PoolStatsPublisher {
+    /// Publish new statistics.
+    pub(super) fn publish(&self, stats: PoolStats) {
+        // if nobody is listening, this will return an error, which we don't mind.
+        let _: std::result::Result<_, _> = self.sender.send(stats);
+    }
+}
+
+/// Subscriber used to get the latest statistics of the pool.
+#[derive(Clone, Debug)]
+pub(crate) struct PoolStatsSubscriber {
+    receiver: tokio::sync::watch::Receiver<PoolStats>,
+}
+
+impl PoolStatsSubscriber {
+    /// Get a copy of the latest statistics.
+    pub(crate) fn stats(&self) -> PoolStats {
+        self.receiver.borrow().clone()
+    }
 }
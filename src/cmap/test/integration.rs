@@ -7,10 +7,13 @@ use super::{
 };
 use crate::{
     bson::{doc, Document},
+    client::auth::Credential,
     cmap::{options::ConnectionPoolOptions, Command, ConnectionPool},
-    event::cmap::{CmapEventHandler, ConnectionClosedReason},
+    error::ErrorKind,
+    event::cmap::{CmapEventHandler, ConnectionClosedReason, PoolSaturatedEvent},
     hello::LEGACY_HELLO_COMMAND_NAME,
     operation::CommandResponse,
+    options::ClientOptions,
     runtime,
     sdam::TopologyUpdater,
     selection_criteria::ReadPreference,
@@ -25,7 +28,10 @@ use crate::{
     },
 };
 use semver::VersionReq;
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 #[derive(Debug, Deserialize)]
 struct ListDatabasesResponse {
@@ -272,3 +278,320 @@ async fn connection_error_during_operation() {
         .await
         .expect("closed event with error reason should have been seen");
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn pool_saturation() {
+    let _guard: RwLockWriteGuard<_> = LOCK.run_exclusively().await;
+
+    let mut client_options = CLIENT_OPTIONS.get().await.clone();
+    if client_options.load_balanced.unwrap_or(false) {
+        log_uncaptured(format!(
+            "skipping {} due to load-balanced topology",
+            function_name!()
+        ));
+        return;
+    }
+    client_options.hosts.drain(1..);
+    client_options.direct_connection = Some(true);
+
+    let client = TestClient::with_options(Some(client_options.clone())).await;
+    if !client.supports_fail_command_appname_initial_handshake() {
+        log_uncaptured(format!(
+            "skipping {} due to failCommand not being supported",
+            function_name!()
+        ));
+        return;
+    }
+
+    // Only the connections of the pool under test are slowed down.
+    let options = FailCommandOptions::builder()
+        .app_name(function_name!().to_string())
+        .block_connection(Duration::from_millis(500))
+        .build();
+    let failpoint = FailPoint::fail_command(
+        &[LEGACY_HELLO_COMMAND_NAME, "hello"],
+        FailPointMode::AlwaysOn,
+        Some(options),
+    );
+    let _fp_guard = client.enable_failpoint(failpoint, None).await.unwrap();
+
+    #[derive(Default)]
+    struct SaturationHandler {
+        events: Mutex<Vec<PoolSaturatedEvent>>,
+    }
+
+    impl CmapEventHandler for SaturationHandler {
+        fn handle_pool_saturated_event(&self, event: PoolSaturatedEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    let handler = Arc::new(SaturationHandler::default());
+    let mut options = ConnectionPoolOptions::from_client_options(&client_options);
+    options.app_name = Some(function_name!().to_string());
+    options.max_connecting = Some(1);
+    options.ready = Some(true);
+    options.cmap_event_handler = Some(handler.clone() as Arc<dyn CmapEventHandler>);
+    let pool = ConnectionPool::new(
+        client_options.hosts[0].clone(),
+        Default::default(),
+        TopologyUpdater::channel().0,
+        Some(options),
+    );
+
+    let tasks: Vec<_> = (0..3)
+        .map(|_| {
+            let pool = pool.clone();
+            runtime::spawn(async move {
+                pool.check_out().await.unwrap();
+            })
+        })
+        .collect();
+
+    // While the first connection is being established, the other checkouts wait for it.
+    runtime::delay_for(Duration::from_millis(250)).await;
+    let stats = pool.stats();
+    assert_eq!(stats.connecting, 1, "{:?}", stats);
+    assert_eq!(stats.waiting, 2, "{:?}", stats);
+    assert!(stats.saturated_since.is_some(), "{:?}", stats);
+    assert!(handler.events.lock().unwrap().is_empty());
+
+    futures::future::join_all(tasks).await;
+    pool.sync_worker().await.unwrap();
+
+    let stats = pool.stats();
+    assert_eq!(stats.connecting, 0, "{:?}", stats);
+    assert_eq!(stats.waiting, 0, "{:?}", stats);
+    assert!(stats.saturated_since.is_none(), "{:?}", stats);
+
+    // The checkouts were saturated for as long as it took to establish the first two connections.
+    let events = handler.events.lock().unwrap();
+    assert_eq!(events.len(), 1, "{:?}", events);
+    assert_eq!(events[0].max_connecting, 1);
+    assert_eq!(events[0].max_waiting, 2);
+    assert!(
+        events[0].duration >= Duration::from_millis(900),
+        "{:?}",
+        events[0]
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn min_pool_size_restored_within_maintenance_interval() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client_options = CLIENT_OPTIONS.get().await.clone();
+    if client_options.load_balanced.unwrap_or(false) {
+        log_uncaptured(format!(
+            "skipping {} due to load-balanced topology",
+            function_name!()
+        ));
+        return;
+    }
+
+    // With a short interval, the pool is refilled well within the event timeout. With a very long
+    // one, it isn't refilled even after several default maintenance intervals, which shows that
+    // the configured interval is used rather than the default.
+    for (interval, expect_refill) in [
+        (Duration::from_millis(100), true),
+        (Duration::from_secs(60 * 60), false),
+    ] {
+        let handler = Arc::new(EventHandler::new());
+        let mut subscriber = handler.subscribe();
+
+        let mut options = ConnectionPoolOptions::from_client_options(&client_options);
+        options.min_pool_size = Some(2);
+        options.maintenance_interval = Some(interval);
+        options.ready = Some(true);
+        options.cmap_event_handler = Some(handler.clone() as Arc<dyn CmapEventHandler>);
+        let pool = ConnectionPool::new(
+            client_options.hosts[0].clone(),
+            Default::default(),
+            TopologyUpdater::channel().0,
+            Some(options),
+        );
+
+        for _ in 0..2 {
+            subscriber
+                .wait_for_event(EVENT_TIMEOUT, |e| matches!(e, Event::ConnectionReady(_)))
+                .await
+                .expect("min_pool_size connections should have been established");
+        }
+
+        // Clearing the pool makes its connections stale; once it is ready again, the next
+        // maintenance run closes them and establishes replacements.
+        pool.clear(
+            ErrorKind::Internal {
+                message: "test".to_string(),
+            }
+            .into(),
+            None,
+        )
+        .await;
+        pool.mark_as_ready().await;
+
+        let is_replacement = |e: &Event| match e {
+            Event::ConnectionCreated(event) => event.connection_id > 2,
+            _ => false,
+        };
+        if expect_refill {
+            for _ in 0..2 {
+                subscriber
+                    .wait_for_event(EVENT_TIMEOUT, is_replacement)
+                    .await
+                    .expect("replacement connections should have been created");
+            }
+        } else {
+            let event = subscriber
+                .wait_for_event(Duration::from_secs(2), is_replacement)
+                .await;
+            assert!(
+                event.is_none(),
+                "pool should not have been refilled, got {:?}",
+                event
+            );
+        }
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn handshake_duration() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut client_options = CLIENT_OPTIONS.get().await.clone();
+    if client_options.load_balanced.unwrap_or(false) {
+        log_uncaptured("skipping handshake_duration due to load-balanced topology");
+        return;
+    }
+
+    let handler = Arc::new(EventHandler::new());
+    let mut subscriber = handler.subscribe();
+    let new_pool = |client_options: &ClientOptions| {
+        let mut options = ConnectionPoolOptions::from_client_options(client_options);
+        options.ready = Some(true);
+        options.cmap_event_handler = Some(handler.clone() as Arc<dyn CmapEventHandler>);
+        ConnectionPool::new(
+            client_options.hosts[0].clone(),
+            Default::default(),
+            TopologyUpdater::channel().0,
+            Some(options),
+        )
+    };
+
+    let pool = new_pool(&client_options);
+    let _connection = pool.check_out().await.unwrap();
+    match subscriber
+        .wait_for_event(EVENT_TIMEOUT, |e| matches!(e, Event::ConnectionReady(_)))
+        .await
+    {
+        Some(Event::ConnectionReady(event)) => {
+            assert!(event.handshake_duration > Duration::ZERO);
+            assert!(event.handshake_duration <= event.duration);
+        }
+        other => panic!("expected a ConnectionReady event, got {:?}", other),
+    }
+
+    // A connection that fails to authenticate reports how long its handshake ran before failing.
+    client_options.credential = Some(
+        Credential::builder()
+            .username("handshake_duration_user".to_string())
+            .password("wrong_password".to_string())
+            .build(),
+    );
+    let pool = new_pool(&client_options);
+    pool.check_out()
+        .await
+        .expect_err("check out should fail to authenticate");
+    match subscriber
+        .wait_for_event(EVENT_TIMEOUT, |e| matches!(e, Event::ConnectionClosed(_)))
+        .await
+    {
+        Some(Event::ConnectionClosed(event)) => {
+            assert_eq!(event.reason, ConnectionClosedReason::Error);
+            assert!(event.handshake_duration.unwrap() > Duration::ZERO);
+        }
+        other => panic!("expected a ConnectionClosed event, got {:?}", other),
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn pool_clear_interrupt_in_use() {
+    let _guard: RwLockWriteGuard<_> = LOCK.run_exclusively().await;
+
+    let mut client_options = CLIENT_OPTIONS.get().await.clone();
+    if client_options.load_balanced.unwrap_or(false) {
+        log_uncaptured(format!(
+            "skipping {} due to load-balanced topology",
+            function_name!()
+        ));
+        return;
+    }
+    client_options.hosts.drain(1..);
+    client_options.direct_connection = Some(true);
+
+    let client = TestClient::with_options(Some(client_options.clone())).await;
+    if !client.supports_block_connection() {
+        log_uncaptured(format!(
+            "skipping {} due to blockConnection not being supported",
+            function_name!()
+        ));
+        return;
+    }
+
+    for interrupt_in_use in [false, true] {
+        let fail_point = FailPoint::fail_command(
+            &["ping"],
+            FailPointMode::Times(1),
+            FailCommandOptions::builder()
+                .block_connection(Duration::from_secs(2))
+                .build(),
+        );
+        let _fp_guard = client.enable_failpoint(fail_point, None).await.unwrap();
+
+        let mut options = ConnectionPoolOptions::from_client_options(&client_options);
+        options.ready = Some(true);
+        options.pool_clear_interrupt_in_use = Some(interrupt_in_use);
+        let pool = ConnectionPool::new(
+            client_options.hosts[0].clone(),
+            Default::default(),
+            TopologyUpdater::channel().0,
+            Some(options),
+        );
+
+        let mut connection = pool.check_out().await.unwrap();
+        let mut cmd = Command::new("ping".to_string(), "admin".to_string(), doc! { "ping": 1 });
+        if let Some(server_api) = client_options.server_api.as_ref() {
+            cmd.set_server_api(server_api);
+        }
+        let start = Instant::now();
+        let task = runtime::spawn(async move { connection.send_command(cmd, None).await });
+
+        // Clear the pool while the healthy command is still in progress.
+        runtime::delay_for(Duration::from_millis(500)).await;
+        pool.clear(
+            ErrorKind::Internal {
+                message: "test".to_string(),
+            }
+            .into(),
+            None,
+        )
+        .await;
+
+        let result = task.await;
+        if interrupt_in_use {
+            let error = result.expect_err("in-use connection should have been interrupted");
+            assert!(error.is_network_error(), "{:?}", error);
+            assert!(start.elapsed() < Duration::from_secs(2));
+        } else {
+            result.expect("in-use connection should not have been interrupted");
+            assert!(start.elapsed() >= Duration::from_secs(2));
+        }
+    }
+}
@@ -16,7 +16,12 @@ use super::{
     manager::{ConnectionSucceeded, ManagementRequestReceiver, PoolManagementRequest, PoolManager},
     options::{ConnectionOptions, ConnectionPoolOptions},
     status,
-    status::{PoolGenerationPublisher, PoolGenerationSubscriber},
+    status::{
+        PoolGenerationPublisher,
+        PoolGenerationSubscriber,
+        PoolStatsPublisher,
+        PoolStatsSubscriber,
+    },
     Connection,
     DEFAULT_MAX_POOL_SIZE,
 };
@@ -30,6 +35,8 @@ use crate::{
         PoolClearedEvent,
         PoolClosedEvent,
         PoolReadyEvent,
+        PoolSaturatedEvent,
+        PoolStats,
     },
     options::ServerAddress,
     runtime::{self, WorkerHandleListener},
@@ -39,10 +46,10 @@ use crate::{
 use std::{
     collections::{HashMap, VecDeque},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-const MAX_CONNECTING: u32 = 2;
+const DEFAULT_MAX_CONNECTING: u32 = 2;
 const MAINTENACE_FREQUENCY: Duration = Duration::from_millis(500);
 
 /// A worker task that manages the shared state of the pool.
@@ -63,6 +70,16 @@ pub(crate) struct ConnectionPoolWorker {
     /// The number of connections currently being established by this pool.
     pending_connection_count: u32,
 
+    /// The maximum number of connections that may be established by this pool concurrently.
+    max_connecting: u32,
+
+    /// When the pool started establishing `max_connecting` connections with checkouts waiting,
+    /// if it currently is.
+    saturated_since: Option<Instant>,
+
+    /// The most checkouts that have been waiting at once since the pool became saturated.
+    saturated_max_waiting: u32,
+
     /// The ID of the next connection created by the pool.
     next_connection_id: u32,
 
@@ -127,6 +144,9 @@ pub(crate) struct ConnectionPoolWorker {
     /// encountered by the pool.
     generation_publisher: PoolGenerationPublisher,
 
+    /// Sender used to publish the latest statistics of the pool.
+    stats_publisher: PoolStatsPublisher,
+
     /// A pool manager that can be cloned and attached to connections checked out of the pool.
     manager: PoolManager,
 
@@ -144,7 +164,12 @@ impl ConnectionPoolWorker {
         http_client: runtime::HttpClient,
         server_updater: TopologyUpdater,
         options: Option<ConnectionPoolOptions>,
-    ) -> (PoolManager, ConnectionRequester, PoolGenerationSubscriber) {
+    ) -> (
+        PoolManager,
+        ConnectionRequester,
+        PoolGenerationSubscriber,
+        PoolStatsSubscriber,
+    ) {
         let establisher = ConnectionEstablisher::new(http_client, options.as_ref());
         let event_handler = options
             .as_ref()
@@ -164,7 +189,12 @@ impl ConnectionPoolWorker {
 
         let min_pool_size = options.as_ref().and_then(|opts| opts.min_pool_size);
 
-        let connection_options: Option<ConnectionOptions> = options
+        let max_connecting = options
+            .as_ref()
+            .and_then(|opts| opts.max_connecting)
+            .unwrap_or(DEFAULT_MAX_CONNECTING);
+
+        let mut connection_options: Option<ConnectionOptions> = options
             .as_ref()
             .map(|pool_options| ConnectionOptions::from(pool_options.clone()));
 
@@ -182,6 +212,14 @@ impl ConnectionPoolWorker {
             PoolGeneration::normal()
         };
         let (generation_publisher, generation_subscriber) = status::channel(generation.clone());
+        let interrupt_in_use = options
+            .as_ref()
+            .and_then(|opts| opts.pool_clear_interrupt_in_use)
+            .unwrap_or(false);
+        if let Some(connection_options) = connection_options.as_mut().filter(|_| interrupt_in_use) {
+            connection_options.interrupt_subscriber = Some(generation_subscriber.clone());
+        }
+        let (stats_publisher, stats_subscriber) = status::stats_channel();
 
         #[cfg(test)]
         let mut state = if options
@@ -203,10 +241,17 @@ impl ConnectionPoolWorker {
                 BackgroundThreadInterval::Never => Duration::from_secs(31_556_952),
                 BackgroundThreadInterval::Every(d) => d,
             })
+            .or_else(|| options.as_ref().and_then(|opts| opts.maintenance_interval))
             .unwrap_or(MAINTENACE_FREQUENCY);
 
         #[cfg(not(test))]
-        let (mut state, maintenance_frequency) = (PoolState::New, MAINTENACE_FREQUENCY);
+        let (mut state, maintenance_frequency) = (
+            PoolState::New,
+            options
+                .as_ref()
+                .and_then(|opts| opts.maintenance_interval)
+                .unwrap_or(MAINTENACE_FREQUENCY),
+        );
 
         if is_load_balanced {
             // Because load balancer servers don't have a monitoring connection, the associated
@@ -223,6 +268,9 @@ impl ConnectionPoolWorker {
             next_connection_id: 1,
             total_connection_count: 0,
             pending_connection_count: 0,
+            max_connecting,
+            saturated_since: None,
+            saturated_max_waiting: 0,
             generation,
             service_connection_count: HashMap::new(),
             connection_options,
@@ -235,6 +283,7 @@ impl ConnectionPoolWorker {
             handle_listener,
             state,
             generation_publisher,
+            stats_publisher,
             maintenance_frequency,
             server_updater,
         };
@@ -243,7 +292,12 @@ impl ConnectionPoolWorker {
             worker.execute().await;
         });
 
-        (manager, connection_requester, generation_subscriber)
+        (
+            manager,
+            connection_requester,
+            generation_subscriber,
+            stats_subscriber,
+        )
     }
 
     /// Run the worker thread, listening on the various receivers until all handles have been
@@ -325,6 +379,8 @@ impl ConnectionPoolWorker {
                     self.check_out(request).await;
                 }
             }
+
+            self.update_stats();
         }
 
         while let Some(connection) = self.available_connections.pop_front() {
@@ -351,7 +407,43 @@ impl ConnectionPoolWorker {
             return true;
         }
 
-        self.below_max_connections() && self.pending_connection_count < MAX_CONNECTING
+        self.below_max_connections() && self.pending_connection_count < self.max_connecting
+    }
+
+    /// Publishes the current statistics of the pool, tracking whether its connection establishment
+    /// is saturated and emitting a `PoolSaturatedEvent` once it no longer is.
+    fn update_stats(&mut self) {
+        let waiting = self.wait_queue.len() as u32;
+        let saturated = self.pending_connection_count >= self.max_connecting && waiting > 0;
+        match self.saturated_since {
+            None if saturated => {
+                self.saturated_since = Some(Instant::now());
+                self.saturated_max_waiting = waiting;
+            }
+            Some(_) if saturated => {
+                self.saturated_max_waiting = self.saturated_max_waiting.max(waiting);
+            }
+            Some(since) => {
+                self.saturated_since = None;
+                self.emit_event(|handler| {
+                    handler.handle_pool_saturated_event(PoolSaturatedEvent {
+                        address: self.address.clone(),
+                        duration: since.elapsed(),
+                        max_connecting: self.max_connecting,
+                        max_waiting: self.saturated_max_waiting,
+                    });
+                });
+            }
+            None => {}
+        }
+
+        self.stats_publisher.publish(PoolStats {
+            total: self.total_connection_count,
+            available: self.available_connections.len() as u32,
+            connecting: self.pending_connection_count,
+            waiting,
+            saturated_since: self.saturated_since,
+        });
     }
 
     async fn check_out(&mut self, request: ConnectionRequest) {
@@ -594,7 +686,7 @@ impl ConnectionPoolWorker {
     fn ensure_min_connections(&mut self) {
         if let Some(min_pool_size) = self.min_pool_size {
             while self.total_connection_count < min_pool_size
-                && self.pending_connection_count < MAX_CONNECTING
+                && self.pending_connection_count < self.max_connecting
             {
                 let pending_connection = self.create_pending_connection();
                 let event_handler = self.event_handler.clone();
@@ -635,6 +727,7 @@ async fn establish_connection(
     let connection_id = pending_connection.id;
     let address = pending_connection.address.clone();
 
+    let start = Instant::now();
     let mut establish_result = establisher.establish_connection(pending_connection).await;
 
     match establish_result {
@@ -651,6 +744,7 @@ async fn establish_connection(
                     address,
                     reason: ConnectionClosedReason::Error,
                     connection_id,
+                    handshake_duration: e.handshake_duration,
                 };
                 handler.handle_connection_closed_event(event);
             }
@@ -658,7 +752,7 @@ async fn establish_connection(
         }
         Ok(ref mut connection) => {
             if let Some(handler) = event_handler {
-                handler.handle_connection_ready_event(connection.ready_event())
+                handler.handle_connection_ready_event(connection.ready_event(start.elapsed()))
             };
         }
     }
@@ -0,0 +1,263 @@
+use crate::bson::{doc, Bson, Document};
+
+/// A builder for query filters, as an alternative to writing the filter document by hand with the
+/// `doc!` macro. A `Filter` can be passed anywhere a filter `Document` is accepted, e.g.
+/// [`Collection::find`](crate::Collection::find).
+///
+/// ```rust
+/// # use mongodb::{bson::{doc, Document}, Filter};
+/// let filter: Document = Filter::new()
+///     .eq("status", "active")
+///     .gt("n", 5)
+///     .in_("kind", ["a", "b"])
+///     .into();
+/// assert_eq!(
+///     filter,
+///     doc! { "status": "active", "n": { "$gt": 5 }, "kind": { "$in": ["a", "b"] } },
+/// );
+/// ```
+///
+/// Conditions on the same field are combined rather than overwriting each other: operators on a
+/// field are merged into one operator document where possible, and any condition that would
+/// conflict with an existing one (e.g. two equality matches on the same field, or a second `$or`
+/// or `$expr`) is added to a top-level `$and` instead.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    doc: Document,
+}
+
+impl Filter {
+    /// Creates an empty filter, which matches every document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches documents where `field` equals `value`.
+    ///
+    /// Document values are matched literally via `$eq` so that they are not interpreted as
+    /// query operators.
+    pub fn eq(mut self, field: impl AsRef<str>, value: impl Into<Bson>) -> Self {
+        let field = field.as_ref();
+        let value = match value.into() {
+            Bson::Document(value) => Bson::Document(doc! { "$eq": value }),
+            value => value,
+        };
+        if self.doc.contains_key(field) {
+            self.push_and(doc! { field: value });
+        } else {
+            self.doc.insert(field, value);
+        }
+        self
+    }
+
+    /// Matches documents where `field` does not equal `value`.
+    pub fn ne(self, field: impl AsRef<str>, value: impl Into<Bson>) -> Self {
+        self.operator(field.as_ref(), "$ne", value.into())
+    }
+
+    /// Matches documents where `field` is greater than `value`.
+    pub fn gt(self, field: impl AsRef<str>, value: impl Into<Bson>) -> Self {
+        self.operator(field.as_ref(), "$gt", value.into())
+    }
+
+    /// Matches documents where `field` is greater than or equal to `value`.
+    pub fn gte(self, field: impl AsRef<str>, value: impl Into<Bson>) -> Self {
+        self.operator(field.as_ref(), "$gte", value.into())
+    }
+
+    /// Matches documents where `field` is less than `value`.
+    pub fn lt(self, field: impl AsRef<str>, value: impl Into<Bson>) -> Self {
+        self.operator(field.as_ref(), "$lt", value.into())
+    }
+
+    /// Matches documents where `field` is less than or equal to `value`.
+    pub fn lte(self, field: impl AsRef<str>, value: impl Into<Bson>) -> Self {
+        self.operator(field.as_ref(), "$lte", value.into())
+    }
+
+    /// Matches documents where `field` equals any of `values`.
+    pub fn in_(
+        self,
+        field: impl AsRef<str>,
+        values: impl IntoIterator<Item = impl Into<Bson>>,
+    ) -> Self {
+        let values: Vec<Bson> = values.into_iter().map(Into::into).collect();
+        self.operator(field.as_ref(), "$in", values.into())
+    }
+
+    /// Matches documents where `field` equals none of `values`.
+    pub fn nin(
+        self,
+        field: impl AsRef<str>,
+        values: impl IntoIterator<Item = impl Into<Bson>>,
+    ) -> Self {
+        let values: Vec<Bson> = values.into_iter().map(Into::into).collect();
+        self.operator(field.as_ref(), "$nin", values.into())
+    }
+
+    /// Matches documents that contain `field` if `exists` is true, or that do not contain it
+    /// otherwise.
+    pub fn exists(self, field: impl AsRef<str>, exists: bool) -> Self {
+        self.operator(field.as_ref(), "$exists", exists.into())
+    }
+
+    /// Matches documents that match all of `filters` in addition to the conditions already in
+    /// this filter.
+    pub fn and(mut self, filters: impl IntoIterator<Item = Filter>) -> Self {
+        for filter in filters {
+            self.push_and(filter.doc);
+        }
+        self
+    }
+
+    /// Matches documents that match at least one of `filters` in addition to the conditions
+    /// already in this filter. `filters` must not be empty.
+    pub fn or(self, filters: impl IntoIterator<Item = Filter>) -> Self {
+        let clauses: Vec<Document> = filters.into_iter().map(|f| f.doc).collect();
+        self.top_level("$or", clauses.into())
+    }
+
+    /// Matches documents for which the aggregation expression `expression` evaluates to true, e.g.
+    /// `doc! { "$gt": ["$a", "$b"] }` to compare two fields of the same document.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/operator/query/expr/)
+    /// for more information on `$expr`.
+    pub fn expr(self, expression: impl Into<Bson>) -> Self {
+        self.top_level("$expr", expression.into())
+    }
+
+    /// Matches documents that satisfy the JSON schema `schema`.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/operator/query/jsonSchema/)
+    /// for more information on `$jsonSchema`.
+    pub fn json_schema(self, schema: Document) -> Self {
+        self.top_level("$jsonSchema", schema.into())
+    }
+
+    /// Returns the filter document.
+    pub fn into_document(self) -> Document {
+        self.doc
+    }
+
+    fn operator(mut self, field: &str, operator: &str, value: Bson) -> Self {
+        match self.doc.get_mut(field) {
+            None => {
+                self.doc.insert(field, doc! { operator: value });
+            }
+            Some(Bson::Document(operators))
+                if operators.keys().all(|k| k.starts_with('$'))
+                    && !operators.contains_key(operator) =>
+            {
+                operators.insert(operator, value);
+            }
+            Some(_) => self.push_and(doc! { field: { operator: value } }),
+        }
+        self
+    }
+
+    fn top_level(mut self, operator: &str, value: Bson) -> Self {
+        if self.doc.contains_key(operator) {
+            self.push_and(doc! { operator: value });
+        } else {
+            self.doc.insert(operator, value);
+        }
+        self
+    }
+
+    fn push_and(&mut self, clause: Document) {
+        match self.doc.get_mut("$and") {
+            Some(Bson::Array(clauses)) => clauses.push(clause.into()),
+            _ => {
+                self.doc.insert("$and", vec![clause]);
+            }
+        }
+    }
+}
+
+impl From<Filter> for Document {
+    fn from(filter: Filter) -> Self {
+        filter.doc
+    }
+}
+
+impl From<Filter> for Option<Document> {
+    fn from(filter: Filter) -> Self {
+        Some(filter.doc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Filter;
+    use crate::bson::{doc, Document};
+
+    #[test]
+    fn merges_operators_on_same_field() {
+        let filter: Document = Filter::new().gt("n", 1).lte("n", 5).ne("n", 3).into();
+        assert_eq!(filter, doc! { "n": { "$gt": 1, "$lte": 5, "$ne": 3 } });
+    }
+
+    #[test]
+    fn conflicting_conditions_use_and() {
+        let filter: Document = Filter::new()
+            .eq("a", 1)
+            .eq("a", 2)
+            .gt("b", 1)
+            .gt("b", 2)
+            .gt("a", 0)
+            .into();
+        assert_eq!(
+            filter,
+            doc! {
+                "a": 1,
+                "b": { "$gt": 1 },
+                "$and": [{ "a": 2 }, { "b": { "$gt": 2 } }, { "a": { "$gt": 0 } }],
+            }
+        );
+    }
+
+    #[test]
+    fn eq_document_is_literal() {
+        let filter: Document = Filter::new().eq("a", doc! { "$gt": 1 }).into();
+        assert_eq!(filter, doc! { "a": { "$eq": { "$gt": 1 } } });
+    }
+
+    #[test]
+    fn expr_and_json_schema() {
+        let schema = doc! { "required": ["a", "b"] };
+        let filter: Document = Filter::new()
+            .expr(doc! { "$gt": ["$a", "$b"] })
+            .json_schema(schema.clone())
+            .expr(doc! { "$ne": ["$a", 0] })
+            .into();
+        assert_eq!(
+            filter,
+            doc! {
+                "$expr": { "$gt": ["$a", "$b"] },
+                "$jsonSchema": schema,
+                "$and": [{ "$expr": { "$ne": ["$a", 0] } }],
+            }
+        );
+    }
+
+    #[test]
+    fn or_with_and() {
+        let filter: Document = Filter::new()
+            .eq("status", "active")
+            .or([Filter::new().lt("n", 0), Filter::new().gt("n", 10)])
+            .and([Filter::new().exists("x", true)])
+            .or([Filter::new().eq("y", 1), Filter::new().eq("z", 1)])
+            .into();
+        assert_eq!(
+            filter,
+            doc! {
+                "status": "active",
+                "$or": [{ "n": { "$lt": 0 } }, { "n": { "$gt": 10 } }],
+                "$and": [
+                    { "x": { "$exists": true } },
+                    { "$or": [{ "y": 1 }, { "z": 1 }] },
+                ],
+            }
+        );
+    }
+}
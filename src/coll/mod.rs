@@ -1,10 +1,18 @@
+mod filter;
 pub mod options;
 
-use std::{borrow::Borrow, collections::HashSet, fmt, fmt::Debug, sync::Arc};
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    fmt,
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures_util::{
     future,
-    stream::{StreamExt, TryStreamExt},
+    stream::{Stream, StreamExt, TryStreamExt},
 };
 use serde::{
     de::{DeserializeOwned, Error as DeError},
@@ -13,9 +21,10 @@ use serde::{
     Serialize,
 };
 
+pub use self::filter::Filter;
 use self::options::*;
 use crate::{
-    bson::{doc, to_document, Bson, Document},
+    bson::{doc, to_document, to_raw_document_buf, Bson, Document, RawDocumentBuf, Uuid},
     bson_util,
     change_stream::{
         event::ChangeStreamEvent,
@@ -23,13 +32,24 @@ use crate::{
         session::SessionChangeStream,
         ChangeStream,
     },
-    client::options::ServerAddress,
+    client::options::{ServerAddress, ServerApi, SessionOptions},
     cmap::conn::PinnedConnectionHandle,
-    concern::{ReadConcern, WriteConcern},
-    error::{convert_bulk_errors, BulkWriteError, BulkWriteFailure, Error, ErrorKind, Result},
+    concern::{Acknowledgment, ReadConcern, WriteConcern},
+    error::{
+        convert_bulk_errors,
+        BulkWriteFailure,
+        Error,
+        ErrorKind,
+        FindManyAndDeleteError,
+        InsertStreamError,
+        Result,
+        DUPLICATE_KEY_CODE,
+    },
     index::IndexModel,
     operation::{
         Aggregate,
+        CollStats,
+        Compact,
         Count,
         CountDocuments,
         CreateIndexes,
@@ -41,17 +61,25 @@ use crate::{
         FindAndModify,
         Insert,
         ListIndexes,
+        ReIndex,
         Update,
+        Validate,
     },
     results::{
+        BulkUpsertResult,
+        CollectionSpecification,
+        CollectionStats,
         CreateIndexResult,
         CreateIndexesResult,
         DeleteResult,
         InsertManyResult,
         InsertOneResult,
+        ShardDocument,
         UpdateResult,
+        ValidationResult,
     },
-    selection_criteria::SelectionCriteria,
+    runtime,
+    selection_criteria::{ReadPreference, SelectionCriteria},
     Client,
     ClientSession,
     Cursor,
@@ -59,6 +87,27 @@ use crate::{
     SessionCursor,
 };
 
+/// The approximate size of each batch returned by a cursor created via
+/// [`Collection::find_streaming`].
+const STREAMING_BATCH_TARGET_BYTES: usize = 4 * 1024 * 1024;
+
+/// The number of `_id`s sampled per requested cursor by [`Collection::parallel_scan`] when
+/// `samples_per_cursor` isn't specified.
+const DEFAULT_PARALLEL_SCAN_SAMPLES_PER_CURSOR: u32 = 10;
+
+/// The maximum number of documents read from the input of [`Collection::insert_stream`] before
+/// they're inserted, matching the server's default `maxWriteBatchSize`.
+const INSERT_STREAM_MAX_BATCH_LEN: usize = 100_000;
+
+/// The maximum total size of the documents read from the input of [`Collection::insert_stream`]
+/// before they're inserted, matching the server's `maxBsonObjectSize`.
+const INSERT_STREAM_MAX_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// The maximum total size of the `_id`s in the `$in` filter of each `delete` command sent by
+/// [`Collection::delete_many_by_ids`]. This is below the server's `maxBsonObjectSize` to leave
+/// room for the rest of the command.
+const DELETE_BY_IDS_MAX_BATCH_BYTES: usize = 12 * 1024 * 1024;
+
 /// `Collection` is the client-side abstraction of a MongoDB Collection. It can be used to
 /// perform collection-level operations such as CRUD operations. A `Collection` can be obtained
 /// through a [`Database`](struct.Database.html) by calling either
@@ -160,7 +209,10 @@ impl<T> Collection<T> {
 
         Self {
             inner: Arc::new(CollectionInner {
-                client: db.client().clone(),
+                client: match options.server_api {
+                    Some(server_api) => db.client().with_server_api(server_api),
+                    None => db.client().clone(),
+                },
                 db,
                 name: name.to_string(),
                 selection_criteria,
@@ -179,6 +231,48 @@ impl<T> Collection<T> {
         }
     }
 
+    /// Gets a handle to the same collection that uses `read_preference` for its read operations
+    /// instead of the collection's configured selection criteria. The read concern and write
+    /// concern are unchanged.
+    ///
+    /// This avoids passing `selection_criteria` to each operation in a group of reads that should
+    /// be routed the same way. Operations run in a transaction still use the transaction's read
+    /// preference, which must be primary.
+    pub fn with_read_preference(&self, read_preference: ReadPreference) -> Collection<T> {
+        Collection {
+            inner: Arc::new(CollectionInner {
+                client: self.inner.client.clone(),
+                db: self.inner.db.clone(),
+                name: self.inner.name.clone(),
+                selection_criteria: Some(SelectionCriteria::ReadPreference(read_preference)),
+                read_concern: self.inner.read_concern.clone(),
+                write_concern: self.inner.write_concern.clone(),
+            }),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Gets a handle to the same collection that uses `write_concern` for its write operations
+    /// instead of the collection's configured write concern. The selection criteria and read
+    /// concern are unchanged.
+    ///
+    /// Operations run in a transaction still use the transaction's write concern. If
+    /// `write_concern` is unacknowledged, writes through the returned handle are not retried and
+    /// cannot be run with an explicit session.
+    pub fn with_write_concern(&self, write_concern: WriteConcern) -> Collection<T> {
+        Collection {
+            inner: Arc::new(CollectionInner {
+                client: self.inner.client.clone(),
+                db: self.inner.db.clone(),
+                name: self.inner.name.clone(),
+                selection_criteria: self.inner.selection_criteria.clone(),
+                read_concern: self.inner.read_concern.clone(),
+                write_concern: Some(write_concern),
+            }),
+            _phantom: Default::default(),
+        }
+    }
+
     /// Get the `Client` that this collection descended from.
     fn client(&self) -> &Client {
         &self.inner.client
@@ -217,6 +311,87 @@ impl<T> Collection<T> {
         self.inner.write_concern.as_ref()
     }
 
+    /// Resolves the read concern that a read operation on this `Collection` would use, given the
+    /// read concern set in the operation's options (if any) and the session it is run with (if
+    /// any). `None` means that no read concern is sent, so the server's default applies.
+    ///
+    /// The collection's read concern is already inherited from its database and client, so the
+    /// precedence is:
+    /// 1. the transaction's read concern, if `session` is in a transaction. Operations in a
+    ///    transaction may not set their own read concern.
+    /// 2. a snapshot read concern, if `session` is a snapshot session.
+    /// 3. `operation_read_concern`.
+    /// 4. the collection's read concern.
+    pub fn effective_read_concern(
+        &self,
+        operation_read_concern: Option<&ReadConcern>,
+        session: Option<&ClientSession>,
+    ) -> Option<ReadConcern> {
+        if let Some(session) = session {
+            if session.in_transaction() {
+                return session
+                    .transaction
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.read_concern.clone());
+            }
+            if session
+                .options()
+                .and_then(|options| options.snapshot)
+                .unwrap_or(false)
+            {
+                return Some(ReadConcern::snapshot());
+            }
+        }
+        operation_read_concern
+            .or_else(|| self.read_concern())
+            .cloned()
+    }
+
+    /// Resolves the write concern that a write operation on this `Collection` would use, given the
+    /// write concern set in the operation's options (if any) and the session it is run with (if
+    /// any). `None` means that no write concern is sent, so the server's default applies.
+    ///
+    /// The collection's write concern is already inherited from its database and client, so the
+    /// precedence is:
+    /// 1. the transaction's write concern, if `session` is in a transaction. This is applied when
+    ///    the transaction is committed, and operations in a transaction may not set their own write
+    ///    concern.
+    /// 2. `operation_write_concern`.
+    /// 3. the collection's write concern.
+    ///
+    /// Outside of a transaction, the client's default `w_timeout` is added to an acknowledged
+    /// write concern that specifies `w` but no `w_timeout`.
+    pub fn effective_write_concern(
+        &self,
+        operation_write_concern: Option<&WriteConcern>,
+        session: Option<&ClientSession>,
+    ) -> Option<WriteConcern> {
+        if let Some(session) = session {
+            if session.in_transaction() {
+                return session
+                    .transaction
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.write_concern.clone());
+            }
+        }
+        let mut write_concern = operation_write_concern
+            .or_else(|| self.write_concern())
+            .cloned();
+        if let (Some(w_timeout), Some(write_concern)) =
+            (self.client().default_w_timeout(), write_concern.as_mut())
+        {
+            write_concern.apply_default_w_timeout(w_timeout);
+        }
+        write_concern
+    }
+
+    /// Gets the stable server API declared on the `Collection`'s commands.
+    pub fn server_api(&self) -> Option<&ServerApi> {
+        self.client().server_api()
+    }
+
     #[allow(clippy::needless_option_as_deref)]
     async fn drop_common(
         &self,
@@ -357,6 +532,48 @@ impl<T> Collection<T> {
             .await
     }
 
+    /// Runs an aggregation operation that is expected to produce at most one document (e.g. a
+    /// `$group` stage with a fixed `_id`), deserializing the result as `R`. `Ok(None)` is returned
+    /// if the pipeline produces no documents.
+    ///
+    /// If the pipeline produces more than one document, only the first one is returned and the
+    /// rest are discarded. Add a `$limit` stage to the pipeline to avoid retrieving documents that
+    /// will not be used.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
+    /// information on aggregations.
+    pub async fn aggregate_one<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let mut cursor = self.aggregate(pipeline, options).await?.with_type::<R>();
+        cursor.try_next().await
+    }
+
+    /// Runs an aggregation operation that is expected to produce at most one document using the
+    /// provided `ClientSession`, deserializing the result as `R`. See
+    /// [`Collection::aggregate_one`] for more details.
+    pub async fn aggregate_one_with_session<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let mut cursor = self
+            .aggregate_with_session(pipeline, options, session)
+            .await?
+            .with_type::<R>();
+        let mut cursor = cursor.stream(session);
+        cursor.try_next().await
+    }
+
     /// Estimates the number of documents in the collection using collection metadata.
     ///
     /// Due to an oversight in versions 5.0.0 - 5.0.7 of MongoDB, the `count` server command,
@@ -380,6 +597,72 @@ impl<T> Collection<T> {
         self.client().execute_operation(op, None).await
     }
 
+    /// Checks the collection's data and indexes for correctness via the `validate` command and
+    /// returns the results. Note that unless the `background` option is specified, this blocks
+    /// writes to the collection while it runs.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/validate/)
+    /// for more information.
+    pub async fn validate(
+        &self,
+        options: impl Into<Option<ValidateOptions>>,
+    ) -> Result<ValidationResult> {
+        let mut options = options.into();
+        resolve_options!(self, options, [selection_criteria]);
+
+        let op = Validate::new(self.namespace(), options);
+        self.client().execute_operation(op, None).await
+    }
+
+    /// Gets storage statistics for the collection via the `collStats` command.
+    ///
+    /// If a `scale` is given in `options`, every size in the result is divided by it, including
+    /// the average document size which the server itself always reports in bytes.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/collStats/)
+    /// for more information.
+    pub async fn stats(
+        &self,
+        options: impl Into<Option<CollectionStatsOptions>>,
+    ) -> Result<CollectionStats> {
+        let mut options = options.into();
+        resolve_options!(self, options, [selection_criteria]);
+
+        let op = CollStats::new(self.namespace(), options);
+        self.client().execute_operation(op, None).await
+    }
+
+    /// Rewrites and defragments the collection's data and indexes via the `compact` command to
+    /// release unused disk space to the operating system, returning the number of bytes freed if
+    /// the server reports it (MongoDB 4.4+).
+    ///
+    /// `compact` is not supported by mongos, so to compact a sharded collection, this must be
+    /// called using a client connected directly to each shard.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/compact/)
+    /// for more information.
+    pub async fn compact(&self, options: impl Into<Option<CompactOptions>>) -> Result<Option<u64>> {
+        let op = Compact::new(self.namespace(), options.into());
+        self.client().execute_operation(op, None).await
+    }
+
+    /// Drops and rebuilds all of the indexes on the collection, including the `_id` index, via the
+    /// `reIndex` command, returning the number of indexes that were rebuilt.
+    ///
+    /// `reIndex` is not supported by mongos or by replica set members running MongoDB 5.0+; an
+    /// [`ErrorKind::InvalidArgument`] error is returned without contacting the server if one of
+    /// those is selected. To rebuild the indexes of a replicated or sharded collection, drop and
+    /// recreate them with
+    /// [`Collection::drop_index`](#method.drop_index) and
+    /// [`Collection::create_index`](#method.create_index) instead.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/reIndex/)
+    /// for more information.
+    pub async fn reindex(&self, options: impl Into<Option<ReIndexOptions>>) -> Result<u64> {
+        let op = ReIndex::new(self.namespace(), options.into());
+        self.client().execute_operation(op, None).await
+    }
+
     async fn count_documents_common(
         &self,
         filter: impl Into<Option<Document>>,
@@ -525,6 +808,81 @@ impl<T> Collection<T> {
         self.delete_many_common(query, options, session).await
     }
 
+    #[allow(clippy::needless_option_as_deref)]
+    async fn delete_many_by_ids_common(
+        &self,
+        ids: impl IntoIterator<Item = Bson>,
+        options: impl Into<Option<DeleteOptions>>,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<DeleteResult> {
+        let mut options = options.into();
+        resolve_write_concern_with_session!(self, options, session.as_ref())?;
+
+        let mut batches = Vec::new();
+        let mut batch = Vec::new();
+        let mut batch_bytes = 0;
+        for id in ids {
+            let id_bytes = to_raw_document_buf(&doc! { "0": id.clone() })?
+                .as_bytes()
+                .len();
+            if !batch.is_empty() && batch_bytes + id_bytes > DELETE_BY_IDS_MAX_BATCH_BYTES {
+                batches.push(std::mem::take(&mut batch));
+                batch_bytes = 0;
+            }
+            batch.push(id);
+            batch_bytes += id_bytes;
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+
+        let mut result = DeleteResult {
+            deleted_count: 0,
+            raw_reply: Document::new(),
+        };
+        for ids in batches {
+            let query = doc! { "_id": { "$in": ids } };
+            let delete = Delete::new(self.namespace(), query, None, options.clone());
+            let batch_result = self
+                .client()
+                .execute_operation(delete, session.as_deref_mut())
+                .await?;
+
+            result.deleted_count += batch_result.deleted_count;
+            result.raw_reply = batch_result.raw_reply;
+        }
+
+        Ok(result)
+    }
+
+    /// Deletes the documents in the collection whose `_id` is one of `ids`. The ids are split
+    /// across as many `delete` commands as needed to keep each `$in` filter within the server's
+    /// document size limit, so any number of ids can be passed. The `deleted_count` of the
+    /// returned [`DeleteResult`] is summed across all of the commands, and its `raw_reply` is the
+    /// reply to the last command sent. No command is sent if `ids` is empty.
+    ///
+    /// If a command fails, no further commands are sent, but the documents deleted by earlier
+    /// commands will already have been deleted.
+    pub async fn delete_many_by_ids(
+        &self,
+        ids: impl IntoIterator<Item = Bson>,
+        options: impl Into<Option<DeleteOptions>>,
+    ) -> Result<DeleteResult> {
+        self.delete_many_by_ids_common(ids, options, None).await
+    }
+
+    /// Deletes the documents in the collection whose `_id` is one of `ids` using the provided
+    /// `ClientSession`. See [`Collection::delete_many_by_ids`] for more details.
+    pub async fn delete_many_by_ids_with_session(
+        &self,
+        ids: impl IntoIterator<Item = Bson>,
+        options: impl Into<Option<DeleteOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<DeleteResult> {
+        self.delete_many_by_ids_common(ids, options, Some(session))
+            .await
+    }
+
     async fn delete_one_common(
         &self,
         query: Document,
@@ -615,6 +973,49 @@ impl<T> Collection<T> {
             .await
     }
 
+    /// Finds the distinct values of the field specified by `field_name` across the collection,
+    /// returning them through a cursor rather than in a single server reply. This is useful when
+    /// the distinct values would not fit in the 16MB reply of a `distinct` command.
+    ///
+    /// The values are found with an aggregation that groups the matching documents by the field,
+    /// so `options` are those of [`Collection::aggregate`]; setting
+    /// [`AggregateOptions::allow_disk_use`] may be necessary when there are many distinct values.
+    /// As with [`Collection::distinct`], the elements of array values are returned individually,
+    /// a dotted `field_name` descends through arrays of embedded documents, and documents without
+    /// the field are ignored. Numeric path components are treated as field names rather than
+    /// array indexes. Unlike [`Collection::distinct`], `null` values
+    /// are not returned, and the values are not returned in any particular order.
+    pub async fn distinct_cursor(
+        &self,
+        field_name: impl AsRef<str>,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Cursor<Bson>> {
+        let pipeline = distinct_pipeline(field_name.as_ref(), filter.into());
+        let mut cursor = self.aggregate(pipeline, options).await?.with_type::<Bson>();
+        cursor.set_values_from_id();
+        Ok(cursor)
+    }
+
+    /// Finds the distinct values of the field specified by `field_name` across the collection
+    /// using the provided `ClientSession`, returning them through a cursor. See
+    /// [`Collection::distinct_cursor`] for more details.
+    pub async fn distinct_cursor_with_session(
+        &self,
+        field_name: impl AsRef<str>,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<SessionCursor<Bson>> {
+        let pipeline = distinct_pipeline(field_name.as_ref(), filter.into());
+        let mut cursor = self
+            .aggregate_with_session(pipeline, options, session)
+            .await?
+            .with_type::<Bson>();
+        cursor.set_values_from_id();
+        Ok(cursor)
+    }
+
     async fn drop_indexes_common(
         &self,
         name: impl Into<Option<&str>>,
@@ -689,9 +1090,19 @@ impl<T> Collection<T> {
         &self,
         options: impl Into<Option<ListIndexesOptions>>,
     ) -> Result<Cursor<IndexModel>> {
-        let list_indexes = ListIndexes::new(self.namespace(), options.into());
+        let options = options.into();
+        let sort_by_name = options
+            .as_ref()
+            .and_then(|o| o.sort_by_name)
+            .unwrap_or(false);
+        let list_indexes = ListIndexes::new(self.namespace(), options);
         let client = self.client();
-        client.execute_cursor_operation(list_indexes).await
+        let cursor = client.execute_cursor_operation(list_indexes).await?;
+        if sort_by_name {
+            cursor.sorted_by_name().await
+        } else {
+            Ok(cursor)
+        }
     }
 
     /// Lists all indexes on this collection using the provided `ClientSession`.
@@ -700,11 +1111,21 @@ impl<T> Collection<T> {
         options: impl Into<Option<ListIndexesOptions>>,
         session: &mut ClientSession,
     ) -> Result<SessionCursor<IndexModel>> {
-        let list_indexes = ListIndexes::new(self.namespace(), options.into());
+        let options = options.into();
+        let sort_by_name = options
+            .as_ref()
+            .and_then(|o| o.sort_by_name)
+            .unwrap_or(false);
+        let list_indexes = ListIndexes::new(self.namespace(), options);
         let client = self.client();
-        client
-            .execute_session_cursor_operation(list_indexes, session)
-            .await
+        let cursor = client
+            .execute_session_cursor_operation(list_indexes, &mut *session)
+            .await?;
+        if sort_by_name {
+            cursor.sorted_by_name(session).await
+        } else {
+            Ok(cursor)
+        }
     }
 
     async fn list_index_names_common(
@@ -732,6 +1153,47 @@ impl<T> Collection<T> {
         self.list_index_names_common(cursor.stream(session)).await
     }
 
+    fn uuid_common(specs: Vec<CollectionSpecification>) -> Result<Option<Uuid>> {
+        let binary = match specs.into_iter().next().and_then(|spec| spec.info.uuid) {
+            Some(binary) => binary,
+            None => return Ok(None),
+        };
+        binary.to_uuid().map(Some).map_err(|e| {
+            ErrorKind::InvalidResponse {
+                message: format!("invalid collection UUID: {}", e),
+            }
+            .into()
+        })
+    }
+
+    /// Gets the UUID of the collection, which can be used to correlate the collection with change
+    /// stream events and oplog entries.
+    ///
+    /// Returns `None` if the collection is a view or does not exist.
+    pub async fn uuid(&self) -> Result<Option<Uuid>> {
+        let specs = self
+            .inner
+            .db
+            .list_collections(doc! { "name": self.name() }, None)
+            .await?
+            .try_collect()
+            .await?;
+        Self::uuid_common(specs)
+    }
+
+    /// Gets the UUID of the collection using the provided `ClientSession`.
+    ///
+    /// Returns `None` if the collection is a view or does not exist.
+    pub async fn uuid_with_session(&self, session: &mut ClientSession) -> Result<Option<Uuid>> {
+        let mut cursor = self
+            .inner
+            .db
+            .list_collections_with_session(doc! { "name": self.name() }, None, session)
+            .await?;
+        let specs = cursor.stream(session).try_collect().await?;
+        Self::uuid_common(specs)
+    }
+
     async fn update_many_common(
         &self,
         query: Document,
@@ -786,6 +1248,93 @@ impl<T> Collection<T> {
             .await
     }
 
+    #[allow(clippy::needless_option_as_deref)]
+    async fn update_many_multi_common(
+        &self,
+        updates: impl IntoIterator<Item = (Document, impl Into<UpdateModifications>)>,
+        options: impl Into<Option<UpdateOptions>>,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<UpdateResult> {
+        let mut options = options.into();
+        resolve_write_concern_with_session!(self, options, session.as_ref())?;
+
+        let mut statements = Vec::new();
+        for (query, update) in updates {
+            let update = update.into();
+            if let UpdateModifications::Document(ref d) = update {
+                bson_util::update_document_check(d)?;
+            }
+            statements.push((query, update));
+        }
+
+        if statements.is_empty() {
+            return Err(ErrorKind::InvalidArgument {
+                message: "No updates provided to update_many_multi".to_string(),
+            }
+            .into());
+        }
+
+        let mut result = UpdateResult {
+            matched_count: 0,
+            modified_count: 0,
+            upserted_id: None,
+            raw_reply: Document::new(),
+        };
+        // Each command sends as many of the remaining statements as the selected server accepts.
+        while !statements.is_empty() {
+            let mut update = Update::new_many(self.namespace(), statements, options.clone());
+            let batch_result = self
+                .client()
+                .execute_operation::<Update>(&mut update, session.as_deref_mut())
+                .await?;
+
+            result.matched_count += batch_result.matched_count;
+            result.modified_count += batch_result.modified_count;
+            if result.upserted_id.is_none() {
+                result.upserted_id = batch_result.upserted_id;
+            }
+            result.raw_reply = batch_result.raw_reply;
+            statements = update.into_unsent();
+        }
+
+        Ok(result)
+    }
+
+    /// Updates all documents matching each filter in `updates` with the corresponding
+    /// modifications, sending the updates to the server in as few `update` commands as possible.
+    /// The matched and modified counts of the returned [`UpdateResult`] are summed across all of
+    /// the updates, so a filter that matches no documents doesn't contribute to them, and its
+    /// `raw_reply` is the reply to the last command sent. If the `upsert` option is set,
+    /// `upserted_id` is the `_id` of the first document that was upserted.
+    ///
+    /// The updates are applied in order, and no further updates are applied after one fails.
+    /// Updates sent in earlier commands than the failing one will already have been applied.
+    ///
+    /// Both `Document` and `Vec<Document>` implement `Into<UpdateModifications>`, so either can be
+    /// used for the modifications. Note: pipeline updates are only supported in MongoDB 4.2+. See
+    /// the official MongoDB
+    /// [documentation](https://www.mongodb.com/docs/manual/reference/command/update/#behavior) for more information on specifying updates.
+    pub async fn update_many_multi(
+        &self,
+        updates: impl IntoIterator<Item = (Document, impl Into<UpdateModifications>)>,
+        options: impl Into<Option<UpdateOptions>>,
+    ) -> Result<UpdateResult> {
+        self.update_many_multi_common(updates, options, None).await
+    }
+
+    /// Updates all documents matching each filter in `updates` with the corresponding
+    /// modifications using the provided `ClientSession`. See [`Collection::update_many_multi`] for
+    /// more details.
+    pub async fn update_many_multi_with_session(
+        &self,
+        updates: impl IntoIterator<Item = (Document, impl Into<UpdateModifications>)>,
+        options: impl Into<Option<UpdateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<UpdateResult> {
+        self.update_many_multi_common(updates, options, Some(session))
+            .await
+    }
+
     async fn update_one_common(
         &self,
         query: Document,
@@ -936,11 +1485,33 @@ impl<T> Collection<T> {
     ) -> Result<Cursor<T>> {
         let mut options = options.into();
         resolve_options!(self, options, [read_concern, selection_criteria]);
+        let remaining = apply_find_deadline(&mut options)?;
 
         let find = Find::new(self.namespace(), filter.into(), options);
         let client = self.client();
 
-        client.execute_cursor_operation(find).await
+        match remaining {
+            Some(remaining) => {
+                runtime::timeout(remaining, client.execute_cursor_operation(find)).await?
+            }
+            None => client.execute_cursor_operation(find).await,
+        }
+    }
+
+    /// Finds the documents in the collection matching `filter`, adjusting the batch size of each
+    /// subsequent request to the server so that every batch is around 4 MiB based on the sizes of
+    /// the documents received so far.
+    ///
+    /// This bounds the memory used by the cursor's buffer when documents are large or vary widely
+    /// in size. If `options` specifies a `batch_size`, it is only used for the first batch.
+    pub async fn find_streaming(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> Result<Cursor<T>> {
+        let mut cursor = self.find(filter, options).await?;
+        cursor.tune_batch_size(STREAMING_BATCH_TARGET_BYTES);
+        Ok(cursor)
     }
 
     /// Finds the documents in the collection matching `filter` using the provided `ClientSession`.
@@ -953,11 +1524,21 @@ impl<T> Collection<T> {
         let mut options = options.into();
         resolve_read_concern_with_session!(self, options, Some(&mut *session))?;
         resolve_selection_criteria_with_session!(self, options, Some(&mut *session))?;
+        let remaining = apply_find_deadline(&mut options)?;
 
         let find = Find::new(self.namespace(), filter.into(), options);
         let client = self.client();
 
-        client.execute_session_cursor_operation(find, session).await
+        match remaining {
+            Some(remaining) => {
+                runtime::timeout(
+                    remaining,
+                    client.execute_session_cursor_operation(find, session),
+                )
+                .await?
+            }
+            None => client.execute_session_cursor_operation(find, session).await,
+        }
     }
 }
 
@@ -998,55 +1579,734 @@ where
         let mut cursor = cursor.stream(session);
         cursor.next().await.transpose()
     }
-}
 
-impl<T> Collection<T>
-where
-    T: DeserializeOwned,
-{
-    async fn find_one_and_delete_common(
+    /// Finds a single document in the collection matching `filter`, checking the primary before
+    /// concluding that no such document exists.
+    ///
+    /// The query is first run with the selection criteria from `options` or, if none is given,
+    /// from the collection. If the deployment is a replica set, that criteria may select a
+    /// secondary and no document is found, the query is run again on the primary to rule out
+    /// replication lag, and its result is returned. This makes lookups of recently written
+    /// documents reliable while still serving most reads from secondaries, without requiring a
+    /// causally consistent session. Otherwise, including against standalones, sharded clusters and
+    /// load balancers, the query is run once.
+    pub async fn find_one_consistent(
         &self,
-        filter: Document,
-        options: impl Into<Option<FindOneAndDeleteOptions>>,
-        session: impl Into<Option<&mut ClientSession>>,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOneOptions>>,
     ) -> Result<Option<T>> {
-        let session = session.into();
-
+        let filter = filter.into();
         let mut options = options.into();
-        resolve_write_concern_with_session!(self, options, session.as_ref())?;
+        resolve_options!(self, options, [read_concern, selection_criteria]);
 
-        let op = FindAndModify::<T>::with_delete(self.namespace(), filter, options);
-        self.client().execute_operation(op, session).await
+        let found = self.find_one(filter.clone(), options.clone()).await?;
+        let is_replica_set = matches!(
+            self.client().topology_type(),
+            crate::sdam::TopologyType::ReplicaSetWithPrimary
+                | crate::sdam::TopologyType::ReplicaSetNoPrimary
+        );
+        let may_read_secondary = is_replica_set
+            && options
+                .as_ref()
+                .and_then(|options| options.selection_criteria.as_ref())
+                .map_or(false, |criteria| !criteria.is_read_pref_primary());
+        if found.is_some() || !may_read_secondary {
+            return Ok(found);
+        }
+
+        let mut options = options.unwrap_or_default();
+        options.selection_criteria =
+            Some(SelectionCriteria::ReadPreference(ReadPreference::Primary));
+        self.find_one(filter, options).await
     }
 
-    /// Atomically finds up to one document in the collection matching `filter` and deletes it.
+    /// Polls the collection until a document matching `filter` is found or `timeout` elapses,
+    /// returning `None` in the latter case. This is useful when waiting for a write made
+    /// elsewhere to become visible, e.g. when reading from a secondary.
     ///
-    /// This operation will retry once upon failure if the connection and encountered error support
-    /// retryability. See the documentation
-    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
-    /// retryable writes.
-    pub async fn find_one_and_delete(
+    /// The first query is run immediately. The delay between queries starts at `poll_interval`
+    /// and doubles after each unsuccessful one, but never extends past the deadline; a final
+    /// query is always run once the deadline is reached, so a document that appears just before
+    /// it is still returned. A `timeout` too large to be represented (e.g. `Duration::MAX`) polls
+    /// without a deadline.
+    pub async fn wait_for(
         &self,
-        filter: Document,
-        options: impl Into<Option<FindOneAndDeleteOptions>>,
+        filter: impl Into<Option<Document>>,
+        timeout: Duration,
+        poll_interval: Duration,
     ) -> Result<Option<T>> {
-        self.find_one_and_delete_common(filter, options, None).await
+        if poll_interval == Duration::ZERO {
+            return Err(ErrorKind::InvalidArgument {
+                message: "poll_interval must be greater than 0".to_string(),
+            }
+            .into());
+        }
+
+        let filter = filter.into();
+        let deadline = Instant::now().checked_add(timeout);
+        let mut interval = poll_interval;
+        loop {
+            if let Some(doc) = self.find_one(filter.clone(), None).await? {
+                return Ok(Some(doc));
+            }
+
+            let delay = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    interval.min(deadline - now)
+                }
+                None => interval,
+            };
+            runtime::delay_for(delay).await;
+            interval = interval.saturating_mul(2);
+        }
     }
 
-    /// Atomically finds up to one document in the collection matching `filter` and deletes it using
-    /// the provided `ClientSession`.
+    /// Finds the documents in the collection matching `filter` in ascending order of the
+    /// `sort_key` field, transparently re-running the query if the server-side cursor is
+    /// killed or times out (i.e. a `CursorNotFound` error occurs). This is useful for scans that
+    /// run for long enough that their cursor may time out between batches.
     ///
-    /// This operation will retry once upon failure if the connection and encountered error support
-    /// retryability. See the documentation
-    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
-    /// retryable writes.
-    pub async fn find_one_and_delete_with_session(
+    /// The query is resumed from the last `sort_key` value returned, skipping documents with that
+    /// value which were already returned based on their `_id`, so every matching document is
+    /// returned exactly once as long as its `sort_key` doesn't change during the scan. For this
+    /// to work, every matching document must have a `sort_key` field of the same type, and any
+    /// projection in `options` must include both `sort_key` and `_id`. `options` must not
+    /// specify `sort`, `skip` or `limit`. `sort_key` may be a dotted path to a field of an
+    /// embedded document (e.g. `"meta.createdAt"`).
+    pub async fn find_resilient(
         &self,
-        filter: Document,
-        options: impl Into<Option<FindOneAndDeleteOptions>>,
-        session: &mut ClientSession,
-    ) -> Result<Option<T>> {
-        self.find_one_and_delete_common(filter, options, session)
+        filter: impl Into<Option<Document>>,
+        sort_key: impl Into<String>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> Result<impl Stream<Item = Result<T>> + Send> {
+        let sort_key = sort_key.into();
+        let mut options = options.into().unwrap_or_default();
+        if options.sort.is_some() || options.skip.is_some() || options.limit.is_some() {
+            return Err(ErrorKind::InvalidArgument {
+                message: "find_resilient does not support the sort, skip or limit options"
+                    .to_string(),
+            }
+            .into());
+        }
+        options.sort = Some(doc! { sort_key.as_str(): 1 });
+
+        let mut state = ResilientFind {
+            coll: self.clone_with_type(),
+            filter: filter.into().unwrap_or_default(),
+            sort_key,
+            options,
+            cursor: None,
+            last_key: None,
+            seen_at_last_key: Vec::new(),
+            resumed: false,
+        };
+        state.cursor = Some(state.query().await?);
+
+        Ok(futures_util::stream::try_unfold(
+            state,
+            ResilientFind::next_item,
+        ))
+    }
+
+    /// Returns a page of at most `page_size` documents from the collection in ascending order of
+    /// the `sort_field` field, along with a token to pass as `after` to get the next page, or
+    /// `None` if this was the last page. Pass `None` as `after` to get the first page.
+    ///
+    /// Unlike paging with `skip`, which makes the server walk past every skipped document, each
+    /// page is found with a range query starting after the last document of the previous page,
+    /// so later pages are as fast to get as the first one as long as there is an index on
+    /// `sort_field` and `_id`. Documents with equal `sort_field` values are ordered by `_id`, so
+    /// no document is skipped or returned twice when such documents span two pages.
+    ///
+    /// `sort_field` may be a dotted path to a field in an embedded document, e.g. `"meta.created"`.
+    ///
+    /// The token contains the `sort_field` value and `_id` of the last document in the page, and
+    /// should otherwise be treated as opaque. Every document in the collection must contain
+    /// `sort_field` and `_id`, and the values of `sort_field` should all have the same type.
+    pub async fn paginate(
+        &self,
+        sort_field: impl AsRef<str>,
+        page_size: u32,
+        after: Option<Bson>,
+    ) -> Result<(Vec<T>, Option<Bson>)> {
+        let sort_field = sort_field.as_ref();
+        if page_size == 0 {
+            return Err(ErrorKind::InvalidArgument {
+                message: "page_size must be greater than 0".to_string(),
+            }
+            .into());
+        }
+
+        let filter = match after {
+            Some(token) => {
+                let (value, id) = match token {
+                    Bson::Document(mut token) => (token.remove("value"), token.remove("_id")),
+                    _ => (None, None),
+                };
+                let (value, id) = match (value, id) {
+                    (Some(value), Some(id)) => (value, id),
+                    _ => {
+                        return Err(ErrorKind::InvalidArgument {
+                            message: "after must be a token returned by paginate".to_string(),
+                        }
+                        .into())
+                    }
+                };
+                if sort_field == "_id" {
+                    Some(doc! { "_id": { "$gt": id } })
+                } else {
+                    Some(doc! {
+                        "$or": [
+                            { sort_field: { "$gt": value.clone() } },
+                            { sort_field: value, "_id": { "$gt": id } },
+                        ]
+                    })
+                }
+            }
+            None => None,
+        };
+        let mut sort = doc! { sort_field: 1 };
+        sort.insert("_id", 1);
+        let options = FindOptions::builder()
+            .sort(sort)
+            .limit(i64::from(page_size))
+            .build();
+
+        let mut cursor = self.find(filter, options).await?;
+        let mut page = Vec::new();
+        let mut last = None;
+        while cursor.advance().await? {
+            let doc = Document::try_from(cursor.current())?;
+            let value = match bson_util::get_dotted(&doc, sort_field) {
+                Some(value) => value.clone(),
+                None => {
+                    return Err(ErrorKind::InvalidArgument {
+                        message: format!(
+                            "paginate requires every document to contain the sort field \"{}\"",
+                            sort_field
+                        ),
+                    }
+                    .into())
+                }
+            };
+            let id = match doc.get("_id") {
+                Some(id) => id.clone(),
+                None => {
+                    return Err(ErrorKind::InvalidArgument {
+                        message: "paginate requires every document to contain an _id".to_string(),
+                    }
+                    .into())
+                }
+            };
+            last = Some((value, id));
+            page.push(cursor.deserialize_current()?);
+        }
+
+        // A short page means there are no more documents after it.
+        let next = match last {
+            Some((value, id)) if page.len() == page_size as usize => {
+                Some(Bson::Document(doc! { "value": value, "_id": id }))
+            }
+            _ => None,
+        };
+        Ok((page, next))
+    }
+
+    /// Finds the documents in the collection matching `filter` on a sharded cluster, annotating
+    /// each with the name of the shard it was read from. This is intended for debugging how data
+    /// is distributed across the shards; a query that only targets one shard yields documents
+    /// annotated with that shard's name.
+    ///
+    /// The shards are found via the `listShards` command and the query is sent directly to each
+    /// of them rather than through mongos, using a separate client for each shard with the same
+    /// options as this one, so the shards must be reachable from the application and, if
+    /// authentication is used, have the same users as the cluster (shard-local users are not
+    /// created by default). The per-shard clients are created on first use and reused by later
+    /// calls; clients for shards that are no longer listed by `listShards` are dropped.
+    ///
+    /// Because mongos is bypassed, the results can include orphaned documents that a shard still
+    /// stores but no longer owns, e.g. during or after a chunk migration, so a document may be
+    /// returned more than once or from a shard that doesn't own it. The shards' results are
+    /// yielded one shard after another and can't be merged into a single order, so `options` must
+    /// not specify `sort`, `skip` or `limit`.
+    ///
+    /// An [`ErrorKind::InvalidArgument`] error is returned if the client isn't connected to a
+    /// sharded cluster or if `options` specifies `sort`, `skip` or `limit`.
+    pub async fn find_with_shard_names(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> Result<impl Stream<Item = Result<ShardDocument<T>>> + Send> {
+        let filter = filter.into();
+        let mut options = options.into();
+        if let Some(ref options) = options {
+            if options.sort.is_some() || options.skip.is_some() || options.limit.is_some() {
+                return Err(ErrorKind::InvalidArgument {
+                    message: "find_with_shard_names does not support the sort, skip or limit \
+                              options"
+                        .to_string(),
+                }
+                .into());
+            }
+        }
+        resolve_options!(self, options, [read_concern, selection_criteria]);
+
+        let response = match self
+            .client()
+            .database("admin")
+            .run_command(doc! { "listShards": 1 }, None)
+            .await
+        {
+            // CommandNotFound
+            Err(error) if error.code() == Some(59) => {
+                return Err(ErrorKind::InvalidArgument {
+                    message: "find_with_shard_names requires a sharded cluster".to_string(),
+                }
+                .into())
+            }
+            result => result?,
+        };
+        let shards = response
+            .get_array("shards")
+            .map_err(|_| ErrorKind::InvalidResponse {
+                message: "Expected \"shards\" array in listShards response".to_string(),
+            })?;
+
+        let mut shard_hosts = Vec::new();
+        for shard in shards {
+            match shard
+                .as_document()
+                .map(|shard| (shard.get_str("_id"), shard.get_str("host")))
+            {
+                Some((Ok(name), Ok(host))) => shard_hosts.push((name.to_string(), host)),
+                _ => {
+                    return Err(ErrorKind::InvalidResponse {
+                        message: format!("invalid shard in listShards response: {}", shard),
+                    }
+                    .into())
+                }
+            };
+        }
+
+        let shard_clients = self
+            .client()
+            .shard_clients(shard_hosts.iter().map(|(_, host)| *host))?;
+        let mut cursors = Vec::new();
+        for ((name, _), shard_client) in shard_hosts.into_iter().zip(shard_clients) {
+            let cursor = shard_client
+                .database(self.inner.db.name())
+                .collection::<T>(self.name())
+                .find(filter.clone(), options.clone())
+                .await?;
+            cursors.push((name, cursor));
+        }
+
+        Ok(
+            futures_util::stream::iter(cursors).flat_map(|(shard, cursor)| {
+                cursor.map(move |result| {
+                    result.map(|document| ShardDocument {
+                        shard: shard.clone(),
+                        document,
+                    })
+                })
+            }),
+        )
+    }
+
+    /// Splits the documents matching the filter in `options` into at most `num_cursors`
+    /// disjoint ranges of `_id` and returns a cursor for each, so that the collection can be read
+    /// in parallel. Every matching document is returned by exactly one of the cursors. This
+    /// emulates the `parallelCollectionScan` command, which was removed in MongoDB 4.2.
+    ///
+    /// The range boundaries are picked from a random sample of `_id`s taken with `$sample`, so
+    /// they work for any type of `_id`, including collections with `_id`s of mixed types, but the
+    /// ranges are only approximately equal in size. Fewer than `num_cursors` cursors are returned
+    /// if the sample does not contain enough distinct `_id`s, e.g. for a small or empty
+    /// collection. Each cursor scans its range of the `_id` index using `min` and `max`.
+    ///
+    /// An [`ErrorKind::InvalidArgument`] error is returned if `num_cursors` is 0.
+    pub async fn parallel_scan(
+        &self,
+        num_cursors: usize,
+        options: impl Into<Option<ParallelScanOptions>>,
+    ) -> Result<Vec<Cursor<T>>> {
+        if num_cursors == 0 {
+            return Err(ErrorKind::InvalidArgument {
+                message: "num_cursors must be greater than 0".to_string(),
+            }
+            .into());
+        }
+        let options = options.into().unwrap_or_default();
+
+        let boundaries = if num_cursors > 1 {
+            self.parallel_scan_boundaries(num_cursors, &options).await?
+        } else {
+            Vec::new()
+        };
+
+        let mut cursors = Vec::with_capacity(boundaries.len() + 1);
+        let mut lower: Option<Bson> = None;
+        for upper in boundaries
+            .into_iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+        {
+            let find_options = FindOptions::builder()
+                .hint(Hint::Keys(doc! { "_id": 1 }))
+                .min(lower.take().map(|id| doc! { "_id": id }))
+                .max(upper.clone().map(|id| doc! { "_id": id }))
+                .projection(options.projection.clone())
+                .batch_size(options.batch_size)
+                .read_concern(options.read_concern.clone())
+                .selection_criteria(options.selection_criteria.clone())
+                .build();
+            cursors.push(self.find(options.filter.clone(), find_options).await?);
+            lower = upper;
+        }
+        Ok(cursors)
+    }
+
+    /// Exports the documents in the collection as they were at a single point in time, even if
+    /// the collection is written to while the export is in progress. This is intended for backup
+    /// and export tools that need a consistent view of a whole collection.
+    ///
+    /// The export is read with a snapshot read concern in a new snapshot session, which the
+    /// returned cursor uses for all of its batches. The server only keeps the history needed for
+    /// the snapshot for a limited time (`minSnapshotHistoryWindowInSeconds`, 300 seconds by
+    /// default), so an export that runs for longer than that can fail partway through with an
+    /// error for which [`Error::is_snapshot_too_old`](crate::error::Error::is_snapshot_too_old)
+    /// returns true, in which case the export must be restarted.
+    ///
+    /// Snapshot reads require MongoDB 5.0+ on a replica set or sharded cluster; an
+    /// [`ErrorKind::IncompatibleServer`] error is returned for older servers.
+    pub async fn snapshot_export(
+        &self,
+        options: impl Into<Option<SnapshotExportOptions>>,
+    ) -> Result<Cursor<T>> {
+        let options = options.into().unwrap_or_default();
+        let mut find_options = Some(
+            FindOptions::builder()
+                .projection(options.projection)
+                .batch_size(options.batch_size)
+                .selection_criteria(options.selection_criteria)
+                .build(),
+        );
+        resolve_options!(self, find_options, [selection_criteria]);
+
+        let session = self
+            .client()
+            .start_session(SessionOptions::builder().snapshot(true).build())
+            .await?;
+        let find = Find::new(self.namespace(), options.filter, find_options);
+        self.client()
+            .execute_cursor_operation_in_owned_session(find, session)
+            .await
+    }
+
+    async fn parallel_scan_boundaries(
+        &self,
+        num_cursors: usize,
+        options: &ParallelScanOptions,
+    ) -> Result<Vec<Bson>> {
+        let samples = options
+            .samples_per_cursor
+            .unwrap_or(DEFAULT_PARALLEL_SCAN_SAMPLES_PER_CURSOR) as usize
+            * num_cursors;
+        let pipeline = vec![
+            doc! { "$sample": { "size": samples as i64 } },
+            doc! { "$project": { "_id": 1 } },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+        let aggregate_options = AggregateOptions::builder()
+            .read_concern(options.read_concern.clone())
+            .selection_criteria(options.selection_criteria.clone())
+            .build();
+        let ids: Vec<Bson> = self
+            .clone_with_type::<Document>()
+            .aggregate(pipeline, aggregate_options)
+            .await?
+            .try_filter_map(|mut sample| future::ok(sample.remove("_id")))
+            .try_collect()
+            .await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut boundaries: Vec<Bson> = (1..num_cursors)
+            .map(|i| ids[i * ids.len() / num_cursors].clone())
+            .collect();
+        // Numbers of different types with the same value are the same boundary to the server.
+        boundaries.dedup_by(|a, b| bson_util::values_equal(a, b));
+        Ok(boundaries)
+    }
+}
+
+/// Applies the [`FindOptions::deadline`] of a find, if any, to its `max_time`, returning the time
+/// remaining until the deadline. Returns a timeout error if the deadline has already passed.
+fn apply_find_deadline(options: &mut Option<FindOptions>) -> Result<Option<Duration>> {
+    let options = match options {
+        Some(options) => options,
+        None => return Ok(None),
+    };
+    let deadline = match options.deadline {
+        Some(deadline) => deadline,
+        None => return Ok(None),
+    };
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "the deadline for the find has already passed",
+        )
+        .into());
+    }
+    // A maxTimeMS of 0 means no limit, so the remaining time is rounded up to a millisecond.
+    let max_time = remaining.max(Duration::from_millis(1));
+    options.max_time = Some(options.max_time.map_or(max_time, |t| t.min(max_time)));
+    Ok(Some(remaining))
+}
+
+/// Builds the pipeline used by [`Collection::distinct_cursor`], which produces one document per
+/// distinct value of `field_name` with the value stored in its `_id` field.
+///
+/// Like `distinct`, a dotted `field_name` descends through arrays at every level of the path, so
+/// each prefix of the path is unwound in turn (e.g. `$a` and then `$a.b` for `a.b`).
+fn distinct_pipeline(field_name: &str, filter: Option<Document>) -> Vec<Document> {
+    let mut pipeline = Vec::new();
+    if let Some(filter) = filter {
+        pipeline.push(doc! { "$match": filter });
+    }
+    let mut path = String::from("$");
+    for (i, part) in field_name.split('.').enumerate() {
+        if i > 0 {
+            path.push('.');
+        }
+        path.push_str(part);
+        pipeline.push(doc! { "$unwind": path.as_str() });
+    }
+    pipeline.push(doc! { "$group": { "_id": path } });
+    pipeline
+}
+
+/// The state of a [`Collection::find_resilient`] scan.
+struct ResilientFind {
+    coll: Collection<RawDocumentBuf>,
+    filter: Document,
+    sort_key: String,
+    options: FindOptions,
+    cursor: Option<Cursor<RawDocumentBuf>>,
+
+    /// The `sort_key` value of the last document returned.
+    last_key: Option<Bson>,
+
+    /// The `_id`s of the documents returned with a `sort_key` value of `last_key`.
+    seen_at_last_key: Vec<Bson>,
+
+    /// Whether the query was re-run without a document having been returned since.
+    resumed: bool,
+}
+
+impl ResilientFind {
+    async fn query(&self) -> Result<Cursor<RawDocumentBuf>> {
+        let filter = match self.last_key {
+            Some(ref last_key) => doc! {
+                "$and": [
+                    self.filter.clone(),
+                    { self.sort_key.as_str(): { "$gte": last_key.clone() } },
+                ]
+            },
+            None => self.filter.clone(),
+        };
+        self.coll.find(filter, self.options.clone()).await
+    }
+
+    async fn next_item<T: DeserializeOwned>(mut self) -> Result<Option<(T, Self)>> {
+        match self.next().await? {
+            Some(doc) => Ok(Some((bson_util::from_raw_document_with_path(&doc)?, self))),
+            None => Ok(None),
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<RawDocumentBuf>> {
+        loop {
+            let cursor = match self.cursor {
+                Some(ref mut cursor) => cursor,
+                None => {
+                    let cursor = self.query().await?;
+                    self.cursor.insert(cursor)
+                }
+            };
+
+            let doc = match cursor.try_next().await {
+                Ok(Some(doc)) => doc,
+                Ok(None) => return Ok(None),
+                // Only resume once in a row so that a query whose cursor is always killed before
+                // returning anything doesn't loop forever.
+                Err(e) if e.is_cursor_not_found() && !self.resumed => {
+                    self.resumed = true;
+                    self.cursor = None;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let document = Document::try_from(doc.as_ref())?;
+            let key = match bson_util::get_dotted(&document, &self.sort_key) {
+                Some(key) => key.clone(),
+                None => {
+                    return Err(ErrorKind::InvalidArgument {
+                        message: format!(
+                            "find_resilient requires every document to contain the sort key \"{}\"",
+                            self.sort_key
+                        ),
+                    }
+                    .into())
+                }
+            };
+            let id = match document.get("_id") {
+                Some(id) => id.clone(),
+                None => {
+                    return Err(ErrorKind::InvalidArgument {
+                        message: "find_resilient requires every document to contain an _id"
+                            .to_string(),
+                    }
+                    .into())
+                }
+            };
+
+            if self.last_key.as_ref() == Some(&key) {
+                if self.seen_at_last_key.contains(&id) {
+                    continue;
+                }
+            } else {
+                self.last_key = Some(key);
+                self.seen_at_last_key.clear();
+            }
+            self.seen_at_last_key.push(id);
+            self.resumed = false;
+            return Ok(Some(doc));
+        }
+    }
+}
+
+impl<T> Collection<T>
+where
+    T: DeserializeOwned,
+{
+    async fn find_one_and_delete_common(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindOneAndDeleteOptions>>,
+        session: impl Into<Option<&mut ClientSession>>,
+    ) -> Result<Option<T>> {
+        let session = session.into();
+
+        let mut options = options.into();
+        resolve_write_concern_with_session!(self, options, session.as_ref())?;
+
+        let op = FindAndModify::<T>::with_delete(self.namespace(), filter, options);
+        self.client().execute_operation(op, session).await
+    }
+
+    /// Atomically finds up to one document in the collection matching `filter` and deletes it.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn find_one_and_delete(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindOneAndDeleteOptions>>,
+    ) -> Result<Option<T>> {
+        self.find_one_and_delete_common(filter, options, None).await
+    }
+
+    /// Atomically finds up to one document in the collection matching `filter` and deletes it using
+    /// the provided `ClientSession`.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn find_one_and_delete_with_session(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindOneAndDeleteOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<T>> {
+        self.find_one_and_delete_common(filter, options, session)
+            .await
+    }
+
+    #[allow(clippy::needless_option_as_deref)]
+    async fn find_many_and_delete_common(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindManyAndDeleteOptions>>,
+        mut session: Option<&mut ClientSession>,
+    ) -> std::result::Result<Vec<T>, FindManyAndDeleteError<T>> {
+        let options = options.into().unwrap_or_default();
+        let find_one_options = options.to_find_one_and_delete_options();
+
+        let mut deleted = Vec::new();
+        while options
+            .limit
+            .map_or(true, |limit| (deleted.len() as u64) < limit)
+        {
+            match self
+                .find_one_and_delete_common(
+                    filter.clone(),
+                    find_one_options.clone(),
+                    session.as_deref_mut(),
+                )
+                .await
+            {
+                Ok(Some(doc)) => deleted.push(doc),
+                Ok(None) => break,
+                Err(error) => return Err(FindManyAndDeleteError { deleted, error }),
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Finds the documents in the collection matching `filter`, deleting and returning up to
+    /// `limit` of them (or all of them if no limit is specified) in the order given by `sort`.
+    ///
+    /// The documents are deleted one at a time using `findAndModify` commands, so while each
+    /// document is found and deleted atomically, they are not deleted as a single atomic
+    /// operation. Documents that other clients insert or update to match `filter` while the
+    /// operation is in progress may be deleted as well, and documents that other clients delete
+    /// in the meantime won't be returned. If all of the documents must be deleted atomically, use
+    /// [`Collection::find_many_and_delete_with_session`] with a session in a transaction.
+    ///
+    /// If an error occurs, the returned [`FindManyAndDeleteError`] contains both the error and the
+    /// documents that were deleted before it occurred. `FindManyAndDeleteError` converts into
+    /// [`Error`], so `?` can be used when those documents aren't needed.
+    pub async fn find_many_and_delete(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindManyAndDeleteOptions>>,
+    ) -> std::result::Result<Vec<T>, FindManyAndDeleteError<T>> {
+        self.find_many_and_delete_common(filter, options, None)
+            .await
+    }
+
+    /// Finds the documents in the collection matching `filter`, deleting and returning up to
+    /// `limit` of them using the provided `ClientSession`. See
+    /// [`Collection::find_many_and_delete`] for more details.
+    pub async fn find_many_and_delete_with_session(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindManyAndDeleteOptions>>,
+        session: &mut ClientSession,
+    ) -> std::result::Result<Vec<T>, FindManyAndDeleteError<T>> {
+        self.find_many_and_delete_common(filter, options, Some(session))
             .await
     }
 
@@ -1106,6 +2366,137 @@ where
         self.find_one_and_update_common(filter, update, options, session)
             .await
     }
+
+    async fn increment_common(
+        &self,
+        filter: Document,
+        field: &str,
+        amount: i64,
+        options: impl Into<Option<IncrementOptions>>,
+        session: impl Into<Option<&mut ClientSession>>,
+    ) -> Result<i64> {
+        let options = FindOneAndUpdateOptions::from_increment_options(
+            options.into().unwrap_or_default(),
+            field,
+        );
+        let updated = self
+            .clone_with_type::<Document>()
+            .find_one_and_update_common(
+                filter,
+                doc! { "$inc": { field: amount } },
+                options,
+                session,
+            )
+            .await?;
+
+        // The field may be a dotted path into an embedded document.
+        let mut value = updated.map(Bson::Document);
+        for key in field.split('.') {
+            value = match value {
+                Some(Bson::Document(mut doc)) => doc.remove(key),
+                _ => None,
+            };
+        }
+        match value.as_ref().and_then(bson_util::get_int) {
+            Some(value) => Ok(value),
+            None => Err(ErrorKind::InvalidResponse {
+                message: format!(
+                    "expected the incremented field \"{}\" to be an integer, got {:?}",
+                    field, value
+                ),
+            }
+            .into()),
+        }
+    }
+
+    /// Atomically adds `amount` to the integer `field` of the document matching `filter` and
+    /// returns the field's new value. `field` may be a dotted path into an embedded document.
+    ///
+    /// If `field` doesn't exist in the matching document, it's set to `amount`, and if no document
+    /// matches `filter`, a new document is inserted with `field` set to `amount` (along with any
+    /// equality conditions in `filter`), so the first increment of a counter returns `amount`.
+    ///
+    /// This is done with a single `findAndModify` command that returns only the counter field.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn increment(
+        &self,
+        filter: Document,
+        field: impl AsRef<str>,
+        amount: i64,
+        options: impl Into<Option<IncrementOptions>>,
+    ) -> Result<i64> {
+        self.increment_common(filter, field.as_ref(), amount, options, None)
+            .await
+    }
+
+    /// Atomically adds `amount` to the integer `field` of the document matching `filter` using the
+    /// provided `ClientSession` and returns the field's new value. See [`Collection::increment`]
+    /// for more details.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn increment_with_session(
+        &self,
+        filter: Document,
+        field: impl AsRef<str>,
+        amount: i64,
+        options: impl Into<Option<IncrementOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<i64> {
+        self.increment_common(filter, field.as_ref(), amount, options, session)
+            .await
+    }
+
+    /// Atomically claims a single job from a collection used as a work queue: finds up to one
+    /// document matching `filter` (e.g. `{ "status": "pending" }`), applies `claim_update` to it
+    /// (e.g. `{ "$set": { "status": "running", "worker": id } }`) and returns the claimed document
+    /// as it is after the update. `Ok(None)` is returned if there is no job to claim.
+    ///
+    /// Since the document is matched and updated by a single `findAndModify` command, concurrent
+    /// claimers never receive the same job, provided that `claim_update` makes the document stop
+    /// matching `filter`. Use [`ClaimOptions::sort`] to control which job is claimed first.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn claim_one(
+        &self,
+        filter: Document,
+        claim_update: impl Into<UpdateModifications>,
+        options: impl Into<Option<ClaimOptions>>,
+    ) -> Result<Option<T>> {
+        let options =
+            FindOneAndUpdateOptions::from_claim_options(options.into().unwrap_or_default());
+        self.find_one_and_update_common(filter, claim_update, options, None)
+            .await
+    }
+
+    /// Atomically claims a single job from a collection used as a work queue using the provided
+    /// `ClientSession`. See [`Collection::claim_one`] for more details.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn claim_one_with_session(
+        &self,
+        filter: Document,
+        claim_update: impl Into<UpdateModifications>,
+        options: impl Into<Option<ClaimOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<T>> {
+        let options =
+            FindOneAndUpdateOptions::from_claim_options(options.into().unwrap_or_default());
+        self.find_one_and_update_common(filter, claim_update, options, session)
+            .await
+    }
 }
 
 impl<T> Collection<T>
@@ -1180,6 +2571,29 @@ where
         let ds: Vec<_> = docs.into_iter().collect();
         let mut options = options.into();
         resolve_write_concern_with_session!(self, options, session.as_ref())?;
+        let on_duplicate = options
+            .as_ref()
+            .and_then(|o| o.on_duplicate)
+            .unwrap_or_default();
+        if on_duplicate != OnDuplicate::Fail {
+            let options = options.get_or_insert_with(Default::default);
+            if options.ordered == Some(true) {
+                return Err(ErrorKind::InvalidArgument {
+                    message: format!(
+                        "ordered cannot be true when on_duplicate is {:?}",
+                        on_duplicate
+                    ),
+                }
+                .into());
+            }
+            options.ordered = Some(false);
+        }
+        if let Some(ordered) = self.client().default_insert_ordered() {
+            options
+                .get_or_insert_with(Default::default)
+                .ordered
+                .get_or_insert(ordered);
+        }
 
         if ds.is_empty() {
             return Err(ErrorKind::InvalidArgument {
@@ -1193,6 +2607,8 @@ where
         let mut cumulative_failure: Option<BulkWriteFailure> = None;
         let mut error_labels: HashSet<String> = Default::default();
         let mut cumulative_result: Option<InsertManyResult> = None;
+        // The replies to every command sent, whether or not it failed, in the order they were sent.
+        let mut raw_replies = Vec::new();
 
         let mut n_attempted = 0;
 
@@ -1215,43 +2631,34 @@ where
                             .inserted_ids
                             .insert(index + n_attempted, id);
                     }
+                    raw_replies.extend(result.raw_replies);
 
                     n_attempted += current_batch_size;
                 }
                 Err(e) => {
                     let labels = e.labels().clone();
                     match *e.kind {
-                        ErrorKind::BulkWrite(bw) => {
+                        ErrorKind::BulkWrite(mut bw) => {
                             // for ordered inserts this size will be incorrect, but knowing the
                             // batch size isn't needed for ordered
                             // failures since we return immediately from
                             // them anyways.
                             let current_batch_size = bw.inserted_ids.len()
                                 + bw.write_errors.as_ref().map(|we| we.len()).unwrap_or(0);
+                            raw_replies.append(&mut bw.raw_replies);
 
-                            let failure_ref =
-                                cumulative_failure.get_or_insert_with(BulkWriteFailure::new);
-                            if let Some(write_errors) = bw.write_errors {
-                                for err in write_errors {
-                                    let index = n_attempted + err.index;
-
-                                    failure_ref
-                                        .write_errors
-                                        .get_or_insert_with(Default::default)
-                                        .push(BulkWriteError { index, ..err });
-                                }
-                            }
-
-                            if let Some(wc_error) = bw.write_concern_error {
-                                failure_ref.write_concern_error = Some(wc_error);
-                            }
+                            // the server reports indexes relative to the start of this batch
+                            cumulative_failure
+                                .get_or_insert_with(BulkWriteFailure::new)
+                                .merge_batch(bw, n_attempted);
 
                             error_labels.extend(labels);
 
                             if ordered {
                                 // this will always be true since we invoked get_or_insert_with
                                 // above.
-                                if let Some(failure) = cumulative_failure {
+                                if let Some(mut failure) = cumulative_failure {
+                                    failure.raw_replies = raw_replies;
                                     return Err(Error::new(
                                         ErrorKind::BulkWrite(failure),
                                         Some(error_labels),
@@ -1266,12 +2673,83 @@ where
             }
         }
 
+        // Resolve the duplicate key errors according to `on_duplicate` now that all of the
+        // inserts have been attempted.
+        if let Some(mut failure) = cumulative_failure.take() {
+            if on_duplicate == OnDuplicate::Fail {
+                cumulative_failure = Some(failure);
+            } else {
+                let result = cumulative_result.get_or_insert_with(InsertManyResult::new);
+                result.inserted_ids.extend(failure.inserted_ids.drain());
+
+                let mut remaining_errors = Vec::new();
+                for write_error in failure.write_errors.take().into_iter().flatten() {
+                    if write_error.code != DUPLICATE_KEY_CODE {
+                        remaining_errors.push(write_error);
+                        continue;
+                    }
+                    if on_duplicate == OnDuplicate::Skip {
+                        continue;
+                    }
+
+                    let doc = ds[write_error.index].borrow();
+                    let id = match to_document(doc)?.remove("_id") {
+                        Some(id) => id,
+                        None => {
+                            remaining_errors.push(write_error);
+                            continue;
+                        }
+                    };
+                    let replace_options = ReplaceOptions {
+                        upsert: Some(true),
+                        bypass_document_validation: options
+                            .as_ref()
+                            .and_then(|o| o.bypass_document_validation),
+                        write_concern: options.as_ref().and_then(|o| o.write_concern.clone()),
+                        ..Default::default()
+                    };
+                    match self
+                        .replace_one_common(
+                            doc! { "_id": id.clone() },
+                            doc,
+                            replace_options,
+                            session.as_deref_mut(),
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            result.inserted_ids.insert(write_error.index, id);
+                        }
+                        // The document conflicts with another one on a different unique index.
+                        Err(e) if e.is_duplicate_key_error() => {
+                            remaining_errors.push(write_error);
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                if !remaining_errors.is_empty() {
+                    failure.write_errors = Some(remaining_errors);
+                }
+                if failure.write_errors.is_some() || failure.write_concern_error.is_some() {
+                    cumulative_failure = Some(failure);
+                }
+            }
+        }
+
         match cumulative_failure {
-            Some(failure) => Err(Error::new(
-                ErrorKind::BulkWrite(failure),
-                Some(error_labels),
-            )),
-            None => Ok(cumulative_result.unwrap_or_else(InsertManyResult::new)),
+            Some(mut failure) => {
+                failure.raw_replies = raw_replies;
+                Err(Error::new(
+                    ErrorKind::BulkWrite(failure),
+                    Some(error_labels),
+                ))
+            }
+            None => {
+                let mut result = cumulative_result.unwrap_or_else(InsertManyResult::new);
+                result.raw_replies = raw_replies;
+                Ok(result)
+            }
         }
     }
 
@@ -1310,6 +2788,123 @@ where
         self.insert_many_common(docs, options, Some(session)).await
     }
 
+    #[allow(clippy::needless_option_as_deref)]
+    async fn insert_stream_common(
+        &self,
+        stream: impl Stream<Item = Result<T>>,
+        options: impl Into<Option<InsertManyOptions>>,
+        mut session: Option<&mut ClientSession>,
+    ) -> std::result::Result<InsertManyResult, InsertStreamError> {
+        let options = options.into();
+        let raw_coll = self.clone_with_type::<RawDocumentBuf>();
+        let mut stream = Box::pin(stream);
+
+        let mut inserted = InsertManyResult::new();
+        let mut n_attempted = 0;
+        let mut finished = false;
+
+        while !finished {
+            let mut batch = Vec::new();
+            let mut batch_bytes = 0;
+            let mut stream_error = None;
+
+            while batch.len() < INSERT_STREAM_MAX_BATCH_LEN
+                && batch_bytes < INSERT_STREAM_MAX_BATCH_BYTES
+            {
+                match stream.next().await {
+                    Some(Ok(doc)) => match to_raw_document_buf(&doc) {
+                        Ok(doc) => {
+                            batch_bytes += doc.as_bytes().len();
+                            batch.push(doc);
+                        }
+                        Err(e) => {
+                            stream_error = Some(e.into());
+                            break;
+                        }
+                    },
+                    Some(Err(e)) => {
+                        stream_error = Some(e);
+                        break;
+                    }
+                    None => {
+                        finished = true;
+                        break;
+                    }
+                }
+            }
+
+            if !batch.is_empty() {
+                match raw_coll
+                    .insert_many_common(&batch, options.clone(), session.as_deref_mut())
+                    .await
+                {
+                    Ok(result) => {
+                        for (index, id) in result.inserted_ids {
+                            inserted.inserted_ids.insert(index + n_attempted, id);
+                        }
+                        inserted.raw_replies.extend(result.raw_replies);
+                    }
+                    Err(mut error) => {
+                        // report write errors relative to the start of the stream rather than
+                        // the batch, and include the documents of the failed batch that were
+                        // inserted
+                        if let ErrorKind::BulkWrite(ref mut failure) = *error.kind {
+                            failure.offset_indexes(n_attempted);
+                            inserted.inserted_ids.extend(failure.inserted_ids.clone());
+                        }
+                        return Err(InsertStreamError { inserted, error });
+                    }
+                }
+                n_attempted += batch.len();
+            }
+
+            if let Some(error) = stream_error {
+                return Err(InsertStreamError { inserted, error });
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    /// Inserts the documents produced by `stream` into the collection, sending them to the server
+    /// in batches as they are produced rather than collecting the whole stream into memory first.
+    /// This is useful for loading data from a source that is itself streamed, e.g. a [`Cursor`]
+    /// over another collection.
+    ///
+    /// If the stream produces an error or an insert fails, no further documents are read from the
+    /// stream and the returned [`InsertStreamError`] contains both the error and the documents
+    /// that were inserted before it occurred. `InsertStreamError` converts into [`Error`], so `?`
+    /// can be used on the result where the partial result isn't needed.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn insert_stream(
+        &self,
+        stream: impl Stream<Item = Result<T>>,
+        options: impl Into<Option<InsertManyOptions>>,
+    ) -> std::result::Result<InsertManyResult, InsertStreamError> {
+        self.insert_stream_common(stream, options, None).await
+    }
+
+    /// Inserts the documents produced by `stream` into the collection using the provided
+    /// `ClientSession`. See [`Collection::insert_stream`] for more details.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn insert_stream_with_session(
+        &self,
+        stream: impl Stream<Item = Result<T>>,
+        options: impl Into<Option<InsertManyOptions>>,
+        session: &mut ClientSession,
+    ) -> std::result::Result<InsertManyResult, InsertStreamError> {
+        self.insert_stream_common(stream, options, Some(session))
+            .await
+    }
+
     async fn insert_one_common(
         &self,
         doc: &T,
@@ -1368,6 +2963,330 @@ where
         self.insert_one_common(doc.borrow(), options, session).await
     }
 
+    /// Inserts `doc` into the collection and waits until the write has propagated to the nodes
+    /// required by `tag_set_mode`, a custom write concern defined in the `getLastErrorModes` of
+    /// the replica set configuration (e.g. one that requires acknowledgement from nodes in
+    /// multiple regions). Any `w_timeout` of the collection's write concern is still applied, so
+    /// setting one bounds how long this waits for nodes that are unavailable.
+    ///
+    /// The server validates `tag_set_mode` before inserting, and an
+    /// [`ErrorKind::InvalidArgument`] error is returned without inserting if it isn't defined in
+    /// the replica set configuration. This is only supported on replica sets.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/tutorial/configure-replica-set-tag-sets/#tag-sets-and-custom-write-concern-behavior)
+    /// for more information on custom write concerns.
+    pub async fn insert_one_and_wait(
+        &self,
+        doc: impl Borrow<T>,
+        tag_set_mode: impl AsRef<str>,
+    ) -> Result<InsertOneResult> {
+        let tag_set_mode = tag_set_mode.as_ref();
+        let mut write_concern = self.write_concern().cloned().unwrap_or_default();
+        write_concern.w = Some(Acknowledgment::Custom(tag_set_mode.to_string()));
+        let options = InsertOneOptions::builder()
+            .write_concern(write_concern)
+            .build();
+        match self.insert_one_common(doc.borrow(), options, None).await {
+            // UnknownReplWriteConcern
+            Err(error) if error.code() == Some(79) => Err(ErrorKind::InvalidArgument {
+                message: format!(
+                    "the write concern tag set \"{}\" is not defined in the getLastErrorModes of \
+                     the replica set configuration",
+                    tag_set_mode
+                ),
+            }
+            .into()),
+            result => result,
+        }
+    }
+
+    /// Inserts `doc` while the given fail point is enabled and asserts that the document was
+    /// stored exactly once. This is intended for test suites that check the idempotency of
+    /// retryable writes: `fail_point` should be a `configureFailPoint` command that causes the
+    /// insert to fail with a retryable error (e.g. a `failCommand` fail point that returns a
+    /// `writeConcernError` after the write has been applied). The fail point is enabled on every
+    /// server the insert may be sent to (the primary, or each mongos of a sharded cluster) and
+    /// turned off again before this method returns.
+    ///
+    /// An `_id` is generated for `doc` if it doesn't already have one. An
+    /// [`ErrorKind::Internal`] error is returned if the document is not found exactly once after
+    /// the insert, or if the insert fails with a duplicate key error, which indicates that a write
+    /// that had already been applied was retried without the server recognizing the retry. In the
+    /// latter case, the duplicate key error is the error's source. Any other error is returned
+    /// as is.
+    ///
+    /// This is only available with the `test-util` feature flag enabled.
+    #[cfg(any(test, feature = "test-util"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub async fn assert_exactly_once_insert(
+        &self,
+        doc: impl Borrow<T>,
+        fail_point: Document,
+    ) -> Result<()> {
+        let mut doc = to_document(doc.borrow())?;
+        let id = doc
+            .entry("_id".to_string())
+            .or_insert_with(|| Bson::ObjectId(crate::bson::oid::ObjectId::new()))
+            .clone();
+        let fail_point_name = fail_point
+            .get_str("configureFailPoint")
+            .map_err(|_| ErrorKind::InvalidArgument {
+                message: "fail_point must be a configureFailPoint command".to_string(),
+            })?
+            .to_string();
+
+        // Wait for the deployment to be discovered so that every server the insert may be
+        // routed to is known.
+        let admin = self.client().database("admin");
+        admin.run_command(doc! { "ping": 1 }, None).await?;
+        let topology = self.client().topology().description();
+        let targets: Vec<SelectionCriteria> =
+            if topology.topology_type() == crate::sdam::TopologyType::Sharded {
+                topology
+                    .servers_with_type(&[crate::sdam::ServerType::Mongos])
+                    .map(|server| SelectionCriteria::from_address(server.address.clone()))
+                    .collect()
+            } else {
+                vec![SelectionCriteria::ReadPreference(ReadPreference::Primary)]
+            };
+
+        let mut enabled = Vec::new();
+        let mut insert_result = Ok(());
+        for target in &targets {
+            if let Err(error) = admin.run_command(fail_point.clone(), target.clone()).await {
+                insert_result = Err(error);
+                break;
+            }
+            enabled.push(target);
+        }
+        if insert_result.is_ok() {
+            insert_result = self
+                .clone_with_type::<Document>()
+                .insert_one(&doc, None)
+                .await
+                .map(|_| ());
+        }
+        for target in enabled {
+            admin
+                .run_command(
+                    doc! { "configureFailPoint": fail_point_name.as_str(), "mode": "off" },
+                    target.clone(),
+                )
+                .await?;
+        }
+
+        if let Err(error) = insert_result {
+            if error.is_duplicate_key_error() {
+                return Err(Error::internal(format!(
+                    "insert of document with _id {} was applied more than once",
+                    id
+                ))
+                .with_source(error));
+            }
+            return Err(error);
+        }
+
+        let count = self
+            .count_documents(doc! { "_id": id.clone() }, None)
+            .await?;
+        if count != 1 {
+            return Err(Error::internal(format!(
+                "expected the document with _id {} to be inserted exactly once, but found {} \
+                 copies",
+                id, count
+            )));
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::needless_option_as_deref)]
+    async fn bulk_upsert_common(
+        &self,
+        docs: impl IntoIterator<Item = impl Borrow<T>>,
+        key_fields: impl IntoIterator<Item = impl AsRef<str>>,
+        options: impl Into<Option<BulkUpsertOptions>>,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<BulkUpsertResult> {
+        let mut options = options.into();
+        resolve_write_concern_with_session!(self, options, session.as_ref())?;
+        let ordered = options.as_ref().and_then(|o| o.ordered).unwrap_or(true);
+        let update_options = UpdateOptions::from_bulk_upsert_options(options.unwrap_or_default());
+
+        let key_fields: Vec<String> = key_fields
+            .into_iter()
+            .map(|field| field.as_ref().to_string())
+            .collect();
+        if key_fields.is_empty() {
+            return Err(ErrorKind::InvalidArgument {
+                message: "No key fields provided to bulk_upsert".to_string(),
+            }
+            .into());
+        }
+
+        let mut keys = HashSet::new();
+        let mut statements = Vec::new();
+        for (index, doc) in docs.into_iter().enumerate() {
+            let mut doc = to_document(doc.borrow())?;
+
+            // `$eq` matches document values exactly rather than treating them as query operators.
+            let mut key = Document::new();
+            for field in &key_fields {
+                match bson_util::get_dotted(&doc, field) {
+                    Some(value) => key.insert(field.as_str(), doc! { "$eq": value.clone() }),
+                    None => {
+                        return Err(ErrorKind::InvalidArgument {
+                            message: format!(
+                                "document {} is missing the key field \"{}\"",
+                                index, field
+                            ),
+                        }
+                        .into())
+                    }
+                };
+            }
+            // The server doesn't guarantee that unordered writes are applied in order, so which of
+            // two documents with the same key would win is undefined.
+            if !ordered && !keys.insert(to_raw_document_buf(&key)?.as_bytes().to_vec()) {
+                return Err(ErrorKind::InvalidArgument {
+                    message: format!(
+                        "document {} has the same key as an earlier document, which is not \
+                         allowed for unordered upserts",
+                        index
+                    ),
+                }
+                .into());
+            }
+
+            // `_id` is immutable, so it can only be set when the document is inserted.
+            let mut update = Document::new();
+            let id = doc.remove("_id");
+            if !doc.is_empty() {
+                update.insert("$set", doc);
+            }
+            if let Some(id) = id {
+                update.insert("$setOnInsert", doc! { "_id": id });
+            }
+
+            statements.push((key, UpdateModifications::Document(update)));
+        }
+
+        if statements.is_empty() {
+            return Err(ErrorKind::InvalidArgument {
+                message: "No documents provided to bulk_upsert".to_string(),
+            }
+            .into());
+        }
+
+        let mut result = BulkUpsertResult {
+            matched_count: 0,
+            modified_count: 0,
+            upserted_ids: HashMap::new(),
+        };
+        let mut cumulative_failure: Option<BulkWriteFailure> = None;
+        let mut error_labels: HashSet<String> = Default::default();
+        // Each command sends as many of the remaining upserts as the selected server accepts.
+        let mut batch_start = 0;
+        while !statements.is_empty() {
+            let mut update = Update::new_single_many(
+                self.namespace(),
+                statements,
+                ordered,
+                Some(update_options.clone()),
+            );
+            let batch_result = self
+                .client()
+                .execute_operation::<Update>(&mut update, session.as_deref_mut())
+                .await;
+            let n_attempted = update.n_attempted();
+            statements = update.into_unsent();
+            match batch_result {
+                Ok(batch_result) => {
+                    result.matched_count += batch_result.matched_count;
+                    result.modified_count += batch_result.modified_count;
+                    let upserted = batch_result
+                        .raw_reply
+                        .get_array("upserted")
+                        .map(|upserted| upserted.iter().filter_map(Bson::as_document));
+                    for upserted in upserted.into_iter().flatten() {
+                        let index = upserted.get("index").and_then(bson_util::get_int);
+                        if let (Some(index), Some(id)) = (index, upserted.get("_id")) {
+                            result
+                                .upserted_ids
+                                .insert(batch_start + index as usize, id.clone());
+                        }
+                    }
+                }
+                Err(e) => {
+                    let labels = e.labels().clone();
+                    match *e.kind {
+                        ErrorKind::BulkWrite(bw) => {
+                            cumulative_failure
+                                .get_or_insert_with(BulkWriteFailure::new)
+                                .merge_batch(bw, batch_start);
+                            error_labels.extend(labels);
+
+                            if ordered {
+                                break;
+                            }
+                        }
+                        _ => return Err(e),
+                    }
+                }
+            }
+            batch_start += n_attempted;
+        }
+
+        match cumulative_failure {
+            Some(failure) => Err(Error::new(
+                ErrorKind::BulkWrite(failure),
+                Some(error_labels),
+            )),
+            None => Ok(result),
+        }
+    }
+
+    /// Upserts each of `docs` by the natural key made up of its `key_fields`: the fields of a
+    /// document are set on the existing document with the same values for `key_fields` if there
+    /// is one, or the document is inserted otherwise. This is done with one `update_one`-style
+    /// upsert per document, sent to the server in as few `update` commands as possible. Every
+    /// document must contain all of `key_fields`, which may be dotted paths into embedded
+    /// documents, and an `_id` in a document is only applied when it's inserted.
+    ///
+    /// If the upserts are ordered (the default), documents with the same key are applied in
+    /// order, so the fields of later documents take precedence, and no further documents are
+    /// upserted after one fails. Unordered upserts don't allow duplicate keys, and an
+    /// [`ErrorKind::InvalidArgument`] error is returned without writing anything if any are
+    /// provided; the remaining documents are still upserted after one fails. Upserts that fail
+    /// are reported by an [`ErrorKind::BulkWrite`] error, with the indexes of the failed
+    /// documents in `docs`; the documents that were upserted are not rolled back.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub async fn bulk_upsert(
+        &self,
+        docs: impl IntoIterator<Item = impl Borrow<T>>,
+        key_fields: impl IntoIterator<Item = impl AsRef<str>>,
+        options: impl Into<Option<BulkUpsertOptions>>,
+    ) -> Result<BulkUpsertResult> {
+        self.bulk_upsert_common(docs, key_fields, options, None)
+            .await
+    }
+
+    /// Upserts each of `docs` by the natural key made up of its `key_fields` using the provided
+    /// `ClientSession`. See [`Collection::bulk_upsert`] for more details.
+    pub async fn bulk_upsert_with_session(
+        &self,
+        docs: impl IntoIterator<Item = impl Borrow<T>>,
+        key_fields: impl IntoIterator<Item = impl AsRef<str>>,
+        options: impl Into<Option<BulkUpsertOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<BulkUpsertResult> {
+        self.bulk_upsert_common(docs, key_fields, options, Some(session))
+            .await
+    }
+
     async fn replace_one_common(
         &self,
         query: Document,
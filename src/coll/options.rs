@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use bson::serde_helpers;
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
@@ -9,8 +9,9 @@ use crate::{
     bson::{doc, Bson, Document},
     bson_util,
     concern::{ReadConcern, WriteConcern},
-    options::Collation,
+    options::{Collation, ServerApi, TimeseriesOptions},
     selection_criteria::SelectionCriteria,
+    Namespace,
 };
 
 /// These are the valid options for creating a [`Collection`](../struct.Collection.html) with
@@ -28,6 +29,19 @@ pub struct CollectionOptions {
 
     /// The default write concern for operations.
     pub write_concern: Option<WriteConcern>,
+
+    /// The stable server API to declare on the commands run via this collection, including
+    /// getMores on its cursors, in place of the one from the database. This allows an application
+    /// to migrate to the stable API one collection at a time.
+    ///
+    /// The client doesn't need to declare a server API. If it does, its version must match this
+    /// one, since connections are established with the one from the client options, or
+    /// operations will fail with an
+    /// [`ErrorKind::InvalidArgument`](crate::error::ErrorKind::InvalidArgument) error. The
+    /// settings of this option (e.g. [`ServerApi::strict`]) take precedence over the client's,
+    /// including on commitTransaction and abortTransaction for transactions whose first operation
+    /// is run via this collection.
+    pub server_api: Option<ServerApi>,
 }
 
 /// Specifies whether a
@@ -127,6 +141,14 @@ pub struct InsertManyOptions {
     /// The write concern for the operation.
     #[serde(skip_deserializing)]
     pub write_concern: Option<WriteConcern>,
+
+    /// What to do with documents that can't be inserted because of a duplicate key error.
+    ///
+    /// Defaults to [`OnDuplicate::Fail`]. When this is [`OnDuplicate::Skip`] or
+    /// [`OnDuplicate::Replace`], the inserts are unordered, and setting `ordered` to true is an
+    /// error.
+    #[serde(skip)]
+    pub on_duplicate: Option<OnDuplicate>,
 }
 
 impl InsertManyOptions {
@@ -135,10 +157,41 @@ impl InsertManyOptions {
             bypass_document_validation: options.bypass_document_validation,
             ordered: None,
             write_concern: options.write_concern,
+            on_duplicate: None,
         }
     }
 }
 
+/// Specifies how an [`Collection::insert_many`](../struct.Collection.html#method.insert_many)
+/// operation handles documents that fail to be inserted because of a duplicate key error, via
+/// [`InsertManyOptions::on_duplicate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OnDuplicate {
+    /// Report the duplicate key errors in an
+    /// [`ErrorKind::BulkWrite`](crate::error::ErrorKind::BulkWrite) error.
+    Fail,
+
+    /// Leave the existing documents unchanged and don't report the duplicate key errors. This
+    /// applies to conflicts on any unique index.
+    Skip,
+
+    /// Replace the existing document that has the same `_id` as a document that couldn't be
+    /// inserted, inserting the document if there is no such document. The `_id`s of documents
+    /// that are written this way are included in
+    /// [`InsertManyResult::inserted_ids`](crate::results::InsertManyResult::inserted_ids).
+    ///
+    /// If the document has no `_id` or it still can't be written because it conflicts with a
+    /// different document on another unique index, the original duplicate key error is reported.
+    Replace,
+}
+
+impl Default for OnDuplicate {
+    fn default() -> Self {
+        Self::Fail
+    }
+}
+
 /// Enum modeling the modifications to apply during an update.
 /// For details, see the official MongoDB
 /// [documentation](https://www.mongodb.com/docs/manual/reference/command/update/#update-command-behaviors)
@@ -195,6 +248,10 @@ pub struct UpdateOptions {
     pub bypass_document_validation: Option<bool>,
 
     /// If true, insert a document if no matching document is found.
+    ///
+    /// When used with `update_one`, the driver generates the `_id` of the document to be inserted
+    /// (via `$setOnInsert`) unless the filter or update already specifies one, so that a retried
+    /// upsert cannot insert a second document.
     pub upsert: Option<bool>,
 
     /// The collation to use for the operation.
@@ -235,6 +292,38 @@ impl UpdateOptions {
     }
 }
 
+/// Specifies the options to a
+/// [`Collection::bulk_upsert`](../struct.Collection.html#method.bulk_upsert) operation.
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct BulkUpsertOptions {
+    /// If true, when a document fails to be upserted, none of the remaining documents are
+    /// upserted. If false, the remaining documents are still upserted, but documents with the same
+    /// key are rejected up front since the order they're applied in isn't guaranteed.
+    ///
+    /// Defaults to true.
+    pub ordered: Option<bool>,
+
+    /// Opt out of document-level validation.
+    pub bypass_document_validation: Option<bool>,
+
+    /// The write concern for the operation.
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl UpdateOptions {
+    pub(crate) fn from_bulk_upsert_options(options: BulkUpsertOptions) -> Self {
+        Self {
+            bypass_document_validation: options.bypass_document_validation,
+            upsert: Some(true),
+            write_concern: options.write_concern,
+            ..Default::default()
+        }
+    }
+}
+
 /// Specifies the options to a
 /// [`Collection::replace_one`](../struct.Collection.html#method.replace_one) operation.
 #[derive(Clone, Debug, Default, Deserialize, TypedBuilder)]
@@ -246,6 +335,10 @@ pub struct ReplaceOptions {
     pub bypass_document_validation: Option<bool>,
 
     /// If true, insert a document if no matching document is found.
+    ///
+    /// The driver generates the `_id` of the document to be inserted unless the filter or
+    /// replacement already specifies one, so that a retried upsert cannot insert a second
+    /// document.
     pub upsert: Option<bool>,
 
     /// The collation to use for the operation.
@@ -345,6 +438,66 @@ pub struct FindOneAndDeleteOptions {
     pub let_vars: Option<Document>,
 }
 
+/// Specifies the options to a
+/// [`Collection::find_many_and_delete`](../struct.Collection.html#method.find_many_and_delete)
+/// operation.
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct FindManyAndDeleteOptions {
+    /// The maximum number of documents to delete. If unset, all documents matching the filter are
+    /// deleted.
+    pub limit: Option<u64>,
+
+    /// The maximum amount of time to allow each of the queries to run.
+    ///
+    /// This options maps to the `maxTimeMS` MongoDB query option, so the duration will be sent
+    /// across the wire as an integer number of milliseconds.
+    pub max_time: Option<Duration>,
+
+    /// Limits the fields of the documents being returned.
+    pub projection: Option<Document>,
+
+    /// The order in which the documents are deleted.
+    pub sort: Option<Document>,
+
+    /// The level of the write concern
+    pub write_concern: Option<WriteConcern>,
+
+    /// The collation to use for the operation.
+    ///
+    /// See the [documentation](https://www.mongodb.com/docs/manual/reference/collation/) for more
+    /// information on how to use this option.
+    pub collation: Option<Collation>,
+
+    /// The index to use for the operation.
+    /// Only available in MongoDB 4.4+.
+    pub hint: Option<Hint>,
+
+    /// Map of parameter names and values. Values must be constant or closed
+    /// expressions that do not reference document fields. Parameters can then be
+    /// accessed as variables in an aggregate expression context (e.g. "$$var").
+    ///
+    /// Only available in MongoDB 5.0+.
+    #[serde(rename = "let")]
+    pub let_vars: Option<Document>,
+}
+
+impl FindManyAndDeleteOptions {
+    pub(crate) fn to_find_one_and_delete_options(&self) -> FindOneAndDeleteOptions {
+        FindOneAndDeleteOptions {
+            max_time: self.max_time,
+            projection: self.projection.clone(),
+            sort: self.sort.clone(),
+            write_concern: self.write_concern.clone(),
+            collation: self.collation.clone(),
+            hint: self.hint.clone(),
+            let_vars: self.let_vars.clone(),
+        }
+    }
+}
+
 /// Specifies the options to a
 /// [`Collection::find_one_and_replace`](../struct.Collection.html#method.find_one_and_replace)
 /// operation.
@@ -365,7 +518,9 @@ pub struct FindOneAndReplaceOptions {
     /// Limits the fields of the document being returned.
     pub projection: Option<Document>,
 
-    /// Whether the operation should return the document before or after modification.
+    /// Whether the operation should return the document before or after modification. When
+    /// `upsert` is true and no document matches the filter, [`ReturnDocument::After`] returns the
+    /// newly inserted document, while [`ReturnDocument::Before`] returns `None`.
     pub return_document: Option<ReturnDocument>,
 
     /// The order of the documents for the purposes of the operation.
@@ -422,7 +577,9 @@ pub struct FindOneAndUpdateOptions {
     /// Limits the fields of the document being returned.
     pub projection: Option<Document>,
 
-    /// Whether the operation should return the document before or after modification.
+    /// Whether the operation should return the document before or after modification. When
+    /// `upsert` is true and no document matches the filter, [`ReturnDocument::After`] returns the
+    /// newly inserted document, while [`ReturnDocument::Before`] returns `None`.
     pub return_document: Option<ReturnDocument>,
 
     /// The order of the documents for the purposes of the operation.
@@ -453,6 +610,97 @@ pub struct FindOneAndUpdateOptions {
     pub let_vars: Option<Document>,
 }
 
+/// Specifies the options to a [`Collection::increment`](../struct.Collection.html#method.increment)
+/// operation.
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct IncrementOptions {
+    /// The maximum amount of time to allow the operation to run.
+    ///
+    /// This options maps to the `maxTimeMS` MongoDB query option, so the duration will be sent
+    /// across the wire as an integer number of milliseconds.
+    pub max_time: Option<Duration>,
+
+    /// The write concern for the operation.
+    pub write_concern: Option<WriteConcern>,
+
+    /// The collation to use for the operation.
+    ///
+    /// See the [documentation](https://www.mongodb.com/docs/manual/reference/collation/) for more
+    /// information on how to use this option.
+    pub collation: Option<Collation>,
+
+    /// The index to use for the operation.
+    /// Only available in MongoDB 4.4+.
+    pub hint: Option<Hint>,
+}
+
+impl FindOneAndUpdateOptions {
+    pub(crate) fn from_increment_options(options: IncrementOptions, field: &str) -> Self {
+        Self {
+            max_time: options.max_time,
+            projection: Some(doc! { "_id": 0, field: 1 }),
+            return_document: Some(ReturnDocument::After),
+            upsert: Some(true),
+            write_concern: options.write_concern,
+            collation: options.collation,
+            hint: options.hint,
+            ..Default::default()
+        }
+    }
+}
+
+/// Specifies the options to a [`Collection::claim_one`](../struct.Collection.html#method.claim_one)
+/// operation.
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct ClaimOptions {
+    /// Determines which job is claimed if more than one matches the filter, e.g. `{ "priority":
+    /// -1, "createdAt": 1 }` to claim the most important and then the oldest job first.
+    pub sort: Option<Document>,
+
+    /// Limits the fields of the claimed document that are returned.
+    pub projection: Option<Document>,
+
+    /// The maximum amount of time to allow the operation to run.
+    ///
+    /// This options maps to the `maxTimeMS` MongoDB query option, so the duration will be sent
+    /// across the wire as an integer number of milliseconds.
+    pub max_time: Option<Duration>,
+
+    /// The write concern for the operation.
+    pub write_concern: Option<WriteConcern>,
+
+    /// The collation to use for the operation.
+    ///
+    /// See the [documentation](https://www.mongodb.com/docs/manual/reference/collation/) for more
+    /// information on how to use this option.
+    pub collation: Option<Collation>,
+
+    /// The index to use for the operation.
+    /// Only available in MongoDB 4.4+.
+    pub hint: Option<Hint>,
+}
+
+impl FindOneAndUpdateOptions {
+    pub(crate) fn from_claim_options(options: ClaimOptions) -> Self {
+        Self {
+            sort: options.sort,
+            projection: options.projection,
+            max_time: options.max_time,
+            return_document: Some(ReturnDocument::After),
+            write_concern: options.write_concern,
+            collation: options.collation,
+            hint: options.hint,
+            ..Default::default()
+        }
+    }
+}
+
 /// Specifies the options to a [`Collection::aggregate`](../struct.Collection.html#method.aggregate)
 /// operation.
 #[skip_serializing_none]
@@ -508,6 +756,11 @@ pub struct AggregateOptions {
     ///
     /// This options maps to the `maxTimeMS` MongoDB query option, so the duration will be sent
     /// across the wire as an integer number of milliseconds.
+    ///
+    /// The server applies this limit to the cursor as a whole, counting the time spent on the
+    /// initial aggregate and on every subsequent getMore, so it is only sent with the initial
+    /// aggregate command. To limit how long each getMore waits on a tailable await cursor, such as
+    /// a change stream, use `max_await_time` instead.
     #[serde(
         serialize_with = "bson_util::serialize_duration_option_as_int_millis",
         rename = "maxTimeMS",
@@ -547,6 +800,621 @@ pub struct AggregateOptions {
     pub let_vars: Option<Document>,
 }
 
+/// A `$merge` aggregation stage, which writes the results of an aggregation pipeline to a
+/// collection. The stage must be the last stage of the pipeline and can be added to it via
+/// `Document::from` or `into()`.
+///
+/// Aggregations that end in a `$merge` stage are sent to a server selected with the
+/// aggregation's read preference only if every server eligible for that read preference is
+/// MongoDB 5.0+. Otherwise, including when no server is eligible, they are sent to the primary.
+///
+/// See the documentation [here](https://www.mongodb.com/docs/manual/reference/operator/aggregation/merge/)
+/// for more information on `$merge`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct MergeStage {
+    /// The collection to write the results to.
+    #[builder(!default)]
+    pub into: Namespace,
+
+    /// The fields that uniquely identify a document in the target collection. The target
+    /// collection must have a unique index on these fields.
+    ///
+    /// Defaults to `_id` on the server.
+    pub on: Option<Vec<String>>,
+
+    /// What to do when a result document matches an existing document in the target collection.
+    ///
+    /// Defaults to [`MergeWhenMatched::Merge`] on the server.
+    pub when_matched: Option<MergeWhenMatched>,
+
+    /// What to do when a result document does not match an existing document in the target
+    /// collection.
+    ///
+    /// Defaults to [`MergeWhenNotMatched::Insert`] on the server.
+    pub when_not_matched: Option<MergeWhenNotMatched>,
+}
+
+impl From<MergeStage> for Document {
+    fn from(stage: MergeStage) -> Self {
+        let mut merge = doc! {
+            "into": { "db": stage.into.db, "coll": stage.into.coll },
+        };
+        if let Some(on) = stage.on {
+            merge.insert("on", on);
+        }
+        if let Some(when_matched) = stage.when_matched {
+            match when_matched {
+                MergeWhenMatched::Pipeline { pipeline, let_vars } => {
+                    if let Some(let_vars) = let_vars {
+                        merge.insert("let", let_vars);
+                    }
+                    merge.insert("whenMatched", pipeline);
+                }
+                other => {
+                    merge.insert("whenMatched", other.as_str());
+                }
+            }
+        }
+        if let Some(when_not_matched) = stage.when_not_matched {
+            merge.insert("whenNotMatched", when_not_matched.as_str());
+        }
+        doc! { "$merge": merge }
+    }
+}
+
+/// The behavior of a [`MergeStage`] when a result document matches an existing document in the
+/// target collection.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum MergeWhenMatched {
+    /// Replace the existing document with the result document.
+    Replace,
+
+    /// Keep the existing document.
+    KeepExisting,
+
+    /// Merge the result document into the existing document.
+    Merge,
+
+    /// Stop the aggregation with an error. Documents already written are not rolled back.
+    Fail,
+
+    /// Update the existing document with an aggregation pipeline.
+    Pipeline {
+        /// The update pipeline. The result document can be accessed via the `$$new` variable.
+        pipeline: Vec<Document>,
+
+        /// Variables to make available to the pipeline, defined in terms of the fields of the
+        /// result document (e.g. `{ "total": "$total" }`).
+        let_vars: Option<Document>,
+    },
+}
+
+impl MergeWhenMatched {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Replace => "replace",
+            Self::KeepExisting => "keepExisting",
+            Self::Merge => "merge",
+            Self::Fail => "fail",
+            Self::Pipeline { .. } => "pipeline",
+        }
+    }
+}
+
+/// The behavior of a [`MergeStage`] when a result document does not match an existing document in
+/// the target collection.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum MergeWhenNotMatched {
+    /// Insert the result document.
+    Insert,
+
+    /// Discard the result document.
+    Discard,
+
+    /// Stop the aggregation with an error. Documents already written are not rolled back.
+    Fail,
+}
+
+impl MergeWhenNotMatched {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Insert => "insert",
+            Self::Discard => "discard",
+            Self::Fail => "fail",
+        }
+    }
+}
+
+/// An `$out` aggregation stage, which writes the results of an aggregation pipeline to a
+/// collection, replacing it if it already exists. The stage must be the last stage of the pipeline
+/// and can be added to it via `Document::from` or `into()`.
+///
+/// Aggregations that end in an `$out` stage are sent to a server selected with the
+/// aggregation's read preference only if every server eligible for that read preference is
+/// MongoDB 5.0+. Otherwise, including when no server is eligible, they are sent to the primary.
+///
+/// See the documentation [here](https://www.mongodb.com/docs/manual/reference/operator/aggregation/out/)
+/// for more information on `$out`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct OutStage {
+    /// The collection to write the results to.
+    #[builder(!default)]
+    pub into: Namespace,
+
+    /// Create the target collection as a time series collection with these options.
+    ///
+    /// The target collection must either not exist or already be a time series collection with
+    /// the same time field; otherwise, the aggregation fails. Only available in MongoDB 7.0.3+:
+    /// the driver returns an `IncompatibleServer` error for servers older than 7.0 without
+    /// sending the aggregation, and 7.0.0-7.0.2 servers return a command error.
+    pub timeseries: Option<TimeseriesOptions>,
+}
+
+impl From<OutStage> for Document {
+    fn from(stage: OutStage) -> Self {
+        let mut out = doc! { "db": stage.into.db, "coll": stage.into.coll };
+        if let Some(timeseries) = stage.timeseries {
+            let mut options = doc! { "timeField": timeseries.time_field };
+            if let Some(meta_field) = timeseries.meta_field {
+                options.insert("metaField", meta_field);
+            }
+            if let Some(granularity) = timeseries.granularity {
+                options.insert("granularity", granularity.as_str());
+            }
+            out.insert("timeseries", options);
+        }
+        doc! { "$out": out }
+    }
+}
+
+/// A `$setWindowFields` aggregation stage, which computes values over windows of documents
+/// (e.g. moving averages or running totals). The stage can be added to a pipeline via
+/// `Document::from` or `into()`.
+///
+/// Only available in MongoDB 5.0+. See the documentation
+/// [here](https://www.mongodb.com/docs/manual/reference/operator/aggregation/setWindowFields/) for
+/// more information on `$setWindowFields`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct SetWindowFieldsStage {
+    /// An expression to group documents by. Windows never span multiple partitions.
+    pub partition_by: Option<Bson>,
+
+    /// The order of the documents within each partition. Required for `documents` windows with
+    /// offsets, for `range` windows and for some window operators.
+    pub sort_by: Option<Document>,
+
+    /// The fields to add to the output documents.
+    #[builder(!default)]
+    pub output: Vec<WindowOutput>,
+}
+
+impl From<SetWindowFieldsStage> for Document {
+    fn from(stage: SetWindowFieldsStage) -> Self {
+        let mut set_window_fields = Document::new();
+        if let Some(partition_by) = stage.partition_by {
+            set_window_fields.insert("partitionBy", partition_by);
+        }
+        if let Some(sort_by) = stage.sort_by {
+            set_window_fields.insert("sortBy", sort_by);
+        }
+        let output: Document = stage
+            .output
+            .into_iter()
+            .map(|output| {
+                let mut field = output.operator;
+                if let Some(window) = output.window {
+                    field.insert("window", window.to_document());
+                }
+                (output.field, Bson::Document(field))
+            })
+            .collect();
+        set_window_fields.insert("output", output);
+        doc! { "$setWindowFields": set_window_fields }
+    }
+}
+
+/// A field computed by a [`SetWindowFieldsStage`].
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct WindowOutput {
+    /// The name of the output field.
+    #[builder(!default)]
+    pub field: String,
+
+    /// The window operator to compute, e.g. `doc! { "$avg": "$price" }`.
+    #[builder(!default)]
+    pub operator: Document,
+
+    /// The window of documents the operator is applied to. Defaults to the whole partition.
+    pub window: Option<Window>,
+}
+
+/// The documents a [`WindowOutput`] operator is applied to, relative to the current document.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Window {
+    /// A window bounded by positions in the sorted partition. Offsets must be integers.
+    Documents {
+        /// The lower bound of the window.
+        lower: WindowBound,
+
+        /// The upper bound of the window.
+        upper: WindowBound,
+    },
+
+    /// A window bounded by values of the `sort_by` field, which must be numeric or, if a `unit`
+    /// is specified, a date.
+    Range {
+        /// The lower bound of the window.
+        lower: WindowBound,
+
+        /// The upper bound of the window.
+        upper: WindowBound,
+
+        /// The unit of the offsets when sorting by a date field.
+        unit: Option<TimeUnit>,
+    },
+}
+
+impl Window {
+    fn to_document(&self) -> Document {
+        match self {
+            Self::Documents { lower, upper } => doc! {
+                "documents": [lower.to_bson(), upper.to_bson()],
+            },
+            Self::Range { lower, upper, unit } => {
+                let mut window = doc! {
+                    "range": [lower.to_bson(), upper.to_bson()],
+                };
+                if let Some(unit) = unit {
+                    window.insert("unit", unit.as_str());
+                }
+                window
+            }
+        }
+    }
+}
+
+/// A bound of a [`Window`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum WindowBound {
+    /// The first or last document in the partition.
+    Unbounded,
+
+    /// The current document.
+    Current,
+
+    /// An offset from the current document. Negative values precede the current document.
+    Offset(Bson),
+}
+
+impl WindowBound {
+    fn to_bson(&self) -> Bson {
+        match self {
+            Self::Unbounded => Bson::String("unbounded".to_string()),
+            Self::Current => Bson::String("current".to_string()),
+            Self::Offset(offset) => offset.clone(),
+        }
+    }
+}
+
+/// A unit of time, used for the offsets of a [`Window::Range`] and the step of a [`DensifyStage`]
+/// over a date field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TimeUnit {
+    /// Years.
+    Year,
+    /// Quarters.
+    Quarter,
+    /// Months.
+    Month,
+    /// Weeks.
+    Week,
+    /// Days.
+    Day,
+    /// Hours.
+    Hour,
+    /// Minutes.
+    Minute,
+    /// Seconds.
+    Second,
+    /// Milliseconds.
+    Millisecond,
+}
+
+impl TimeUnit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Year => "year",
+            Self::Quarter => "quarter",
+            Self::Month => "month",
+            Self::Week => "week",
+            Self::Day => "day",
+            Self::Hour => "hour",
+            Self::Minute => "minute",
+            Self::Second => "second",
+            Self::Millisecond => "millisecond",
+        }
+    }
+}
+
+/// A `$densify` aggregation stage, which adds documents to fill the gaps in a sequence of numeric
+/// or date values of a field, e.g. to produce a document for every hour of a time series. The
+/// added documents only contain `field` and the `partition_by_fields`. The stage can be added to a
+/// pipeline via `Document::from` or `into()`.
+///
+/// Only available in MongoDB 5.1+. See the documentation
+/// [here](https://www.mongodb.com/docs/manual/reference/operator/aggregation/densify/) for more
+/// information on `$densify`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct DensifyStage {
+    /// The field to densify. Its values must all be numbers, or all be dates if a `unit` is
+    /// specified.
+    #[builder(!default)]
+    pub field: String,
+
+    /// The fields to group documents by. Each partition is densified separately, and the
+    /// documents added to it have the same values for these fields.
+    pub partition_by_fields: Option<Vec<String>>,
+
+    /// The amount to increment `field` by between consecutive documents.
+    #[builder(!default)]
+    pub step: Bson,
+
+    /// The unit of `step` when densifying a date field.
+    pub unit: Option<TimeUnit>,
+
+    /// The range of values to densify.
+    #[builder(!default)]
+    pub bounds: DensifyBounds,
+}
+
+impl From<DensifyStage> for Document {
+    fn from(stage: DensifyStage) -> Self {
+        let mut range = doc! {
+            "step": stage.step,
+            "bounds": stage.bounds.to_bson(),
+        };
+        if let Some(unit) = stage.unit {
+            range.insert("unit", unit.as_str());
+        }
+        let mut densify = doc! { "field": stage.field };
+        if let Some(partition_by_fields) = stage.partition_by_fields {
+            densify.insert("partitionByFields", partition_by_fields);
+        }
+        densify.insert("range", range);
+        doc! { "$densify": densify }
+    }
+}
+
+/// The range of values filled in by a [`DensifyStage`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum DensifyBounds {
+    /// From the minimum to the maximum value of the field across all documents.
+    Full,
+
+    /// From the minimum to the maximum value of the field within each partition.
+    Partition,
+
+    /// From `lower` (inclusive) to `upper` (exclusive). The bounds must be numbers, or dates if a
+    /// `unit` is specified.
+    Range {
+        /// The lower bound of the range.
+        lower: Bson,
+
+        /// The upper bound of the range.
+        upper: Bson,
+    },
+}
+
+impl DensifyBounds {
+    fn to_bson(&self) -> Bson {
+        match self {
+            Self::Full => Bson::String("full".to_string()),
+            Self::Partition => Bson::String("partition".to_string()),
+            Self::Range { lower, upper } => Bson::Array(vec![lower.clone(), upper.clone()]),
+        }
+    }
+}
+
+/// A `$fill` aggregation stage, which fills in `null` and missing values of fields, e.g. in the
+/// documents added by a [`DensifyStage`]. The stage can be added to a pipeline via
+/// `Document::from` or `into()`.
+///
+/// Only available in MongoDB 5.3+. See the documentation
+/// [here](https://www.mongodb.com/docs/manual/reference/operator/aggregation/fill/) for more
+/// information on `$fill`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct FillStage {
+    /// An expression to group documents by. Values are only filled in from documents in the same
+    /// partition. Cannot be combined with `partition_by_fields`.
+    pub partition_by: Option<Bson>,
+
+    /// The fields to group documents by. Cannot be combined with `partition_by`.
+    pub partition_by_fields: Option<Vec<String>>,
+
+    /// The order of the documents within each partition. Required if any output uses
+    /// [`FillMethod::Linear`] or [`FillMethod::LastObservation`].
+    pub sort_by: Option<Document>,
+
+    /// The fields to fill in.
+    #[builder(!default)]
+    pub output: Vec<FillOutput>,
+}
+
+impl From<FillStage> for Document {
+    fn from(stage: FillStage) -> Self {
+        let mut fill = Document::new();
+        if let Some(partition_by) = stage.partition_by {
+            fill.insert("partitionBy", partition_by);
+        }
+        if let Some(partition_by_fields) = stage.partition_by_fields {
+            fill.insert("partitionByFields", partition_by_fields);
+        }
+        if let Some(sort_by) = stage.sort_by {
+            fill.insert("sortBy", sort_by);
+        }
+        let output: Document = stage
+            .output
+            .into_iter()
+            .map(|output| (output.field, Bson::Document(output.method.to_document())))
+            .collect();
+        fill.insert("output", output);
+        doc! { "$fill": fill }
+    }
+}
+
+/// A field filled in by a [`FillStage`].
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(setter(into)))]
+#[non_exhaustive]
+pub struct FillOutput {
+    /// The name of the field to fill in.
+    pub field: String,
+
+    /// How to compute the missing values.
+    pub method: FillMethod,
+}
+
+/// How a [`FillOutput`] computes the values it fills in.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum FillMethod {
+    /// The result of an expression, e.g. a constant.
+    Value(Bson),
+
+    /// Linear interpolation between the surrounding non-null values, in `sort_by` order.
+    Linear,
+
+    /// The last non-null value, in `sort_by` order.
+    LastObservation,
+}
+
+impl FillMethod {
+    fn to_document(&self) -> Document {
+        match self {
+            Self::Value(value) => doc! { "value": value.clone() },
+            Self::Linear => doc! { "method": "linear" },
+            Self::LastObservation => doc! { "method": "locf" },
+        }
+    }
+}
+
+/// A `$vectorSearch` aggregation stage, which performs an approximate nearest neighbor search on
+/// a field indexed by an Atlas Vector Search index. The stage must be the first stage of the
+/// pipeline and can be added to it via `Document::from` or `into()`. Results are returned in
+/// order of decreasing similarity to `query_vector`; the similarity score of each result can be
+/// added to it with a [`VectorSearchStage::score_stage`].
+///
+/// Only available on MongoDB Atlas. See the documentation
+/// [here](https://www.mongodb.com/docs/atlas/atlas-vector-search/vector-search-stage/) for more
+/// information on `$vectorSearch`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct VectorSearchStage {
+    /// The name of the Atlas Vector Search index to use.
+    #[builder(!default)]
+    pub index: String,
+
+    /// The indexed vector field to search.
+    #[builder(!default)]
+    pub path: String,
+
+    /// The vector to search for. Must have the same number of dimensions as the indexed field.
+    #[builder(!default)]
+    pub query_vector: Vec<f64>,
+
+    /// The number of nearest neighbors to consider during the search. Must be at least `limit`;
+    /// higher values improve accuracy at the cost of latency.
+    #[builder(!default)]
+    pub num_candidates: u32,
+
+    /// The number of documents to return.
+    #[builder(!default)]
+    pub limit: u32,
+
+    /// A query on fields indexed as `filter` fields of the vector search index, restricting the
+    /// documents that are searched (e.g. `doc! { "category": "books" }`). The filter is applied
+    /// before the nearest neighbors are selected, so up to `limit` matching documents are still
+    /// returned.
+    pub filter: Option<Document>,
+}
+
+impl VectorSearchStage {
+    /// Returns an `$addFields` stage that stores the similarity score computed by a preceding
+    /// `$vectorSearch` stage in `field` of each result, so that it can be deserialized as part of
+    /// the result type (e.g. as an `f64` field).
+    pub fn score_stage(field: impl AsRef<str>) -> Document {
+        doc! {
+            "$addFields": { field.as_ref(): { "$meta": "vectorSearchScore" } },
+        }
+    }
+}
+
+impl From<VectorSearchStage> for Document {
+    fn from(stage: VectorSearchStage) -> Self {
+        let mut vector_search = doc! {
+            "index": stage.index,
+            "path": stage.path,
+            "queryVector": stage.query_vector,
+            "numCandidates": stage.num_candidates,
+            "limit": stage.limit,
+        };
+        if let Some(filter) = stage.filter {
+            vector_search.insert("filter", filter);
+        }
+        doc! { "$vectorSearch": vector_search }
+    }
+}
+
+/// A `$unionWith` aggregation stage, which adds the documents of another collection in the same
+/// database to the results of the pipeline, optionally processing them with a pipeline of their
+/// own first. The stage can be added to a pipeline via `Document::from` or `into()`.
+///
+/// Only available in MongoDB 4.4+. See the documentation
+/// [here](https://www.mongodb.com/docs/manual/reference/operator/aggregation/unionWith/) for more
+/// information on `$unionWith`.
+#[derive(Clone, Debug, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct UnionWithStage {
+    /// The name of the collection whose documents are added to the results.
+    #[builder(!default)]
+    pub coll: String,
+
+    /// The pipeline applied to the documents of `coll` before they are added to the results. It
+    /// may contain further `$unionWith` stages, but not `$out` or `$merge`.
+    pub pipeline: Option<Vec<Document>>,
+}
+
+impl From<UnionWithStage> for Document {
+    fn from(stage: UnionWithStage) -> Self {
+        match stage.pipeline {
+            Some(pipeline) => doc! {
+                "$unionWith": { "coll": stage.coll, "pipeline": pipeline },
+            },
+            None => doc! { "$unionWith": stage.coll },
+        }
+    }
+}
+
 /// Specifies the options to a
 /// [`Collection::count_documents`](../struct.Collection.html#method.count_documents) operation.
 #[derive(Clone, Debug, Default, Deserialize, TypedBuilder)]
@@ -715,7 +1583,10 @@ pub struct FindOptions {
     pub max: Option<Document>,
 
     /// The maximum amount of time for the server to wait on new documents to satisfy a tailable
-    /// cursor query. If the cursor is not tailable, this option is ignored.
+    /// cursor query. This option can only be set when `cursor_type` is
+    /// [`CursorType::TailableAwait`]; otherwise, the find returns an
+    /// [`ErrorKind::InvalidArgument`](crate::error::ErrorKind::InvalidArgument) error without
+    /// sending anything to the server.
     #[serde(skip)]
     pub max_await_time: Option<Duration>,
 
@@ -783,6 +1654,100 @@ pub struct FindOptions {
     /// Only available in MongoDB 5.0+.
     #[serde(rename = "let")]
     pub let_vars: Option<Document>,
+
+    /// If true, an error is returned when a document returned by the query contains a field that
+    /// the type it's deserialized to doesn't have, including fields of nested documents.
+    /// Otherwise, such fields are ignored. Defaults to false.
+    ///
+    /// When this is enabled, documents are deserialized from an owned copy, so types that borrow
+    /// from the returned documents (e.g. ones with `&str` fields) can't be used with
+    /// [`Cursor::deserialize_current`](../struct.Cursor.html#method.deserialize_current).
+    #[serde(skip)]
+    pub deny_unknown_fields: Option<bool>,
+
+    /// If set, BSON doubles are converted when they are deserialized into integer fields, as
+    /// specified by the given [`NumericCoercion`], instead of producing an error. This helps
+    /// tolerate documents whose numeric fields were written with different types over time.
+    ///
+    /// Integers of any width can always be deserialized into any integer or floating point type
+    /// that can hold their value; out of range values produce an error regardless of this option.
+    ///
+    /// When this is set, documents are deserialized from an owned copy, so types that borrow from
+    /// the returned documents (e.g. ones with `&str` fields) can't be used with
+    /// [`Cursor::deserialize_current`](../struct.Cursor.html#method.deserialize_current).
+    #[serde(skip)]
+    pub numeric_coercion: Option<NumericCoercion>,
+
+    /// If set, an error is returned instead of deserializing a document returned by the query
+    /// that has more than this many levels of nested documents and arrays, counting the returned
+    /// document itself as the first level. The depth is checked without recursion before the
+    /// document is deserialized, which guards applications that pass through untrusted data
+    /// against stack overflows from maliciously deep documents.
+    ///
+    /// By default, the depth is not limited.
+    #[serde(skip)]
+    pub max_document_depth: Option<u32>,
+
+    /// An absolute point in time by which the find must complete. The time remaining until the
+    /// deadline is sent as the `maxTimeMS` of the `find` command, unless `max_time` is shorter,
+    /// and the find returns a timeout error if it has not completed by the deadline, including
+    /// the time spent selecting a server and checking out a connection. If the deadline has
+    /// already passed, the timeout error is returned without sending anything to the server.
+    ///
+    /// The deadline only applies to the initial `find` command; iterating the returned cursor
+    /// is not bounded by it.
+    #[serde(skip)]
+    pub deadline: Option<Instant>,
+}
+
+/// Specifies the options to a
+/// [`Collection::parallel_scan`](../struct.Collection.html#method.parallel_scan) operation.
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct ParallelScanOptions {
+    /// The filter that the documents returned by the cursors must match. The `_id` ranges are
+    /// chosen without regard to the filter.
+    pub filter: Option<Document>,
+
+    /// Limits the fields of the documents returned by the cursors.
+    pub projection: Option<Document>,
+
+    /// The number of documents the server should return per cursor batch.
+    pub batch_size: Option<u32>,
+
+    /// The number of `_id`s to sample per requested cursor when choosing the range boundaries.
+    /// More samples make the ranges closer to equal in size at the cost of a more expensive
+    /// `$sample` stage. Defaults to 10.
+    pub samples_per_cursor: Option<u32>,
+
+    /// The read concern to use for sampling and for the cursors.
+    pub read_concern: Option<ReadConcern>,
+
+    /// The criteria used to select a server for sampling and for each of the cursors. If none is
+    /// specified, the selection criteria defined on the collection will be used.
+    pub selection_criteria: Option<SelectionCriteria>,
+}
+
+/// Specifies the options to a
+/// [`Collection::snapshot_export`](../struct.Collection.html#method.snapshot_export) operation.
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct SnapshotExportOptions {
+    /// The filter that the exported documents must match. By default, every document in the
+    /// collection is exported.
+    pub filter: Option<Document>,
+
+    /// Limits the fields of the exported documents.
+    pub projection: Option<Document>,
+
+    /// The number of documents the server should return per cursor batch.
+    pub batch_size: Option<u32>,
+
+    /// The criteria used to select the server to export from. If none is specified, the
+    /// selection criteria defined on the collection will be used.
+    pub selection_criteria: Option<SelectionCriteria>,
 }
 
 impl From<FindOneOptions> for FindOptions {
@@ -810,10 +1775,27 @@ impl From<FindOneOptions> for FindOptions {
             no_cursor_timeout: None,
             sort: options.sort,
             let_vars: options.let_vars,
+            deny_unknown_fields: options.deny_unknown_fields,
+            numeric_coercion: options.numeric_coercion,
+            max_document_depth: options.max_document_depth,
+            deadline: None,
         }
     }
 }
 
+/// Specifies how BSON doubles are converted when they are deserialized into integer fields. See
+/// [`FindOptions::numeric_coercion`] for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NumericCoercion {
+    /// Doubles without a fractional part are converted, e.g. `5.0` into `5`. Doubles with a
+    /// fractional part produce an error.
+    Exact,
+
+    /// Doubles are truncated towards zero, e.g. `5.9` into `5` and `-5.9` into `-5`.
+    Truncate,
+}
+
 /// Custom serializer used to serialize limit as its absolute value.
 fn serialize_absolute_value<S>(
     val: &Option<i64>,
@@ -906,6 +1888,23 @@ pub struct FindOneOptions {
     /// Only available in MongoDB 5.0+.
     #[serde(rename = "let")]
     pub let_vars: Option<Document>,
+
+    /// If true, an error is returned when the document returned by the query contains a field
+    /// that the type it's deserialized to doesn't have, including fields of nested documents.
+    /// Otherwise, such fields are ignored. Defaults to false.
+    #[serde(skip)]
+    pub deny_unknown_fields: Option<bool>,
+
+    /// If set, BSON doubles are converted when they are deserialized into integer fields, as
+    /// specified by the given [`NumericCoercion`], instead of producing an error.
+    #[serde(skip)]
+    pub numeric_coercion: Option<NumericCoercion>,
+
+    /// If set, an error is returned instead of deserializing the document returned by the query
+    /// if it has more than this many levels of nested documents and arrays. See
+    /// [`FindOptions::max_document_depth`] for more information.
+    #[serde(skip)]
+    pub max_document_depth: Option<u32>,
 }
 
 /// Specifies the options to a
@@ -924,6 +1923,9 @@ pub struct CreateIndexOptions {
 
     /// The maximum amount of time to allow the index to build.
     ///
+    /// Note that the server may not abort an index build that is already in progress when this
+    /// limit is exceeded.
+    ///
     /// This option maps to the `maxTimeMS` MongoDB query option, so the duration will be sent
     /// across the wire as an integer number of milliseconds.
     #[serde(
@@ -936,6 +1938,12 @@ pub struct CreateIndexOptions {
 
     /// The write concern for the operation.
     pub write_concern: Option<WriteConcern>,
+
+    /// Tags the command with an arbitrary BSON value to help trace the operation through the
+    /// database profiler, currentOp and logs.
+    ///
+    /// This option is only available on server versions 4.4+.
+    pub comment: Option<Bson>,
 }
 
 /// Specifies the options to a [`Collection::drop`](../struct.Collection.html#method.drop)
@@ -978,6 +1986,12 @@ pub struct DropIndexOptions {
 
     /// The write concern for the operation.
     pub write_concern: Option<WriteConcern>,
+
+    /// Tags the command with an arbitrary BSON value to help trace the operation through the
+    /// database profiler, currentOp and logs.
+    ///
+    /// This option is only available on server versions 4.4+.
+    pub comment: Option<Bson>,
 }
 
 /// Specifies the options to a
@@ -1003,6 +2017,15 @@ pub struct ListIndexesOptions {
     /// The number of indexes the server should return per cursor batch.
     #[serde(default, skip_serializing)]
     pub batch_size: Option<u32>,
+
+    /// If true, the results are sorted by index name. The server does not guarantee any particular
+    /// order, so this can be used for stable, reproducible iteration.
+    ///
+    /// Sorting is performed by the driver: all of the results are fetched before the returned
+    /// cursor yields the first one. Names are compared byte-wise, so e.g. "B_1" sorts before
+    /// "a_1".
+    #[serde(skip)]
+    pub sort_by_name: Option<bool>,
 }
 
 /// The minimum number of data-bearing voting replica set members (i.e. commit quorum), including
@@ -1065,3 +2088,89 @@ impl<'de> Deserialize<'de> for CommitQuorum {
         }
     }
 }
+
+/// Specifies the options to a [`Collection::validate`](../struct.Collection.html#method.validate)
+/// operation.
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder, Serialize)]
+#[builder(field_defaults(default, setter(into)))]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ValidateOptions {
+    /// Whether to perform a more thorough but slower validation that checks the collection's
+    /// underlying storage in full. This blocks all reads and writes on the collection while it
+    /// runs, and cannot be combined with `background`.
+    ///
+    /// Defaults to false.
+    pub full: Option<bool>,
+
+    /// Whether to validate the collection without blocking reads and writes to it while the
+    /// validation runs, which validates a checkpoint of the collection rather than its latest
+    /// state.
+    ///
+    /// This option is only available on server versions 4.4+ using the WiredTiger storage engine;
+    /// other servers return an error. Defaults to false.
+    pub background: Option<bool>,
+
+    /// The criteria used to select a server for this operation.
+    ///
+    /// If none specified, the default set on the collection will be used.
+    #[serde(skip_serializing)]
+    pub selection_criteria: Option<SelectionCriteria>,
+
+    /// Tags the command with an arbitrary BSON value to help trace the operation through the
+    /// database profiler, currentOp and logs.
+    ///
+    /// This option is only available on server versions 4.4+.
+    pub comment: Option<Bson>,
+}
+
+/// Specifies the options to a [`Collection::stats`](../struct.Collection.html#method.stats)
+/// operation.
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct CollectionStatsOptions {
+    /// The factor to divide the sizes in the result by, e.g. 1024 to report them in kibibytes.
+    /// Sizes are rounded down. Must be greater than 0; defaults to 1, i.e. bytes.
+    pub scale: Option<u32>,
+
+    /// The criteria used to select a server for this operation.
+    ///
+    /// If none specified, the default set on the collection will be used.
+    pub selection_criteria: Option<SelectionCriteria>,
+}
+
+/// Specifies the options to a [`Collection::compact`](../struct.Collection.html#method.compact)
+/// operation.
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder, Serialize)]
+#[builder(field_defaults(default, setter(into)))]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CompactOptions {
+    /// Whether to allow compacting the collection on the primary of a replica set. This is
+    /// required to run the command on a primary before MongoDB 4.4.
+    pub force: Option<bool>,
+
+    /// Tags the command with an arbitrary BSON value to help trace the operation through the
+    /// database profiler, currentOp and logs.
+    ///
+    /// This option is only available on server versions 4.4+.
+    pub comment: Option<Bson>,
+}
+
+/// Specifies the options to a [`Collection::reindex`](../struct.Collection.html#method.reindex)
+/// operation.
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder, Serialize)]
+#[builder(field_defaults(default, setter(into)))]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ReIndexOptions {
+    /// Tags the command with an arbitrary BSON value to help trace the operation through the
+    /// database profiler, currentOp and logs.
+    ///
+    /// This option is only available on server versions 4.4+.
+    pub comment: Option<Bson>,
+}
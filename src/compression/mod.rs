@@ -22,7 +22,15 @@ use std::convert::TryInto;
 ))]
 use std::io::Write;
 
-use crate::error::{Error, ErrorKind, Result};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    error::{Error, ErrorKind, Result},
+    results::{CompressionStats, CompressorStats},
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum CompressorId {
@@ -51,6 +59,18 @@ impl CompressorId {
             .into()),
         }
     }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            CompressorId::Noop => "noop",
+            #[cfg(feature = "snappy-compression")]
+            CompressorId::Snappy => "snappy",
+            #[cfg(feature = "zlib-compression")]
+            CompressorId::Zlib => "zlib",
+            #[cfg(feature = "zstd-compression")]
+            CompressorId::Zstd => "zstd",
+        }
+    }
 }
 
 /// Enum representing supported compressor algorithms.
@@ -324,3 +344,96 @@ impl Decoder {
         }
     }
 }
+
+/// The number of bytes a wire protocol message occupied on the wire, along with the number it
+/// would have occupied had it not been compressed.
+#[derive(Clone, Debug)]
+pub(crate) struct MessageSize {
+    /// The compressor used for the message, if it was compressed.
+    pub(crate) compressor: Option<CompressorId>,
+
+    /// The size of the message as an uncompressed OP_MSG.
+    pub(crate) uncompressed: usize,
+
+    /// The size of the message as it was sent or received.
+    pub(crate) wire: usize,
+}
+
+/// The number of compressor ids, i.e. one more than the largest `CompressorId`.
+const NUM_COMPRESSOR_IDS: usize = 4;
+
+/// Tracks the sizes of the messages sent and received by the connections of a `Client`. The
+/// counters are atomic so that connections recording concurrently don't contend on a lock.
+#[derive(Debug, Default)]
+pub(crate) struct CompressionStatsRecorder {
+    /// The counters for each compressor, indexed by its `CompressorId`.
+    by_compressor: [AtomicCompressorStats; NUM_COMPRESSOR_IDS],
+    uncompressed: AtomicCompressorStats,
+}
+
+impl CompressionStatsRecorder {
+    pub(crate) fn record_sent(&self, size: &MessageSize) {
+        let stats = self.stats_for(size);
+        stats
+            .bytes_sent_uncompressed
+            .fetch_add(size.uncompressed as u64, Ordering::Relaxed);
+        stats
+            .bytes_sent
+            .fetch_add(size.wire as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_received(&self, size: &MessageSize) {
+        let stats = self.stats_for(size);
+        stats
+            .bytes_received_uncompressed
+            .fetch_add(size.uncompressed as u64, Ordering::Relaxed);
+        stats
+            .bytes_received
+            .fetch_add(size.wire as u64, Ordering::Relaxed);
+    }
+
+    fn stats_for(&self, size: &MessageSize) -> &AtomicCompressorStats {
+        match size.compressor {
+            Some(ref id) => &self.by_compressor[id.clone() as usize],
+            None => &self.uncompressed,
+        }
+    }
+
+    /// Returns the stats recorded so far. Compressors that were never used are omitted.
+    pub(crate) fn snapshot(&self) -> CompressionStats {
+        let mut by_compressor = HashMap::new();
+        for (id, stats) in self.by_compressor.iter().enumerate() {
+            let stats = stats.snapshot();
+            if stats == CompressorStats::default() {
+                continue;
+            }
+            if let Ok(id) = CompressorId::from_u8(id as u8) {
+                by_compressor.insert(id.name().to_string(), stats);
+            }
+        }
+        CompressionStats {
+            by_compressor,
+            uncompressed: self.uncompressed.snapshot(),
+        }
+    }
+}
+
+/// The counters of a `CompressorStats`.
+#[derive(Debug, Default)]
+struct AtomicCompressorStats {
+    bytes_sent: AtomicU64,
+    bytes_sent_uncompressed: AtomicU64,
+    bytes_received: AtomicU64,
+    bytes_received_uncompressed: AtomicU64,
+}
+
+impl AtomicCompressorStats {
+    fn snapshot(&self) -> CompressorStats {
+        CompressorStats {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_sent_uncompressed: self.bytes_sent_uncompressed.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            bytes_received_uncompressed: self.bytes_received_uncompressed.load(Ordering::Relaxed),
+        }
+    }
+}
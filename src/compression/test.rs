@@ -121,3 +121,47 @@ async fn send_ping_with_compression(client_options: ClientOptions) {
     let ret = ret.unwrap();
     assert_eq!(ret.get("ok"), Some(Bson::Double(1.0)).as_ref());
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[cfg(feature = "zstd-compression")]
+#[function_name::named]
+async fn compression_stats_with_zstd() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut client_options = CLIENT_OPTIONS.get().await.clone();
+    client_options.compressors = Some(vec![Compressor::Zstd { level: None }]);
+    let client = TestClient::with_options(Some(client_options)).await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let docs: Vec<bson::Document> = (0..100)
+        .map(|i| doc! { "i": i, "text": "compressible ".repeat(100) })
+        .collect();
+    coll.insert_many(docs, None).await.unwrap();
+
+    let stats = client.compression_stats();
+    let zstd = match stats.by_compressor.get("zstd") {
+        Some(zstd) => zstd,
+        None => {
+            crate::test::log_uncaptured(format!(
+                "skipping {} due to the server not supporting zstd",
+                function_name!()
+            ));
+            return;
+        }
+    };
+    assert!(zstd.bytes_sent < zstd.bytes_sent_uncompressed, "{:?}", zstd);
+    assert!(zstd.sent_ratio() < 0.5, "{:?}", zstd);
+    assert!(zstd.bytes_received > 0, "{:?}", zstd);
+
+    // The handshake can't be compressed, so its traffic is counted as uncompressed.
+    let uncompressed = &stats.uncompressed;
+    assert!(uncompressed.bytes_sent > 0, "{:?}", uncompressed);
+    assert_eq!(
+        uncompressed.bytes_sent,
+        uncompressed.bytes_sent_uncompressed
+    );
+    assert_eq!(uncompressed.sent_ratio(), 1.0);
+}
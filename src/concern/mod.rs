@@ -27,6 +27,10 @@ use crate::{
 pub struct ReadConcern {
     /// The level of the read concern.
     pub level: ReadConcernLevel,
+
+    /// The cluster time at which a "snapshot" read concern reads data. See
+    /// [`ReadConcern::snapshot_at`].
+    pub at_cluster_time: Option<Timestamp>,
 }
 
 /// An internal-only read concern type that allows the omission of a "level" as well as
@@ -78,6 +82,21 @@ impl ReadConcern {
         ReadConcernLevel::Snapshot.into()
     }
 
+    /// Creates a read concern with level "snapshot" that reads data as it was at the cluster time
+    /// `at_cluster_time`, e.g. the `operationTime` of an earlier operation. This can be used to
+    /// read a consistent view of data across multiple operations without using a session with
+    /// [`SessionOptions::snapshot`](crate::options::SessionOptions::snapshot).
+    ///
+    /// Reading at a cluster time outside of a transaction is only supported on MongoDB 5.0+. The
+    /// server returns an error if `at_cluster_time` is older than the history it retains (see the
+    /// `minSnapshotHistoryWindowInSeconds` server parameter) or newer than its current time.
+    pub fn snapshot_at(at_cluster_time: Timestamp) -> Self {
+        Self {
+            level: ReadConcernLevel::Snapshot,
+            at_cluster_time: Some(at_cluster_time),
+        }
+    }
+
     /// Creates a read concern with a custom read concern level. This is present to provide forwards
     /// compatibility with any future read concerns which may be added to new versions of
     /// MongoDB.
@@ -109,7 +128,7 @@ impl From<ReadConcern> for ReadConcernInternal {
     fn from(rc: ReadConcern) -> Self {
         ReadConcernInternal {
             level: Some(rc.level),
-            at_cluster_time: None,
+            at_cluster_time: rc.at_cluster_time,
             after_cluster_time: None,
         }
     }
@@ -117,7 +136,10 @@ impl From<ReadConcern> for ReadConcernInternal {
 
 impl From<ReadConcernLevel> for ReadConcern {
     fn from(level: ReadConcernLevel) -> Self {
-        Self { level }
+        Self {
+            level,
+            at_cluster_time: None,
+        }
     }
 }
 
@@ -225,8 +247,9 @@ pub struct WriteConcern {
 pub enum Acknowledgment {
     /// Requires acknowledgement that the write has reached the specified number of nodes.
     ///
-    /// Note: specifying 0 here indicates that the write concern is unacknowledged, which is
-    /// currently unsupported and will result in an error during operation execution.
+    /// Note: specifying 0 here indicates that the write concern is unacknowledged. Unacknowledged
+    /// writes are sent without a session, are never retried, and can't be run with an explicit
+    /// `ClientSession`.
     Nodes(u32),
 
     /// Requires acknowledgement that the write has reached the majority of nodes.
@@ -285,10 +308,30 @@ impl From<String> for Acknowledgment {
 }
 
 impl WriteConcern {
+    /// Creates a write concern with `w: "majority"` and the given `w_timeout`, so that a write
+    /// returns a write concern error rather than blocking indefinitely if a majority of nodes
+    /// can't acknowledge it within `w_timeout`.
+    pub fn majority_with_timeout(w_timeout: Duration) -> Self {
+        Self {
+            w: Some(Acknowledgment::Majority),
+            w_timeout: Some(w_timeout),
+            journal: None,
+        }
+    }
+
     pub(crate) fn is_acknowledged(&self) -> bool {
         self.w != Some(Acknowledgment::Nodes(0)) || self.journal == Some(true)
     }
 
+    /// Sets `w_timeout` to `default` if it isn't specified, provided that `w` is specified and the
+    /// write concern is acknowledged. A write concern without `w` is left as is so that the
+    /// server's default write concern is applied unchanged.
+    pub(crate) fn apply_default_w_timeout(&mut self, default: Duration) {
+        if self.w.is_some() && self.is_acknowledged() {
+            self.w_timeout.get_or_insert(default);
+        }
+    }
+
     /// Whether the write concern was created with no values specified. If true, the write concern
     /// should be considered the server's default.
     pub(crate) fn is_empty(&self) -> bool {
@@ -1,9 +1,11 @@
 use std::time::Duration;
-use tokio::sync::RwLockReadGuard;
+
+use futures::TryStreamExt;
+use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
-    bson::{doc, Bson, Document},
-    error::ErrorKind,
+    bson::{doc, Bson, Document, Timestamp},
+    error::{Error, ErrorKind, WriteFailure},
     options::{
         Acknowledgment,
         AggregateOptions,
@@ -14,15 +16,18 @@ use crate::{
         FindOneAndReplaceOptions,
         FindOneAndUpdateOptions,
         FindOneOptions,
+        FindOptions,
         InsertManyOptions,
         InsertOneOptions,
         ReadConcern,
         ReplaceOptions,
+        SelectionCriteria,
         TransactionOptions,
         UpdateOptions,
         WriteConcern,
     },
-    test::{EventClient, TestClient, LOCK},
+    sdam::ServerType,
+    test::{log_uncaptured, EventClient, FailPoint, TestClient, CLIENT_OPTIONS, LOCK, SERVERLESS},
     Collection,
 };
 
@@ -106,6 +111,22 @@ fn write_concern_deserialize() {
     );
 }
 
+#[test]
+fn read_concern_snapshot_at_serialize() {
+    let ts = Timestamp {
+        time: 100,
+        increment: 1,
+    };
+    assert_eq!(
+        bson::to_document(&ReadConcern::snapshot_at(ts)).unwrap(),
+        doc! { "level": "snapshot", "atClusterTime": ts }
+    );
+    assert_eq!(
+        bson::to_document(&ReadConcern::snapshot()).unwrap(),
+        doc! { "level": "snapshot" }
+    );
+}
+
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
@@ -144,23 +165,29 @@ async fn inconsistent_write_concern_rejected() {
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn unacknowledged_write_concern_rejected() {
+async fn unacknowledged_write_concern_accepted() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
     let client = TestClient::new().await;
     let db = client.database(function_name!());
-    let error = db
-        .run_command(
-            doc! {
-                "insert": function_name!(),
-                "documents": [ {} ],
-                "writeConcern": { "w": 0 }
-            },
-            None,
-        )
+    db.run_command(
+        doc! {
+            "insert": function_name!(),
+            "documents": [ {} ],
+            "writeConcern": { "w": 0 }
+        },
+        None,
+    )
+    .await
+    .expect("unacknowledged insert via run_command should succeed");
+
+    let coll = db.collection::<Document>(function_name!());
+    let options = InsertOneOptions::builder()
+        .write_concern(WriteConcern::builder().w(Acknowledgment::Nodes(0)).build())
+        .build();
+    coll.insert_one(doc! { "x": 1 }, options)
         .await
-        .expect_err("insert should fail");
-    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+        .expect("unacknowledged insert_one should succeed");
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
@@ -936,3 +963,198 @@ fn command_write_concerns(client: &EventClient, key: &str) -> Vec<Document> {
         .map(|d| d.command.get_document("writeConcern").unwrap().clone())
         .collect()
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn snapshot_read_at_cluster_time() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(5, 0) || client.is_standalone() || *SERVERLESS {
+        log_uncaptured(
+            "skipping snapshot_read_at_cluster_time due to lack of support for snapshot reads",
+        );
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let options = InsertOneOptions::builder()
+        .write_concern(WriteConcern::builder().w(Acknowledgment::Majority).build())
+        .build();
+    let mut session = client.start_session(None).await.unwrap();
+    coll.insert_one_with_session(doc! { "x": 1 }, options.clone(), &mut session)
+        .await
+        .unwrap();
+    let cluster_time = session.operation_time().unwrap();
+    coll.insert_one(doc! { "x": 2 }, options).await.unwrap();
+
+    let options = FindOptions::builder()
+        .read_concern(ReadConcern::snapshot_at(cluster_time))
+        .projection(doc! { "_id": 0 })
+        .build();
+    let docs: Vec<Document> = coll
+        .find(None, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(docs, vec![doc! { "x": 1 }]);
+
+    // The server doesn't retain history this far back.
+    let options = FindOptions::builder()
+        .read_concern(ReadConcern::snapshot_at(Timestamp {
+            time: 1,
+            increment: 0,
+        }))
+        .build();
+    assert!(coll.find(None, options).await.is_err());
+}
+
+#[test]
+fn majority_with_timeout() {
+    let write_concern = WriteConcern::majority_with_timeout(Duration::from_millis(500));
+    assert_eq!(
+        write_concern,
+        WriteConcern::builder()
+            .w(Acknowledgment::Majority)
+            .w_timeout(Duration::from_millis(500))
+            .build()
+    );
+    assert_eq!(
+        bson::to_document(&write_concern).unwrap(),
+        doc! { "w": "majority", "wtimeout": 500 }
+    );
+}
+
+#[test]
+fn apply_default_w_timeout() {
+    let default = Duration::from_millis(500);
+
+    let mut majority = WriteConcern::builder().w(Acknowledgment::Majority).build();
+    majority.apply_default_w_timeout(default);
+    assert_eq!(majority, WriteConcern::majority_with_timeout(default));
+
+    let mut with_timeout = WriteConcern::majority_with_timeout(Duration::from_millis(250));
+    with_timeout.apply_default_w_timeout(default);
+    assert_eq!(with_timeout.w_timeout, Some(Duration::from_millis(250)));
+
+    let mut unacknowledged = WriteConcern::builder().w(Acknowledgment::Nodes(0)).build();
+    unacknowledged.apply_default_w_timeout(default);
+    assert_eq!(unacknowledged.w_timeout, None);
+
+    let mut without_w = WriteConcern::builder().journal(true).build();
+    without_w.apply_default_w_timeout(default);
+    assert_eq!(without_w.w_timeout, None);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))] // multi_thread required for FailPoint
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn default_w_timeout() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let setup_client = TestClient::new().await;
+    if !setup_client.is_replica_set() || setup_client.server_version_lt(4, 0) || *SERVERLESS {
+        log_uncaptured("skipping default_w_timeout due to test configuration");
+        return;
+    }
+    let secondaries: Vec<_> = setup_client
+        .topology_description()
+        .servers
+        .into_values()
+        .filter(|server| server.server_type == ServerType::RsSecondary)
+        .map(|server| server.address)
+        .collect();
+    if secondaries.is_empty() {
+        log_uncaptured("skipping default_w_timeout due to test configuration");
+        return;
+    }
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.default_w_timeout = Some(Duration::from_millis(500));
+    let client = EventClient::with_options(options).await;
+    let coll: Collection<Document> = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // Writes that don't specify `w` are left to the server's default write concern.
+    coll.insert_one(doc! { "_id": 0 }, None).await.unwrap();
+    let inserts = client.get_command_started_events(&["insert"]);
+    assert!(!inserts[0].command.contains_key("writeConcern"));
+    client.clear_cached_events();
+
+    // Stop replication so that no secondary can acknowledge a majority write.
+    let mut guards = Vec::new();
+    for address in secondaries {
+        let fail_point: FailPoint = bson::from_document(doc! {
+            "configureFailPoint": "stopReplProducer",
+            "mode": "alwaysOn",
+        })
+        .unwrap();
+        guards.push(
+            fail_point
+                .enable(&setup_client, SelectionCriteria::from_address(address))
+                .await
+                .unwrap(),
+        );
+    }
+
+    let majority = WriteConcern::builder().w(Acknowledgment::Majority).build();
+    let options = InsertOneOptions::builder()
+        .write_concern(majority.clone())
+        .build();
+    let error = coll
+        .insert_one(doc! { "_id": 1 }, options)
+        .await
+        .unwrap_err();
+    assert_write_concern_timeout(&error);
+
+    // A timeout specified on the write concern takes precedence over the default.
+    let options = InsertOneOptions::builder()
+        .write_concern(WriteConcern::majority_with_timeout(Duration::from_millis(
+            250,
+        )))
+        .build();
+    let error = coll
+        .insert_one(doc! { "_id": 2 }, options)
+        .await
+        .unwrap_err();
+    assert_write_concern_timeout(&error);
+
+    assert_eq!(
+        command_write_concerns(&client, "insert"),
+        vec![
+            doc! { "w": "majority", "wtimeout": 500 },
+            doc! { "w": "majority", "wtimeout": 250 },
+        ]
+    );
+
+    drop(guards);
+
+    // The writes were applied even though their write concern wasn't satisfied.
+    let ids: Vec<Document> = coll
+        .find(None, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        ids,
+        vec![doc! { "_id": 0 }, doc! { "_id": 1 }, doc! { "_id": 2 }]
+    );
+}
+
+fn assert_write_concern_timeout(error: &Error) {
+    match *error.kind {
+        ErrorKind::Write(WriteFailure::WriteConcernError(ref wc_error)) => {
+            // WriteConcernFailed
+            assert_eq!(wc_error.code, 64, "{:?}", wc_error);
+        }
+        _ => panic!("expected write concern error, got {:?}", error),
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     collections::VecDeque,
+    convert::TryFrom,
     marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
@@ -14,12 +15,19 @@ use serde::{de::DeserializeOwned, Deserialize};
 use tokio::sync::oneshot;
 
 use crate::{
-    bson::Document,
+    bson::{Bson, Document},
+    bson_util,
     change_stream::event::ResumeToken,
     cmap::conn::PinnedConnectionHandle,
     error::{Error, ErrorKind, Result},
+    event::cursor::{
+        CursorExhaustedEvent,
+        CursorGetMoreEvent,
+        CursorKilledEvent,
+        CursorOpenedEvent,
+    },
     operation,
-    options::ServerAddress,
+    options::{NumericCoercion, ServerAddress},
     results::GetMoreResult,
     runtime,
     Client,
@@ -40,6 +48,7 @@ where
     /// This is an `Option` to allow it to be "taken" when the cursor is no longer needed
     /// but may be resumed in the future for `SessionCursor`.
     state: Option<CursorState>,
+    batch_size_tuner: Option<BatchSizeTuner>,
     _phantom: PhantomData<T>,
 }
 
@@ -54,6 +63,7 @@ where
         get_more_provider: P,
     ) -> Self {
         let exhausted = spec.id() == 0;
+        emit_opened_events(&client, &spec.info, exhausted);
         Self {
             client,
             provider: get_more_provider,
@@ -63,7 +73,9 @@ where
                 exhausted,
                 post_batch_resume_token: None,
                 pinned_connection,
+                get_more_count: 0,
             }),
+            batch_size_tuner: None,
             _phantom: Default::default(),
         }
     }
@@ -80,6 +92,7 @@ where
             info,
             _phantom: Default::default(),
             state: state.into(),
+            batch_size_tuner: None,
         }
     }
 
@@ -145,6 +158,10 @@ where
         &self.info.ns
     }
 
+    pub(super) fn info(&self) -> &CursorInformation {
+        &self.info
+    }
+
     pub(super) fn address(&self) -> &ServerAddress {
         &self.info.address
     }
@@ -153,10 +170,33 @@ where
         &self.state().pinned_connection
     }
 
+    pub(super) fn get_more_count(&self) -> u32 {
+        self.state().get_more_count
+    }
+
     pub(super) fn post_batch_resume_token(&self) -> Option<&ResumeToken> {
         self.state().post_batch_resume_token.as_ref()
     }
 
+    /// Adjust the `batchSize` of subsequent getMores so that each batch is close to
+    /// `target_bytes`, starting from the sizes of the documents currently buffered.
+    pub(super) fn tune_batch_size(&mut self, target_bytes: usize) {
+        let mut tuner = BatchSizeTuner::new(target_bytes);
+        if let Some(batch_size) = tuner.observe(self.state().buffer.as_ref()) {
+            self.info.batch_size = Some(batch_size);
+        }
+        self.batch_size_tuner = Some(tuner);
+    }
+
+    pub(super) fn set_values_from_id(&mut self) {
+        self.info.values_from_id = true;
+    }
+
+    #[cfg(test)]
+    pub(super) fn batch_size(&self) -> Option<u32> {
+        self.info.batch_size
+    }
+
     fn mark_exhausted(&mut self) {
         self.state_mut().exhausted = true;
         self.state_mut().pinned_connection = PinnedConnection::Unpinned;
@@ -165,8 +205,31 @@ where
     fn handle_get_more_result(&mut self, get_more_result: Result<GetMoreResult>) -> Result<()> {
         match get_more_result {
             Ok(get_more) => {
+                self.state_mut().get_more_count += 1;
+                let get_more_count = self.state().get_more_count;
+                self.client.emit_cursor_event(|handler| {
+                    handler.handle_cursor_get_more_event(CursorGetMoreEvent {
+                        cursor_id: self.info.id,
+                        namespace: self.info.ns.clone(),
+                        batch_size: get_more.batch.len(),
+                        get_more_count,
+                    });
+                });
+
                 if get_more.exhausted {
                     self.mark_exhausted();
+                    self.client.emit_cursor_event(|handler| {
+                        handler.handle_cursor_exhausted_event(CursorExhaustedEvent {
+                            cursor_id: self.info.id,
+                            namespace: self.info.ns.clone(),
+                            get_more_count,
+                        });
+                    });
+                }
+                if let Some(ref mut tuner) = self.batch_size_tuner {
+                    if let Some(batch_size) = tuner.observe(&get_more.batch) {
+                        self.info.batch_size = Some(batch_size);
+                    }
                 }
                 self.state_mut().buffer = CursorBuffer::new(get_more.batch);
                 self.state_mut().post_batch_resume_token = get_more.post_batch_resume_token;
@@ -202,6 +265,7 @@ where
             provider: self.provider,
             info: self.info,
             state: self.state,
+            batch_size_tuner: self.batch_size_tuner,
             _phantom: Default::default(),
         }
     }
@@ -209,6 +273,27 @@ where
 
 pub(crate) trait CursorStream {
     fn poll_next_in_batch(&mut self, cx: &mut Context<'_>) -> Poll<Result<BatchValue>>;
+
+    /// Whether documents with fields that aren't present in the type they're deserialized to
+    /// should produce an error rather than having those fields ignored.
+    fn deny_unknown_fields(&self) -> bool {
+        false
+    }
+
+    /// How BSON doubles should be converted when deserialized into integer fields, if at all.
+    fn numeric_coercion(&self) -> Option<NumericCoercion> {
+        None
+    }
+
+    /// Whether only the `_id` of each document should be deserialized and returned.
+    fn values_from_id(&self) -> bool {
+        false
+    }
+
+    /// The maximum nesting depth of documents that may be deserialized, if limited.
+    fn max_document_depth(&self) -> Option<u32> {
+        None
+    }
 }
 
 pub(crate) enum BatchValue {
@@ -255,6 +340,22 @@ where
             None => Poll::Ready(Ok(BatchValue::Exhausted)),
         }
     }
+
+    fn deny_unknown_fields(&self) -> bool {
+        self.info.deny_unknown_fields
+    }
+
+    fn numeric_coercion(&self) -> Option<NumericCoercion> {
+        self.info.numeric_coercion
+    }
+
+    fn values_from_id(&self) -> bool {
+        self.info.values_from_id
+    }
+
+    fn max_document_depth(&self) -> Option<u32> {
+        self.info.max_document_depth
+    }
 }
 
 // To avoid a private trait (`CursorStream`) in a public interface (`impl Stream`), this is provided
@@ -269,7 +370,13 @@ where
             Poll::Pending => return Poll::Pending,
             Poll::Ready(bv) => match bv? {
                 BatchValue::Some { doc, .. } => {
-                    return Poll::Ready(Some(Ok(bson::from_slice(doc.as_bytes())?)))
+                    return Poll::Ready(Some(deserialize_cursor_document(
+                        &doc,
+                        this.deny_unknown_fields(),
+                        this.numeric_coercion(),
+                        this.values_from_id(),
+                        this.max_document_depth(),
+                    )))
                 }
                 BatchValue::Empty => continue,
                 BatchValue::Exhausted => return Poll::Ready(None),
@@ -278,6 +385,88 @@ where
     }
 }
 
+/// Deserializes a document returned by a cursor as [`deserialize_document`] does, or only its
+/// `_id` field if `values_from_id` is true. If `max_document_depth` is set, an error is returned
+/// without deserializing the document if it is nested more deeply than that.
+pub(crate) fn deserialize_cursor_document<'a, V>(
+    doc: &'a RawDocument,
+    deny_unknown_fields: bool,
+    numeric_coercion: Option<NumericCoercion>,
+    values_from_id: bool,
+    max_document_depth: Option<u32>,
+) -> Result<V>
+where
+    V: Deserialize<'a>,
+{
+    #[derive(Deserialize)]
+    struct IdValue<U> {
+        #[serde(rename = "_id")]
+        value: U,
+    }
+
+    if let Some(max_depth) = max_document_depth {
+        bson_util::check_document_depth(doc, max_depth)?;
+    }
+
+    if values_from_id {
+        deserialize_document::<IdValue<V>>(doc, deny_unknown_fields, numeric_coercion)
+            .map(|id| id.value)
+    } else {
+        deserialize_document(doc, deny_unknown_fields, numeric_coercion)
+    }
+}
+
+/// Deserializes a document returned by a cursor. If `deny_unknown_fields` is true, an error is
+/// returned if the document contains any fields that `V` doesn't, including in nested documents.
+/// If `numeric_coercion` is set, BSON doubles are converted when deserialized into integer fields.
+pub(crate) fn deserialize_document<'a, V>(
+    doc: &'a RawDocument,
+    deny_unknown_fields: bool,
+    numeric_coercion: Option<NumericCoercion>,
+) -> Result<V>
+where
+    V: Deserialize<'a>,
+{
+    if !deny_unknown_fields && numeric_coercion.is_none() {
+        return bson_util::from_raw_document_with_path(doc);
+    }
+
+    // The raw deserializer isn't public, so the document is deserialized from an owned copy.
+    let doc = Bson::Document(Document::try_from(doc)?);
+    match numeric_coercion {
+        Some(mode) if deny_unknown_fields => {
+            deserialize_denying_unknown_fields(bson_util::CoercingDeserializer::new(doc, mode))
+        }
+        Some(mode) => {
+            bson_util::from_deserializer_with_path(bson_util::CoercingDeserializer::new(doc, mode))
+        }
+        None => deserialize_denying_unknown_fields(bson::Deserializer::new(doc)),
+    }
+}
+
+fn deserialize_denying_unknown_fields<'de, D, V>(deserializer: D) -> Result<V>
+where
+    D: serde::Deserializer<'de, Error = bson::de::Error>,
+    V: Deserialize<'de>,
+{
+    let mut unknown_fields = Vec::new();
+    let mut callback = |path: serde_ignored::Path| unknown_fields.push(path.to_string());
+    let value = bson_util::from_deserializer_with_path(serde_ignored::Deserializer::new(
+        deserializer,
+        &mut callback,
+    ))?;
+    if !unknown_fields.is_empty() {
+        return Err(
+            ErrorKind::BsonDeserialization(serde::de::Error::custom(format!(
+                "unknown fields: {}",
+                unknown_fields.join(", ")
+            )))
+            .into(),
+        );
+    }
+    Ok(value)
+}
+
 pub(crate) struct NextInBatchFuture<'a, T>(&'a mut T);
 
 impl<'a, T> NextInBatchFuture<'a, T>
@@ -374,6 +563,7 @@ impl CursorSpecification {
         address: ServerAddress,
         batch_size: impl Into<Option<u32>>,
         max_time: impl Into<Option<Duration>>,
+        comment: impl Into<Option<Bson>>,
     ) -> Self {
         Self {
             info: CursorInformation {
@@ -382,12 +572,29 @@ impl CursorSpecification {
                 address,
                 batch_size: batch_size.into(),
                 max_time: max_time.into(),
+                comment: comment.into(),
+                deny_unknown_fields: false,
+                numeric_coercion: None,
+                values_from_id: false,
+                max_document_depth: None,
             },
             initial_buffer: info.first_batch,
             post_batch_resume_token: ResumeToken::from_raw(info.post_batch_resume_token),
         }
     }
 
+    /// Creates the specification of an already-exhausted cursor whose only batch is `docs`, sorted
+    /// by their `name` field. This is used when the results of a listing operation have been
+    /// buffered in full so that they can be returned in a deterministic order.
+    pub(crate) fn sorted_by_name(info: CursorInformation, mut docs: Vec<RawDocumentBuf>) -> Self {
+        docs.sort_by(|a, b| a.get_str("name").ok().cmp(&b.get_str("name").ok()));
+        Self {
+            info: CursorInformation { id: 0, ..info },
+            initial_buffer: docs.into(),
+            post_batch_resume_token: None,
+        }
+    }
+
     pub(crate) fn id(&self) -> i64 {
         self.info.id
     }
@@ -406,6 +613,11 @@ impl CursorSpecification {
     pub(crate) fn max_time(&self) -> Option<Duration> {
         self.info.max_time
     }
+
+    #[cfg(test)]
+    pub(crate) fn comment(&self) -> Option<&Bson> {
+        self.info.comment.as_ref()
+    }
 }
 
 /// Static information about a cursor.
@@ -416,6 +628,49 @@ pub(crate) struct CursorInformation {
     pub(crate) id: i64,
     pub(crate) batch_size: Option<u32>,
     pub(crate) max_time: Option<Duration>,
+    pub(crate) comment: Option<Bson>,
+    pub(crate) deny_unknown_fields: bool,
+    pub(crate) numeric_coercion: Option<NumericCoercion>,
+    /// Whether each document returned by the cursor has the form `{ _id: <value> }`, and only the
+    /// value should be returned, e.g. for the results of a `$group` stage.
+    pub(crate) values_from_id: bool,
+    pub(crate) max_document_depth: Option<u32>,
+}
+
+/// Estimates the average size of the documents returned by a cursor to pick a getMore `batchSize`
+/// that keeps each batch close to a target size in bytes.
+#[derive(Clone, Debug)]
+pub(crate) struct BatchSizeTuner {
+    target_bytes: usize,
+    avg_doc_bytes: Option<f64>,
+}
+
+impl BatchSizeTuner {
+    pub(crate) fn new(target_bytes: usize) -> Self {
+        Self {
+            target_bytes,
+            avg_doc_bytes: None,
+        }
+    }
+
+    /// Update the estimated document size with the documents in `batch` and return the batch size
+    /// to use for the next getMore, if there is enough information to pick one.
+    pub(crate) fn observe(&mut self, batch: &VecDeque<RawDocumentBuf>) -> Option<u32> {
+        if !batch.is_empty() {
+            let batch_bytes: usize = batch.iter().map(|doc| doc.as_bytes().len()).sum();
+            let batch_avg = batch_bytes as f64 / batch.len() as f64;
+            // Weigh the latest batch equally with the history so that the estimate follows
+            // changes in document size quickly without being dominated by a single batch.
+            self.avg_doc_bytes = Some(match self.avg_doc_bytes {
+                Some(avg) => (avg + batch_avg) / 2.0,
+                None => batch_avg,
+            });
+        }
+        self.avg_doc_bytes.map(|avg| {
+            let batch_size = (self.target_bytes as f64 / avg).floor();
+            batch_size.clamp(1.0, i32::MAX as f64) as u32
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -466,14 +721,41 @@ impl PinnedConnection {
     }
 }
 
+/// Emits the events describing the creation of a cursor. A cursor whose results were all returned
+/// in the initial batch is exhausted as soon as it is opened.
+pub(super) fn emit_opened_events(client: &Client, info: &CursorInformation, exhausted: bool) {
+    client.emit_cursor_event(|handler| {
+        handler.handle_cursor_opened_event(CursorOpenedEvent {
+            cursor_id: info.id,
+            namespace: info.ns.clone(),
+            address: info.address.clone(),
+        });
+        if exhausted {
+            handler.handle_cursor_exhausted_event(CursorExhaustedEvent {
+                cursor_id: info.id,
+                namespace: info.ns.clone(),
+                get_more_count: 0,
+            });
+        }
+    });
+}
+
 pub(super) fn kill_cursor(
     client: Client,
     ns: &Namespace,
     cursor_id: i64,
+    get_more_count: u32,
     pinned_conn: PinnedConnection,
     drop_address: Option<ServerAddress>,
     #[cfg(test)] kill_watcher: Option<oneshot::Sender<()>>,
 ) {
+    client.emit_cursor_event(|handler| {
+        handler.handle_cursor_killed_event(CursorKilledEvent {
+            cursor_id,
+            namespace: ns.clone(),
+            get_more_count,
+        });
+    });
     let coll = client
         .database(ns.db.as_str())
         .collection::<Document>(ns.coll.as_str());
@@ -496,6 +778,7 @@ pub(crate) struct CursorState {
     pub(crate) exhausted: bool,
     pub(crate) post_batch_resume_token: Option<ResumeToken>,
     pub(crate) pinned_connection: PinnedConnection,
+    pub(crate) get_more_count: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -10,9 +10,9 @@ use std::{
 
 use bson::RawDocument;
 
-#[cfg(test)]
 use bson::RawDocumentBuf;
 use futures_core::{future::BoxFuture, Stream};
+use futures_util::TryStreamExt;
 use serde::{de::DeserializeOwned, Deserialize};
 #[cfg(test)]
 use tokio::sync::oneshot;
@@ -23,12 +23,15 @@ use crate::{
     cmap::conn::PinnedConnectionHandle,
     error::{Error, Result},
     operation::GetMore,
+    options::NumericCoercion,
     results::GetMoreResult,
     Client,
     ClientSession,
 };
-use common::{kill_cursor, GenericCursor, GetMoreProvider, GetMoreProviderResult};
+#[cfg(test)]
+pub(crate) use common::deserialize_document;
 pub(crate) use common::{
+    deserialize_cursor_document,
     stream_poll_next,
     BatchValue,
     CursorInformation,
@@ -37,6 +40,7 @@ pub(crate) use common::{
     NextInBatchFuture,
     PinnedConnection,
 };
+use common::{kill_cursor, GenericCursor, GetMoreProvider, GetMoreProviderResult};
 
 /// A [`Cursor`] streams the result of a query. When a query is made, the returned [`Cursor`] will
 /// contain the first batch of results from the server; the individual results will then be returned
@@ -153,6 +157,28 @@ impl<T> Cursor<T> {
         self.drop_address = Some(address);
     }
 
+    pub(crate) fn tune_batch_size(&mut self, target_bytes: usize) {
+        self.wrapped_cursor
+            .as_mut()
+            .unwrap()
+            .tune_batch_size(target_bytes);
+    }
+
+    /// Makes the cursor return only the `_id` of each document.
+    pub(crate) fn set_values_from_id(&mut self) {
+        self.wrapped_cursor.as_mut().unwrap().set_values_from_id();
+    }
+
+    /// Buffers all of the remaining results and returns an exhausted cursor that yields them sorted
+    /// by their `name` field.
+    pub(crate) async fn sorted_by_name(self) -> Result<Self> {
+        let client = self.client.clone();
+        let info = self.wrapped_cursor.as_ref().unwrap().info().clone();
+        let docs: Vec<RawDocumentBuf> = self.with_type::<RawDocumentBuf>().try_collect().await?;
+        let spec = CursorSpecification::sorted_by_name(info, docs);
+        Ok(Self::new(client, spec, None, None))
+    }
+
     pub(crate) fn take_implicit_session(&mut self) -> Option<ClientSession> {
         self.wrapped_cursor
             .as_mut()
@@ -243,7 +269,14 @@ impl<T> Cursor<T> {
     where
         T: Deserialize<'a>,
     {
-        bson::from_slice(self.current().as_bytes()).map_err(Error::from)
+        let cursor = self.wrapped_cursor.as_ref().unwrap();
+        deserialize_cursor_document(
+            self.current(),
+            cursor.deny_unknown_fields(),
+            cursor.numeric_coercion(),
+            cursor.values_from_id(),
+            cursor.max_document_depth(),
+        )
     }
 
     /// Update the type streamed values will be parsed as.
@@ -288,6 +321,22 @@ where
     fn poll_next_in_batch(&mut self, cx: &mut Context<'_>) -> Poll<Result<BatchValue>> {
         self.wrapped_cursor.as_mut().unwrap().poll_next_in_batch(cx)
     }
+
+    fn deny_unknown_fields(&self) -> bool {
+        self.wrapped_cursor.as_ref().unwrap().deny_unknown_fields()
+    }
+
+    fn numeric_coercion(&self) -> Option<NumericCoercion> {
+        self.wrapped_cursor.as_ref().unwrap().numeric_coercion()
+    }
+
+    fn values_from_id(&self) -> bool {
+        self.wrapped_cursor.as_ref().unwrap().values_from_id()
+    }
+
+    fn max_document_depth(&self) -> Option<u32> {
+        self.wrapped_cursor.as_ref().unwrap().max_document_depth()
+    }
 }
 
 impl<T> Stream for Cursor<T>
@@ -316,6 +365,7 @@ impl<T> Drop for Cursor<T> {
             self.client.clone(),
             wrapped_cursor.namespace(),
             wrapped_cursor.id(),
+            wrapped_cursor.get_more_count(),
             wrapped_cursor.pinned_connection().replicate(),
             self.drop_address.take(),
             #[cfg(test)]
@@ -4,15 +4,16 @@ use std::{
     task::{Context, Poll},
 };
 
-use bson::RawDocument;
+use bson::{RawDocument, RawDocumentBuf};
 use futures_core::{future::BoxFuture, Stream};
-use futures_util::StreamExt;
+use futures_util::{StreamExt, TryStreamExt};
 use serde::{de::DeserializeOwned, Deserialize};
 #[cfg(test)]
 use tokio::sync::oneshot;
 
 use super::{
     common::{
+        emit_opened_events,
         kill_cursor,
         CursorBuffer,
         CursorInformation,
@@ -22,6 +23,7 @@ use super::{
         GetMoreProviderResult,
         PinnedConnection,
     },
+    deserialize_cursor_document,
     BatchValue,
     CursorStream,
 };
@@ -33,6 +35,7 @@ use crate::{
     cursor::CursorSpecification,
     error::{Error, Result},
     operation::GetMore,
+    options::NumericCoercion,
     results::GetMoreResult,
     Client,
     ClientSession,
@@ -83,6 +86,7 @@ impl<T> SessionCursor<T> {
         pinned: Option<PinnedConnectionHandle>,
     ) -> Self {
         let exhausted = spec.info.id == 0;
+        emit_opened_events(&client, &spec.info, exhausted);
 
         Self {
             client,
@@ -96,10 +100,16 @@ impl<T> SessionCursor<T> {
                 exhausted,
                 post_batch_resume_token: None,
                 pinned_connection: PinnedConnection::new(pinned),
+                get_more_count: 0,
             }
             .into(),
         }
     }
+
+    /// Makes the cursor return only the `_id` of each document.
+    pub(crate) fn set_values_from_id(&mut self) {
+        self.info.values_from_id = true;
+    }
 }
 
 impl<T> SessionCursor<T>
@@ -296,7 +306,13 @@ impl<T> SessionCursor<T> {
     where
         T: Deserialize<'a>,
     {
-        bson::from_slice(self.current().as_bytes()).map_err(Error::from)
+        deserialize_cursor_document(
+            self.current(),
+            self.info.deny_unknown_fields,
+            self.info.numeric_coercion,
+            self.info.values_from_id,
+            self.info.max_document_depth,
+        )
     }
 
     /// Update the type streamed values will be parsed as.
@@ -325,6 +341,17 @@ impl<T> SessionCursor<T> {
         self.drop_address = Some(address);
     }
 
+    /// Buffers all of the remaining results and returns an exhausted cursor that yields them sorted
+    /// by their `name` field.
+    pub(crate) async fn sorted_by_name(self, session: &mut ClientSession) -> Result<Self> {
+        let client = self.client.clone();
+        let info = self.info.clone();
+        let mut cursor = self.with_type::<RawDocumentBuf>();
+        let docs: Vec<RawDocumentBuf> = cursor.stream(session).try_collect().await?;
+        let spec = CursorSpecification::sorted_by_name(info, docs);
+        Ok(Self::new(client, spec, None))
+    }
+
     /// Some tests need to be able to observe the events generated by `killCommand` execution;
     /// however, because that happens asynchronously on `drop`, the test runner can conclude before
     /// the event is published.  To fix that, tests can set a "kill watcher" on cursors - a
@@ -360,6 +387,7 @@ impl<T> Drop for SessionCursor<T> {
             self.client.clone(),
             &self.info.ns,
             self.info.id,
+            self.state.as_ref().unwrap().get_more_count,
             self.state.as_ref().unwrap().pinned_connection.replicate(),
             self.drop_address.take(),
             #[cfg(test)]
@@ -414,6 +442,22 @@ where
     fn poll_next_in_batch(&mut self, cx: &mut Context<'_>) -> Poll<Result<BatchValue>> {
         self.generic_cursor.poll_next_in_batch(cx)
     }
+
+    fn deny_unknown_fields(&self) -> bool {
+        self.generic_cursor.deny_unknown_fields()
+    }
+
+    fn numeric_coercion(&self) -> Option<NumericCoercion> {
+        self.generic_cursor.numeric_coercion()
+    }
+
+    fn values_from_id(&self) -> bool {
+        self.generic_cursor.values_from_id()
+    }
+
+    fn max_document_depth(&self) -> Option<u32> {
+        self.generic_cursor.max_document_depth()
+    }
 }
 
 impl<'cursor, 'session, T> Drop for SessionCursorStream<'cursor, 'session, T> {
@@ -5,9 +5,11 @@ use std::{fmt::Debug, sync::Arc};
 #[cfg(feature = "csfle")]
 use bson::doc;
 use futures_util::stream::TryStreamExt;
+use serde::de::DeserializeOwned;
 
 use crate::{
-    bson::{Bson, Document},
+    bson::{Bson, Document, RawDocumentBuf},
+    bson_util,
     change_stream::{
         event::ChangeStreamEvent,
         options::ChangeStreamOptions,
@@ -18,23 +20,40 @@ use crate::{
     cmap::conn::PinnedConnectionHandle,
     concern::{ReadConcern, WriteConcern},
     cursor::Cursor,
-    error::{Error, ErrorKind, Result},
+    error::{Error, ErrorKind, InsertStreamError, Result},
     gridfs::{
         options::GridFsBucketOptions,
         GridFsBucket,
         DEFAULT_BUCKET_NAME,
         DEFAULT_CHUNK_SIZE_BYTES,
     },
-    operation::{Aggregate, AggregateTarget, Create, DropDatabase, ListCollections, RunCommand},
+    operation::{
+        Aggregate,
+        AggregateTarget,
+        Create,
+        DbStats,
+        DropDatabase,
+        ListCollections,
+        RunCommand,
+    },
     options::{
         AggregateOptions,
         CollectionOptions,
+        CopyCollectionOptions,
         CreateCollectionOptions,
+        CreateViewOptions,
         DatabaseOptions,
+        DatabaseStatsOptions,
+        DropCollectionOptions,
         DropDatabaseOptions,
+        FindOptions,
+        InsertManyOptions,
         ListCollectionsOptions,
+        ProfilingLevel,
+        ProfilingOptions,
+        ServerApi,
     },
-    results::CollectionSpecification,
+    results::{CollectionSpecification, DatabaseStats, InsertManyResult, ProfileEntry},
     selection_criteria::SelectionCriteria,
     Client,
     ClientSession,
@@ -109,6 +128,11 @@ impl Database {
             .write_concern
             .or_else(|| client.write_concern().cloned());
 
+        let client = match options.server_api {
+            Some(server_api) => client.with_server_api(server_api),
+            None => client,
+        };
+
         Self {
             inner: Arc::new(DatabaseInner {
                 client,
@@ -145,6 +169,11 @@ impl Database {
         self.inner.write_concern.as_ref()
     }
 
+    /// Gets the stable server API declared on the `Database`'s commands.
+    pub fn server_api(&self) -> Option<&ServerApi> {
+        self.client().server_api()
+    }
+
     /// Gets a handle to a collection in this database with the provided name. The
     /// [`Collection`] options (e.g. read preference and write concern) will default to those of
     /// this [`Database`].
@@ -211,15 +240,22 @@ impl Database {
         filter: impl Into<Option<Document>>,
         options: impl Into<Option<ListCollectionsOptions>>,
     ) -> Result<Cursor<CollectionSpecification>> {
-        let list_collections = ListCollections::new(
-            self.name().to_string(),
-            filter.into(),
-            false,
-            options.into(),
-        );
-        self.client()
+        let options = options.into();
+        let sort_by_name = options
+            .as_ref()
+            .and_then(|o| o.sort_by_name)
+            .unwrap_or(false);
+        let list_collections =
+            ListCollections::new(self.name().to_string(), filter.into(), false, options);
+        let cursor = self
+            .client()
             .execute_cursor_operation(list_collections)
-            .await
+            .await?;
+        if sort_by_name {
+            cursor.sorted_by_name().await
+        } else {
+            Ok(cursor)
+        }
     }
 
     /// Gets information about each of the collections in the database using the provided
@@ -231,15 +267,22 @@ impl Database {
         options: impl Into<Option<ListCollectionsOptions>>,
         session: &mut ClientSession,
     ) -> Result<SessionCursor<CollectionSpecification>> {
-        let list_collections = ListCollections::new(
-            self.name().to_string(),
-            filter.into(),
-            false,
-            options.into(),
-        );
-        self.client()
-            .execute_session_cursor_operation(list_collections, session)
-            .await
+        let options = options.into();
+        let sort_by_name = options
+            .as_ref()
+            .and_then(|o| o.sort_by_name)
+            .unwrap_or(false);
+        let list_collections =
+            ListCollections::new(self.name().to_string(), filter.into(), false, options);
+        let cursor = self
+            .client()
+            .execute_session_cursor_operation(list_collections, &mut *session)
+            .await?;
+        if sort_by_name {
+            cursor.sorted_by_name(session).await
+        } else {
+            Ok(cursor)
+        }
     }
 
     async fn list_collection_names_common(
@@ -293,6 +336,98 @@ impl Database {
             .await
     }
 
+    /// Gets storage statistics for the database via the `dbStats` command.
+    ///
+    /// If a `scale` is given in `options`, every size in the result is divided by it, including
+    /// the average document size which the server itself always reports in bytes.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/dbStats/)
+    /// for more information.
+    pub async fn stats(
+        &self,
+        options: impl Into<Option<DatabaseStatsOptions>>,
+    ) -> Result<DatabaseStats> {
+        let mut options = options.into();
+        resolve_options!(self, options, [selection_criteria]);
+
+        let op = DbStats::new(self.name().to_string(), options);
+        self.client().execute_operation(op, None).await
+    }
+
+    #[allow(clippy::needless_option_as_deref)]
+    async fn drop_all_collections_common(
+        &self,
+        options: Option<DropCollectionOptions>,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<()> {
+        let list_collections = ListCollections::new(self.name().to_string(), None, true, None);
+        let specs: Vec<Document> = match session.as_deref_mut() {
+            Some(s) => {
+                let mut cursor: SessionCursor<Document> = self
+                    .client()
+                    .execute_session_cursor_operation(list_collections, &mut *s)
+                    .await?;
+                cursor.stream(s).try_collect().await?
+            }
+            None => {
+                let cursor: Cursor<Document> = self
+                    .client()
+                    .execute_cursor_operation(list_collections)
+                    .await?;
+                cursor.try_collect().await?
+            }
+        };
+
+        // Views are dropped before the collections they may be defined on, and system collections
+        // (including the buckets of time series collections) are left to the server.
+        let mut names: Vec<(bool, &str)> = specs
+            .iter()
+            .filter_map(|spec| {
+                let name = spec.get_str("name").ok()?;
+                let is_view = spec.get_str("type").ok() == Some("view");
+                Some((!is_view, name))
+            })
+            .filter(|(_, name)| !name.starts_with("system."))
+            .collect();
+        names.sort_unstable();
+
+        for (_, name) in names {
+            let coll = self.collection::<Document>(name);
+            match session.as_deref_mut() {
+                Some(s) => coll.drop_with_session(options.clone(), s).await?,
+                None => coll.drop(options.clone()).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops every non-system collection and view in the database, leaving the database itself
+    /// and its system collections in place. This is mainly useful for setting up or tearing down
+    /// tests.
+    ///
+    /// Views are dropped before collections. Collections that are dropped concurrently by another
+    /// client are ignored.
+    pub async fn drop_all_collections(
+        &self,
+        options: impl Into<Option<DropCollectionOptions>>,
+    ) -> Result<()> {
+        self.drop_all_collections_common(options.into(), None).await
+    }
+
+    /// Drops every non-system collection and view in the database using the provided
+    /// `ClientSession`, leaving the database itself and its system collections in place.
+    ///
+    /// Views are dropped before collections. Collections that are dropped concurrently by another
+    /// client are ignored.
+    pub async fn drop_all_collections_with_session(
+        &self,
+        options: impl Into<Option<DropCollectionOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<()> {
+        self.drop_all_collections_common(options.into(), Some(session))
+            .await
+    }
+
     #[allow(clippy::needless_option_as_deref)]
     async fn create_collection_common(
         &self,
@@ -408,6 +543,50 @@ impl Database {
         self.create_collection_common(name, options, session).await
     }
 
+    /// Creates a new view in the database with the given `name` that runs `pipeline` against the
+    /// collection or view named `view_on`.
+    ///
+    /// The source does not need to exist when the view is created; until it does, queries against
+    /// the view return no documents.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/core/views/) for more
+    /// information on views.
+    pub async fn create_view(
+        &self,
+        name: impl AsRef<str>,
+        view_on: impl Into<String>,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<CreateViewOptions>>,
+    ) -> Result<()> {
+        let options = CreateCollectionOptions::from_view_options(
+            view_on.into(),
+            pipeline.into_iter().collect(),
+            options.into(),
+        );
+        self.create_collection_common(name, options, None).await
+    }
+
+    /// Creates a new view in the database with the given `name` that runs `pipeline` against the
+    /// collection or view named `view_on` using the provided `ClientSession`.
+    ///
+    /// The source does not need to exist when the view is created; until it does, queries against
+    /// the view return no documents.
+    pub async fn create_view_with_session(
+        &self,
+        name: impl AsRef<str>,
+        view_on: impl Into<String>,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<CreateViewOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<()> {
+        let options = CreateCollectionOptions::from_view_options(
+            view_on.into(),
+            pipeline.into_iter().collect(),
+            options.into(),
+        );
+        self.create_collection_common(name, options, session).await
+    }
+
     pub(crate) async fn run_command_common(
         &self,
         command: Document,
@@ -479,6 +658,105 @@ impl Database {
             .await
     }
 
+    /// Sets the level of the database profiler for this database via the `profile` command.
+    /// Profiled operations are recorded in the database's `system.profile` collection.
+    ///
+    /// On a sharded cluster, mongos does not record profiling data, but the slow operation
+    /// threshold and sample rate still apply to its diagnostic log.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/profile/)
+    /// for more information.
+    pub async fn set_profiling_level(
+        &self,
+        level: ProfilingLevel,
+        options: impl Into<Option<ProfilingOptions>>,
+    ) -> Result<()> {
+        let mut command = doc! { "profile": level.as_i32() };
+        if let Some(options) = options.into() {
+            command.extend(bson::to_document(&options)?);
+        }
+        self.run_command(command, None).await?;
+        Ok(())
+    }
+
+    /// Gets the current level of the database profiler for this database via the `profile`
+    /// command.
+    pub async fn profiling_level(&self) -> Result<ProfilingLevel> {
+        let response = self.run_command(doc! { "profile": -1 }, None).await?;
+        response
+            .get("was")
+            .and_then(bson_util::get_int)
+            .and_then(ProfilingLevel::from_i64)
+            .ok_or_else(|| {
+                ErrorKind::InvalidResponse {
+                    message: format!("invalid profile response: {}", response),
+                }
+                .into()
+            })
+    }
+
+    /// Queries the entries recorded by the database profiler in this database's `system.profile`
+    /// collection. The entries are returned in the order the profiler recorded them unless a sort
+    /// is specified in `options`.
+    ///
+    /// If the profiler has never been enabled for this database (see
+    /// [`Database::set_profiling_level`]), the returned cursor is empty.
+    pub async fn profile_entries(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> Result<Cursor<ProfileEntry>> {
+        self.collection::<ProfileEntry>("system.profile")
+            .find(filter, options)
+            .await
+    }
+
+    /// Copies the documents in the collection named `from` into the collection named `to` in this
+    /// database. The source is read with [`Collection::find_streaming`] and the documents are
+    /// inserted with [`Collection::insert_stream`], so the copy is done in batches without loading
+    /// the whole collection into memory. A filter and projection can be applied to the copied
+    /// documents via `options`.
+    ///
+    /// Indexes and collection options are not copied. If an insert into the target collection
+    /// fails (e.g. because the target has a unique index that the source documents violate),
+    /// copying stops and the returned [`InsertStreamError`] contains both the error and the
+    /// documents that were copied before it occurred.
+    pub async fn copy_collection(
+        &self,
+        from: &str,
+        to: &str,
+        options: impl Into<Option<CopyCollectionOptions>>,
+    ) -> std::result::Result<InsertManyResult, InsertStreamError> {
+        let options = options.into().unwrap_or_default();
+        let not_started = |error| InsertStreamError {
+            inserted: InsertManyResult::new(),
+            error,
+        };
+        if from == to {
+            return Err(not_started(
+                ErrorKind::InvalidArgument {
+                    message: format!("cannot copy collection {} into itself", from),
+                }
+                .into(),
+            ));
+        }
+
+        let find_options = FindOptions::builder()
+            .projection(options.projection)
+            .build();
+        let cursor = self
+            .collection::<RawDocumentBuf>(from)
+            .find_streaming(options.filter, find_options)
+            .await
+            .map_err(not_started)?;
+        let insert_options = InsertManyOptions::builder()
+            .write_concern(options.write_concern)
+            .build();
+        self.collection::<RawDocumentBuf>(to)
+            .insert_stream(cursor, insert_options)
+            .await
+    }
+
     /// Runs an aggregation operation.
     ///
     /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
@@ -524,6 +802,47 @@ impl Database {
             .await
     }
 
+    /// Runs a database-level aggregation operation (e.g. one beginning with a `$currentOp` or
+    /// `$documents` stage) that is expected to produce at most one document, deserializing the
+    /// result as `R`. `Ok(None)` is returned if the pipeline produces no documents.
+    ///
+    /// If the pipeline produces more than one document, only the first one is returned and the
+    /// rest are discarded.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
+    /// information on aggregations.
+    pub async fn aggregate_one<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let mut cursor = self.aggregate(pipeline, options).await?.with_type::<R>();
+        cursor.try_next().await
+    }
+
+    /// Runs a database-level aggregation operation that is expected to produce at most one
+    /// document using the provided `ClientSession`, deserializing the result as `R`. See
+    /// [`Database::aggregate_one`] for more details.
+    pub async fn aggregate_one_with_session<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let mut cursor = self
+            .aggregate_with_session(pipeline, options, session)
+            .await?
+            .with_type::<R>();
+        let mut cursor = cursor.stream(session);
+        cursor.try_next().await
+    }
+
     /// Starts a new [`ChangeStream`](change_stream/struct.ChangeStream.html) that receives events
     /// for all changes in this database. The stream does not observe changes from system
     /// collections and cannot be started on "config", "local" or "admin" databases.
@@ -9,7 +9,7 @@ use crate::{
     bson::Document,
     bson_util,
     concern::{ReadConcern, WriteConcern},
-    options::Collation,
+    options::{Collation, ServerApi},
     selection_criteria::SelectionCriteria,
 };
 
@@ -27,6 +27,19 @@ pub struct DatabaseOptions {
 
     /// The default write concern for operations.
     pub write_concern: Option<WriteConcern>,
+
+    /// The stable server API to declare on the commands run via this database and its
+    /// collections, including getMores on their cursors, in place of the one from the client
+    /// options. This allows an application to migrate to the stable API one database at a time.
+    ///
+    /// The client doesn't need to declare a server API. If it does, its version must match this
+    /// one, since connections are established with the one from the client options, or
+    /// operations will fail with an
+    /// [`ErrorKind::InvalidArgument`](crate::error::ErrorKind::InvalidArgument) error. The
+    /// settings of this option (e.g. [`ServerApi::strict`]) take precedence over the client's,
+    /// including on commitTransaction and abortTransaction for transactions whose first operation
+    /// is run via this database.
+    pub server_api: Option<ServerApi>,
 }
 
 /// These are the valid options for creating a collection with
@@ -116,6 +129,38 @@ pub struct CreateCollectionOptions {
     pub encrypted_fields: Option<Document>,
 }
 
+/// These are the valid options for creating a view with
+/// [`Database::create_view`](../struct.Database.html#method.create_view).
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, TypedBuilder, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct CreateViewOptions {
+    /// The default collation for the view.
+    pub collation: Option<Collation>,
+
+    /// The write concern for the operation.
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl CreateCollectionOptions {
+    pub(crate) fn from_view_options(
+        view_on: String,
+        pipeline: Vec<Document>,
+        options: Option<CreateViewOptions>,
+    ) -> Self {
+        let options = options.unwrap_or_default();
+        Self {
+            view_on: Some(view_on),
+            pipeline: Some(pipeline),
+            collation: options.collation,
+            write_concern: options.write_concern,
+            ..Default::default()
+        }
+    }
+}
+
 /// Specifies how strictly the database should apply validation rules to existing documents during
 /// an update.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -218,6 +263,16 @@ pub enum TimeseriesGranularity {
     Hours,
 }
 
+impl TimeseriesGranularity {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Seconds => "seconds",
+            Self::Minutes => "minutes",
+            Self::Hours => "hours",
+        }
+    }
+}
+
 /// Specifies the options to a [`Database::drop`](../struct.Database.html#method.drop) operation.
 #[derive(Clone, Debug, Default, TypedBuilder, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -246,6 +301,31 @@ pub struct ListCollectionsOptions {
         rename(serialize = "cursor")
     )]
     pub batch_size: Option<u32>,
+
+    /// If true, the results are sorted by collection name. The server does not guarantee any
+    /// particular order, so this can be used for stable, reproducible iteration.
+    ///
+    /// Sorting is performed by the driver: all of the results are fetched before the returned
+    /// cursor yields the first one. Names are compared byte-wise, so e.g. "coll10" sorts before
+    /// "coll2".
+    #[serde(skip)]
+    pub sort_by_name: Option<bool>,
+}
+
+/// Specifies the options to a [`Database::stats`](../struct.Database.html#method.stats)
+/// operation.
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct DatabaseStatsOptions {
+    /// The factor to divide the sizes in the result by, e.g. 1024 to report them in kibibytes.
+    /// Sizes are rounded down. Must be greater than 0; defaults to 1, i.e. bytes.
+    pub scale: Option<u32>,
+
+    /// The criteria used to select a server for this operation.
+    ///
+    /// If none specified, the default set on the database will be used.
+    pub selection_criteria: Option<SelectionCriteria>,
 }
 
 /// Specifies the options to a
@@ -269,3 +349,89 @@ pub struct ChangeStreamPreAndPostImages {
     /// If `true`, change streams will be able to include pre- and post-images.
     pub enabled: bool,
 }
+
+/// The level of the database profiler, as set by
+/// [`Database::set_profiling_level`](../struct.Database.html#method.set_profiling_level).
+///
+/// See the documentation [here](https://www.mongodb.com/docs/manual/tutorial/manage-the-database-profiler/)
+/// for more information on the database profiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProfilingLevel {
+    /// The profiler is off and does not collect any data.
+    Off,
+    /// The profiler collects data for operations that are slower than the configured threshold
+    /// or that match the configured filter.
+    SlowOperations,
+    /// The profiler collects data for all operations.
+    All,
+}
+
+impl ProfilingLevel {
+    pub(crate) fn as_i32(self) -> i32 {
+        match self {
+            Self::Off => 0,
+            Self::SlowOperations => 1,
+            Self::All => 2,
+        }
+    }
+
+    pub(crate) fn from_i64(level: i64) -> Option<Self> {
+        match level {
+            0 => Some(Self::Off),
+            1 => Some(Self::SlowOperations),
+            2 => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// Specifies the options to a
+/// [`Database::set_profiling_level`](../struct.Database.html#method.set_profiling_level)
+/// operation.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, TypedBuilder, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct ProfilingOptions {
+    /// Operations that take longer than this threshold are considered slow. The server's default
+    /// is 100 milliseconds.
+    ///
+    /// Note that this threshold also determines which operations are written to the diagnostic
+    /// log, regardless of the profiling level, so it may be useful to set it even when setting
+    /// the level to [`ProfilingLevel::Off`].
+    #[serde(
+        rename = "slowms",
+        serialize_with = "bson_util::serialize_duration_option_as_int_millis"
+    )]
+    pub slow_operation_threshold: Option<Duration>,
+
+    /// The fraction of slow operations that should be profiled or logged, between 0.0 and 1.0.
+    pub sample_rate: Option<f64>,
+
+    /// A filter expression that determines which operations are profiled or logged. If set, the
+    /// filter is used instead of the slow operation threshold and sample rate.
+    ///
+    /// This option is only supported on server versions 4.4.2+.
+    pub filter: Option<Document>,
+}
+
+/// Specifies the options to a
+/// [`Database::copy_collection`](../struct.Database.html#method.copy_collection) operation.
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+#[non_exhaustive]
+pub struct CopyCollectionOptions {
+    /// A filter selecting which documents to copy. If unset, every document in the source
+    /// collection is copied.
+    pub filter: Option<Document>,
+
+    /// A projection applied to the documents before they are inserted into the target collection.
+    /// If the projection excludes `_id`, new ids are generated for the copies.
+    pub projection: Option<Document>,
+
+    /// The write concern used for the inserts into the target collection. If unset, the
+    /// database's write concern is used.
+    pub write_concern: Option<WriteConcern>,
+}
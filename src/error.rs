@@ -3,6 +3,7 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::{self, Debug},
+    ops::RangeInclusive,
     sync::Arc,
 };
 
@@ -10,7 +11,7 @@ use bson::Bson;
 use serde::Deserialize;
 use thiserror::Error;
 
-use crate::{bson::Document, options::ServerAddress};
+use crate::{bson::Document, bson_util, options::ServerAddress, results::InsertManyResult};
 
 const RECOVERING_CODES: [i32; 5] = [11600, 11602, 13436, 189, 91];
 const NOTWRITABLEPRIMARY_CODES: [i32; 3] = [10107, 13435, 10058];
@@ -21,6 +22,8 @@ const RETRYABLE_WRITE_CODES: [i32; 12] = [
     11600, 11602, 10107, 13435, 13436, 189, 91, 7, 6, 89, 9001, 262,
 ];
 const UNKNOWN_TRANSACTION_COMMIT_RESULT_LABEL_CODES: [i32; 3] = [50, 64, 91];
+pub(crate) const DUPLICATE_KEY_CODE: i32 = 11000;
+const DOCUMENT_VALIDATION_FAILURE_CODE: i32 = 121;
 
 /// Retryable write error label. This label will be added to an error when the error is
 /// write-retryable.
@@ -255,7 +258,7 @@ impl Error {
             ErrorKind::BulkWrite(BulkWriteFailure {
                 write_concern_error,
                 write_errors,
-                inserted_ids: _,
+                ..
             }) => {
                 let mut msg = "".to_string();
                 if let Some(wc_error) = write_concern_error {
@@ -275,7 +278,7 @@ impl Error {
                 Some(write_error.message.clone())
             }
             ErrorKind::Transaction { message } => Some(message.clone()),
-            ErrorKind::IncompatibleServer { message } => Some(message.clone()),
+            ErrorKind::IncompatibleServer { message, .. } => Some(message.clone()),
             _ => None,
         }
     }
@@ -311,26 +314,91 @@ impl Error {
             .unwrap_or(false)
     }
 
-    /// If this error corresponds to a "node is shutting down" error as per the SDAM spec.
-    pub(crate) fn is_shutting_down(&self) -> bool {
+    /// Whether this error indicates that the server is shutting down (error codes 11600 and 91),
+    /// e.g. during a rolling restart. The driver marks such a server as unknown and clears its
+    /// connection pool; applications may want to back off before retrying rather than retrying
+    /// immediately.
+    ///
+    /// This is only true for errors reported by the server; network errors caused by the server
+    /// closing connections while shutting down do not have an error code and return false.
+    pub fn is_shutdown_in_progress(&self) -> bool {
         self.code()
             .map(|code| SHUTTING_DOWN_CODES.contains(&code))
             .unwrap_or(false)
     }
 
+    /// Whether this error indicates that the server rejected a command because it is not part of
+    /// the declared stable API version (error code 323, `APIStrictError`). This can only happen
+    /// when [`ServerApi::strict`](crate::options::ServerApi::strict) is set, and means the
+    /// operation must be replaced with one in the stable API or run without `strict`.
+    ///
+    /// Other command errors, including those for commands that fail for unrelated reasons while
+    /// `strict` is set, return false.
+    pub fn is_api_strict_violation(&self) -> bool {
+        matches!(self.kind.as_ref(), ErrorKind::Command(ref err) if err.code == 323)
+    }
+
+    /// Whether this error indicates that a write was attempted against a namespace that does not
+    /// support writes, i.e. a view (error code 166, `CommandNotSupportedOnView`). Views are
+    /// read-only, so inserts, updates, deletes, and aggregations with `$out` or `$merge` targeting
+    /// a view all fail with this error.
+    pub fn is_read_only_namespace(&self) -> bool {
+        const COMMAND_NOT_SUPPORTED_ON_VIEW: i32 = 166;
+        match self.kind.as_ref() {
+            ErrorKind::Write(WriteFailure::WriteError(ref err)) => {
+                err.code == COMMAND_NOT_SUPPORTED_ON_VIEW
+            }
+            ErrorKind::BulkWrite(ref failure) => failure
+                .write_errors
+                .iter()
+                .flatten()
+                .any(|err| err.code == COMMAND_NOT_SUPPORTED_ON_VIEW),
+            _ => self.code() == Some(COMMAND_NOT_SUPPORTED_ON_VIEW),
+        }
+    }
+
+    /// Whether this error indicates that a read at a point in time failed because the server no
+    /// longer has the history needed to serve it (error code 239, `SnapshotTooOld`). This can
+    /// happen to snapshot reads, e.g. those of
+    /// [`Collection::snapshot_export`](crate::Collection::snapshot_export), that run for longer
+    /// than the server's `minSnapshotHistoryWindowInSeconds`; the read must then be restarted
+    /// at a more recent point in time.
+    pub fn is_snapshot_too_old(&self) -> bool {
+        self.code() == Some(239)
+    }
+
+    /// Whether this error indicates that a write violated a unique index.
+    pub(crate) fn is_duplicate_key_error(&self) -> bool {
+        match self.kind.as_ref() {
+            ErrorKind::Write(WriteFailure::WriteError(ref err)) => err.code == DUPLICATE_KEY_CODE,
+            ErrorKind::BulkWrite(ref failure) => failure
+                .write_errors
+                .iter()
+                .flatten()
+                .any(|err| err.code == DUPLICATE_KEY_CODE),
+            _ => self.code() == Some(DUPLICATE_KEY_CODE),
+        }
+    }
+
     pub(crate) fn is_pool_cleared(&self) -> bool {
         matches!(self.kind.as_ref(), ErrorKind::ConnectionPoolCleared { .. })
     }
 
+    /// Whether this error indicates that a cursor no longer exists on the server, e.g. because it
+    /// timed out or was killed.
+    pub(crate) fn is_cursor_not_found(&self) -> bool {
+        self.code() == Some(43)
+    }
+
     /// If this error is resumable as per the change streams spec.
     pub(crate) fn is_resumable(&self) -> bool {
         if !self.is_server_error() {
             return true;
         }
-        let code = self.code();
-        if code == Some(43) {
+        if self.is_cursor_not_found() {
             return true;
         }
+        let code = self.code();
         if matches!(self.wire_version, Some(v) if v >= 9)
             && self.contains_label("ResumableChangeStreamError")
         {
@@ -483,9 +551,22 @@ pub enum ErrorKind {
     Transaction { message: String },
 
     /// The server does not support the operation.
+    ///
+    /// If the server's range of supported wire versions does not overlap with the driver's (i.e.
+    /// the server is too old for this version of the driver, or too new), `driver_range` and
+    /// `server_range` contain the respective ranges and `message` describes whether the server or
+    /// the driver needs to be upgraded. Both are `None` for other incompatibilities.
     #[error("The server does not support a database operation: {message}")]
     #[non_exhaustive]
-    IncompatibleServer { message: String },
+    IncompatibleServer {
+        message: String,
+
+        /// The range of wire versions supported by the driver.
+        driver_range: Option<RangeInclusive<i32>>,
+
+        /// The range of wire versions supported by the server.
+        server_range: Option<RangeInclusive<i32>>,
+    },
 
     /// No resume token was present in a change stream document.
     #[error("Cannot provide resume functionality when the resume token is missing")]
@@ -615,6 +696,137 @@ pub struct BulkWriteError {
     pub details: Option<Document>,
 }
 
+impl WriteError {
+    /// If this error occurred because the document failed validation against the collection's
+    /// `$jsonSchema` validator, returns the rules that the document did not satisfy. The server
+    /// only reports these details on 5.0+, so `None` is returned for older servers.
+    pub fn schema_validation_error(&self) -> Option<SchemaValidationError> {
+        SchemaValidationError::parse(self.code, self.details.as_ref())
+    }
+}
+
+impl BulkWriteError {
+    /// If this error occurred because the document failed validation against the collection's
+    /// `$jsonSchema` validator, returns the rules that the document did not satisfy. The server
+    /// only reports these details on 5.0+, so `None` is returned for older servers.
+    pub fn schema_validation_error(&self) -> Option<SchemaValidationError> {
+        SchemaValidationError::parse(self.code, self.details.as_ref())
+    }
+}
+
+/// The details of why a document failed validation against a collection's `$jsonSchema`
+/// validator, as returned by [`WriteError::schema_validation_error`] and
+/// [`BulkWriteError::schema_validation_error`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct SchemaValidationError {
+    /// The `_id` of the document that failed validation.
+    pub failing_document_id: Option<Bson>,
+
+    /// The `title` of the schema, if it specifies one.
+    pub title: Option<String>,
+
+    /// The rules of the schema that the document did not satisfy. Rules nested within the
+    /// `properties`, `items` and logical keywords of the schema are reported individually, each
+    /// with the path of the field it applies to.
+    pub violations: Vec<SchemaViolation>,
+}
+
+/// A single rule of a `$jsonSchema` validator that a document did not satisfy.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct SchemaViolation {
+    /// The schema keyword that was not satisfied, e.g. `bsonType` or `required`.
+    pub keyword: String,
+
+    /// The dotted path of the field that the keyword applies to, with array elements identified
+    /// by their index, e.g. `address.zip` or `tags.2`. This is empty for keywords that apply to
+    /// the document itself, such as a top-level `required`.
+    pub path: String,
+
+    /// The server's explanation of why the rule was not satisfied, if it provided one.
+    pub reason: Option<String>,
+
+    /// The server's full report for the rule, which contains further keyword-specific fields such
+    /// as `consideredValue`, `specifiedAs` or `missingProperties`.
+    pub details: Document,
+}
+
+impl SchemaValidationError {
+    fn parse(code: i32, err_info: Option<&Document>) -> Option<Self> {
+        if code != DOCUMENT_VALIDATION_FAILURE_CODE {
+            return None;
+        }
+        let err_info = err_info?;
+        let schema = find_json_schema_details(err_info.get_document("details").ok()?)?;
+
+        let mut violations = Vec::new();
+        if let Ok(rules) = schema.get_array("schemaRulesNotSatisfied") {
+            collect_schema_violations(rules, "", &mut violations);
+        }
+        Some(Self {
+            failing_document_id: err_info.get("failingDocumentId").cloned(),
+            title: schema.get_str("title").ok().map(String::from),
+            violations,
+        })
+    }
+}
+
+/// Finds the report for the `$jsonSchema` operator, which may be nested within the clauses of a
+/// validator that combines it with query operators.
+fn find_json_schema_details(details: &Document) -> Option<&Document> {
+    if details.get_str("operatorName") == Ok("$jsonSchema") {
+        return Some(details);
+    }
+    details
+        .get_array("clausesNotSatisfied")
+        .ok()?
+        .iter()
+        .filter_map(|clause| clause.as_document()?.get_document("details").ok())
+        .find_map(find_json_schema_details)
+}
+
+fn collect_schema_violations(rules: &[Bson], path: &str, violations: &mut Vec<SchemaViolation>) {
+    let join = |component: String| {
+        if path.is_empty() {
+            component
+        } else {
+            format!("{}.{}", path, component)
+        }
+    };
+
+    for rule in rules.iter().filter_map(Bson::as_document) {
+        if let Ok(properties) = rule.get_array("propertiesNotSatisfied") {
+            for property in properties.iter().filter_map(Bson::as_document) {
+                if let (Ok(name), Ok(details)) = (
+                    property.get_str("propertyName"),
+                    property.get_array("details"),
+                ) {
+                    collect_schema_violations(details, &join(name.to_string()), violations);
+                }
+            }
+        } else if let Ok(schemas) = rule.get_array("schemasNotSatisfied") {
+            for schema in schemas.iter().filter_map(Bson::as_document) {
+                if let Ok(details) = schema.get_array("details") {
+                    collect_schema_violations(details, path, violations);
+                }
+            }
+        } else if let (Some(index), Ok(details)) = (
+            rule.get("itemIndex").and_then(bson_util::get_int),
+            rule.get_array("details"),
+        ) {
+            collect_schema_violations(details, &join(index.to_string()), violations);
+        } else {
+            violations.push(SchemaViolation {
+                keyword: rule.get_str("operatorName").unwrap_or_default().to_string(),
+                path: path.to_string(),
+                reason: rule.get_str("reason").ok().map(String::from),
+                details: rule.clone(),
+            });
+        }
+    }
+}
+
 /// The set of errors that occurred during a write operation.
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -626,6 +838,13 @@ pub struct BulkWriteFailure {
     /// The error that occurred on account of write concern failure.
     pub write_concern_error: Option<WriteConcernError>,
 
+    /// The full replies returned by the server for the `insert` commands of an
+    /// [`insert_many`](crate::Collection::insert_many) that failed, in the order they were sent.
+    /// This includes the replies to the commands that succeeded, and may include fields not
+    /// otherwise exposed (e.g. `opTime` or `electionId`).
+    #[serde(skip)]
+    pub raw_replies: Vec<Document>,
+
     #[serde(skip)]
     pub(crate) inserted_ids: HashMap<usize, Bson>,
 }
@@ -635,9 +854,87 @@ impl BulkWriteFailure {
         BulkWriteFailure {
             write_errors: None,
             write_concern_error: None,
+            raw_replies: Vec::new(),
             inserted_ids: Default::default(),
         }
     }
+
+    /// Shifts the indexes in this failure by `offset`. The server reports indexes relative to the
+    /// start of the command that failed, so this is used to make them relative to the start of
+    /// the whole write when it was split into multiple commands.
+    pub(crate) fn offset_indexes(&mut self, offset: usize) {
+        for write_error in self.write_errors.iter_mut().flatten() {
+            write_error.index += offset;
+        }
+        self.inserted_ids = std::mem::take(&mut self.inserted_ids)
+            .into_iter()
+            .map(|(index, id)| (index + offset, id))
+            .collect();
+    }
+
+    /// Merges the failure of a batch that started at index `batch_start` of the whole write into
+    /// this one.
+    pub(crate) fn merge_batch(&mut self, mut batch: BulkWriteFailure, batch_start: usize) {
+        batch.offset_indexes(batch_start);
+        if let Some(write_errors) = batch.write_errors {
+            self.write_errors
+                .get_or_insert_with(Default::default)
+                .extend(write_errors);
+        }
+        if let Some(wc_error) = batch.write_concern_error {
+            self.write_concern_error = Some(wc_error);
+        }
+        self.raw_replies.extend(batch.raw_replies);
+        self.inserted_ids.extend(batch.inserted_ids);
+    }
+}
+
+/// An error that occurred partway through a
+/// [`Collection::insert_stream`](../struct.Collection.html#method.insert_stream) operation, either
+/// because the input stream produced an error or because an insert failed.
+#[derive(Debug, Error)]
+#[error("{error}")]
+#[non_exhaustive]
+pub struct InsertStreamError {
+    /// The documents that were inserted before the error occurred, including the documents of
+    /// the batch whose insert failed that were inserted anyway (e.g. the ones before or, for an
+    /// unordered insert, after a duplicate key). See the `BulkWrite` error for which documents
+    /// failed.
+    pub inserted: InsertManyResult,
+
+    /// The error that stopped the insert.
+    #[source]
+    pub error: Error,
+}
+
+impl From<InsertStreamError> for Error {
+    fn from(err: InsertStreamError) -> Self {
+        err.error
+    }
+}
+
+/// The error returned when one of the `findAndModify` commands sent by
+/// [`Collection::find_many_and_delete`](../struct.Collection.html#method.find_many_and_delete)
+/// fails. Since the documents are deleted one at a time, the ones deleted by earlier commands are
+/// already gone from the collection, so they're returned here rather than being lost.
+#[derive(Debug, Error)]
+#[error("{error}")]
+#[non_exhaustive]
+pub struct FindManyAndDeleteError<T> {
+    /// The documents deleted by the commands that succeeded, in the order they were deleted. The
+    /// document targeted by the failed command is not included, even if the error (e.g. a network
+    /// error) occurred after the server deleted it.
+    pub deleted: Vec<T>,
+
+    /// The error returned by the failed `findAndModify` command.
+    #[source]
+    pub error: Error,
+}
+
+impl<T> From<FindManyAndDeleteError<T>> for Error {
+    fn from(err: FindManyAndDeleteError<T>) -> Self {
+        err.error
+    }
 }
 
 /// An error that occurred when trying to execute a write operation.
@@ -0,0 +1,179 @@
+//! Contains a bounded in-memory buffer for capturing events emitted by a `Client`.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use super::command::{
+    CommandEvent,
+    CommandEventHandler,
+    CommandFailedEvent,
+    CommandStartedEvent,
+    CommandSucceededEvent,
+};
+
+/// A bounded, thread-safe buffer of events. Once the buffer holds `capacity` events, each new
+/// event causes the oldest one to be dropped, and the number of events dropped this way is
+/// reported by [`EventBuffer::dropped_count`]. This allows events to be captured from a
+/// long-running or high-throughput application without unbounded memory growth.
+///
+/// Cloning an `EventBuffer` returns a handle to the same underlying buffer, so one handle can be
+/// registered with a `Client` while another is used to read the captured events.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// #
+/// # use mongodb::{
+/// #     error::Result,
+/// #     event::{buffer::EventBuffer, command::CommandEvent},
+/// #     options::ClientOptions,
+/// # };
+/// # #[cfg(any(feature = "sync", feature = "tokio-sync"))]
+/// # use mongodb::sync::Client;
+/// # #[cfg(all(not(feature = "sync"), not(feature = "tokio-sync")))]
+/// # use mongodb::Client;
+/// #
+/// # fn do_stuff() -> Result<()> {
+/// let buffer: EventBuffer<CommandEvent> = EventBuffer::new(1000);
+/// let options = ClientOptions::builder()
+///                   .command_event_handler(Arc::new(buffer.clone()))
+///                   .build();
+/// let client = Client::with_options(options)?;
+///
+/// // Do things with the client, then inspect the most recent command events.
+/// for event in buffer.events() {
+///     println!("{:?}", event);
+/// }
+/// println!("{} events were dropped", buffer.dropped_count());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct EventBuffer<T> {
+    inner: Arc<Mutex<BufferInner<T>>>,
+}
+
+#[derive(Debug)]
+struct BufferInner<T> {
+    events: VecDeque<T>,
+    capacity: usize,
+    dropped_count: u64,
+}
+
+impl<T> Clone for EventBuffer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> EventBuffer<T> {
+    /// Creates a new buffer that holds at most `capacity` events. A buffer with a capacity of 0
+    /// drops (and counts) every event that is added to it.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BufferInner {
+                events: VecDeque::with_capacity(capacity),
+                capacity,
+                dropped_count: 0,
+            })),
+        }
+    }
+
+    /// Adds an event to the buffer, dropping the oldest buffered event if the buffer is full.
+    pub fn push(&self, event: T) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.capacity == 0 {
+            inner.dropped_count += 1;
+            return;
+        }
+        if inner.events.len() == inner.capacity {
+            inner.events.pop_front();
+            inner.dropped_count += 1;
+        }
+        inner.events.push_back(event);
+    }
+
+    /// The maximum number of events this buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.inner.lock().unwrap().capacity
+    }
+
+    /// The number of events currently in the buffer.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().events.len()
+    }
+
+    /// Whether the buffer currently holds no events.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The total number of events that have been dropped because the buffer was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.inner.lock().unwrap().dropped_count
+    }
+
+    /// Removes and returns all of the events in the buffer, oldest first. This does not reset
+    /// the dropped count.
+    pub fn drain(&self) -> Vec<T> {
+        self.inner.lock().unwrap().events.drain(..).collect()
+    }
+}
+
+impl<T: Clone> EventBuffer<T> {
+    /// Returns a copy of the events in the buffer, oldest first, without removing them.
+    pub fn events(&self) -> Vec<T> {
+        self.inner.lock().unwrap().events.iter().cloned().collect()
+    }
+}
+
+impl CommandEventHandler for EventBuffer<CommandEvent> {
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        self.push(CommandEvent::Started(event));
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.push(CommandEvent::Succeeded(event));
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        self.push(CommandEvent::Failed(event));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EventBuffer;
+
+    #[test]
+    fn drops_oldest_on_overflow() {
+        let buffer = EventBuffer::new(3);
+        let handle = buffer.clone();
+        for i in 0..10 {
+            handle.push(i);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.dropped_count(), 7);
+        assert_eq!(buffer.events(), vec![7, 8, 9]);
+
+        assert_eq!(buffer.drain(), vec![7, 8, 9]);
+        assert!(buffer.is_empty());
+        buffer.push(10);
+        assert_eq!(buffer.events(), vec![10]);
+        assert_eq!(buffer.dropped_count(), 7);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let buffer = EventBuffer::new(0);
+        for i in 0..5 {
+            buffer.push(i);
+        }
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.dropped_count(), 5);
+        assert_eq!(buffer.events(), Vec::<i32>::new());
+    }
+}
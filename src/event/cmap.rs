@@ -1,7 +1,7 @@
 //! Contains the events and functionality for monitoring behavior of the connection pooling of a
 //! `Client`.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 
@@ -94,6 +94,54 @@ pub struct PoolClosedEvent {
     pub address: ServerAddress,
 }
 
+/// Event emitted when a period during which a connection pool's connection establishment was
+/// saturated ends, i.e. when the pool is no longer establishing its maximum number of connections
+/// concurrently with checkouts waiting for one of them. Saturation that doesn't last long is a
+/// normal result of bursts of activity, so the `duration` should be taken into account when
+/// reacting to these events (e.g. by scaling out).
+///
+/// The current state of a pool can be read via
+/// [`Client::pool_stats`](../../struct.Client.html#method.pool_stats).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct PoolSaturatedEvent {
+    /// The address of the server that the pool's connections will connect to.
+    pub address: ServerAddress,
+
+    /// How long the pool's connection establishment was saturated for.
+    pub duration: Duration,
+
+    /// The maximum number of connections that the pool may be establishing concurrently.
+    pub max_connecting: u32,
+
+    /// The largest number of checkouts that were waiting for a connection at once while the pool
+    /// was saturated.
+    pub max_waiting: u32,
+}
+
+/// A snapshot of the state of a connection pool, as returned by
+/// [`Client::pool_stats`](../../struct.Client.html#method.pool_stats).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct PoolStats {
+    /// The total number of connections managed by the pool, including connections which are
+    /// checked out or still being established.
+    pub total: u32,
+
+    /// The number of established connections that are checked into the pool.
+    pub available: u32,
+
+    /// The number of connections that are currently being established.
+    pub connecting: u32,
+
+    /// The number of checkouts waiting for a connection.
+    pub waiting: u32,
+
+    /// When the pool's connection establishment became saturated, if it currently is. See
+    /// [`PoolSaturatedEvent`] for more details.
+    pub saturated_since: Option<Instant>,
+}
+
 /// Event emitted when a connection is created.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -125,6 +173,17 @@ pub struct ConnectionReadyEvent {
     /// to identify other events related to this connection.
     #[serde(default = "default_connection_id")]
     pub connection_id: u32,
+
+    /// How long it took to establish the connection, from opening the socket (and negotiating
+    /// TLS, if enabled) to the end of the handshake.
+    #[serde(skip)]
+    pub duration: Duration,
+
+    /// How long the connection's handshake took: the initial `hello`, which also negotiates
+    /// compression, followed by authentication if credentials were provided. Comparing this to
+    /// `duration` helps to tell slow authentication apart from slow network connections.
+    #[serde(skip)]
+    pub handshake_duration: Duration,
 }
 
 /// Event emitted when a connection is closed.
@@ -144,6 +203,13 @@ pub struct ConnectionClosedEvent {
 
     /// The reason that the connection was closed.
     pub reason: ConnectionClosedReason,
+
+    /// If the connection was closed because its handshake or authentication failed, how long the
+    /// handshake ran before failing. This is `None` for connections that failed before the
+    /// handshake started (e.g. because the socket couldn't be opened) and for connections that
+    /// were closed after being established.
+    #[serde(skip)]
+    pub handshake_duration: Option<Duration>,
 }
 
 /// The reasons that a connection may be closed.
@@ -299,6 +365,10 @@ pub trait CmapEventHandler: Send + Sync {
     /// whenever a connection pool is cleared.
     fn handle_pool_closed_event(&self, _event: PoolClosedEvent) {}
 
+    /// A [`Client`](../../struct.Client.html) will call this method on each registered handler
+    /// whenever a period of saturated connection establishment in a connection pool ends.
+    fn handle_pool_saturated_event(&self, _event: PoolSaturatedEvent) {}
+
     /// A [`Client`](../../struct.Client.html) will call this method on each registered handler
     /// whenever a connection is created.
     fn handle_connection_created_event(&self, _event: ConnectionCreatedEvent) {}
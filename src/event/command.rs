@@ -40,6 +40,24 @@ pub struct CommandStartedEvent {
     pub service_id: Option<ObjectId>,
 }
 
+impl CommandStartedEvent {
+    /// The id of the session the command was sent with (i.e. its `lsid` field), if any.
+    ///
+    /// Operations that are run without an explicit session use an implicit one if the deployment
+    /// supports sessions, so this is also present for those. Unacknowledged writes never use a
+    /// session.
+    pub fn session_id(&self) -> Option<&Document> {
+        self.command.get_document("lsid").ok()
+    }
+
+    /// The transaction number the command was sent with (i.e. its `txnNumber` field), if any.
+    ///
+    /// This is present for retryable writes and for commands run in a transaction.
+    pub fn txn_number(&self) -> Option<i64> {
+        self.command.get_i64("txnNumber").ok()
+    }
+}
+
 /// An event that triggers when a database command completes without an error.
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -59,6 +77,10 @@ pub struct CommandSucceededEvent {
     /// earlier.
     pub request_id: i32,
 
+    /// The `responseTo` field of the wire protocol header of the server's reply, which should be
+    /// equal to `request_id`.
+    pub response_to: i32,
+
     /// Information about the connect the command will be run on.
     pub connection: ConnectionInfo,
 
@@ -86,6 +108,11 @@ pub struct CommandFailedEvent {
     /// earlier.
     pub request_id: i32,
 
+    /// The `responseTo` field of the wire protocol header of the server's reply, which should be
+    /// equal to `request_id`. This is `None` if no reply was received, e.g. due to a network
+    /// error.
+    pub response_to: Option<i32>,
+
     /// Information about the connect the command will be run on.
     pub connection: ConnectionInfo,
 
@@ -93,6 +120,22 @@ pub struct CommandFailedEvent {
     pub service_id: Option<ObjectId>,
 }
 
+/// A command event of any kind. This is the type of event captured by an
+/// [`EventBuffer`](../buffer/struct.EventBuffer.html) registered as a [`CommandEventHandler`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum CommandEvent {
+    /// A command was initiated.
+    Started(CommandStartedEvent),
+
+    /// A command completed without an error.
+    Succeeded(CommandSucceededEvent),
+
+    /// A command failed to complete successfully.
+    Failed(CommandFailedEvent),
+}
+
 /// Applications can implement this trait to specify custom logic to run on each command event sent
 /// by the driver.
 ///
@@ -0,0 +1,140 @@
+//! Contains the events and functionality to monitor the lifecycle of the cursors created by a
+//! `Client`.
+
+use crate::{options::ServerAddress, Namespace};
+
+/// An event that triggers when a cursor is created by an operation (e.g. `find` or `aggregate`).
+///
+/// If the server returned all of the cursor's results in the initial batch, a
+/// [`CursorExhaustedEvent`] will immediately follow this event.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CursorOpenedEvent {
+    /// The server-generated identifier for the cursor. This will be 0 if the server returned all
+    /// of the cursor's results in the initial batch.
+    pub cursor_id: i64,
+
+    /// The namespace the cursor is iterating over.
+    pub namespace: Namespace,
+
+    /// The address of the server the cursor was created on.
+    pub address: ServerAddress,
+}
+
+/// An event that triggers when a `getMore` command run by a cursor completes successfully.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CursorGetMoreEvent {
+    /// The server-generated identifier for the cursor.
+    pub cursor_id: i64,
+
+    /// The namespace the cursor is iterating over.
+    pub namespace: Namespace,
+
+    /// The number of documents returned in the batch.
+    pub batch_size: usize,
+
+    /// The total number of `getMore` commands the cursor has run, including this one.
+    pub get_more_count: u32,
+}
+
+/// An event that triggers when the server has returned all of a cursor's results.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CursorExhaustedEvent {
+    /// The server-generated identifier for the cursor.
+    pub cursor_id: i64,
+
+    /// The namespace the cursor was iterating over.
+    pub namespace: Namespace,
+
+    /// The total number of `getMore` commands the cursor ran.
+    pub get_more_count: u32,
+}
+
+/// An event that triggers when a cursor is dropped before being exhausted, causing the driver to
+/// kill it on the server with a `killCursors` command.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CursorKilledEvent {
+    /// The server-generated identifier for the cursor.
+    pub cursor_id: i64,
+
+    /// The namespace the cursor was iterating over.
+    pub namespace: Namespace,
+
+    /// The total number of `getMore` commands the cursor ran before being killed.
+    pub get_more_count: u32,
+}
+
+/// Applications can implement this trait to specify custom logic to run on each cursor lifecycle
+/// event emitted by the driver, e.g. to track how many cursors are currently open.
+///
+/// ```rust
+/// # use std::sync::{
+/// #     atomic::{AtomicI64, Ordering},
+/// #     Arc,
+/// # };
+/// #
+/// # use mongodb::{
+/// #     error::Result,
+/// #     event::cursor::{
+/// #         CursorEventHandler,
+/// #         CursorExhaustedEvent,
+/// #         CursorKilledEvent,
+/// #         CursorOpenedEvent,
+/// #     },
+/// #     options::ClientOptions,
+/// # };
+/// # #[cfg(any(feature = "sync", feature = "tokio-sync"))]
+/// # use mongodb::sync::Client;
+/// # #[cfg(all(not(feature = "sync"), not(feature = "tokio-sync")))]
+/// # use mongodb::Client;
+/// #
+/// #[derive(Default)]
+/// struct OpenCursorCounter {
+///     open: AtomicI64,
+/// }
+///
+/// impl CursorEventHandler for OpenCursorCounter {
+///     fn handle_cursor_opened_event(&self, _event: CursorOpenedEvent) {
+///         self.open.fetch_add(1, Ordering::SeqCst);
+///     }
+///
+///     fn handle_cursor_exhausted_event(&self, _event: CursorExhaustedEvent) {
+///         self.open.fetch_sub(1, Ordering::SeqCst);
+///     }
+///
+///     fn handle_cursor_killed_event(&self, _event: CursorKilledEvent) {
+///         self.open.fetch_sub(1, Ordering::SeqCst);
+///     }
+/// }
+///
+/// # fn do_stuff() -> Result<()> {
+/// let handler: Arc<dyn CursorEventHandler> = Arc::new(OpenCursorCounter::default());
+/// let options = ClientOptions::builder()
+///                   .cursor_event_handler(handler)
+///                   .build();
+/// let client = Client::with_options(options)?;
+///
+/// // Do things with the client, and the number of open cursors will be tracked.
+/// # Ok(())
+/// # }
+/// ```
+pub trait CursorEventHandler: Send + Sync {
+    /// A [`Client`](../../struct.Client.html) will call this method on each registered handler
+    /// whenever a cursor is created.
+    fn handle_cursor_opened_event(&self, _event: CursorOpenedEvent) {}
+
+    /// A [`Client`](../../struct.Client.html) will call this method on each registered handler
+    /// whenever a cursor successfully retrieves a batch of documents via `getMore`.
+    fn handle_cursor_get_more_event(&self, _event: CursorGetMoreEvent) {}
+
+    /// A [`Client`](../../struct.Client.html) will call this method on each registered handler
+    /// whenever a cursor has been exhausted.
+    fn handle_cursor_exhausted_event(&self, _event: CursorExhaustedEvent) {}
+
+    /// A [`Client`](../../struct.Client.html) will call this method on each registered handler
+    /// whenever a cursor is killed after being dropped before exhaustion.
+    fn handle_cursor_killed_event(&self, _event: CursorKilledEvent) {}
+}
@@ -1,5 +1,7 @@
 //! Contains the events and functionality for monitoring internal `Client` behavior.
 
+pub mod buffer;
 pub mod cmap;
 pub mod command;
+pub mod cursor;
 pub mod sdam;
@@ -111,6 +111,22 @@ pub struct TopologyClosedEvent {
     pub topology_id: ObjectId,
 }
 
+/// Published when a client configured with
+/// [`FutureWireVersionBehavior::BestEffort`](crate::options::FutureWireVersionBehavior::BestEffort)
+/// discovers a server that requires a newer wire version than the driver supports, and proceeds
+/// to use it anyway.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CompatibilityWarningEvent {
+    /// The ID of the topology.
+    pub topology_id: ObjectId,
+
+    /// A description of the incompatibility, which is also available via
+    /// [`TopologyDescription::compatibility_warning`].
+    pub message: String,
+}
+
 /// Published when a server monitor's `hello` or legacy hello command is started.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -165,6 +181,7 @@ pub(crate) enum SdamEvent {
     ServerHeartbeatStarted(ServerHeartbeatStartedEvent),
     ServerHeartbeatSucceeded(ServerHeartbeatSucceededEvent),
     ServerHeartbeatFailed(ServerHeartbeatFailedEvent),
+    CompatibilityWarning(CompatibilityWarningEvent),
 }
 
 /// Applications can implement this trait to specify custom logic to run on each SDAM event sent
@@ -242,6 +259,10 @@ pub trait SdamEventHandler: Send + Sync {
     /// A [`Client`](../../struct.Client.html) will call this method on each registered handler when
     /// a server heartbeat fails.
     fn handle_server_heartbeat_failed_event(&self, _event: ServerHeartbeatFailedEvent) {}
+
+    /// A [`Client`](../../struct.Client.html) will call this method on each registered handler when
+    /// it proceeds to use a server that requires a newer wire version than the driver supports.
+    fn handle_compatibility_warning_event(&self, _event: CompatibilityWarningEvent) {}
 }
 
 pub(crate) fn handle_sdam_event(handler: &dyn SdamEventHandler, event: SdamEvent) {
@@ -261,5 +282,6 @@ pub(crate) fn handle_sdam_event(handler: &dyn SdamEventHandler, event: SdamEvent
             handler.handle_server_heartbeat_succeeded_event(e)
         }
         SdamEvent::ServerHeartbeatFailed(e) => handler.handle_server_heartbeat_failed_event(e),
+        SdamEvent::CompatibilityWarning(e) => handler.handle_compatibility_warning_event(e),
     }
 }
@@ -80,6 +80,13 @@ impl TopologyDescription {
         self.description.compatibility_error.as_ref()
     }
 
+    /// Gets the compatibility warning of the topology, which describes a server that requires a
+    /// newer wire version than the driver supports when the client was configured with
+    /// [`FutureWireVersionBehavior::BestEffort`](crate::options::FutureWireVersionBehavior::BestEffort).
+    pub fn compatibility_warning(&self) -> Option<&String> {
+        self.description.compatibility_warning.as_ref()
+    }
+
     /// Gets the servers in the topology.
     pub fn servers(&self) -> HashMap<&ServerAddress, ServerInfo> {
         self.description
@@ -98,6 +105,7 @@ impl fmt::Debug for TopologyDescription {
             .field("Max Set Version", &self.max_set_version())
             .field("Max Election ID", &self.max_election_id())
             .field("Compatibility Error", &self.compatibility_error())
+            .field("Compatibility Warning", &self.compatibility_warning())
             .field("Servers", &self.servers().values())
             .finish()
     }
@@ -123,6 +131,10 @@ impl fmt::Display for TopologyDescription {
             write!(f, ", Compatibility Error: {}", compatibility_error)?;
         }
 
+        if let Some(ref compatibility_warning) = self.description.compatibility_warning {
+            write!(f, ", Compatibility Warning: {}", compatibility_warning)?;
+        }
+
         if !self.description.servers.is_empty() {
             write!(f, ", Servers: ")?;
             let mut iter = self.description.servers.values();
@@ -20,7 +20,7 @@ use crate::{
         ServerHeartbeatStartedEvent,
         ServerHeartbeatSucceededEvent,
     },
-    sdam::{ServerType, Topology},
+    sdam::{ServerType, Topology, TopologyVersion},
     selection_criteria::TagSet,
 };
 
@@ -232,8 +232,9 @@ pub(crate) struct HelloCommandResponse {
     /// If the connection is to a load balancer, the id of the selected backend.
     pub service_id: Option<ObjectId>,
 
-    /// For internal use.
-    pub topology_version: Option<Document>,
+    /// The version of the server's topology state, used to detect stale responses and to run
+    /// awaitable hellos.
+    pub topology_version: Option<TopologyVersion>,
 
     /// The maximum permitted size of a BSON wire protocol message.
     pub max_message_size_bytes: i32,
@@ -332,6 +332,7 @@ pub mod results;
 pub(crate) mod runtime;
 mod sdam;
 mod selection_criteria;
+pub mod serde_helpers;
 mod srv;
 #[cfg(any(feature = "sync", feature = "tokio-sync", docsrs))]
 #[cfg_attr(docsrs, doc(cfg(any(feature = "sync", feature = "tokio-sync"))))]
@@ -353,7 +354,12 @@ pub use crate::{
     db::Database,
 };
 
-pub use {client::session::ClusterTime, coll::Namespace, index::IndexModel, sdam::public::*};
+pub use {
+    client::session::{ClusterTime, TransactionBuilder},
+    coll::{Filter, Namespace},
+    index::IndexModel,
+    sdam::public::*,
+};
 
 #[cfg(all(feature = "tokio-runtime", feature = "async-std-runtime",))]
 compile_error!(
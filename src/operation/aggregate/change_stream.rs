@@ -4,7 +4,7 @@ use crate::{
     cmap::{Command, RawCommandResponse, StreamDescription},
     cursor::CursorSpecification,
     error::Result,
-    operation::{append_options, Operation, Retryability},
+    operation::{append_options, Operation, OverrideCriteriaFn, Retryability},
     options::{ChangeStreamOptions, SelectionCriteria, WriteConcern},
 };
 
@@ -18,6 +18,15 @@ pub(crate) struct ChangeStreamAggregate {
 
 impl ChangeStreamAggregate {
     pub(crate) fn new(args: &WatchArgs, resume_data: Option<ChangeStreamData>) -> Result<Self> {
+        if let Some(options) = &args.options {
+            for token in options
+                .resume_after
+                .iter()
+                .chain(options.start_after.iter())
+            {
+                token.validate()?;
+            }
+        }
         Ok(Self {
             inner: Self::build_inner(args)?,
             args: args.clone(),
@@ -31,6 +40,10 @@ impl ChangeStreamAggregate {
 
         let mut agg_pipeline = vec![doc! { "$changeStream": bson_options }];
         agg_pipeline.extend(args.pipeline.iter().cloned());
+        // The server requires `$changeStreamSplitLargeEvent` to be the last stage.
+        if args.options.as_ref().and_then(|o| o.split_large_events) == Some(true) {
+            agg_pipeline.push(doc! { "$changeStreamSplitLargeEvent": {} });
+        }
         Ok(Aggregate::new(
             args.target.clone(),
             agg_pipeline,
@@ -72,7 +85,11 @@ impl Operation for ChangeStreamAggregate {
                 ..self.args.clone()
             })?;
         }
-        self.inner.build(description)
+        let mut cmd = self.inner.build(description)?;
+        if let Some(comment) = self.args.options.as_ref().and_then(|o| o.comment.clone()) {
+            cmd.body.insert("comment", comment);
+        }
+        Ok(cmd)
     }
 
     fn extract_at_cluster_time(
@@ -91,7 +108,10 @@ impl Operation for ChangeStreamAggregate {
             .raw_body()
             .get("operationTime")?
             .and_then(bson::RawBsonRef::as_timestamp);
-        let spec = self.inner.handle_response(response, description)?;
+        let mut spec = self.inner.handle_response(response, description)?;
+        if let Some(comment) = self.args.options.as_ref().and_then(|o| o.comment.clone()) {
+            spec.info.comment = Some(comment);
+        }
 
         let mut data = ChangeStreamData {
             resume_token: ResumeToken::initial(self.args.options.as_ref(), &spec),
@@ -117,6 +137,10 @@ impl Operation for ChangeStreamAggregate {
         self.inner.selection_criteria()
     }
 
+    fn override_criteria(&self) -> OverrideCriteriaFn {
+        self.inner.override_criteria()
+    }
+
     fn supports_read_concern(&self, description: &StreamDescription) -> bool {
         self.inner.supports_read_concern(description)
     }
@@ -125,6 +149,14 @@ impl Operation for ChangeStreamAggregate {
         self.inner.write_concern()
     }
 
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        self.args
+            .options
+            .as_ref()
+            .map_or(true, |opts| opts.comment.is_none())
+            && self.inner.supports_default_comment(description)
+    }
+
     fn retryability(&self) -> Retryability {
         self.inner.retryability()
     }
@@ -8,13 +8,26 @@ use crate::{
     bson_util,
     cmap::{Command, RawCommandResponse, StreamDescription},
     cursor::CursorSpecification,
-    error::Result,
-    operation::{append_options, remove_empty_write_concern, Operation, Retryability},
-    options::{AggregateOptions, SelectionCriteria, WriteConcern},
+    error::{ErrorKind, Result},
+    operation::{
+        append_options,
+        remove_empty_write_concern,
+        Operation,
+        OverrideCriteriaFn,
+        Retryability,
+    },
+    options::{AggregateOptions, ReadPreference, SelectionCriteria, WriteConcern},
+    sdam::TopologyType,
     Namespace,
 };
 
-use super::{CursorBody, WriteConcernOnlyBody, SERVER_4_2_0_WIRE_VERSION};
+use super::{
+    CursorBody,
+    WriteConcernOnlyBody,
+    SERVER_4_2_0_WIRE_VERSION,
+    SERVER_5_0_0_WIRE_VERSION,
+    SERVER_7_0_0_WIRE_VERSION,
+};
 
 pub(crate) use change_stream::ChangeStreamAggregate;
 
@@ -52,7 +65,9 @@ impl Operation for Aggregate {
 
     const NAME: &'static str = "aggregate";
 
-    fn build(&mut self, _description: &StreamDescription) -> Result<Command> {
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        self.validate_out_timeseries(description)?;
+
         let mut body = doc! {
             Self::NAME: self.target.to_bson(),
             "pipeline": bson_util::to_bson_array(&self.pipeline),
@@ -100,6 +115,10 @@ impl Operation for Aggregate {
             description.server_address.clone(),
             self.options.as_ref().and_then(|opts| opts.batch_size),
             self.options.as_ref().and_then(|opts| opts.max_await_time),
+            self.options
+                .as_ref()
+                .and_then(|opts| opts.comment.clone())
+                .map(Bson::String),
         ))
     }
 
@@ -109,6 +128,38 @@ impl Operation for Aggregate {
             .and_then(|opts| opts.selection_criteria.as_ref())
     }
 
+    fn override_criteria(&self) -> OverrideCriteriaFn {
+        if !self.is_out_or_merge() {
+            return |_, _| None;
+        }
+        // Aggregations that write can only be executed on secondaries on MongoDB 5.0+, so they're
+        // routed to the primary unless every server eligible for the criteria is new enough. This
+        // also applies to criteria that only allow secondaries, and when no server is eligible the
+        // primary is selected rather than waiting for one to be discovered.
+        |criteria, topology| {
+            if criteria.is_read_pref_primary()
+                || matches!(
+                    topology.topology_type(),
+                    TopologyType::Single | TopologyType::LoadBalanced
+                )
+            {
+                return None;
+            }
+            // Errors are left to be reported by server selection with the original criteria.
+            let candidates = topology.suitable_servers_in_latency_window(criteria).ok()?;
+            let supported = !candidates.is_empty()
+                && candidates.iter().all(|server| {
+                    server.max_wire_version().ok().flatten().unwrap_or(0)
+                        >= SERVER_5_0_0_WIRE_VERSION
+                });
+            if supported {
+                None
+            } else {
+                Some(SelectionCriteria::ReadPreference(ReadPreference::Primary))
+            }
+        }
+    }
+
     fn supports_read_concern(&self, description: &StreamDescription) -> bool {
         // for aggregates that write, read concern is only supported in MongoDB 4.2+.
         !self.is_out_or_merge()
@@ -121,8 +172,24 @@ impl Operation for Aggregate {
             .and_then(|opts| opts.write_concern.as_ref())
     }
 
+    fn is_write(&self) -> bool {
+        self.is_out_or_merge()
+    }
+
+    fn supports_default_comment(&self, _description: &StreamDescription) -> bool {
+        self.options
+            .as_ref()
+            .map_or(true, |opts| opts.comment.is_none())
+    }
+
+    fn supports_default_allow_disk_use(&self, _description: &StreamDescription) -> bool {
+        self.options
+            .as_ref()
+            .map_or(true, |opts| opts.allow_disk_use.is_none())
+    }
+
     fn retryability(&self) -> Retryability {
-        if self.is_out_or_merge() {
+        if self.is_out_or_merge() || self.is_node_local() {
             Retryability::None
         } else {
             Retryability::Read
@@ -141,6 +208,64 @@ impl Aggregate {
             })
             .unwrap_or(false)
     }
+
+    /// Checks the `timeseries` options of a trailing `$out` stage, if any, before the command is
+    /// sent.
+    fn validate_out_timeseries(&self, description: &StreamDescription) -> Result<()> {
+        let timeseries = match self
+            .pipeline
+            .last()
+            .and_then(|stage| stage.get_document("$out").ok())
+            .and_then(|out| out.get("timeseries"))
+        {
+            Some(timeseries) => timeseries,
+            None => return Ok(()),
+        };
+        let invalid = |message: &str| -> Result<()> {
+            Err(ErrorKind::InvalidArgument {
+                message: format!("invalid $out timeseries options: {}", message),
+            }
+            .into())
+        };
+
+        // Patch releases share a wire version, so only servers older than 7.0 can be rejected here;
+        // 7.0.0-7.0.2 servers reject the option themselves.
+        if description.max_wire_version.unwrap_or(0) < SERVER_7_0_0_WIRE_VERSION {
+            return Err(ErrorKind::IncompatibleServer {
+                message: "$out to a time series collection requires MongoDB 7.0+".to_string(),
+                driver_range: None,
+                server_range: None,
+            }
+            .into());
+        }
+        let timeseries = match timeseries {
+            Bson::Document(timeseries) => timeseries,
+            _ => return invalid("expected a document"),
+        };
+        let time_field = match timeseries.get_str("timeField") {
+            Ok(time_field) if !time_field.is_empty() => time_field,
+            _ => return invalid("timeField must be a non-empty string"),
+        };
+        if time_field == "_id" || time_field.contains('.') {
+            return invalid("timeField must name a top-level field other than _id");
+        }
+        if timeseries.get_str("metaField").ok() == Some(time_field) {
+            return invalid("timeField and metaField must be different");
+        }
+        Ok(())
+    }
+
+    /// Returns whether this aggregation reports on the state of the node it runs on, in which case
+    /// retrying it on another node would produce different results.
+    fn is_node_local(&self) -> bool {
+        self.pipeline
+            .first()
+            .map(|stage| {
+                let stage = bson_util::first_key(stage);
+                stage == Some("$listLocalSessions") || stage == Some("$currentOp")
+            })
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Clone, Debug)]
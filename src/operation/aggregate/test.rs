@@ -2,17 +2,44 @@ use std::time::Duration;
 
 use super::AggregateTarget;
 use crate::{
-    bson::{doc, Document},
+    bson::{doc, Bson, Document},
     bson_util,
     cmap::StreamDescription,
     concern::{ReadConcern, ReadConcernLevel},
     error::{ErrorKind, WriteFailure},
+    hello::{HelloCommandResponse, HelloReply},
     operation::{
         test::{self, handle_response_test},
         Aggregate,
         Operation,
+        Retryability,
     },
-    options::{AggregateOptions, Hint},
+    options::{
+        AggregateOptions,
+        DensifyBounds,
+        DensifyStage,
+        FillMethod,
+        FillOutput,
+        FillStage,
+        Hint,
+        MergeStage,
+        MergeWhenMatched,
+        MergeWhenNotMatched,
+        OutStage,
+        ReadPreference,
+        SelectionCriteria,
+        ServerAddress,
+        SetWindowFieldsStage,
+        TimeUnit,
+        TimeseriesGranularity,
+        TimeseriesOptions,
+        UnionWithStage,
+        VectorSearchStage,
+        Window,
+        WindowBound,
+        WindowOutput,
+    },
+    sdam::{ServerDescription, TopologyDescription, TopologyType},
     Namespace,
 };
 
@@ -181,6 +208,449 @@ fn op_selection_criteria() {
     });
 }
 
+#[test]
+fn merge_selection_criteria() {
+    let options = AggregateOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(
+            ReadPreference::SecondaryPreferred {
+                options: Default::default(),
+            },
+        ))
+        .build();
+
+    let merge: Document = MergeStage::builder()
+        .into(Namespace {
+            db: "test_db".to_string(),
+            coll: "out".to_string(),
+        })
+        .build()
+        .into();
+    let out = doc! { "$out": "out" };
+    let primary = SelectionCriteria::ReadPreference(ReadPreference::Primary);
+    let secondary = options.selection_criteria.clone().unwrap();
+    // No servers have been discovered, so none are eligible to write on a secondary.
+    let topology = TopologyDescription::new_empty();
+
+    for pipeline in [vec![merge], vec![out]] {
+        let aggregate = Aggregate::new("".to_string(), pipeline, Some(options.clone()));
+        assert_eq!(aggregate.selection_criteria(), Some(&secondary));
+        assert_eq!(
+            aggregate.override_criteria()(&secondary, &topology),
+            Some(primary.clone())
+        );
+        assert!(aggregate.override_criteria()(&primary, &topology).is_none());
+    }
+
+    let aggregate = Aggregate::new("".to_string(), Vec::new(), Some(options));
+    assert_eq!(aggregate.selection_criteria(), Some(&secondary));
+    assert!(aggregate.override_criteria()(&secondary, &topology).is_none());
+}
+
+/// Creates a replica set topology with a primary and secondaries reporting the given max wire
+/// versions.
+fn replica_set(primary_wire_version: i32, secondary_wire_versions: &[i32]) -> TopologyDescription {
+    let mut topology = TopologyDescription::new_empty();
+    topology.topology_type = TopologyType::ReplicaSetWithPrimary;
+
+    let mut add_server = |port: u16, wire_version: i32, primary: bool| {
+        let address = ServerAddress::Tcp {
+            host: "localhost".to_string(),
+            port: Some(port),
+        };
+        let command_response = HelloCommandResponse {
+            set_name: Some("rs".to_string()),
+            is_writable_primary: Some(primary),
+            secondary: Some(!primary),
+            max_wire_version: Some(wire_version),
+            ..Default::default()
+        };
+        let reply = HelloReply {
+            server_address: address.clone(),
+            command_response,
+            round_trip_time: Duration::from_millis(1),
+            cluster_time: None,
+            raw_command_response: Default::default(),
+        };
+        topology.servers.insert(
+            address.clone(),
+            ServerDescription::new(address, Some(Ok(reply))),
+        );
+    };
+    add_server(27017, primary_wire_version, true);
+    for (i, wire_version) in secondary_wire_versions.iter().enumerate() {
+        add_server(27018 + i as u16, *wire_version, false);
+    }
+
+    topology
+}
+
+#[test]
+fn merge_override_criteria() {
+    let out = vec![doc! { "$out": "out" }];
+    let primary = SelectionCriteria::ReadPreference(ReadPreference::Primary);
+    let secondary_preferred =
+        SelectionCriteria::ReadPreference(ReadPreference::SecondaryPreferred {
+            options: Default::default(),
+        });
+    let secondary = SelectionCriteria::ReadPreference(ReadPreference::Secondary {
+        options: Default::default(),
+    });
+    let override_criteria = Aggregate::new("".to_string(), out, None).override_criteria();
+
+    // Every eligible server is 5.0+, so the user's preference is used.
+    let topology = replica_set(13, &[13, 17]);
+    assert!(override_criteria(&secondary_preferred, &topology).is_none());
+    assert!(override_criteria(&secondary, &topology).is_none());
+
+    // An eligible server is older than 5.0, so the primary is used instead.
+    let topology = replica_set(13, &[13, 12]);
+    assert_eq!(
+        override_criteria(&secondary_preferred, &topology),
+        Some(primary.clone())
+    );
+    assert_eq!(
+        override_criteria(&secondary, &topology),
+        Some(primary.clone())
+    );
+
+    // Only the primary is too old, and it isn't eligible for secondary-only criteria.
+    let topology = replica_set(12, &[13]);
+    assert!(override_criteria(&secondary, &topology).is_none());
+
+    // No server is eligible for secondary-only criteria, so the primary is used.
+    let topology = replica_set(13, &[]);
+    assert_eq!(
+        override_criteria(&secondary, &topology),
+        Some(primary.clone())
+    );
+
+    // Primary criteria are never overridden.
+    let topology = replica_set(12, &[12]);
+    assert!(override_criteria(&primary, &topology).is_none());
+}
+
+#[test]
+fn merge_stage() {
+    let into = Namespace {
+        db: "test_db".to_string(),
+        coll: "out".to_string(),
+    };
+
+    let stage: Document = MergeStage::builder().into(into.clone()).build().into();
+    assert_eq!(
+        stage,
+        doc! { "$merge": { "into": { "db": "test_db", "coll": "out" } } }
+    );
+
+    let stage: Document = MergeStage::builder()
+        .into(into.clone())
+        .on(vec!["a".to_string(), "b".to_string()])
+        .when_matched(MergeWhenMatched::KeepExisting)
+        .when_not_matched(MergeWhenNotMatched::Discard)
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! {
+            "$merge": {
+                "into": { "db": "test_db", "coll": "out" },
+                "on": ["a", "b"],
+                "whenMatched": "keepExisting",
+                "whenNotMatched": "discard",
+            }
+        }
+    );
+
+    let stage: Document = MergeStage::builder()
+        .into(into)
+        .when_matched(MergeWhenMatched::Pipeline {
+            pipeline: vec![doc! { "$set": { "total": { "$add": ["$total", "$$inc"] } } }],
+            let_vars: Some(doc! { "inc": "$total" }),
+        })
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! {
+            "$merge": {
+                "into": { "db": "test_db", "coll": "out" },
+                "let": { "inc": "$total" },
+                "whenMatched": [{ "$set": { "total": { "$add": ["$total", "$$inc"] } } }],
+            }
+        }
+    );
+}
+
+#[test]
+fn out_stage() {
+    let into = Namespace {
+        db: "test_db".to_string(),
+        coll: "out".to_string(),
+    };
+
+    let stage: Document = OutStage::builder().into(into.clone()).build().into();
+    assert_eq!(stage, doc! { "$out": { "db": "test_db", "coll": "out" } });
+
+    let stage: Document = OutStage::builder()
+        .into(into)
+        .timeseries(
+            TimeseriesOptions::builder()
+                .time_field("ts".to_string())
+                .meta_field(Some("sensor".to_string()))
+                .granularity(Some(TimeseriesGranularity::Minutes))
+                .build(),
+        )
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! {
+            "$out": {
+                "db": "test_db",
+                "coll": "out",
+                "timeseries": { "timeField": "ts", "metaField": "sensor", "granularity": "minutes" },
+            }
+        }
+    );
+}
+
+#[test]
+fn out_timeseries_validation() {
+    let build = |timeseries: Document, wire_version: i32| {
+        let out = doc! { "$out": { "db": "test_db", "coll": "out", "timeseries": timeseries } };
+        Aggregate::new("test_db".to_string(), vec![out], None)
+            .build(&StreamDescription::with_wire_version(wire_version))
+            .map(|_| ())
+    };
+
+    assert!(build(doc! { "timeField": "ts", "metaField": "sensor" }, 21).is_ok());
+
+    let error = build(doc! { "timeField": "ts" }, 17).unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::IncompatibleServer { .. }));
+
+    for timeseries in [
+        doc! {},
+        doc! { "timeField": "" },
+        doc! { "timeField": 1 },
+        doc! { "timeField": "_id" },
+        doc! { "timeField": "a.b" },
+        doc! { "timeField": "ts", "metaField": "ts" },
+    ] {
+        let error = build(timeseries.clone(), 21).unwrap_err();
+        assert!(
+            matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+            "{:?}: {:?}",
+            timeseries,
+            error
+        );
+    }
+}
+
+#[test]
+fn node_local_retryability() {
+    let aggregate = Aggregate::new("admin".to_string(), vec![doc! { "$match": {} }], None);
+    assert_eq!(aggregate.retryability(), Retryability::Read);
+
+    for stage in ["$listLocalSessions", "$currentOp"] {
+        let aggregate = Aggregate::new(
+            "admin".to_string(),
+            vec![doc! { stage: {} }, doc! { "$match": {} }],
+            None,
+        );
+        assert_eq!(aggregate.retryability(), Retryability::None);
+    }
+}
+
+#[test]
+fn set_window_fields_stage() {
+    let stage: Document = SetWindowFieldsStage::builder()
+        .partition_by(Bson::String("$sensor".to_string()))
+        .sort_by(doc! { "ts": 1 })
+        .output(vec![
+            WindowOutput::builder()
+                .field("avg")
+                .operator(doc! { "$avg": "$value" })
+                .window(Window::Documents {
+                    lower: WindowBound::Offset(Bson::Int32(-1)),
+                    upper: WindowBound::Current,
+                })
+                .build(),
+            WindowOutput::builder()
+                .field("daily")
+                .operator(doc! { "$sum": "$value" })
+                .window(Window::Range {
+                    lower: WindowBound::Offset(Bson::Int32(-1)),
+                    upper: WindowBound::Current,
+                    unit: Some(TimeUnit::Day),
+                })
+                .build(),
+            WindowOutput::builder()
+                .field("total")
+                .operator(doc! { "$sum": "$value" })
+                .window(Window::Range {
+                    lower: WindowBound::Unbounded,
+                    upper: WindowBound::Unbounded,
+                    unit: None,
+                })
+                .build(),
+            WindowOutput::builder()
+                .field("rank")
+                .operator(doc! { "$rank": {} })
+                .build(),
+        ])
+        .build()
+        .into();
+
+    assert_eq!(
+        stage,
+        doc! {
+            "$setWindowFields": {
+                "partitionBy": "$sensor",
+                "sortBy": { "ts": 1 },
+                "output": {
+                    "avg": { "$avg": "$value", "window": { "documents": [-1, "current"] } },
+                    "daily": {
+                        "$sum": "$value",
+                        "window": { "range": [-1, "current"], "unit": "day" },
+                    },
+                    "total": {
+                        "$sum": "$value",
+                        "window": { "range": ["unbounded", "unbounded"] },
+                    },
+                    "rank": { "$rank": {} },
+                },
+            }
+        }
+    );
+}
+
+#[test]
+fn vector_search_stage() {
+    let stage: Document = VectorSearchStage::builder()
+        .index("vector_index")
+        .path("embedding")
+        .query_vector(vec![0.5, -1.0, 2.0])
+        .num_candidates(100u32)
+        .limit(10u32)
+        .filter(doc! { "category": { "$eq": "books" } })
+        .build()
+        .into();
+
+    assert_eq!(
+        stage,
+        doc! {
+            "$vectorSearch": {
+                "index": "vector_index",
+                "path": "embedding",
+                "queryVector": [0.5, -1.0, 2.0],
+                "numCandidates": 100i64,
+                "limit": 10i64,
+                "filter": { "category": { "$eq": "books" } },
+            }
+        }
+    );
+
+    assert_eq!(
+        VectorSearchStage::score_stage("score"),
+        doc! { "$addFields": { "score": { "$meta": "vectorSearchScore" } } }
+    );
+}
+
+#[test]
+fn densify_and_fill_stages() {
+    let stage: Document = DensifyStage::builder()
+        .field("ts")
+        .partition_by_fields(vec!["sensor".to_string()])
+        .step(Bson::Int32(1))
+        .unit(TimeUnit::Hour)
+        .bounds(DensifyBounds::Partition)
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! {
+            "$densify": {
+                "field": "ts",
+                "partitionByFields": ["sensor"],
+                "range": { "step": 1, "bounds": "partition", "unit": "hour" },
+            }
+        }
+    );
+
+    let stage: Document = DensifyStage::builder()
+        .field("n")
+        .step(Bson::Double(0.5))
+        .bounds(DensifyBounds::Range {
+            lower: Bson::Int32(0),
+            upper: Bson::Int32(10),
+        })
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! { "$densify": { "field": "n", "range": { "step": 0.5, "bounds": [0, 10] } } }
+    );
+
+    let stage: Document = FillStage::builder()
+        .partition_by_fields(vec!["sensor".to_string()])
+        .sort_by(doc! { "ts": 1 })
+        .output(vec![
+            FillOutput::builder()
+                .field("value")
+                .method(FillMethod::Linear)
+                .build(),
+            FillOutput::builder()
+                .field("status")
+                .method(FillMethod::LastObservation)
+                .build(),
+            FillOutput::builder()
+                .field("source")
+                .method(FillMethod::Value(Bson::String("filled".to_string())))
+                .build(),
+        ])
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! {
+            "$fill": {
+                "partitionByFields": ["sensor"],
+                "sortBy": { "ts": 1 },
+                "output": {
+                    "value": { "method": "linear" },
+                    "status": { "method": "locf" },
+                    "source": { "value": "filled" },
+                },
+            }
+        }
+    );
+}
+
+#[test]
+fn union_with_stage() {
+    let stage: Document = UnionWithStage::builder().coll("other").build().into();
+    assert_eq!(stage, doc! { "$unionWith": "other" });
+
+    let nested: Document = UnionWithStage::builder().coll("third").build().into();
+    let stage: Document = UnionWithStage::builder()
+        .coll("other")
+        .pipeline(vec![doc! { "$match": { "x": 1 } }, nested])
+        .build()
+        .into();
+    assert_eq!(
+        stage,
+        doc! {
+            "$unionWith": {
+                "coll": "other",
+                "pipeline": [
+                    { "$match": { "x": 1 } },
+                    { "$unionWith": "third" },
+                ],
+            }
+        }
+    );
+}
+
 #[test]
 fn handle_max_await_time() {
     let response = doc! {
@@ -201,8 +671,16 @@ fn handle_max_await_time() {
         .max_await_time(max_await)
         .build();
     let aggregate = Aggregate::new(Namespace::empty(), Vec::new(), Some(options));
-    let spec = handle_response_test(&aggregate, response).unwrap();
+    let spec = handle_response_test(&aggregate, response.clone()).unwrap();
     assert_eq!(spec.max_time(), Some(max_await));
+
+    // maxTimeMS is enforced by the server across the whole cursor, so it isn't sent on getMores.
+    let options = AggregateOptions::builder()
+        .max_time(Duration::from_millis(456))
+        .build();
+    let aggregate = Aggregate::new(Namespace::empty(), Vec::new(), Some(options));
+    let spec = handle_response_test(&aggregate, response).unwrap();
+    assert!(spec.max_time().is_none());
 }
 
 #[test]
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod test;
+
+use bson::Document;
+
+use crate::{
+    bson::doc,
+    cmap::{Command, RawCommandResponse, StreamDescription},
+    coll::{options::CollectionStatsOptions, Namespace},
+    error::{ErrorKind, Result},
+    operation::Operation,
+    results::CollectionStats,
+    selection_criteria::SelectionCriteria,
+};
+
+#[derive(Debug)]
+pub(crate) struct CollStats {
+    ns: Namespace,
+    options: Option<CollectionStatsOptions>,
+}
+
+impl CollStats {
+    pub(crate) fn new(ns: Namespace, options: Option<CollectionStatsOptions>) -> Self {
+        Self { ns, options }
+    }
+
+    #[cfg(test)]
+    fn empty() -> Self {
+        Self::new(Namespace::empty(), None)
+    }
+
+    fn scale(&self) -> u32 {
+        self.options.as_ref().and_then(|o| o.scale).unwrap_or(1)
+    }
+}
+
+impl Operation for CollStats {
+    type O = CollectionStats;
+    type Command = Document;
+
+    const NAME: &'static str = "collStats";
+
+    fn build(&mut self, _description: &StreamDescription) -> Result<Command> {
+        if self.scale() == 0 {
+            return Err(ErrorKind::InvalidArgument {
+                message: "scale must be greater than 0".to_string(),
+            }
+            .into());
+        }
+
+        // The sizes are always requested in bytes and scaled by the driver, since the server
+        // doesn't scale all of them (e.g. `avgObjSize`).
+        let body = doc! {
+            Self::NAME: self.ns.coll.clone(),
+        };
+
+        Ok(Command::new(
+            Self::NAME.to_string(),
+            self.ns.db.clone(),
+            body,
+        ))
+    }
+
+    fn handle_response(
+        &self,
+        response: RawCommandResponse,
+        _description: &StreamDescription,
+    ) -> Result<Self::O> {
+        CollectionStats::from_reply(response.body()?, self.scale())
+    }
+
+    fn selection_criteria(&self) -> Option<&SelectionCriteria> {
+        self.options
+            .as_ref()
+            .and_then(|opts| opts.selection_criteria.as_ref())
+    }
+}
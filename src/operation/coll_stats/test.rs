@@ -0,0 +1,100 @@
+use crate::{
+    bson::doc,
+    cmap::StreamDescription,
+    coll::{options::CollectionStatsOptions, Namespace},
+    error::ErrorKind,
+    operation::{test::handle_response_test, CollStats, Operation},
+};
+
+#[test]
+fn build() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let options = CollectionStatsOptions::builder().scale(1024).build();
+    let mut op = CollStats::new(ns, Some(options));
+    let command = op
+        .build(&StreamDescription::new_testing())
+        .expect("error on build");
+    // The scale is applied by the driver rather than sent to the server.
+    assert_eq!(command.body, doc! { "collStats": "test_coll" });
+    assert_eq!(command.target_db, "test_db");
+}
+
+#[test]
+fn build_zero_scale() {
+    let options = CollectionStatsOptions::builder().scale(0).build();
+    let mut op = CollStats::new(Namespace::empty(), Some(options));
+    let err = op
+        .build(&StreamDescription::new_testing())
+        .expect_err("a scale of 0 should be rejected");
+    assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+}
+
+#[test]
+fn handle_scaled() {
+    let response = doc! {
+        "ns": "test_db.test_coll",
+        "count": 10,
+        "size": 10_000_i64,
+        "avgObjSize": 1000,
+        "storageSize": 20_480,
+        "freeStorageSize": 4_096,
+        "nindexes": 2,
+        "totalIndexSize": 8_191.0,
+        "indexSizes": { "_id_": 4_096, "x_1": 4_095 },
+        "totalSize": 28_671,
+        "capped": false,
+        "ok": 1.0,
+    };
+
+    let result =
+        handle_response_test(&CollStats::empty(), response.clone()).expect("handle should succeed");
+    assert_eq!(result.ns, "test_db.test_coll");
+    assert_eq!(result.count, 10);
+    assert_eq!(result.size, 10_000);
+    assert_eq!(result.avg_obj_size, 1000);
+    assert_eq!(result.storage_size, 20_480);
+    assert_eq!(result.free_storage_size, Some(4_096));
+    assert_eq!(result.total_index_size, 8_191);
+    assert_eq!(result.index_sizes["x_1"], 4_095);
+    assert_eq!(result.total_size, Some(28_671));
+    assert_eq!(result.scale_factor, 1);
+    assert_eq!(result.raw_reply, response);
+
+    let options = CollectionStatsOptions::builder().scale(1024).build();
+    let op = CollStats::new(Namespace::empty(), Some(options));
+    let result = handle_response_test(&op, response).expect("handle should succeed");
+    // Counts are not scaled, and every size is divided by the scale and rounded down.
+    assert_eq!(result.count, 10);
+    assert_eq!(result.nindexes, 2);
+    assert_eq!(result.size, 9);
+    assert_eq!(result.avg_obj_size, 0);
+    assert_eq!(result.storage_size, 20);
+    assert_eq!(result.free_storage_size, Some(4));
+    assert_eq!(result.total_index_size, 7);
+    assert_eq!(result.index_sizes["_id_"], 4);
+    assert_eq!(result.index_sizes["x_1"], 3);
+    assert_eq!(result.total_size, Some(27));
+    assert_eq!(result.scale_factor, 1024);
+}
+
+#[test]
+fn handle_without_free_storage_size() {
+    let response = doc! {
+        "ns": "test_db.test_coll",
+        "count": 0,
+        "size": 0,
+        "storageSize": 4_096,
+        "nindexes": 1,
+        "totalIndexSize": 4_096,
+        "indexSizes": { "_id_": 4_096 },
+        "ok": 1.0,
+    };
+    let result =
+        handle_response_test(&CollStats::empty(), response).expect("handle should succeed");
+    assert_eq!(result.free_storage_size, None);
+    assert_eq!(result.total_size, None);
+    assert_eq!(result.avg_obj_size, 0);
+}
@@ -57,6 +57,10 @@ impl Operation for CommitTransaction {
             .and_then(|opts| opts.write_concern.as_ref())
     }
 
+    fn is_write(&self) -> bool {
+        true
+    }
+
     fn retryability(&self) -> Retryability {
         Retryability::Write
     }
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod test;
+
+use bson::Document;
+use serde::Deserialize;
+
+use crate::{
+    bson::{doc, Bson},
+    bson_util,
+    cmap::{Command, RawCommandResponse, StreamDescription},
+    coll::{options::CompactOptions, Namespace},
+    error::{ErrorKind, Result},
+    operation::{append_options, supports_comment_on_all_commands, Operation},
+    ServerType,
+};
+
+#[derive(Debug)]
+pub(crate) struct Compact {
+    ns: Namespace,
+    options: Option<CompactOptions>,
+}
+
+impl Compact {
+    pub(crate) fn new(ns: Namespace, options: Option<CompactOptions>) -> Self {
+        Self { ns, options }
+    }
+
+    #[cfg(test)]
+    fn empty() -> Self {
+        Self::new(
+            Namespace {
+                db: String::new(),
+                coll: String::new(),
+            },
+            None,
+        )
+    }
+}
+
+impl Operation for Compact {
+    type O = Option<u64>;
+    type Command = Document;
+
+    const NAME: &'static str = "compact";
+
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        // mongos doesn't support compact, so it has to be run against each shard directly.
+        if description.initial_server_type == ServerType::Mongos {
+            return Err(ErrorKind::InvalidArgument {
+                message: "compact cannot be run through mongos; connect to each shard directly to \
+                          compact a sharded collection"
+                    .to_string(),
+            }
+            .into());
+        }
+
+        let mut body = doc! {
+            Self::NAME: self.ns.coll.clone(),
+        };
+        append_options(&mut body, self.options.as_ref())?;
+
+        Ok(Command::new(
+            Self::NAME.to_string(),
+            self.ns.db.clone(),
+            body,
+        ))
+    }
+
+    fn handle_response(
+        &self,
+        response: RawCommandResponse,
+        _description: &StreamDescription,
+    ) -> Result<Self::O> {
+        let response: ResponseBody = response.body()?;
+        Ok(response.bytes_freed.as_ref().and_then(bson_util::get_u64))
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.comment.is_none())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseBody {
+    /// Only reported by MongoDB 4.4+.
+    bytes_freed: Option<Bson>,
+}
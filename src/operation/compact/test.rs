@@ -0,0 +1,45 @@
+use crate::{
+    bson::doc,
+    cmap::StreamDescription,
+    coll::{options::CompactOptions, Namespace},
+    error::ErrorKind,
+    operation::{test::handle_response_test, Compact, Operation},
+    ServerType,
+};
+
+#[test]
+fn build() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let options = CompactOptions::builder().force(true).build();
+    let mut op = Compact::new(ns, Some(options));
+    let command = op
+        .build(&StreamDescription::new_testing())
+        .expect("error on build");
+    assert_eq!(command.body, doc! { "compact": "test_coll", "force": true });
+    assert_eq!(command.target_db, "test_db");
+}
+
+#[test]
+fn build_mongos() {
+    let mut description = StreamDescription::new_testing();
+    description.initial_server_type = ServerType::Mongos;
+    let err = Compact::empty()
+        .build(&description)
+        .expect_err("compact through mongos should be rejected");
+    assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+}
+
+#[test]
+fn handle_success() {
+    let op = Compact::empty();
+
+    let bytes_freed = handle_response_test(&op, doc! { "bytesFreed": 1024i64, "ok": 1.0 })
+        .expect("handle should succeed");
+    assert_eq!(bytes_freed, Some(1024));
+
+    let bytes_freed = handle_response_test(&op, doc! { "ok": 1.0 }).expect("handle should succeed");
+    assert_eq!(bytes_freed, None);
+}
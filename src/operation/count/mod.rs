@@ -9,7 +9,7 @@ use crate::{
     cmap::{Command, RawCommandResponse, StreamDescription},
     coll::{options::EstimatedDocumentCountOptions, Namespace},
     error::{Error, Result},
-    operation::{append_options, Operation, Retryability},
+    operation::{append_options, supports_comment_on_all_commands, Operation, Retryability},
     selection_criteria::SelectionCriteria,
 };
 
@@ -84,6 +84,14 @@ impl Operation for Count {
         true
     }
 
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.comment.is_none())
+    }
+
     fn retryability(&self) -> Retryability {
         Retryability::Read
     }
@@ -6,7 +6,12 @@ use crate::{
     cmap::{Command, RawCommandResponse, StreamDescription},
     error::{ErrorKind, Result},
     index::IndexModel,
-    operation::{append_options, remove_empty_write_concern, Operation},
+    operation::{
+        append_options,
+        remove_empty_write_concern,
+        supports_comment_on_all_commands,
+        Operation,
+    },
     options::{CreateIndexOptions, WriteConcern},
     results::CreateIndexesResult,
     Namespace,
@@ -101,4 +106,12 @@ impl Operation for CreateIndexes {
             .as_ref()
             .and_then(|opts| opts.write_concern.as_ref())
     }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.comment.is_none())
+    }
 }
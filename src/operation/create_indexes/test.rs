@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use crate::{
-    bson::doc,
+    bson::{doc, Bson},
     cmap::StreamDescription,
     coll::{
         options::{CommitQuorum, CreateIndexOptions},
@@ -31,6 +31,7 @@ fn build() {
         .commit_quorum(Some(CommitQuorum::Majority))
         .max_time(Some(Duration::from_millis(42)))
         .write_concern(Some(WriteConcern::builder().journal(Some(true)).build()))
+        .comment(Some(Bson::from("create")))
         .build();
     let mut create_indexes = CreateIndexes::new(ns, vec![index_model], Some(create_options));
 
@@ -49,6 +50,7 @@ fn build() {
             "commitQuorum": "majority",
             "maxTimeMS": 42,
             "writeConcern": { "j": true },
+            "comment": "create",
         }
     )
 }
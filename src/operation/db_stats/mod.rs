@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod test;
+
+use bson::Document;
+
+use crate::{
+    bson::doc,
+    cmap::{Command, RawCommandResponse, StreamDescription},
+    db::options::DatabaseStatsOptions,
+    error::{ErrorKind, Result},
+    operation::{Operation, SERVER_4_4_0_WIRE_VERSION},
+    results::DatabaseStats,
+    selection_criteria::SelectionCriteria,
+};
+
+#[derive(Debug)]
+pub(crate) struct DbStats {
+    db: String,
+    options: Option<DatabaseStatsOptions>,
+}
+
+impl DbStats {
+    pub(crate) fn new(db: String, options: Option<DatabaseStatsOptions>) -> Self {
+        Self { db, options }
+    }
+
+    #[cfg(test)]
+    fn empty() -> Self {
+        Self::new(String::new(), None)
+    }
+
+    fn scale(&self) -> u32 {
+        self.options.as_ref().and_then(|o| o.scale).unwrap_or(1)
+    }
+}
+
+impl Operation for DbStats {
+    type O = DatabaseStats;
+    type Command = Document;
+
+    const NAME: &'static str = "dbStats";
+
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        if self.scale() == 0 {
+            return Err(ErrorKind::InvalidArgument {
+                message: "scale must be greater than 0".to_string(),
+            }
+            .into());
+        }
+
+        // The sizes are always requested in bytes and scaled by the driver, since the server
+        // doesn't scale all of them (e.g. `avgObjSize`).
+        let mut body = doc! {
+            Self::NAME: 1,
+        };
+        if description.max_wire_version.unwrap_or(0) >= SERVER_4_4_0_WIRE_VERSION {
+            body.insert("freeStorage", 1);
+        }
+
+        Ok(Command::new(Self::NAME.to_string(), self.db.clone(), body))
+    }
+
+    fn handle_response(
+        &self,
+        response: RawCommandResponse,
+        _description: &StreamDescription,
+    ) -> Result<Self::O> {
+        DatabaseStats::from_reply(response.body()?, self.scale())
+    }
+
+    fn selection_criteria(&self) -> Option<&SelectionCriteria> {
+        self.options
+            .as_ref()
+            .and_then(|opts| opts.selection_criteria.as_ref())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    bson::doc,
+    cmap::StreamDescription,
+    db::options::DatabaseStatsOptions,
+    operation::{test::handle_response_test, DbStats, Operation},
+};
+
+#[test]
+fn build() {
+    let mut op = DbStats::new("test_db".to_string(), None);
+
+    let mut description = StreamDescription::new_testing();
+    description.max_wire_version = Some(8);
+    let command = op.build(&description).expect("error on build");
+    assert_eq!(command.body, doc! { "dbStats": 1 });
+    assert_eq!(command.target_db, "test_db");
+
+    // Free storage sizes are requested from servers that can report them.
+    description.max_wire_version = Some(9);
+    let command = op.build(&description).expect("error on build");
+    assert_eq!(command.body, doc! { "dbStats": 1, "freeStorage": 1 });
+}
+
+#[test]
+fn handle_scaled() {
+    let response = doc! {
+        "db": "test_db",
+        "collections": 3,
+        "views": 1,
+        "objects": 100_i64,
+        "avgObjSize": 2_048.5,
+        "dataSize": 204_850.0,
+        "storageSize": 131_072,
+        "freeStorageSize": 16_384,
+        "indexes": 4,
+        "indexSize": 65_536,
+        "totalSize": 196_608,
+        "scaleFactor": 1,
+        "ok": 1.0,
+    };
+
+    let result =
+        handle_response_test(&DbStats::empty(), response.clone()).expect("handle should succeed");
+    assert_eq!(result.db, "test_db");
+    assert_eq!(result.collections, 3);
+    assert_eq!(result.views, 1);
+    assert_eq!(result.objects, 100);
+    assert_eq!(result.avg_obj_size, 2_048);
+    assert_eq!(result.data_size, 204_850);
+    assert_eq!(result.free_storage_size, Some(16_384));
+    assert_eq!(result.raw_reply, response);
+
+    let options = DatabaseStatsOptions::builder().scale(1024).build();
+    let op = DbStats::new("test_db".to_string(), Some(options));
+    let result = handle_response_test(&op, response).expect("handle should succeed");
+    assert_eq!(result.objects, 100);
+    assert_eq!(result.indexes, 4);
+    assert_eq!(result.avg_obj_size, 2);
+    assert_eq!(result.data_size, 200);
+    assert_eq!(result.storage_size, 128);
+    assert_eq!(result.free_storage_size, Some(16));
+    assert_eq!(result.index_size, 64);
+    assert_eq!(result.total_size, Some(192));
+    assert_eq!(result.scale_factor, 1024);
+}
@@ -6,10 +6,11 @@ use crate::{
     cmap::{Command, RawCommandResponse, StreamDescription},
     coll::Namespace,
     collation::Collation,
-    error::{convert_bulk_errors, Result},
+    error::{convert_bulk_errors, ErrorKind, Result},
     operation::{
         append_options,
         remove_empty_write_concern,
+        supports_comment_on_all_commands,
         Operation,
         Retryability,
         WriteResponseBody,
@@ -65,7 +66,19 @@ impl Operation for Delete {
 
     const NAME: &'static str = "delete";
 
-    fn build(&mut self, _description: &StreamDescription) -> Result<Command> {
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        if !self.is_acknowledged()
+            && self.hint.is_some()
+            && description.max_wire_version.unwrap_or(0) < 9
+        {
+            return Err(ErrorKind::InvalidArgument {
+                message: "Specifying a hint to an unacknowledged delete is not supported on \
+                          server versions < 4.4"
+                    .to_string(),
+            }
+            .into());
+        }
+
         let mut delete = doc! {
             "q": self.filter.clone(),
             "limit": self.limit,
@@ -100,11 +113,12 @@ impl Operation for Delete {
         response: RawCommandResponse,
         _description: &StreamDescription,
     ) -> Result<Self::O> {
-        let response: WriteResponseBody = response.body()?;
-        response.validate().map_err(convert_bulk_errors)?;
+        let body: WriteResponseBody = response.body_utf8_lossy()?;
+        body.validate().map_err(convert_bulk_errors)?;
 
         Ok(DeleteResult {
-            deleted_count: response.n,
+            deleted_count: body.n,
+            raw_reply: response.body_utf8_lossy()?,
         })
     }
 
@@ -114,6 +128,14 @@ impl Operation for Delete {
             .and_then(|opts| opts.write_concern.as_ref())
     }
 
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+    }
+
     fn retryability(&self) -> Retryability {
         if self.limit == 1 {
             Retryability::Write
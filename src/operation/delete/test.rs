@@ -108,6 +108,8 @@ fn handle_success() {
     )
     .expect("should succeed");
     assert_eq!(delete_result.deleted_count, 3);
+    assert_eq!(delete_result.raw_reply.get_i32("n"), Ok(3));
+    assert_eq!(delete_result.raw_reply.get_f64("ok"), Ok(1.0));
 }
 
 #[test]
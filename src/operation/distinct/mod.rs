@@ -9,7 +9,7 @@ use crate::{
     cmap::{Command, RawCommandResponse, StreamDescription},
     coll::{options::DistinctOptions, Namespace},
     error::Result,
-    operation::{append_options, Operation, Retryability},
+    operation::{append_options, supports_comment_on_all_commands, Operation, Retryability},
     selection_criteria::SelectionCriteria,
 };
 
@@ -107,6 +107,10 @@ impl Operation for Distinct {
     fn supports_read_concern(&self, _description: &StreamDescription) -> bool {
         true
     }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+    }
 }
 
 #[derive(Debug, Deserialize)]
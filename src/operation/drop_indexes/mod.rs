@@ -5,7 +5,12 @@ use crate::{
     bson::{doc, Document},
     cmap::{Command, RawCommandResponse, StreamDescription},
     error::Result,
-    operation::{append_options, remove_empty_write_concern, Operation},
+    operation::{
+        append_options,
+        remove_empty_write_concern,
+        supports_comment_on_all_commands,
+        Operation,
+    },
     options::{DropIndexOptions, WriteConcern},
     Namespace,
 };
@@ -68,4 +73,12 @@ impl Operation for DropIndexes {
             .as_ref()
             .and_then(|opts| opts.write_concern.as_ref())
     }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.comment.is_none())
+    }
 }
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use crate::{
-    bson::doc,
+    bson::{doc, Bson},
     cmap::StreamDescription,
     coll::{options::DropIndexOptions, Namespace},
     concern::WriteConcern,
@@ -18,6 +18,7 @@ fn build() {
     let options = DropIndexOptions::builder()
         .max_time(Some(Duration::from_secs(1)))
         .write_concern(Some(WriteConcern::builder().journal(Some(true)).build()))
+        .comment(Some(Bson::from("drop")))
         .build();
 
     let mut drop_index = DropIndexes::new(ns, "foo".to_string(), Some(options));
@@ -31,6 +32,7 @@ fn build() {
             "index": "foo",
             "maxTimeMS": 1000,
             "writeConcern": { "j": true },
+            "comment": "drop",
         }
     )
 }
@@ -2,11 +2,11 @@
 mod test;
 
 use crate::{
-    bson::{doc, Document},
+    bson::{doc, Bson, Document},
     cmap::{Command, RawCommandResponse, StreamDescription},
     cursor::CursorSpecification,
     error::{ErrorKind, Result},
-    operation::{append_options, CursorBody, Operation, Retryability},
+    operation::{append_options, CursorBody, Operation, Retryability, SERVER_4_4_0_WIRE_VERSION},
     options::{CursorType, FindOptions, SelectionCriteria},
     Namespace,
 };
@@ -44,6 +44,28 @@ impl Find {
     }
 }
 
+/// Returns an error if `max_await_time` is set for a cursor that isn't `TailableAwait`. The server
+/// only accepts a `maxTimeMS` on the getMore commands of awaitData cursors, so the option would
+/// otherwise cause every getMore to fail.
+fn validate_max_await_time(options: &FindOptions) -> Result<()> {
+    if options.max_await_time.is_none() {
+        return Ok(());
+    }
+    let cursor_description = match options.cursor_type {
+        Some(CursorType::TailableAwait) => return Ok(()),
+        Some(CursorType::Tailable) => "tailable cursors without awaitData",
+        _ => "non-tailable cursors",
+    };
+    Err(ErrorKind::InvalidArgument {
+        message: format!(
+            "max_await_time can only be used with CursorType::TailableAwait, since the server \
+             rejects maxTimeMS on getMore commands for {}",
+            cursor_description
+        ),
+    }
+    .into())
+}
+
 impl Operation for Find {
     type O = CursorSpecification;
     type Command = Document;
@@ -81,6 +103,8 @@ impl Operation for Find {
                 }
                 _ => {}
             };
+
+            validate_max_await_time(options)?;
         }
 
         append_options(&mut body, self.options.as_ref())?;
@@ -111,18 +135,49 @@ impl Operation for Find {
     ) -> Result<Self::O> {
         let response: CursorBody = response.body()?;
 
-        Ok(CursorSpecification::new(
+        let mut spec = CursorSpecification::new(
             response.cursor,
             description.server_address.clone(),
             self.options.as_ref().and_then(|opts| opts.batch_size),
             self.options.as_ref().and_then(|opts| opts.max_await_time),
-        ))
+            self.options
+                .as_ref()
+                .and_then(|opts| opts.comment.clone())
+                .map(Bson::String),
+        );
+        spec.info.deny_unknown_fields = self
+            .options
+            .as_ref()
+            .and_then(|opts| opts.deny_unknown_fields)
+            .unwrap_or(false);
+        spec.info.numeric_coercion = self.options.as_ref().and_then(|opts| opts.numeric_coercion);
+        spec.info.max_document_depth = self
+            .options
+            .as_ref()
+            .and_then(|opts| opts.max_document_depth);
+        Ok(spec)
     }
 
     fn supports_read_concern(&self, _description: &StreamDescription) -> bool {
         true
     }
 
+    fn supports_default_comment(&self, _description: &StreamDescription) -> bool {
+        self.options
+            .as_ref()
+            .map_or(true, |opts| opts.comment.is_none())
+    }
+
+    fn supports_default_allow_disk_use(&self, description: &StreamDescription) -> bool {
+        // Servers older than 4.4 reject allowDiskUse on find, so the default is only applied
+        // where it would be accepted.
+        description.max_wire_version.unwrap_or(0) >= SERVER_4_4_0_WIRE_VERSION
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.allow_disk_use.is_none())
+    }
+
     fn selection_criteria(&self) -> Option<&SelectionCriteria> {
         self.options
             .as_ref()
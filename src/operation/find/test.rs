@@ -4,6 +4,7 @@ use crate::{
     bson::{doc, Document},
     bson_util,
     cmap::StreamDescription,
+    error::ErrorKind,
     operation::{
         test::{self, handle_response_test},
         Find,
@@ -74,6 +75,25 @@ fn build() {
     build_test(ns, Some(filter), Some(options), expected_body);
 }
 
+#[test]
+fn build_preserves_hint_for_retry() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let options = FindOptions::builder()
+        .hint(Hint::Name("x_1".to_string()))
+        .build();
+    let mut find = Find::new(ns, None, Some(options));
+
+    // A retry builds the command again from the same operation, possibly for a different server.
+    for _ in 0..2 {
+        let cmd = find.build(&StreamDescription::new_testing()).unwrap();
+        assert_eq!(cmd.body.get_str("hint"), Ok("x_1"));
+        find.update_for_retry();
+    }
+}
+
 #[test]
 fn build_cursor_type() {
     let ns = Namespace {
@@ -131,6 +151,7 @@ fn build_max_await_time() {
     };
 
     let options = FindOptions::builder()
+        .cursor_type(CursorType::TailableAwait)
         .max_await_time(Duration::from_millis(5))
         .max_time(Duration::from_millis(10))
         .build();
@@ -138,12 +159,39 @@ fn build_max_await_time() {
     let body = doc! {
         "find": "test_coll",
         "$db": "test_db",
+        "tailable": true,
+        "awaitData": true,
         "maxTimeMS": 10i32
     };
 
     build_test(ns, None, Some(options), body);
 }
 
+#[test]
+fn build_max_await_time_without_await_data() {
+    for cursor_type in [
+        None,
+        Some(CursorType::NonTailable),
+        Some(CursorType::Tailable),
+    ] {
+        let options = FindOptions {
+            cursor_type,
+            max_await_time: Some(Duration::from_millis(5)),
+            ..Default::default()
+        };
+        let mut find = Find::new(Namespace::empty(), None, Some(options));
+        let error = find
+            .build(&StreamDescription::new_testing())
+            .expect_err("max_await_time should be rejected");
+        match *error.kind {
+            ErrorKind::InvalidArgument { ref message } => {
+                assert!(message.contains("TailableAwait"), "{}", message)
+            }
+            ref e => panic!("expected InvalidArgument, got {:?}", e),
+        }
+    }
+}
+
 #[test]
 fn build_limit() {
     let ns = Namespace {
@@ -231,12 +279,7 @@ fn verify_max_await_time(max_await_time: Option<Duration>, cursor_type: Option<C
 #[test]
 fn handle_max_await_time() {
     verify_max_await_time(None, None);
-    verify_max_await_time(Some(Duration::from_millis(5)), None);
-    verify_max_await_time(
-        Some(Duration::from_millis(5)),
-        Some(CursorType::NonTailable),
-    );
-    verify_max_await_time(Some(Duration::from_millis(5)), Some(CursorType::Tailable));
+    verify_max_await_time(None, Some(CursorType::Tailable));
     verify_max_await_time(
         Some(Duration::from_millis(5)),
         Some(CursorType::TailableAwait),
@@ -8,7 +8,7 @@ use serde::{de::DeserializeOwned, Deserialize};
 
 use self::options::FindAndModifyOptions;
 use crate::{
-    bson::{doc, from_document, Bson, Document},
+    bson::{doc, Bson, Document},
     bson_util,
     cmap::{Command, RawCommandResponse, StreamDescription},
     coll::{
@@ -21,7 +21,13 @@ use crate::{
         Namespace,
     },
     error::{ErrorKind, Result},
-    operation::{append_options, remove_empty_write_concern, Operation, Retryability},
+    operation::{
+        append_options,
+        remove_empty_write_concern,
+        supports_comment_on_all_commands,
+        Operation,
+        Retryability,
+    },
     options::WriteConcern,
 };
 
@@ -112,6 +118,17 @@ where
             }
             .into());
         }
+        if self.options.hint.is_some()
+            && !self.is_acknowledged()
+            && description.max_wire_version.unwrap_or(0) < 9
+        {
+            return Err(ErrorKind::InvalidArgument {
+                message: "Specifying a hint to an unacknowledged find_one_and_x is not supported \
+                          on server versions < 4.4"
+                    .to_string(),
+            }
+            .into());
+        }
 
         let mut body: Document = doc! {
             Self::NAME: self.ns.coll.clone(),
@@ -136,7 +153,9 @@ where
         let response: Response = response.body()?;
 
         match response.value {
-            Bson::Document(doc) => Ok(Some(from_document(doc)?)),
+            Bson::Document(doc) => Ok(Some(bson_util::from_deserializer_with_path(
+                bson::Deserializer::new(Bson::Document(doc)),
+            )?)),
             Bson::Null => Ok(None),
             other => Err(ErrorKind::InvalidResponse {
                 message: format!(
@@ -153,6 +172,14 @@ where
         self.options.write_concern.as_ref()
     }
 
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+    }
+
     fn retryability(&self) -> Retryability {
         Retryability::Write
     }
@@ -160,5 +187,7 @@ where
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct Response {
+    // Unacknowledged findAndModify replies don't include a value.
+    #[serde(default)]
     value: Bson,
 }
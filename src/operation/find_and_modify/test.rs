@@ -7,11 +7,13 @@ use crate::{
     coll::options::ReturnDocument,
     operation::{test::handle_response_test, FindAndModify, Operation},
     options::{
+        Acknowledgment,
         FindOneAndDeleteOptions,
         FindOneAndReplaceOptions,
         FindOneAndUpdateOptions,
         Hint,
         UpdateModifications,
+        WriteConcern,
     },
     Namespace,
 };
@@ -129,6 +131,38 @@ fn build_with_delete() {
     assert_eq!(cmd.body, expected_body);
 }
 
+#[test]
+fn build_with_delete_write_concern() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let filter = doc! { "x": 1 };
+    let options = FindOneAndDeleteOptions {
+        write_concern: Some(WriteConcern::builder().w(Acknowledgment::Majority).build()),
+        max_time: Some(Duration::from_millis(500)),
+        ..Default::default()
+    };
+
+    let mut op = FindAndModify::<Document>::with_delete(ns, filter.clone(), Some(options));
+
+    let description = StreamDescription::new_testing();
+    let mut cmd = op.build(&description).unwrap();
+
+    let mut expected_body = doc! {
+        "findAndModify": "test_coll",
+        "query": filter,
+        "writeConcern": { "w": "majority" },
+        "maxTimeMS": 500,
+        "remove": true,
+    };
+
+    bson_util::sort_document(&mut cmd.body);
+    bson_util::sort_document(&mut expected_body);
+
+    assert_eq!(cmd.body, expected_body);
+}
+
 #[test]
 fn handle_success_delete() {
     let op = empty_delete();
@@ -308,6 +342,45 @@ fn build_with_replace() {
     assert_eq!(cmd.body, expected_body);
 }
 
+#[test]
+fn build_with_replace_write_concern() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let filter = doc! { "x": 1 };
+    let replacement = doc! { "x": 2 };
+    let options = FindOneAndReplaceOptions {
+        write_concern: Some(WriteConcern::builder().w(Acknowledgment::Majority).build()),
+        max_time: Some(Duration::from_millis(500)),
+        ..Default::default()
+    };
+
+    let mut op = FindAndModify::<Document>::with_replace(
+        ns,
+        filter.clone(),
+        replacement.clone(),
+        Some(options),
+    )
+    .unwrap();
+
+    let description = StreamDescription::new_testing();
+    let mut cmd = op.build(&description).unwrap();
+
+    let mut expected_body = doc! {
+        "findAndModify": "test_coll",
+        "query": filter,
+        "writeConcern": { "w": "majority" },
+        "maxTimeMS": 500,
+        "update": replacement,
+    };
+
+    bson_util::sort_document(&mut cmd.body);
+    bson_util::sort_document(&mut expected_body);
+
+    assert_eq!(cmd.body, expected_body);
+}
+
 #[test]
 fn handle_success_replace() {
     let op = empty_replace();
@@ -471,6 +544,90 @@ fn build_with_update() {
     assert_eq!(cmd.body, expected_body);
 }
 
+#[test]
+fn build_with_update_upsert_return_after() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let filter = doc! { "x": 1 };
+    let update = UpdateModifications::Document(doc! { "$inc": { "count": 1 } });
+    let options = FindOneAndUpdateOptions {
+        upsert: Some(true),
+        return_document: Some(ReturnDocument::After),
+        ..Default::default()
+    };
+
+    let mut op =
+        FindAndModify::<Document>::with_update(ns, filter.clone(), update.clone(), Some(options))
+            .unwrap();
+
+    let description = StreamDescription::new_testing();
+    let mut cmd = op.build(&description).unwrap();
+
+    let mut expected_body = doc! {
+        "findAndModify": "test_coll",
+        "query": filter,
+        "update": update.to_bson(),
+        "upsert": true,
+        "new": true,
+    };
+
+    bson_util::sort_document(&mut cmd.body);
+    bson_util::sort_document(&mut expected_body);
+
+    assert_eq!(cmd.body, expected_body);
+
+    // When the upsert inserts a new document, the server reports it as the "after" document.
+    let value = doc! { "_id": Bson::ObjectId(ObjectId::new()), "x": 1, "count": 1 };
+    let response = doc! {
+        "lastErrorObject": {
+            "n": 1,
+            "updatedExisting": false,
+            "upserted": value.get("_id").unwrap().clone(),
+        },
+        "value": value.clone(),
+        "ok": 1,
+    };
+    let result = handle_response_test(&op, response).unwrap();
+    assert_eq!(result, Some(value));
+}
+
+#[test]
+fn build_with_update_write_concern() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let filter = doc! { "x": 1 };
+    let update = UpdateModifications::Document(doc! { "$inc": { "x": 1 } });
+    let options = FindOneAndUpdateOptions {
+        write_concern: Some(WriteConcern::builder().w(Acknowledgment::Majority).build()),
+        max_time: Some(Duration::from_millis(500)),
+        ..Default::default()
+    };
+
+    let mut op =
+        FindAndModify::<Document>::with_update(ns, filter.clone(), update.clone(), Some(options))
+            .unwrap();
+
+    let description = StreamDescription::new_testing();
+    let mut cmd = op.build(&description).unwrap();
+
+    let mut expected_body = doc! {
+        "findAndModify": "test_coll",
+        "query": filter,
+        "writeConcern": { "w": "majority" },
+        "maxTimeMS": 500,
+        "update": { "$inc": { "x": 1 } },
+    };
+
+    bson_util::sort_document(&mut cmd.body);
+    bson_util::sort_document(&mut expected_body);
+
+    assert_eq!(cmd.body, expected_body);
+}
+
 #[test]
 fn handle_success_update() {
     let op = empty_update();
@@ -3,7 +3,7 @@ mod test;
 
 use std::{collections::VecDeque, time::Duration};
 
-use bson::{Document, RawDocumentBuf};
+use bson::{Bson, Document, RawDocumentBuf};
 use serde::Deserialize;
 
 use crate::{
@@ -12,7 +12,7 @@ use crate::{
     cmap::{conn::PinnedConnectionHandle, Command, RawCommandResponse, StreamDescription},
     cursor::CursorInformation,
     error::{ErrorKind, Result},
-    operation::Operation,
+    operation::{supports_comment_on_all_commands, Operation},
     options::SelectionCriteria,
     results::GetMoreResult,
     Namespace,
@@ -25,6 +25,7 @@ pub(crate) struct GetMore<'conn> {
     selection_criteria: SelectionCriteria,
     batch_size: Option<u32>,
     max_time: Option<Duration>,
+    comment: Option<Bson>,
     pinned_connection: Option<&'conn PinnedConnectionHandle>,
 }
 
@@ -39,6 +40,7 @@ impl<'conn> GetMore<'conn> {
             selection_criteria: SelectionCriteria::from_address(info.address),
             batch_size: info.batch_size,
             max_time: info.max_time,
+            comment: info.comment,
             pinned_connection: pinned,
         }
     }
@@ -50,7 +52,7 @@ impl<'conn> Operation for GetMore<'conn> {
 
     const NAME: &'static str = "getMore";
 
-    fn build(&mut self, _description: &StreamDescription) -> Result<Command> {
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
         let mut body = doc! {
             Self::NAME: self.cursor_id,
             "collection": self.ns.coll.clone(),
@@ -71,6 +73,13 @@ impl<'conn> Operation for GetMore<'conn> {
             body.insert("maxTimeMS", max_time.as_millis() as i32);
         }
 
+        // The comment field is only supported on getMore as of 4.4.
+        if let Some(ref comment) = self.comment {
+            if supports_comment_on_all_commands(description) {
+                body.insert("comment", comment.clone());
+            }
+        }
+
         Ok(Command::new(
             Self::NAME.to_string(),
             self.ns.db.clone(),
@@ -96,6 +105,10 @@ impl<'conn> Operation for GetMore<'conn> {
         Some(&self.selection_criteria)
     }
 
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        self.comment.is_none() && supports_comment_on_all_commands(description)
+    }
+
     fn pinned_connection(&self) -> Option<&PinnedConnectionHandle> {
         self.pinned_connection
     }
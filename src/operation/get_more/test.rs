@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use crate::{
-    bson::{doc, Document},
+    bson::{doc, Bson, Document},
     bson_util,
     cmap::StreamDescription,
     cursor::CursorInformation,
@@ -25,6 +25,11 @@ fn build_test(
         address,
         batch_size,
         max_time,
+        comment: None,
+        deny_unknown_fields: false,
+        numeric_coercion: None,
+        values_from_id: false,
+        max_document_depth: None,
     };
     let mut get_more = GetMore::new(info, None);
 
@@ -114,6 +119,11 @@ fn build_batch_size() {
         id: cursor_id,
         batch_size: Some((std::i32::MAX as u32) + 1),
         max_time: None,
+        comment: None,
+        deny_unknown_fields: false,
+        numeric_coercion: None,
+        values_from_id: false,
+        max_document_depth: None,
     };
     let mut op = GetMore::new(info, None);
     assert!(op.build(&StreamDescription::new_testing()).is_err())
@@ -132,6 +142,11 @@ fn op_selection_criteria() {
         id: 123,
         batch_size: None,
         max_time: None,
+        comment: None,
+        deny_unknown_fields: false,
+        numeric_coercion: None,
+        values_from_id: false,
+        max_document_depth: None,
     };
     let get_more = GetMore::new(info, None);
     let server_description = ServerDescription {
@@ -157,3 +172,28 @@ fn op_selection_criteria() {
     let server_info = ServerInfo::new_borrowed(&server_description);
     assert!(!predicate(&server_info));
 }
+
+#[test]
+fn build_comment() {
+    let info = CursorInformation {
+        ns: Namespace::empty(),
+        address: ServerAddress::default(),
+        id: 123,
+        batch_size: None,
+        max_time: None,
+        comment: Some(Bson::String("my comment".to_string())),
+        deny_unknown_fields: false,
+        numeric_coercion: None,
+        values_from_id: false,
+        max_document_depth: None,
+    };
+
+    let mut op = GetMore::new(info.clone(), None);
+    let cmd = op.build(&StreamDescription::with_wire_version(9)).unwrap();
+    assert_eq!(cmd.body.get_str("comment"), Ok("my comment"));
+
+    // getMore only accepts a comment on 4.4+.
+    let mut op = GetMore::new(info, None);
+    let cmd = op.build(&StreamDescription::new_testing()).unwrap();
+    assert!(cmd.body.get("comment").is_none());
+}
@@ -11,7 +11,13 @@ use crate::{
     bson_util,
     cmap::{Command, RawCommandResponse, StreamDescription},
     error::{BulkWriteFailure, Error, ErrorKind, Result},
-    operation::{remove_empty_write_concern, Operation, Retryability, WriteResponseBody},
+    operation::{
+        remove_empty_write_concern,
+        supports_comment_on_all_commands,
+        Operation,
+        Retryability,
+        WriteResponseBody,
+    },
     options::{InsertManyOptions, WriteConcern},
     results::InsertManyResult,
     Namespace,
@@ -167,19 +173,31 @@ impl<'a, T: Serialize> Operation for Insert<'a, T> {
                 ErrorKind::BulkWrite(BulkWriteFailure {
                     write_errors: response.write_errors,
                     write_concern_error: response.write_concern_error,
+                    raw_replies: vec![raw_response.body_utf8_lossy()?],
                     inserted_ids: map,
                 }),
                 response.labels,
             ));
         }
 
-        Ok(InsertManyResult { inserted_ids: map })
+        Ok(InsertManyResult {
+            inserted_ids: map,
+            raw_replies: vec![raw_response.body_utf8_lossy()?],
+        })
     }
 
     fn write_concern(&self) -> Option<&WriteConcern> {
         self.options.as_ref().and_then(|o| o.write_concern.as_ref())
     }
 
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+    }
+
     fn retryability(&self) -> Retryability {
         Retryability::Write
     }
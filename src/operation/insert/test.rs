@@ -271,6 +271,7 @@ fn handle_success() {
         .build(&StreamDescription::new_testing())
         .unwrap();
     let response = handle_response_test(&fixtures.op, doc! { "ok": 1.0, "n": 3 }).unwrap();
+    assert_eq!(response.raw_replies, vec![doc! { "ok": 1.0, "n": 3 }]);
     let inserted_ids = response.inserted_ids;
     assert_eq!(inserted_ids.len(), 3);
     assert_eq!(
@@ -322,10 +323,10 @@ fn handle_write_failure() {
         }
     };
 
-    let write_error_response =
-        handle_response_test(&fixtures.op, write_error_response).unwrap_err();
-    match *write_error_response.kind {
+    let error = handle_response_test(&fixtures.op, write_error_response.clone()).unwrap_err();
+    match *error.kind {
         ErrorKind::BulkWrite(bwe) => {
+            assert_eq!(bwe.raw_replies, vec![write_error_response]);
             let write_errors = bwe.write_errors.expect("write errors should be present");
             assert_eq!(write_errors.len(), 1);
             let expected_err = BulkWriteError {
@@ -69,6 +69,7 @@ impl Operation for ListIndexes {
             description.server_address.clone(),
             self.options.as_ref().and_then(|o| o.batch_size),
             self.options.as_ref().and_then(|o| o.max_time),
+            None,
         ))
     }
 
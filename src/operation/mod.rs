@@ -1,10 +1,13 @@
 mod abort_transaction;
 mod aggregate;
+mod coll_stats;
 mod commit_transaction;
+mod compact;
 mod count;
 mod count_documents;
 mod create;
 mod create_indexes;
+mod db_stats;
 mod delete;
 mod distinct;
 mod drop_collection;
@@ -17,8 +20,11 @@ mod insert;
 mod list_collections;
 mod list_databases;
 mod list_indexes;
+mod re_index;
 mod run_command;
+mod set_feature_compatibility_version;
 mod update;
+mod validate;
 
 #[cfg(test)]
 mod test;
@@ -44,17 +50,21 @@ use crate::{
         WriteFailure,
     },
     options::WriteConcern,
+    sdam::TopologyDescription,
     selection_criteria::SelectionCriteria,
     Namespace,
 };
 
 pub(crate) use abort_transaction::AbortTransaction;
 pub(crate) use aggregate::{Aggregate, AggregateTarget, ChangeStreamAggregate};
+pub(crate) use coll_stats::CollStats;
 pub(crate) use commit_transaction::CommitTransaction;
+pub(crate) use compact::Compact;
 pub(crate) use count::Count;
 pub(crate) use count_documents::CountDocuments;
 pub(crate) use create::Create;
 pub(crate) use create_indexes::CreateIndexes;
+pub(crate) use db_stats::DbStats;
 pub(crate) use delete::Delete;
 pub(crate) use distinct::Distinct;
 pub(crate) use drop_collection::DropCollection;
@@ -67,10 +77,27 @@ pub(crate) use insert::Insert;
 pub(crate) use list_collections::ListCollections;
 pub(crate) use list_databases::ListDatabases;
 pub(crate) use list_indexes::ListIndexes;
+pub(crate) use re_index::ReIndex;
 pub(crate) use run_command::RunCommand;
+pub(crate) use set_feature_compatibility_version::SetFeatureCompatibilityVersion;
 pub(crate) use update::Update;
+pub(crate) use validate::Validate;
 
 const SERVER_4_2_0_WIRE_VERSION: i32 = 8;
+const SERVER_4_4_0_WIRE_VERSION: i32 = 9;
+const SERVER_5_0_0_WIRE_VERSION: i32 = 13;
+const SERVER_7_0_0_WIRE_VERSION: i32 = 21;
+
+/// Returns whether the server supports the `comment` field on all of its commands, rather than
+/// just on `find` and `aggregate`.
+fn supports_comment_on_all_commands(description: &StreamDescription) -> bool {
+    description.max_wire_version.unwrap_or(0) >= SERVER_4_4_0_WIRE_VERSION
+}
+
+/// A function that is given an operation's selection criteria and the current topology, and
+/// returns the criteria to select a server with instead, if they should be overridden.
+pub(crate) type OverrideCriteriaFn =
+    fn(&SelectionCriteria, &TopologyDescription) -> Option<SelectionCriteria>;
 
 /// A trait modeling the behavior of a server side operation.
 pub(crate) trait Operation {
@@ -117,6 +144,12 @@ pub(crate) trait Operation {
         None
     }
 
+    /// Overrides the selection criteria based on the state of the topology, e.g. to route the
+    /// operation to the primary when some servers don't support executing it on a secondary.
+    fn override_criteria(&self) -> OverrideCriteriaFn {
+        |_, _| None
+    }
+
     /// Whether or not this operation will request acknowledgment from the server.
     fn is_acknowledged(&self) -> bool {
         self.write_concern()
@@ -129,11 +162,29 @@ pub(crate) trait Operation {
         None
     }
 
+    /// Whether this operation may modify data on the server.
+    fn is_write(&self) -> bool {
+        false
+    }
+
     /// Returns whether or not this command supports the `readConcern` field.
     fn supports_read_concern(&self, _description: &StreamDescription) -> bool {
         false
     }
 
+    /// Returns whether or not the client's default comment can be attached to this command, i.e.
+    /// whether the command supports the `comment` field and doesn't already specify one.
+    fn supports_default_comment(&self, _description: &StreamDescription) -> bool {
+        false
+    }
+
+    /// Returns whether or not the client's default `allowDiskUse` value can be attached to this
+    /// command, i.e. whether the command supports the `allowDiskUse` field on the server it is
+    /// being sent to and doesn't already specify one.
+    fn supports_default_allow_disk_use(&self, _description: &StreamDescription) -> bool {
+        false
+    }
+
     /// Whether this operation supports sessions or not.
     fn supports_sessions(&self) -> bool {
         true
@@ -282,6 +333,7 @@ pub(crate) struct WriteResponseBody<T = EmptyBody> {
     #[serde(flatten)]
     body: T,
 
+    #[serde(default)]
     n: u64,
 
     #[serde(rename = "writeErrors")]
@@ -303,6 +355,7 @@ impl<T> WriteResponseBody<T> {
         let failure = BulkWriteFailure {
             write_errors: self.write_errors.clone(),
             write_concern_error: self.write_concern_error.clone(),
+            raw_replies: Vec::new(),
             inserted_ids: Default::default(),
         };
 
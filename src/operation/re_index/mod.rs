@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod test;
+
+use bson::Document;
+use serde::Deserialize;
+
+use crate::{
+    bson::{doc, Bson},
+    bson_util,
+    cmap::{Command, RawCommandResponse, StreamDescription},
+    coll::{options::ReIndexOptions, Namespace},
+    error::{ErrorKind, Result},
+    operation::{
+        append_options,
+        supports_comment_on_all_commands,
+        Operation,
+        SERVER_5_0_0_WIRE_VERSION,
+    },
+    ServerType,
+};
+
+#[derive(Debug)]
+pub(crate) struct ReIndex {
+    ns: Namespace,
+    options: Option<ReIndexOptions>,
+}
+
+impl ReIndex {
+    pub(crate) fn new(ns: Namespace, options: Option<ReIndexOptions>) -> Self {
+        Self { ns, options }
+    }
+
+    #[cfg(test)]
+    fn empty() -> Self {
+        Self::new(
+            Namespace {
+                db: String::new(),
+                coll: String::new(),
+            },
+            None,
+        )
+    }
+}
+
+impl Operation for ReIndex {
+    type O = u64;
+    type Command = Document;
+
+    const NAME: &'static str = "reIndex";
+
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        // mongos never accepts reIndex, and replica set members stopped accepting it in 5.0;
+        // rejecting it up front gives a clearer error than the one returned by the server. Other
+        // deployments are left for the server to decide.
+        let is_replica_set_member = matches!(
+            description.initial_server_type,
+            ServerType::RsPrimary
+                | ServerType::RsSecondary
+                | ServerType::RsArbiter
+                | ServerType::RsOther
+                | ServerType::RsGhost
+        );
+        if description.initial_server_type == ServerType::Mongos
+            || (is_replica_set_member
+                && description.max_wire_version.unwrap_or(0) >= SERVER_5_0_0_WIRE_VERSION)
+        {
+            return Err(ErrorKind::InvalidArgument {
+                message: format!(
+                    "reIndex is not supported by the selected server of type {:?}; use \
+                     dropIndexes and createIndexes to rebuild the indexes of a replicated or \
+                     sharded collection",
+                    description.initial_server_type
+                ),
+            }
+            .into());
+        }
+
+        let mut body = doc! {
+            Self::NAME: self.ns.coll.clone(),
+        };
+        append_options(&mut body, self.options.as_ref())?;
+
+        Ok(Command::new(
+            Self::NAME.to_string(),
+            self.ns.db.clone(),
+            body,
+        ))
+    }
+
+    fn handle_response(
+        &self,
+        response: RawCommandResponse,
+        _description: &StreamDescription,
+    ) -> Result<Self::O> {
+        let response: ResponseBody = response.body()?;
+        bson_util::get_u64(&response.n_indexes).ok_or_else(|| {
+            ErrorKind::InvalidResponse {
+                message: format!(
+                    "expected integer nIndexes in reIndex response, but instead got {:?}",
+                    response.n_indexes
+                ),
+            }
+            .into()
+        })
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.comment.is_none())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseBody {
+    n_indexes: Bson,
+}
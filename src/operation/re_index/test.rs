@@ -0,0 +1,68 @@
+use crate::{
+    bson::doc,
+    cmap::StreamDescription,
+    coll::Namespace,
+    error::ErrorKind,
+    operation::{test::handle_response_test, Operation, ReIndex},
+    ServerType,
+};
+
+#[test]
+fn build() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let mut op = ReIndex::new(ns, None);
+    let mut description = StreamDescription::new_testing();
+    description.initial_server_type = ServerType::Standalone;
+    let command = op.build(&description).expect("error on build");
+    assert_eq!(command.body, doc! { "reIndex": "test_coll" });
+    assert_eq!(command.target_db, "test_db");
+}
+
+#[test]
+fn build_unsupported_server() {
+    for (server_type, max_wire_version) in [
+        (ServerType::Mongos, 8),
+        (ServerType::RsPrimary, 13),
+        (ServerType::RsSecondary, 17),
+    ] {
+        let mut description = StreamDescription::with_wire_version(max_wire_version);
+        description.initial_server_type = server_type;
+        let err = ReIndex::empty()
+            .build(&description)
+            .expect_err("reIndex should be rejected");
+        assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+    }
+}
+
+#[test]
+fn build_supported_server() {
+    for (server_type, max_wire_version) in [
+        (ServerType::Standalone, 17),
+        (ServerType::RsPrimary, 9),
+        (ServerType::RsSecondary, 12),
+        (ServerType::LoadBalancer, 17),
+    ] {
+        let mut description = StreamDescription::with_wire_version(max_wire_version);
+        description.initial_server_type = server_type;
+        ReIndex::empty()
+            .build(&description)
+            .expect("reIndex should be left to the server");
+    }
+}
+
+#[test]
+fn handle_success() {
+    let op = ReIndex::empty();
+
+    let n_indexes = handle_response_test(
+        &op,
+        doc! { "nIndexesWas": 3, "nIndexes": 3, "indexes": [], "ok": 1.0 },
+    )
+    .expect("handle should succeed");
+    assert_eq!(n_indexes, 3);
+
+    handle_response_test(&op, doc! { "ok": 1.0 }).expect_err("nIndexes should be required");
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod test;
+
+use bson::Document;
+
+use crate::{
+    bson::doc,
+    cmap::{Command, RawCommandResponse, StreamDescription},
+    error::Result,
+    operation::{Operation, WriteConcernOnlyBody, SERVER_7_0_0_WIRE_VERSION},
+    results::FeatureCompatibilityVersion,
+};
+
+#[derive(Debug)]
+pub(crate) struct SetFeatureCompatibilityVersion {
+    version: FeatureCompatibilityVersion,
+}
+
+impl SetFeatureCompatibilityVersion {
+    pub(crate) fn new(version: FeatureCompatibilityVersion) -> Self {
+        Self { version }
+    }
+}
+
+impl Operation for SetFeatureCompatibilityVersion {
+    type O = ();
+    type Command = Document;
+
+    const NAME: &'static str = "setFeatureCompatibilityVersion";
+
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        let mut body = doc! {
+            Self::NAME: self.version.to_string(),
+        };
+        if description.max_wire_version.unwrap_or(0) >= SERVER_7_0_0_WIRE_VERSION {
+            body.insert("confirm", true);
+        }
+
+        Ok(Command::new(
+            Self::NAME.to_string(),
+            "admin".to_string(),
+            body,
+        ))
+    }
+
+    fn handle_response(
+        &self,
+        response: RawCommandResponse,
+        _description: &StreamDescription,
+    ) -> Result<Self::O> {
+        let response: WriteConcernOnlyBody = response.body()?;
+        response.validate()
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{
+    bson::doc,
+    cmap::StreamDescription,
+    operation::{test::handle_response_test, Operation, SetFeatureCompatibilityVersion},
+    results::FeatureCompatibilityVersion,
+};
+
+#[test]
+fn build() {
+    let mut op = SetFeatureCompatibilityVersion::new(FeatureCompatibilityVersion::new(6, 0));
+    let command = op
+        .build(&StreamDescription::with_wire_version(17))
+        .expect("error on build");
+    assert_eq!(
+        command.body,
+        doc! { "setFeatureCompatibilityVersion": "6.0" }
+    );
+    assert_eq!(command.target_db, "admin");
+
+    // 7.0+ requires confirmation.
+    let mut op = SetFeatureCompatibilityVersion::new(FeatureCompatibilityVersion::new(7, 0));
+    let command = op
+        .build(&StreamDescription::with_wire_version(21))
+        .expect("error on build");
+    assert_eq!(
+        command.body,
+        doc! { "setFeatureCompatibilityVersion": "7.0", "confirm": true }
+    );
+}
+
+#[test]
+fn handle_success() {
+    let op = SetFeatureCompatibilityVersion::new(FeatureCompatibilityVersion::new(6, 0));
+    handle_response_test(&op, doc! { "ok": 1.0 }).unwrap();
+}
+
+#[test]
+fn parse_version() {
+    let version: FeatureCompatibilityVersion = "6.0".parse().unwrap();
+    assert_eq!(version, FeatureCompatibilityVersion::new(6, 0));
+    assert_eq!(version.to_string(), "6.0");
+    assert!(FeatureCompatibilityVersion::new(4, 4) < version);
+
+    assert!("6".parse::<FeatureCompatibilityVersion>().is_err());
+    assert!("six.zero".parse::<FeatureCompatibilityVersion>().is_err());
+}
@@ -4,11 +4,11 @@ mod test;
 use serde::Deserialize;
 
 use crate::{
-    bson::{doc, Bson, Document},
+    bson::{doc, oid::ObjectId, Bson, Document},
     bson_util,
     cmap::{Command, RawCommandResponse, StreamDescription},
-    error::{convert_bulk_errors, Result},
-    operation::{Operation, Retryability, WriteResponseBody},
+    error::{convert_bulk_errors, ErrorKind, Result},
+    operation::{supports_comment_on_all_commands, Operation, Retryability, WriteResponseBody},
     options::{UpdateModifications, UpdateOptions, WriteConcern},
     results::UpdateResult,
     Namespace,
@@ -17,10 +17,16 @@ use crate::{
 #[derive(Debug)]
 pub(crate) struct Update {
     ns: Namespace,
-    filter: Document,
-    update: UpdateModifications,
+    statements: Vec<(Document, UpdateModifications)>,
     multi: Option<bool>,
+    ordered: bool,
     options: Option<UpdateOptions>,
+    /// Whether write errors are returned as an `ErrorKind::BulkWrite` with the index of each
+    /// failed statement rather than being converted to an `ErrorKind::Write`.
+    bulk_errors: bool,
+    /// The number of statements included in the last command built, which is limited by the
+    /// selected server's `maxWriteBatchSize` and `maxBsonObjectSize`.
+    n_attempted: usize,
 }
 
 impl Update {
@@ -41,18 +47,134 @@ impl Update {
     pub(crate) fn new(
         ns: Namespace,
         filter: Document,
-        update: UpdateModifications,
+        mut update: UpdateModifications,
         multi: bool,
         options: Option<UpdateOptions>,
     ) -> Self {
+        let upsert = options.as_ref().and_then(|opts| opts.upsert) == Some(true);
+        if upsert && !multi && !references_id(&filter) {
+            add_upsert_id(&mut update);
+        }
+
         Self {
             ns,
-            filter,
-            update,
+            statements: vec![(filter, update)],
             multi: if multi { Some(true) } else { None },
+            ordered: true,
+            options,
+            bulk_errors: false,
+            n_attempted: 0,
+        }
+    }
+
+    /// Creates an operation that updates all documents matching each filter in `statements` with
+    /// the corresponding modifications, sending as many of the statements as the selected server
+    /// accepts in a single command. The rest can be retrieved with [`Update::into_unsent`].
+    pub(crate) fn new_many(
+        ns: Namespace,
+        statements: Vec<(Document, UpdateModifications)>,
+        options: Option<UpdateOptions>,
+    ) -> Self {
+        Self {
+            ns,
+            statements,
+            multi: Some(true),
+            ordered: true,
+            options,
+            bulk_errors: false,
+            n_attempted: 0,
+        }
+    }
+
+    /// Creates an operation that updates up to one document matching each filter in `statements`
+    /// with the corresponding modifications, sending as many of the statements as the selected
+    /// server accepts in a single command.
+    /// Unlike [`Update::new`], no `_id` is generated for upserts, and write errors are returned as
+    /// an `ErrorKind::BulkWrite` indexed by statement.
+    pub(crate) fn new_single_many(
+        ns: Namespace,
+        statements: Vec<(Document, UpdateModifications)>,
+        ordered: bool,
+        options: Option<UpdateOptions>,
+    ) -> Self {
+        Self {
+            ns,
+            statements,
+            multi: None,
+            ordered,
             options,
+            bulk_errors: true,
+            n_attempted: 0,
         }
     }
+
+    /// The number of statements that were sent in the command for this operation.
+    pub(crate) fn n_attempted(&self) -> usize {
+        self.n_attempted
+    }
+
+    /// Consumes the operation, returning the statements that didn't fit in the command that was
+    /// sent for it.
+    pub(crate) fn into_unsent(mut self) -> Vec<(Document, UpdateModifications)> {
+        self.statements.split_off(self.n_attempted)
+    }
+}
+
+/// Pre-generates the `_id` of the document that a single-document upsert will insert, so that the
+/// same `_id` is sent if the write is retried. Without this, a retry of an upsert whose
+/// acknowledgement was lost could insert a second document with a server-generated `_id`.
+///
+/// Updates that already reference an `_id` (anywhere in the filter, or in the modifications),
+/// pipeline updates and replacements are left unchanged. A replacement's `_id` would also be
+/// applied when it replaces an existing document, which the server rejects as a change to an
+/// immutable field, so the `_id` of an upserted replacement is left to the server.
+fn add_upsert_id(update: &mut UpdateModifications) {
+    let update = match update {
+        UpdateModifications::Document(ref mut update) => update,
+        UpdateModifications::Pipeline(_) => return,
+    };
+
+    match update.keys().next() {
+        Some(key) if key.starts_with('$') => {}
+        _ => return,
+    }
+
+    let sets_id = update.values().any(|fields| match fields {
+        Bson::Document(fields) => fields.keys().any(|k| k == "_id" || k.starts_with("_id.")),
+        _ => false,
+    });
+    if sets_id {
+        return;
+    }
+
+    // An invalid `$setOnInsert` is left as-is to be rejected by the server.
+    if let Bson::Document(set_on_insert) = update
+        .entry("$setOnInsert".to_string())
+        .or_insert_with(|| Bson::Document(Document::new()))
+    {
+        set_on_insert.insert("_id", ObjectId::new());
+    }
+}
+
+/// Whether `filter` refers to `_id` anywhere, whether as a field (e.g. `{ "_id.a": 1 }`, or nested
+/// within `$and`/`$or`) or as a field path in an aggregation expression (e.g. `"$_id"` in `$expr`).
+fn references_id(filter: &Document) -> bool {
+    fn is_id_path(path: &str) -> bool {
+        path == "_id" || path.starts_with("_id.")
+    }
+
+    fn value_references_id(value: &Bson) -> bool {
+        match value {
+            Bson::Document(doc) => references_id(doc),
+            Bson::Array(values) => values.iter().any(value_references_id),
+            Bson::String(s) => s.strip_prefix('$').map_or(false, is_id_path),
+            _ => false,
+        }
+    }
+
+    filter
+        .iter()
+        .any(|(key, value)| is_id_path(key) || value_references_id(value))
 }
 
 impl Operation for Update {
@@ -61,33 +183,76 @@ impl Operation for Update {
 
     const NAME: &'static str = "update";
 
-    fn build(&mut self, _description: &StreamDescription) -> Result<Command> {
+    fn build(&mut self, description: &StreamDescription) -> Result<Command> {
+        if !self.is_acknowledged()
+            && self
+                .options
+                .as_ref()
+                .map_or(false, |opts| opts.hint.is_some())
+            && description.max_wire_version.unwrap_or(0) < 8
+        {
+            return Err(ErrorKind::InvalidArgument {
+                message: "Specifying a hint to an unacknowledged update is not supported on \
+                          server versions < 4.2"
+                    .to_string(),
+            }
+            .into());
+        }
+
         let mut body = doc! {
             Self::NAME: self.ns.coll.clone(),
         };
 
-        let mut update = doc! {
-            "q": self.filter.clone(),
-            "u": self.update.to_bson(),
-        };
+        let mut updates = Vec::new();
+        let mut size = 0;
+        for (i, (filter, modifications)) in self
+            .statements
+            .iter()
+            .take(description.max_write_batch_size as usize)
+            .enumerate()
+        {
+            let mut update = doc! {
+                "q": filter.clone(),
+                "u": modifications.to_bson(),
+            };
 
-        if let Some(ref options) = self.options {
-            if let Some(upsert) = options.upsert {
-                update.insert("upsert", upsert);
-            }
+            if let Some(ref options) = self.options {
+                if let Some(upsert) = options.upsert {
+                    update.insert("upsert", upsert);
+                }
+
+                if let Some(ref array_filters) = options.array_filters {
+                    update.insert("arrayFilters", bson_util::to_bson_array(array_filters));
+                }
 
-            if let Some(ref array_filters) = options.array_filters {
-                update.insert("arrayFilters", bson_util::to_bson_array(array_filters));
+                if let Some(ref hint) = options.hint {
+                    update.insert("hint", hint.to_bson());
+                }
+
+                if let Some(ref collation) = options.collation {
+                    update.insert("collation", bson::to_bson(collation)?);
+                }
             }
 
-            if let Some(ref hint) = options.hint {
-                update.insert("hint", hint.to_bson());
+            if let Some(multi) = self.multi {
+                update.insert("multi", multi);
             }
 
-            if let Some(ref collation) = options.collation {
-                update.insert("collation", bson::to_bson(collation)?);
+            // The first statement is always sent so that the server reports one that is too large
+            // on its own.
+            let update_size = bson_util::array_entry_size_bytes(
+                i,
+                bson::to_raw_document_buf(&update)?.as_bytes().len(),
+            );
+            if i > 0 && size + update_size > description.max_bson_object_size as u64 {
+                break;
             }
+            updates.push(Bson::Document(update));
+            size += update_size;
+        }
+        self.n_attempted = updates.len();
 
+        if let Some(ref options) = self.options {
             if let Some(bypass_doc_validation) = options.bypass_document_validation {
                 body.insert("bypassDocumentValidation", bypass_doc_validation);
             }
@@ -103,12 +268,8 @@ impl Operation for Update {
             }
         };
 
-        if let Some(multi) = self.multi {
-            update.insert("multi", multi);
-        }
-
-        body.insert("updates", vec![Bson::Document(update)]);
-        body.insert("ordered", true); // command monitoring tests expect this (SPEC-1130)
+        body.insert("updates", updates);
+        body.insert("ordered", self.ordered); // command monitoring tests expect this (SPEC-1130)
 
         Ok(Command::new(
             Self::NAME.to_string(),
@@ -123,7 +284,11 @@ impl Operation for Update {
         _description: &StreamDescription,
     ) -> Result<Self::O> {
         let response: WriteResponseBody<UpdateBody> = raw_response.body_utf8_lossy()?;
-        response.validate().map_err(convert_bulk_errors)?;
+        if self.bulk_errors {
+            response.validate()?;
+        } else {
+            response.validate().map_err(convert_bulk_errors)?;
+        }
 
         let modified_count = response.n_modified;
         let upserted_id = response
@@ -133,12 +298,19 @@ impl Operation for Update {
             .and_then(|doc| doc.get("_id"))
             .map(Clone::clone);
 
-        let matched_count = if upserted_id.is_some() { 0 } else { response.n };
+        // `n` includes upserted documents, which didn't match any of the filters.
+        let n_upserted = response.upserted.as_ref().map_or(0, Vec::len) as u64;
+        let matched_count = if self.statements.len() == 1 && n_upserted > 0 {
+            0
+        } else {
+            response.n.saturating_sub(n_upserted)
+        };
 
         Ok(UpdateResult {
             matched_count,
             modified_count,
             upserted_id,
+            raw_reply: raw_response.body_utf8_lossy()?,
         })
     }
 
@@ -148,6 +320,14 @@ impl Operation for Update {
             .and_then(|opts| opts.write_concern.as_ref())
     }
 
+    fn is_write(&self) -> bool {
+        true
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+    }
+
     fn retryability(&self) -> Retryability {
         if self.multi != Some(true) {
             Retryability::Write
@@ -159,7 +339,7 @@ impl Operation for Update {
 
 #[derive(Deserialize)]
 pub(crate) struct UpdateBody {
-    #[serde(rename = "nModified")]
+    #[serde(rename = "nModified", default)]
     n_modified: u64,
     upserted: Option<Vec<Document>>,
 }
@@ -1,13 +1,13 @@
 use pretty_assertions::assert_eq;
 
 use crate::{
-    bson::{doc, Bson},
+    bson::{doc, Bson, Document},
     bson_util,
     cmap::StreamDescription,
     coll::options::Hint,
     concern::{Acknowledgment, WriteConcern},
     error::{ErrorKind, WriteConcernError, WriteError, WriteFailure},
-    operation::{test::handle_response_test, Operation, Update},
+    operation::{test::handle_response_test, Operation, Retryability, Update},
     options::{UpdateModifications, UpdateOptions},
     Namespace,
 };
@@ -150,6 +150,192 @@ fn build_many() {
     assert_eq!(cmd.body, expected_body);
 }
 
+#[test]
+fn build_upsert_generates_id() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let filter = doc! { "x": 1 };
+    let update = UpdateModifications::Document(doc! { "$inc": { "y": 1 } });
+    let options = UpdateOptions {
+        upsert: Some(true),
+        ..Default::default()
+    };
+
+    let mut op = Update::new(ns.clone(), filter, update, false, Some(options.clone()));
+    let description = StreamDescription::new_testing();
+
+    let first = op.build(&description).unwrap().body;
+    let second = op.build(&description).unwrap().body;
+    assert_eq!(first, second);
+
+    let u = first.get_array("updates").unwrap()[0]
+        .as_document()
+        .unwrap()
+        .get_document("u")
+        .unwrap();
+    assert_eq!(u.get_document("$inc").unwrap(), &doc! { "y": 1 });
+    assert!(matches!(
+        u.get_document("$setOnInsert").unwrap().get("_id"),
+        Some(Bson::ObjectId(_))
+    ));
+
+    // An `_id` in the filter or the modifications is used as-is.
+    let cases = [
+        (doc! { "_id": 1 }, doc! { "$inc": { "y": 1 } }),
+        (doc! { "_id.a": 1 }, doc! { "$inc": { "y": 1 } }),
+        (
+            doc! { "$and": [ { "x": 1 }, { "_id": 1 } ] },
+            doc! { "$inc": { "y": 1 } },
+        ),
+        (
+            doc! { "$or": [ { "_id": 1 }, { "_id": 2 } ] },
+            doc! { "$inc": { "y": 1 } },
+        ),
+        (
+            doc! { "$expr": { "$eq": [ "$_id", 1 ] } },
+            doc! { "$inc": { "y": 1 } },
+        ),
+        (doc! { "x": 1 }, doc! { "$set": { "_id": 1 } }),
+        (doc! { "x": 1 }, doc! { "$set": { "_id.a": 1 } }),
+        // Replacements may replace an existing document, whose `_id` can't be changed.
+        (doc! { "x": 1 }, doc! { "x": 1, "y": 1 }),
+    ];
+    for (filter, update) in cases {
+        let mut op = Update::new(
+            ns.clone(),
+            filter,
+            update.clone().into(),
+            false,
+            Some(options.clone()),
+        );
+        let body = op.build(&description).unwrap().body;
+        let u = body.get_array("updates").unwrap()[0]
+            .as_document()
+            .unwrap()
+            .get_document("u")
+            .unwrap();
+        assert_eq!(u, &update);
+    }
+
+    // Multi-document upserts are not modified.
+    let update = doc! { "$inc": { "y": 1 } };
+    let mut op = Update::new(ns, doc! {}, update.clone().into(), true, Some(options));
+    let body = op.build(&description).unwrap().body;
+    let u = body.get_array("updates").unwrap()[0]
+        .as_document()
+        .unwrap()
+        .get_document("u")
+        .unwrap();
+    assert_eq!(u, &update);
+}
+
+#[test]
+fn build_many() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let statements = vec![
+        (doc! { "x": 1 }, doc! { "$set": { "y": 1 } }.into()),
+        (doc! { "x": 2 }, vec![doc! { "$set": { "y": 2 } }].into()),
+    ];
+    let options = UpdateOptions {
+        upsert: Some(true),
+        bypass_document_validation: Some(true),
+        ..Default::default()
+    };
+
+    let mut op = Update::new_many(ns, statements, Some(options));
+    let mut cmd = op.build(&StreamDescription::new_testing()).unwrap();
+
+    let mut expected_body = doc! {
+        "update": "test_coll",
+        "updates": [
+            { "q": { "x": 1 }, "u": { "$set": { "y": 1 } }, "upsert": true, "multi": true },
+            { "q": { "x": 2 }, "u": [{ "$set": { "y": 2 } }], "upsert": true, "multi": true },
+        ],
+        "bypassDocumentValidation": true,
+        "ordered": true,
+    };
+
+    bson_util::sort_document(&mut cmd.body);
+    bson_util::sort_document(&mut expected_body);
+
+    assert_eq!(cmd.body, expected_body);
+    assert_eq!(op.retryability(), Retryability::None);
+}
+
+#[test]
+fn build_many_split() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let statements: Vec<(Document, UpdateModifications)> = (0..3)
+        .map(|i| {
+            (
+                doc! { "x": i },
+                doc! { "$set": { "y": "a".repeat(100) } }.into(),
+            )
+        })
+        .collect();
+
+    // Only as many statements as the server accepts in one batch are sent.
+    let mut description = StreamDescription::new_testing();
+    description.max_write_batch_size = 2;
+    let mut op = Update::new_many(ns.clone(), statements.clone(), None);
+    let cmd = op.build(&description).unwrap();
+    assert_eq!(cmd.body.get_array("updates").unwrap().len(), 2);
+    assert_eq!(op.n_attempted(), 2);
+    assert_eq!(unsent_filters(op), vec![doc! { "x": 2 }]);
+
+    // The same goes for the total size of the statements, but the first is always sent.
+    let mut description = StreamDescription::new_testing();
+    description.max_bson_object_size = 100;
+    let mut op = Update::new_many(ns, statements, None);
+    let cmd = op.build(&description).unwrap();
+    assert_eq!(cmd.body.get_array("updates").unwrap().len(), 1);
+    assert_eq!(op.n_attempted(), 1);
+    assert_eq!(unsent_filters(op), vec![doc! { "x": 1 }, doc! { "x": 2 }]);
+}
+
+fn unsent_filters(op: Update) -> Vec<Document> {
+    op.into_unsent()
+        .into_iter()
+        .map(|(filter, _)| filter)
+        .collect()
+}
+
+#[test]
+fn handle_success_many() {
+    let ns = Namespace {
+        db: String::new(),
+        coll: String::new(),
+    };
+    let statements = vec![
+        (doc! { "x": 1 }, doc! { "$set": { "y": 1 } }.into()),
+        (doc! { "x": 2 }, doc! { "$set": { "y": 2 } }.into()),
+        (doc! { "x": 3 }, doc! { "$set": { "y": 3 } }.into()),
+    ];
+    let op = Update::new_many(ns, statements, None);
+
+    let ok_response = doc! {
+        "ok": 1.0,
+        "n": 5,
+        "nModified": 3,
+        "upserted": [
+            { "index": 1, "_id": 1 }
+        ]
+    };
+
+    let update_result = handle_response_test(&op, ok_response).unwrap();
+    assert_eq!(update_result.matched_count, 4);
+    assert_eq!(update_result.modified_count, 3);
+    assert_eq!(update_result.upserted_id, Some(Bson::Int32(1)));
+}
+
 #[test]
 fn handle_success() {
     let op = Update::empty();
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod test;
+
+use bson::Document;
+
+use crate::{
+    bson::doc,
+    cmap::{Command, RawCommandResponse, StreamDescription},
+    coll::{options::ValidateOptions, Namespace},
+    error::{ErrorKind, Result},
+    operation::{append_options, supports_comment_on_all_commands, Operation},
+    results::ValidationResult,
+    selection_criteria::SelectionCriteria,
+};
+
+#[derive(Debug)]
+pub(crate) struct Validate {
+    ns: Namespace,
+    options: Option<ValidateOptions>,
+}
+
+impl Validate {
+    pub(crate) fn new(ns: Namespace, options: Option<ValidateOptions>) -> Self {
+        Self { ns, options }
+    }
+
+    #[cfg(test)]
+    fn empty() -> Self {
+        Self::new(
+            Namespace {
+                db: String::new(),
+                coll: String::new(),
+            },
+            None,
+        )
+    }
+}
+
+impl Operation for Validate {
+    type O = ValidationResult;
+    type Command = Document;
+
+    const NAME: &'static str = "validate";
+
+    fn build(&mut self, _description: &StreamDescription) -> Result<Command> {
+        if let Some(ref options) = self.options {
+            if options.full == Some(true) && options.background == Some(true) {
+                return Err(ErrorKind::InvalidArgument {
+                    message: "the full and background options to validate cannot both be true"
+                        .to_string(),
+                }
+                .into());
+            }
+        }
+
+        let mut body = doc! {
+            Self::NAME: self.ns.coll.clone(),
+        };
+        append_options(&mut body, self.options.as_ref())?;
+
+        Ok(Command::new(
+            Self::NAME.to_string(),
+            self.ns.db.clone(),
+            body,
+        ))
+    }
+
+    fn handle_response(
+        &self,
+        response: RawCommandResponse,
+        _description: &StreamDescription,
+    ) -> Result<Self::O> {
+        let mut result: ValidationResult = response.body()?;
+        result.raw_reply = response.body()?;
+        Ok(result)
+    }
+
+    fn selection_criteria(&self) -> Option<&SelectionCriteria> {
+        self.options
+            .as_ref()
+            .and_then(|opts| opts.selection_criteria.as_ref())
+    }
+
+    fn supports_default_comment(&self, description: &StreamDescription) -> bool {
+        supports_comment_on_all_commands(description)
+            && self
+                .options
+                .as_ref()
+                .map_or(true, |opts| opts.comment.is_none())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    bson::doc,
+    cmap::StreamDescription,
+    coll::{options::ValidateOptions, Namespace},
+    error::ErrorKind,
+    operation::{test::handle_response_test, Operation, Validate},
+};
+
+#[test]
+fn build() {
+    let ns = Namespace {
+        db: "test_db".to_string(),
+        coll: "test_coll".to_string(),
+    };
+    let options = ValidateOptions::builder().background(true).build();
+    let mut op = Validate::new(ns, Some(options));
+    let command = op
+        .build(&StreamDescription::new_testing())
+        .expect("error on build");
+    assert_eq!(
+        command.body,
+        doc! { "validate": "test_coll", "background": true }
+    );
+    assert_eq!(command.target_db, "test_db");
+}
+
+#[test]
+fn build_full_and_background() {
+    let options = ValidateOptions::builder()
+        .full(true)
+        .background(true)
+        .build();
+    let mut op = Validate::new(Namespace::empty(), Some(options));
+    let err = op
+        .build(&StreamDescription::new_testing())
+        .expect_err("full and background should be rejected");
+    assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+}
+
+#[test]
+fn handle_success() {
+    let op = Validate::empty();
+
+    let response = doc! {
+        "ns": "test_db.test_coll",
+        "nInvalidDocuments": 0,
+        "nrecords": 10,
+        "nIndexes": 1,
+        "keysPerIndex": { "_id_": 10 },
+        "valid": true,
+        "warnings": ["a warning"],
+        "errors": [],
+        "ok": 1.0,
+    };
+    let result = handle_response_test(&op, response.clone()).expect("handle should succeed");
+    assert_eq!(result.ns, "test_db.test_coll");
+    assert!(result.valid);
+    assert!(result.errors.is_empty());
+    assert_eq!(result.warnings, vec!["a warning".to_string()]);
+    assert_eq!(result.n_records, Some(10));
+    assert_eq!(result.n_indexes, Some(1));
+    assert_eq!(result.raw_reply, response);
+}
+
+#[test]
+fn handle_invalid() {
+    let op = Validate::empty();
+
+    let response = doc! {
+        "ns": "test_db.test_coll",
+        "valid": false,
+        "errors": ["corrupt record"],
+        "ok": 1.0,
+    };
+    let result = handle_response_test(&op, response).expect("handle should succeed");
+    assert!(!result.valid);
+    assert_eq!(result.errors, vec!["corrupt record".to_string()]);
+    assert!(result.warnings.is_empty());
+    assert_eq!(result.n_records, None);
+}
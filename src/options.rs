@@ -67,12 +67,22 @@ macro_rules! resolve_read_concern_with_session {
     }};
 }
 
-/// Updates the read concern of an options struct. If a transaction is starting or in progress,
+/// Updates the write concern of an options struct. If a transaction is starting or in progress,
 /// return an error if a write concern was specified for the operation. Otherwise, inherit the write
-/// concern from the collection/database.
+/// concern from the collection/database, and apply the client's default `w_timeout` to it if it
+/// specifies an acknowledged `w` but no `w_timeout`.
 macro_rules! resolve_write_concern_with_session {
     ($obj:expr, $opts:expr, $session:expr) => {{
-        resolve_rw_concern_with_session!($obj, $opts, $session, write_concern, "write")
+        resolve_rw_concern_with_session!($obj, $opts, $session, write_concern, "write")?;
+        if !$session.map_or(false, |session| session.in_transaction()) {
+            if let (Some(w_timeout), Some(write_concern)) = (
+                $obj.client().default_w_timeout(),
+                $opts.as_mut().and_then(|opts| opts.write_concern.as_mut()),
+            ) {
+                write_concern.apply_default_w_timeout(w_timeout);
+            }
+        }
+        crate::error::Result::<()>::Ok(())
     }};
 }
 
@@ -111,7 +121,8 @@ macro_rules! resolve_rw_concern_with_session {
 /// Updates the selection criteria of an options struct. If a transaction is starting or in progress
 /// and the selection criteria is not configured directly on the operation, inherit the selection
 /// criteria from the transaction options. Otherwise, use the selection criteria defined on the
-/// operation or inherit it from the collection/database.
+/// operation, route the operation to the primary if it is the first read after a write in a session
+/// with `read_primary_after_write` set, or inherit it from the collection/database.
 macro_rules! resolve_selection_criteria_with_session {
     ($obj:expr, $opts:expr, $session:expr) => {{
         use crate::client::session::TransactionState;
@@ -133,6 +144,20 @@ macro_rules! resolve_selection_criteria_with_session {
                     }
                 }
                 _ => {
+                    if session.read_primary_pending()
+                        && $opts
+                            .as_ref()
+                            .map(|opts| opts.selection_criteria.is_none())
+                            .unwrap_or(true)
+                    {
+                        $opts
+                            .get_or_insert_with(Default::default)
+                            .selection_criteria = Some(
+                            crate::selection_criteria::SelectionCriteria::ReadPreference(
+                                crate::selection_criteria::ReadPreference::Primary,
+                            ),
+                        );
+                    }
                     resolve_options!($obj, $opts, [selection_criteria]);
                 }
             }
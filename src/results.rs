@@ -1,12 +1,17 @@
 //! Contains the types of results returned by CRUD operations.
 
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    str::FromStr,
+};
 
 use crate::{
-    bson::{Bson, Document},
+    bson::{Bson, DateTime, Document, Timestamp},
     bson_util,
     change_stream::event::ResumeToken,
     db::options::CreateCollectionOptions,
+    error::{Error, ErrorKind, Result},
 };
 
 use bson::{Binary, RawDocumentBuf};
@@ -20,12 +25,18 @@ use serde::{Deserialize, Serialize};
 pub struct InsertOneResult {
     /// The `_id` field of the document inserted.
     pub inserted_id: Bson,
+
+    /// The full reply returned by the server, which may include fields not otherwise exposed by
+    /// this type (e.g. `opTime` or `electionId`).
+    #[serde(skip)]
+    pub raw_reply: Document,
 }
 
 impl InsertOneResult {
     pub(crate) fn from_insert_many_result(result: InsertManyResult) -> Self {
         Self {
             inserted_id: result.inserted_ids.get(&0).cloned().unwrap_or(Bson::Null),
+            raw_reply: result.raw_replies.into_iter().next().unwrap_or_default(),
         }
     }
 }
@@ -38,18 +49,28 @@ impl InsertOneResult {
 pub struct InsertManyResult {
     /// The `_id` field of the documents inserted.
     pub inserted_ids: HashMap<usize, Bson>,
+
+    /// The full replies returned by the server, which may include fields not otherwise exposed by
+    /// this type (e.g. `opTime` or `electionId`). The driver splits inserts that exceed the
+    /// server's size limits into multiple `insert` commands, in which case this will contain one
+    /// reply per command in the order they were sent.
+    #[serde(skip)]
+    pub raw_replies: Vec<Document>,
 }
 
 impl InsertManyResult {
     pub(crate) fn new() -> Self {
         InsertManyResult {
             inserted_ids: HashMap::new(),
+            raw_replies: Vec::new(),
         }
     }
 }
 
-/// The result of a [`Collection::update_one`](../struct.Collection.html#method.update_one) or
-/// [`Collection::update_many`](../struct.Collection.html#method.update_many) operation.
+/// The result of a [`Collection::update_one`](../struct.Collection.html#method.update_one),
+/// [`Collection::update_many`](../struct.Collection.html#method.update_many) or
+/// [`Collection::update_many_multi`](../struct.Collection.html#method.update_many_multi)
+/// operation.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
@@ -64,6 +85,30 @@ pub struct UpdateResult {
 
     /// The `_id` field of the upserted document.
     pub upserted_id: Option<Bson>,
+
+    /// The full reply returned by the server, which may include fields not otherwise exposed by
+    /// this type (e.g. `opTime` or `electionId`).
+    #[serde(skip)]
+    pub raw_reply: Document,
+}
+
+/// The result of a [`Collection::bulk_upsert`](../struct.Collection.html#method.bulk_upsert)
+/// operation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BulkUpsertResult {
+    /// The number of input documents whose key matched an existing document.
+    #[serde(serialize_with = "crate::bson::serde_helpers::serialize_u64_as_i64")]
+    pub matched_count: u64,
+
+    /// The number of existing documents that were changed by the operation.
+    #[serde(serialize_with = "crate::bson::serde_helpers::serialize_u64_as_i64")]
+    pub modified_count: u64,
+
+    /// The `_id` field of the documents that were inserted, keyed by the index of the input
+    /// document they were inserted for.
+    pub upserted_ids: HashMap<usize, Bson>,
 }
 
 /// The result of a [`Collection::delete_one`](../struct.Collection.html#method.delete_one) or
@@ -75,6 +120,11 @@ pub struct DeleteResult {
     /// The number of documents deleted by the operation.
     #[serde(serialize_with = "crate::bson::serde_helpers::serialize_u64_as_i64")]
     pub deleted_count: u64,
+
+    /// The full reply returned by the server, which may include fields not otherwise exposed by
+    /// this type (e.g. `opTime` or `electionId`).
+    #[serde(skip)]
+    pub raw_reply: Document,
 }
 
 /// Information about the index created as a result of a
@@ -110,6 +160,19 @@ pub(crate) struct GetMoreResult {
     pub(crate) post_batch_resume_token: Option<ResumeToken>,
 }
 
+/// A document returned by
+/// [`Collection::find_with_shard_names`](../struct.Collection.html#method.find_with_shard_names),
+/// along with the name of the shard it was read from.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ShardDocument<T> {
+    /// The name of the shard, as reported by the `listShards` command.
+    pub shard: String,
+
+    /// The document.
+    pub document: T,
+}
+
 /// Describes the type of data store returned when executing
 /// [`Database::list_collections`](../struct.Database.html#method.list_collections).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -190,3 +253,464 @@ pub struct DatabaseSpecification {
     /// is `None`.
     pub shards: Option<Document>,
 }
+
+/// The result of a [`Collection::validate`](../struct.Collection.html#method.validate) operation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ValidationResult {
+    /// The namespace of the validated collection.
+    pub ns: String,
+
+    /// Whether the collection is valid.
+    pub valid: bool,
+
+    /// The problems found in the collection. If this is non-empty, `valid` is false.
+    #[serde(default)]
+    pub errors: Vec<String>,
+
+    /// The issues found in the collection that don't make it invalid, e.g. ones which the server
+    /// fixed while validating it.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+
+    /// The number of documents in the collection. This is `None` when validating a sharded
+    /// collection, in which case the per-shard results are available in `raw_reply`.
+    #[serde(rename = "nrecords")]
+    pub n_records: Option<i64>,
+
+    /// The number of indexes on the collection. This is `None` when validating a sharded
+    /// collection, in which case the per-shard results are available in `raw_reply`.
+    pub n_indexes: Option<i64>,
+
+    /// The full reply returned by the server, which includes details not otherwise exposed by
+    /// this type (e.g. the validation results of each index).
+    #[serde(skip)]
+    pub raw_reply: Document,
+}
+
+/// Storage statistics for a collection, as returned by
+/// [`Collection::stats`](../struct.Collection.html#method.stats).
+///
+/// All of the sizes are divided by the scale factor requested in the options, rounding down.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CollectionStats {
+    /// The namespace of the collection.
+    pub ns: String,
+
+    /// The number of documents in the collection.
+    pub count: u64,
+
+    /// The total uncompressed size of the documents in the collection.
+    pub size: u64,
+
+    /// The average uncompressed size of a document in the collection. Unlike the `avgObjSize`
+    /// field reported by the server, this is scaled like the other sizes.
+    pub avg_obj_size: u64,
+
+    /// The amount of storage allocated to the collection's documents.
+    pub storage_size: u64,
+
+    /// The amount of storage allocated to the collection's documents that could be reclaimed.
+    /// This is only reported by server versions 4.4+ using the WiredTiger storage engine.
+    pub free_storage_size: Option<u64>,
+
+    /// The number of indexes on the collection.
+    pub nindexes: u64,
+
+    /// The total size of all indexes on the collection.
+    pub total_index_size: u64,
+
+    /// The size of each index on the collection, keyed by index name.
+    pub index_sizes: HashMap<String, u64>,
+
+    /// The sum of `storage_size` and `total_index_size`. This is only reported by server versions
+    /// 4.4+.
+    pub total_size: Option<u64>,
+
+    /// Whether the collection is capped.
+    pub capped: bool,
+
+    /// The scale factor the sizes were divided by.
+    pub scale_factor: u32,
+
+    /// The full reply returned by the server, with sizes in bytes, which includes details not
+    /// otherwise exposed by this type (e.g. storage engine statistics).
+    pub raw_reply: Document,
+}
+
+impl CollectionStats {
+    pub(crate) fn from_reply(reply: Document, scale: u32) -> Result<Self> {
+        let index_sizes = match reply.get_document("indexSizes") {
+            Ok(sizes) => sizes
+                .keys()
+                .map(|name| Ok((name.clone(), scaled_size(sizes, name, scale)?)))
+                .collect::<Result<_>>()?,
+            Err(_) => HashMap::new(),
+        };
+        Ok(Self {
+            ns: reply.get_str("ns").unwrap_or_default().to_string(),
+            count: stats_number(&reply, "count")?.unwrap_or(0.0) as u64,
+            size: scaled_size(&reply, "size", scale)?,
+            avg_obj_size: scaled_size(&reply, "avgObjSize", scale)?,
+            storage_size: scaled_size(&reply, "storageSize", scale)?,
+            free_storage_size: optional_scaled_size(&reply, "freeStorageSize", scale)?,
+            nindexes: stats_number(&reply, "nindexes")?.unwrap_or(0.0) as u64,
+            total_index_size: scaled_size(&reply, "totalIndexSize", scale)?,
+            index_sizes,
+            total_size: optional_scaled_size(&reply, "totalSize", scale)?,
+            capped: reply.get_bool("capped").unwrap_or(false),
+            scale_factor: scale,
+            raw_reply: reply,
+        })
+    }
+}
+
+/// Storage statistics for a database, as returned by
+/// [`Database::stats`](../struct.Database.html#method.stats).
+///
+/// All of the sizes are divided by the scale factor requested in the options, rounding down.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DatabaseStats {
+    /// The name of the database.
+    pub db: String,
+
+    /// The number of collections in the database.
+    pub collections: u64,
+
+    /// The number of views in the database.
+    pub views: u64,
+
+    /// The number of documents in the database across all collections.
+    pub objects: u64,
+
+    /// The average uncompressed size of a document in the database. Unlike the `avgObjSize` field
+    /// reported by the server, this is scaled like the other sizes.
+    pub avg_obj_size: u64,
+
+    /// The total uncompressed size of the documents in the database.
+    pub data_size: u64,
+
+    /// The amount of storage allocated to the documents in the database.
+    pub storage_size: u64,
+
+    /// The amount of storage allocated to the documents and indexes in the database that could be
+    /// reclaimed. This is only reported by server versions 4.4+ using the WiredTiger storage
+    /// engine.
+    pub free_storage_size: Option<u64>,
+
+    /// The number of indexes in the database across all collections.
+    pub indexes: u64,
+
+    /// The total size of all indexes in the database.
+    pub index_size: u64,
+
+    /// The sum of `storage_size` and `index_size`. This is only reported by server versions 4.4+.
+    pub total_size: Option<u64>,
+
+    /// The scale factor the sizes were divided by.
+    pub scale_factor: u32,
+
+    /// The full reply returned by the server, with sizes in bytes, which includes details not
+    /// otherwise exposed by this type (e.g. filesystem usage).
+    pub raw_reply: Document,
+}
+
+impl DatabaseStats {
+    pub(crate) fn from_reply(reply: Document, scale: u32) -> Result<Self> {
+        Ok(Self {
+            db: reply.get_str("db").unwrap_or_default().to_string(),
+            collections: stats_number(&reply, "collections")?.unwrap_or(0.0) as u64,
+            views: stats_number(&reply, "views")?.unwrap_or(0.0) as u64,
+            objects: stats_number(&reply, "objects")?.unwrap_or(0.0) as u64,
+            avg_obj_size: scaled_size(&reply, "avgObjSize", scale)?,
+            data_size: scaled_size(&reply, "dataSize", scale)?,
+            storage_size: scaled_size(&reply, "storageSize", scale)?,
+            free_storage_size: optional_scaled_size(&reply, "freeStorageSize", scale)?,
+            indexes: stats_number(&reply, "indexes")?.unwrap_or(0.0) as u64,
+            index_size: scaled_size(&reply, "indexSize", scale)?,
+            total_size: optional_scaled_size(&reply, "totalSize", scale)?,
+            scale_factor: scale,
+            raw_reply: reply,
+        })
+    }
+}
+
+/// Gets a numeric field from a stats reply, whose numbers may be of any BSON numeric type.
+fn stats_number(reply: &Document, key: &str) -> Result<Option<f64>> {
+    match reply.get(key) {
+        None => Ok(None),
+        Some(Bson::Int32(n)) => Ok(Some(f64::from(*n))),
+        Some(Bson::Int64(n)) => Ok(Some(*n as f64)),
+        Some(Bson::Double(n)) => Ok(Some(*n)),
+        Some(other) => Err(ErrorKind::InvalidResponse {
+            message: format!("expected {} to be a number, got {}", key, other),
+        }
+        .into()),
+    }
+}
+
+fn optional_scaled_size(reply: &Document, key: &str, scale: u32) -> Result<Option<u64>> {
+    Ok(stats_number(reply, key)?.map(|bytes| (bytes / f64::from(scale)).floor() as u64))
+}
+
+fn scaled_size(reply: &Document, key: &str, scale: u32) -> Result<u64> {
+    Ok(optional_scaled_size(reply, key, scale)?.unwrap_or(0))
+}
+
+/// A feature compatibility version of a MongoDB deployment, which determines the persisted
+/// features it can use. See [`Client::get_fcv`](../struct.Client.html#method.get_fcv) and
+/// [`Client::set_fcv`](../struct.Client.html#method.set_fcv).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct FeatureCompatibilityVersion {
+    /// The major version, e.g. 6 for "6.0".
+    pub major: u32,
+
+    /// The minor version, e.g. 0 for "6.0".
+    pub minor: u32,
+}
+
+impl FeatureCompatibilityVersion {
+    /// Creates a new `FeatureCompatibilityVersion` from its major and minor versions.
+    pub fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for FeatureCompatibilityVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for FeatureCompatibilityVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::from(ErrorKind::InvalidArgument {
+                message: format!("invalid feature compatibility version: {:?}", s),
+            })
+        };
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        Ok(Self {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// An entry recorded by the database profiler, as returned from
+/// [`Database::profile_entries`](../struct.Database.html#method.profile_entries).
+///
+/// See the documentation [here](https://www.mongodb.com/docs/manual/reference/database-profiler/)
+/// for more information on the fields of profiler output.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ProfileEntry {
+    /// The type of the operation, e.g. "query", "insert" or "command".
+    pub op: String,
+
+    /// The namespace the operation targeted.
+    pub ns: String,
+
+    /// The time in milliseconds the server took to perform the operation.
+    #[serde(
+        deserialize_with = "bson_util::deserialize_u64_from_bson_number",
+        serialize_with = "crate::bson::serde_helpers::serialize_u64_as_i64"
+    )]
+    pub millis: u64,
+
+    /// The command that was run. This is `None` for operations that are not associated with a
+    /// command document (e.g. replicated writes).
+    pub command: Option<Document>,
+
+    /// The time at which the operation began.
+    pub ts: DateTime,
+}
+
+/// An entry in the oplog of a replica set member, as returned from
+/// [`Client::oplog_tail`](../struct.Client.html#method.oplog_tail).
+///
+/// The format of oplog entries is internal to the server and may change between server versions,
+/// so [change streams](https://www.mongodb.com/docs/manual/changeStreams/) should be preferred
+/// where possible.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[non_exhaustive]
+pub struct OplogEntry {
+    /// The time at which the operation was applied on the primary.
+    pub ts: Timestamp,
+
+    /// The type of the operation: "i" (insert), "u" (update), "d" (delete), "c" (command) or "n"
+    /// (no-op).
+    pub op: String,
+
+    /// The namespace the operation applies to.
+    pub ns: String,
+
+    /// The operation itself, e.g. the inserted document or the modifications of an update.
+    pub o: Document,
+
+    /// For updates, the query identifying the document that was updated.
+    pub o2: Option<Document>,
+}
+
+/// A server's response to the `hello` command (or the legacy `isMaster` command), as returned from
+/// [`Client::hello`](../struct.Client.html#method.hello).
+///
+/// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/hello/) for
+/// more information on the fields of the response.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct HelloResult {
+    /// Whether the server is writable. This is true for a replica set primary, a mongos and a
+    /// standalone.
+    #[serde(alias = "ismaster", default)]
+    pub is_writable_primary: bool,
+
+    /// The name of the replica set the server belongs to. This is `None` if the server is not a
+    /// replica set member, e.g. a standalone or a mongos.
+    pub set_name: Option<String>,
+
+    /// The hosts in the replica set that are neither hidden, passive nor arbiters.
+    #[serde(default)]
+    pub hosts: Vec<String>,
+
+    /// The passive members (i.e. members with priority 0) of the replica set.
+    #[serde(default)]
+    pub passives: Vec<String>,
+
+    /// The arbiters of the replica set.
+    #[serde(default)]
+    pub arbiters: Vec<String>,
+
+    /// The address of the server that returned this response, as known to the replica set.
+    pub me: Option<String>,
+
+    /// The address of the current primary of the replica set, if there is one.
+    pub primary: Option<String>,
+
+    /// The maximum wire protocol version that the server supports.
+    pub max_wire_version: i32,
+
+    /// The time in minutes that a session remains active after its most recent use. This is
+    /// `None` if the server doesn't support sessions.
+    pub logical_session_timeout_minutes: Option<i64>,
+
+    /// The compressors the server supports of those configured via
+    /// [`ClientOptions::compressors`](crate::options::ClientOptions::compressors). This is `None`
+    /// if no compressors are configured.
+    pub compression: Option<Vec<String>>,
+
+    /// The SASL mechanisms supported for the client's user, if the client has a credential
+    /// without an explicit mechanism configured.
+    pub sasl_supported_mechs: Option<Vec<String>>,
+}
+
+/// The number of bytes sent and received by a [`Client`](../struct.Client.html), as returned by
+/// [`Client::compression_stats`](../struct.Client.html#method.compression_stats).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct CompressionStats {
+    /// The traffic that was compressed, keyed by the name of the compressor used (e.g. "zstd").
+    /// Messages received from the server are counted under whichever compressor the server used.
+    pub by_compressor: HashMap<String, CompressorStats>,
+
+    /// The traffic that was not compressed, e.g. because no compressor was agreed upon with the
+    /// server or because the command may not be compressed (such as the initial handshake).
+    pub uncompressed: CompressorStats,
+}
+
+/// The number of bytes sent and received using a single compressor (or none). Each message is
+/// counted both by its size on the wire and by the size it would have had without compression.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct CompressorStats {
+    /// The number of bytes sent to the server.
+    pub bytes_sent: u64,
+
+    /// The number of bytes that would have been sent to the server without compression.
+    pub bytes_sent_uncompressed: u64,
+
+    /// The number of bytes received from the server.
+    pub bytes_received: u64,
+
+    /// The number of bytes that would have been received from the server without compression.
+    pub bytes_received_uncompressed: u64,
+}
+
+impl CompressorStats {
+    /// The ratio of the number of bytes sent to the number that would have been sent without
+    /// compression. This is 1.0 if no bytes have been sent.
+    pub fn sent_ratio(&self) -> f64 {
+        compression_ratio(self.bytes_sent, self.bytes_sent_uncompressed)
+    }
+
+    /// The ratio of the number of bytes received to the number that would have been received
+    /// without compression. This is 1.0 if no bytes have been received.
+    pub fn received_ratio(&self) -> f64 {
+        compression_ratio(self.bytes_received, self.bytes_received_uncompressed)
+    }
+}
+
+/// The number of times an operation was retried, as reported to the callback set via
+/// [`Client::set_retry_hook`](../struct.Client.html#method.set_retry_hook).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OperationRetries {
+    /// The name of the command that was run for the operation, e.g. "find" or "insert".
+    pub command_name: String,
+
+    /// The number of retry attempts that were made after the first attempt failed. This is 0 if
+    /// the first attempt succeeded or failed with an error that can't be retried.
+    pub retries: u32,
+
+    /// Whether the operation ultimately succeeded.
+    pub succeeded: bool,
+}
+
+/// The number of commands a [`Client`](../struct.Client.html) has sent to a single server, as
+/// returned by [`Client::operation_counts`](../struct.Client.html#method.operation_counts).
+/// Commands are counted once per command rather than per document, so e.g. an `insert_many` that
+/// is sent as a single `insert` command is counted as one insert.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct OpCounts {
+    /// The number of `find` commands.
+    pub query: u64,
+
+    /// The number of `insert` commands.
+    pub insert: u64,
+
+    /// The number of `update` commands.
+    pub update: u64,
+
+    /// The number of `delete` commands.
+    pub delete: u64,
+
+    /// The number of `getMore` commands.
+    pub get_more: u64,
+
+    /// The number of all other commands, e.g. `aggregate` and `findAndModify`.
+    pub command: u64,
+}
+
+impl OpCounts {
+    /// The total number of commands sent to the server.
+    pub fn total(&self) -> u64 {
+        self.query + self.insert + self.update + self.delete + self.get_more + self.command
+    }
+}
+
+fn compression_ratio(wire: u64, uncompressed: u64) -> f64 {
+    if uncompressed == 0 {
+        1.0
+    } else {
+        wire as f64 / uncompressed as f64
+    }
+}
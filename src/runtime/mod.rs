@@ -22,7 +22,10 @@ pub(crate) use self::{
     sync_read_ext::SyncLittleEndianRead,
     worker_handle::{WorkerHandle, WorkerHandleListener},
 };
-use crate::{error::Result, options::ServerAddress};
+use crate::{
+    error::{ErrorKind, Result},
+    options::ServerAddress,
+};
 pub(crate) use http::HttpClient;
 #[cfg(feature = "async-std-runtime")]
 use interval::Interval;
@@ -141,15 +144,25 @@ pub(crate) fn interval(duration: Duration) -> Interval {
 pub(crate) async fn resolve_address(
     address: &ServerAddress,
 ) -> Result<impl Iterator<Item = SocketAddr>> {
+    if let ServerAddress::Unix { .. } = address {
+        return Err(ErrorKind::Internal {
+            message: format!("cannot resolve Unix domain socket address {}", address),
+        }
+        .into());
+    }
+
+    // The host is resolved separately from the port so that IPv6 literals (which are bracketed in
+    // the address's string form) and zone ids are handled correctly.
+    let host = (address.host(), address.port().unwrap_or(27017));
+
     #[cfg(feature = "tokio-runtime")]
     {
-        let socket_addrs = tokio::net::lookup_host(format!("{}", address)).await?;
+        let socket_addrs = tokio::net::lookup_host(host).await?;
         Ok(socket_addrs)
     }
 
     #[cfg(feature = "async-std-runtime")]
     {
-        let host = (address.host(), address.port().unwrap_or(27017));
         let socket_addrs = async_std::net::ToSocketAddrs::to_socket_addrs(&host).await?;
         Ok(socket_addrs)
     }
@@ -1,3 +1,5 @@
+#[cfg(unix)]
+use std::path::Path;
 use std::{
     net::SocketAddr,
     ops::DerefMut,
@@ -31,6 +33,10 @@ pub(crate) enum AsyncStream {
 
     /// A TLS connection over TCP.
     Tls(AsyncTlsStream),
+
+    /// A connection to the server over a Unix domain socket.
+    #[cfg(unix)]
+    Unix(AsyncUnixStream),
 }
 
 /// A runtime-agnostic async stream.
@@ -45,6 +51,19 @@ pub(crate) enum AsyncTcpStream {
     AsyncStd(async_std::net::TcpStream),
 }
 
+/// A runtime-agnostic async Unix domain socket stream.
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) enum AsyncUnixStream {
+    /// Wrapper around `tokio::net::UnixStream`.
+    #[cfg(feature = "tokio-runtime")]
+    Tokio(tokio::net::UnixStream),
+
+    /// Wrapper around `async_std::os::unix::net::UnixStream`.
+    #[cfg(feature = "async-std-runtime")]
+    AsyncStd(async_std::os::unix::net::UnixStream),
+}
+
 #[cfg(feature = "tokio-runtime")]
 impl From<tokio::net::TcpStream> for AsyncTcpStream {
     fn from(stream: tokio::net::TcpStream) -> Self {
@@ -140,9 +159,57 @@ impl AsyncTcpStream {
     }
 }
 
+#[cfg(unix)]
+impl AsyncUnixStream {
+    #[cfg(feature = "tokio-runtime")]
+    async fn try_connect(path: &Path) -> Result<Self> {
+        Ok(Self::Tokio(tokio::net::UnixStream::connect(path).await?))
+    }
+
+    #[cfg(feature = "async-std-runtime")]
+    async fn try_connect(path: &Path) -> Result<Self> {
+        Ok(Self::AsyncStd(
+            async_std::os::unix::net::UnixStream::connect(path).await?,
+        ))
+    }
+
+    async fn connect(path: &Path, connect_timeout: Option<Duration>) -> Result<Self> {
+        let timeout = connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+
+        if timeout == Duration::from_secs(0) {
+            Self::try_connect(path).await
+        } else {
+            runtime::timeout(timeout, Self::try_connect(path)).await?
+        }
+    }
+}
+
 impl AsyncStream {
     /// Creates a new Tokio TCP stream connected to the server as specified by `options`.
     pub(crate) async fn connect(options: StreamOptions) -> Result<Self> {
+        if let ServerAddress::Unix { ref path } = options.address {
+            if options.tls_options.is_some() {
+                return Err(ErrorKind::InvalidArgument {
+                    message: "TLS cannot be used with Unix domain socket connections".to_string(),
+                }
+                .into());
+            }
+
+            #[cfg(unix)]
+            return Ok(Self::Unix(
+                AsyncUnixStream::connect(path, options.connect_timeout).await?,
+            ));
+
+            #[cfg(not(unix))]
+            return Err(ErrorKind::InvalidArgument {
+                message: format!(
+                    "cannot connect to {}: Unix domain sockets are not supported on this platform",
+                    path.display()
+                ),
+            }
+            .into());
+        }
+
         let inner = AsyncTcpStream::connect(&options.address, options.connect_timeout).await?;
 
         // If there are TLS options, wrap the inner stream with rustls.
@@ -166,6 +233,8 @@ impl tokio::io::AsyncRead for AsyncStream {
             Self::Null => Poll::Ready(Ok(())),
             Self::Tcp(ref mut inner) => tokio::io::AsyncRead::poll_read(Pin::new(inner), cx, buf),
             Self::Tls(ref mut inner) => tokio::io::AsyncRead::poll_read(Pin::new(inner), cx, buf),
+            #[cfg(unix)]
+            Self::Unix(ref mut inner) => tokio::io::AsyncRead::poll_read(Pin::new(inner), cx, buf),
         }
     }
 }
@@ -180,6 +249,8 @@ impl AsyncWrite for AsyncStream {
             Self::Null => Poll::Ready(Ok(0)),
             Self::Tcp(ref mut inner) => AsyncWrite::poll_write(Pin::new(inner), cx, buf),
             Self::Tls(ref mut inner) => Pin::new(inner).poll_write(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(ref mut inner) => AsyncWrite::poll_write(Pin::new(inner), cx, buf),
         }
     }
 
@@ -188,6 +259,8 @@ impl AsyncWrite for AsyncStream {
             Self::Null => Poll::Ready(Ok(())),
             Self::Tcp(ref mut inner) => AsyncWrite::poll_flush(Pin::new(inner), cx),
             Self::Tls(ref mut inner) => Pin::new(inner).poll_flush(cx),
+            #[cfg(unix)]
+            Self::Unix(ref mut inner) => AsyncWrite::poll_flush(Pin::new(inner), cx),
         }
     }
 
@@ -196,6 +269,8 @@ impl AsyncWrite for AsyncStream {
             Self::Null => Poll::Ready(Ok(())),
             Self::Tcp(ref mut inner) => Pin::new(inner).poll_shutdown(cx),
             Self::Tls(ref mut inner) => Pin::new(inner).poll_shutdown(cx),
+            #[cfg(unix)]
+            Self::Unix(ref mut inner) => Pin::new(inner).poll_shutdown(cx),
         }
     }
 }
@@ -267,3 +342,73 @@ impl AsyncWrite for AsyncTcpStream {
         }
     }
 }
+
+#[cfg(unix)]
+impl AsyncRead for AsyncUnixStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf,
+    ) -> Poll<tokio::io::Result<()>> {
+        match self.deref_mut() {
+            #[cfg(feature = "tokio-runtime")]
+            Self::Tokio(ref mut inner) => Pin::new(inner).poll_read(cx, buf),
+
+            #[cfg(feature = "async-std-runtime")]
+            Self::AsyncStd(ref mut inner) => {
+                use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+                Pin::new(&mut inner.compat()).poll_read(cx, buf)
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsyncWrite for AsyncUnixStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<tokio::io::Result<usize>> {
+        match self.deref_mut() {
+            #[cfg(feature = "tokio-runtime")]
+            Self::Tokio(ref mut inner) => Pin::new(inner).poll_write(cx, buf),
+
+            #[cfg(feature = "async-std-runtime")]
+            Self::AsyncStd(ref mut inner) => {
+                use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+                Pin::new(&mut inner.compat()).poll_write(cx, buf)
+            }
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<tokio::io::Result<()>> {
+        match self.deref_mut() {
+            #[cfg(feature = "tokio-runtime")]
+            Self::Tokio(ref mut inner) => Pin::new(inner).poll_flush(cx),
+
+            #[cfg(feature = "async-std-runtime")]
+            Self::AsyncStd(ref mut inner) => {
+                use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+                Pin::new(&mut inner.compat()).poll_flush(cx)
+            }
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<tokio::io::Result<()>> {
+        match self.deref_mut() {
+            #[cfg(feature = "tokio-runtime")]
+            Self::Tokio(ref mut inner) => Pin::new(inner).poll_shutdown(cx),
+
+            #[cfg(feature = "async-std-runtime")]
+            Self::AsyncStd(ref mut inner) => {
+                use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+                Pin::new(&mut inner.compat()).poll_shutdown(cx)
+            }
+        }
+    }
+}
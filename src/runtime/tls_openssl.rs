@@ -6,13 +6,15 @@ use std::{
 
 use openssl::{
     error::ErrorStack,
-    ssl::{SslConnector, SslFiletype, SslMethod, SslVerifyMode},
+    pkey::PKey,
+    ssl::{SslConnector, SslFiletype, SslMethod, SslVerifyMode, SslVersion},
+    x509::X509,
 };
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_openssl::SslStream;
 
 use crate::{
-    client::options::TlsOptions,
+    client::options::{TlsOptions, TlsVersion},
     error::{Error, ErrorKind, Result},
 };
 
@@ -31,11 +33,7 @@ impl AsyncTlsStream {
     ) -> Result<Self> {
         init_trust();
 
-        let mut stream = make_ssl_stream(host, tcp_stream, cfg).map_err(|err| {
-            Error::from(ErrorKind::InvalidTlsConfig {
-                message: err.to_string(),
-            })
-        })?;
+        let mut stream = make_ssl_stream(host, tcp_stream, cfg)?;
         Pin::new(&mut stream).connect().await.map_err(|err| {
             use std::io;
             match err.into_io_error() {
@@ -83,6 +81,10 @@ fn make_openssl_connector(cfg: TlsOptions) -> std::result::Result<SslConnector,
         ca_file_path,
         cert_key_file_path,
         allow_invalid_hostnames: _,
+        min_tls_version,
+        ca_certificate_bytes,
+        client_certificate_bytes,
+        connector: _,
     } = cfg;
 
     if let Some(true) = allow_invalid_certificates {
@@ -91,9 +93,31 @@ fn make_openssl_connector(cfg: TlsOptions) -> std::result::Result<SslConnector,
     if let Some(path) = ca_file_path {
         builder.set_ca_file(path)?;
     }
+    if let Some(bytes) = ca_certificate_bytes {
+        for cert in X509::stack_from_pem(&bytes)? {
+            builder.cert_store_mut().add_cert(cert)?;
+        }
+    }
     if let Some(path) = cert_key_file_path {
         builder.set_certificate_file(path.clone(), SslFiletype::PEM)?;
         builder.set_private_key_file(path, SslFiletype::PEM)?;
+    } else if let Some(bytes) = client_certificate_bytes {
+        // The first certificate is the client's own; any others complete its chain.
+        let mut certs = X509::stack_from_pem(&bytes)?.into_iter();
+        if let Some(cert) = certs.next() {
+            builder.set_certificate(&cert)?;
+        }
+        for cert in certs {
+            builder.add_extra_chain_cert(cert)?;
+        }
+        builder.set_private_key(&PKey::private_key_from_pem(&bytes)?)?;
+    }
+    if let Some(version) = min_tls_version {
+        let version = match version {
+            TlsVersion::Tls12 => SslVersion::TLS1_2,
+            TlsVersion::Tls13 => SslVersion::TLS1_3,
+        };
+        builder.set_min_proto_version(Some(version))?;
     }
 
     Ok(builder.build())
@@ -108,13 +132,138 @@ fn make_ssl_stream(
     host: &str,
     tcp_stream: AsyncTcpStream,
     cfg: TlsOptions,
-) -> std::result::Result<SslStream<AsyncTcpStream>, ErrorStack> {
+) -> Result<SslStream<AsyncTcpStream>> {
     let verify_hostname = !cfg.allow_invalid_hostnames.unwrap_or(false);
-    let connector = make_openssl_connector(cfg)?;
+    let connector = match cfg.connector {
+        Some(ref connector) => connector.openssl_connector(host)?,
+        None => make_openssl_connector(cfg).map_err(tls_config_error)?,
+    };
     let ssl = connector
-        .configure()?
-        .use_server_name_indication(true)
-        .verify_hostname(verify_hostname)
-        .into_ssl(host)?;
-    SslStream::new(ssl, tcp_stream)
+        .configure()
+        .and_then(|config| {
+            config
+                .use_server_name_indication(true)
+                .verify_hostname(verify_hostname)
+                .into_ssl(host)
+        })
+        .map_err(tls_config_error)?;
+    SslStream::new(ssl, tcp_stream).map_err(tls_config_error)
+}
+
+fn tls_config_error(err: ErrorStack) -> Error {
+    ErrorKind::InvalidTlsConfig {
+        message: err.to_string(),
+    }
+    .into()
+}
+
+#[cfg(all(test, feature = "tokio-runtime"))]
+mod test {
+    use std::pin::Pin;
+
+    use openssl::{
+        asn1::Asn1Time,
+        hash::MessageDigest,
+        pkey::PKey,
+        rsa::Rsa,
+        ssl::{Ssl, SslAcceptor, SslMethod, SslVersion},
+        x509::{X509NameBuilder, X509},
+    };
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_openssl::SslStream;
+
+    use super::AsyncTlsStream;
+    use crate::{
+        client::options::{TlsOptions, TlsVersion},
+        error::{ErrorKind, Result},
+        runtime::stream::AsyncTcpStream,
+    };
+
+    /// Creates an acceptor with a self-signed certificate that only negotiates TLS versions between
+    /// `min` and `max`.
+    fn make_acceptor(min: SslVersion, max: SslVersion) -> SslAcceptor {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "localhost").unwrap();
+        let name = name.build();
+
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+        let cert = cert.build();
+
+        let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls_server()).unwrap();
+        builder.set_private_key(&key).unwrap();
+        builder.set_certificate(&cert).unwrap();
+        // TLS 1.1 has no ciphers allowed at OpenSSL's default security level.
+        builder.set_cipher_list("DEFAULT@SECLEVEL=0").unwrap();
+        builder.set_min_proto_version(Some(min)).unwrap();
+        builder.set_max_proto_version(Some(max)).unwrap();
+        builder.build()
+    }
+
+    /// Connects to a local server that only supports TLS versions between `server_min` and
+    /// `server_max`, requiring at least `client_min`.
+    async fn connect(
+        server_min: SslVersion,
+        server_max: SslVersion,
+        client_min: TlsVersion,
+    ) -> Result<AsyncTlsStream> {
+        let acceptor = make_acceptor(server_min, server_max);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ssl = Ssl::new(acceptor.context()).unwrap();
+            let mut stream = SslStream::new(ssl, stream).unwrap();
+            let _ = Pin::new(&mut stream).accept().await;
+            stream
+        });
+
+        let tcp_stream = AsyncTcpStream::from(TcpStream::connect(address).await.unwrap());
+        let options = TlsOptions::builder()
+            .allow_invalid_certificates(true)
+            .allow_invalid_hostnames(true)
+            .min_tls_version(client_min)
+            .build();
+        let result = AsyncTlsStream::connect("localhost", tcp_stream, options).await;
+        let _ = server.await;
+        result
+    }
+
+    #[tokio::test]
+    async fn min_tls_version_rejects_older_server() {
+        // The same server is accepted without the stricter minimum, so the failure below is due to
+        // the minimum version alone.
+        connect(SslVersion::TLS1_2, SslVersion::TLS1_2, TlsVersion::Tls12)
+            .await
+            .unwrap();
+
+        let error = connect(SslVersion::TLS1_2, SslVersion::TLS1_2, TlsVersion::Tls13)
+            .await
+            .unwrap_err();
+        assert!(matches!(*error.kind, ErrorKind::Io(_)), "{:?}", error);
+
+        let error = connect(SslVersion::TLS1_1, SslVersion::TLS1_1, TlsVersion::Tls12)
+            .await
+            .unwrap_err();
+        assert!(matches!(*error.kind, ErrorKind::Io(_)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn min_tls_version_accepts_newer_server() {
+        connect(SslVersion::TLS1_2, SslVersion::TLS1_3, TlsVersion::Tls12)
+            .await
+            .unwrap();
+        connect(SslVersion::TLS1_2, SslVersion::TLS1_3, TlsVersion::Tls13)
+            .await
+            .unwrap();
+    }
 }
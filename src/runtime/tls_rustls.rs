@@ -1,7 +1,8 @@
 use std::{
     convert::TryFrom,
+    fmt::Display,
     fs::File,
-    io::{BufReader, Seek, SeekFrom},
+    io::{BufRead, BufReader, Cursor, Seek, SeekFrom},
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
@@ -10,10 +11,12 @@ use std::{
 
 use rustls::{
     client::{ClientConfig, ServerCertVerified, ServerCertVerifier, ServerName},
+    version::{TLS12, TLS13},
     Certificate,
     Error as TlsError,
     OwnedTrustAnchor,
     RootCertStore,
+    SupportedProtocolVersion,
 };
 use rustls_pemfile::{certs, read_one, Item};
 use tokio::io::{AsyncRead, AsyncWrite};
@@ -21,7 +24,7 @@ use tokio_rustls::TlsConnector;
 use webpki_roots::TLS_SERVER_ROOTS;
 
 use crate::{
-    client::options::TlsOptions,
+    client::options::{TlsOptions, TlsVersion},
     error::{ErrorKind, Result},
 };
 
@@ -41,10 +44,16 @@ impl AsyncTlsStream {
         let name = ServerName::try_from(host).map_err(|e| ErrorKind::DnsResolve {
             message: format!("could not resolve {:?}: {}", host, e),
         })?;
-        let mut tls_config = make_rustls_config(cfg)?;
-        tls_config.enable_sni = true;
+        let tls_config = match cfg.connector {
+            Some(ref connector) => connector.rustls_config(host)?,
+            None => {
+                let mut tls_config = make_rustls_config(cfg)?;
+                tls_config.enable_sni = true;
+                Arc::new(tls_config)
+            }
+        };
 
-        let connector: TlsConnector = Arc::new(tls_config).into();
+        let connector: TlsConnector = tls_config.into();
         let conn = connector
             .connect_with(name, tcp_stream, |c| {
                 c.set_buffer_limit(None);
@@ -85,17 +94,7 @@ impl AsyncWrite for AsyncTlsStream {
 /// Converts `TlsOptions` into a rustls::ClientConfig.
 fn make_rustls_config(cfg: TlsOptions) -> Result<rustls::ClientConfig> {
     let mut store = RootCertStore::empty();
-    if let Some(path) = cfg.ca_file_path {
-        let ders = certs(&mut BufReader::new(File::open(&path)?)).map_err(|_| {
-            ErrorKind::InvalidTlsConfig {
-                message: format!(
-                    "Unable to parse PEM-encoded root certificate from {}",
-                    path.display()
-                ),
-            }
-        })?;
-        store.add_parsable_certificates(&ders);
-    } else {
+    if cfg.ca_file_path.is_none() && cfg.ca_certificate_bytes.is_none() {
         let trust_anchors = TLS_SERVER_ROOTS.0.iter().map(|ta| {
             OwnedTrustAnchor::from_subject_spki_name_constraints(
                 ta.subject,
@@ -105,60 +104,43 @@ fn make_rustls_config(cfg: TlsOptions) -> Result<rustls::ClientConfig> {
         });
         store.add_server_trust_anchors(trust_anchors);
     }
-
-    let mut config = if let Some(path) = cfg.cert_key_file_path {
+    if let Some(path) = cfg.ca_file_path {
         let mut file = BufReader::new(File::open(&path)?);
-        let certs = match certs(&mut file) {
-            Ok(certs) => certs.into_iter().map(Certificate).collect(),
-            Err(error) => {
-                return Err(ErrorKind::InvalidTlsConfig {
-                    message: format!(
-                        "Unable to parse PEM-encoded client certificate from {}: {}",
-                        path.display(),
-                        error,
-                    ),
-                }
-                .into())
-            }
-        };
+        add_root_certificates(&mut store, &mut file, &path.display())?;
+    }
+    if let Some(bytes) = cfg.ca_certificate_bytes {
+        add_root_certificates(&mut store, &mut bytes.as_slice(), &"ca_certificate_bytes")?;
+    }
 
-        file.seek(SeekFrom::Start(0))?;
-        let key = loop {
-            match read_one(&mut file) {
-                Ok(Some(Item::PKCS8Key(bytes))) | Ok(Some(Item::RSAKey(bytes))) => {
-                    break rustls::PrivateKey(bytes)
-                }
-                Ok(Some(_)) => continue,
-                Ok(None) => {
-                    return Err(ErrorKind::InvalidTlsConfig {
-                        message: format!("No PEM-encoded keys in {}", path.display()),
-                    }
-                    .into())
-                }
-                Err(_) => {
-                    return Err(ErrorKind::InvalidTlsConfig {
-                        message: format!(
-                            "Unable to parse PEM-encoded item from {}",
-                            path.display()
-                        ),
-                    }
-                    .into())
-                }
-            }
-        };
+    let builder = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(protocol_versions(cfg.min_tls_version))
+        .map_err(|error| ErrorKind::InvalidTlsConfig {
+            message: error.to_string(),
+        })?
+        .with_root_certificates(store);
 
-        ClientConfig::builder()
-            .with_safe_defaults()
-            .with_root_certificates(store)
+    let client_certificate = if let Some(path) = cfg.cert_key_file_path {
+        let mut file = BufReader::new(File::open(&path)?);
+        Some(read_client_certificate(&mut file, &path.display())?)
+    } else if let Some(bytes) = cfg.client_certificate_bytes {
+        Some(read_client_certificate(
+            &mut Cursor::new(bytes.as_slice()),
+            &"client_certificate_bytes",
+        )?)
+    } else {
+        None
+    };
+
+    let mut config = if let Some((certs, key)) = client_certificate {
+        builder
             .with_single_cert(certs, key)
             .map_err(|error| ErrorKind::InvalidTlsConfig {
                 message: error.to_string(),
             })?
     } else {
-        ClientConfig::builder()
-            .with_safe_defaults()
-            .with_root_certificates(store)
-            .with_no_client_auth()
+        builder.with_no_client_auth()
     };
 
     if let Some(true) = cfg.allow_invalid_certificates {
@@ -170,6 +152,78 @@ fn make_rustls_config(cfg: TlsOptions) -> Result<rustls::ClientConfig> {
     Ok(config)
 }
 
+/// Adds every PEM-encoded certificate read from `reader` to `store`. `source` describes where the
+/// certificates came from for use in error messages.
+fn add_root_certificates(
+    store: &mut RootCertStore,
+    reader: &mut impl BufRead,
+    source: &dyn Display,
+) -> Result<()> {
+    let ders = certs(reader).map_err(|_| ErrorKind::InvalidTlsConfig {
+        message: format!(
+            "Unable to parse PEM-encoded root certificate from {}",
+            source
+        ),
+    })?;
+    store.add_parsable_certificates(&ders);
+    Ok(())
+}
+
+/// Reads a PEM-encoded certificate chain and the private key for its first certificate from
+/// `reader`. `source` describes where the certificate came from for use in error messages.
+fn read_client_certificate(
+    reader: &mut (impl BufRead + Seek),
+    source: &dyn Display,
+) -> Result<(Vec<Certificate>, rustls::PrivateKey)> {
+    let certs = match certs(reader) {
+        Ok(certs) => certs.into_iter().map(Certificate).collect(),
+        Err(error) => {
+            return Err(ErrorKind::InvalidTlsConfig {
+                message: format!(
+                    "Unable to parse PEM-encoded client certificate from {}: {}",
+                    source, error,
+                ),
+            }
+            .into())
+        }
+    };
+
+    reader.seek(SeekFrom::Start(0))?;
+    let key = loop {
+        match read_one(reader) {
+            Ok(Some(Item::PKCS8Key(bytes))) | Ok(Some(Item::RSAKey(bytes))) => {
+                break rustls::PrivateKey(bytes)
+            }
+            Ok(Some(_)) => continue,
+            Ok(None) => {
+                return Err(ErrorKind::InvalidTlsConfig {
+                    message: format!("No PEM-encoded keys in {}", source),
+                }
+                .into())
+            }
+            Err(_) => {
+                return Err(ErrorKind::InvalidTlsConfig {
+                    message: format!("Unable to parse PEM-encoded item from {}", source),
+                }
+                .into())
+            }
+        }
+    };
+
+    Ok((certs, key))
+}
+
+/// The protocol versions to enable given the minimum version from the `TlsOptions`. rustls only
+/// supports TLS 1.2 and 1.3, both of which are enabled by default.
+fn protocol_versions(
+    min_tls_version: Option<TlsVersion>,
+) -> &'static [&'static SupportedProtocolVersion] {
+    match min_tls_version {
+        Some(TlsVersion::Tls13) => &[&TLS13],
+        Some(TlsVersion::Tls12) | None => &[&TLS13, &TLS12],
+    }
+}
+
 struct NoCertVerifier {}
 
 impl ServerCertVerifier for NoCertVerifier {
@@ -185,3 +239,34 @@ impl ServerCertVerifier for NoCertVerifier {
         Ok(ServerCertVerified::assertion())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use rustls::ProtocolVersion;
+
+    use super::protocol_versions;
+    use crate::client::options::TlsVersion;
+
+    fn versions(min_tls_version: Option<TlsVersion>) -> Vec<ProtocolVersion> {
+        protocol_versions(min_tls_version)
+            .iter()
+            .map(|version| version.version)
+            .collect()
+    }
+
+    #[test]
+    fn min_tls_version() {
+        assert_eq!(
+            versions(None),
+            vec![ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2]
+        );
+        assert_eq!(
+            versions(Some(TlsVersion::Tls12)),
+            vec![ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2]
+        );
+        assert_eq!(
+            versions(Some(TlsVersion::Tls13)),
+            vec![ProtocolVersion::TLSv1_3]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{ops::RangeInclusive, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
@@ -6,7 +6,7 @@ use crate::{
     bson::{oid::ObjectId, DateTime},
     bson_util,
     client::ClusterTime,
-    error::{ErrorKind, Result},
+    error::{Error, ErrorKind, Result},
     hello::HelloReply,
     options::ServerAddress,
     selection_criteria::TagSet,
@@ -82,6 +82,31 @@ impl Default for ServerType {
     }
 }
 
+/// The version of a server's topology state, as reported in its `hello` responses (MongoDB 4.4+).
+/// The counter increases whenever the server's state changes (e.g. on election or stepdown), and
+/// the process ID changes whenever the server process restarts.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TopologyVersion {
+    /// The ID of the server process that reported this version.
+    pub process_id: ObjectId,
+
+    /// A counter that increases whenever the server's topology state changes.
+    pub counter: i64,
+}
+
+impl TopologyVersion {
+    /// Whether a response reporting this version is older than one reporting `current`, and
+    /// should therefore be ignored.
+    ///
+    /// Versions reported by different processes (e.g. before and after a restart) are not
+    /// comparable, so a version is never stale with respect to one from another process.
+    pub(crate) fn is_stale(&self, current: &TopologyVersion) -> bool {
+        self.process_id == current.process_id && self.counter < current.counter
+    }
+}
+
 /// A description of the most up-to-date information known about a server.
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct ServerDescription {
@@ -141,10 +166,7 @@ impl PartialEq for ServerDescription {
 
 impl ServerDescription {
     pub(crate) fn new(mut address: ServerAddress, hello_reply: Option<Result<HelloReply>>) -> Self {
-        address = ServerAddress::Tcp {
-            host: address.host().to_lowercase(),
-            port: address.port(),
-        };
+        address = address.normalized();
 
         let mut description = Self {
             address,
@@ -210,33 +232,65 @@ impl ServerDescription {
         self.server_type.is_available()
     }
 
+    /// Whether the server's minimum wire version is newer than the maximum supported by the driver.
+    pub(crate) fn requires_future_wire_version(&self) -> bool {
+        matches!(
+            self.reply,
+            Ok(Some(ref reply))
+                if reply.command_response.min_wire_version.unwrap_or(0) > DRIVER_MAX_WIRE_VERSION
+        )
+    }
+
     pub(crate) fn compatibility_error_message(&self) -> Option<String> {
-        if let Ok(Some(ref reply)) = self.reply {
-            let hello_min_wire_version = reply.command_response.min_wire_version.unwrap_or(0);
-
-            if hello_min_wire_version > DRIVER_MAX_WIRE_VERSION {
-                return Some(format!(
-                    "Server at {} requires wire version {}, but this version of the MongoDB Rust \
-                     driver only supports up to {}",
-                    self.address, hello_min_wire_version, DRIVER_MAX_WIRE_VERSION,
-                ));
-            }
+        self.wire_version_incompatibility()
+            .map(|(message, _)| message)
+    }
 
-            let hello_max_wire_version = reply.command_response.max_wire_version.unwrap_or(0);
-
-            if hello_max_wire_version < DRIVER_MIN_WIRE_VERSION {
-                return Some(format!(
-                    "Server at {} reports wire version {}, but this version of the MongoDB Rust \
-                     driver requires at least {} (MongoDB {}).",
-                    self.address,
-                    hello_max_wire_version,
-                    DRIVER_MIN_WIRE_VERSION,
-                    DRIVER_MIN_DB_VERSION
-                ));
+    /// Gets an [`ErrorKind::IncompatibleServer`] error describing why the driver cannot
+    /// communicate with this server, if their ranges of supported wire versions do not overlap.
+    pub(crate) fn compatibility_error(&self) -> Option<Error> {
+        let (message, server_range) = self.wire_version_incompatibility()?;
+        Some(
+            ErrorKind::IncompatibleServer {
+                message,
+                driver_range: Some(DRIVER_MIN_WIRE_VERSION..=DRIVER_MAX_WIRE_VERSION),
+                server_range: Some(server_range),
             }
-        }
+            .into(),
+        )
+    }
+
+    fn wire_version_incompatibility(&self) -> Option<(String, RangeInclusive<i32>)> {
+        let reply = match self.reply {
+            Ok(Some(ref reply)) => reply,
+            _ => return None,
+        };
+        let hello_min_wire_version = reply.command_response.min_wire_version.unwrap_or(0);
+        let hello_max_wire_version = reply.command_response.max_wire_version.unwrap_or(0);
+
+        let message = if hello_min_wire_version > DRIVER_MAX_WIRE_VERSION {
+            format!(
+                "Server at {} requires wire version {}, but this version of the MongoDB Rust \
+                 driver only supports up to {}. Upgrade the driver to a version that supports \
+                 this server.",
+                self.address, hello_min_wire_version, DRIVER_MAX_WIRE_VERSION,
+            )
+        } else if hello_max_wire_version < DRIVER_MIN_WIRE_VERSION {
+            format!(
+                "Server at {} reports wire version {}, but this version of the MongoDB Rust \
+                 driver requires at least {} (MongoDB {}). Upgrade the server to MongoDB {} or \
+                 later, or use an older version of the driver.",
+                self.address,
+                hello_max_wire_version,
+                DRIVER_MIN_WIRE_VERSION,
+                DRIVER_MIN_DB_VERSION,
+                DRIVER_MIN_DB_VERSION
+            )
+        } else {
+            return None;
+        };
 
-        None
+        Some((message, hello_min_wire_version..=hello_max_wire_version))
     }
 
     pub(crate) fn set_name(&self) -> Result<Option<String>> {
@@ -344,6 +398,13 @@ impl ServerDescription {
         }
     }
 
+    pub(crate) fn topology_version(&self) -> Option<TopologyVersion> {
+        match self.reply {
+            Ok(Some(ref reply)) => reply.command_response.topology_version,
+            _ => None,
+        }
+    }
+
     pub(crate) fn cluster_time(&self) -> Result<Option<ClusterTime>> {
         match self.reply {
             Ok(None) => Ok(None),
@@ -14,7 +14,7 @@ use crate::{
     client::ClusterTime,
     cmap::Command,
     error::{Error, Result},
-    options::{ClientOptions, ServerAddress},
+    options::{ClientOptions, FutureWireVersionBehavior, ServerAddress},
     sdam::{
         description::server::{ServerDescription, ServerType},
         DEFAULT_HEARTBEAT_FREQUENCY,
@@ -89,6 +89,20 @@ pub(crate) struct TopologyDescription {
     /// respective supported wire versions.
     pub(crate) compatibility_error: Option<String>,
 
+    /// The [`ErrorKind::IncompatibleServer`](crate::error::ErrorKind::IncompatibleServer) error
+    /// corresponding to `compatibility_error`, which is returned from server selection.
+    #[serde(skip)]
+    pub(crate) incompatible_server_error: Option<Error>,
+
+    /// Describes a wire version incompatibility that the driver is ignoring because the client
+    /// was configured with [`FutureWireVersionBehavior::BestEffort`].
+    #[serde(skip)]
+    pub(crate) compatibility_warning: Option<String>,
+
+    /// How to handle servers that require a newer wire version than the driver supports.
+    #[serde(skip)]
+    pub(crate) future_wire_version_behavior: Option<FutureWireVersionBehavior>,
+
     /// Whether or not this topology supports sessions, and if so, what the logicalSessionTimeout
     /// is for them.
     #[serde(skip)]
@@ -107,6 +121,11 @@ pub(crate) struct TopologyDescription {
     #[serde(skip)]
     pub(crate) local_threshold: Option<Duration>,
 
+    /// The maximum amount of random jitter added to the round trip time of each suitable server
+    /// when determining the latency window for reads that may go to secondaries.
+    #[serde(skip)]
+    pub(crate) server_selection_jitter: Option<Duration>,
+
     /// The maximum amount of time to wait before checking a given server by sending server check.
     #[serde(skip)]
     pub(crate) heartbeat_freq: Option<Duration>,
@@ -121,6 +140,7 @@ impl PartialEq for TopologyDescription {
         // selection operations to try to select again, the only fields we care about are the ones
         // checked by the server selection algorithm.
         self.compatibility_error == other.compatibility_error
+            && self.compatibility_warning == other.compatibility_warning
             && self.servers == other.servers
             && self.topology_type == other.topology_type
     }
@@ -171,10 +191,14 @@ impl TopologyDescription {
             max_set_version: None,
             max_election_id: None,
             compatibility_error: None,
+            incompatible_server_error: None,
+            compatibility_warning: None,
+            future_wire_version_behavior: options.future_wire_version_behavior,
             session_support_status,
             transaction_support_status,
             cluster_time: None,
             local_threshold: options.local_threshold,
+            server_selection_jitter: options.server_selection_jitter,
             heartbeat_freq: options.heartbeat_freq,
             servers,
         })
@@ -188,10 +212,14 @@ impl TopologyDescription {
             max_set_version: None,
             max_election_id: None,
             compatibility_error: None,
+            incompatible_server_error: None,
+            compatibility_warning: None,
+            future_wire_version_behavior: None,
             session_support_status: SessionSupportStatus::Undetermined,
             transaction_support_status: TransactionSupportStatus::Undetermined,
             cluster_time: None,
             local_threshold: None,
+            server_selection_jitter: None,
             heartbeat_freq: None,
             servers: HashMap::new(),
         }
@@ -288,16 +316,32 @@ impl TopologyDescription {
     }
 
     /// Check the cluster for a compatibility error, and record the error message if one is found.
+    ///
+    /// If the client is configured to proceed in best-effort mode, a server that requires a newer
+    /// wire version than the driver supports is recorded as a warning instead.
     fn check_compatibility(&mut self) {
         self.compatibility_error = None;
+        self.incompatible_server_error = None;
+        self.compatibility_warning = None;
 
+        let best_effort =
+            self.future_wire_version_behavior == Some(FutureWireVersionBehavior::BestEffort);
         for server in self.servers.values() {
-            let error_message = server.compatibility_error_message();
-
-            if error_message.is_some() {
-                self.compatibility_error = error_message;
-                return;
+            let error_message = match server.compatibility_error_message() {
+                Some(message) => message,
+                None => continue,
+            };
+
+            if best_effort && server.requires_future_wire_version() {
+                if self.compatibility_warning.is_none() {
+                    self.compatibility_warning = Some(error_message);
+                }
+                continue;
             }
+
+            self.compatibility_error = Some(error_message);
+            self.incompatible_server_error = server.compatibility_error();
+            return;
         }
     }
 
@@ -463,8 +507,20 @@ impl TopologyDescription {
     /// ServerDescription.
     pub(crate) fn update(&mut self, mut server_description: ServerDescription) -> Result<()> {
         // Ignore updates from servers not currently in the cluster.
-        if !self.servers.contains_key(&server_description.address) {
-            return Ok(());
+        let current = match self.servers.get(&server_description.address) {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+
+        // Ignore responses that are older than the one the current description is based on, e.g.
+        // a response that was delayed on the network and arrives after a more recent one.
+        if let (Some(new_version), Some(current_version)) = (
+            server_description.topology_version(),
+            current.topology_version(),
+        ) {
+            if new_version.is_stale(&current_version) {
+                return Ok(());
+            }
         }
 
         // Update the round trip time on the server description to the weighted average as described
@@ -3,7 +3,7 @@ mod test;
 
 use std::{collections::HashMap, fmt, ops::Deref, sync::Arc, time::Duration};
 
-use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
 
 use super::TopologyDescription;
 use crate::{
@@ -105,10 +105,12 @@ impl TopologyDescription {
         criteria: &'a SelectionCriteria,
     ) -> Result<Vec<&'a ServerDescription>> {
         if let Some(message) = self.compatibility_error() {
-            return Err(ErrorKind::ServerSelection {
-                message: message.to_string(),
-            }
-            .into());
+            return Err(self.incompatible_server_error.clone().unwrap_or_else(|| {
+                ErrorKind::ServerSelection {
+                    message: message.to_string(),
+                }
+                .into()
+            }));
         }
 
         if let TopologyType::Unknown = self.topology_type {
@@ -124,7 +126,12 @@ impl TopologyDescription {
                 .collect(),
         };
 
-        self.retain_servers_within_latency_window(&mut suitable_servers);
+        let jitter = match criteria {
+            SelectionCriteria::ReadPreference(ReadPreference::Primary)
+            | SelectionCriteria::Predicate(_) => None,
+            SelectionCriteria::ReadPreference(_) => self.server_selection_jitter,
+        };
+        self.retain_servers_within_latency_window(&mut suitable_servers, jitter);
 
         Ok(suitable_servers)
     }
@@ -152,21 +159,38 @@ impl TopologyDescription {
     fn retain_servers_within_latency_window<'a>(
         &self,
         suitable_servers: &mut Vec<&'a ServerDescription>,
+        jitter: Option<Duration>,
     ) {
-        let shortest_average_rtt = suitable_servers
+        // Jitter only changes which servers are selected when there is more than one to choose
+        // from, so the random number generator isn't needed otherwise.
+        let jitter = jitter.filter(|jitter| !jitter.is_zero() && suitable_servers.len() > 1);
+        let mut rng = jitter.map(|_| SmallRng::from_entropy());
+        let rtts: Vec<Option<Duration>> = suitable_servers
             .iter()
-            .filter_map(|server_desc| server_desc.average_round_trip_time)
-            .fold(Option::<Duration>::None, |min, curr| match min {
-                Some(prev) => Some(prev.min(curr)),
-                None => Some(curr),
-            });
+            .map(|server_desc| {
+                let rtt = server_desc.average_round_trip_time?;
+                Some(match (jitter, rng.as_mut()) {
+                    (Some(jitter), Some(rng)) => rtt + jitter.mul_f64(rng.gen::<f64>()),
+                    _ => rtt,
+                })
+            })
+            .collect();
+
+        let shortest_average_rtt =
+            rtts.iter()
+                .flatten()
+                .fold(Option::<Duration>::None, |min, &curr| match min {
+                    Some(prev) => Some(prev.min(curr)),
+                    None => Some(curr),
+                });
 
         let local_threshold = self.local_threshold.unwrap_or(DEFAULT_LOCAL_THRESHOLD);
 
         let max_rtt_within_window = shortest_average_rtt.map(|rtt| rtt + local_threshold);
 
-        suitable_servers.retain(move |server_desc| {
-            if let Some(server_rtt) = server_desc.average_round_trip_time {
+        let mut rtts = rtts.into_iter();
+        suitable_servers.retain(move |_| {
+            if let Some(Some(server_rtt)) = rtts.next() {
                 if let Some(max_rtt) = max_rtt_within_window {
                     return server_rtt <= max_rtt;
                 }
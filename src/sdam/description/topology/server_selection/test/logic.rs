@@ -95,7 +95,7 @@ async fn run_test(test_file: TestFile) {
         );
 
         if let Some(ref expected_in_latency_window) = test_file.in_latency_window {
-            topology.retain_servers_within_latency_window(&mut actual_servers);
+            topology.retain_servers_within_latency_window(&mut actual_servers, None);
 
             assert_eq!(
                 get_sorted_addresses!(expected_in_latency_window),
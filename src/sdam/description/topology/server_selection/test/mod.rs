@@ -1,5 +1,6 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
+use rand::seq::SliceRandom;
 use serde::Deserialize;
 
 use crate::{
@@ -12,7 +13,7 @@ use crate::{
         ServerType,
         TopologyDescription,
     },
-    selection_criteria::{SelectionCriteria, TagSet},
+    selection_criteria::{ReadPreference, SelectionCriteria, TagSet},
 };
 
 mod in_window;
@@ -46,10 +47,14 @@ impl TestTopologyDescription {
             max_set_version: None,
             max_election_id: None,
             compatibility_error: None,
+            incompatible_server_error: None,
+            compatibility_warning: None,
+            future_wire_version_behavior: None,
             session_support_status: Default::default(),
             transaction_support_status: Default::default(),
             cluster_time: None,
             local_threshold: None,
+            server_selection_jitter: None,
             heartbeat_freq: heartbeat_frequency,
             servers,
         }
@@ -247,3 +252,93 @@ fn predicate_omits_unavailable() {
         Vec::<&ServerDescription>::new()
     );
 }
+
+fn test_server(
+    address: &str,
+    avg_rtt_ms: f64,
+    server_type: TestServerType,
+) -> TestServerDescription {
+    TestServerDescription {
+        address: address.to_string(),
+        avg_rtt_ms: Some(avg_rtt_ms),
+        server_type,
+        tags: None,
+        last_update_time: None,
+        last_write: None,
+        _max_wire_version: None,
+    }
+}
+
+#[test]
+fn jitter_spreads_reads_across_secondaries() {
+    let mut desc = TestTopologyDescription {
+        topology_type: TopologyType::ReplicaSetWithPrimary,
+        servers: vec![
+            test_server("localhost:27017", 1.0, TestServerType::RsPrimary),
+            test_server("localhost:27018", 5.0, TestServerType::RsSecondary),
+            test_server("localhost:27019", 25.0, TestServerType::RsSecondary),
+        ],
+    }
+    .into_topology_description(None);
+    let secondary = SelectionCriteria::ReadPreference(ReadPreference::Secondary {
+        options: Default::default(),
+    });
+    let secondary_preferred =
+        SelectionCriteria::ReadPreference(ReadPreference::SecondaryPreferred {
+            options: Default::default(),
+        });
+    let far = ServerAddress::parse("localhost:27019").unwrap();
+
+    // Without jitter, the farther secondary is always outside of the latency window.
+    for _ in 0..100 {
+        let in_window = desc.suitable_servers_in_latency_window(&secondary).unwrap();
+        assert_eq!(in_window.len(), 1);
+        assert_ne!(in_window[0].address, far);
+    }
+
+    desc.server_selection_jitter = Some(Duration::from_millis(100));
+    for criteria in [&secondary, &secondary_preferred] {
+        let mut counts: HashMap<ServerAddress, u32> = HashMap::new();
+        for _ in 0..2000 {
+            let in_window = desc.suitable_servers_in_latency_window(criteria).unwrap();
+            let selected = in_window.choose(&mut rand::thread_rng()).unwrap();
+            *counts.entry(selected.address.clone()).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 2, "{:?}", counts);
+        for count in counts.values() {
+            assert!(
+                *count > 400,
+                "reads not spread across secondaries: {:?}",
+                counts
+            );
+        }
+    }
+
+    // The primary is the only server suitable for a primary read, so jitter never excludes it.
+    let primary = SelectionCriteria::ReadPreference(ReadPreference::Primary);
+    for _ in 0..100 {
+        let in_window = desc.suitable_servers_in_latency_window(&primary).unwrap();
+        assert_eq!(in_window.len(), 1);
+    }
+}
+
+#[test]
+fn jitter_single_eligible_server() {
+    let mut desc = TestTopologyDescription {
+        topology_type: TopologyType::ReplicaSetWithPrimary,
+        servers: vec![
+            test_server("localhost:27017", 1.0, TestServerType::RsPrimary),
+            test_server("localhost:27018", 50.0, TestServerType::RsSecondary),
+        ],
+    }
+    .into_topology_description(None);
+    desc.server_selection_jitter = Some(Duration::from_secs(10));
+    let criteria = SelectionCriteria::ReadPreference(ReadPreference::Secondary {
+        options: Default::default(),
+    });
+    for _ in 0..100 {
+        let in_window = desc.suitable_servers_in_latency_window(&criteria).unwrap();
+        assert_eq!(in_window.len(), 1);
+        assert_eq!(in_window[0].address.to_string(), "localhost:27018");
+    }
+}
@@ -47,10 +47,14 @@ async fn run_test(test_file: TestFile) {
         max_set_version: None,
         max_election_id: None,
         compatibility_error: None,
+        incompatible_server_error: None,
+        compatibility_warning: None,
+        future_wire_version_behavior: None,
         session_support_status: Default::default(),
         transaction_support_status: Default::default(),
         cluster_time: None,
         local_threshold: None,
+        server_selection_jitter: None,
         heartbeat_freq: None,
         servers: {
             let mut servers = HashMap::new();
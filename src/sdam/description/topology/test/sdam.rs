@@ -12,7 +12,13 @@ use crate::{
     cmap::{conn::ConnectionGeneration, PoolGeneration},
     error::{BulkWriteFailure, CommandError, Error, ErrorKind},
     hello::{HelloCommandResponse, HelloReply, LastWrite, LEGACY_HELLO_COMMAND_NAME},
-    options::{ClientOptions, ReadPreference, SelectionCriteria, ServerAddress},
+    options::{
+        ClientOptions,
+        FutureWireVersionBehavior,
+        ReadPreference,
+        SelectionCriteria,
+        ServerAddress,
+    },
     sdam::{
         description::{
             server::{ServerDescription, ServerType},
@@ -21,6 +27,7 @@ use crate::{
         HandshakePhase,
         Topology,
         TopologyDescription,
+        TopologyVersion,
     },
     selection_criteria::TagSet,
     test::{
@@ -563,6 +570,228 @@ async fn load_balanced() {
     .await;
 }
 
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn stale_topology_version_ignored() {
+    let mut options = ClientOptions::parse_uri("mongodb://a/?directConnection=true", None)
+        .await
+        .unwrap();
+    options.test_options_mut().disable_monitoring_threads = true;
+    let topology = Topology::new(options).unwrap();
+    let address = ServerAddress::parse("a").unwrap();
+
+    let reply = |primary: bool, topology_version: TopologyVersion| {
+        let response = TestHelloCommandResponse {
+            is_writable_primary: Some(primary),
+            secondary: Some(!primary),
+            set_name: Some("rs".to_string()),
+            hosts: Some(vec!["a:27017".to_string()]),
+            ok: Some(1.0),
+            min_wire_version: Some(0),
+            max_wire_version: Some(9),
+            ..Default::default()
+        };
+        let mut command_response: HelloCommandResponse = response.into();
+        command_response.topology_version = Some(topology_version);
+        let reply = HelloReply {
+            server_address: address.clone(),
+            command_response,
+            round_trip_time: Duration::from_millis(1),
+            cluster_time: None,
+            raw_command_response: Default::default(),
+        };
+        ServerDescription::new(address.clone(), Some(Ok(reply)))
+    };
+    let server_type = || {
+        topology
+            .description()
+            .get_server_description(&address)
+            .unwrap()
+            .server_type
+    };
+
+    let process_id = ObjectId::new();
+    topology
+        .clone_updater()
+        .update(reply(
+            true,
+            TopologyVersion {
+                process_id,
+                counter: 2,
+            },
+        ))
+        .await;
+    assert_eq!(server_type(), ServerType::RsPrimary);
+
+    // A response with an older counter from the same process is ignored.
+    topology
+        .clone_updater()
+        .update(reply(
+            false,
+            TopologyVersion {
+                process_id,
+                counter: 1,
+            },
+        ))
+        .await;
+    assert_eq!(server_type(), ServerType::RsPrimary);
+
+    // A response with the same counter is applied.
+    topology
+        .clone_updater()
+        .update(reply(
+            false,
+            TopologyVersion {
+                process_id,
+                counter: 2,
+            },
+        ))
+        .await;
+    assert_eq!(server_type(), ServerType::RsSecondary);
+
+    // A response from a restarted process is applied even though its counter was reset.
+    topology
+        .clone_updater()
+        .update(reply(
+            true,
+            TopologyVersion {
+                process_id: ObjectId::new(),
+                counter: 0,
+            },
+        ))
+        .await;
+    assert_eq!(server_type(), ServerType::RsPrimary);
+    assert_eq!(
+        topology
+            .description()
+            .get_server_description(&address)
+            .unwrap()
+            .topology_version()
+            .map(|tv| tv.counter),
+        Some(0)
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn future_wire_version_behavior() {
+    let address = ServerAddress::parse("a").unwrap();
+    let reply = || {
+        let response = TestHelloCommandResponse {
+            is_writable_primary: Some(true),
+            ok: Some(1.0),
+            min_wire_version: Some(100),
+            max_wire_version: Some(120),
+            ..Default::default()
+        };
+        let reply = HelloReply {
+            server_address: address.clone(),
+            command_response: response.into(),
+            round_trip_time: Duration::from_millis(1),
+            cluster_time: None,
+            raw_command_response: Default::default(),
+        };
+        ServerDescription::new(address.clone(), Some(Ok(reply)))
+    };
+
+    for behavior in [
+        None,
+        Some(FutureWireVersionBehavior::Fail),
+        Some(FutureWireVersionBehavior::BestEffort),
+    ] {
+        let mut options = ClientOptions::parse_uri("mongodb://a/?directConnection=true", None)
+            .await
+            .unwrap();
+        options.future_wire_version_behavior = behavior;
+        let handler = Arc::new(EventHandler::new());
+        options.sdam_event_handler = Some(handler.clone());
+        options.test_options_mut().disable_monitoring_threads = true;
+        let mut subscriber = handler.subscribe();
+        let topology = Topology::new(options).unwrap();
+        topology.clone_updater().update(reply()).await;
+
+        let description = topology.description();
+        let warning_event = subscriber
+            .wait_for_event(Duration::from_millis(500), |event| {
+                matches!(event, Event::Sdam(SdamEvent::CompatibilityWarning(_)))
+            })
+            .await
+            .map(|event| match event.unwrap_sdam_event() {
+                SdamEvent::CompatibilityWarning(event) => event.message,
+                _ => unreachable!(),
+            });
+        if behavior == Some(FutureWireVersionBehavior::BestEffort) {
+            assert!(description.compatibility_error().is_none());
+            let warning = description.compatibility_warning.unwrap();
+            assert!(warning.contains("requires wire version 100"), "{}", warning);
+            assert_eq!(warning_event, Some(warning));
+            assert_eq!(
+                description
+                    .get_server_description(&address)
+                    .unwrap()
+                    .server_type,
+                ServerType::Standalone
+            );
+        } else {
+            let error = description.compatibility_error().unwrap();
+            assert!(error.contains("requires wire version 100"), "{}", error);
+            assert!(description.compatibility_warning.is_none());
+            assert!(warning_event.is_none());
+        }
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn server_too_old() {
+    let mut options = ClientOptions::parse_uri("mongodb://a/?directConnection=true", None)
+        .await
+        .unwrap();
+    options.test_options_mut().disable_monitoring_threads = true;
+    let topology = Topology::new(options).unwrap();
+    let address = ServerAddress::parse("a").unwrap();
+
+    let response = TestHelloCommandResponse {
+        is_writable_primary: Some(true),
+        ok: Some(1.0),
+        min_wire_version: Some(0),
+        max_wire_version: Some(2),
+        ..Default::default()
+    };
+    let reply = HelloReply {
+        server_address: address.clone(),
+        command_response: response.into(),
+        round_trip_time: Duration::from_millis(1),
+        cluster_time: None,
+        raw_command_response: Default::default(),
+    };
+    topology
+        .clone_updater()
+        .update(ServerDescription::new(address, Some(Ok(reply))))
+        .await;
+
+    let error = topology
+        .description()
+        .suitable_servers_in_latency_window(&SelectionCriteria::ReadPreference(
+            ReadPreference::Primary,
+        ))
+        .unwrap_err();
+    match *error.kind {
+        ErrorKind::IncompatibleServer {
+            ref message,
+            ref driver_range,
+            ref server_range,
+        } => {
+            assert!(message.contains("Upgrade the server"), "{}", message);
+            assert_eq!(server_range, &Some(0..=2));
+            let driver_range = driver_range.clone().unwrap();
+            assert!(*driver_range.start() > 2);
+            assert!(driver_range.start() <= driver_range.end());
+        }
+        _ => panic!("expected IncompatibleServer error, got {:?}", error),
+    }
+}
+
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
@@ -7,7 +7,7 @@ mod srv_polling;
 mod test;
 mod topology;
 
-pub use self::public::{ServerInfo, ServerType, TopologyType};
+pub use self::public::{ServerInfo, ServerType, TopologyType, TopologyVersion};
 
 pub(crate) use self::{
     description::{
@@ -25,6 +25,7 @@ pub(crate) use self::{
         HandshakePhase,
         Topology,
         TopologyCheckRequestReceiver,
+        TopologyState,
         TopologyUpdater,
         TopologyWatcher,
     },
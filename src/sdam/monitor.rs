@@ -1,6 +1,10 @@
-use std::time::{Duration, Instant};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use bson::doc;
+use tokio::sync::Notify;
 
 use super::{
     description::server::ServerDescription,
@@ -21,10 +25,12 @@ use crate::{
     hello::{hello_command, run_hello, HelloReply},
     options::{ClientOptions, ServerAddress},
     runtime,
+    sdam::TopologyVersion,
 };
 
 pub(crate) const DEFAULT_HEARTBEAT_FREQUENCY: Duration = Duration::from_secs(10);
 pub(crate) const MIN_HEARTBEAT_FREQUENCY: Duration = Duration::from_millis(500);
+const INITIAL_HANDSHAKE_RETRY_BACKOFF: Duration = Duration::from_millis(100);
 
 /// Monitor that performs regular heartbeats to determine server status.
 pub(crate) struct Monitor {
@@ -36,6 +42,23 @@ pub(crate) struct Monitor {
     sdam_event_emitter: Option<SdamEventEmitter>,
     update_request_receiver: TopologyCheckRequestReceiver,
     client_options: ClientOptions,
+
+    /// The topology version reported by the most recent check on the current connection. If
+    /// present, the next check uses an awaitable hello, which the server only responds to once its
+    /// state changes or `heartbeat_freq` has elapsed.
+    topology_version: Option<TopologyVersion>,
+
+    /// Whether the most recent check was an awaitable hello that succeeded, in which case the next
+    /// check can start without waiting for the heartbeat frequency.
+    last_check_awaited: bool,
+
+    /// Whether the result of the first check of this server has been reported to the topology.
+    /// Until it has, a failed handshake may be retried according to `initial_handshake_retries`.
+    initial_check_complete: bool,
+
+    /// The state shared with the [`RttMonitor`] for this server, which is started once checks
+    /// begin using awaitable hellos.
+    rtt_monitor: Option<Arc<RttMonitorState>>,
 }
 
 impl Monitor {
@@ -57,6 +80,10 @@ impl Monitor {
             sdam_event_emitter,
             update_request_receiver,
             connection: None,
+            topology_version: None,
+            last_check_awaited: false,
+            initial_check_complete: false,
+            rtt_monitor: None,
         };
         runtime::execute(monitor.execute())
     }
@@ -70,21 +97,13 @@ impl Monitor {
         while self.topology_watcher.is_alive() {
             self.check_server().await;
 
-            #[cfg(test)]
-            let min_frequency = self
-                .client_options
-                .test_options
-                .as_ref()
-                .and_then(|to| to.heartbeat_freq)
-                .unwrap_or(MIN_HEARTBEAT_FREQUENCY);
-
-            #[cfg(not(test))]
-            let min_frequency = MIN_HEARTBEAT_FREQUENCY;
-
+            let min_frequency = min_heartbeat_frequency(&self.client_options);
             runtime::delay_for(min_frequency).await;
-            self.update_request_receiver
-                .wait_for_check_request(heartbeat_frequency - min_frequency)
-                .await;
+            if !self.last_check_awaited {
+                self.update_request_receiver
+                    .wait_for_check_request(heartbeat_frequency - min_frequency)
+                    .await;
+            }
         }
     }
 
@@ -97,6 +116,7 @@ impl Monitor {
         let mut retried = false;
         let check_result = match self.perform_hello().await {
             Ok(reply) => Ok(reply),
+            Err(e) if !self.initial_check_complete => self.retry_initial_handshake(e).await,
             Err(e) => {
                 let previous_description = self.topology_watcher.server_description(&self.address);
                 if e.is_network_error()
@@ -113,6 +133,7 @@ impl Monitor {
             }
         };
 
+        self.initial_check_complete = true;
         match check_result {
             Ok(reply) => {
                 let server_description =
@@ -123,28 +144,90 @@ impl Monitor {
         }
     }
 
+    /// Retries a failed initial handshake up to `initial_handshake_retries` times with an
+    /// exponential backoff, returning the result of the last attempt.
+    async fn retry_initial_handshake(&mut self, mut error: Error) -> Result<HelloReply> {
+        let max_retries = self.client_options.initial_handshake_retries.unwrap_or(0);
+        let max_backoff = self
+            .client_options
+            .heartbeat_freq
+            .unwrap_or(DEFAULT_HEARTBEAT_FREQUENCY);
+        let mut backoff = INITIAL_HANDSHAKE_RETRY_BACKOFF;
+
+        for _ in 0..max_retries {
+            if !error.is_network_error() || !self.topology_watcher.is_alive() {
+                break;
+            }
+            runtime::delay_for(backoff).await;
+            backoff = std::cmp::min(backoff * 2, max_backoff);
+
+            match self.perform_hello().await {
+                Ok(reply) => return Ok(reply),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+
     async fn perform_hello(&mut self) -> Result<HelloReply> {
-        self.emit_event(|| {
-            SdamEvent::ServerHeartbeatStarted(ServerHeartbeatStartedEvent {
-                server_address: self.address.clone(),
-            })
-        })
-        .await;
+        emit_heartbeat_started(&self.sdam_event_emitter, &self.address).await;
 
-        let (duration, result) = match self.connection {
-            Some(ref mut conn) => {
-                let command = hello_command(
-                    self.client_options.server_api.as_ref(),
-                    self.client_options.load_balanced,
-                    Some(conn.stream_description()?.hello_ok),
-                );
-                let start = Instant::now();
-                (
-                    start.elapsed(),
-                    run_hello(conn, command, None, &self.client_options.sdam_event_handler).await,
-                )
+        if self.topology_version.is_some() && self.rtt_monitor.is_none() {
+            self.rtt_monitor = Some(RttMonitor::start(self));
+        }
+
+        let mut awaited = false;
+        let mut hello_result = None;
+        if let Some(ref mut conn) = self.connection {
+            let mut command = hello_command(
+                self.client_options.server_api.as_ref(),
+                self.client_options.load_balanced,
+                Some(conn.stream_description()?.hello_ok),
+            );
+            if let Some(topology_version) = self.topology_version {
+                let max_await_time = self
+                    .client_options
+                    .heartbeat_freq
+                    .unwrap_or(DEFAULT_HEARTBEAT_FREQUENCY);
+                command
+                    .body
+                    .insert("topologyVersion", bson::to_bson(&topology_version)?);
+                command
+                    .body
+                    .insert("maxAwaitTimeMS", max_await_time.as_millis() as i64);
+                awaited = true;
             }
+
+            let start = Instant::now();
+            let mut hello = Box::pin(run_hello(
+                conn,
+                command,
+                None,
+                &self.client_options.sdam_event_handler,
+            ));
+            let result = match self.rtt_monitor {
+                Some(ref rtt_monitor) if awaited => loop {
+                    // An awaitable hello may not complete until `heartbeat_freq` has elapsed, so
+                    // checks requested in the meantime are performed by the RTT monitor on its own
+                    // connection rather than by cancelling the hello.
+                    tokio::select! {
+                        result = &mut hello => break result,
+                        _ = self.update_request_receiver.wait_for_check_request_indefinitely() => {
+                            rtt_monitor.request_check();
+                            self.update_request_receiver.clear_check_requests();
+                        }
+                    }
+                },
+                _ => hello.await,
+            };
+            hello_result = Some((start.elapsed(), result));
+        }
+
+        let (duration, mut result) = match hello_result {
+            Some(hello_result) => hello_result,
             None => {
+                self.topology_version = None;
                 let mut connection = Connection::connect_monitoring(
                     self.address.clone(),
                     self.client_options.connect_timeout,
@@ -168,36 +251,37 @@ impl Monitor {
             }
         };
 
+        if awaited {
+            // The server may have held an awaitable hello for up to `heartbeat_freq`, so its
+            // duration doesn't reflect the round trip time. Use the latest sample from the RTT
+            // monitor instead, or keep the current average until it has one.
+            if let Ok(ref mut reply) = result {
+                if let Some(rtt) = self
+                    .rtt_monitor
+                    .as_ref()
+                    .and_then(|rtt_monitor| rtt_monitor.latest_rtt())
+                    .or_else(|| {
+                        self.topology_watcher
+                            .server_description(&self.address)
+                            .and_then(|sd| sd.average_round_trip_time)
+                    })
+                {
+                    reply.round_trip_time = rtt;
+                }
+            }
+        }
+        self.last_check_awaited = awaited && result.is_ok();
+
         match result {
             Ok(ref r) => {
-                self.emit_event(|| {
-                    let mut reply = r
-                        .raw_command_response
-                        .to_document()
-                        .unwrap_or_else(|e| doc! { "deserialization error": e.to_string() });
-                    // if this hello call is part of a handshake, remove speculative authentication
-                    // information before publishing an event
-                    reply.remove("speculativeAuthenticate");
-                    SdamEvent::ServerHeartbeatSucceeded(ServerHeartbeatSucceededEvent {
-                        duration,
-                        reply,
-                        server_address: self.address.clone(),
-                    })
-                })
-                .await;
+                self.topology_version = r.command_response.topology_version;
             }
-            Err(ref e) => {
+            Err(_) => {
                 self.connection.take();
-                self.emit_event(|| {
-                    SdamEvent::ServerHeartbeatFailed(ServerHeartbeatFailedEvent {
-                        duration,
-                        failure: e.clone(),
-                        server_address: self.address.clone(),
-                    })
-                })
-                .await;
+                self.topology_version = None;
             }
         }
+        emit_heartbeat_result(&self.sdam_event_emitter, &self.address, duration, &result).await;
 
         result
     }
@@ -207,13 +291,202 @@ impl Monitor {
             .handle_monitor_error(self.address.clone(), error)
             .await
     }
+}
+
+/// The minimum time between checks of a server.
+#[cfg_attr(not(test), allow(unused_variables))]
+fn min_heartbeat_frequency(client_options: &ClientOptions) -> Duration {
+    #[cfg(test)]
+    let min_frequency = client_options
+        .test_options
+        .as_ref()
+        .and_then(|to| to.heartbeat_freq)
+        .unwrap_or(MIN_HEARTBEAT_FREQUENCY);
+
+    #[cfg(not(test))]
+    let min_frequency = MIN_HEARTBEAT_FREQUENCY;
 
-    async fn emit_event<F>(&self, event: F)
-    where
-        F: FnOnce() -> SdamEvent,
-    {
-        if let Some(ref emitter) = self.sdam_event_emitter {
-            emitter.emit(event()).await
+    min_frequency
+}
+
+/// Emits a heartbeat started event for the server at `address`.
+async fn emit_heartbeat_started(emitter: &Option<SdamEventEmitter>, address: &ServerAddress) {
+    if let Some(emitter) = emitter {
+        let event = SdamEvent::ServerHeartbeatStarted(ServerHeartbeatStartedEvent {
+            server_address: address.clone(),
+        });
+        emitter.emit(event).await
+    }
+}
+
+/// Emits a heartbeat succeeded or failed event for the server at `address`, depending on the
+/// result of the hello that took `duration`.
+async fn emit_heartbeat_result(
+    emitter: &Option<SdamEventEmitter>,
+    address: &ServerAddress,
+    duration: Duration,
+    result: &Result<HelloReply>,
+) {
+    let emitter = match emitter {
+        Some(emitter) => emitter,
+        None => return,
+    };
+    let event = match result {
+        Ok(reply) => {
+            let mut reply = reply
+                .raw_command_response
+                .to_document()
+                .unwrap_or_else(|e| doc! { "deserialization error": e.to_string() });
+            // if this hello call is part of a handshake, remove speculative authentication
+            // information before publishing an event
+            reply.remove("speculativeAuthenticate");
+            SdamEvent::ServerHeartbeatSucceeded(ServerHeartbeatSucceededEvent {
+                duration,
+                reply,
+                server_address: address.clone(),
+            })
         }
+        Err(e) => SdamEvent::ServerHeartbeatFailed(ServerHeartbeatFailedEvent {
+            duration,
+            failure: e.clone(),
+            server_address: address.clone(),
+        }),
+    };
+    emitter.emit(event).await
+}
+
+/// State shared between a [`Monitor`] and its [`RttMonitor`].
+#[derive(Default)]
+struct RttMonitorState {
+    /// The round trip time measured by the most recent successful check.
+    latest_rtt: Mutex<Option<Duration>>,
+
+    /// Notified when a check of the server is requested while an awaitable hello is in progress.
+    check_requested: Notify,
+}
+
+impl RttMonitorState {
+    fn latest_rtt(&self) -> Option<Duration> {
+        *self.latest_rtt.lock().unwrap()
+    }
+
+    /// Requests a check of the server. Requests made while one is pending are coalesced into it.
+    fn request_check(&self) {
+        self.check_requested.notify_one();
+    }
+}
+
+/// Monitor that measures the round trip time to a server once its [`Monitor`] uses awaitable
+/// hellos, whose duration includes the time the server held them. This uses a dedicated
+/// connection to run regular hellos every `heartbeat_freq`, and also performs the checks that are
+/// requested while an awaitable hello is in progress.
+struct RttMonitor {
+    address: ServerAddress,
+    connection: Option<Connection>,
+    handshaker: Handshaker,
+    topology_updater: TopologyUpdater,
+    topology_watcher: TopologyWatcher,
+    sdam_event_emitter: Option<SdamEventEmitter>,
+    client_options: ClientOptions,
+    state: Arc<RttMonitorState>,
+}
+
+impl RttMonitor {
+    fn start(monitor: &Monitor) -> Arc<RttMonitorState> {
+        let state = Arc::new(RttMonitorState::default());
+        let rtt_monitor = Self {
+            address: monitor.address.clone(),
+            connection: None,
+            handshaker: Handshaker::new(Some(monitor.client_options.clone().into())),
+            topology_updater: monitor.topology_updater.clone(),
+            topology_watcher: monitor.topology_watcher.clone(),
+            sdam_event_emitter: monitor.sdam_event_emitter.clone(),
+            client_options: monitor.client_options.clone(),
+            state: state.clone(),
+        };
+        runtime::execute(rtt_monitor.execute());
+        state
+    }
+
+    async fn execute(mut self) {
+        let heartbeat_frequency = self
+            .client_options
+            .heartbeat_freq
+            .unwrap_or(DEFAULT_HEARTBEAT_FREQUENCY);
+        let min_frequency = min_heartbeat_frequency(&self.client_options);
+
+        let mut check_requested = false;
+        // Once the state is no longer shared, the `Monitor` that started this one has stopped.
+        while self.topology_watcher.is_alive() && Arc::strong_count(&self.state) > 1 {
+            if check_requested {
+                self.check_server().await;
+            } else {
+                let _: Result<_> = self.perform_hello().await;
+            }
+
+            runtime::delay_for(min_frequency).await;
+            check_requested = runtime::timeout(
+                heartbeat_frequency - min_frequency,
+                self.state.check_requested.notified(),
+            )
+            .await
+            .is_ok();
+        }
+    }
+
+    /// Performs a requested check of the server, reporting a successful result to the topology.
+    /// Failures are left for the `Monitor` to detect, since its connection is likely to fail too.
+    async fn check_server(&mut self) {
+        emit_heartbeat_started(&self.sdam_event_emitter, &self.address).await;
+
+        let start = Instant::now();
+        let result = self.perform_hello().await;
+        emit_heartbeat_result(
+            &self.sdam_event_emitter,
+            &self.address,
+            start.elapsed(),
+            &result,
+        )
+        .await;
+        if let Ok(reply) = result {
+            let server_description = ServerDescription::new(self.address.clone(), Some(Ok(reply)));
+            self.topology_updater.update(server_description).await;
+        }
+    }
+
+    /// Runs a hello on the dedicated connection, establishing it first if necessary, and records
+    /// the round trip time if it succeeds.
+    async fn perform_hello(&mut self) -> Result<HelloReply> {
+        let result = match self.connection {
+            Some(ref mut conn) => {
+                let command = hello_command(
+                    self.client_options.server_api.as_ref(),
+                    self.client_options.load_balanced,
+                    Some(conn.stream_description()?.hello_ok),
+                );
+                run_hello(conn, command, None, &None).await
+            }
+            None => {
+                let mut connection = Connection::connect_monitoring(
+                    self.address.clone(),
+                    self.client_options.connect_timeout,
+                    self.client_options.tls_options(),
+                )
+                .await?;
+                let result = self
+                    .handshaker
+                    .handshake(&mut connection, None, &None)
+                    .await
+                    .map(|r| r.hello_reply);
+                self.connection = Some(connection);
+                result
+            }
+        };
+
+        match result {
+            Ok(ref reply) => *self.state.latest_rtt.lock().unwrap() = Some(reply.round_trip_time),
+            Err(_) => self.connection = None,
+        }
+        result
     }
 }
@@ -2,7 +2,10 @@ use std::{borrow::Cow, fmt, time::Duration};
 
 use serde::Serialize;
 
-pub use crate::sdam::description::{server::ServerType, topology::TopologyType};
+pub use crate::sdam::description::{
+    server::{ServerType, TopologyVersion},
+    topology::TopologyType,
+};
 use crate::{
     bson::DateTime,
     error::Error,
@@ -97,6 +100,12 @@ impl<'a> ServerInfo<'a> {
         self.description.server_type
     }
 
+    /// Gets the version of the server's topology state reported in its most recent `hello`
+    /// response. Only reported by MongoDB 4.4+.
+    pub fn topology_version(&self) -> Option<TopologyVersion> {
+        self.description.topology_version()
+    }
+
     /// Gets the tags associated with the server.
     pub fn tags(&self) -> Option<&TagSet> {
         self.command_response_getter(|r| r.tags.as_ref())
@@ -10,6 +10,7 @@ use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 use crate::{
     error::{Error, ErrorKind},
     hello::{LEGACY_HELLO_COMMAND_NAME, LEGACY_HELLO_COMMAND_NAME_LOWERCASE},
+    options::ServerAddress,
     runtime,
     test::{
         log_uncaptured,
@@ -26,6 +27,7 @@ use crate::{
         LOCK,
     },
     Client,
+    ServerType,
 };
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
@@ -391,6 +393,185 @@ async fn auth_error() {
     );
 }
 
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn shutdown_in_progress() {
+    let _guard: RwLockWriteGuard<_> = LOCK.run_exclusively().await;
+
+    let mut setup_client_options = CLIENT_OPTIONS.get().await.clone();
+    if setup_client_options.load_balanced.unwrap_or(false) {
+        log_uncaptured("skipping shutdown_in_progress test due to load-balanced topology");
+        return;
+    }
+    setup_client_options.hosts.drain(1..);
+    setup_client_options.direct_connection = Some(true);
+    let setup_client = TestClient::with_options(Some(setup_client_options.clone())).await;
+    if !VersionReq::parse(">= 4.2.9")
+        .unwrap()
+        .matches(&setup_client.server_version)
+    {
+        log_uncaptured(
+            "skipping shutdown_in_progress test due to server not supporting appName failCommand",
+        );
+        return;
+    }
+
+    let fp_options = FailCommandOptions::builder()
+        .app_name("shutdownInProgressTest".to_string())
+        .error_code(91)
+        .build();
+    let failpoint = FailPoint::fail_command(&["find"], FailPointMode::Times(1), fp_options);
+    let _fp_guard = setup_client
+        .enable_failpoint(failpoint, None)
+        .await
+        .expect("enabling failpoint should succeed");
+
+    let handler = Arc::new(EventHandler::new());
+    let mut subscriber = handler.subscribe();
+
+    let mut options = setup_client_options;
+    options.app_name = Some("shutdownInProgressTest".to_string());
+    options.retry_reads = Some(false);
+    options.sdam_event_handler = Some(handler.clone());
+    let client = Client::with_options(options).expect("client creation should succeed");
+
+    let err = client
+        .database("shutdown_in_progress")
+        .collection::<bson::Document>("shutdown_in_progress")
+        .find_one(None, None)
+        .await
+        .expect_err("find should fail");
+    assert!(err.is_shutdown_in_progress(), "{:?}", err);
+    assert!(!err.is_network_error());
+
+    subscriber
+        .wait_for_event(Duration::from_millis(1000), |event| {
+            matches!(
+                event,
+                Event::Sdam(SdamEvent::ServerDescriptionChanged(event))
+                    if event.is_marked_unknown_event()
+            )
+        })
+        .await
+        .expect("server should be marked unknown");
+
+    let network_error: Error =
+        ErrorKind::Io(Arc::new(std::io::ErrorKind::ConnectionReset.into())).into();
+    assert!(!network_error.is_shutdown_in_progress());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn initial_handshake_retries() {
+    let _guard: RwLockWriteGuard<_> = LOCK.run_exclusively().await;
+
+    let mut setup_client_options = CLIENT_OPTIONS.get().await.clone();
+    if setup_client_options.load_balanced.unwrap_or(false) {
+        log_uncaptured("skipping initial_handshake_retries test due to load-balanced topology");
+        return;
+    }
+    setup_client_options.hosts.drain(1..);
+    setup_client_options.direct_connection = Some(true);
+
+    let setup_client = TestClient::with_options(Some(setup_client_options.clone())).await;
+    if !setup_client.supports_fail_command_appname_initial_handshake() {
+        log_uncaptured(
+            "skipping initial_handshake_retries test due to server not supporting failcommand \
+             appname",
+        );
+        return;
+    }
+
+    // The server refuses the first two handshakes, which are retried before the server is
+    // reported to the topology.
+    let fp_options = FailCommandOptions::builder()
+        .app_name("initialHandshakeRetriesTest".to_string())
+        .close_connection(true)
+        .build();
+    let failpoint = FailPoint::fail_command(
+        &[LEGACY_HELLO_COMMAND_NAME, "hello"],
+        FailPointMode::Times(2),
+        fp_options,
+    );
+    let _fp_guard = setup_client
+        .enable_failpoint(failpoint, None)
+        .await
+        .expect("enabling failpoint should succeed");
+
+    let handler = Arc::new(EventHandler::new());
+    let mut subscriber = handler.subscribe();
+
+    let mut options = setup_client_options.clone();
+    options.app_name = Some("initialHandshakeRetriesTest".to_string());
+    options.initial_handshake_retries = Some(3);
+    options.sdam_event_handler = Some(handler.clone());
+    let client = Client::with_options(options).expect("client creation should succeed");
+
+    let start = Instant::now();
+    client
+        .database("admin")
+        .run_command(doc! { "ping": 1 }, None)
+        .await
+        .expect("ping should succeed");
+    // Without retries, each failed check would be followed by at least MIN_HEARTBEAT_FREQUENCY.
+    let elapsed = start.elapsed();
+    assert!(elapsed < Duration::from_millis(1000), "took {:?}", elapsed);
+
+    let mut failed_heartbeats = 0;
+    let event = subscriber
+        .wait_for_event(Duration::from_millis(500), |event| match event {
+            Event::Sdam(SdamEvent::ServerHeartbeatFailed(_)) => {
+                failed_heartbeats += 1;
+                false
+            }
+            Event::Sdam(SdamEvent::ServerDescriptionChanged(_)) => true,
+            _ => false,
+        })
+        .await
+        .expect("should see server description changed event");
+    assert_eq!(failed_heartbeats, 2);
+    match event {
+        Event::Sdam(SdamEvent::ServerDescriptionChanged(event)) => {
+            assert!(event.new_description.server_type().is_available());
+        }
+        _ => unreachable!(),
+    }
+
+    // A server that is down for longer is marked Unknown once the retries are exhausted.
+    let handler = Arc::new(EventHandler::new());
+    let mut subscriber = handler.subscribe();
+
+    let mut options = setup_client_options;
+    options.hosts = vec![ServerAddress::Tcp {
+        host: "localhost".to_string(),
+        port: Some(1),
+    }];
+    options.initial_handshake_retries = Some(2);
+    options.sdam_event_handler = Some(handler.clone());
+    let _client = Client::with_options(options).expect("client creation should succeed");
+
+    let mut failed_heartbeats = 0;
+    let event = subscriber
+        .wait_for_event(Duration::from_millis(2000), |event| match event {
+            Event::Sdam(SdamEvent::ServerHeartbeatFailed(_)) => {
+                failed_heartbeats += 1;
+                false
+            }
+            Event::Sdam(SdamEvent::ServerDescriptionChanged(_)) => true,
+            _ => false,
+        })
+        .await
+        .expect("should see server description changed event");
+    assert_eq!(failed_heartbeats, 3);
+    match event {
+        Event::Sdam(SdamEvent::ServerDescriptionChanged(event)) => {
+            assert_eq!(event.new_description.server_type(), ServerType::Unknown);
+            assert!(event.new_description.error().is_some());
+        }
+        _ => unreachable!(),
+    }
+}
+
 #[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 async fn hello_ok_true() {
@@ -21,6 +21,7 @@ use crate::{
     error::{load_balanced_mode_mismatch, Error, Result},
     event::sdam::{
         handle_sdam_event,
+        CompatibilityWarningEvent,
         SdamEvent,
         ServerClosedEvent,
         ServerDescriptionChangedEvent,
@@ -107,6 +108,7 @@ impl Topology {
         let state = TopologyState {
             description,
             servers,
+            initial_discovery_complete: false,
         };
 
         let addresses = state.servers.keys().cloned().collect::<Vec<_>>();
@@ -320,6 +322,8 @@ impl Topology {
 pub(crate) struct TopologyState {
     pub(crate) description: TopologyDescription,
     pub(crate) servers: HashMap<ServerAddress, Arc<Server>>,
+    /// Whether any server has been available since the topology was created.
+    pub(crate) initial_discovery_complete: bool,
 }
 
 #[derive(Debug)]
@@ -504,6 +508,9 @@ impl TopologyWorker {
 
         // TODO: RUST-1270 change this method to not return a result.
         let _ = latest_state.description.update(sd);
+        if latest_state.description.has_available_servers() {
+            latest_state.initial_discovery_complete = true;
+        }
 
         let hosts = latest_state
             .description
@@ -573,6 +580,16 @@ impl TopologyWorker {
                     new_description: new_description.clone().into(),
                 };
                 let _ = emitter.emit(SdamEvent::TopologyDescriptionChanged(Box::new(event)));
+
+                if let Some(ref message) = new_description.compatibility_warning {
+                    if old_description.compatibility_warning.as_ref() != Some(message) {
+                        let event = CompatibilityWarningEvent {
+                            topology_id: self.id,
+                            message: message.clone(),
+                        };
+                        let _ = emitter.emit(SdamEvent::CompatibilityWarning(event));
+                    }
+                }
             }
         }
         changed
@@ -625,7 +642,9 @@ impl TopologyWorker {
             let updated =
                 is_load_balanced || self.mark_server_as_unknown(address, error.clone()).await;
 
-            if updated && (error.is_shutting_down() || handshake.wire_version().unwrap_or(0) < 8) {
+            if updated
+                && (error.is_shutdown_in_progress() || handshake.wire_version().unwrap_or(0) < 8)
+            {
                 server.pool.clear(error, handshake.service_id()).await;
             }
             self.update_requester.request();
@@ -873,6 +892,13 @@ impl TopologyCheckRequestReceiver {
         let _: std::result::Result<_, _> = runtime::timeout(timeout, self.receiver.recv()).await;
     }
 
+    /// Wait until a check request is seen. If no more requests can be sent, this never completes.
+    pub(crate) async fn wait_for_check_request_indefinitely(&mut self) {
+        if let Err(broadcast::error::RecvError::Closed) = self.receiver.recv().await {
+            std::future::pending::<()>().await;
+        }
+    }
+
     /// Clear out prior check requests so that the next call to `wait_for_check_requests` doesn't
     /// return immediately.
     pub(crate) fn clear_check_requests(&mut self) {
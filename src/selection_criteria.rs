@@ -5,7 +5,7 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize};
 use typed_builder::TypedBuilder;
 
 use crate::{
-    bson::doc,
+    bson::{doc, Document},
     bson_util,
     error::{ErrorKind, Result},
     options::ServerAddress,
@@ -59,7 +59,6 @@ impl SelectionCriteria {
         }
     }
 
-    #[cfg(test)]
     pub(crate) fn is_read_pref_primary(&self) -> bool {
         matches!(self, Self::ReadPreference(ReadPreference::Primary))
     }
@@ -72,6 +71,15 @@ impl SelectionCriteria {
         SelectionCriteria::Predicate(Arc::new(move |server| server.address() == &address))
     }
 
+    /// Returns the document the driver sends as the `$readPreference` field of commands for this
+    /// selection criteria, e.g. `{ mode: "secondaryPreferred", maxStalenessSeconds: 120 }`.
+    ///
+    /// Returns `None` for a `Predicate`, as predicates are only evaluated client-side.
+    pub fn to_read_preference_document(&self) -> Option<Document> {
+        self.as_read_pref()
+            .and_then(|read_pref| bson::to_document(read_pref).ok())
+    }
+
     #[cfg(test)]
     pub(crate) fn serialize_for_client_options<S>(
         selection_criteria: &Option<SelectionCriteria>,
@@ -147,8 +155,21 @@ impl<'de> Deserialize<'de> for ReadPreference {
         }
         let preference = ReadPreferenceHelper::deserialize(deserializer)?;
 
+        // Modes are accepted both capitalized and in camelCase, the latter being the form they're
+        // serialized in.
         match preference.mode.as_str() {
-            "Primary" => {
+            "Primary" | "primary" => {
+                if preference.options.max_staleness.is_some() {
+                    return Err(D::Error::custom(
+                        "maxStalenessSeconds cannot be specified with read preference mode = \
+                         primary",
+                    ));
+                }
+                if preference.options.hedge.is_some() {
+                    return Err(D::Error::custom(
+                        "hedge cannot be specified with read preference mode = primary",
+                    ));
+                }
                 if !preference.options.is_default() {
                     return Err(D::Error::custom(&format!(
                         "no options can be specified with read preference mode = primary, but got \
@@ -158,16 +179,16 @@ impl<'de> Deserialize<'de> for ReadPreference {
                 }
                 Ok(ReadPreference::Primary)
             }
-            "Secondary" => Ok(ReadPreference::Secondary {
+            "Secondary" | "secondary" => Ok(ReadPreference::Secondary {
                 options: preference.options,
             }),
-            "PrimaryPreferred" => Ok(ReadPreference::PrimaryPreferred {
+            "PrimaryPreferred" | "primaryPreferred" => Ok(ReadPreference::PrimaryPreferred {
                 options: preference.options,
             }),
             "SecondaryPreferred" | "secondaryPreferred" => Ok(ReadPreference::SecondaryPreferred {
                 options: preference.options,
             }),
-            "Nearest" => Ok(ReadPreference::Nearest {
+            "Nearest" | "nearest" => Ok(ReadPreference::Nearest {
                 options: preference.options,
             }),
             other => Err(D::Error::custom(format!(
@@ -239,7 +260,7 @@ pub struct ReadPreferenceOptions {
     #[serde(
         rename = "maxStalenessSeconds",
         default,
-        deserialize_with = "bson_util::deserialize_duration_option_from_u64_seconds",
+        deserialize_with = "deserialize_max_staleness",
         serialize_with = "bson_util::serialize_duration_option_as_int_secs"
     )]
     pub max_staleness: Option<Duration>,
@@ -252,6 +273,23 @@ pub struct ReadPreferenceOptions {
     pub hedge: Option<HedgedReadOptions>,
 }
 
+/// Deserializes `maxStalenessSeconds`, treating -1 as no max staleness.
+fn deserialize_max_staleness<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<i64>::deserialize(deserializer)? {
+        None | Some(-1) => Ok(None),
+        Some(seconds) if seconds < 0 => Err(D::Error::custom(format!(
+            "maxStalenessSeconds must be -1 or positive, instead got {}",
+            seconds
+        ))),
+        Some(seconds) => Ok(Some(Duration::from_secs(seconds as u64))),
+    }
+}
+
 impl ReadPreferenceOptions {
     pub(crate) fn is_default(&self) -> bool {
         self.hedge.is_none()
@@ -336,6 +374,35 @@ impl ReadPreference {
         Ok(self)
     }
 
+    /// Returns a copy of this read preference with the given hedging behavior, leaving its mode,
+    /// tag sets and max staleness unchanged. This can be used to enable or disable hedged reads
+    /// for an individual operation by specifying the returned read preference in its options,
+    /// regardless of the hedging behavior of the client's default read preference.
+    ///
+    /// Hedged reads only apply to sharded clusters on servers that are at least version 4.4.
+    ///
+    /// Returns an error if this read preference is `Primary`, as primary reads cannot be hedged.
+    pub fn with_hedge(mut self, hedge: HedgedReadOptions) -> Result<Self> {
+        let options = match self {
+            ReadPreference::Primary => {
+                return Err(ErrorKind::InvalidArgument {
+                    message: "hedged reads can only be specified when a non-primary mode is \
+                              specified"
+                        .to_string(),
+                }
+                .into());
+            }
+            ReadPreference::Secondary { ref mut options } => options,
+            ReadPreference::PrimaryPreferred { ref mut options } => options,
+            ReadPreference::SecondaryPreferred { ref mut options } => options,
+            ReadPreference::Nearest { ref mut options } => options,
+        };
+
+        options.hedge = Some(hedge);
+
+        Ok(self)
+    }
+
     #[cfg(test)]
     pub(crate) fn serialize_for_client_options<S>(
         read_preference: &ReadPreference,
@@ -391,7 +458,9 @@ pub type TagSet = HashMap<String, String>;
 
 #[cfg(test)]
 mod test {
-    use super::{HedgedReadOptions, ReadPreference, ReadPreferenceOptions};
+    use std::{sync::Arc, time::Duration};
+
+    use super::{HedgedReadOptions, ReadPreference, ReadPreferenceOptions, SelectionCriteria};
     use crate::bson::doc;
 
     #[test]
@@ -408,4 +477,138 @@ mod test {
             doc! { "mode": "secondary", "hedge": { "enabled": true } }
         );
     }
+
+    #[test]
+    fn with_hedge() {
+        let read_pref = ReadPreference::Nearest {
+            options: Default::default(),
+        }
+        .with_hedge(HedgedReadOptions::with_enabled(false))
+        .unwrap();
+        assert_eq!(
+            bson::to_document(&read_pref).unwrap(),
+            doc! { "mode": "nearest", "hedge": { "enabled": false } }
+        );
+
+        assert!(ReadPreference::Primary
+            .with_hedge(HedgedReadOptions::with_enabled(true))
+            .is_err());
+    }
+
+    #[test]
+    fn deserialize_primary_preferred_with_all_options() {
+        let doc = doc! {
+            "mode": "primaryPreferred",
+            "tagSets": [{ "dc": "ny", "rack": "1" }, { "dc": "sf" }, {}],
+            "maxStalenessSeconds": 120,
+            "hedge": { "enabled": true },
+        };
+        let criteria: SelectionCriteria = bson::from_document(doc).unwrap();
+
+        let options = match criteria {
+            SelectionCriteria::ReadPreference(ReadPreference::PrimaryPreferred { options }) => {
+                options
+            }
+            other => panic!("expected primaryPreferred read preference, got {:?}", other),
+        };
+        let tag_sets = options.tag_sets.unwrap();
+        assert_eq!(tag_sets.len(), 3);
+        assert_eq!(tag_sets[0].get("dc").map(String::as_str), Some("ny"));
+        assert_eq!(tag_sets[0].get("rack").map(String::as_str), Some("1"));
+        assert_eq!(tag_sets[1].get("dc").map(String::as_str), Some("sf"));
+        assert!(tag_sets[2].is_empty());
+        assert_eq!(options.max_staleness, Some(Duration::from_secs(120)));
+        assert_eq!(options.hedge, Some(HedgedReadOptions::with_enabled(true)));
+    }
+
+    #[test]
+    fn serialize_deserialize_round_trip() {
+        let options = ReadPreferenceOptions::builder()
+            .tag_sets(vec![[("dc".to_string(), "ny".to_string())]
+                .into_iter()
+                .collect()])
+            .max_staleness(Duration::from_secs(90))
+            .build();
+        let read_prefs = vec![
+            ReadPreference::Primary,
+            ReadPreference::Secondary {
+                options: options.clone(),
+            },
+            ReadPreference::PrimaryPreferred {
+                options: options.clone(),
+            },
+            ReadPreference::SecondaryPreferred {
+                options: options.clone(),
+            },
+            ReadPreference::Nearest { options },
+        ];
+        for read_pref in read_prefs {
+            let doc = bson::to_document(&read_pref).unwrap();
+            let deserialized: ReadPreference = bson::from_document(doc).unwrap();
+            assert_eq!(deserialized, read_pref);
+        }
+    }
+
+    #[test]
+    fn deserialize_primary_with_options() {
+        let err = bson::from_document::<ReadPreference>(doc! {
+            "mode": "primary",
+            "maxStalenessSeconds": 120,
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("maxStalenessSeconds"), "{}", err);
+
+        let err = bson::from_document::<SelectionCriteria>(doc! {
+            "mode": "primary",
+            "hedge": { "enabled": true },
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("hedge"), "{}", err);
+
+        // -1 means that no max staleness is specified.
+        let read_pref: ReadPreference = bson::from_document(doc! {
+            "mode": "primary",
+            "maxStalenessSeconds": -1,
+        })
+        .unwrap();
+        assert_eq!(read_pref, ReadPreference::Primary);
+
+        let read_pref: ReadPreference = bson::from_document(doc! {
+            "mode": "secondary",
+            "maxStalenessSeconds": -1,
+        })
+        .unwrap();
+        assert_eq!(read_pref.max_staleness(), None);
+
+        assert!(bson::from_document::<ReadPreference>(doc! {
+            "mode": "secondary",
+            "maxStalenessSeconds": -2,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn to_read_preference_document() {
+        let options = ReadPreferenceOptions::builder()
+            .max_staleness(Duration::from_secs(120))
+            .hedge(HedgedReadOptions::with_enabled(true))
+            .build();
+        let criteria =
+            SelectionCriteria::ReadPreference(ReadPreference::SecondaryPreferred { options });
+
+        let doc = criteria.to_read_preference_document().unwrap();
+        assert_eq!(
+            doc,
+            doc! {
+                "mode": "secondaryPreferred",
+                "maxStalenessSeconds": 120,
+                "hedge": { "enabled": true },
+            }
+        );
+        let deserialized: SelectionCriteria = bson::from_document(doc).unwrap();
+        assert_eq!(deserialized.as_read_pref(), criteria.as_read_pref());
+
+        let predicate = SelectionCriteria::Predicate(Arc::new(|_| true));
+        assert!(predicate.to_read_preference_document().is_none());
+    }
 }
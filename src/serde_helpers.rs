@@ -0,0 +1,726 @@
+//! Helpers for controlling how Rust values are serialized into BSON.
+//!
+//! The server stores dates with millisecond granularity, so a [`SystemTime`] with a finer
+//! precision (e.g. microseconds, as returned by `SystemTime::now()` on most platforms) cannot be
+//! stored exactly. [`bson::DateTime::from_system_time`] silently truncates the sub-millisecond
+//! part; the helpers in this module make that choice explicit and allow rejecting such values
+//! instead. Applications that want to be warned whenever precision is lost can install a handler
+//! with [`set_precision_loss_handler`].
+//!
+//! BSON also has no 128-bit integer type, so this module provides helpers for storing `i128` and
+//! `u128` values as BSON `Decimal128` values, which can hold any integer of up to 34 decimal
+//! digits exactly.
+//!
+//! Finally, BSON binary values carry a subtype describing their contents. The [`UuidBinary`],
+//! [`Md5Binary`] and [`UserDefinedBinary`] wrappers check that subtype when they are deserialized,
+//! so that e.g. a UUID written by a legacy driver with subtype 3 is reported as an error rather
+//! than being read as if it were a standard subtype 4 UUID.
+
+use std::{
+    sync::RwLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use lazy_static::lazy_static;
+use serde::{
+    de::Error as SerdeDeError,
+    ser::Error as SerdeSerError,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+use crate::{
+    bson::{spec::BinarySubtype, Binary, Bson, DateTime, Decimal128, Uuid},
+    error::{Error, ErrorKind, Result},
+};
+
+/// Specifies what to do when converting a [`SystemTime`] that has sub-millisecond precision into
+/// a [`bson::DateTime`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DateTimePrecision {
+    /// Truncate the value to millisecond precision. This matches the behavior of
+    /// [`bson::DateTime::from_system_time`], except that the handler installed with
+    /// [`set_precision_loss_handler`], if any, is called when precision is lost.
+    Truncate,
+
+    /// Return an error rather than losing precision.
+    Error,
+}
+
+impl Default for DateTimePrecision {
+    fn default() -> Self {
+        Self::Truncate
+    }
+}
+
+/// A function called with a [`SystemTime`] and the sub-millisecond part of it that was lost when
+/// it was truncated to a [`bson::DateTime`].
+pub type PrecisionLossHandler = fn(SystemTime, Duration);
+
+lazy_static! {
+    static ref PRECISION_LOSS_HANDLER: RwLock<Option<PrecisionLossHandler>> = RwLock::new(None);
+}
+
+/// Sets the function that is called whenever the helpers in this module truncate a
+/// [`SystemTime`] with sub-millisecond precision, e.g. to log a warning. This applies to the whole
+/// process; passing `None` removes the handler. No handler is installed by default.
+pub fn set_precision_loss_handler(handler: Option<PrecisionLossHandler>) {
+    *PRECISION_LOSS_HANDLER
+        .write()
+        .unwrap_or_else(|error| error.into_inner()) = handler;
+}
+
+/// Returns the sub-millisecond part of `time` that is lost when converting it into a
+/// [`bson::DateTime`], or `None` if `time` is a whole number of milliseconds from the Unix epoch.
+pub fn truncated_precision(time: SystemTime) -> Option<Duration> {
+    let sub_millis_nanos = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.subsec_nanos() % 1_000_000,
+        Err(e) => e.duration().subsec_nanos() % 1_000_000,
+    };
+    if sub_millis_nanos == 0 {
+        None
+    } else {
+        Some(Duration::from_nanos(sub_millis_nanos.into()))
+    }
+}
+
+/// Converts `time` into a [`bson::DateTime`], handling any sub-millisecond precision as specified
+/// by `precision`.
+///
+/// If `time` is not a whole number of milliseconds from the Unix epoch, an
+/// [`ErrorKind::InvalidArgument`] error is returned if `precision` is
+/// [`DateTimePrecision::Error`]; otherwise, the handler installed with
+/// [`set_precision_loss_handler`], if any, is called before the value is truncated.
+pub fn system_time_to_bson_datetime(
+    time: SystemTime,
+    precision: DateTimePrecision,
+) -> Result<DateTime> {
+    if let Some(lost) = truncated_precision(time) {
+        match precision {
+            DateTimePrecision::Error => {
+                return Err(ErrorKind::InvalidArgument {
+                    message: format!(
+                        "{:?} has sub-millisecond precision ({:?}) and cannot be stored exactly \
+                         as a BSON datetime",
+                        time, lost
+                    ),
+                }
+                .into());
+            }
+            DateTimePrecision::Truncate => {
+                let handler = *PRECISION_LOSS_HANDLER
+                    .read()
+                    .unwrap_or_else(|error| error.into_inner());
+                if let Some(handler) = handler {
+                    handler(time, lost);
+                }
+            }
+        }
+    }
+    Ok(DateTime::from_system_time(time))
+}
+
+/// Serializes a [`SystemTime`] as a BSON datetime, truncating it to millisecond precision. See
+/// [`set_precision_loss_handler`] for being notified when precision is lost.
+///
+/// For use with `#[serde(serialize_with = "...")]`.
+pub fn serialize_system_time_as_datetime_truncated<S: Serializer>(
+    time: &SystemTime,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serialize_system_time(time, DateTimePrecision::Truncate, serializer)
+}
+
+/// Serializes a [`SystemTime`] as a BSON datetime, returning an error if it has sub-millisecond
+/// precision that would be lost.
+///
+/// For use with `#[serde(serialize_with = "...")]`.
+pub fn serialize_system_time_as_datetime_strict<S: Serializer>(
+    time: &SystemTime,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serialize_system_time(time, DateTimePrecision::Error, serializer)
+}
+
+fn serialize_system_time<S: Serializer>(
+    time: &SystemTime,
+    precision: DateTimePrecision,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let datetime = system_time_to_bson_datetime(*time, precision).map_err(S::Error::custom)?;
+    datetime.serialize(serializer)
+}
+
+/// The largest integer that can be stored exactly as a `Decimal128`, which has 34 decimal digits of
+/// precision.
+const DECIMAL128_MAX_INTEGER: i128 = 9_999_999_999_999_999_999_999_999_999_999_999;
+
+/// The amount that a `Decimal128` exponent is biased by when it is encoded.
+const DECIMAL128_EXPONENT_BIAS: i32 = 6176;
+
+/// The number of bits used for the coefficient of a `Decimal128` whose coefficient fits in them.
+const DECIMAL128_COEFFICIENT_BITS: u32 = 113;
+
+/// Converts `value` into a [`Decimal128`] that represents it exactly.
+///
+/// Values with more than 34 decimal digits, i.e. with an absolute value greater than
+/// 10<sup>34</sup> - 1, cannot be represented without losing precision, and an
+/// [`ErrorKind::InvalidArgument`] error is returned for them.
+pub fn i128_to_decimal128(value: i128) -> Result<Decimal128> {
+    let coefficient = integer_coefficient(value.unsigned_abs(), value)?;
+    // The coefficient is less than 2^113, so it is stored in the low bits, with the biased
+    // exponent (always 0 here) above it and the sign in the highest bit.
+    let sign = if value < 0 { 1u128 << 127 } else { 0 };
+    let exponent = (DECIMAL128_EXPONENT_BIAS as u128) << DECIMAL128_COEFFICIENT_BITS;
+    Ok(Decimal128::from_bytes(
+        (sign | exponent | coefficient).to_le_bytes(),
+    ))
+}
+
+fn integer_coefficient(coefficient: u128, value: impl std::fmt::Display) -> Result<u128> {
+    if coefficient > DECIMAL128_MAX_INTEGER as u128 {
+        return Err(ErrorKind::InvalidArgument {
+            message: format!(
+                "{} has more than 34 digits and cannot be stored exactly as a BSON Decimal128",
+                value
+            ),
+        }
+        .into());
+    }
+    Ok(coefficient)
+}
+
+/// Converts `value` into an `i128`.
+///
+/// An [`ErrorKind::InvalidArgument`] error is returned if `value` is not an integer, e.g. because
+/// it has a fractional part or is infinite or NaN.
+pub fn decimal128_to_i128(value: &Decimal128) -> Result<i128> {
+    let bits = u128::from_le_bytes(value.bytes());
+    let error = |reason: &str| -> Error {
+        ErrorKind::InvalidArgument {
+            message: format!("Decimal128 {:?} {}", value, reason),
+        }
+        .into()
+    };
+
+    // If the two bits after the sign are both set, the value is infinite, NaN, or has a
+    // coefficient of at least 2^113, which is larger than the maximum of 10^34 - 1 and therefore
+    // non-canonical.
+    if (bits >> 125) & 0b11 == 0b11 {
+        return Err(error("is not a finite integer"));
+    }
+    let negative = bits >> 127 == 1;
+    let exponent =
+        ((bits >> DECIMAL128_COEFFICIENT_BITS) & 0x3fff) as i32 - DECIMAL128_EXPONENT_BIAS;
+    let coefficient = (bits & ((1u128 << DECIMAL128_COEFFICIENT_BITS) - 1)) as i128;
+    if coefficient > DECIMAL128_MAX_INTEGER {
+        return Err(error("has a non-canonical coefficient"));
+    }
+
+    let mut integer = coefficient;
+    if exponent > 0 {
+        for _ in 0..exponent {
+            if integer == 0 {
+                break;
+            }
+            integer = integer
+                .checked_mul(10)
+                .ok_or_else(|| error("is out of range for a 128-bit integer"))?;
+        }
+    } else {
+        for _ in exponent..0 {
+            if integer % 10 != 0 {
+                return Err(error("has a fractional part"));
+            }
+            integer /= 10;
+        }
+    }
+    Ok(if negative { -integer } else { integer })
+}
+
+/// Serializes an `i128` as a BSON `Decimal128`, returning an error if it has more than 34 digits
+/// and so cannot be stored exactly.
+///
+/// For use with `#[serde(serialize_with = "...")]`.
+pub fn serialize_i128_as_decimal128<S: Serializer>(
+    value: &i128,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    i128_to_decimal128(*value)
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Serializes a `u128` as a BSON `Decimal128`, returning an error if it has more than 34 digits
+/// and so cannot be stored exactly.
+///
+/// For use with `#[serde(serialize_with = "...")]`.
+pub fn serialize_u128_as_decimal128<S: Serializer>(
+    value: &u128,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let value = integer_coefficient(*value, value).map_err(S::Error::custom)?;
+    serialize_i128_as_decimal128(&(value as i128), serializer)
+}
+
+/// Deserializes an `i128` from a BSON `Decimal128` that holds an integer. BSON 32-bit and 64-bit
+/// integers are also accepted.
+///
+/// For use with `#[serde(deserialize_with = "...")]`.
+pub fn deserialize_i128_from_decimal128<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<i128, D::Error> {
+    match Bson::deserialize(deserializer)? {
+        Bson::Decimal128(value) => decimal128_to_i128(&value).map_err(D::Error::custom),
+        Bson::Int32(value) => Ok(value.into()),
+        Bson::Int64(value) => Ok(value.into()),
+        other => Err(D::Error::custom(format!(
+            "expected a Decimal128 or an integer, got {:?}",
+            other
+        ))),
+    }
+}
+
+/// Deserializes a `u128` from a BSON `Decimal128` that holds a non-negative integer. BSON 32-bit
+/// and 64-bit integers are also accepted.
+///
+/// For use with `#[serde(deserialize_with = "...")]`.
+pub fn deserialize_u128_from_decimal128<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<u128, D::Error> {
+    let value = deserialize_i128_from_decimal128(deserializer)?;
+    u128::try_from(value)
+        .map_err(|_| D::Error::custom(format!("{} is out of range for a u128", value)))
+}
+
+fn serialize_binary<S: Serializer>(
+    subtype: BinarySubtype,
+    bytes: &[u8],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    Binary {
+        subtype,
+        bytes: bytes.to_vec(),
+    }
+    .serialize(serializer)
+}
+
+/// Deserializes the bytes of a BSON binary value, returning an error if its subtype is not
+/// `expected`.
+fn deserialize_binary<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    expected: BinarySubtype,
+) -> std::result::Result<Vec<u8>, D::Error> {
+    match Bson::deserialize(deserializer)? {
+        Bson::Binary(binary) if binary.subtype == expected => Ok(binary.bytes),
+        Bson::Binary(Binary {
+            subtype: BinarySubtype::UuidOld,
+            ..
+        }) if expected == BinarySubtype::Uuid => Err(D::Error::custom(
+            "expected a UUID with binary subtype 4, got a legacy UUID with binary subtype 3 whose \
+             byte order depends on the driver that wrote it",
+        )),
+        Bson::Binary(binary) => Err(D::Error::custom(format!(
+            "expected binary subtype {:?}, got {:?}",
+            expected, binary.subtype
+        ))),
+        other => Err(D::Error::custom(format!(
+            "expected a binary value with subtype {:?}, got {:?}",
+            expected, other
+        ))),
+    }
+}
+
+fn deserialize_16_bytes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    expected: BinarySubtype,
+) -> std::result::Result<[u8; 16], D::Error> {
+    let bytes = deserialize_binary(deserializer, expected)?;
+    let length = bytes.len();
+    bytes.try_into().map_err(|_| {
+        D::Error::custom(format!(
+            "expected 16 bytes for binary subtype {:?}, got {}",
+            expected, length
+        ))
+    })
+}
+
+/// A UUID stored as a BSON binary value with the standard UUID subtype 4.
+///
+/// Deserializing a binary value with any other subtype returns an error. In particular, UUIDs
+/// written by legacy drivers with subtype 3 are rejected, since the order of their bytes depends
+/// on the driver that wrote them; such values can be read as a [`Binary`] instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UuidBinary(pub Uuid);
+
+impl Serialize for UuidBinary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serialize_binary(BinarySubtype::Uuid, &self.0.bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UuidBinary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_16_bytes(deserializer, BinarySubtype::Uuid)
+            .map(|bytes| Self(Uuid::from_bytes(bytes)))
+    }
+}
+
+impl From<Uuid> for UuidBinary {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+/// An MD5 digest stored as a BSON binary value with the MD5 subtype 5.
+///
+/// Deserializing a binary value with any other subtype, or that is not 16 bytes long, returns an
+/// error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Md5Binary(pub [u8; 16]);
+
+impl Serialize for Md5Binary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serialize_binary(BinarySubtype::Md5, &self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Md5Binary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserialize_16_bytes(deserializer, BinarySubtype::Md5).map(Self)
+    }
+}
+
+/// Bytes stored as a BSON binary value with the user-defined subtype `SUBTYPE`, which must be in
+/// the range 0x80 to 0xFF reserved for user-defined subtypes.
+///
+/// Deserializing a binary value with any other subtype returns an error, as does serializing or
+/// deserializing a `UserDefinedBinary` whose `SUBTYPE` is outside of the user-defined range.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UserDefinedBinary<const SUBTYPE: u8>(pub Vec<u8>);
+
+impl<const SUBTYPE: u8> UserDefinedBinary<SUBTYPE> {
+    fn subtype() -> std::result::Result<BinarySubtype, String> {
+        if SUBTYPE < 0x80 {
+            return Err(format!(
+                "binary subtype {:#04x} is not in the user-defined range of 0x80 to 0xFF",
+                SUBTYPE
+            ));
+        }
+        Ok(BinarySubtype::UserDefined(SUBTYPE))
+    }
+}
+
+impl<const SUBTYPE: u8> Serialize for UserDefinedBinary<SUBTYPE> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let subtype = Self::subtype().map_err(S::Error::custom)?;
+        serialize_binary(subtype, &self.0, serializer)
+    }
+}
+
+impl<'de, const SUBTYPE: u8> Deserialize<'de> for UserDefinedBinary<SUBTYPE> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let subtype = Self::subtype().map_err(D::Error::custom)?;
+        deserialize_binary(deserializer, subtype).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::Mutex,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use lazy_static::lazy_static;
+    use serde::{Deserialize, Serialize};
+
+    use super::{
+        decimal128_to_i128,
+        i128_to_decimal128,
+        set_precision_loss_handler,
+        system_time_to_bson_datetime,
+        truncated_precision,
+        DateTimePrecision,
+        Md5Binary,
+        UserDefinedBinary,
+        UuidBinary,
+    };
+    use crate::{
+        bson::{
+            doc,
+            from_document,
+            from_slice,
+            spec::BinarySubtype,
+            to_document,
+            to_vec,
+            Binary,
+            Bson,
+            DateTime,
+            Decimal128,
+            Uuid,
+        },
+        error::ErrorKind,
+    };
+
+    #[test]
+    fn truncates_sub_millisecond_precision() {
+        let time = UNIX_EPOCH + Duration::from_micros(1_234_567);
+        assert_eq!(truncated_precision(time), Some(Duration::from_micros(567)));
+
+        let truncated = system_time_to_bson_datetime(time, DateTimePrecision::Truncate).unwrap();
+        assert_eq!(truncated, DateTime::from_millis(1_234));
+
+        let err = system_time_to_bson_datetime(time, DateTimePrecision::Error).unwrap_err();
+        assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+
+        let before_epoch = UNIX_EPOCH - Duration::from_micros(1_500);
+        assert!(system_time_to_bson_datetime(before_epoch, DateTimePrecision::Error).is_err());
+
+        let exact = UNIX_EPOCH + Duration::from_millis(1_234);
+        assert_eq!(truncated_precision(exact), None);
+        assert_eq!(
+            system_time_to_bson_datetime(exact, DateTimePrecision::Error).unwrap(),
+            DateTime::from_millis(1_234)
+        );
+    }
+
+    lazy_static! {
+        static ref PRECISION_LOSSES: Mutex<Vec<(SystemTime, Duration)>> = Default::default();
+    }
+
+    fn record_precision_loss(time: SystemTime, lost: Duration) {
+        PRECISION_LOSSES.lock().unwrap().push((time, lost));
+    }
+
+    #[test]
+    fn reports_precision_loss() {
+        // Other tests may truncate values while the handler is installed, so only the values used
+        // here are checked.
+        let time = UNIX_EPOCH + Duration::from_micros(9_876_543);
+        let exact = UNIX_EPOCH + Duration::from_millis(9_876);
+        let reported = |time: SystemTime| {
+            PRECISION_LOSSES
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(reported, _)| *reported == time)
+                .map(|(_, lost)| *lost)
+                .collect::<Vec<_>>()
+        };
+
+        set_precision_loss_handler(Some(record_precision_loss));
+        system_time_to_bson_datetime(time, DateTimePrecision::Truncate).unwrap();
+        system_time_to_bson_datetime(time, DateTimePrecision::Error).unwrap_err();
+        system_time_to_bson_datetime(exact, DateTimePrecision::Truncate).unwrap();
+        set_precision_loss_handler(None);
+        system_time_to_bson_datetime(time, DateTimePrecision::Truncate).unwrap();
+
+        assert_eq!(reported(time), vec![Duration::from_micros(543)]);
+        assert!(reported(exact).is_empty());
+    }
+
+    #[test]
+    fn serialize_system_time() {
+        #[derive(Serialize)]
+        struct Truncated {
+            #[serde(
+                serialize_with = "crate::serde_helpers::serialize_system_time_as_datetime_truncated"
+            )]
+            time: SystemTime,
+        }
+
+        #[derive(Serialize)]
+        struct Strict {
+            #[serde(
+                serialize_with = "crate::serde_helpers::serialize_system_time_as_datetime_strict"
+            )]
+            time: SystemTime,
+        }
+
+        let time = UNIX_EPOCH + Duration::from_micros(1_234_567);
+        assert_eq!(
+            to_document(&Truncated { time }).unwrap(),
+            doc! { "time": DateTime::from_millis(1_234) }
+        );
+        assert!(to_document(&Strict { time }).is_err());
+
+        let time = UNIX_EPOCH + Duration::from_millis(1_234);
+        assert_eq!(
+            to_document(&Strict { time }).unwrap(),
+            doc! { "time": DateTime::from_millis(1_234) }
+        );
+    }
+
+    #[test]
+    fn decimal128_encoding() {
+        // 1 has a coefficient of 1 and an exponent of 0, which is biased to 6176.
+        let one = i128_to_decimal128(1).unwrap();
+        assert_eq!(
+            u128::from_le_bytes(one.bytes()),
+            0x3040_0000_0000_0000_0000_0000_0000_0001
+        );
+        let minus_one = i128_to_decimal128(-1).unwrap();
+        assert_eq!(
+            u128::from_le_bytes(minus_one.bytes()),
+            0xb040_0000_0000_0000_0000_0000_0000_0001
+        );
+
+        // 1E+3 and 1000E-3 are both the integer 1000.
+        let one_e_three = Decimal128::from_bytes((0x3046u128 << 112 | 1).to_le_bytes());
+        assert_eq!(decimal128_to_i128(&one_e_three).unwrap(), 1000);
+        let thousand_e_minus_three =
+            Decimal128::from_bytes((0x303au128 << 112 | 1000).to_le_bytes());
+        assert_eq!(decimal128_to_i128(&thousand_e_minus_three).unwrap(), 1);
+
+        // 15E-1 is 1.5.
+        let fractional = Decimal128::from_bytes((0x303eu128 << 112 | 15).to_le_bytes());
+        assert!(decimal128_to_i128(&fractional).is_err());
+
+        // Infinity.
+        let infinity = Decimal128::from_bytes((0x7800u128 << 112).to_le_bytes());
+        assert!(decimal128_to_i128(&infinity).is_err());
+    }
+
+    #[test]
+    fn round_trip_large_integers_through_decimal128() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Amounts {
+            #[serde(
+                serialize_with = "crate::serde_helpers::serialize_i128_as_decimal128",
+                deserialize_with = "crate::serde_helpers::deserialize_i128_from_decimal128"
+            )]
+            signed: i128,
+            #[serde(
+                serialize_with = "crate::serde_helpers::serialize_u128_as_decimal128",
+                deserialize_with = "crate::serde_helpers::deserialize_u128_from_decimal128"
+            )]
+            unsigned: u128,
+        }
+
+        let max = 9_999_999_999_999_999_999_999_999_999_999_999;
+        for (signed, unsigned) in [
+            (0, 0),
+            (-max, max as u128),
+            (max, 12_345_678_901_234_567_890),
+        ] {
+            let amounts = Amounts { signed, unsigned };
+            let doc = to_document(&amounts).unwrap();
+            assert!(matches!(doc.get("signed"), Some(Bson::Decimal128(_))));
+            assert!(matches!(doc.get("unsigned"), Some(Bson::Decimal128(_))));
+            assert_eq!(from_document::<Amounts>(doc).unwrap(), amounts);
+        }
+
+        // Values with more than 34 digits can't be stored exactly.
+        let too_large = Amounts {
+            signed: max + 1,
+            unsigned: 0,
+        };
+        assert!(to_document(&too_large).is_err());
+        let too_large = Amounts {
+            signed: 0,
+            unsigned: u128::MAX,
+        };
+        assert!(to_document(&too_large).is_err());
+        let err = i128_to_decimal128(-max - 1).unwrap_err();
+        assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+
+        // Integers stored by other applications as Int64 or as a negative Decimal128 are read
+        // where they fit.
+        let doc = doc! { "signed": 5i64, "unsigned": 7 };
+        assert_eq!(
+            from_document::<Amounts>(doc).unwrap(),
+            Amounts {
+                signed: 5,
+                unsigned: 7
+            }
+        );
+        let doc = doc! { "signed": 0, "unsigned": i128_to_decimal128(-1).unwrap() };
+        assert!(from_document::<Amounts>(doc).is_err());
+    }
+
+    #[test]
+    fn binary_subtypes() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Record {
+            id: UuidBinary,
+            checksum: Md5Binary,
+            payload: UserDefinedBinary<0x80>,
+        }
+
+        let record = Record {
+            id: UuidBinary(Uuid::new()),
+            checksum: Md5Binary([7; 16]),
+            payload: UserDefinedBinary(vec![1, 2, 3]),
+        };
+        let doc = to_document(&record).unwrap();
+        assert_eq!(
+            doc.get("id"),
+            Some(&Bson::Binary(Binary {
+                subtype: BinarySubtype::Uuid,
+                bytes: record.id.0.bytes().to_vec(),
+            }))
+        );
+        assert_eq!(
+            doc.get("checksum"),
+            Some(&Bson::Binary(Binary {
+                subtype: BinarySubtype::Md5,
+                bytes: vec![7; 16],
+            }))
+        );
+        assert_eq!(
+            doc.get("payload"),
+            Some(&Bson::Binary(Binary {
+                subtype: BinarySubtype::UserDefined(0x80),
+                bytes: vec![1, 2, 3],
+            }))
+        );
+        assert_eq!(from_document::<Record>(doc.clone()).unwrap(), record);
+        assert_eq!(
+            from_slice::<Record>(&to_vec(&record).unwrap()).unwrap(),
+            record
+        );
+
+        // A legacy subtype 3 UUID is not read as a standard UUID.
+        let mut legacy = doc.clone();
+        legacy.insert(
+            "id",
+            Binary {
+                subtype: BinarySubtype::UuidOld,
+                bytes: record.id.0.bytes().to_vec(),
+            },
+        );
+        let err = from_document::<Record>(legacy).unwrap_err();
+        assert!(err.to_string().contains("legacy UUID"), "{}", err);
+
+        let mut wrong_subtype = doc.clone();
+        wrong_subtype.insert(
+            "payload",
+            Binary {
+                subtype: BinarySubtype::UserDefined(0x81),
+                bytes: vec![1, 2, 3],
+            },
+        );
+        assert!(from_document::<Record>(wrong_subtype).is_err());
+
+        let mut wrong_length = doc.clone();
+        wrong_length.insert(
+            "checksum",
+            Binary {
+                subtype: BinarySubtype::Md5,
+                bytes: vec![7; 15],
+            },
+        );
+        assert!(from_document::<Record>(wrong_length).is_err());
+
+        let mut not_binary = doc;
+        not_binary.insert("id", record.id.0.to_string());
+        assert!(from_document::<Record>(not_binary).is_err());
+
+        // Subtypes outside of the user-defined range can't be used.
+        assert!(crate::bson::to_bson(&UserDefinedBinary::<0x05>(vec![1])).is_err());
+    }
+}
@@ -1,19 +1,32 @@
 pub mod session;
 
-use super::{ChangeStream, ClientSession, Database, SessionChangeStream};
+use std::{collections::HashMap, time::Duration};
+
+use super::{ChangeStream, ClientSession, Cursor, Database, SessionChangeStream};
 use crate::{
-    bson::Document,
+    bson::{Document, Timestamp},
     change_stream::{event::ChangeStreamEvent, options::ChangeStreamOptions},
     concern::{ReadConcern, WriteConcern},
     error::Result,
+    event::cmap::PoolStats,
     options::{
+        AggregateOptions,
         ClientOptions,
         DatabaseOptions,
         ListDatabasesOptions,
         SelectionCriteria,
+        ServerAddress,
         SessionOptions,
+        SessionUser,
+    },
+    results::{
+        DatabaseSpecification,
+        FeatureCompatibilityVersion,
+        HelloResult,
+        OpCounts,
+        OperationRetries,
+        OplogEntry,
     },
-    results::DatabaseSpecification,
     runtime,
     Client as AsyncClient,
 };
@@ -128,6 +141,33 @@ impl Client {
         self.async_client.default_database().map(Database::new)
     }
 
+    /// Requests that the driver immediately check the state of every server in the deployment
+    /// rather than waiting for the next heartbeat, e.g. after the application sees a network
+    /// error. This lets the driver notice failovers and recovered servers sooner.
+    ///
+    /// Checks are still rate limited: each server is checked at most once per 500ms, and requests
+    /// made while a check is pending are coalesced into it, so calling this repeatedly is cheap.
+    pub fn request_immediate_check(&self) {
+        self.async_client.request_immediate_check()
+    }
+
+    /// Sets a callback that is invoked with the selection criteria and the address of the chosen
+    /// server each time the client selects a server. See
+    /// [`crate::Client::set_server_selection_hook`] for more details.
+    pub fn set_server_selection_hook(
+        &self,
+        hook: impl Fn(&SelectionCriteria, &ServerAddress) + Send + Sync + 'static,
+    ) {
+        self.async_client.set_server_selection_hook(hook)
+    }
+
+    /// Sets a callback that is invoked once each operation run by the client completes, reporting
+    /// how many times the operation was retried. See [`crate::Client::set_retry_hook`] for more
+    /// details.
+    pub fn set_retry_hook(&self, hook: impl Fn(&OperationRetries) + Send + Sync + 'static) {
+        self.async_client.set_retry_hook(hook)
+    }
+
     /// Gets information about each database present in the cluster the Client is connected to.
     pub fn list_databases(
         &self,
@@ -152,11 +192,190 @@ impl Client {
         )
     }
 
+    /// Runs a collectionless aggregation against the `admin` database, such as one beginning with
+    /// a `$currentOp` or `$listLocalSessions` stage, and returns a cursor over its results.
+    ///
+    /// Stages like these report on the state of the node they run on, so aggregations beginning
+    /// with them are not retried, since a retry could be executed by a different node.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
+    /// information on aggregations.
+    pub fn aggregate_admin(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Cursor<Document>> {
+        let pipeline: Vec<Document> = pipeline.into_iter().collect();
+        runtime::block_on(self.async_client.aggregate_admin(pipeline, options.into()))
+            .map(Cursor::new)
+    }
+
+    /// Gets the most recent entries of the in-memory log named `name` of the server the command is
+    /// sent to, via the `getLog` command (e.g. `"global"` or `"startupWarnings"`). This requires
+    /// the `getLog` privilege on the cluster.
+    ///
+    /// An error is returned if the server has no log named `name`. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/reference/command/getLog/) for more information.
+    pub fn get_log(&self, name: impl AsRef<str>) -> Result<Vec<String>> {
+        runtime::block_on(self.async_client.get_log(name.as_ref()))
+    }
+
+    /// Opens a tailable cursor over the oplog (`local.oplog.rs`) of a replica set member,
+    /// starting at the entry with timestamp `start_at` (or the first entry after it, if there is
+    /// no such entry). The cursor waits for new entries to be written rather than being exhausted
+    /// when it reaches the end of the oplog.
+    ///
+    /// An error is returned if the oplog has already rolled over past `start_at`, i.e. its oldest
+    /// entry is newer than `start_at`, as entries after `start_at` may have been lost. If the
+    /// oplog rolls over past the cursor's position while it is being iterated, the cursor returns
+    /// an error.
+    ///
+    /// This requires a connection to a replica set; the oplog can't be read via mongos.
+    /// [Change streams](https://www.mongodb.com/docs/manual/changeStreams/) should be preferred
+    /// over reading the oplog where possible, as the oplog's format is internal to the server.
+    pub fn oplog_tail(&self, start_at: Timestamp) -> Result<Cursor<OplogEntry>> {
+        runtime::block_on(self.async_client.oplog_tail(start_at)).map(Cursor::new)
+    }
+
+    /// Runs the `hello` command against the server at `address`, returning that server's
+    /// description of itself and of the topology. The legacy `isMaster` command is used instead if
+    /// the server doesn't support `hello`.
+    ///
+    /// `address` must be one of the servers in the client's topology; otherwise, this returns an
+    /// error once the server selection timeout elapses.
+    pub fn hello(&self, address: ServerAddress) -> Result<HelloResult> {
+        runtime::block_on(self.async_client.hello(address))
+    }
+
+    /// Verifies that the deployment supports the stable API version configured via
+    /// [`ClientOptions::server_api`](crate::options::ClientOptions::server_api), so that an
+    /// application can fail at startup rather than on its first operation if it requests a version
+    /// the server doesn't support. Does nothing if no server API is configured.
+    ///
+    /// An [`ErrorKind::IncompatibleServer`](crate::error::ErrorKind::IncompatibleServer) error is
+    /// returned if the server is too old to support the stable API at all (it requires MongoDB
+    /// 5.0+) or if it rejects the requested version.
+    pub fn verify_server_api(&self) -> Result<()> {
+        runtime::block_on(self.async_client.verify_server_api())
+    }
+
+    /// Gets the feature compatibility version of the deployment via the `getParameter` command.
+    ///
+    /// mongos does not report a feature compatibility version, so for a sharded cluster, this must
+    /// be called using a client connected directly to a shard or config server.
+    pub fn get_fcv(&self) -> Result<FeatureCompatibilityVersion> {
+        runtime::block_on(self.async_client.get_fcv())
+    }
+
+    /// Sets the feature compatibility version of the deployment via the
+    /// `setFeatureCompatibilityVersion` command, enabling or disabling the persisted features of
+    /// the corresponding server version. On MongoDB 7.0+, the command is sent with
+    /// `confirm: true`, as the server requires.
+    ///
+    /// The server returns an error if `version` is not supported by its binary version. See the
+    /// documentation [here](https://www.mongodb.com/docs/manual/reference/command/setFeatureCompatibilityVersion/)
+    /// for more information.
+    pub fn set_fcv(&self, version: FeatureCompatibilityVersion) -> Result<()> {
+        runtime::block_on(self.async_client.set_fcv(version))
+    }
+
+    /// Waits until a server that writes can be sent to has been discovered (e.g. the primary of a
+    /// replica set or any mongos of a sharded cluster) and returns its address. This can be used
+    /// by applications that must not issue writes until a primary is available.
+    ///
+    /// A server selection error is returned if no such server is discovered within `timeout`.
+    pub fn wait_for_primary(&self, timeout: Duration) -> Result<ServerAddress> {
+        runtime::block_on(self.async_client.wait_for_primary(timeout))
+    }
+
+    /// Attempts to reach each server currently known to the client, and reports either the round
+    /// trip time of a `hello` command or the error that prevented it for each server, ordered by
+    /// address. This is intended for diagnostics, e.g. to check which members of a deployment
+    /// (including arbiters and hidden members) are reachable from the application's host.
+    ///
+    /// Each server is checked concurrently on a new connection dedicated to the check, so the
+    /// reported times are unaffected by the state of the client's connection pools. A server that
+    /// can't be reached is reported with the error from connecting to it, which may take up to
+    /// [`ClientOptions::connect_timeout`](crate::options::ClientOptions::connect_timeout).
+    pub fn connectivity_report(&self) -> Vec<(ServerAddress, Result<Duration>)> {
+        runtime::block_on(self.async_client.connectivity_report())
+    }
+
+    /// Returns a snapshot of the state of the connection pool for each server currently known to
+    /// the client. See [`crate::Client::pool_stats`] for more details.
+    pub fn pool_stats(&self) -> HashMap<ServerAddress, PoolStats> {
+        self.async_client.pool_stats()
+    }
+
+    /// Returns the number of commands this client has sent to each server, grouped by the kind of
+    /// operation. See [`crate::Client::operation_counts`] for more details.
+    pub fn operation_counts(&self) -> HashMap<ServerAddress, OpCounts> {
+        self.async_client.operation_counts()
+    }
+
+    /// Resets the counts returned by [`Client::operation_counts`].
+    pub fn reset_operation_counts(&self) {
+        self.async_client.reset_operation_counts()
+    }
+
     /// Starts a new `ClientSession`.
     pub fn start_session(&self, options: Option<SessionOptions>) -> Result<ClientSession> {
         runtime::block_on(self.async_client.start_session(options)).map(Into::into)
     }
 
+    /// Returns the number of server sessions currently in use by this client, i.e. the number of
+    /// explicit sessions that haven't been dropped plus the number of implicit sessions in use by
+    /// in-progress operations and open cursors.
+    ///
+    /// Server sessions are returned to a pool rather than ended when they're no longer in use, and
+    /// reused by later sessions until they've been idle for close to the deployment's
+    /// `logicalSessionTimeoutMinutes`. Sessions are returned to the pool in the background, so a
+    /// session may be counted briefly after it's dropped.
+    pub fn active_session_count(&self) -> usize {
+        self.async_client.active_session_count()
+    }
+
+    /// Refreshes the sessions with the given ids (as returned by
+    /// [`ClientSession::id`](crate::ClientSession::id)) via the `refreshSessions` command,
+    /// resetting the time after which the server times them out due to inactivity (the
+    /// deployment's `logicalSessionTimeoutMinutes`). This can be used to keep sessions alive that
+    /// are referred to outside of this client, e.g. by id in another process.
+    ///
+    /// The idle sessions in this client's session pool are refreshed automatically once they've
+    /// been unused for half of the timeout, so that they can be reused. Sessions that are in use
+    /// by a [`ClientSession`](crate::sync::ClientSession) are not; those that may be idle for long
+    /// periods can be kept alive with
+    /// [`ClientSession::refresh`](crate::sync::ClientSession::refresh).
+    ///
+    /// Refreshing a session that has already timed out on the server starts a new session with
+    /// the same id, so the state of the old session (e.g. an in-progress transaction) is lost.
+    pub fn refresh_sessions(&self, ids: impl IntoIterator<Item = Document>) -> Result<()> {
+        runtime::block_on(self.async_client.refresh_sessions(ids))
+    }
+
+    /// Kills the sessions of the given users via the `killAllSessions` command, aborting their
+    /// transactions and killing their in-progress operations and open cursors. If `users` is
+    /// empty, the sessions of all users are killed. This can be used to clean up sessions that are
+    /// holding on to server resources, e.g. stuck transactions.
+    ///
+    /// The idle server sessions in this client's session pool are discarded rather than reused.
+    /// Sessions of this client that are in use when they're killed can still be used, but any
+    /// transaction in progress on them is aborted.
+    pub fn kill_all_sessions(&self, users: impl IntoIterator<Item = SessionUser>) -> Result<()> {
+        runtime::block_on(self.async_client.kill_all_sessions(users))
+    }
+
+    /// Kills the sessions with the given ids (as returned by
+    /// [`ClientSession::id`](crate::ClientSession::id)) via the `killSessions` command, aborting
+    /// their transactions and killing their in-progress operations and open cursors.
+    ///
+    /// If any of the ids belong to idle server sessions in this client's session pool, those are
+    /// discarded rather than reused. Sessions of this client that are in use when they're killed
+    /// can still be used, but any transaction in progress on them is aborted.
+    pub fn kill_sessions(&self, ids: impl IntoIterator<Item = Document>) -> Result<()> {
+        runtime::block_on(self.async_client.kill_sessions(ids))
+    }
+
     /// Starts a new [`ChangeStream`] that receives events for all changes in the cluster. The
     /// stream does not observe changes from system collections or the "config", "local" or
     /// "admin" databases. Note that this method (`watch` on a cluster) is only supported in
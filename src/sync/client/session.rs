@@ -1,6 +1,8 @@
+use std::time::Duration;
+
 use super::Client;
 use crate::{
-    bson::Document,
+    bson::{Document, Timestamp},
     client::session::ClusterTime,
     error::Result,
     options::{SessionOptions, TransactionOptions},
@@ -54,6 +56,24 @@ impl ClientSession {
         self.async_client_session.advance_cluster_time(to)
     }
 
+    /// Refreshes this session on the server via the `refreshSessions` command so that it isn't
+    /// timed out due to inactivity. This can be used to keep a session that is idle for long
+    /// periods (e.g. between the operations of a long-running transaction) alive. See
+    /// [`Client::refresh_sessions`](crate::sync::Client::refresh_sessions) for more details.
+    pub fn refresh(&mut self) -> Result<()> {
+        runtime::block_on(self.async_client_session.refresh())
+    }
+
+    /// Ensures that the effects of operations up to `operation_time` are visible to reads using
+    /// this session, waiting up to `timeout` for the selected server to catch up. See
+    /// [`crate::ClientSession::ensure_visible`] for more details.
+    pub fn ensure_visible(&mut self, operation_time: Timestamp, timeout: Duration) -> Result<()> {
+        runtime::block_on(
+            self.async_client_session
+                .ensure_visible(operation_time, timeout),
+        )
+    }
+
     /// Starts a new transaction on this session with the given `TransactionOptions`. If no options
     /// are provided, the session's `defaultTransactionOptions` will be used. This session must
     /// be passed into each operation within the transaction; otherwise, the operation will be
@@ -1,15 +1,19 @@
-use std::{borrow::Borrow, fmt::Debug};
+use std::{borrow::Borrow, fmt::Debug, time::Duration};
 
 use serde::{de::DeserializeOwned, Serialize};
 
 use super::{ChangeStream, ClientSession, Cursor, SessionChangeStream, SessionCursor};
 use crate::{
-    bson::{Bson, Document},
+    bson::{Bson, Document, Uuid},
     change_stream::{event::ChangeStreamEvent, options::ChangeStreamOptions},
-    error::Result,
+    error::{FindManyAndDeleteError, Result},
     index::IndexModel,
     options::{
         AggregateOptions,
+        BulkUpsertOptions,
+        ClaimOptions,
+        CollectionStatsOptions,
+        CompactOptions,
         CountOptions,
         CreateIndexOptions,
         DeleteOptions,
@@ -17,28 +21,39 @@ use crate::{
         DropCollectionOptions,
         DropIndexOptions,
         EstimatedDocumentCountOptions,
+        FindManyAndDeleteOptions,
         FindOneAndDeleteOptions,
         FindOneAndReplaceOptions,
         FindOneAndUpdateOptions,
         FindOneOptions,
         FindOptions,
+        IncrementOptions,
         InsertManyOptions,
         InsertOneOptions,
         ListIndexesOptions,
+        ParallelScanOptions,
+        ReIndexOptions,
         ReadConcern,
+        ReadPreference,
         ReplaceOptions,
         SelectionCriteria,
+        ServerApi,
+        SnapshotExportOptions,
         UpdateModifications,
         UpdateOptions,
+        ValidateOptions,
         WriteConcern,
     },
     results::{
+        BulkUpsertResult,
+        CollectionStats,
         CreateIndexResult,
         CreateIndexesResult,
         DeleteResult,
         InsertManyResult,
         InsertOneResult,
         UpdateResult,
+        ValidationResult,
     },
     runtime,
     Collection as AsyncCollection,
@@ -96,6 +111,27 @@ impl<T> Collection<T> {
         Collection::new(self.async_collection.clone_with_type())
     }
 
+    /// Gets a handle to the same collection that uses `read_preference` for its read operations
+    /// instead of the collection's configured selection criteria. The read concern and write
+    /// concern are unchanged.
+    ///
+    /// Operations run in a transaction still use the transaction's read preference, which must be
+    /// primary.
+    pub fn with_read_preference(&self, read_preference: ReadPreference) -> Collection<T> {
+        Collection::new(self.async_collection.with_read_preference(read_preference))
+    }
+
+    /// Gets a handle to the same collection that uses `write_concern` for its write operations
+    /// instead of the collection's configured write concern. The selection criteria and read
+    /// concern are unchanged.
+    ///
+    /// Operations run in a transaction still use the transaction's write concern. If
+    /// `write_concern` is unacknowledged, writes through the returned handle are not retried and
+    /// cannot be run with an explicit session.
+    pub fn with_write_concern(&self, write_concern: WriteConcern) -> Collection<T> {
+        Collection::new(self.async_collection.with_write_concern(write_concern))
+    }
+
     /// Gets the name of the `Collection`.
     pub fn name(&self) -> &str {
         self.async_collection.name()
@@ -126,6 +162,37 @@ impl<T> Collection<T> {
         self.async_collection.write_concern()
     }
 
+    /// Resolves the read concern that a read operation on this `Collection` would use. See
+    /// [`crate::Collection::effective_read_concern`] for more details.
+    pub fn effective_read_concern(
+        &self,
+        operation_read_concern: Option<&ReadConcern>,
+        session: Option<&ClientSession>,
+    ) -> Option<ReadConcern> {
+        self.async_collection.effective_read_concern(
+            operation_read_concern,
+            session.map(|session| &session.async_client_session),
+        )
+    }
+
+    /// Resolves the write concern that a write operation on this `Collection` would use. See
+    /// [`crate::Collection::effective_write_concern`] for more details.
+    pub fn effective_write_concern(
+        &self,
+        operation_write_concern: Option<&WriteConcern>,
+        session: Option<&ClientSession>,
+    ) -> Option<WriteConcern> {
+        self.async_collection.effective_write_concern(
+            operation_write_concern,
+            session.map(|session| &session.async_client_session),
+        )
+    }
+
+    /// Gets the stable server API declared on the `Collection`'s commands.
+    pub fn server_api(&self) -> Option<&ServerApi> {
+        self.async_collection.server_api()
+    }
+
     /// Drops the collection, deleting all data, users, and indexes stored in it.
     pub fn drop(&self, options: impl Into<Option<DropCollectionOptions>>) -> Result<()> {
         runtime::block_on(self.async_collection.drop(options.into()))
@@ -177,6 +244,51 @@ impl<T> Collection<T> {
         .map(SessionCursor::new)
     }
 
+    /// Runs an aggregation operation that is expected to produce at most one document (e.g. a
+    /// `$group` stage with a fixed `_id`), deserializing the result as `R`. `Ok(None)` is returned
+    /// if the pipeline produces no documents.
+    ///
+    /// If the pipeline produces more than one document, only the first one is returned and the
+    /// rest are discarded. Add a `$limit` stage to the pipeline to avoid retrieving documents that
+    /// will not be used.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
+    /// information on aggregations.
+    pub fn aggregate_one<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let pipeline: Vec<Document> = pipeline.into_iter().collect();
+        runtime::block_on(
+            self.async_collection
+                .aggregate_one(pipeline, options.into()),
+        )
+    }
+
+    /// Runs an aggregation operation that is expected to produce at most one document using the
+    /// provided `ClientSession`, deserializing the result as `R`. See
+    /// [`Collection::aggregate_one`] for more details.
+    pub fn aggregate_one_with_session<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let pipeline: Vec<Document> = pipeline.into_iter().collect();
+        runtime::block_on(self.async_collection.aggregate_one_with_session(
+            pipeline,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Estimates the number of documents in the collection using collection metadata.
     ///
     /// Due to an oversight in versions 5.0.0 - 5.0.7 of MongoDB, the `count` server command,
@@ -198,6 +310,51 @@ impl<T> Collection<T> {
         )
     }
 
+    /// Checks the collection's data and indexes for correctness via the `validate` command and
+    /// returns the results. Note that unless the `background` option is specified, this blocks
+    /// writes to the collection while it runs.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/validate/)
+    /// for more information.
+    pub fn validate(
+        &self,
+        options: impl Into<Option<ValidateOptions>>,
+    ) -> Result<ValidationResult> {
+        runtime::block_on(self.async_collection.validate(options.into()))
+    }
+
+    /// Gets storage statistics for the collection via the `collStats` command.
+    ///
+    /// If a `scale` is given in `options`, every size in the result is divided by it, including
+    /// the average document size which the server itself always reports in bytes.
+    pub fn stats(
+        &self,
+        options: impl Into<Option<CollectionStatsOptions>>,
+    ) -> Result<CollectionStats> {
+        runtime::block_on(self.async_collection.stats(options.into()))
+    }
+
+    /// Rewrites and defragments the collection's data and indexes via the `compact` command to
+    /// release unused disk space to the operating system, returning the number of bytes freed if
+    /// the server reports it (MongoDB 4.4+).
+    ///
+    /// `compact` is not supported by mongos, so to compact a sharded collection, this must be
+    /// called using a client connected directly to each shard.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/compact/)
+    /// for more information.
+    pub fn compact(&self, options: impl Into<Option<CompactOptions>>) -> Result<Option<u64>> {
+        runtime::block_on(self.async_collection.compact(options.into()))
+    }
+
+    /// Drops and rebuilds all of the indexes on the collection via the `reIndex` command,
+    /// returning the number of indexes that were rebuilt. This is not supported by mongos or by
+    /// replica set members running MongoDB 5.0+. See [`crate::Collection::reindex`] for more
+    /// details.
+    pub fn reindex(&self, options: impl Into<Option<ReIndexOptions>>) -> Result<u64> {
+        runtime::block_on(self.async_collection.reindex(options.into()))
+    }
+
     /// Gets the number of documents matching `filter`.
     ///
     /// Note that using [`Collection::estimated_document_count`](#method.estimated_document_count)
@@ -300,6 +457,36 @@ impl<T> Collection<T> {
         ))
     }
 
+    /// Deletes the documents in the collection whose `_id` is one of `ids`. See
+    /// [`crate::Collection::delete_many_by_ids`] for more details.
+    pub fn delete_many_by_ids(
+        &self,
+        ids: impl IntoIterator<Item = Bson>,
+        options: impl Into<Option<DeleteOptions>>,
+    ) -> Result<DeleteResult> {
+        let ids: Vec<Bson> = ids.into_iter().collect();
+        runtime::block_on(
+            self.async_collection
+                .delete_many_by_ids(ids, options.into()),
+        )
+    }
+
+    /// Deletes the documents in the collection whose `_id` is one of `ids` using the provided
+    /// `ClientSession`. See [`crate::Collection::delete_many_by_ids`] for more details.
+    pub fn delete_many_by_ids_with_session(
+        &self,
+        ids: impl IntoIterator<Item = Bson>,
+        options: impl Into<Option<DeleteOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<DeleteResult> {
+        let ids: Vec<Bson> = ids.into_iter().collect();
+        runtime::block_on(self.async_collection.delete_many_by_ids_with_session(
+            ids,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Deletes up to one document found matching `query`.
     ///
     /// This operation will retry once upon failure if the connection and encountered error support
@@ -364,6 +551,42 @@ impl<T> Collection<T> {
         ))
     }
 
+    /// Finds the distinct values of the field specified by `field_name` across the collection,
+    /// returning them through a cursor. See [`crate::Collection::distinct_cursor`] for more
+    /// details.
+    pub fn distinct_cursor(
+        &self,
+        field_name: impl AsRef<str>,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Cursor<Bson>> {
+        runtime::block_on(self.async_collection.distinct_cursor(
+            field_name.as_ref(),
+            filter.into(),
+            options.into(),
+        ))
+        .map(Cursor::new)
+    }
+
+    /// Finds the distinct values of the field specified by `field_name` across the collection
+    /// using the provided `ClientSession`, returning them through a cursor. See
+    /// [`crate::Collection::distinct_cursor`] for more details.
+    pub fn distinct_cursor_with_session(
+        &self,
+        field_name: impl AsRef<str>,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<SessionCursor<Bson>> {
+        runtime::block_on(self.async_collection.distinct_cursor_with_session(
+            field_name.as_ref(),
+            filter.into(),
+            options.into(),
+            &mut session.async_client_session,
+        ))
+        .map(SessionCursor::new)
+    }
+
     /// Updates all documents matching `query` in the collection.
     ///
     /// Both `Document` and `Vec<Document>` implement `Into<UpdateModifications>`, so either can be
@@ -459,6 +682,24 @@ impl<T> Collection<T> {
         )
     }
 
+    /// Gets the UUID of the collection, which can be used to correlate the collection with change
+    /// stream events and oplog entries.
+    ///
+    /// Returns `None` if the collection is a view or does not exist.
+    pub fn uuid(&self) -> Result<Option<Uuid>> {
+        runtime::block_on(self.async_collection.uuid())
+    }
+
+    /// Gets the UUID of the collection using the provided `ClientSession`.
+    ///
+    /// Returns `None` if the collection is a view or does not exist.
+    pub fn uuid_with_session(&self, session: &mut ClientSession) -> Result<Option<Uuid>> {
+        runtime::block_on(
+            self.async_collection
+                .uuid_with_session(&mut session.async_client_session),
+        )
+    }
+
     /// Updates all documents matching `query` in the collection using the provided `ClientSession`.
     ///
     /// Both `Document` and `Vec<Document>` implement `Into<UpdateModifications>`, so either can be
@@ -480,6 +721,55 @@ impl<T> Collection<T> {
         ))
     }
 
+    /// Updates all documents matching each filter in `updates` with the corresponding
+    /// modifications, sending the updates to the server in as few `update` commands as possible.
+    /// The matched and modified counts of the returned [`UpdateResult`] are summed across all of
+    /// the updates, so a filter that matches no documents doesn't contribute to them, and its
+    /// `raw_reply` is the reply to the last command sent. If the `upsert` option is set,
+    /// `upserted_id` is the `_id` of the first document that was upserted.
+    ///
+    /// The updates are applied in order, and no further updates are applied after one fails.
+    /// Updates sent in earlier commands than the failing one will already have been applied.
+    ///
+    /// Both `Document` and `Vec<Document>` implement `Into<UpdateModifications>`, so either can be
+    /// used for the modifications. Note: pipeline updates are only supported in MongoDB 4.2+. See
+    /// the official MongoDB
+    /// [documentation](https://www.mongodb.com/docs/manual/reference/command/update/#behavior) for more information on specifying updates.
+    pub fn update_many_multi(
+        &self,
+        updates: impl IntoIterator<Item = (Document, impl Into<UpdateModifications>)>,
+        options: impl Into<Option<UpdateOptions>>,
+    ) -> Result<UpdateResult> {
+        let updates: Vec<(Document, UpdateModifications)> = updates
+            .into_iter()
+            .map(|(query, update)| (query, update.into()))
+            .collect();
+        runtime::block_on(
+            self.async_collection
+                .update_many_multi(updates, options.into()),
+        )
+    }
+
+    /// Updates all documents matching each filter in `updates` with the corresponding
+    /// modifications using the provided `ClientSession`. See [`Collection::update_many_multi`] for
+    /// more details.
+    pub fn update_many_multi_with_session(
+        &self,
+        updates: impl IntoIterator<Item = (Document, impl Into<UpdateModifications>)>,
+        options: impl Into<Option<UpdateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<UpdateResult> {
+        let updates: Vec<(Document, UpdateModifications)> = updates
+            .into_iter()
+            .map(|(query, update)| (query, update.into()))
+            .collect();
+        runtime::block_on(self.async_collection.update_many_multi_with_session(
+            updates,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Updates up to one document matching `query` in the collection.
     ///
     /// Both `Document` and `Vec<Document>` implement `Into<UpdateModifications>`, so either can be
@@ -589,6 +879,50 @@ impl<T> Collection<T> {
             .map(Cursor::new)
     }
 
+    /// Finds the documents in the collection matching `filter`, adjusting the batch size of each
+    /// subsequent request to the server so that every batch is around 4 MiB based on the sizes of
+    /// the documents received so far.
+    ///
+    /// This bounds the memory used by the cursor's buffer when documents are large or vary widely
+    /// in size. If `options` specifies a `batch_size`, it is only used for the first batch.
+    pub fn find_streaming(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> Result<Cursor<T>> {
+        runtime::block_on(
+            self.async_collection
+                .find_streaming(filter.into(), options.into()),
+        )
+        .map(Cursor::new)
+    }
+
+    /// Splits the documents matching the filter in `options` into at most `num_cursors`
+    /// disjoint ranges of `_id` and returns a cursor for each, so that the collection can be read
+    /// in parallel. Every matching document is returned by exactly one of the cursors. See
+    /// [`crate::Collection::parallel_scan`] for more details.
+    pub fn parallel_scan(
+        &self,
+        num_cursors: usize,
+        options: impl Into<Option<ParallelScanOptions>>,
+    ) -> Result<Vec<Cursor<T>>> {
+        runtime::block_on(
+            self.async_collection
+                .parallel_scan(num_cursors, options.into()),
+        )
+        .map(|cursors| cursors.into_iter().map(Cursor::new).collect())
+    }
+
+    /// Exports the documents in the collection as they were at a single point in time, even if
+    /// the collection is written to while the export is in progress. See
+    /// [`crate::Collection::snapshot_export`] for more details.
+    pub fn snapshot_export(
+        &self,
+        options: impl Into<Option<SnapshotExportOptions>>,
+    ) -> Result<Cursor<T>> {
+        runtime::block_on(self.async_collection.snapshot_export(options.into())).map(Cursor::new)
+    }
+
     /// Finds the documents in the collection matching `filter` using the provided `ClientSession`.
     pub fn find_with_session(
         &self,
@@ -621,6 +955,20 @@ where
         )
     }
 
+    /// Finds a single document in the collection matching `filter`, checking the primary before
+    /// concluding that no such document exists. See [`crate::Collection::find_one_consistent`]
+    /// for more details.
+    pub fn find_one_consistent(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOneOptions>>,
+    ) -> Result<Option<T>> {
+        runtime::block_on(
+            self.async_collection
+                .find_one_consistent(filter.into(), options.into()),
+        )
+    }
+
     /// Finds a single document in the collection matching `filter` using the provided
     /// `ClientSession`.
     pub fn find_one_with_session(
@@ -635,6 +983,42 @@ where
             &mut session.async_client_session,
         ))
     }
+
+    /// Polls the collection until a document matching `filter` is found or `timeout` elapses,
+    /// returning `None` in the latter case. This is useful when waiting for a write made
+    /// elsewhere to become visible, e.g. when reading from a secondary.
+    ///
+    /// The first query is run immediately. The delay between queries starts at `poll_interval`
+    /// and doubles after each unsuccessful one, but never extends past the deadline; a final
+    /// query is always run once the deadline is reached, so a document that appears just before
+    /// it is still returned. A `timeout` too large to be represented (e.g. `Duration::MAX`) polls
+    /// without a deadline.
+    pub fn wait_for(
+        &self,
+        filter: impl Into<Option<Document>>,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Option<T>> {
+        runtime::block_on(
+            self.async_collection
+                .wait_for(filter.into(), timeout, poll_interval),
+        )
+    }
+
+    /// Returns a page of at most `page_size` documents from the collection in ascending order of
+    /// the `sort_field` field, along with a token to pass as `after` to get the next page, or
+    /// `None` if this was the last page. See [`crate::Collection::paginate`] for more details.
+    pub fn paginate(
+        &self,
+        sort_field: impl AsRef<str>,
+        page_size: u32,
+        after: Option<Bson>,
+    ) -> Result<(Vec<T>, Option<Bson>)> {
+        runtime::block_on(
+            self.async_collection
+                .paginate(sort_field.as_ref(), page_size, after),
+        )
+    }
 }
 
 impl<T> Collection<T>
@@ -678,6 +1062,46 @@ where
         ))
     }
 
+    /// Finds the documents in the collection matching `filter`, deleting and returning up to
+    /// `limit` of them (or all of them if no limit is specified) in the order given by `sort`.
+    ///
+    /// The documents are deleted one at a time using `findAndModify` commands, so while each
+    /// document is found and deleted atomically, they are not deleted as a single atomic
+    /// operation. Documents that other clients insert or update to match `filter` while the
+    /// operation is in progress may be deleted as well, and documents that other clients delete
+    /// in the meantime won't be returned. If all of the documents must be deleted atomically, use
+    /// [`Collection::find_many_and_delete_with_session`] with a session in a transaction.
+    ///
+    /// If an error occurs, the returned [`FindManyAndDeleteError`] contains both the error and the
+    /// documents that were deleted before it occurred. `FindManyAndDeleteError` converts into
+    /// [`Error`](crate::error::Error), so `?` can be used when those documents aren't needed.
+    pub fn find_many_and_delete(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindManyAndDeleteOptions>>,
+    ) -> std::result::Result<Vec<T>, FindManyAndDeleteError<T>> {
+        runtime::block_on(
+            self.async_collection
+                .find_many_and_delete(filter, options.into()),
+        )
+    }
+
+    /// Finds the documents in the collection matching `filter`, deleting and returning up to
+    /// `limit` of them using the provided `ClientSession`. See
+    /// [`Collection::find_many_and_delete`] for more details.
+    pub fn find_many_and_delete_with_session(
+        &self,
+        filter: Document,
+        options: impl Into<Option<FindManyAndDeleteOptions>>,
+        session: &mut ClientSession,
+    ) -> std::result::Result<Vec<T>, FindManyAndDeleteError<T>> {
+        runtime::block_on(self.async_collection.find_many_and_delete_with_session(
+            filter,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Atomically finds up to one document in the collection matching `filter` and updates it.
     /// Both `Document` and `Vec<Document>` implement `Into<UpdateModifications>`, so either can be
     /// passed in place of constructing the enum case. Note: pipeline updates are only supported
@@ -723,6 +1147,98 @@ where
             &mut session.async_client_session,
         ))
     }
+
+    /// Atomically adds `amount` to the integer `field` of the document matching `filter` and
+    /// returns the field's new value. `field` may be a dotted path into an embedded document.
+    ///
+    /// If `field` doesn't exist in the matching document, it's set to `amount`, and if no document
+    /// matches `filter`, a new document is inserted with `field` set to `amount` (along with any
+    /// equality conditions in `filter`), so the first increment of a counter returns `amount`.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub fn increment(
+        &self,
+        filter: Document,
+        field: impl AsRef<str>,
+        amount: i64,
+        options: impl Into<Option<IncrementOptions>>,
+    ) -> Result<i64> {
+        runtime::block_on(
+            self.async_collection
+                .increment(filter, field, amount, options.into()),
+        )
+    }
+
+    /// Atomically adds `amount` to the integer `field` of the document matching `filter` using the
+    /// provided `ClientSession` and returns the field's new value. See [`Collection::increment`]
+    /// for more details.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub fn increment_with_session(
+        &self,
+        filter: Document,
+        field: impl AsRef<str>,
+        amount: i64,
+        options: impl Into<Option<IncrementOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<i64> {
+        runtime::block_on(self.async_collection.increment_with_session(
+            filter,
+            field,
+            amount,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
+    /// Atomically claims a single job from a collection used as a work queue, returning the
+    /// claimed document as it is after `claim_update` is applied, or `None` if there is no job to
+    /// claim. See [`crate::Collection::claim_one`] for more details.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub fn claim_one(
+        &self,
+        filter: Document,
+        claim_update: impl Into<UpdateModifications>,
+        options: impl Into<Option<ClaimOptions>>,
+    ) -> Result<Option<T>> {
+        runtime::block_on(self.async_collection.claim_one(
+            filter,
+            claim_update.into(),
+            options.into(),
+        ))
+    }
+
+    /// Atomically claims a single job from a collection used as a work queue using the provided
+    /// `ClientSession`. See [`crate::Collection::claim_one`] for more details.
+    ///
+    /// This operation will retry once upon failure if the connection and encountered error support
+    /// retryability. See the documentation
+    /// [here](https://www.mongodb.com/docs/manual/core/retryable-writes/) for more information on
+    /// retryable writes.
+    pub fn claim_one_with_session(
+        &self,
+        filter: Document,
+        claim_update: impl Into<UpdateModifications>,
+        options: impl Into<Option<ClaimOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<T>> {
+        runtime::block_on(self.async_collection.claim_one_with_session(
+            filter,
+            claim_update.into(),
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
 }
 
 impl<T> Collection<T>
@@ -845,6 +1361,61 @@ where
         ))
     }
 
+    /// Inserts `doc` into the collection and waits until the write has propagated to the nodes
+    /// required by `tag_set_mode`, a custom write concern defined in the `getLastErrorModes` of
+    /// the replica set configuration (e.g. one that requires acknowledgement from nodes in
+    /// multiple regions). Any `w_timeout` of the collection's write concern is still applied, so
+    /// setting one bounds how long this waits for nodes that are unavailable.
+    ///
+    /// The server validates `tag_set_mode` before inserting, and an
+    /// [`ErrorKind::InvalidArgument`](crate::error::ErrorKind::InvalidArgument) error is returned
+    /// without inserting if it isn't defined in the replica set configuration. This is only
+    /// supported on replica sets.
+    pub fn insert_one_and_wait(
+        &self,
+        doc: impl Borrow<T>,
+        tag_set_mode: impl AsRef<str>,
+    ) -> Result<InsertOneResult> {
+        runtime::block_on(
+            self.async_collection
+                .insert_one_and_wait(doc.borrow(), tag_set_mode),
+        )
+    }
+
+    /// Upserts each of `docs` by the natural key made up of its `key_fields`: the fields of a
+    /// document are set on the existing document with the same values for `key_fields` if there
+    /// is one, or the document is inserted otherwise. See
+    /// [`Collection::bulk_upsert`](crate::Collection::bulk_upsert) for more details.
+    pub fn bulk_upsert(
+        &self,
+        docs: impl IntoIterator<Item = impl Borrow<T>>,
+        key_fields: impl IntoIterator<Item = impl AsRef<str>>,
+        options: impl Into<Option<BulkUpsertOptions>>,
+    ) -> Result<BulkUpsertResult> {
+        runtime::block_on(
+            self.async_collection
+                .bulk_upsert(docs, key_fields, options.into()),
+        )
+    }
+
+    /// Upserts each of `docs` by the natural key made up of its `key_fields` using the provided
+    /// `ClientSession`. See [`Collection::bulk_upsert`](crate::Collection::bulk_upsert) for more
+    /// details.
+    pub fn bulk_upsert_with_session(
+        &self,
+        docs: impl IntoIterator<Item = impl Borrow<T>>,
+        key_fields: impl IntoIterator<Item = impl AsRef<str>>,
+        options: impl Into<Option<BulkUpsertOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<BulkUpsertResult> {
+        runtime::block_on(self.async_collection.bulk_upsert_with_session(
+            docs,
+            key_fields,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Replaces up to one document matching `query` in the collection with `replacement`.
     ///
     /// This operation will retry once upon failure if the connection and encountered error support
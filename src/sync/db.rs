@@ -1,21 +1,31 @@
 use std::fmt::Debug;
 
+use serde::de::DeserializeOwned;
+
 use super::{ChangeStream, ClientSession, Collection, Cursor, SessionChangeStream, SessionCursor};
 use crate::{
     bson::Document,
     change_stream::{event::ChangeStreamEvent, options::ChangeStreamOptions},
-    error::Result,
+    error::{InsertStreamError, Result},
     options::{
         AggregateOptions,
         CollectionOptions,
+        CopyCollectionOptions,
         CreateCollectionOptions,
+        CreateViewOptions,
+        DatabaseStatsOptions,
+        DropCollectionOptions,
         DropDatabaseOptions,
+        FindOptions,
         ListCollectionsOptions,
+        ProfilingLevel,
+        ProfilingOptions,
         ReadConcern,
         SelectionCriteria,
+        ServerApi,
         WriteConcern,
     },
-    results::CollectionSpecification,
+    results::{CollectionSpecification, DatabaseStats, InsertManyResult, ProfileEntry},
     runtime,
     Database as AsyncDatabase,
 };
@@ -82,6 +92,11 @@ impl Database {
         self.async_database.write_concern()
     }
 
+    /// Gets the stable server API declared on the `Database`'s commands.
+    pub fn server_api(&self) -> Option<&ServerApi> {
+        self.async_database.server_api()
+    }
+
     /// Gets a handle to a collection with type `T` specified by `name` of the database. The
     /// `Collection` options (e.g. read preference and write concern) will default to those of the
     /// `Database`.
@@ -210,6 +225,90 @@ impl Database {
         ))
     }
 
+    /// Gets storage statistics for the database via the `dbStats` command.
+    ///
+    /// If a `scale` is given in `options`, every size in the result is divided by it, including
+    /// the average document size which the server itself always reports in bytes.
+    pub fn stats(&self, options: impl Into<Option<DatabaseStatsOptions>>) -> Result<DatabaseStats> {
+        runtime::block_on(self.async_database.stats(options.into()))
+    }
+
+    /// Drops every non-system collection and view in the database, leaving the database itself
+    /// and its system collections in place. This is mainly useful for setting up or tearing down
+    /// tests.
+    ///
+    /// Views are dropped before collections. Collections that are dropped concurrently by another
+    /// client are ignored.
+    pub fn drop_all_collections(
+        &self,
+        options: impl Into<Option<DropCollectionOptions>>,
+    ) -> Result<()> {
+        runtime::block_on(self.async_database.drop_all_collections(options.into()))
+    }
+
+    /// Drops every non-system collection and view in the database using the provided
+    /// `ClientSession`, leaving the database itself and its system collections in place.
+    ///
+    /// Views are dropped before collections. Collections that are dropped concurrently by another
+    /// client are ignored.
+    pub fn drop_all_collections_with_session(
+        &self,
+        options: impl Into<Option<DropCollectionOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<()> {
+        runtime::block_on(
+            self.async_database.drop_all_collections_with_session(
+                options.into(),
+                &mut session.async_client_session,
+            ),
+        )
+    }
+
+    /// Creates a new view in the database with the given `name` that runs `pipeline` against the
+    /// collection or view named `view_on`.
+    ///
+    /// The source does not need to exist when the view is created; until it does, queries against
+    /// the view return no documents.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/core/views/) for more
+    /// information on views.
+    pub fn create_view(
+        &self,
+        name: impl AsRef<str>,
+        view_on: impl Into<String>,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<CreateViewOptions>>,
+    ) -> Result<()> {
+        runtime::block_on(self.async_database.create_view(
+            name.as_ref(),
+            view_on,
+            pipeline,
+            options.into(),
+        ))
+    }
+
+    /// Creates a new view in the database with the given `name` that runs `pipeline` against the
+    /// collection or view named `view_on` using the provided `ClientSession`.
+    ///
+    /// The source does not need to exist when the view is created; until it does, queries against
+    /// the view return no documents.
+    pub fn create_view_with_session(
+        &self,
+        name: impl AsRef<str>,
+        view_on: impl Into<String>,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<CreateViewOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<()> {
+        runtime::block_on(self.async_database.create_view_with_session(
+            name.as_ref(),
+            view_on,
+            pipeline,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Runs a database-level command.
     ///
     /// Note that no inspection is done on `doc`, so the command will not use the database's default
@@ -244,6 +343,67 @@ impl Database {
         ))
     }
 
+    /// Sets the level of the database profiler for this database via the `profile` command.
+    /// Profiled operations are recorded in the database's `system.profile` collection.
+    ///
+    /// On a sharded cluster, mongos does not record profiling data, but the slow operation
+    /// threshold and sample rate still apply to its diagnostic log.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/reference/command/profile/)
+    /// for more information.
+    pub fn set_profiling_level(
+        &self,
+        level: ProfilingLevel,
+        options: impl Into<Option<ProfilingOptions>>,
+    ) -> Result<()> {
+        runtime::block_on(
+            self.async_database
+                .set_profiling_level(level, options.into()),
+        )
+    }
+
+    /// Gets the current level of the database profiler for this database via the `profile`
+    /// command.
+    pub fn profiling_level(&self) -> Result<ProfilingLevel> {
+        runtime::block_on(self.async_database.profiling_level())
+    }
+
+    /// Queries the entries recorded by the database profiler in this database's `system.profile`
+    /// collection. The entries are returned in the order the profiler recorded them unless a sort
+    /// is specified in `options`.
+    ///
+    /// If the profiler has never been enabled for this database (see
+    /// [`Database::set_profiling_level`]), the returned cursor is empty.
+    pub fn profile_entries(
+        &self,
+        filter: impl Into<Option<Document>>,
+        options: impl Into<Option<FindOptions>>,
+    ) -> Result<Cursor<ProfileEntry>> {
+        runtime::block_on(
+            self.async_database
+                .profile_entries(filter.into(), options.into()),
+        )
+        .map(Cursor::new)
+    }
+
+    /// Copies the documents in the collection named `from` into the collection named `to` in this
+    /// database in batches, optionally applying a filter and projection via `options`.
+    ///
+    /// Indexes and collection options are not copied. If an insert into the target collection
+    /// fails, copying stops and the returned [`InsertStreamError`] contains both the error and the
+    /// documents that were copied before it occurred.
+    pub fn copy_collection(
+        &self,
+        from: &str,
+        to: &str,
+        options: impl Into<Option<CopyCollectionOptions>>,
+    ) -> std::result::Result<InsertManyResult, InsertStreamError> {
+        runtime::block_on(
+            self.async_database
+                .copy_collection(from, to, options.into()),
+        )
+    }
+
     /// Runs an aggregation operation.
     ///
     /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
@@ -276,6 +436,47 @@ impl Database {
         .map(SessionCursor::new)
     }
 
+    /// Runs a database-level aggregation operation (e.g. one beginning with a `$currentOp` or
+    /// `$documents` stage) that is expected to produce at most one document, deserializing the
+    /// result as `R`. `Ok(None)` is returned if the pipeline produces no documents.
+    ///
+    /// If the pipeline produces more than one document, only the first one is returned and the
+    /// rest are discarded.
+    ///
+    /// See the documentation [here](https://www.mongodb.com/docs/manual/aggregation/) for more
+    /// information on aggregations.
+    pub fn aggregate_one<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let pipeline: Vec<Document> = pipeline.into_iter().collect();
+        runtime::block_on(self.async_database.aggregate_one(pipeline, options.into()))
+    }
+
+    /// Runs a database-level aggregation operation that is expected to produce at most one
+    /// document using the provided `ClientSession`, deserializing the result as `R`. See
+    /// [`Database::aggregate_one`] for more details.
+    pub fn aggregate_one_with_session<R>(
+        &self,
+        pipeline: impl IntoIterator<Item = Document>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Option<R>>
+    where
+        R: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let pipeline: Vec<Document> = pipeline.into_iter().collect();
+        runtime::block_on(self.async_database.aggregate_one_with_session(
+            pipeline,
+            options.into(),
+            &mut session.async_client_session,
+        ))
+    }
+
     /// Starts a new [`ChangeStream`](change_stream/struct.ChangeStream.html) that receives events
     /// for all changes in this database. The stream does not observe changes from system
     /// collections and cannot be started on "config", "local" or "admin" databases.
@@ -1,15 +1,18 @@
+use std::time::Duration;
+
 use bson::{doc, Bson, Document};
 use futures_util::StreamExt;
 use semver::VersionReq;
 
 use crate::{
     change_stream::{
-        event::{ChangeStreamEvent, OperationType},
-        options::ChangeStreamOptions,
+        event::{ChangeStreamEvent, OperationType, ResumeToken},
+        options::{ChangeStreamOptions, FullDocumentBeforeChangeType, FullDocumentType},
         ChangeStream,
     },
     coll::options::CollectionOptions,
     db::options::{ChangeStreamPreAndPostImages, CreateCollectionOptions},
+    error::ErrorKind,
     event::command::{CommandStartedEvent, CommandSucceededEvent},
     options::{Acknowledgment, WriteConcern},
     test::{CommandEvent, FailCommandOptions, FailPoint, FailPointMode},
@@ -709,3 +712,285 @@ async fn create_coll_pre_post() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn max_time_and_max_await_time() -> Result<()> {
+    let _guard = LOCK.run_concurrently().await;
+
+    let (client, coll, _) = match init_stream("max_time_and_max_await_time", false).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    let options = ChangeStreamOptions::builder()
+        .max_time(Duration::from_secs(5))
+        .max_await_time(Duration::from_millis(100))
+        .build();
+    let mut stream = coll.watch(None, options).await?;
+    assert!(stream.next_if_any().await?.is_none());
+
+    let aggregate = client
+        .get_command_started_events(&["aggregate"])
+        .pop()
+        .unwrap();
+    assert_eq!(aggregate.command.get("maxTimeMS"), Some(&Bson::Int32(5000)));
+    let get_more = client
+        .get_command_started_events(&["getMore"])
+        .pop()
+        .unwrap();
+    assert_eq!(get_more.command.get("maxTimeMS"), Some(&Bson::Int32(100)));
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn comment() -> Result<()> {
+    let _guard = LOCK.run_concurrently().await;
+
+    let (client, coll, _) = match init_stream("comment", false).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+    if client.server_version_lt(4, 4) {
+        log_uncaptured("skipping change stream comment test: requires server 4.4+");
+        return Ok(());
+    }
+
+    let options = ChangeStreamOptions::builder()
+        .comment(Bson::from("change_stream_comment"))
+        .build();
+    let mut stream = coll.watch(None, options).await?;
+    assert!(stream.next_if_any().await?.is_none());
+
+    let aggregate = client
+        .get_command_started_events(&["aggregate"])
+        .pop()
+        .unwrap();
+    assert_eq!(
+        aggregate.command.get_str("comment"),
+        Ok("change_stream_comment")
+    );
+    let get_more = client
+        .get_command_started_events(&["getMore"])
+        .pop()
+        .unwrap();
+    assert_eq!(
+        get_more.command.get_str("comment"),
+        Ok("change_stream_comment")
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn batch_size() -> Result<()> {
+    let _guard = LOCK.run_concurrently().await;
+
+    let (client, coll, _) = match init_stream("batch_size", false).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    let options = ChangeStreamOptions::builder().batch_size(5).build();
+    let mut stream = coll.watch(None, options).await?;
+    assert!(stream.next_if_any().await?.is_none());
+
+    let aggregate = client
+        .get_command_started_events(&["aggregate"])
+        .pop()
+        .unwrap();
+    assert_eq!(
+        aggregate.command.get_document("cursor")?.get("batchSize"),
+        Some(&Bson::Int32(5))
+    );
+    let get_more = client
+        .get_command_started_events(&["getMore"])
+        .pop()
+        .unwrap();
+    assert_eq!(get_more.command.get("batchSize"), Some(&Bson::Int32(5)));
+
+    // A batch size of 0 leaves the getMore batch size up to the server.
+    let options = ChangeStreamOptions::builder().batch_size(0).build();
+    let mut stream = coll.watch(None, options).await?;
+    assert!(stream.next_if_any().await?.is_none());
+
+    let get_more = client
+        .get_command_started_events(&["getMore"])
+        .pop()
+        .unwrap();
+    assert_eq!(get_more.command.get("batchSize"), None);
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn resume_from_saved_token() -> Result<()> {
+    let _guard = LOCK.run_concurrently().await;
+
+    let (_, coll, mut stream) = match init_stream("resume_from_saved_token", false).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    coll.insert_one(doc! { "_id": 1 }, None).await?;
+    stream.next().await.transpose()?;
+    let saved = bson::to_document(&stream.resume_token().unwrap())?;
+    drop(stream);
+
+    coll.insert_one(doc! { "_id": 2 }, None).await?;
+    let options = ChangeStreamOptions::builder()
+        .resume_after(Some(ResumeToken::from_document(saved)?))
+        .build();
+    let mut stream = coll.watch(None, options).await?;
+    assert!(matches!(stream.next().await.transpose()?,
+        Some(ChangeStreamEvent {
+            operation_type: OperationType::Insert,
+            document_key: Some(key),
+            ..
+        }) if key == doc! { "_id": 2 }
+    ));
+
+    coll.insert_one(doc! { "_id": 3 }, None).await?;
+    assert!(matches!(stream.next().await.transpose()?,
+        Some(ChangeStreamEvent {
+            operation_type: OperationType::Insert,
+            document_key: Some(key),
+            ..
+        }) if key == doc! { "_id": 3 }
+    ));
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn malformed_resume_token() -> Result<()> {
+    let _guard = LOCK.run_concurrently().await;
+
+    let (client, coll, _) = match init_stream("malformed_resume_token", false).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    // A token that isn't a document is rejected without contacting the server.
+    let token: ResumeToken = bson::from_bson(Bson::String("8262".to_string()))?;
+    let options = ChangeStreamOptions::builder()
+        .resume_after(Some(token))
+        .build();
+    let err = coll.watch(None, options).await.unwrap_err();
+    assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
+    // Only the `aggregate` from `init_stream` should have been sent.
+    assert_eq!(client.get_command_started_events(&["aggregate"]).len(), 1);
+
+    // The contents of a document token are left for the server to check.
+    let token = ResumeToken::from_document(doc! { "data": "8262" })?;
+    let options = ChangeStreamOptions::builder()
+        .resume_after(Some(token))
+        .build();
+    let err = coll.watch(None, options).await.unwrap_err();
+    assert!(matches!(*err.kind, ErrorKind::Command(_)), "{:?}", err);
+    assert_eq!(client.get_command_started_events(&["aggregate"]).len(), 2);
+
+    Ok(())
+}
+
+/// A `ChangeStreamHistoryLost` error means the resume token has aged out of the oplog, so the
+/// change stream must surface it rather than attempting to resume.
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))] // multi_thread required for FailPoint
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn history_lost_not_resumed() -> Result<()> {
+    let _guard = LOCK.run_exclusively().await;
+
+    let (client, coll, mut stream) = match init_stream("history_lost_not_resumed", true).await? {
+        Some(t) => t,
+        None => return Ok(()),
+    };
+
+    let _guard = FailPoint::fail_command(
+        &["getMore"],
+        FailPointMode::Times(1),
+        FailCommandOptions::builder().error_code(286).build(),
+    )
+    .enable(&client, None)
+    .await?;
+
+    coll.insert_one(doc! {}, None).await?;
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(err.code(), Some(286));
+    assert!(!err.is_resumable());
+
+    // Assert that no resume was attempted.
+    let events = client.get_command_started_events(&["aggregate"]);
+    assert_eq!(events.len(), 1);
+
+    Ok(())
+}
+
+/// Change events exceeding 16MB are split into fragments by `$changeStreamSplitLargeEvent` and
+/// must be returned as a single event.
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn split_large_event() -> Result<()> {
+    let _guard = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    if !client.is_replica_set() || client.server_version_lt(7, 0) {
+        log_uncaptured("skipping split_large_event: requires a 7.0+ replica set");
+        return Ok(());
+    }
+
+    let db = client.database("change_stream_tests");
+    db.collection::<Document>("split_large_event")
+        .drop(None)
+        .await?;
+    db.create_collection(
+        "split_large_event",
+        CreateCollectionOptions::builder()
+            .change_stream_pre_and_post_images(ChangeStreamPreAndPostImages { enabled: true })
+            .build(),
+    )
+    .await?;
+    let coll = db.collection::<Document>("split_large_event");
+
+    let options = ChangeStreamOptions::builder()
+        .full_document(Some(FullDocumentType::Required))
+        .full_document_before_change(Some(FullDocumentBeforeChangeType::Required))
+        .split_large_events(Some(true))
+        .build();
+    let mut stream = coll
+        .watch([doc! { "$match": { "operationType": "replace" } }], options)
+        .await?;
+
+    // The pre- and post-images together make the replace event larger than 16MB.
+    let size = 10 * 1024 * 1024;
+    coll.insert_one(doc! { "_id": 1, "value": "a".repeat(size) }, None)
+        .await?;
+    coll.replace_one(
+        doc! { "_id": 1 },
+        doc! { "_id": 1, "value": "b".repeat(size) },
+        None,
+    )
+    .await?;
+
+    let event = stream.next().await.transpose()?.unwrap();
+    assert_eq!(event.operation_type, OperationType::Replace);
+    assert_eq!(event.document_key, Some(doc! { "_id": 1 }));
+    let before = event.full_document_before_change.unwrap();
+    assert_eq!(before.get_str("value")?, "a".repeat(size));
+    let after = event.full_document.unwrap();
+    assert_eq!(after.get_str("value")?, "b".repeat(size));
+    assert_eq!(stream.resume_token(), Some(event.id));
+
+    let aggregate = client.get_command_started_events(&["aggregate"]);
+    let pipeline = aggregate[0].command.get_array("pipeline")?;
+    assert_eq!(
+        pipeline.last(),
+        Some(&Bson::Document(doc! { "$changeStreamSplitLargeEvent": {} }))
+    );
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use std::{fmt::Debug, time::Duration};
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    time::{Duration, Instant},
+};
 
 use futures::stream::{StreamExt, TryStreamExt};
 use lazy_static::lazy_static;
@@ -7,35 +11,77 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
-    bson::{doc, to_document, Bson, Document},
+    bson::{doc, oid::ObjectId, to_document, Bson, DateTime, Document},
+    bson_util,
     error::{ErrorKind, Result, WriteFailure},
+    event::command::CommandStartedEvent,
     options::{
         Acknowledgment,
         AggregateOptions,
+        BulkUpsertOptions,
+        ClaimOptions,
         CollectionOptions,
+        CreateCollectionOptions,
+        DatabaseOptions,
         DeleteOptions,
+        DensifyBounds,
+        DensifyStage,
         DropCollectionOptions,
+        FillMethod,
+        FillOutput,
+        FillStage,
+        FindManyAndDeleteOptions,
         FindOneAndDeleteOptions,
+        FindOneAndUpdateOptions,
         FindOneOptions,
         FindOptions,
         Hint,
         IndexOptions,
         InsertManyOptions,
+        MergeStage,
+        MergeWhenMatched,
+        MergeWhenNotMatched,
+        NumericCoercion,
+        OnDuplicate,
+        OutStage,
+        ParallelScanOptions,
         ReadConcern,
         ReadPreference,
+        ReplaceOptions,
+        ReturnDocument,
         SelectionCriteria,
+        ServerApi,
+        ServerApiVersion,
+        SetWindowFieldsStage,
+        SnapshotExportOptions,
+        TimeUnit,
+        TimeseriesOptions,
+        UnionWithStage,
         UpdateOptions,
+        ValidateOptions,
+        VectorSearchStage,
+        Window,
+        WindowBound,
+        WindowOutput,
         WriteConcern,
     },
-    results::DeleteResult,
+    results::{DeleteResult, ShardDocument},
     runtime,
+    sdam::ServerType,
     test::{
         log_uncaptured,
         util::{drop_collection, EventClient, TestClient},
+        FailCommandOptions,
+        FailPoint,
+        FailPointMode,
         CLIENT_OPTIONS,
         LOCK,
+        SERVERLESS,
+        SERVER_API,
     },
+    Client,
     Collection,
+    Filter,
     IndexModel,
 };
 
@@ -268,6 +314,592 @@ async fn aggregate_out() {
         .any(|name| name.as_str() == out_coll.name()));
 }
 
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_union_with() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(4, 4) {
+        log_uncaptured("skipping aggregate_union_with due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let other = client
+        .init_db_and_coll(function_name!(), &format!("{}_other", function_name!()))
+        .await;
+
+    coll.insert_many((0..3).map(|i| doc! { "x": i }), None)
+        .await
+        .unwrap();
+    other
+        .insert_many((0..5).map(|i| doc! { "x": i }), None)
+        .await
+        .unwrap();
+
+    let union: Document = UnionWithStage::builder().coll(other.name()).build().into();
+    let results: Vec<Document> = coll
+        .aggregate(vec![union], None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 8);
+
+    // The unioned collection's pipeline can itself union with another collection.
+    let nested: Document = UnionWithStage::builder().coll(coll.name()).build().into();
+    let union: Document = UnionWithStage::builder()
+        .coll(other.name())
+        .pipeline(vec![doc! { "$match": { "x": { "$lt": 2 } } }, nested])
+        .build()
+        .into();
+    let results: Vec<Document> = coll
+        .aggregate(vec![union], None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 3 + 2 + 3);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_merge() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(4, 2) {
+        log_uncaptured("skipping aggregate_merge due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let target = client
+        .init_db_and_coll(function_name!(), &format!("{}_target", function_name!()))
+        .await;
+
+    coll.insert_many(
+        vec![
+            doc! { "_id": 1, "total": 10 },
+            doc! { "_id": 2, "total": 20 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    target
+        .insert_many(
+            vec![
+                doc! { "_id": 1, "total": 1, "kept": true },
+                doc! { "_id": 3, "total": 3 },
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Matching documents are updated via the pipeline, unmatched ones are discarded.
+    let merge = MergeStage::builder()
+        .into(target.namespace())
+        .when_matched(MergeWhenMatched::Pipeline {
+            pipeline: vec![doc! { "$set": { "total": { "$add": ["$total", "$$inc"] } } }],
+            let_vars: Some(doc! { "inc": "$total" }),
+        })
+        .when_not_matched(MergeWhenNotMatched::Discard)
+        .build();
+    coll.aggregate(vec![merge.into()], None).await.unwrap();
+
+    let results: Vec<Document> = target
+        .find(None, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "_id": 1, "total": 11, "kept": true },
+            doc! { "_id": 3, "total": 3 },
+        ]
+    );
+
+    // Matching documents are replaced and unmatched ones are inserted.
+    let merge = MergeStage::builder()
+        .into(target.namespace())
+        .when_matched(MergeWhenMatched::Replace)
+        .when_not_matched(MergeWhenNotMatched::Insert)
+        .build();
+    coll.aggregate(vec![merge.into()], None).await.unwrap();
+
+    let results: Vec<Document> = target
+        .find(None, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "_id": 1, "total": 10 },
+            doc! { "_id": 2, "total": 20 },
+            doc! { "_id": 3, "total": 3 },
+        ]
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_merge_secondary_read_preference() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    if !client.is_replica_set() || client.server_version_lt(4, 2) {
+        log_uncaptured(
+            "skipping aggregate_merge_secondary_read_preference due to test configuration",
+        );
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let target = client
+        .init_db_and_coll(function_name!(), &format!("{}_target", function_name!()))
+        .await;
+    coll.insert_one(doc! { "_id": 1 }, None).await.unwrap();
+
+    let merge = MergeStage::builder().into(target.namespace()).build();
+    let options = AggregateOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(
+            ReadPreference::SecondaryPreferred {
+                options: Default::default(),
+            },
+        ))
+        .build();
+    coll.aggregate(vec![merge.into()], options).await.unwrap();
+    assert_eq!(target.count_documents(None, None).await.unwrap(), 1);
+
+    // Servers older than 5.0 can't execute aggregations that write on secondaries, so the
+    // requested read preference is only used on newer servers.
+    let address = client.get_command_started_events(&["aggregate"])[0]
+        .connection
+        .address
+        .clone();
+    let topology = client.topology_description();
+    let has_secondary = topology
+        .servers
+        .values()
+        .any(|server| server.server_type == ServerType::RsSecondary);
+    let server_type = topology.servers[&address].server_type;
+    if client.server_version_lt(5, 0) || !has_secondary {
+        assert_eq!(server_type, ServerType::RsPrimary);
+    } else {
+        assert_eq!(server_type, ServerType::RsSecondary);
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_out_timeseries() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if !VersionReq::parse(">= 7.0.3")
+        .unwrap()
+        .matches(&client.server_version)
+    {
+        log_uncaptured("skipping aggregate_out_timeseries due to test configuration");
+        return;
+    }
+    let db = client.database(function_name!());
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let target = db.collection::<Document>(&format!("{}_target", function_name!()));
+    drop_collection(&target).await;
+
+    let now = DateTime::now().timestamp_millis();
+    coll.insert_many(
+        (0..5)
+            .map(|n| {
+                doc! { "ts": DateTime::from_millis(now + n * 1000), "sensor": n % 2, "value": n }
+            })
+            .collect::<Vec<_>>(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let timeseries = TimeseriesOptions::builder()
+        .time_field("ts".to_string())
+        .meta_field(Some("sensor".to_string()))
+        .granularity(None)
+        .build();
+    let out = OutStage::builder()
+        .into(target.namespace())
+        .timeseries(timeseries.clone())
+        .build();
+    coll.aggregate(vec![doc! { "$project": { "_id": 0 } }, out.into()], None)
+        .await
+        .unwrap();
+
+    let response = db
+        .run_command(
+            doc! { "listCollections": 1, "filter": { "name": target.name() } },
+            None,
+        )
+        .await
+        .unwrap();
+    let spec = response
+        .get_document("cursor")
+        .and_then(|cursor| cursor.get_array("firstBatch"))
+        .unwrap()[0]
+        .as_document()
+        .unwrap()
+        .clone();
+    assert_eq!(spec.get_str("type"), Ok("timeseries"));
+    assert_eq!(
+        spec.get_document("options")
+            .and_then(|options| options.get_document("timeseries"))
+            .and_then(|timeseries| timeseries.get_str("timeField")),
+        Ok("ts")
+    );
+    assert_eq!(target.count_documents(None, None).await.unwrap(), 5);
+
+    // $out with time series options cannot replace a collection that is not a time series
+    // collection.
+    let regular = client
+        .init_db_and_coll(function_name!(), &format!("{}_regular", function_name!()))
+        .await;
+    regular.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    let out = OutStage::builder()
+        .into(regular.namespace())
+        .timeseries(timeseries)
+        .build();
+    let error = coll
+        .aggregate(vec![doc! { "$project": { "_id": 0 } }, out.into()], None)
+        .await
+        .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::Command(_)), "{:?}", error);
+    assert_eq!(regular.count_documents(None, None).await.unwrap(), 1);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_set_window_fields() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(5, 0) {
+        log_uncaptured("skipping aggregate_set_window_fields due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let day = 24 * 60 * 60 * 1000;
+    coll.insert_many(
+        vec![
+            doc! { "_id": 1, "ts": DateTime::from_millis(0), "value": 2.0 },
+            doc! { "_id": 2, "ts": DateTime::from_millis(day), "value": 4.0 },
+            doc! { "_id": 3, "ts": DateTime::from_millis(2 * day), "value": 6.0 },
+            doc! { "_id": 4, "ts": DateTime::from_millis(5 * day), "value": 8.0 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    // A moving average over the previous document and the current one, and over the last day.
+    let stage = SetWindowFieldsStage::builder()
+        .sort_by(doc! { "ts": 1 })
+        .output(vec![
+            WindowOutput::builder()
+                .field("byDocuments")
+                .operator(doc! { "$avg": "$value" })
+                .window(Window::Documents {
+                    lower: WindowBound::Offset(Bson::Int32(-1)),
+                    upper: WindowBound::Current,
+                })
+                .build(),
+            WindowOutput::builder()
+                .field("byRange")
+                .operator(doc! { "$avg": "$value" })
+                .window(Window::Range {
+                    lower: WindowBound::Offset(Bson::Int32(-1)),
+                    upper: WindowBound::Current,
+                    unit: Some(TimeUnit::Day),
+                })
+                .build(),
+        ])
+        .build();
+    let pipeline = vec![
+        stage.into(),
+        doc! { "$sort": { "_id": 1 } },
+        doc! { "$project": { "_id": 0, "byDocuments": 1, "byRange": 1 } },
+    ];
+
+    let results: Vec<Document> = coll
+        .aggregate(pipeline, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "byDocuments": 2.0, "byRange": 2.0 },
+            doc! { "byDocuments": 3.0, "byRange": 3.0 },
+            doc! { "byDocuments": 5.0, "byRange": 5.0 },
+            doc! { "byDocuments": 7.0, "byRange": 8.0 },
+        ]
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_densify_and_fill() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(5, 1) {
+        log_uncaptured("skipping aggregate_densify_and_fill due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let hour = 60 * 60 * 1000;
+    coll.insert_many(
+        vec![
+            doc! { "sensor": "a", "ts": DateTime::from_millis(0), "value": 1.0 },
+            doc! { "sensor": "a", "ts": DateTime::from_millis(3 * hour), "value": 4.0 },
+            doc! { "sensor": "b", "ts": DateTime::from_millis(0), "value": 10.0 },
+            doc! { "sensor": "b", "ts": DateTime::from_millis(hour), "value": 20.0 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Each sensor's readings are densified separately, so only sensor "a" has gaps to fill.
+    let densify = DensifyStage::builder()
+        .field("ts")
+        .partition_by_fields(vec!["sensor".to_string()])
+        .step(Bson::Int32(1))
+        .unit(TimeUnit::Hour)
+        .bounds(DensifyBounds::Partition)
+        .build();
+    let pipeline = vec![
+        densify.clone().into(),
+        doc! { "$sort": { "sensor": 1, "ts": 1 } },
+        doc! { "$project": { "_id": 0 } },
+    ];
+    let results: Vec<Document> = coll
+        .aggregate(pipeline, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "sensor": "a", "ts": DateTime::from_millis(0), "value": 1.0 },
+            doc! { "sensor": "a", "ts": DateTime::from_millis(hour) },
+            doc! { "sensor": "a", "ts": DateTime::from_millis(2 * hour) },
+            doc! { "sensor": "a", "ts": DateTime::from_millis(3 * hour), "value": 4.0 },
+            doc! { "sensor": "b", "ts": DateTime::from_millis(0), "value": 10.0 },
+            doc! { "sensor": "b", "ts": DateTime::from_millis(hour), "value": 20.0 },
+        ]
+    );
+
+    if client.server_version_lt(5, 3) {
+        log_uncaptured("skipping $fill part of aggregate_densify_and_fill");
+        return;
+    }
+
+    // The generated documents' values are interpolated from the surrounding readings.
+    let fill = FillStage::builder()
+        .partition_by_fields(vec!["sensor".to_string()])
+        .sort_by(doc! { "ts": 1 })
+        .output(vec![FillOutput::builder()
+            .field("value")
+            .method(FillMethod::Linear)
+            .build()])
+        .build();
+    let pipeline = vec![
+        densify.into(),
+        fill.into(),
+        doc! { "$match": { "sensor": "a" } },
+        doc! { "$sort": { "ts": 1 } },
+        doc! { "$project": { "_id": 0, "value": 1 } },
+    ];
+    let results: Vec<Document> = coll
+        .aggregate(pipeline, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "value": 1.0 },
+            doc! { "value": 2.0 },
+            doc! { "value": 3.0 },
+            doc! { "value": 4.0 },
+        ]
+    );
+}
+
+/// Requires `MONGO_ATLAS_VECTOR_SEARCH_URI` to point to an Atlas cluster with an Atlas Vector
+/// Search index named "vector_index" on `vector_search.items`, indexing "embedding" as a
+/// 2-dimensional euclidean vector field and "category" as a filter field.
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn aggregate_vector_search() {
+    let uri = match std::env::var("MONGO_ATLAS_VECTOR_SEARCH_URI") {
+        Ok(uri) => uri,
+        Err(_) => {
+            log_uncaptured(
+                "skipping aggregate_vector_search due to undefined environment variable \
+                 MONGO_ATLAS_VECTOR_SEARCH_URI",
+            );
+            return;
+        }
+    };
+
+    #[derive(Debug, Deserialize)]
+    struct ScoredItem {
+        name: String,
+        score: f64,
+    }
+
+    let client = Client::with_uri_str(uri).await.unwrap();
+    // Dropping the collection would also drop its search index, so only its contents are reset.
+    let coll = client
+        .database("vector_search")
+        .collection::<Document>("items");
+    coll.delete_many(doc! {}, None).await.unwrap();
+    coll.insert_many(
+        vec![
+            doc! { "name": "a", "category": "x", "embedding": [1.0, 0.0] },
+            doc! { "name": "b", "category": "y", "embedding": [2.0, 0.0] },
+            doc! { "name": "c", "category": "x", "embedding": [3.5, 0.0] },
+            doc! { "name": "d", "category": "x", "embedding": [9.0, 0.0] },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let stage = VectorSearchStage::builder()
+        .index("vector_index")
+        .path("embedding")
+        .query_vector(vec![2.0, 0.0])
+        .num_candidates(10u32)
+        .limit(3u32)
+        .filter(doc! { "category": "x" })
+        .build();
+    let pipeline = vec![
+        stage.into(),
+        VectorSearchStage::score_stage("score"),
+        doc! { "$project": { "_id": 0, "name": 1, "score": 1 } },
+    ];
+
+    // Search indexes are updated asynchronously, so wait for the inserted documents to appear.
+    let mut results = Vec::new();
+    for _ in 0..60 {
+        results = coll
+            .aggregate(pipeline.clone(), None)
+            .await
+            .unwrap()
+            .with_type::<ScoredItem>()
+            .try_collect()
+            .await
+            .unwrap();
+        if results.len() == 3 {
+            break;
+        }
+        runtime::delay_for(Duration::from_secs(1)).await;
+    }
+
+    // "b" is excluded by the filter even though it is the nearest neighbor.
+    let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "c", "d"]);
+    assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_one() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Total {
+        total: i32,
+        count: i32,
+    }
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let pipeline = vec![doc! {
+        "$group": {
+            "_id": Bson::Null,
+            "total": { "$sum": "$x" },
+            "count": { "$sum": 1 },
+        }
+    }];
+
+    // An empty collection produces no groups.
+    let result: Option<Total> = coll.aggregate_one(pipeline.clone(), None).await.unwrap();
+    assert!(result.is_none());
+
+    coll.insert_many(
+        (1i32..=4).map(|x| doc! { "x": x }).collect::<Vec<_>>(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let result: Option<Total> = coll.aggregate_one(pipeline, None).await.unwrap();
+    assert_eq!(
+        result,
+        Some(Total {
+            total: 10,
+            count: 4
+        })
+    );
+
+    // Only the first of multiple results is returned.
+    let pipeline = vec![
+        doc! { "$sort": { "x": -1 } },
+        doc! { "$project": { "_id": 0 } },
+    ];
+    let result: Option<Document> = coll.aggregate_one(pipeline, None).await.unwrap();
+    assert_eq!(result, Some(doc! { "x": 4 }));
+}
+
 fn kill_cursors_sent(client: &EventClient) -> bool {
     !client
         .get_command_started_events(&["killCursors"])
@@ -417,787 +1049,3558 @@ async fn large_insert() {
     let coll = client
         .init_db_and_coll(function_name!(), function_name!())
         .await;
-    assert_eq!(
-        coll.insert_many(docs, None)
-            .await
-            .unwrap()
-            .inserted_ids
-            .len(),
-        35000
-    );
+    let result = coll.insert_many(docs, None).await.unwrap();
+    assert_eq!(result.inserted_ids.len(), 35000);
+
+    // The insert is split into multiple commands, each of which has its own reply.
+    assert!(result.raw_replies.len() > 1);
+    let mut n = 0;
+    for reply in &result.raw_replies {
+        assert_eq!(reply.get_f64("ok"), Ok(1.0));
+        n += reply.get_i32("n").unwrap();
+    }
+    assert_eq!(n, 35000);
 }
 
-/// Returns a vector of documents that cannot be sent in one batch (35000 documents).
-/// Includes duplicate _id's across different batches.
-fn multibatch_documents_with_duplicate_keys() -> Vec<Document> {
-    let large_doc = LARGE_DOC.clone();
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn validate() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let mut docs: Vec<Document> = Vec::new();
-    docs.extend(vec![large_doc.clone(); 7498]);
+    if *SERVERLESS {
+        log_uncaptured("skipping validate due to serverless not supporting the validate command");
+        return;
+    }
 
-    docs.push(doc! { "_id": 1 });
-    docs.push(doc! { "_id": 1 }); // error in first batch, index 7499
-
-    docs.extend(vec![large_doc.clone(); 14999]);
-    docs.push(doc! { "_id": 1 }); // error in second batch, index 22499
-
-    docs.extend(vec![large_doc.clone(); 9999]);
-    docs.push(doc! { "_id": 1 }); // error in third batch, index 32499
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many((0..10).map(|i| doc! { "x": i }), None)
+        .await
+        .unwrap();
 
-    docs.extend(vec![large_doc; 2500]);
+    let result = coll.validate(None).await.unwrap();
+    assert!(result.valid, "{:?}", result);
+    assert!(result.errors.is_empty());
+    if !client.is_sharded() {
+        assert_eq!(result.n_records, Some(10));
+        assert_eq!(result.n_indexes, Some(1));
+    }
 
-    assert_eq!(docs.len(), 35000);
-    docs
+    // Background validation is only supported by WiredTiger on 4.4+.
+    let status = client
+        .database("admin")
+        .run_command(doc! { "serverStatus": 1 }, None)
+        .await
+        .unwrap();
+    let wired_tiger = status
+        .get_document("storageEngine")
+        .and_then(|engine| engine.get_str("name"))
+        .map_or(false, |name| name == "wiredTiger");
+    if client.server_version_gte(4, 4) && wired_tiger {
+        let options = ValidateOptions::builder().background(true).build();
+        let result = coll.validate(options).await.unwrap();
+        assert!(result.valid, "{:?}", result);
+    }
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn large_insert_unordered_with_errors() {
-    if std::env::consts::OS != "linux" {
-        log_uncaptured("skipping large_insert_unordered_with_errors due to unsupported OS");
-        return;
-    }
-
+async fn compact() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let docs = multibatch_documents_with_duplicate_keys();
-
     let client = TestClient::new().await;
+    if !client.is_standalone() {
+        log_uncaptured("skipping compact due to test configuration");
+        return;
+    }
     let coll = client
         .init_db_and_coll(function_name!(), function_name!())
         .await;
-    let options = InsertManyOptions::builder().ordered(false).build();
-
-    match *coll
-        .insert_many(docs, options)
+    coll.insert_many((0..100).map(|i| doc! { "x": i }), None)
         .await
-        .expect_err("should get error")
-        .kind
-    {
-        ErrorKind::BulkWrite(ref failure) => {
-            let mut write_errors = failure
-                .write_errors
-                .clone()
-                .expect("should have write errors");
-            assert_eq!(write_errors.len(), 3);
-            write_errors.sort_by(|lhs, rhs| lhs.index.cmp(&rhs.index));
+        .unwrap();
+    coll.delete_many(doc! { "x": { "$lt": 50 } }, None)
+        .await
+        .unwrap();
 
-            assert_eq!(write_errors[0].index, 7499);
-            assert_eq!(write_errors[1].index, 22499);
-            assert_eq!(write_errors[2].index, 32499);
-        }
-        e => panic!("expected bulk write error, got {:?} instead", e),
+    let bytes_freed = coll.compact(None).await.unwrap();
+    if client.server_version_gte(4, 4) {
+        assert!(bytes_freed.is_some());
     }
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn large_insert_ordered_with_errors() {
-    if std::env::consts::OS != "linux" {
-        log_uncaptured("skipping large_insert_ordered_with_errors due to unsupported OS");
-        return;
-    }
-
+async fn reindex() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let docs = multibatch_documents_with_duplicate_keys();
-
     let client = TestClient::new().await;
     let coll = client
         .init_db_and_coll(function_name!(), function_name!())
         .await;
-    let options = InsertManyOptions::builder().ordered(true).build();
+    coll.insert_many(
+        (0..100).map(|i| doc! { "x": i, "text": format!("document number {}", i) }),
+        None,
+    )
+    .await
+    .unwrap();
+    coll.create_indexes(
+        vec![
+            IndexModel::builder().keys(doc! { "x": 1 }).build(),
+            IndexModel::builder().keys(doc! { "text": "text" }).build(),
+        ],
+        None,
+    )
+    .await
+    .unwrap();
 
-    match *coll
-        .insert_many(docs, options)
-        .await
-        .expect_err("should get error")
-        .kind
-    {
-        ErrorKind::BulkWrite(ref failure) => {
-            let write_errors = failure
-                .write_errors
-                .clone()
-                .expect("should have write errors");
-            assert_eq!(write_errors.len(), 1);
-            assert_eq!(write_errors[0].index, 7499);
-            assert_eq!(
-                coll.count_documents(None, None)
-                    .await
-                    .expect("count should succeed"),
-                7499
-            );
-        }
-        e => panic!("expected bulk write error, got {:?} instead", e),
+    if !client.is_standalone() {
+        let error = coll.reindex(None).await.unwrap_err();
+        assert!(
+            matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+            "{:?}",
+            error
+        );
+        return;
     }
+
+    // The _id index, the x index and the text index.
+    assert_eq!(coll.reindex(None).await.unwrap(), 3);
+    let results: Vec<_> = coll
+        .find(doc! { "$text": { "$search": "number" } }, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 100);
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn empty_insert() {
+async fn update_many_multi() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let client = TestClient::new().await;
+    let client = EventClient::new().await;
     let coll = client
-        .database(function_name!())
-        .collection::<Document>(function_name!());
-    match *coll
-        .insert_many(Vec::<Document>::new(), None)
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many((0..10).map(|i| doc! { "x": i }), None)
         .await
-        .expect_err("should get error")
-        .kind
-    {
-        ErrorKind::InvalidArgument { .. } => {}
-        e => panic!("expected argument error, got {:?}", e),
-    };
-}
+        .unwrap();
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-async fn find_allow_disk_use() {
-    let find_opts = FindOptions::builder().allow_disk_use(true).build();
-    allow_disk_use_test(find_opts, Some(true)).await;
-}
+    let updates = vec![
+        (doc! { "x": { "$lt": 3 } }, doc! { "$set": { "a": 1 } }),
+        // matches no documents
+        (doc! { "x": { "$gte": 100 } }, doc! { "$set": { "a": 1 } }),
+        // only modifies the document with x = 3, as the others were modified by the first update
+        (doc! { "x": { "$lte": 3 } }, doc! { "$set": { "a": 1 } }),
+    ];
+    let result = coll.update_many_multi(updates, None).await.unwrap();
+    assert_eq!(result.matched_count, 7);
+    assert_eq!(result.modified_count, 4);
+    assert_eq!(result.upserted_id, None);
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-async fn find_do_not_allow_disk_use() {
-    let find_opts = FindOptions::builder().allow_disk_use(false).build();
-    allow_disk_use_test(find_opts, Some(false)).await;
+    assert_eq!(
+        coll.count_documents(doc! { "a": 1 }, None).await.unwrap(),
+        4
+    );
+    assert_eq!(client.get_command_started_events(&["update"]).len(), 1);
+
+    let no_updates: Vec<(Document, Document)> = Vec::new();
+    let err = coll.update_many_multi(no_updates, None).await.unwrap_err();
+    assert!(matches!(*err.kind, ErrorKind::InvalidArgument { .. }));
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
-async fn find_allow_disk_use_not_specified() {
-    let find_opts = FindOptions::builder().build();
-    allow_disk_use_test(find_opts, None).await;
-}
-
 #[function_name::named]
-async fn allow_disk_use_test(options: FindOptions, expected_value: Option<bool>) {
+async fn bulk_upsert() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let event_client = EventClient::new().await;
-    if event_client.server_version_lt(4, 3) {
-        log_uncaptured("skipping allow_disk_use_test due to server version < 4.3");
-        return;
-    }
-    let coll = event_client
-        .database(function_name!())
-        .collection::<Document>(function_name!());
-    coll.find(None, options).await.unwrap();
+    let client = EventClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many(
+        vec![
+            doc! { "sku": "a", "region": "eu", "qty": 1, "kept": true },
+            doc! { "sku": "b", "region": "eu", "qty": 2 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let docs = vec![
+        doc! { "sku": "a", "region": "eu", "qty": 10 },
+        doc! { "sku": "a", "region": "us", "qty": 20 },
+        // unchanged
+        doc! { "sku": "b", "region": "eu", "qty": 2 },
+        doc! { "_id": 7, "sku": "c", "region": "eu", "qty": 30 },
+        // same key as the second document, so it's applied on top of it
+        doc! { "sku": "a", "region": "us", "qty": 21 },
+    ];
+    let result = coll
+        .bulk_upsert(docs.clone(), ["sku", "region"], None)
+        .await
+        .unwrap();
+    assert_eq!(result.matched_count, 3);
+    assert_eq!(result.modified_count, 2);
+    assert_eq!(result.upserted_ids.len(), 2);
+    assert_eq!(result.upserted_ids.get(&3), Some(&Bson::Int32(7)));
+    assert!(result.upserted_ids.contains_key(&1));
+    assert_eq!(client.get_command_started_events(&["update"]).len(), 1);
+
+    let results: Vec<Document> = coll
+        .find(
+            None,
+            FindOptions::builder()
+                .projection(doc! { "_id": 0 })
+                .sort(doc! { "sku": 1, "region": 1 })
+                .build(),
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "sku": "a", "region": "eu", "qty": 10, "kept": true },
+            doc! { "sku": "a", "region": "us", "qty": 21 },
+            doc! { "sku": "b", "region": "eu", "qty": 2 },
+            doc! { "sku": "c", "region": "eu", "qty": 30 },
+        ]
+    );
 
-    let events = event_client.get_command_started_events(&["find"]);
-    assert_eq!(events.len(), 1);
+    // Unordered upserts reject duplicate keys before writing anything.
+    let options = BulkUpsertOptions::builder().ordered(false).build();
+    let error = coll
+        .bulk_upsert(docs, ["sku", "region"], options.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 4);
 
-    let allow_disk_use = events[0].command.get_bool("allowDiskUse").ok();
-    assert_eq!(allow_disk_use, expected_value);
+    let result = coll
+        .bulk_upsert(
+            vec![
+                doc! { "sku": "a", "region": "us", "qty": 22 },
+                doc! { "sku": "d", "region": "eu", "qty": 40 },
+            ],
+            ["sku", "region"],
+            options,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.matched_count, 1);
+    assert_eq!(result.modified_count, 1);
+    assert_eq!(result.upserted_ids.keys().collect::<Vec<_>>(), vec![&1]);
+
+    let error = coll
+        .bulk_upsert(vec![doc! { "sku": "e" }], ["sku", "region"], None)
+        .await
+        .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn ns_not_found_suppression() {
+async fn bulk_upsert_embedded_keys() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
     let client = TestClient::new().await;
-    let coll = client.get_coll(function_name!(), function_name!());
-    coll.drop(None).await.expect("drop should not fail");
-    coll.drop(None).await.expect("drop should not fail");
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(
+        doc! { "item": { "sku": "a", "size": 1 }, "loc": { "region": "eu" }, "qty": 1 },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let result = coll
+        .bulk_upsert(
+            vec![
+                doc! { "item": { "sku": "a", "size": 1 }, "loc": { "region": "eu" }, "qty": 2 },
+                doc! { "item": { "sku": "a", "size": 1 }, "loc": { "region": "us" }, "qty": 3 },
+            ],
+            ["item", "loc.region"],
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.matched_count, 1);
+    assert_eq!(result.modified_count, 1);
+    assert_eq!(result.upserted_ids.keys().collect::<Vec<_>>(), vec![&1]);
+    assert_eq!(
+        coll.count_documents(doc! { "loc.region": "eu", "qty": 2 }, None)
+            .await
+            .unwrap(),
+        1
+    );
 }
 
-async fn delete_hint_test(options: Option<DeleteOptions>, name: &str) {
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn bulk_upsert_write_errors() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let client = EventClient::new().await;
-    let coll = client.database(name).collection::<Document>(name);
-    let _: Result<DeleteResult> = coll.delete_many(doc! {}, options.clone()).await;
-
-    let events = client.get_command_started_events(&["delete"]);
-    assert_eq!(events.len(), 1);
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.create_index(
+        IndexModel::builder()
+            .keys(doc! { "code": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        None,
+    )
+    .await
+    .unwrap();
+    coll.insert_one(doc! { "sku": "a", "code": 1 }, None)
+        .await
+        .unwrap();
 
-    let event_hint = events[0].command.get_array("deletes").unwrap()[0]
-        .as_document()
-        .unwrap()
-        .get("hint");
-    let expected_hint = match options {
-        Some(options) => options.hint.map(|hint| hint.to_bson()),
-        None => None,
+    let failed_indexes = |error: crate::error::Error| -> Vec<usize> {
+        match *error.kind {
+            ErrorKind::BulkWrite(failure) => failure
+                .write_errors
+                .unwrap()
+                .into_iter()
+                .map(|e| e.index)
+                .collect(),
+            other => panic!("expected bulk write error, got {:?}", other),
+        }
     };
-    assert_eq!(event_hint, expected_hint.as_ref());
-}
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-#[function_name::named]
-async fn delete_hint_keys_specified() {
-    let options = DeleteOptions::builder().hint(Hint::Keys(doc! {})).build();
-    delete_hint_test(Some(options), function_name!()).await;
-}
+    // Ordered upserts stop at the first failure.
+    let error = coll
+        .bulk_upsert(
+            vec![
+                doc! { "sku": "b", "code": 2 },
+                doc! { "sku": "c", "code": 1 },
+                doc! { "sku": "d", "code": 3 },
+            ],
+            ["sku"],
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(failed_indexes(error), vec![1]);
+    assert_eq!(
+        coll.count_documents(doc! { "sku": "b" }, None)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        coll.count_documents(doc! { "sku": "d" }, None)
+            .await
+            .unwrap(),
+        0
+    );
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-#[function_name::named]
-async fn delete_hint_string_specified() {
-    let options = DeleteOptions::builder()
-        .hint(Hint::Name(String::new()))
-        .build();
-    delete_hint_test(Some(options), function_name!()).await;
+    // Unordered upserts continue past failures.
+    let error = coll
+        .bulk_upsert(
+            vec![
+                doc! { "sku": "e", "code": 1 },
+                doc! { "sku": "f", "code": 4 },
+                doc! { "sku": "g", "code": 2 },
+            ],
+            ["sku"],
+            BulkUpsertOptions::builder().ordered(false).build(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(failed_indexes(error), vec![0, 2]);
+    assert_eq!(
+        coll.count_documents(doc! { "sku": "f" }, None)
+            .await
+            .unwrap(),
+        1
+    );
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn delete_hint_not_specified() {
-    delete_hint_test(None, function_name!()).await;
-}
-
-async fn find_one_and_delete_hint_test(options: Option<FindOneAndDeleteOptions>, name: &str) {
+async fn find_many_and_delete() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
-    let client = EventClient::new().await;
-
-    let req = VersionReq::parse(">= 4.2").unwrap();
-    if options.is_some() && !req.matches(&client.server_version) {
-        log_uncaptured("skipping find_one_and_delete_hint_test due to test configuration");
-        return;
-    }
 
-    let coll = client.database(name).collection(name);
-    let _: Result<Option<Document>> = coll.find_one_and_delete(doc! {}, options.clone()).await;
-
-    let events = client.get_command_started_events(&["findAndModify"]);
-    assert_eq!(events.len(), 1);
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many((0..10).map(|i| doc! { "x": i }), None)
+        .await
+        .unwrap();
 
-    let event_hint = events[0].command.get("hint").cloned();
-    let expected_hint = options.and_then(|options| options.hint.map(|hint| hint.to_bson()));
-    assert_eq!(event_hint, expected_hint);
-}
+    let xs = |docs: Vec<Document>| -> Vec<i32> {
+        docs.iter().map(|doc| doc.get_i32("x").unwrap()).collect()
+    };
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-#[function_name::named]
-async fn find_one_and_delete_hint_keys_specified() {
-    let options = FindOneAndDeleteOptions::builder()
-        .hint(Hint::Keys(doc! {}))
+    let options = FindManyAndDeleteOptions::builder()
+        .limit(4)
+        .sort(doc! { "x": 1 })
         .build();
-    find_one_and_delete_hint_test(Some(options), function_name!()).await;
-}
+    let deleted = coll
+        .find_many_and_delete(doc! { "x": { "$gte": 3 } }, options)
+        .await
+        .unwrap();
+    assert_eq!(xs(deleted), vec![3, 4, 5, 6]);
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 6);
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-#[function_name::named]
-async fn find_one_and_delete_hint_string_specified() {
-    let options = FindOneAndDeleteOptions::builder()
-        .hint(Hint::Name(String::new()))
+    let options = FindManyAndDeleteOptions::builder()
+        .sort(doc! { "x": -1 })
         .build();
-    find_one_and_delete_hint_test(Some(options), function_name!()).await;
-}
+    let deleted = coll
+        .find_many_and_delete(doc! { "x": { "$gte": 3 } }, options)
+        .await
+        .unwrap();
+    assert_eq!(xs(deleted), vec![9, 8, 7]);
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-#[function_name::named]
-async fn find_one_and_delete_hint_not_specified() {
-    find_one_and_delete_hint_test(None, function_name!()).await;
+    let deleted = coll
+        .find_many_and_delete(doc! { "x": { "$gte": 3 } }, None)
+        .await
+        .unwrap();
+    assert!(deleted.is_empty());
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 3);
 }
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))] // multi_thread required for FailPoint
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn find_one_and_delete_hint_server_version() {
-    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+async fn find_many_and_delete_partial_failure() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
 
-    let client = EventClient::new().await;
+    let client = TestClient::new().await;
+    if !client.supports_fail_command() {
+        log_uncaptured(
+            "skipping find_many_and_delete_partial_failure due to failCommand not being supported",
+        );
+        return;
+    }
     let coll = client
-        .database(function_name!())
-        .collection::<Document>("coll");
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many((0..5).map(|i| doc! { "x": i }), None)
+        .await
+        .unwrap();
 
-    let options = FindOneAndDeleteOptions::builder()
-        .hint(Hint::Name(String::new()))
+    // The first two deletes succeed and every later one fails with a non-retryable error.
+    let _fp_guard = FailPoint::fail_command(
+        &["findAndModify"],
+        FailPointMode::Skip(2),
+        FailCommandOptions::builder().error_code(2).build(),
+    )
+    .enable(&client, None)
+    .await
+    .unwrap();
+
+    let options = FindManyAndDeleteOptions::builder()
+        .sort(doc! { "x": 1 })
         .build();
-    let res = coll.find_one_and_delete(doc! {}, options).await;
-
-    let req1 = VersionReq::parse("< 4.2").unwrap();
-    let req2 = VersionReq::parse("4.2.*").unwrap();
-    if req1.matches(&client.server_version) {
-        let error = res.expect_err("find one and delete should fail");
-        assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
-    } else if req2.matches(&client.server_version) {
-        let error = res.expect_err("find one and delete should fail");
-        assert!(matches!(*error.kind, ErrorKind::Command { .. }));
-    } else {
-        assert!(res.is_ok());
-    }
+    let error = coll
+        .find_many_and_delete(doc! {}, options)
+        .await
+        .expect_err("third delete should fail");
+    let xs: Vec<i32> = error
+        .deleted
+        .iter()
+        .map(|doc| doc.get_i32("x").unwrap())
+        .collect();
+    assert_eq!(xs, vec![0, 1]);
+    assert!(matches!(*error.error.kind, ErrorKind::Command(ref e) if e.code == 2));
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn no_read_preference_to_standalone() {
+async fn find_deny_unknown_fields() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let client = EventClient::new().await;
-
-    if !client.is_standalone() {
-        log_uncaptured("skipping no_read_preference_to_standalone due to test topology");
-        return;
+    #[derive(Debug, Deserialize)]
+    struct Inner {
+        a: i32,
     }
 
-    let options = FindOneOptions::builder()
-        .selection_criteria(SelectionCriteria::ReadPreference(
-            ReadPreference::SecondaryPreferred {
-                options: Default::default(),
-            },
-        ))
-        .build();
+    #[derive(Debug, Deserialize)]
+    struct Outer {
+        x: i32,
+        inner: Inner,
+    }
 
-    client
-        .database(function_name!())
-        .collection::<Document>(function_name!())
-        .find_one(None, options)
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(doc! { "_id": 1, "x": 1, "inner": { "a": 2, "b": 3 } }, None)
         .await
         .unwrap();
+    let coll = coll.clone_with_type::<Outer>();
 
-    let command_started = client.get_successful_command_execution("find").0;
+    let found = coll.find_one(None, None).await.unwrap().unwrap();
+    assert_eq!(found.x, 1);
+    assert_eq!(found.inner.a, 2);
 
-    assert!(!command_started.command.contains_key("$readPreference"));
-}
+    let options = FindOneOptions::builder()
+        .projection(doc! { "_id": 0 })
+        .deny_unknown_fields(true)
+        .build();
+    let err = coll.find_one(None, options).await.unwrap_err();
+    assert!(matches!(*err.kind, ErrorKind::BsonDeserialization(_)));
+    assert!(err.to_string().contains("inner.b"));
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
-struct UserType {
-    x: i32,
-    str: String,
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 0, "inner.b": 0 })
+        .deny_unknown_fields(true)
+        .build();
+    let found: Vec<Outer> = coll
+        .find(None, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+
+    let options = FindOptions::builder().deny_unknown_fields(true).build();
+    let mut cursor = coll.find(None, options).await.unwrap();
+    assert!(cursor.advance().await.unwrap());
+    let err = cursor.deserialize_current().unwrap_err();
+    assert!(matches!(*err.kind, ErrorKind::BsonDeserialization(_)));
+    assert!(err.to_string().contains("_id"));
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn typed_insert_one() {
+async fn insert_stream() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
-    let client = TestClient::new().await;
 
+    let client = TestClient::new().await;
     let coll = client
-        .init_db_and_typed_coll(function_name!(), function_name!())
+        .init_db_and_coll(function_name!(), function_name!())
         .await;
-    let insert_data = UserType {
-        x: 1,
-        str: "a".into(),
-    };
-    insert_one_and_find(&coll, insert_data).await;
 
-    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
-    struct OtherType {
-        b: bool,
-        nums: Vec<i32>,
-    }
+    // The documents are produced lazily, so only one batch of them is held in memory at a time.
+    let docs = futures::stream::iter(0..100_000).map(|_| Ok(LARGE_DOC.clone()));
+    let result = coll.insert_stream(docs, None).await.unwrap();
+    assert_eq!(result.inserted_ids.len(), 100_000);
+    assert!(result.inserted_ids.contains_key(&99_999));
+    assert!(result.raw_replies.len() > 1);
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 100_000);
+
+    coll.drop(None).await.unwrap();
+
+    // An error from the stream stops the insert and reports the documents inserted before it.
+    let docs = futures::stream::iter(0..100).map(|i| {
+        if i == 50 {
+            Err(ErrorKind::Internal {
+                message: "stream failed".to_string(),
+            }
+            .into())
+        } else {
+            Ok(doc! { "x": i })
+        }
+    });
+    let err = coll.insert_stream(docs, None).await.unwrap_err();
+    assert!(
+        matches!(*err.error.kind, ErrorKind::Internal { .. }),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.inserted.inserted_ids.len(), 50);
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 50);
 
-    let coll = coll.clone_with_type();
-    let insert_data = OtherType {
-        b: true,
-        nums: vec![1, 2, 3],
-    };
-    insert_one_and_find(&coll, insert_data).await;
-}
+    coll.drop(None).await.unwrap();
 
-async fn insert_one_and_find<T>(coll: &Collection<T>, insert_data: T)
-where
-    T: Serialize + DeserializeOwned + Clone + PartialEq + Debug + Unpin + Send + Sync,
-{
-    coll.insert_one(insert_data.clone(), None).await.unwrap();
-    let result = coll
-        .find_one(to_document(&insert_data).unwrap(), None)
-        .await
-        .unwrap();
-    match result {
-        Some(actual) => {
-            assert_eq!(actual, insert_data);
+    // The documents of an unordered batch that were inserted despite a duplicate key in the
+    // middle of it are reported.
+    let docs = futures::stream::iter(0..10).map(|i| Ok(doc! { "_id": if i == 5 { 0 } else { i } }));
+    let options = InsertManyOptions::builder().ordered(false).build();
+    let err = coll.insert_stream(docs, options).await.unwrap_err();
+    match *err.error.kind {
+        ErrorKind::BulkWrite(ref failure) => {
+            let indexes: Vec<usize> = failure
+                .write_errors
+                .iter()
+                .flatten()
+                .map(|error| error.index)
+                .collect();
+            assert_eq!(indexes, vec![5]);
         }
-        None => panic!("find_one should return a value"),
+        ref other => panic!("expected bulk write error, got {:?}", other),
     }
+    let mut inserted: Vec<usize> = err.inserted.inserted_ids.keys().copied().collect();
+    inserted.sort_unstable();
+    assert_eq!(inserted, vec![0, 1, 2, 3, 4, 6, 7, 8, 9]);
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 9);
 }
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
-#[cfg_attr(feature = "async-std-runtime", async_std::test)]
-#[function_name::named]
-async fn typed_insert_many() {
-    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+/// Returns a vector of documents that cannot be sent in one batch (35000 documents).
+/// Includes duplicate _id's across different batches.
+fn multibatch_documents_with_duplicate_keys() -> Vec<Document> {
+    let large_doc = LARGE_DOC.clone();
 
-    let client = TestClient::new().await;
-    let coll = client
-        .init_db_and_typed_coll(function_name!(), function_name!())
-        .await;
+    let mut docs: Vec<Document> = Vec::new();
+    docs.extend(vec![large_doc.clone(); 7498]);
 
-    let insert_data = vec![
-        UserType {
-            x: 2,
-            str: "a".into(),
-        },
-        UserType {
-            x: 2,
-            str: "b".into(),
-        },
-    ];
-    coll.insert_many(insert_data.clone(), None).await.unwrap();
+    docs.push(doc! { "_id": 1 });
+    docs.push(doc! { "_id": 1 }); // error in first batch, index 7499
 
-    let options = FindOptions::builder().sort(doc! { "x": 1 }).build();
-    let actual: Vec<UserType> = coll
-        .find(doc! { "x": 2 }, options)
-        .await
-        .unwrap()
-        .try_collect()
-        .await
-        .unwrap();
-    assert_eq!(actual, insert_data);
+    docs.extend(vec![large_doc.clone(); 14999]);
+    docs.push(doc! { "_id": 1 }); // error in second batch, index 22499
+
+    docs.extend(vec![large_doc.clone(); 9999]);
+    docs.push(doc! { "_id": 1 }); // error in third batch, index 32499
+
+    docs.extend(vec![large_doc; 2500]);
+
+    assert_eq!(docs.len(), 35000);
+    docs
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn typed_find_one_and_replace() {
+async fn large_insert_unordered_with_errors() {
+    if std::env::consts::OS != "linux" {
+        log_uncaptured("skipping large_insert_unordered_with_errors due to unsupported OS");
+        return;
+    }
+
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
+    let docs = multibatch_documents_with_duplicate_keys();
+
     let client = TestClient::new().await;
     let coll = client
-        .init_db_and_typed_coll(function_name!(), function_name!())
+        .init_db_and_coll(function_name!(), function_name!())
         .await;
+    let options = InsertManyOptions::builder().ordered(false).build();
 
-    let insert_data = UserType {
-        x: 1,
-        str: "a".into(),
-    };
-    coll.insert_one(insert_data.clone(), None).await.unwrap();
-
-    let replacement = UserType {
-        x: 2,
-        str: "b".into(),
-    };
-    let result = coll
-        .find_one_and_replace(doc! { "x": 1 }, replacement.clone(), None)
+    match *coll
+        .insert_many(docs, options)
         .await
-        .unwrap()
-        .unwrap();
-    assert_eq!(result, insert_data);
+        .expect_err("should get error")
+        .kind
+    {
+        ErrorKind::BulkWrite(ref failure) => {
+            let mut write_errors = failure
+                .write_errors
+                .clone()
+                .expect("should have write errors");
+            assert_eq!(write_errors.len(), 3);
+            write_errors.sort_by(|lhs, rhs| lhs.index.cmp(&rhs.index));
 
-    let result = coll.find_one(doc! { "x": 2 }, None).await.unwrap().unwrap();
-    assert_eq!(result, replacement);
+            assert_eq!(write_errors[0].index, 7499);
+            assert_eq!(write_errors[1].index, 22499);
+            assert_eq!(write_errors[2].index, 32499);
+        }
+        e => panic!("expected bulk write error, got {:?} instead", e),
+    }
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn typed_replace_one() {
-    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+async fn large_insert_unordered_with_errors_in_first_and_third_batches() {
+    if std::env::consts::OS != "linux" {
+        log_uncaptured(
+            "skipping large_insert_unordered_with_errors_in_first_and_third_batches due to \
+             unsupported OS",
+        );
+        return;
+    }
 
-    let client = TestClient::new().await;
-    let coll = client
-        .init_db_and_typed_coll(function_name!(), function_name!())
-        .await;
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let insert_data = UserType {
-        x: 1,
-        str: "a".into(),
+    // Same layout as multibatch_documents_with_duplicate_keys, but the second batch succeeds.
+    let large_doc = LARGE_DOC.clone();
+    let mut docs: Vec<Document> = Vec::new();
+    docs.extend(vec![large_doc.clone(); 7498]);
+    docs.push(doc! { "_id": 1 });
+    docs.push(doc! { "_id": 1 }); // error in first batch, index 7499
+    docs.extend(vec![large_doc.clone(); 25000]);
+    docs.push(doc! { "_id": 1 }); // error in third batch, index 32500
+    docs.extend(vec![large_doc; 2499]);
+    assert_eq!(docs.len(), 35000);
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let options = InsertManyOptions::builder().ordered(false).build();
+
+    match *coll
+        .insert_many(docs, options)
+        .await
+        .expect_err("should get error")
+        .kind
+    {
+        ErrorKind::BulkWrite(ref failure) => {
+            let mut indexes: Vec<_> = failure
+                .write_errors
+                .iter()
+                .flatten()
+                .map(|e| e.index)
+                .collect();
+            indexes.sort_unstable();
+            assert_eq!(indexes, vec![7499, 32500]);
+        }
+        e => panic!("expected bulk write error, got {:?} instead", e),
+    }
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 34998);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn default_insert_ordered() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.default_insert_ordered = Some(false);
+    let client = TestClient::with_options(Some(options)).await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let docs = vec![
+        doc! { "_id": 1 },
+        doc! { "_id": 1 },
+        doc! { "_id": 2 },
+        doc! { "_id": 2 },
+        doc! { "_id": 3 },
+    ];
+
+    // Without an explicit `ordered`, the insert continues past the duplicates.
+    match *coll
+        .insert_many(docs.clone(), None)
+        .await
+        .expect_err("should get error")
+        .kind
+    {
+        ErrorKind::BulkWrite(ref failure) => {
+            let mut write_errors = failure
+                .write_errors
+                .clone()
+                .expect("should have write errors");
+            write_errors.sort_by(|lhs, rhs| lhs.index.cmp(&rhs.index));
+            let indexes: Vec<_> = write_errors.iter().map(|e| e.index).collect();
+            assert_eq!(indexes, vec![1, 3]);
+        }
+        e => panic!("expected bulk write error, got {:?} instead", e),
+    }
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 3);
+
+    // An explicit `ordered` takes precedence over the client's default.
+    coll.drop(None).await.unwrap();
+    let options = InsertManyOptions::builder().ordered(true).build();
+    coll.insert_many(docs, options)
+        .await
+        .expect_err("should get error");
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 1);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn large_insert_ordered_with_errors() {
+    if std::env::consts::OS != "linux" {
+        log_uncaptured("skipping large_insert_ordered_with_errors due to unsupported OS");
+        return;
+    }
+
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let docs = multibatch_documents_with_duplicate_keys();
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let options = InsertManyOptions::builder().ordered(true).build();
+
+    match *coll
+        .insert_many(docs, options)
+        .await
+        .expect_err("should get error")
+        .kind
+    {
+        ErrorKind::BulkWrite(ref failure) => {
+            let write_errors = failure
+                .write_errors
+                .clone()
+                .expect("should have write errors");
+            assert_eq!(write_errors.len(), 1);
+            assert_eq!(write_errors[0].index, 7499);
+            assert_eq!(
+                coll.count_documents(None, None)
+                    .await
+                    .expect("count should succeed"),
+                7499
+            );
+        }
+        e => panic!("expected bulk write error, got {:?} instead", e),
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn empty_insert() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .database(function_name!())
+        .collection::<Document>(function_name!());
+    match *coll
+        .insert_many(Vec::<Document>::new(), None)
+        .await
+        .expect_err("should get error")
+        .kind
+    {
+        ErrorKind::InvalidArgument { .. } => {}
+        e => panic!("expected argument error, got {:?}", e),
     };
-    let replacement = UserType {
-        x: 2,
-        str: "b".into(),
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn find_allow_disk_use() {
+    let find_opts = FindOptions::builder().allow_disk_use(true).build();
+    allow_disk_use_test(find_opts, Some(true)).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn find_do_not_allow_disk_use() {
+    let find_opts = FindOptions::builder().allow_disk_use(false).build();
+    allow_disk_use_test(find_opts, Some(false)).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn find_allow_disk_use_not_specified() {
+    let find_opts = FindOptions::builder().build();
+    allow_disk_use_test(find_opts, None).await;
+}
+
+#[function_name::named]
+async fn allow_disk_use_test(options: FindOptions, expected_value: Option<bool>) {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let event_client = EventClient::new().await;
+    if event_client.server_version_lt(4, 3) {
+        log_uncaptured("skipping allow_disk_use_test due to server version < 4.3");
+        return;
+    }
+    let coll = event_client
+        .database(function_name!())
+        .collection::<Document>(function_name!());
+    coll.find(None, options).await.unwrap();
+
+    let events = event_client.get_command_started_events(&["find"]);
+    assert_eq!(events.len(), 1);
+
+    let allow_disk_use = events[0].command.get_bool("allowDiskUse").ok();
+    assert_eq!(allow_disk_use, expected_value);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn aggregate_allow_disk_use_default() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.aggregate_allow_disk_use_default = Some(true);
+    let client = EventClient::with_options(options).await;
+    let coll = client
+        .database(function_name!())
+        .collection::<Document>(function_name!());
+
+    coll.aggregate(vec![doc! { "$sort": { "x": 1 } }], None)
+        .await
+        .unwrap();
+    coll.aggregate(
+        vec![doc! { "$sort": { "x": 1 } }],
+        AggregateOptions::builder().allow_disk_use(false).build(),
+    )
+    .await
+    .unwrap();
+    let events = client.get_command_started_events(&["aggregate"]);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].command.get_bool("allowDiskUse"), Ok(true));
+    assert_eq!(events[1].command.get_bool("allowDiskUse"), Ok(false));
+
+    // Servers older than 4.4 don't accept allowDiskUse on find, so the default isn't sent to them.
+    coll.find(None, None).await.unwrap();
+    let events = client.get_command_started_events(&["find"]);
+    assert_eq!(events.len(), 1);
+    if client.server_version_lt(4, 4) {
+        assert!(!events[0].command.contains_key("allowDiskUse"));
+        return;
+    }
+    assert_eq!(events[0].command.get_bool("allowDiskUse"), Ok(true));
+
+    coll.find(None, FindOptions::builder().allow_disk_use(false).build())
+        .await
+        .unwrap();
+    let events = client.get_command_started_events(&["find"]);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].command.get_bool("allowDiskUse"), Ok(false));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn ns_not_found_suppression() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client.get_coll(function_name!(), function_name!());
+    coll.drop(None).await.expect("drop should not fail");
+    coll.drop(None).await.expect("drop should not fail");
+}
+
+async fn delete_hint_test(options: Option<DeleteOptions>, name: &str) {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    let coll = client.database(name).collection::<Document>(name);
+    let _: Result<DeleteResult> = coll.delete_many(doc! {}, options.clone()).await;
+
+    let events = client.get_command_started_events(&["delete"]);
+    assert_eq!(events.len(), 1);
+
+    let event_hint = events[0].command.get_array("deletes").unwrap()[0]
+        .as_document()
+        .unwrap()
+        .get("hint");
+    let expected_hint = match options {
+        Some(options) => options.hint.map(|hint| hint.to_bson()),
+        None => None,
     };
-    coll.insert_one(insert_data, None).await.unwrap();
-    coll.replace_one(doc! { "x": 1 }, replacement.clone(), None)
+    assert_eq!(event_hint, expected_hint.as_ref());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn delete_hint_keys_specified() {
+    let options = DeleteOptions::builder().hint(Hint::Keys(doc! {})).build();
+    delete_hint_test(Some(options), function_name!()).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn delete_hint_string_specified() {
+    let options = DeleteOptions::builder()
+        .hint(Hint::Name(String::new()))
+        .build();
+    delete_hint_test(Some(options), function_name!()).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn delete_hint_not_specified() {
+    delete_hint_test(None, function_name!()).await;
+}
+
+async fn find_one_and_delete_hint_test(options: Option<FindOneAndDeleteOptions>, name: &str) {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+    let client = EventClient::new().await;
+
+    let req = VersionReq::parse(">= 4.2").unwrap();
+    if options.is_some() && !req.matches(&client.server_version) {
+        log_uncaptured("skipping find_one_and_delete_hint_test due to test configuration");
+        return;
+    }
+
+    let coll = client.database(name).collection(name);
+    let _: Result<Option<Document>> = coll.find_one_and_delete(doc! {}, options.clone()).await;
+
+    let events = client.get_command_started_events(&["findAndModify"]);
+    assert_eq!(events.len(), 1);
+
+    let event_hint = events[0].command.get("hint").cloned();
+    let expected_hint = options.and_then(|options| options.hint.map(|hint| hint.to_bson()));
+    assert_eq!(event_hint, expected_hint);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_one_and_delete_hint_keys_specified() {
+    let options = FindOneAndDeleteOptions::builder()
+        .hint(Hint::Keys(doc! {}))
+        .build();
+    find_one_and_delete_hint_test(Some(options), function_name!()).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_one_and_delete_hint_string_specified() {
+    let options = FindOneAndDeleteOptions::builder()
+        .hint(Hint::Name(String::new()))
+        .build();
+    find_one_and_delete_hint_test(Some(options), function_name!()).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_one_and_delete_hint_not_specified() {
+    find_one_and_delete_hint_test(None, function_name!()).await;
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_one_and_delete_hint_server_version() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    let coll = client
+        .database(function_name!())
+        .collection::<Document>("coll");
+
+    let options = FindOneAndDeleteOptions::builder()
+        .hint(Hint::Name(String::new()))
+        .build();
+    let res = coll.find_one_and_delete(doc! {}, options).await;
+
+    let req1 = VersionReq::parse("< 4.2").unwrap();
+    let req2 = VersionReq::parse("4.2.*").unwrap();
+    if req1.matches(&client.server_version) {
+        let error = res.expect_err("find one and delete should fail");
+        assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+    } else if req2.matches(&client.server_version) {
+        let error = res.expect_err("find one and delete should fail");
+        assert!(matches!(*error.kind, ErrorKind::Command { .. }));
+    } else {
+        assert!(res.is_ok());
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn no_read_preference_to_standalone() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+
+    if !client.is_standalone() {
+        log_uncaptured("skipping no_read_preference_to_standalone due to test topology");
+        return;
+    }
+
+    let options = FindOneOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(
+            ReadPreference::SecondaryPreferred {
+                options: Default::default(),
+            },
+        ))
+        .build();
+
+    client
+        .database(function_name!())
+        .collection::<Document>(function_name!())
+        .find_one(None, options)
+        .await
+        .unwrap();
+
+    let command_started = client.get_successful_command_execution("find").0;
+
+    assert!(!command_started.command.contains_key("$readPreference"));
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+struct UserType {
+    x: i32,
+    str: String,
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn typed_insert_one() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+    let client = TestClient::new().await;
+
+    let coll = client
+        .init_db_and_typed_coll(function_name!(), function_name!())
+        .await;
+    let insert_data = UserType {
+        x: 1,
+        str: "a".into(),
+    };
+    insert_one_and_find(&coll, insert_data).await;
+
+    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+    struct OtherType {
+        b: bool,
+        nums: Vec<i32>,
+    }
+
+    let coll = coll.clone_with_type();
+    let insert_data = OtherType {
+        b: true,
+        nums: vec![1, 2, 3],
+    };
+    insert_one_and_find(&coll, insert_data).await;
+}
+
+async fn insert_one_and_find<T>(coll: &Collection<T>, insert_data: T)
+where
+    T: Serialize + DeserializeOwned + Clone + PartialEq + Debug + Unpin + Send + Sync,
+{
+    coll.insert_one(insert_data.clone(), None).await.unwrap();
+    let result = coll
+        .find_one(to_document(&insert_data).unwrap(), None)
+        .await
+        .unwrap();
+    match result {
+        Some(actual) => {
+            assert_eq!(actual, insert_data);
+        }
+        None => panic!("find_one should return a value"),
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn typed_insert_many() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_typed_coll(function_name!(), function_name!())
+        .await;
+
+    let insert_data = vec![
+        UserType {
+            x: 2,
+            str: "a".into(),
+        },
+        UserType {
+            x: 2,
+            str: "b".into(),
+        },
+    ];
+    coll.insert_many(insert_data.clone(), None).await.unwrap();
+
+    let options = FindOptions::builder().sort(doc! { "x": 1 }).build();
+    let actual: Vec<UserType> = coll
+        .find(doc! { "x": 2 }, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(actual, insert_data);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn typed_find_one_and_replace() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_typed_coll(function_name!(), function_name!())
+        .await;
+
+    let insert_data = UserType {
+        x: 1,
+        str: "a".into(),
+    };
+    coll.insert_one(insert_data.clone(), None).await.unwrap();
+
+    let replacement = UserType {
+        x: 2,
+        str: "b".into(),
+    };
+    let result = coll
+        .find_one_and_replace(doc! { "x": 1 }, replacement.clone(), None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, insert_data);
+
+    let result = coll.find_one(doc! { "x": 2 }, None).await.unwrap().unwrap();
+    assert_eq!(result, replacement);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn typed_replace_one() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_typed_coll(function_name!(), function_name!())
+        .await;
+
+    let insert_data = UserType {
+        x: 1,
+        str: "a".into(),
+    };
+    let replacement = UserType {
+        x: 2,
+        str: "b".into(),
+    };
+    coll.insert_one(insert_data, None).await.unwrap();
+    coll.replace_one(doc! { "x": 1 }, replacement.clone(), None)
+        .await
+        .unwrap();
+
+    let result = coll.find_one(doc! { "x": 2 }, None).await.unwrap().unwrap();
+    assert_eq!(result, replacement);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn typed_returns() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_typed_coll(function_name!(), function_name!())
+        .await;
+
+    let insert_data = UserType {
+        x: 1,
+        str: "a".into(),
+    };
+    coll.insert_one(insert_data.clone(), None).await.unwrap();
+
+    let result = coll
+        .find_one_and_update(doc! { "x": 1 }, doc! { "$inc": { "x": 1 } }, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, insert_data);
+
+    let result = coll
+        .find_one_and_delete(doc! { "x": 2 }, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        result,
+        UserType {
+            x: 2,
+            str: "a".into()
+        }
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn count_documents_with_wc() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.write_concern = WriteConcern::builder()
+        .w(Acknowledgment::Majority)
+        .journal(true)
+        .build()
+        .into();
+
+    let client = TestClient::with_options(Some(options)).await;
+    let coll = client
+        .database(function_name!())
+        .collection(function_name!());
+
+    coll.insert_one(doc! {}, None).await.unwrap();
+
+    coll.count_documents(doc! {}, None)
+        .await
+        .expect("count_documents should succeed");
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn collection_options_inherited() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+
+    let read_concern = ReadConcern::majority();
+    let selection_criteria = SelectionCriteria::ReadPreference(ReadPreference::Secondary {
+        options: Default::default(),
+    });
+
+    let options = CollectionOptions::builder()
+        .read_concern(read_concern)
+        .selection_criteria(selection_criteria)
+        .build();
+    let coll = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+
+    coll.find(None, None).await.unwrap();
+    assert_options_inherited(&client, "find").await;
+
+    coll.find_one(None, None).await.unwrap();
+    assert_options_inherited(&client, "find").await;
+
+    coll.count_documents(None, None).await.unwrap();
+    assert_options_inherited(&client, "aggregate").await;
+}
+
+async fn assert_options_inherited(client: &EventClient, command_name: &str) {
+    let events = client.get_command_started_events(&[command_name]);
+    let event = events.iter().last().unwrap();
+    assert!(event.command.contains_key("readConcern"));
+    assert_eq!(
+        event.command.contains_key("$readPreference"),
+        !client.is_standalone()
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn with_read_preference() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    if !client.is_replica_set() {
+        log_uncaptured("skipping with_read_preference due to non-replica set topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+
+    let secondary = coll.with_read_preference(ReadPreference::Secondary {
+        options: Default::default(),
+    });
+    assert_eq!(secondary.name(), coll.name());
+
+    secondary.find_one(None, None).await.unwrap();
+    let event = client.get_command_started_events(&["find"]).pop().unwrap();
+    assert_eq!(
+        event.command.get_document("$readPreference").unwrap(),
+        &doc! { "mode": "secondary" }
+    );
+    let hello = client.hello(event.connection.address).await.unwrap();
+    assert!(!hello.is_writable_primary);
+
+    // The original handle is unaffected.
+    coll.find_one(None, None).await.unwrap();
+    let event = client.get_command_started_events(&["find"]).pop().unwrap();
+    assert_eq!(
+        event.command.get_document("$readPreference").unwrap(),
+        &doc! { "mode": "primary" }
+    );
+    let hello = client.hello(event.connection.address).await.unwrap();
+    assert!(hello.is_writable_primary);
+
+    // Inside a transaction, the transaction's read preference takes precedence.
+    if client.supports_transactions() {
+        let mut session = client.start_session(None).await.unwrap();
+        session.start_transaction(None).await.unwrap();
+        secondary
+            .find_one_with_session(None, None, &mut session)
+            .await
+            .unwrap();
+        session.commit_transaction().await.unwrap();
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn with_write_concern() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let majority =
+        coll.with_write_concern(WriteConcern::builder().w(Acknowledgment::Majority).build());
+    majority.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    let event = client
+        .get_command_started_events(&["insert"])
+        .pop()
+        .unwrap();
+    assert_eq!(
+        event.command.get_document("writeConcern").unwrap(),
+        &doc! { "w": "majority" }
+    );
+
+    // The original handle is unaffected.
+    coll.insert_one(doc! { "x": 2 }, None).await.unwrap();
+    let event = client
+        .get_command_started_events(&["insert"])
+        .pop()
+        .unwrap();
+    assert!(!event.command.contains_key("writeConcern"));
+
+    if client.is_standalone() {
+        return;
+    }
+
+    // Unacknowledged writes are not retryable, so no transaction number is sent.
+    let unacknowledged =
+        coll.with_write_concern(WriteConcern::builder().w(Acknowledgment::Nodes(0)).build());
+    unacknowledged
+        .insert_one(doc! { "x": 3 }, None)
+        .await
+        .unwrap();
+    let event = client
+        .get_command_started_events(&["insert"])
+        .pop()
+        .unwrap();
+    assert_eq!(
+        event.command.get_document("writeConcern").unwrap(),
+        &doc! { "w": 0 }
+    );
+    assert!(event.txn_number().is_none());
+}
+
+/// Creates an `EventClient` that declares a server API, which the server APIs declared for its
+/// databases and collections must be compatible with, along with whether that API is strict.
+async fn server_api_event_client() -> (EventClient, Option<bool>) {
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    let server_api = options
+        .server_api
+        .get_or_insert_with(|| ServerApi::builder().version(ServerApiVersion::V1).build());
+    let strict = server_api.strict;
+    (EventClient::with_options(options).await, strict)
+}
+
+fn assert_strict_server_api(events: Vec<CommandStartedEvent>) {
+    for event in events {
+        assert_eq!(
+            event.command.get_str("apiVersion"),
+            Ok("1"),
+            "{}",
+            event.command_name
+        );
+        assert_eq!(
+            event.command.get_bool("apiStrict"),
+            Ok(true),
+            "{}",
+            event.command_name
+        );
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn collection_server_api() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let (client, client_strict) = server_api_event_client().await;
+    if client.server_version_lt(5, 0) {
+        log_uncaptured("skipping collection_server_api due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let server_api = ServerApi::builder()
+        .version(ServerApiVersion::V1)
+        .strict(true)
+        .build();
+    let options = CollectionOptions::builder()
+        .server_api(server_api.clone())
+        .build();
+    let api_coll = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+    assert_eq!(api_coll.server_api(), Some(&server_api));
+
+    api_coll
+        .insert_many((0..5).map(|i| doc! { "x": i }), None)
+        .await
+        .unwrap();
+    let options = FindOptions::builder().batch_size(2).build();
+    let docs: Vec<Document> = api_coll
+        .find(None, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(docs.len(), 5);
+
+    let events = client.get_command_started_events(&["insert", "find", "getMore"]);
+    assert!(events.iter().any(|e| e.command_name == "getMore"));
+    assert_strict_server_api(events);
+
+    // Other collections in the database use the client's server API.
+    coll.find_one(None, None).await.unwrap();
+    let event = client.get_command_started_events(&["find"]).pop().unwrap();
+    assert_eq!(event.command.get_str("apiVersion"), Ok("1"));
+    assert_eq!(event.command.get_bool("apiStrict").ok(), client_strict);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn database_server_api() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let (client, client_strict) = server_api_event_client().await;
+    if client.server_version_lt(5, 0) {
+        log_uncaptured("skipping database_server_api due to test configuration");
+        return;
+    }
+    client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let server_api = ServerApi::builder()
+        .version(ServerApiVersion::V1)
+        .strict(true)
+        .build();
+    let options = DatabaseOptions::builder()
+        .server_api(server_api.clone())
+        .build();
+    let api_db = client.database_with_options(function_name!(), options);
+    assert_eq!(api_db.server_api(), Some(&server_api));
+
+    // Collections inherit the database's server API.
+    let api_coll = api_db.collection::<Document>(function_name!());
+    assert_eq!(api_coll.server_api(), Some(&server_api));
+
+    api_db.run_command(doc! { "ping": 1 }, None).await.unwrap();
+    api_coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    api_coll.find_one(None, None).await.unwrap();
+    assert_strict_server_api(client.get_command_started_events(&["ping", "insert", "find"]));
+
+    // Other databases use the client's server API.
+    client
+        .database(function_name!())
+        .run_command(doc! { "ping": 1 }, None)
+        .await
+        .unwrap();
+    let event = client.get_command_started_events(&["ping"]).pop().unwrap();
+    assert_eq!(event.command.get_str("apiVersion"), Ok("1"));
+    assert_eq!(event.command.get_bool("apiStrict").ok(), client_strict);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn transaction_server_api() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let (client, _) = server_api_event_client().await;
+    if client.server_version_lt(5, 0) || !client.supports_transactions() {
+        log_uncaptured("skipping transaction_server_api due to test configuration");
+        return;
+    }
+    client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await
+        .insert_one(doc! {}, None)
+        .await
+        .unwrap();
+
+    let options = CollectionOptions::builder()
+        .server_api(
+            ServerApi::builder()
+                .version(ServerApiVersion::V1)
+                .strict(true)
+                .build(),
+        )
+        .build();
+    let api_coll = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+
+    // commitTransaction and abortTransaction are sent by the session, which was started from the
+    // client, but declare the server API of the collection that ran the transaction.
+    let mut session = client.start_session(None).await.unwrap();
+    session.start_transaction(None).await.unwrap();
+    api_coll
+        .insert_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+    session.commit_transaction().await.unwrap();
+
+    session.start_transaction(None).await.unwrap();
+    api_coll
+        .insert_one_with_session(doc! { "x": 2 }, None, &mut session)
+        .await
+        .unwrap();
+    session.abort_transaction().await.unwrap();
+
+    let events = client.get_command_started_events(&["commitTransaction", "abortTransaction"]);
+    assert_eq!(events.len(), 2);
+    assert_strict_server_api(events);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn server_api_without_client_server_api() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    if SERVER_API.is_some() {
+        log_uncaptured(
+            "skipping server_api_without_client_server_api due to the client declaring a server \
+             API",
+        );
+        return;
+    }
+    let client = EventClient::new().await;
+    if client.server_version_lt(5, 0) {
+        log_uncaptured("skipping server_api_without_client_server_api due to test configuration");
+        return;
+    }
+    client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // A collection can declare a server API even though the client's connections were established
+    // without one.
+    let options = CollectionOptions::builder()
+        .server_api(
+            ServerApi::builder()
+                .version(ServerApiVersion::V1)
+                .strict(true)
+                .build(),
+        )
+        .build();
+    let coll = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    coll.find_one(None, None).await.unwrap();
+    assert_strict_server_api(client.get_command_started_events(&["insert", "find"]));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn server_api_version_mismatch() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let (client, _) = server_api_event_client().await;
+    let options = CollectionOptions::builder()
+        .server_api(ServerApi::builder().version(ServerApiVersion::Test).build())
+        .build();
+    let coll = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+
+    let error = coll.find_one(None, None).await.unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+        "{:?}",
+        error
+    );
+    assert!(client.get_command_started_events(&["find"]).is_empty());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn api_strict_violation() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    // The client's server API is always compatible with the collection's, so the only errors are
+    // the ones returned by the server.
+    let (client, _) = server_api_event_client().await;
+    if client.server_version_lt(5, 0) {
+        log_uncaptured("skipping api_strict_violation due to test configuration");
+        return;
+    }
+    client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let options = CollectionOptions::builder()
+        .server_api(
+            ServerApi::builder()
+                .version(ServerApiVersion::V1)
+                .strict(true)
+                .build(),
+        )
+        .build();
+    let coll = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+
+    // distinct is not part of stable API version 1.
+    let error = coll.distinct("x", None, None).await.unwrap_err();
+    assert!(error.is_api_strict_violation(), "{:?}", error);
+
+    // A command in the stable API that fails for another reason is not a violation.
+    let error = coll.find_one(doc! { "$foo": 1 }, None).await.unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::Command(_)), "{:?}", error);
+    assert!(!error.is_api_strict_violation());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn insert_one_and_wait() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if !client.is_replica_set() || *SERVERLESS {
+        log_uncaptured("skipping insert_one_and_wait due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let error = coll
+        .insert_one_and_wait(doc! { "_id": 0 }, "undefinedMode")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+        "{:?}",
+        error
+    );
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 0);
+
+    // Custom modes defined in the replica set configuration are sent to the server the same way
+    // as the built-in "majority" mode, which is used here to avoid reconfiguring the shared
+    // replica set.
+    let options = CollectionOptions::builder()
+        .write_concern(
+            WriteConcern::builder()
+                .w_timeout(Duration::from_secs(10))
+                .build(),
+        )
+        .build();
+    let with_timeout = client
+        .database(function_name!())
+        .collection_with_options::<Document>(function_name!(), options);
+    let result = with_timeout
+        .insert_one_and_wait(doc! { "_id": 1 }, "majority")
+        .await
+        .unwrap();
+    assert_eq!(result.inserted_id, Bson::Int32(1));
+    let inserted = coll.find_one(doc! { "_id": 1 }, None).await.unwrap();
+    assert_eq!(inserted, Some(doc! { "_id": 1 }));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_with_shard_names() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    if *SERVERLESS {
+        log_uncaptured("skipping find_with_shard_names due to test configuration");
+        return;
+    }
+    if !client.is_sharded() {
+        let coll = client
+            .init_db_and_coll(function_name!(), function_name!())
+            .await;
+        let error = match coll.find_with_shard_names(None, None).await {
+            Ok(_) => panic!("expected find_with_shard_names to fail"),
+            Err(error) => error,
+        };
+        assert!(
+            matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+            "{:?}",
+            error
+        );
+        return;
+    }
+    // The shards are queried directly, which requires them to have the same users as the cluster.
+    if CLIENT_OPTIONS.get().await.credential.is_some() {
+        log_uncaptured("skipping find_with_shard_names due to test configuration");
+        return;
+    }
+    let admin = client.database("admin");
+    let shards = admin
+        .run_command(doc! { "listShards": 1 }, None)
+        .await
+        .unwrap();
+    if shards.get_array("shards").unwrap().len() < 2 {
+        log_uncaptured("skipping find_with_shard_names due to test configuration");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    admin
+        .run_command(doc! { "enableSharding": function_name!() }, None)
+        .await
+        .unwrap();
+    admin
+        .run_command(
+            doc! {
+                "shardCollection": coll.namespace().to_string(),
+                "key": { "_id": "hashed" },
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    coll.insert_many((0..100).map(|i| doc! { "_id": i }), None)
+        .await
+        .unwrap();
+
+    let docs: Vec<ShardDocument<Document>> = coll
+        .find_with_shard_names(None, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(docs.len(), 100);
+    let shard_names: HashSet<&str> = docs.iter().map(|doc| doc.shard.as_str()).collect();
+    assert!(shard_names.len() > 1, "{:?}", shard_names);
+
+    // A query matching a single document only returns it from the shard that owns it.
+    let docs: Vec<ShardDocument<Document>> = coll
+        .find_with_shard_names(doc! { "_id": 7 }, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs[0].document, doc! { "_id": 7 });
+    assert!(shard_names.contains(docs[0].shard.as_str()));
+
+    // Options whose results would have to be merged across shards are rejected.
+    for options in [
+        FindOptions::builder().sort(doc! { "_id": 1 }).build(),
+        FindOptions::builder().skip(1).build(),
+        FindOptions::builder().limit(1).build(),
+    ] {
+        let error = match coll.find_with_shard_names(None, options).await {
+            Ok(_) => panic!("expected find_with_shard_names to fail"),
+            Err(error) => error,
+        };
+        assert!(
+            matches!(*error.kind, ErrorKind::InvalidArgument { .. }),
+            "{:?}",
+            error
+        );
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_with_filter_builder() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let statuses = ["a", "b", "c"];
+    coll.insert_many(
+        (0..30).map(|i| doc! { "_id": i, "n": i, "status": statuses[i as usize % 3] }),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let filter = Filter::new()
+        .in_("status", ["a", "b"])
+        .gte("n", 5)
+        .or([Filter::new().lt("n", 10), Filter::new().gt("n", 20)])
+        .and([Filter::new().ne("n", 7)]);
+    let expected = doc! {
+        "status": { "$in": ["a", "b"] },
+        "n": { "$gte": 5 },
+        "$or": [{ "n": { "$lt": 10 } }, { "n": { "$gt": 20 } }],
+        "$and": [{ "n": { "$ne": 7 } }],
+    };
+    assert_eq!(filter.clone().into_document(), expected);
+
+    let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+    let built: Vec<Document> = coll
+        .find(filter, options.clone())
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let hand_written: Vec<Document> = coll
+        .find(expected, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(built, hand_written);
+    let ids: Vec<i32> = built.iter().map(|d| d.get_i32("_id").unwrap()).collect();
+    assert_eq!(ids, vec![6, 9, 21, 22, 24, 25, 27, 28]);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_with_filter_builder_expr() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many(
+        vec![
+            doc! { "_id": 1, "a": 2, "b": 1 },
+            doc! { "_id": 2, "a": 1, "b": 2 },
+            doc! { "_id": 3, "a": 3, "b": 3 },
+            doc! { "_id": 4, "a": 5 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let filter = Filter::new()
+        .expr(doc! { "$gt": ["$a", "$b"] })
+        .json_schema(doc! { "required": ["a", "b"] });
+    let ids: Vec<i32> = coll
+        .find(filter, None)
+        .await
+        .unwrap()
+        .map(|d| d.unwrap().get_i32("_id").unwrap())
+        .collect()
+        .await;
+    // Document 4 satisfies the `$expr` since a missing field compares as less than a number, but
+    // not the schema.
+    assert_eq!(ids, vec![1]);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn drop_skip_serializing_none() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll: Collection<Document> = client
+        .database(function_name!())
+        .collection(function_name!());
+    let options = DropCollectionOptions::builder().build();
+    assert!(coll.drop(options).await.is_ok());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn collection_generic_bounds() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    #[derive(Deserialize)]
+    struct Foo;
+
+    let client = TestClient::new().await;
+
+    // ensure this code successfully compiles
+    let coll: Collection<Foo> = client
+        .database(function_name!())
+        .collection(function_name!());
+    let _result: Result<Option<Foo>> = coll.find_one(None, None).await;
+
+    #[derive(Serialize)]
+    struct Bar;
+
+    // ensure this code successfully compiles
+    let coll: Collection<Bar> = client
+        .database(function_name!())
+        .collection(function_name!());
+    let _result = coll.insert_one(Bar {}, None).await;
+}
+
+/// Verify that a cursor with multiple batches whose last batch isn't full
+/// iterates without errors.
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn cursor_batch_size() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll("cursor_batch_size", "cursor_batch_size")
+        .await;
+
+    let doc = Document::new();
+    coll.insert_many(vec![&doc; 10], None).await.unwrap();
+
+    let opts = FindOptions::builder().batch_size(3).build();
+    let cursor_no_session = coll.find(doc! {}, opts.clone()).await.unwrap();
+    let docs: Vec<_> = cursor_no_session.try_collect().await.unwrap();
+    assert_eq!(docs.len(), 10);
+
+    // test session cursors
+    if client.is_standalone() {
+        log_uncaptured("skipping cursor_batch_size due to standalone topology");
+        return;
+    }
+    let mut session = client.start_session(None).await.unwrap();
+    let mut cursor = coll
+        .find_with_session(doc! {}, opts.clone(), &mut session)
+        .await
+        .unwrap();
+    let mut docs = Vec::new();
+    while let Some(doc) = cursor.next(&mut session).await {
+        docs.push(doc.unwrap());
+    }
+    assert_eq!(docs.len(), 10);
+
+    let mut cursor = coll
+        .find_with_session(doc! {}, opts, &mut session)
+        .await
+        .unwrap();
+    let docs: Vec<_> = cursor.stream(&mut session).try_collect().await.unwrap();
+    assert_eq!(docs.len(), 10);
+}
+
+/// Test that the driver gracefully handles cases where the server returns invalid UTF-8 in error
+/// messages. See SERVER-24007 and related tickets for details.
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn invalid_utf8_response() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll("invalid_uft8_handling", "invalid_uft8_handling")
+        .await;
+
+    let index_model = IndexModel::builder()
+        .keys(doc! {"name": 1})
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    coll.create_index(index_model, None)
+        .await
+        .expect("creating an index should succeed");
+
+    // a document containing a long string with multi-byte unicode characters. taken from a user
+    // repro in RUBY-2560.
+    let long_unicode_str_doc = doc! {"name":  "(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻"};
+    coll.insert_one(&long_unicode_str_doc, None)
+        .await
+        .expect("first insert of document should succeed");
+
+    // test triggering an invalid error message via an insert_one.
+    let insert_err = coll
+        .insert_one(&long_unicode_str_doc, None)
+        .await
+        .expect_err("second insert of document should fail")
+        .kind;
+    assert_duplicate_key_error_with_utf8_replacement(&insert_err);
+
+    // test triggering an invalid error message via an insert_many.
+    let insert_err = coll
+        .insert_many([&long_unicode_str_doc], None)
+        .await
+        .expect_err("second insert of document should fail")
+        .kind;
+    assert_duplicate_key_error_with_utf8_replacement(&insert_err);
+
+    // test triggering an invalid error message via an update_one.
+    coll.insert_one(doc! {"x": 1}, None)
+        .await
+        .expect("inserting new document should succeed");
+
+    let update_err = coll
+        .update_one(doc! {"x": 1}, doc! {"$set": &long_unicode_str_doc}, None)
+        .await
+        .expect_err("update setting duplicate key should fail")
+        .kind;
+    assert_duplicate_key_error_with_utf8_replacement(&update_err);
+
+    // test triggering an invalid error message via an update_many.
+    let update_err = coll
+        .update_many(doc! {"x": 1}, doc! {"$set": &long_unicode_str_doc}, None)
+        .await
+        .expect_err("update setting duplicate key should fail")
+        .kind;
+    assert_duplicate_key_error_with_utf8_replacement(&update_err);
+
+    // test triggering an invalid error message via a replace_one.
+    let replace_err = coll
+        .replace_one(doc! {"x": 1}, &long_unicode_str_doc, None)
+        .await
+        .expect_err("replacement with duplicate key should fail")
+        .kind;
+    assert_duplicate_key_error_with_utf8_replacement(&replace_err);
+}
+
+/// Check that we successfully decoded a duplicate key error and that the error message contains the
+/// unicode replacement character, meaning we gracefully handled the invalid UTF-8.
+fn assert_duplicate_key_error_with_utf8_replacement(error: &ErrorKind) {
+    match error {
+        ErrorKind::Write(ref failure) => match failure {
+            WriteFailure::WriteError(err) => {
+                assert_eq!(err.code, 11000);
+                assert!(err.message.contains('�'));
+            }
+            e => panic!("expected WriteFailure::WriteError, got {:?} instead", e),
+        },
+        ErrorKind::BulkWrite(ref failure) => match &failure.write_errors {
+            Some(write_errors) => {
+                assert_eq!(write_errors.len(), 1);
+                assert_eq!(write_errors[0].code, 11000);
+                assert!(write_errors[0].message.contains('�'));
+            }
+            None => panic!(
+                "expected BulkWriteFailure containing write errors, got {:?} instead",
+                failure
+            ),
+        },
+        e => panic!(
+            "expected ErrorKind::Write or ErrorKind::BulkWrite, got {:?} instead",
+            e
+        ),
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))] // multi_thread required for FailPoint
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn retried_upsert_is_idempotent() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(4, 4) || !client.is_replica_set() {
+        log_uncaptured("skipping retried_upsert_is_idempotent due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // The first update is applied but its acknowledgement is replaced by a retryable error.
+    let _fp_guard = FailPoint::fail_command(
+        &["update"],
+        FailPointMode::Times(1),
+        FailCommandOptions::builder()
+            .write_concern_error(doc! { "code": 91, "errmsg": "Replication is being shut down" })
+            .error_labels(vec!["RetryableWriteError".to_string()])
+            .build(),
+    )
+    .enable(&client, None)
+    .await
+    .unwrap();
+
+    let options = UpdateOptions::builder().upsert(true).build();
+    let result = coll
+        .update_one(doc! { "x": 1 }, doc! { "$inc": { "y": 1 } }, options)
+        .await
+        .unwrap();
+
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 1);
+    let stored = coll.find_one(None, None).await.unwrap().unwrap();
+    assert_eq!(result.upserted_id.as_ref(), stored.get("_id"));
+    assert_eq!(stored.get_i32("y"), Ok(1));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn replace_upsert_existing_match() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(doc! { "_id": 1, "x": 1 }, None)
+        .await
+        .unwrap();
+
+    let options = ReplaceOptions::builder().upsert(true).build();
+    let result = coll
+        .replace_one(doc! { "x": 1 }, doc! { "x": 1, "y": 2 }, options.clone())
+        .await
+        .unwrap();
+    assert_eq!(result.matched_count, 1);
+    assert!(result.upserted_id.is_none());
+    assert_eq!(
+        coll.find_one(None, None).await.unwrap(),
+        Some(doc! { "_id": 1, "x": 1, "y": 2 })
+    );
+
+    // Without a match, the server generates the upserted document's `_id`.
+    let result = coll
+        .replace_one(doc! { "x": 2 }, doc! { "x": 2 }, options)
+        .await
+        .unwrap();
+    let upserted_id = result.upserted_id.expect("should have upserted");
+    let stored = coll.find_one(doc! { "x": 2 }, None).await.unwrap().unwrap();
+    assert_eq!(stored.get("_id"), Some(&upserted_id));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn assert_exactly_once_insert() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let client = TestClient::new().await;
+    if client.server_version_lt(4, 4) || !client.is_replica_set() {
+        log_uncaptured("skipping assert_exactly_once_insert due to test configuration");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // The first insert is applied but its acknowledgement is replaced by a retryable error.
+    let fail_point = FailPoint::fail_command(
+        &["insert"],
+        FailPointMode::Times(1),
+        FailCommandOptions::builder()
+            .write_concern_error(doc! { "code": 91, "errmsg": "Replication is being shut down" })
+            .error_labels(vec!["RetryableWriteError".to_string()])
+            .build(),
+    );
+    let fail_point = to_document(&fail_point).unwrap();
+
+    coll.assert_exactly_once_insert(doc! { "x": 1 }, fail_point.clone())
+        .await
+        .unwrap();
+    coll.assert_exactly_once_insert(doc! { "_id": 2, "x": 2 }, fail_point.clone())
+        .await
+        .unwrap();
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 2);
+
+    // The fail point is turned off afterwards.
+    coll.insert_one(doc! { "x": 3 }, None).await.unwrap();
+
+    // A duplicate key error is reported as the insert having been applied more than once.
+    let error = coll
+        .assert_exactly_once_insert(doc! { "_id": 2, "x": 2 }, fail_point)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::Internal { .. }),
+        "{:?}",
+        error
+    );
+    assert!(error.source.as_ref().unwrap().is_duplicate_key_error());
+    assert_eq!(
+        coll.count_documents(doc! { "_id": 2 }, None).await.unwrap(),
+        1
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn collection_uuid() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+
+    let uuid = coll.uuid().await.unwrap();
+    assert!(uuid.is_some());
+    // The UUID is stable for the lifetime of the collection.
+    assert_eq!(coll.uuid().await.unwrap(), uuid);
+
+    let view = client
+        .create_fresh_collection(
+            function_name!(),
+            &format!("{}_view", function_name!()),
+            CreateCollectionOptions::builder()
+                .view_on(function_name!().to_string())
+                .pipeline(vec![doc! { "$match": {} }])
+                .build(),
+        )
+        .await;
+    assert_eq!(view.uuid().await.unwrap(), None);
+
+    let missing = client
+        .database(function_name!())
+        .collection::<Document>("missing");
+    assert_eq!(missing.uuid().await.unwrap(), None);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn write_to_view_is_read_only_namespace() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let source = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    source.insert_one(doc! { "x": 1 }, None).await.unwrap();
+
+    let view_name = format!("{}_view", function_name!());
+    let db = client.database(function_name!());
+    drop_collection(&db.collection::<Document>(&view_name)).await;
+    db.create_view(&view_name, function_name!(), Vec::new(), None)
+        .await
+        .unwrap();
+    let view = db.collection::<Document>(&view_name);
+
+    // Reads are allowed.
+    assert_eq!(view.count_documents(None, None).await.unwrap(), 1);
+
+    let error = view.insert_one(doc! { "x": 2 }, None).await.unwrap_err();
+    assert!(error.is_read_only_namespace(), "{:?}", error);
+
+    let error = view
+        .update_one(doc! {}, doc! { "$set": { "x": 2 } }, None)
+        .await
+        .unwrap_err();
+    assert!(error.is_read_only_namespace(), "{:?}", error);
+
+    let error = source
+        .aggregate(vec![doc! { "$out": view_name.as_str() }], None)
+        .await
+        .unwrap_err();
+    assert!(error.is_read_only_namespace(), "{:?}", error);
+
+    // Unrelated errors are not reported as read-only namespace errors.
+    let error = source
+        .insert_many(vec![doc! { "_id": 1 }, doc! { "_id": 1 }], None)
+        .await
+        .unwrap_err();
+    assert!(!error.is_read_only_namespace());
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn parallel_scan() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // `_id`s of several types, which are ordered by type before value on the server.
+    let mut docs: Vec<Document> = (0..300).map(|i| doc! { "_id": i, "n": i }).collect();
+    docs.extend((0..300).map(|i| doc! { "_id": format!("s{}", i), "n": i }));
+    docs.extend((0..300).map(|i| doc! { "_id": ObjectId::new(), "n": i }));
+    coll.insert_many(docs, None).await.unwrap();
+
+    let cursors = coll.parallel_scan(4, None).await.unwrap();
+    assert!(!cursors.is_empty() && cursors.len() <= 4);
+    let results = futures::future::join_all(
+        cursors
+            .into_iter()
+            .map(|cursor| cursor.try_collect::<Vec<Document>>()),
+    )
+    .await;
+
+    let mut seen = HashSet::new();
+    let mut total = 0;
+    for result in results {
+        for doc in result.unwrap() {
+            total += 1;
+            assert!(seen.insert(format!("{:?}", doc.get("_id").unwrap())));
+        }
+    }
+    assert_eq!(total, 900);
+
+    // The filter and projection are applied to every cursor.
+    let options = ParallelScanOptions::builder()
+        .filter(doc! { "n": { "$lt": 10 } })
+        .projection(doc! { "n": 0 })
+        .build();
+    let mut total = 0;
+    for cursor in coll.parallel_scan(3, options).await.unwrap() {
+        let docs: Vec<Document> = cursor.try_collect().await.unwrap();
+        assert!(docs.iter().all(|doc| !doc.contains_key("n")));
+        total += docs.len();
+    }
+    assert_eq!(total, 30);
+
+    assert_eq!(coll.parallel_scan(1, None).await.unwrap().len(), 1);
+    let error = coll.parallel_scan(0, None).await.unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+
+    // An empty collection yields a single cursor with no results.
+    coll.drop(None).await.unwrap();
+    let cursors = coll.parallel_scan(4, None).await.unwrap();
+    assert_eq!(cursors.len(), 1);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_numeric_coercion() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        qty: u64,
+        price: i64,
+    }
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    // The same fields were stored with different numeric types over time.
+    coll.insert_many(
+        vec![
+            doc! { "_id": 1, "qty": 1_i32, "price": 10_i64 },
+            doc! { "_id": 2, "qty": 2_i64, "price": 20.0 },
+            doc! { "_id": 3, "qty": 3.0, "price": 30.75 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    let coll = coll.clone_with_type::<Item>();
+
+    let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+    let error = coll
+        .find(None, options)
+        .await
+        .unwrap()
+        .try_collect::<Vec<Item>>()
+        .await
+        .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::BsonDeserialization(_)));
+
+    let options = FindOptions::builder()
+        .sort(doc! { "_id": 1 })
+        .numeric_coercion(NumericCoercion::Exact)
+        .build();
+    let mut cursor = coll.find(None, options).await.unwrap();
+    for _ in 0..2 {
+        cursor.try_next().await.unwrap().unwrap();
+    }
+    let error = cursor.try_next().await.unwrap_err();
+    assert!(error.to_string().contains("fractional"), "{}", error);
+
+    let options = FindOptions::builder()
+        .sort(doc! { "_id": 1 })
+        .numeric_coercion(NumericCoercion::Truncate)
+        .build();
+    let items: Vec<Item> = coll
+        .find(None, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let items: Vec<(u64, i64)> = items.iter().map(|i| (i.qty, i.price)).collect();
+    assert_eq!(items, vec![(1, 10), (2, 20), (3, 30)]);
+
+    let options = FindOneOptions::builder()
+        .numeric_coercion(NumericCoercion::Exact)
+        .build();
+    let item = coll
+        .find_one(doc! { "_id": 2 }, options)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((item.qty, item.price), (2, 20));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn wait_for() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    let inserter = coll.clone();
+    let task = runtime::spawn(async move {
+        runtime::delay_for(Duration::from_millis(500)).await;
+        inserter.insert_one(doc! { "x": 1 }, None).await
+    });
+
+    let found = coll
+        .wait_for(
+            doc! { "x": 1 },
+            Duration::from_secs(10),
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap();
+    assert_eq!(found.and_then(|d| d.get_i32("x").ok()), Some(1));
+    task.await.unwrap();
+
+    let start = std::time::Instant::now();
+    let missing = coll
+        .wait_for(
+            doc! { "x": 2 },
+            Duration::from_millis(300),
+            Duration::from_millis(50),
+        )
         .await
         .unwrap();
+    assert!(missing.is_none());
+    assert!(start.elapsed() >= Duration::from_millis(300));
 
-    let result = coll.find_one(doc! { "x": 2 }, None).await.unwrap().unwrap();
-    assert_eq!(result, replacement);
+    // A timeout that overflows the deadline polls without one.
+    let found = coll
+        .wait_for(doc! { "x": 1 }, Duration::MAX, Duration::from_millis(50))
+        .await
+        .unwrap();
+    assert!(found.is_some());
+
+    let error = coll
+        .wait_for(doc! {}, Duration::from_secs(1), Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
 }
 
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn typed_returns() {
+async fn find_one_and_update_upsert_return_after() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
     let client = TestClient::new().await;
     let coll = client
-        .init_db_and_typed_coll(function_name!(), function_name!())
+        .init_db_and_coll(function_name!(), function_name!())
         .await;
 
-    let insert_data = UserType {
-        x: 1,
-        str: "a".into(),
+    let options = || {
+        FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build()
     };
-    coll.insert_one(insert_data.clone(), None).await.unwrap();
 
-    let result = coll
-        .find_one_and_update(doc! { "x": 1 }, doc! { "$inc": { "x": 1 } }, None)
+    // An upsert that inserts a new document returns the inserted document rather than None.
+    let inserted = coll
+        .find_one_and_update(doc! { "x": 1 }, doc! { "$inc": { "count": 1 } }, options())
         .await
         .unwrap()
+        .expect("upsert with ReturnDocument::After should return the inserted document");
+    assert_eq!(inserted.get_i32("x"), Ok(1));
+    assert_eq!(inserted.get_i32("count"), Ok(1));
+    assert!(inserted.contains_key("_id"));
+
+    // Servers before 4.2 can report a duplicate key error to one of several concurrent upserts
+    // racing to insert the same document instead of retrying it as an update.
+    if client.server_version_lt(4, 2) {
+        log_uncaptured(
+            "skipping concurrent part of find_one_and_update_upsert_return_after due to server \
+             version",
+        );
+        return;
+    }
+
+    let index_model = IndexModel::builder()
+        .keys(doc! { "y": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    coll.create_index(index_model, None).await.unwrap();
+
+    const N: i32 = 10;
+    let mut tasks = Vec::new();
+    for _ in 0..N {
+        let coll = coll.clone();
+        tasks.push(runtime::spawn(async move {
+            coll.find_one_and_update(doc! { "y": 1 }, doc! { "$inc": { "count": 1 } }, options())
+                .await
+        }));
+    }
+
+    // Every caller gets the document as it was after its own increment, whether its upsert
+    // inserted the document or matched one inserted concurrently.
+    let mut counts = HashSet::new();
+    for task in tasks {
+        let after = task.await.unwrap().expect("upsert returned None");
+        counts.insert(after.get_i32("count").unwrap());
+    }
+    assert_eq!(counts, (1..=N).collect());
+
+    assert_eq!(
+        coll.count_documents(doc! { "y": 1 }, None).await.unwrap(),
+        1
+    );
+    let stored = coll.find_one(doc! { "y": 1 }, None).await.unwrap().unwrap();
+    assert_eq!(stored.get_i32("count"), Ok(N));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn increment() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // The first increment of a missing counter inserts it with the amount.
+    let value = coll
+        .increment(doc! { "_id": "missing" }, "count", 5, None)
+        .await
         .unwrap();
-    assert_eq!(result, insert_data);
+    assert_eq!(value, 5);
 
-    let result = coll
-        .find_one_and_delete(doc! { "x": 2 }, None)
+    // A missing field on an existing document is set to the amount.
+    coll.insert_one(doc! { "_id": "existing", "other": true }, None)
+        .await
+        .unwrap();
+    let value = coll
+        .increment(doc! { "_id": "existing" }, "stats.count", -2, None)
+        .await
+        .unwrap();
+    assert_eq!(value, -2);
+
+    const TASKS: i64 = 10;
+    const INCREMENTS_PER_TASK: i64 = 20;
+    coll.insert_one(doc! { "_id": "counter", "count": 0 }, None)
+        .await
+        .unwrap();
+    let mut tasks = Vec::new();
+    for _ in 0..TASKS {
+        let coll = coll.clone();
+        tasks.push(runtime::spawn(async move {
+            let mut values = Vec::new();
+            for _ in 0..INCREMENTS_PER_TASK {
+                let value = coll
+                    .increment(doc! { "_id": "counter" }, "count", 1, None)
+                    .await
+                    .unwrap();
+                values.push(value);
+            }
+            values
+        }));
+    }
+
+    // Every increment observes a distinct value.
+    let mut values = HashSet::new();
+    for task in tasks {
+        for value in task.await {
+            assert!(values.insert(value), "{} was returned twice", value);
+        }
+    }
+    assert_eq!(values, (1..=TASKS * INCREMENTS_PER_TASK).collect());
+
+    let counter = coll
+        .find_one(doc! { "_id": "counter" }, None)
         .await
         .unwrap()
         .unwrap();
     assert_eq!(
-        result,
-        UserType {
-            x: 2,
-            str: "a".into()
+        counter.get("count").and_then(bson_util::get_int),
+        Some(TASKS * INCREMENTS_PER_TASK)
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn claim_one() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // There is nothing to claim in an empty queue.
+    let claimed = coll
+        .claim_one(
+            doc! { "status": "pending" },
+            doc! { "$set": { "status": "running" } },
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(claimed.is_none());
+
+    const JOBS: i32 = 200;
+    const WORKERS: i32 = 8;
+    coll.insert_many(
+        (0..JOBS).map(|i| doc! { "_id": i, "status": "pending" }),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut workers = Vec::new();
+    for worker in 0..WORKERS {
+        let coll = coll.clone();
+        workers.push(runtime::spawn(async move {
+            let options = ClaimOptions::builder().sort(doc! { "_id": 1 }).build();
+            let mut claimed = Vec::new();
+            while let Some(job) = coll
+                .claim_one(
+                    doc! { "status": "pending" },
+                    doc! { "$set": { "status": "running", "worker": worker } },
+                    options.clone(),
+                )
+                .await
+                .unwrap()
+            {
+                // The document is returned as it is after the claim.
+                assert_eq!(job.get_str("status"), Ok("running"));
+                assert_eq!(job.get_i32("worker"), Ok(worker));
+                claimed.push(job.get_i32("_id").unwrap());
+            }
+            claimed
+        }));
+    }
+
+    let mut claimed = HashSet::new();
+    for worker in workers {
+        for id in worker.await {
+            assert!(claimed.insert(id), "job {} was claimed twice", id);
+        }
+    }
+    assert_eq!(claimed, (0..JOBS).collect());
+    assert_eq!(
+        coll.count_documents(doc! { "status": "running" }, None)
+            .await
+            .unwrap(),
+        JOBS as u64
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn insert_many_on_duplicate() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let index_model = IndexModel::builder()
+        .keys(doc! { "a": 1, "b": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    coll.create_index(index_model, None).await.unwrap();
+
+    let coll_ref = &coll;
+    let reset = move || async move {
+        coll_ref.delete_many(doc! {}, None).await.unwrap();
+        coll_ref
+            .insert_many(
+                vec![
+                    doc! { "_id": 1, "a": 1, "b": 1, "v": "old" },
+                    doc! { "_id": 2, "a": 2, "b": 2, "v": "old" },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+    };
+    let docs = vec![
+        doc! { "_id": 1, "a": 1, "b": 1, "v": "new" },
+        doc! { "_id": 3, "a": 3, "b": 3, "v": "new" },
+        doc! { "_id": 2, "a": 2, "b": 2, "v": "new" },
+        // Conflicts with _id 1 on the compound unique index rather than on _id.
+        doc! { "_id": 4, "a": 1, "b": 1, "v": "new" },
+        doc! { "_id": 5, "a": 5, "b": 5, "v": "new" },
+    ];
+    let values = move || async move {
+        coll_ref
+            .find(None, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+            .await
+            .unwrap()
+            .map(|doc| {
+                let doc = doc.unwrap();
+                (
+                    doc.get_i32("_id").unwrap(),
+                    doc.get_str("v").unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .await
+    };
+    let error_indexes = |error: crate::error::Error| match *error.kind {
+        ErrorKind::BulkWrite(ref failure) => {
+            let mut indexes: Vec<_> = failure
+                .write_errors
+                .iter()
+                .flatten()
+                .map(|e| e.index)
+                .collect();
+            indexes.sort_unstable();
+            indexes
         }
+        ref e => panic!("expected bulk write error, got {:?} instead", e),
+    };
+
+    // Fail reports every duplicate, including the compound index conflict.
+    reset().await;
+    let options = InsertManyOptions::builder()
+        .ordered(false)
+        .on_duplicate(OnDuplicate::Fail)
+        .build();
+    let error = coll.insert_many(&docs, options).await.unwrap_err();
+    assert_eq!(error_indexes(error), vec![0, 2, 3]);
+
+    // Skip ignores all of them and leaves the existing documents alone.
+    reset().await;
+    let options = InsertManyOptions::builder()
+        .on_duplicate(OnDuplicate::Skip)
+        .build();
+    let result = coll.insert_many(&docs, options).await.unwrap();
+    let mut inserted: Vec<_> = result.inserted_ids.keys().copied().collect();
+    inserted.sort_unstable();
+    assert_eq!(inserted, vec![1, 4]);
+    assert_eq!(
+        values().await,
+        vec![
+            (1, "old".to_string()),
+            (2, "old".to_string()),
+            (3, "new".to_string()),
+            (5, "new".to_string()),
+        ]
+    );
+
+    // Replace overwrites the documents with conflicting _ids, but the compound index conflict
+    // can't be resolved by _id and is still reported.
+    reset().await;
+    let options = InsertManyOptions::builder()
+        .on_duplicate(OnDuplicate::Replace)
+        .build();
+    let error = coll.insert_many(&docs, options).await.unwrap_err();
+    assert_eq!(error_indexes(error), vec![3]);
+    assert_eq!(
+        values().await,
+        vec![
+            (1, "new".to_string()),
+            (2, "new".to_string()),
+            (3, "new".to_string()),
+            (5, "new".to_string()),
+        ]
+    );
+
+    let options = InsertManyOptions::builder()
+        .on_duplicate(OnDuplicate::Replace)
+        .build();
+    let result = coll.insert_many(&docs[..3], options).await.unwrap();
+    assert_eq!(result.inserted_ids.len(), 3);
+
+    // Skipping or replacing duplicates requires unordered inserts.
+    let options = InsertManyOptions::builder()
+        .ordered(true)
+        .on_duplicate(OnDuplicate::Skip)
+        .build();
+    let error = coll.insert_many(&docs, options).await.unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn distinct_cursor() {
+    if std::env::consts::OS != "linux" {
+        log_uncaptured("skipping distinct_cursor due to unsupported OS");
+        return;
+    }
+
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // 20 distinct values of 1MB each don't fit in a single 16MB distinct reply.
+    let values: HashSet<String> = (0..20)
+        .map(|i| format!("{:02}{}", i, "x".repeat(1024 * 1024)))
+        .collect();
+    for value in &values {
+        coll.insert_many(
+            vec![doc! { "x": value, "y": 1 }, doc! { "x": value, "y": 2 }],
+            None,
+        )
+        .await
+        .unwrap();
+    }
+    coll.distinct("x", None, None).await.unwrap_err();
+
+    let found: Vec<Bson> = coll
+        .distinct_cursor("x", None, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(found.len(), values.len());
+    let found: HashSet<String> = found
+        .into_iter()
+        .map(|value| value.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(found, values);
+
+    // Array elements are returned individually, and null and missing values are skipped.
+    coll.drop(None).await.unwrap();
+    coll.insert_many(
+        vec![
+            doc! { "x": [1, 2], "y": 1 },
+            doc! { "x": 2, "y": 1 },
+            doc! { "x": 3, "y": 2 },
+            doc! { "x": Bson::Null, "y": 1 },
+            doc! { "y": 1 },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    let mut found: Vec<i32> = coll
+        .distinct_cursor("x", doc! { "y": 1 }, None)
+        .await
+        .unwrap()
+        .map(|value| value.unwrap().as_i32().unwrap())
+        .collect()
+        .await;
+    found.sort_unstable();
+    assert_eq!(found, vec![1, 2]);
+
+    // A dotted path descends through arrays of embedded documents, as it does for distinct.
+    coll.drop(None).await.unwrap();
+    coll.insert_many(
+        vec![
+            doc! { "a": [{ "b": 1 }, { "b": 2 }] },
+            doc! { "a": { "b": 3 } },
+            doc! { "a": [{ "b": [4, 5] }, { "c": 6 }] },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    let mut found: Vec<i32> = coll
+        .distinct_cursor("a.b", None, None)
+        .await
+        .unwrap()
+        .map(|value| value.unwrap().as_i32().unwrap())
+        .collect()
+        .await;
+    found.sort_unstable();
+    let mut expected: Vec<i32> = coll
+        .distinct("a.b", None, None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|value| value.as_i32().unwrap())
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(found, expected);
+    assert_eq!(found, vec![1, 2, 3, 4, 5]);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn effective_concerns() {
+    use crate::options::{DatabaseOptions, TransactionOptions};
+
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let w = |n: u32| WriteConcern::builder().w(Acknowledgment::Nodes(n)).build();
+    let majority = WriteConcern::builder().w(Acknowledgment::Majority).build();
+
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.read_concern = Some(ReadConcern::local());
+    options.write_concern = Some(w(1));
+    let client = Client::with_options(options).unwrap();
+
+    // The client's concerns apply when nothing else is set.
+    let coll = client
+        .database(function_name!())
+        .collection::<Document>(function_name!());
+    assert_eq!(
+        coll.effective_read_concern(None, None),
+        Some(ReadConcern::local())
+    );
+    assert_eq!(coll.effective_write_concern(None, None), Some(w(1)));
+
+    // The database's concerns override the client's, and the collection's override the
+    // database's.
+    let db = client.database_with_options(
+        function_name!(),
+        DatabaseOptions::builder()
+            .read_concern(ReadConcern::majority())
+            .write_concern(majority.clone())
+            .build(),
+    );
+    let coll = db.collection::<Document>(function_name!());
+    assert_eq!(
+        coll.effective_read_concern(None, None),
+        Some(ReadConcern::majority())
+    );
+    assert_eq!(
+        coll.effective_write_concern(None, None),
+        Some(majority.clone())
+    );
+    let coll = db.collection_with_options::<Document>(
+        function_name!(),
+        CollectionOptions::builder()
+            .read_concern(ReadConcern::available())
+            .write_concern(w(2))
+            .build(),
+    );
+    assert_eq!(
+        coll.effective_read_concern(None, None),
+        Some(ReadConcern::available())
+    );
+    assert_eq!(coll.effective_write_concern(None, None), Some(w(2)));
+
+    // The operation's concerns override the collection's, including in a session that isn't in a
+    // transaction.
+    let session = client.start_session(None).await.unwrap();
+    assert_eq!(
+        coll.effective_read_concern(Some(&ReadConcern::linearizable()), Some(&session)),
+        Some(ReadConcern::linearizable())
+    );
+    assert_eq!(
+        coll.effective_write_concern(Some(&w(3)), Some(&session)),
+        Some(w(3))
+    );
+    drop(session);
+
+    if !TestClient::new().await.supports_transactions() {
+        log_uncaptured("skipping transaction part of effective_concerns");
+        return;
+    }
+
+    // A transaction's concerns override everything else.
+    let mut session = client.start_session(None).await.unwrap();
+    session
+        .start_transaction(
+            TransactionOptions::builder()
+                .read_concern(ReadConcern::snapshot())
+                .write_concern(majority.clone())
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        coll.effective_read_concern(Some(&ReadConcern::linearizable()), Some(&session)),
+        Some(ReadConcern::snapshot())
+    );
+    assert_eq!(
+        coll.effective_write_concern(Some(&w(3)), Some(&session)),
+        Some(majority)
+    );
+    session.abort_transaction().await.unwrap();
+    assert_eq!(
+        coll.effective_read_concern(None, Some(&session)),
+        Some(ReadConcern::available())
+    );
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn delete_many_by_ids() {
+    if std::env::consts::OS != "linux" {
+        log_uncaptured("skipping delete_many_by_ids due to unsupported OS");
+        return;
+    }
+
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = EventClient::new().await;
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+
+    // The ids are long enough that they don't all fit in a single `$in` filter.
+    let id = |i: usize| Bson::String(format!("{:0>300}", i));
+    let docs: Vec<Document> = (0..50_000).map(|i| doc! { "_id": id(i) }).collect();
+    coll.insert_many(docs, None).await.unwrap();
+    coll.insert_one(doc! { "_id": "kept" }, None).await.unwrap();
+
+    // Ids without a matching document don't contribute to the count.
+    let ids = (0..50_010).map(id);
+    let result = coll.delete_many_by_ids(ids, None).await.unwrap();
+    assert_eq!(result.deleted_count, 50_000);
+    assert_eq!(client.get_command_started_events(&["delete"]).len(), 2);
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 1);
+
+    let result = coll.delete_many_by_ids(Vec::new(), None).await.unwrap();
+    assert_eq!(result.deleted_count, 0);
+    assert_eq!(client.get_command_started_events(&["delete"]).len(), 2);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn find_hint_survives_retry() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let client = EventClient::new().await;
+    if !client.supports_fail_command() {
+        log_uncaptured("skipping find_hint_survives_retry due to failCommand not being supported");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+    coll.create_index(IndexModel::builder().keys(doc! { "x": 1 }).build(), None)
+        .await
+        .unwrap();
+
+    let _fp_guard = FailPoint::fail_command(
+        &["find"],
+        FailPointMode::Times(1),
+        FailCommandOptions::builder().error_code(91).build(),
+    )
+    .enable(&client, None)
+    .await
+    .unwrap();
+
+    let options = FindOptions::builder()
+        .hint(Hint::Name("x_1".to_string()))
+        .build();
+    let docs: Vec<Document> = coll
+        .find(None, options)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(docs.len(), 1);
+
+    let events = client.get_command_started_events(&["find"]);
+    assert_eq!(events.len(), 2);
+    for event in events {
+        assert_eq!(event.command.get_str("hint"), Ok("x_1"));
+    }
+
+    // A hint naming an index that doesn't exist is rejected by the server rather than ignored.
+    let options = FindOptions::builder()
+        .hint(Hint::Name("nonexistent".to_string()))
+        .build();
+    let error = coll.find(None, options).await.unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::Command(ref err) if err.code == 2),
+        "{:?}",
+        error
     );
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn count_documents_with_wc() {
+async fn schema_validation_error_details() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let mut options = CLIENT_OPTIONS.get().await.clone();
-    options.write_concern = WriteConcern::builder()
-        .w(Acknowledgment::Majority)
-        .journal(true)
-        .build()
-        .into();
-
-    let client = TestClient::with_options(Some(options)).await;
+    let client = TestClient::new().await;
+    if client.server_version_lt(5, 0) {
+        log_uncaptured("skipping schema_validation_error_details due to server version < 5.0");
+        return;
+    }
     let coll = client
-        .database(function_name!())
-        .collection(function_name!());
+        .create_fresh_collection(
+            function_name!(),
+            function_name!(),
+            CreateCollectionOptions::builder()
+                .validator(doc! {
+                    "$jsonSchema": {
+                        "title": "person",
+                        "required": ["name"],
+                        "properties": {
+                            "address": {
+                                "bsonType": "object",
+                                "properties": {
+                                    "zip": { "bsonType": "string" },
+                                },
+                            },
+                        },
+                    },
+                })
+                .build(),
+        )
+        .await;
 
-    coll.insert_one(doc! {}, None).await.unwrap();
+    let error = coll
+        .insert_one(doc! { "_id": 1, "address": { "zip": 12345 } }, None)
+        .await
+        .expect_err("insert of an invalid document should fail");
+    let validation_error = match *error.kind {
+        ErrorKind::Write(WriteFailure::WriteError(ref err)) => err
+            .schema_validation_error()
+            .expect("validation failure should have details"),
+        ref e => panic!("expected WriteFailure::WriteError, got {:?} instead", e),
+    };
+    assert_eq!(validation_error.failing_document_id, Some(Bson::Int32(1)));
+    assert_eq!(validation_error.title.as_deref(), Some("person"));
+
+    let mut violations: Vec<_> = validation_error
+        .violations
+        .iter()
+        .map(|violation| (violation.keyword.as_str(), violation.path.as_str()))
+        .collect();
+    violations.sort_unstable();
+    assert_eq!(
+        violations,
+        vec![("bsonType", "address.zip"), ("required", "")]
+    );
 
-    coll.count_documents(doc! {}, None)
+    // Bulk write errors are parsed the same way.
+    let error = coll
+        .insert_many(vec![doc! { "name": "a" }, doc! { "address": 5 }], None)
         .await
-        .expect("count_documents should succeed");
+        .expect_err("insert of an invalid document should fail");
+    let write_errors = match *error.kind {
+        ErrorKind::BulkWrite(ref failure) => failure.write_errors.clone().unwrap(),
+        ref e => panic!("expected ErrorKind::BulkWrite, got {:?} instead", e),
+    };
+    assert_eq!(write_errors.len(), 1);
+    let violations = write_errors[0]
+        .schema_validation_error()
+        .unwrap()
+        .violations;
+    assert_eq!(violations.len(), 2, "{:?}", violations);
+    assert!(violations
+        .iter()
+        .any(|violation| violation.keyword == "bsonType" && violation.path == "address"));
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn collection_options_inherited() {
+async fn paginate() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let client = EventClient::new().await;
-
-    let read_concern = ReadConcern::majority();
-    let selection_criteria = SelectionCriteria::ReadPreference(ReadPreference::Secondary {
-        options: Default::default(),
-    });
-
-    let options = CollectionOptions::builder()
-        .read_concern(read_concern)
-        .selection_criteria(selection_criteria)
-        .build();
+    let client = TestClient::new().await;
     let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    // Many documents share a "group" value, so runs of equal values span page boundaries.
+    let docs: Vec<Document> = (0..1000)
+        .map(|i| doc! { "_id": i, "group": (i * 7) % 100 })
+        .collect();
+    coll.insert_many(docs, None).await.unwrap();
+
+    let mut seen = Vec::new();
+    let mut after = None;
+    let mut pages = 0;
+    loop {
+        let (page, next) = coll.paginate("group", 30, after).await.unwrap();
+        pages += 1;
+        assert!(page.len() <= 30);
+        seen.extend(
+            page.iter()
+                .map(|doc| (doc.get_i32("group").unwrap(), doc.get_i32("_id").unwrap())),
+        );
+        match next {
+            Some(next) => after = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 34);
+
+    // Every document is returned exactly once, ordered by the sort field and then by _id.
+    assert_eq!(seen.len(), 1000);
+    assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+    let ids: HashSet<i32> = seen.iter().map(|(_, id)| *id).collect();
+    assert_eq!(ids.len(), 1000);
+
+    let (page, next) = coll.paginate("_id", 1000, None).await.unwrap();
+    assert_eq!(page.len(), 1000);
+    let (page, next) = coll.paginate("_id", 1000, next).await.unwrap();
+    assert!(page.is_empty());
+    assert!(next.is_none());
+
+    // Dotted sort fields are resolved within embedded documents.
+    let nested = client
         .database(function_name!())
-        .collection_with_options::<Document>(function_name!(), options);
-
-    coll.find(None, None).await.unwrap();
-    assert_options_inherited(&client, "find").await;
-
-    coll.find_one(None, None).await.unwrap();
-    assert_options_inherited(&client, "find").await;
-
-    coll.count_documents(None, None).await.unwrap();
-    assert_options_inherited(&client, "aggregate").await;
-}
-
-async fn assert_options_inherited(client: &EventClient, command_name: &str) {
-    let events = client.get_command_started_events(&[command_name]);
-    let event = events.iter().last().unwrap();
-    assert!(event.command.contains_key("readConcern"));
-    assert_eq!(
-        event.command.contains_key("$readPreference"),
-        !client.is_standalone()
-    );
+        .collection::<Document>(&format!("{}_nested", function_name!()));
+    nested.drop(None).await.unwrap();
+    nested
+        .insert_many(
+            (0..10).map(|i| doc! { "_id": i, "meta": { "rank": 9 - i } }),
+            None,
+        )
+        .await
+        .unwrap();
+    let (page, next) = nested.paginate("meta.rank", 4, None).await.unwrap();
+    let ids: Vec<i32> = page.iter().map(|doc| doc.get_i32("_id").unwrap()).collect();
+    assert_eq!(ids, vec![9, 8, 7, 6]);
+    let (page, _) = nested.paginate("meta.rank", 4, next).await.unwrap();
+    let ids: Vec<i32> = page.iter().map(|doc| doc.get_i32("_id").unwrap()).collect();
+    assert_eq!(ids, vec![5, 4, 3, 2]);
+
+    let error = coll.paginate("group", 10, Some(Bson::Int32(5))).await;
+    assert!(matches!(
+        *error.unwrap_err().kind,
+        ErrorKind::InvalidArgument { .. }
+    ));
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn drop_skip_serializing_none() {
+async fn find_max_document_depth() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
     let client = TestClient::new().await;
-    let coll: Collection<Document> = client
-        .database(function_name!())
-        .collection(function_name!());
-    let options = DropCollectionOptions::builder().build();
-    assert!(coll.drop(options).await.is_ok());
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    coll.insert_many(
+        vec![
+            doc! { "_id": 1, "a": { "b": [1] } },
+            doc! { "_id": 2, "a": { "b": [{ "c": 1 }] } },
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let options = FindOneOptions::builder().max_document_depth(3).build();
+    // A document exactly at the limit is returned.
+    let doc = coll
+        .find_one(doc! { "_id": 1 }, options.clone())
+        .await
+        .unwrap();
+    assert_eq!(doc, Some(doc! { "_id": 1, "a": { "b": [1] } }));
+
+    let error = coll.find_one(doc! { "_id": 2 }, options).await.unwrap_err();
+    assert!(
+        matches!(*error.kind, ErrorKind::BsonDeserialization(_)),
+        "{:?}",
+        error
+    );
+
+    let options = FindOptions::builder()
+        .max_document_depth(3)
+        .sort(doc! { "_id": 1 })
+        .build();
+    let mut cursor = coll.find(None, options).await.unwrap();
+    assert!(cursor.try_next().await.unwrap().is_some());
+    assert!(cursor.try_next().await.is_err());
 }
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
-async fn collection_generic_bounds() {
+async fn snapshot_export() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    #[derive(Deserialize)]
-    struct Foo;
-
     let client = TestClient::new().await;
+    if client.server_version_lt(5, 0) || !(client.is_replica_set() || client.is_sharded()) {
+        log_uncaptured("skipping snapshot_export due to unsupported topology");
+        return;
+    }
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let docs: Vec<_> = (0..500).map(|i| doc! { "_id": i, "v": 0 }).collect();
+    coll.insert_many(docs, None).await.unwrap();
+
+    let options = SnapshotExportOptions::builder().batch_size(10).build();
+    let mut cursor = coll.snapshot_export(options).await.unwrap();
+    // The snapshot is established by the first batch, so every write made after this point must
+    // be invisible to the rest of the export.
+    let mut exported = vec![cursor.try_next().await.unwrap().unwrap()];
+
+    for round in 0..5 {
+        // Read past the end of the current batch so that each round of writes is made between
+        // getMores.
+        for _ in 0..50 {
+            exported.push(cursor.try_next().await.unwrap().unwrap());
+        }
+        coll.update_many(doc! {}, doc! { "$inc": { "v": 1 } }, None)
+            .await
+            .unwrap();
+        coll.insert_one(doc! { "_id": 1000 + round, "v": 0 }, None)
+            .await
+            .unwrap();
+        coll.delete_one(doc! { "_id": 499 - round }, None)
+            .await
+            .unwrap();
+    }
+    // The writes were applied outside of the snapshot while the export was still in progress.
+    assert_eq!(
+        coll.count_documents(doc! { "v": 5 }, None).await.unwrap(),
+        495
+    );
 
-    // ensure this code successfully compiles
-    let coll: Collection<Foo> = client
-        .database(function_name!())
-        .collection(function_name!());
-    let _result: Result<Option<Foo>> = coll.find_one(None, None).await;
-
-    #[derive(Serialize)]
-    struct Bar;
+    while let Some(doc) = cursor.try_next().await.unwrap() {
+        exported.push(doc);
+    }
 
-    // ensure this code successfully compiles
-    let coll: Collection<Bar> = client
-        .database(function_name!())
-        .collection(function_name!());
-    let _result = coll.insert_one(Bar {}, None).await;
+    let ids: HashSet<i32> = exported
+        .iter()
+        .map(|doc| doc.get_i32("_id").unwrap())
+        .collect();
+    assert_eq!(exported.len(), 500);
+    assert_eq!(ids, (0..500).collect());
+    for doc in &exported {
+        assert_eq!(doc.get_i32("v").unwrap(), 0, "torn read: {}", doc);
+    }
 }
 
-/// Verify that a cursor with multiple batches whose last batch isn't full
-/// iterates without errors.
-#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))] // multi_thread required for FailPoint
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
-async fn cursor_batch_size() {
-    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+#[function_name::named]
+async fn find_deadline() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
 
-    let client = TestClient::new().await;
+    let client = EventClient::new().await;
     let coll = client
-        .init_db_and_coll("cursor_batch_size", "cursor_batch_size")
+        .init_db_and_coll(function_name!(), function_name!())
         .await;
+    let docs: Vec<_> = (0..3).map(|i| doc! { "_id": i }).collect();
+    coll.insert_many(docs, None).await.unwrap();
 
-    let doc = Document::new();
-    coll.insert_many(vec![&doc; 10], None).await.unwrap();
+    // The time remaining until the deadline is sent as maxTimeMS.
+    let options = FindOptions::builder()
+        .deadline(Instant::now() + Duration::from_secs(10))
+        .build();
+    let cursor = coll.find(None, options).await.unwrap();
+    assert_eq!(cursor.try_collect::<Vec<_>>().await.unwrap().len(), 3);
+    let events = client.get_command_started_events(&["find"]);
+    let max_time = events[0]
+        .command
+        .get("maxTimeMS")
+        .and_then(bson_util::get_int)
+        .unwrap();
+    assert!(max_time > 0 && max_time <= 10_000, "{}", max_time);
 
-    let opts = FindOptions::builder().batch_size(3).build();
-    let cursor_no_session = coll.find(doc! {}, opts.clone()).await.unwrap();
-    let docs: Vec<_> = cursor_no_session.try_collect().await.unwrap();
-    assert_eq!(docs.len(), 10);
+    // A deadline that has already passed times out without sending a command.
+    let options = FindOptions::builder().deadline(Instant::now()).build();
+    let error = coll.find(None, options).await.unwrap_err();
+    assert!(error.is_network_timeout(), "{:?}", error);
+    assert_eq!(client.get_command_started_events(&["find"]).len(), 1);
 
-    // test session cursors
-    if client.is_standalone() {
-        log_uncaptured("skipping cursor_batch_size due to standalone topology");
+    // The deadline also bounds the time spent waiting on a server that doesn't respond.
+    if !client.supports_block_connection() {
+        log_uncaptured("skipping find_deadline blocking check due to unsupported server version");
         return;
     }
-    let mut session = client.start_session(None).await.unwrap();
-    let mut cursor = coll
-        .find_with_session(doc! {}, opts.clone(), &mut session)
-        .await
-        .unwrap();
-    let mut docs = Vec::new();
-    while let Some(doc) = cursor.next(&mut session).await {
-        docs.push(doc.unwrap());
-    }
-    assert_eq!(docs.len(), 10);
+    let fail_point = FailPoint::fail_command(
+        &["find"],
+        FailPointMode::Times(1),
+        FailCommandOptions::builder()
+            .block_connection(Duration::from_secs(2))
+            .build(),
+    );
+    let _fp_guard = client.enable_failpoint(fail_point, None).await.unwrap();
 
-    let mut cursor = coll
-        .find_with_session(doc! {}, opts, &mut session)
-        .await
-        .unwrap();
-    let docs: Vec<_> = cursor.stream(&mut session).try_collect().await.unwrap();
-    assert_eq!(docs.len(), 10);
+    let start = Instant::now();
+    let options = FindOptions::builder()
+        .deadline(start + Duration::from_millis(300))
+        .build();
+    let error = coll.find(None, options).await.unwrap_err();
+    assert!(error.is_network_timeout(), "{:?}", error);
+    assert!(start.elapsed() < Duration::from_secs(2));
 }
 
-/// Test that the driver gracefully handles cases where the server returns invalid UTF-8 in error
-/// messages. See SERVER-24007 and related tickets for details.
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
-async fn invalid_utf8_response() {
+#[function_name::named]
+async fn find_one_consistent() {
     let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
 
-    let client = TestClient::new().await;
+    let client = EventClient::new().await;
     let coll = client
-        .init_db_and_coll("invalid_uft8_handling", "invalid_uft8_handling")
+        .init_db_and_coll(function_name!(), function_name!())
         .await;
-
-    let index_model = IndexModel::builder()
-        .keys(doc! {"name": 1})
-        .options(IndexOptions::builder().unique(true).build())
+    let secondary_preferred = FindOneOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(
+            ReadPreference::SecondaryPreferred {
+                options: Default::default(),
+            },
+        ))
         .build();
-    coll.create_index(index_model, None)
-        .await
-        .expect("creating an index should succeed");
-
-    // a document containing a long string with multi-byte unicode characters. taken from a user
-    // repro in RUBY-2560.
-    let long_unicode_str_doc = doc! {"name":  "(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻(╯°□°)╯︵ ┻━┻"};
-    coll.insert_one(&long_unicode_str_doc, None)
-        .await
-        .expect("first insert of document should succeed");
-
-    // test triggering an invalid error message via an insert_one.
-    let insert_err = coll
-        .insert_one(&long_unicode_str_doc, None)
-        .await
-        .expect_err("second insert of document should fail")
-        .kind;
-    assert_duplicate_key_error_with_utf8_replacement(&insert_err);
-
-    // test triggering an invalid error message via an insert_many.
-    let insert_err = coll
-        .insert_many([&long_unicode_str_doc], None)
-        .await
-        .expect_err("second insert of document should fail")
-        .kind;
-    assert_duplicate_key_error_with_utf8_replacement(&insert_err);
 
-    // test triggering an invalid error message via an update_one.
-    coll.insert_one(doc! {"x": 1}, None)
+    // A document written to the primary is found even if a lagging secondary doesn't have it yet.
+    let options = InsertManyOptions::builder()
+        .write_concern(WriteConcern::builder().w(Acknowledgment::Nodes(1)).build())
+        .build();
+    coll.insert_many(vec![doc! { "_id": 1 }], options)
         .await
-        .expect("inserting new document should succeed");
-
-    let update_err = coll
-        .update_one(doc! {"x": 1}, doc! {"$set": &long_unicode_str_doc}, None)
+        .unwrap();
+    let found = coll
+        .find_one_consistent(doc! { "_id": 1 }, secondary_preferred.clone())
         .await
-        .expect_err("update setting duplicate key should fail")
-        .kind;
-    assert_duplicate_key_error_with_utf8_replacement(&update_err);
+        .unwrap();
+    assert_eq!(found, Some(doc! { "_id": 1 }));
 
-    // test triggering an invalid error message via an update_many.
-    let update_err = coll
-        .update_many(doc! {"x": 1}, doc! {"$set": &long_unicode_str_doc}, None)
+    // On a replica set, a document that doesn't exist is only reported missing after checking
+    // the primary. Other deployments route the read themselves, so it isn't repeated.
+    let before = client.get_command_started_events(&["find"]).len();
+    let missing = coll
+        .find_one_consistent(doc! { "_id": 2 }, secondary_preferred)
         .await
-        .expect_err("update setting duplicate key should fail")
-        .kind;
-    assert_duplicate_key_error_with_utf8_replacement(&update_err);
+        .unwrap();
+    assert!(missing.is_none());
+    let finds = client.get_command_started_events(&["find"]);
+    if client.is_replica_set() {
+        assert_eq!(finds.len(), before + 2);
+        let primary = client
+            .topology_description()
+            .primary()
+            .map(|server| server.address.clone());
+        assert_eq!(Some(finds[before + 1].connection.address.clone()), primary);
+    } else {
+        assert_eq!(finds.len(), before + 1);
+    }
 
-    // test triggering an invalid error message via a replace_one.
-    let replace_err = coll
-        .replace_one(doc! {"x": 1}, &long_unicode_str_doc, None)
+    // Reads that can only go to the primary are not repeated.
+    let before = client.get_command_started_events(&["find"]).len();
+    let options = FindOneOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(ReadPreference::Primary))
+        .build();
+    let missing = coll
+        .find_one_consistent(doc! { "_id": 2 }, options)
         .await
-        .expect_err("replacement with duplicate key should fail")
-        .kind;
-    assert_duplicate_key_error_with_utf8_replacement(&replace_err);
-}
-
-/// Check that we successfully decoded a duplicate key error and that the error message contains the
-/// unicode replacement character, meaning we gracefully handled the invalid UTF-8.
-fn assert_duplicate_key_error_with_utf8_replacement(error: &ErrorKind) {
-    match error {
-        ErrorKind::Write(ref failure) => match failure {
-            WriteFailure::WriteError(err) => {
-                assert_eq!(err.code, 11000);
-                assert!(err.message.contains('�'));
-            }
-            e => panic!("expected WriteFailure::WriteError, got {:?} instead", e),
-        },
-        ErrorKind::BulkWrite(ref failure) => match &failure.write_errors {
-            Some(write_errors) => {
-                assert_eq!(write_errors.len(), 1);
-                assert_eq!(write_errors[0].code, 11000);
-                assert!(write_errors[0].message.contains('�'));
-            }
-            None => panic!(
-                "expected BulkWriteFailure containing write errors, got {:?} instead",
-                failure
-            ),
-        },
-        e => panic!(
-            "expected ErrorKind::Write or ErrorKind::BulkWrite, got {:?} instead",
-            e
-        ),
-    }
+        .unwrap();
+    assert!(missing.is_none());
+    assert_eq!(
+        client.get_command_started_events(&["find"]).len(),
+        before + 1
+    );
 }
@@ -1,14 +1,38 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use futures::{future::Either, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLockReadGuard;
+use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
-    bson::doc,
-    options::{CreateCollectionOptions, CursorType, FindOptions},
+    bson::{doc, Document},
+    bson_util,
+    event::cursor::{
+        CursorEventHandler,
+        CursorExhaustedEvent,
+        CursorGetMoreEvent,
+        CursorKilledEvent,
+        CursorOpenedEvent,
+    },
+    options::{AggregateOptions, CreateCollectionOptions, CursorType, FindOptions},
     runtime,
-    test::{log_uncaptured, util::EventClient, TestClient, LOCK, SERVERLESS},
+    test::{
+        log_uncaptured,
+        util::EventClient,
+        FailCommandOptions,
+        FailPoint,
+        FailPointMode,
+        TestClient,
+        CLIENT_OPTIONS,
+        LOCK,
+        SERVERLESS,
+    },
 };
 
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
@@ -235,3 +259,271 @@ async fn borrowed_deserialization() {
         i += 1;
     }
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn get_more_comment() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+    let client = EventClient::new().await;
+    if client.server_version_lt(4, 4) {
+        log_uncaptured("skipping get_more_comment: requires server 4.4+");
+        return;
+    }
+
+    let coll = client
+        .create_fresh_collection(
+            "cursor_get_more_comment_db",
+            "cursor_get_more_comment_coll",
+            None,
+        )
+        .await;
+    coll.insert_many((0..5).map(|i| doc! { "_id": i }), None)
+        .await
+        .unwrap();
+
+    let cursor = coll
+        .find(
+            None,
+            FindOptions::builder()
+                .batch_size(2)
+                .comment("get_more_comment".to_string())
+                .build(),
+        )
+        .await
+        .unwrap();
+    let docs: Vec<_> = cursor.try_collect().await.unwrap();
+    assert_eq!(docs.len(), 5);
+
+    let get_mores = client.get_command_started_events(&["getMore"]);
+    assert!(!get_mores.is_empty());
+    for event in get_mores {
+        assert_eq!(event.command.get_str("comment"), Ok("get_more_comment"));
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn aggregate_get_more_comment_and_max_time() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+    let client = EventClient::new().await;
+    if client.server_version_lt(4, 4) {
+        log_uncaptured("skipping aggregate_get_more_comment_and_max_time: requires server 4.4+");
+        return;
+    }
+
+    let coll = client
+        .create_fresh_collection(
+            "cursor_aggregate_get_more_db",
+            "cursor_aggregate_get_more_coll",
+            None,
+        )
+        .await;
+    coll.insert_many((0..5).map(|i| doc! { "_id": i }), None)
+        .await
+        .unwrap();
+
+    let cursor = coll
+        .aggregate(
+            vec![doc! { "$match": {} }],
+            AggregateOptions::builder()
+                .batch_size(2)
+                .max_time(Duration::from_secs(10))
+                .comment("aggregate_get_more".to_string())
+                .build(),
+        )
+        .await
+        .unwrap();
+    let docs: Vec<_> = cursor.try_collect().await.unwrap();
+    assert_eq!(docs.len(), 5);
+
+    let aggregate = client.get_command_started_events(&["aggregate"]);
+    assert_eq!(aggregate[0].command.get_i32("maxTimeMS"), Ok(10_000));
+
+    // The server applies the aggregate's maxTimeMS to the cursor as a whole, and rejects a
+    // maxTimeMS on getMores of cursors that aren't awaitData, so only the comment is sent again.
+    let get_mores = client.get_command_started_events(&["getMore"]);
+    assert!(get_mores.len() >= 2);
+    for event in get_mores {
+        assert_eq!(event.command.get_str("comment"), Ok("aggregate_get_more"));
+        assert!(!event.command.contains_key("maxTimeMS"));
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn find_streaming_batch_size() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+    let client = EventClient::new().await;
+
+    let coll = client
+        .create_fresh_collection(
+            "cursor_find_streaming_batch_size_db",
+            "cursor_find_streaming_batch_size_coll",
+            None,
+        )
+        .await;
+    // Documents of roughly 1MB followed by small documents.
+    let large = "x".repeat(1_000_000);
+    coll.insert_many(
+        (0..12).map(|i| doc! { "_id": i, "data": large.as_str() }),
+        None,
+    )
+    .await
+    .unwrap();
+    coll.insert_many((12..62).map(|i| doc! { "_id": i }), None)
+        .await
+        .unwrap();
+
+    let cursor = coll
+        .find_streaming(
+            None,
+            FindOptions::builder()
+                .batch_size(2)
+                .sort(doc! { "_id": 1 })
+                .build(),
+        )
+        .await
+        .unwrap();
+    let docs: Vec<_> = cursor.try_collect().await.unwrap();
+    assert_eq!(docs.len(), 62);
+
+    let batch_sizes: Vec<_> = client
+        .get_command_started_events(&["getMore"])
+        .into_iter()
+        .map(|e| bson_util::get_int(e.command.get("batchSize").unwrap()).unwrap())
+        .collect();
+    // While documents are large, batches are limited to around 4MB.
+    assert_eq!(batch_sizes[0], 4);
+    assert_eq!(batch_sizes[1], 4);
+    // Once documents become small, batches grow.
+    assert!(*batch_sizes.last().unwrap() > 4, "{:?}", batch_sizes);
+}
+
+#[derive(Debug, Default)]
+struct CursorEventCounts {
+    opened: AtomicUsize,
+    get_more: AtomicUsize,
+    exhausted: AtomicUsize,
+    killed: AtomicUsize,
+}
+
+impl CursorEventHandler for CursorEventCounts {
+    fn handle_cursor_opened_event(&self, _event: CursorOpenedEvent) {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn handle_cursor_get_more_event(&self, _event: CursorGetMoreEvent) {
+        self.get_more.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn handle_cursor_exhausted_event(&self, _event: CursorExhaustedEvent) {
+        self.exhausted.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn handle_cursor_killed_event(&self, _event: CursorKilledEvent) {
+        self.killed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn cursor_lifecycle_events() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let counts = Arc::new(CursorEventCounts::default());
+    let mut options = CLIENT_OPTIONS.get().await.clone();
+    options.cursor_event_handler = Some(counts.clone());
+    let client = TestClient::with_options(Some(options)).await;
+
+    let coll = client
+        .create_fresh_collection(
+            "cursor_lifecycle_events_db",
+            "cursor_lifecycle_events_coll",
+            None,
+        )
+        .await;
+    coll.insert_many((0..5).map(|i| doc! { "_id": i }), None)
+        .await
+        .unwrap();
+    let options = FindOptions::builder().batch_size(2).build();
+
+    // A fully-consumed cursor is exhausted exactly once and never killed.
+    let cursor = coll.find(None, options.clone()).await.unwrap();
+    let docs: Vec<_> = cursor.try_collect().await.unwrap();
+    assert_eq!(docs.len(), 5);
+    assert_eq!(counts.opened.load(Ordering::SeqCst), 1);
+    assert_eq!(counts.get_more.load(Ordering::SeqCst), 2);
+    assert_eq!(counts.exhausted.load(Ordering::SeqCst), 1);
+    assert_eq!(counts.killed.load(Ordering::SeqCst), 0);
+
+    // A cursor dropped before exhaustion is killed rather than exhausted.
+    let mut cursor = coll.find(None, options).await.unwrap();
+    cursor.next().await.unwrap().unwrap();
+    drop(cursor);
+    assert_eq!(counts.opened.load(Ordering::SeqCst), 2);
+    assert_eq!(counts.exhausted.load(Ordering::SeqCst), 1);
+    assert_eq!(counts.killed.load(Ordering::SeqCst), 1);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn find_resilient_resumes_after_cursor_not_found() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+    let client = EventClient::new().await;
+    if !client.supports_fail_command() {
+        log_uncaptured(
+            "skipping find_resilient_resumes_after_cursor_not_found due to failCommand not being \
+             supported",
+        );
+        return;
+    }
+
+    let coll = client
+        .create_fresh_collection(
+            "cursor_find_resilient_db",
+            "cursor_find_resilient_coll",
+            None,
+        )
+        .await;
+    // Several documents share each sort key value, so the first batch ends in the middle of a
+    // group of equal values.
+    coll.insert_many(
+        (0..25).map(|i| doc! { "_id": i, "group": i / 3, "meta": { "group": i / 3 } }),
+        None,
+    )
+    .await
+    .unwrap();
+
+    // The sort key may also be a path into an embedded document.
+    for sort_key in ["group", "meta.group"] {
+        client.clear_cached_events();
+
+        // Simulate the cursor timing out on the server before the first getMore.
+        let failpoint = FailPoint::fail_command(
+            &["getMore"],
+            FailPointMode::Times(1),
+            FailCommandOptions::builder().error_code(43).build(),
+        );
+        let _fp_guard = client.enable_failpoint(failpoint, None).await.unwrap();
+
+        let stream = coll
+            .find_resilient(None, sort_key, FindOptions::builder().batch_size(4).build())
+            .await
+            .unwrap();
+        let docs: Vec<Document> = stream.try_collect().await.unwrap();
+
+        let mut ids: Vec<i32> = docs.iter().map(|d| d.get_i32("_id").unwrap()).collect();
+        let groups: Vec<i32> = docs.iter().map(|d| d.get_i32("group").unwrap()).collect();
+        assert!(groups.windows(2).all(|w| w[0] <= w[1]), "{:?}", groups);
+        ids.sort_unstable();
+        assert_eq!(ids, (0..25).collect::<Vec<_>>(), "{}", sort_key);
+
+        // The query was re-run once after the error.
+        assert_eq!(
+            client.get_command_started_events(&["find"]).len(),
+            2,
+            "{}",
+            sort_key
+        );
+    }
+}
@@ -1,23 +1,36 @@
-use std::cmp::Ord;
+use std::{cmp::Ord, time::Duration};
 
 use futures::stream::TryStreamExt;
-use tokio::sync::RwLockReadGuard;
+use serde::Deserialize;
+use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
     bson::{doc, Document},
-    error::Result,
+    error::{ErrorKind, Result},
+    index::{options::IndexOptions, IndexModel},
     options::{
         AggregateOptions,
         Collation,
+        CollationStrength,
+        CollectionStatsOptions,
+        CopyCollectionOptions,
         CreateCollectionOptions,
+        CreateViewOptions,
+        DatabaseStatsOptions,
         IndexOptionDefaults,
+        ListCollectionsOptions,
+        ListIndexesOptions,
+        ProfilingLevel,
+        ProfilingOptions,
+        TimeseriesOptions,
         ValidationAction,
         ValidationLevel,
     },
-    results::{CollectionSpecification, CollectionType},
+    results::{CollectionSpecification, CollectionType, ProfileEntry},
     test::{
         util::{EventClient, TestClient},
         LOCK,
+        SERVERLESS,
     },
     Database,
 };
@@ -330,6 +343,42 @@ async fn db_aggregate_disk_use() {
         .expect("aggregate with disk use should succeed");
 }
 
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn db_aggregate_one() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    let client = TestClient::new().await;
+
+    if client.server_version_lt(5, 1) {
+        log_uncaptured("skipping db_aggregate_one due to server version < 5.1");
+        return;
+    }
+
+    let db = client.database(function_name!());
+
+    let pipeline = vec![
+        doc! { "$documents": [{ "x": 1, "y": 2 }] },
+        doc! { "$project": { "_id": 0 } },
+    ];
+    let point: Option<Point> = db.aggregate_one(pipeline, None).await.unwrap();
+    assert_eq!(point, Some(Point { x: 1, y: 2 }));
+
+    let pipeline = vec![
+        doc! { "$documents": [{ "x": 1, "y": 2 }] },
+        doc! { "$match": { "x": 2 } },
+    ];
+    let point: Option<Point> = db.aggregate_one(pipeline, None).await.unwrap();
+    assert_eq!(point, None);
+}
+
 #[cfg_attr(feature = "tokio-runtime", tokio::test)]
 #[cfg_attr(feature = "async-std-runtime", async_std::test)]
 #[function_name::named]
@@ -370,3 +419,434 @@ async fn index_option_defaults_test(defaults: Option<IndexOptionDefaults>, name:
     };
     assert_eq!(event_defaults, defaults);
 }
+
+/// Gets the server's slow operation threshold. The threshold is global rather than per-database,
+/// so tests that change it must run exclusively and restore it afterwards.
+async fn slow_operation_threshold(db: &Database) -> Duration {
+    let status = db.run_command(doc! { "profile": -1 }, None).await.unwrap();
+    let slowms = status
+        .get("slowms")
+        .and_then(crate::bson_util::get_int)
+        .unwrap();
+    Duration::from_millis(slowms as u64)
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn profiling_level() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let client = TestClient::new().await;
+    if client.is_sharded() || client.is_load_balanced() || *SERVERLESS {
+        log_uncaptured("skipping profiling_level due to mongos not recording profiling data");
+        return;
+    }
+
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+    let previous_level = db.profiling_level().await.unwrap();
+    let previous_threshold = slow_operation_threshold(&db).await;
+    assert_eq!(previous_level, ProfilingLevel::Off);
+
+    db.set_profiling_level(ProfilingLevel::All, None)
+        .await
+        .unwrap();
+    assert_eq!(db.profiling_level().await.unwrap(), ProfilingLevel::All);
+
+    let coll = db.collection::<Document>(function_name!());
+    coll.find_one(doc! { "x": 1 }, None).await.unwrap();
+
+    let ns = format!("{}.{}", function_name!(), function_name!());
+    let profiled = db
+        .collection::<Document>("system.profile")
+        .find_one(doc! { "ns": ns, "command.find": function_name!() }, None)
+        .await
+        .unwrap();
+
+    // The slow operation threshold can still be set when turning the profiler off, as it also
+    // applies to the diagnostic log.
+    let options = ProfilingOptions::builder()
+        .slow_operation_threshold(Duration::from_millis(200))
+        .build();
+    db.set_profiling_level(ProfilingLevel::Off, options)
+        .await
+        .unwrap();
+    assert_eq!(db.profiling_level().await.unwrap(), ProfilingLevel::Off);
+    assert_eq!(
+        slow_operation_threshold(&db).await,
+        Duration::from_millis(200)
+    );
+
+    let options = ProfilingOptions::builder()
+        .slow_operation_threshold(previous_threshold)
+        .build();
+    db.set_profiling_level(previous_level, options)
+        .await
+        .unwrap();
+
+    assert!(profiled.is_some(), "find was not profiled");
+    db.drop(None).await.unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn profile_entries() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let client = TestClient::new().await;
+    if client.is_sharded() || client.is_load_balanced() || *SERVERLESS {
+        log_uncaptured("skipping profile_entries due to mongos not recording profiling data");
+        return;
+    }
+
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+    let previous_level = db.profiling_level().await.unwrap();
+    let previous_threshold = slow_operation_threshold(&db).await;
+
+    // Profiling has never been enabled for this database.
+    let entries: Vec<ProfileEntry> = db
+        .profile_entries(None, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(entries.is_empty(), "{:?}", entries);
+
+    let coll = db.collection::<Document>(function_name!());
+    coll.insert_one(doc! { "x": 1 }, None).await.unwrap();
+
+    let options = ProfilingOptions::builder()
+        .slow_operation_threshold(Duration::from_millis(50))
+        .build();
+    db.set_profiling_level(ProfilingLevel::SlowOperations, options)
+        .await
+        .unwrap();
+    coll.find_one(doc! { "$where": "sleep(200) || true" }, None)
+        .await
+        .unwrap();
+
+    let options = ProfilingOptions::builder()
+        .slow_operation_threshold(previous_threshold)
+        .build();
+    db.set_profiling_level(previous_level, options)
+        .await
+        .unwrap();
+
+    let ns = format!("{}.{}", function_name!(), function_name!());
+    let entries: Vec<ProfileEntry> = db
+        .profile_entries(doc! { "ns": ns.as_str() }, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let entry = entries
+        .iter()
+        .find(|entry| {
+            entry
+                .command
+                .as_ref()
+                .and_then(|command| command.get_str("find").ok())
+                == Some(function_name!())
+        })
+        .unwrap_or_else(|| panic!("slow find was not profiled: {:?}", entries));
+    assert_eq!(entry.op, "query");
+    assert_eq!(entry.ns, ns);
+    assert!(entry.millis >= 200, "{:?}", entry);
+
+    db.drop(None).await.unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn copy_collection() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+
+    let source = db.collection::<Document>("source");
+    let docs: Vec<_> = (0..10_000)
+        .map(|i| doc! { "_id": i, "x": i % 10 })
+        .collect();
+    source.insert_many(docs, None).await.unwrap();
+
+    let result = db.copy_collection("source", "target", None).await.unwrap();
+    assert_eq!(result.inserted_ids.len(), 10_000);
+    let target = db.collection::<Document>("target");
+    assert_eq!(target.count_documents(None, None).await.unwrap(), 10_000);
+
+    let options = CopyCollectionOptions::builder()
+        .filter(doc! { "x": 0 })
+        .projection(doc! { "x": 1 })
+        .build();
+    db.copy_collection("source", "filtered", options)
+        .await
+        .unwrap();
+    let filtered = db.collection::<Document>("filtered");
+    assert_eq!(filtered.count_documents(None, None).await.unwrap(), 1_000);
+
+    // Duplicate values in the source violate a unique index on the target.
+    let unique = db.collection::<Document>("unique");
+    let index = IndexModel::builder()
+        .keys(doc! { "x": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    unique.create_index(index, None).await.unwrap();
+    let err = db
+        .copy_collection("source", "unique", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(*err.error.kind, ErrorKind::BulkWrite(_)));
+    // The ordered insert stops at the first duplicate, so at most one document per distinct value
+    // was copied.
+    assert!(unique.count_documents(None, None).await.unwrap() <= 10);
+
+    let err = db
+        .copy_collection("source", "source", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(*err.error.kind, ErrorKind::InvalidArgument { .. }));
+
+    db.drop(None).await.unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn create_view() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+
+    let source = db.collection::<Document>("source");
+    source
+        .insert_many(
+            vec![
+                doc! { "_id": 1, "name": "a", "n": 1 },
+                doc! { "_id": 2, "name": "B", "n": 5 },
+                doc! { "_id": 3, "name": "b", "n": 10 },
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+    let options = CreateViewOptions::builder()
+        .collation(
+            Collation::builder()
+                .locale("en".to_string())
+                .strength(CollationStrength::Secondary)
+                .build(),
+        )
+        .build();
+    db.create_view(
+        "view",
+        "source",
+        vec![
+            doc! { "$match": { "n": { "$gte": 5 } } },
+            doc! { "$project": { "n": 0 } },
+        ],
+        options,
+    )
+    .await
+    .unwrap();
+
+    let colls = get_coll_info(&db, Some(doc! { "name": "view" })).await;
+    assert_eq!(colls.len(), 1);
+    assert_eq!(colls[0].collection_type, CollectionType::View);
+    assert_eq!(colls[0].options.view_on.as_deref(), Some("source"));
+
+    // The view's default collation is case-insensitive, so both "B" and "b" match.
+    let view = db.collection::<Document>("view");
+    let docs: Vec<Document> = view
+        .find(doc! { "name": "b" }, None)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        docs,
+        vec![
+            doc! { "_id": 2, "name": "B" },
+            doc! { "_id": 3, "name": "b" }
+        ]
+    );
+
+    // A view on a source that does not exist can be created, but contains no documents.
+    db.create_view("missing_view", "missing", Vec::new(), None)
+        .await
+        .unwrap();
+    let missing_view = db.collection::<Document>("missing_view");
+    assert_eq!(missing_view.count_documents(None, None).await.unwrap(), 0);
+
+    db.drop(None).await.unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn drop_all_collections() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+
+    for name in ["a", "b", "c"] {
+        db.create_collection(name, None).await.unwrap();
+    }
+    db.collection::<Document>("a")
+        .insert_one(doc! { "x": 1 }, None)
+        .await
+        .unwrap();
+    db.create_view("a_view", "a", Vec::new(), None)
+        .await
+        .unwrap();
+    // Views may themselves be defined on other views.
+    db.create_view("a_view_view", "a_view", Vec::new(), None)
+        .await
+        .unwrap();
+    if !client.server_version_lt(5, 0) {
+        let timeseries = TimeseriesOptions::builder()
+            .time_field("t".to_string())
+            .build();
+        db.create_collection(
+            "ts",
+            CreateCollectionOptions::builder()
+                .timeseries(timeseries)
+                .build(),
+        )
+        .await
+        .unwrap();
+    }
+
+    db.drop_all_collections(None).await.unwrap();
+
+    let remaining = db.list_collection_names(None).await.unwrap();
+    assert!(
+        remaining.iter().all(|name| name.starts_with("system.")),
+        "{:?}",
+        remaining
+    );
+
+    // Clearing an already empty database is a no-op.
+    db.drop_all_collections(None).await.unwrap();
+
+    db.drop(None).await.unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn list_sorted_by_name() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+
+    let names = ["coll2", "coll10", "Coll1", "coll1_a", "coll1", "coll"];
+    for name in names {
+        db.create_collection(name, None).await.unwrap();
+    }
+    let mut expected: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+    expected.sort();
+
+    // A small batch size ensures results spanning multiple batches are sorted together.
+    let options = ListCollectionsOptions::builder()
+        .batch_size(2)
+        .sort_by_name(true)
+        .build();
+    let listed: Vec<String> = db
+        .list_collections(None, options)
+        .await
+        .unwrap()
+        .map_ok(|spec| spec.name)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(listed, expected);
+
+    let coll = db.collection::<Document>("coll");
+    let index_names = ["b_1", "a_1", "B_1", "a_1_b_1"];
+    for name in index_names {
+        let index = IndexModel::builder()
+            .keys(doc! { name: 1 })
+            .options(IndexOptions::builder().name(name.to_string()).build())
+            .build();
+        coll.create_index(index, None).await.unwrap();
+    }
+    let mut expected: Vec<String> = index_names.iter().map(|n| n.to_string()).collect();
+    expected.push("_id_".to_string());
+    expected.sort();
+
+    let options = ListIndexesOptions::builder()
+        .batch_size(1)
+        .sort_by_name(true)
+        .build();
+    let listed: Vec<String> = coll
+        .list_indexes(options)
+        .await
+        .unwrap()
+        .map_ok(|index| index.options.and_then(|o| o.name).unwrap())
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(listed, expected);
+
+    db.drop(None).await.unwrap();
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn stats_scale() {
+    let _guard: RwLockReadGuard<()> = LOCK.run_concurrently().await;
+
+    let client = TestClient::new().await;
+    let db = client.database(function_name!());
+    db.drop(None).await.unwrap();
+
+    let coll = db.collection::<Document>("coll");
+    let docs: Vec<Document> = (0..100)
+        .map(|i| doc! { "x": i, "padding": "a".repeat(2048) })
+        .collect();
+    coll.insert_many(docs, None).await.unwrap();
+
+    let bytes = coll.stats(None).await.unwrap();
+    assert_eq!(bytes.count, 100);
+    assert_eq!(bytes.scale_factor, 1);
+    assert!(bytes.avg_obj_size > 2048);
+
+    let options = CollectionStatsOptions::builder().scale(1024).build();
+    let kilobytes = coll.stats(options).await.unwrap();
+    assert_eq!(kilobytes.count, 100);
+    assert_eq!(kilobytes.scale_factor, 1024);
+    assert_eq!(kilobytes.size, bytes.size / 1024);
+    assert_eq!(kilobytes.avg_obj_size, bytes.avg_obj_size / 1024);
+
+    let options = DatabaseStatsOptions::builder().scale(1024).build();
+    let db_stats = db.stats(options).await.unwrap();
+    assert_eq!(db_stats.collections, 1);
+    assert_eq!(db_stats.objects, 100);
+    assert_eq!(db_stats.avg_obj_size, bytes.avg_obj_size / 1024);
+    if client.server_version_gte(4, 4) {
+        assert!(db_stats.free_storage_size.is_some());
+    }
+
+    let options = DatabaseStatsOptions::builder().scale(0).build();
+    let error = db.stats(options).await.unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+
+    db.drop(None).await.unwrap();
+}
@@ -327,3 +327,68 @@ async fn interrupted_at_shutdown() {
 
     run_test(function_name!(), interrupted_at_shutdown_test).await;
 }
+
+#[function_name::named]
+#[cfg_attr(feature = "tokio-runtime", tokio::test)]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+async fn retried_write_selects_new_primary() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    // Use a long heartbeat frequency so that the stepdown is only discovered via the write error.
+    let options = ClientOptions::builder()
+        .hosts(CLIENT_OPTIONS.get().await.hosts.clone())
+        .retry_writes(true)
+        .heartbeat_freq(Duration::from_secs(60))
+        .build();
+    let client = EventClient::with_additional_options(Some(options), None, None, None).await;
+
+    if !client.is_replica_set() || client.get_hosts().await.len() < 2 {
+        log_uncaptured(format!(
+            "skipping test {:?} due to not running on a multi-member replica set",
+            function_name!()
+        ));
+        return;
+    }
+    if client.server_version_lt(4, 2) {
+        log_uncaptured(format!(
+            "skipping test {:?} due to server version < 4.2",
+            function_name!()
+        ));
+        return;
+    }
+
+    let name = format!("step-down-{}", function_name!());
+    let coll = client.init_db_and_coll(&name, &name).await;
+
+    let old_primary = client
+        .wait_for_primary(Duration::from_secs(10))
+        .await
+        .unwrap();
+    client
+        .database("admin")
+        .run_command(doc! { "replSetStepDown": 30, "force": true }, None)
+        .await
+        .expect("stepdown should have succeeded");
+
+    // The old primary is still reported as primary by the driver, so the first attempt goes to it
+    // and fails with NotWritablePrimary. The retry must wait for the election and land on the new
+    // primary.
+    coll.insert_one(doc! { "x": 1 }, None)
+        .await
+        .expect("retried insert should have succeeded");
+
+    let events = client.get_command_started_events(&["insert"]);
+    assert_eq!(
+        events.len(),
+        2,
+        "expected an attempt and a retry: {:?}",
+        events
+    );
+    assert_eq!(events[0].connection.address, old_primary);
+    assert_ne!(events[1].connection.address, old_primary);
+
+    client
+        .wait_for_primary(Duration::from_secs(30))
+        .await
+        .unwrap();
+}
@@ -15,13 +15,11 @@ async fn run() {
 }
 
 fn test_predicate(test: &TestCase) -> bool {
-    // The Rust driver doesn't support unacknowledged writes.
     let lower = test.description.to_lowercase();
 
-    !lower.contains("unacknowledged")
     // TODO: RUST-1071: unskip comment tests
-        && (!lower.contains("comment")
-            || lower.contains("estimateddocumentcount"))
+    (!lower.contains("comment")
+        || lower.contains("estimateddocumentcount"))
     // TODO: RUST-663: unskip aggregate $out and $merge tests
         && !(lower.contains("aggregate with $out includes read preference for 5.0+ server"))
         && !(lower.contains("aggregate with $out omits read preference for pre-5.0 server"))
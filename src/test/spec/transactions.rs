@@ -1,9 +1,21 @@
+use futures::{FutureExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 
 use crate::{
-    bson::{doc, Document},
-    test::{log_uncaptured, run_spec_test, TestClient, LOCK},
+    bson::{doc, Bson, Document},
+    error::{ErrorKind, TRANSIENT_TRANSACTION_ERROR},
+    options::{Acknowledgment, ReadConcern, WriteConcern},
+    test::{
+        log_uncaptured,
+        run_spec_test,
+        EventClient,
+        FailCommandOptions,
+        FailPoint,
+        FailPointMode,
+        TestClient,
+        LOCK,
+    },
     Collection,
 };
 
@@ -80,3 +92,176 @@ async fn deserialize_recovery_token() {
     // Nevertheless, the recovery token should have been retrieved from the ok: 1 response.
     assert!(session.transaction.recovery_token.is_some());
 }
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn recovery_token_sent_on_commit_retry() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    // Use a single mongos so that the fail point is set on the mongos the transaction is pinned to.
+    let client = EventClient::with_additional_options(None, None, Some(false), None).await;
+    if !client.is_sharded() || client.server_version_lt(4, 4) {
+        log_uncaptured("skipping recovery_token_sent_on_commit_retry due to test topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let mut session = client.start_session(None).await.unwrap();
+
+    session.start_transaction(None).await.unwrap();
+    assert!(session.recovery_token().is_none());
+    coll.insert_one_with_session(doc! { "x": 1 }, None, &mut session)
+        .await
+        .unwrap();
+    let recovery_token = session
+        .recovery_token()
+        .cloned()
+        .expect("mongos should return a recovery token");
+
+    // The connection is closed as if the mongos went away, so the commit is retried.
+    let _fp_guard = client
+        .enable_failpoint(
+            FailPoint::fail_command(
+                &["commitTransaction"],
+                FailPointMode::Times(1),
+                FailCommandOptions::builder().close_connection(true).build(),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    session.commit_transaction().await.unwrap();
+
+    let commits = client.get_command_started_events(&["commitTransaction"]);
+    assert_eq!(commits.len(), 2);
+    for commit in commits {
+        assert_eq!(
+            commit.command.get_document("recoveryToken"),
+            Ok(&recovery_token)
+        );
+    }
+    assert_eq!(coll.count_documents(None, None).await.unwrap(), 1);
+}
+
+#[cfg_attr(feature = "tokio-runtime", tokio::test(flavor = "multi_thread"))]
+#[cfg_attr(feature = "async-std-runtime", async_std::test)]
+#[function_name::named]
+async fn transaction_builder() {
+    let _guard: RwLockWriteGuard<()> = LOCK.run_exclusively().await;
+
+    let client = EventClient::new().await;
+    if !client.supports_transactions() || client.server_version_lt(4, 4) {
+        log_uncaptured("skipping transaction_builder due to test topology");
+        return;
+    }
+
+    let coll = client
+        .init_db_and_coll(function_name!(), function_name!())
+        .await;
+    let mut session = client.start_session(None).await.unwrap();
+
+    // A transient error on the first insert causes the whole transaction to be retried.
+    let _fp_guard = client
+        .enable_failpoint(
+            FailPoint::fail_command(
+                &["insert"],
+                FailPointMode::Times(1),
+                FailCommandOptions::builder()
+                    .error_code(112)
+                    .error_labels(vec![TRANSIENT_TRANSACTION_ERROR.to_string()])
+                    .build(),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let mut builder = session
+        .transaction()
+        .read_concern(ReadConcern::majority())
+        .write_concern(WriteConcern::builder().w(Acknowledgment::Majority).build());
+    let mut attempts = 0;
+    let value = builder
+        .and_run(&mut attempts, |session, attempts| {
+            let coll = coll.clone();
+            async move {
+                **attempts += 1;
+                coll.insert_one_with_session(doc! { "x": 1 }, None, session)
+                    .await?;
+                Ok(**attempts)
+            }
+            .boxed()
+        })
+        .await
+        .unwrap();
+    assert_eq!(value, 2);
+
+    // The builder can be reused once the previous transaction has been committed.
+    builder
+        .and_run((), |session, _| {
+            let coll = coll.clone();
+            async move {
+                coll.insert_one_with_session(doc! { "x": 2 }, None, session)
+                    .await?;
+                Ok(())
+            }
+            .boxed()
+        })
+        .await
+        .unwrap();
+
+    // A non-transient error from the callback aborts the transaction and is returned.
+    let error = builder
+        .and_run((), |session, _| {
+            let coll = coll.clone();
+            async move {
+                coll.insert_one_with_session(doc! { "x": 3 }, None, session)
+                    .await?;
+                Err::<(), _>(
+                    ErrorKind::InvalidArgument {
+                        message: "abort".to_string(),
+                    }
+                    .into(),
+                )
+            }
+            .boxed()
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(*error.kind, ErrorKind::InvalidArgument { .. }));
+
+    let mut values: Vec<i32> = coll
+        .find(None, None)
+        .await
+        .unwrap()
+        .try_collect::<Vec<Document>>()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|doc| doc.get_i32("x").unwrap())
+        .collect();
+    values.sort_unstable();
+    assert_eq!(values, vec![1, 2]);
+
+    let inserts = client.get_command_started_events(&["insert"]);
+    assert_eq!(inserts.len(), 4);
+    let txn_numbers: Vec<&Bson> = inserts
+        .iter()
+        .map(|event| event.command.get("txnNumber").unwrap())
+        .collect();
+    assert!(txn_numbers.windows(2).all(|pair| pair[0] != pair[1]));
+    for event in &inserts {
+        let read_concern = event.command.get_document("readConcern").unwrap();
+        assert_eq!(read_concern.get_str("level"), Ok("majority"));
+    }
+
+    let commits = client.get_command_started_events(&["commitTransaction"]);
+    assert_eq!(commits.len(), 2);
+    for event in &commits {
+        let write_concern = event.command.get_document("writeConcern").unwrap();
+        assert_eq!(write_concern.get_str("w"), Ok("majority"));
+    }
+}
@@ -530,6 +530,10 @@ impl Find {
             max_await_time: None,
             selection_criteria: None,
             let_vars: self.let_vars.clone(),
+            deny_unknown_fields: None,
+            numeric_coercion: None,
+            max_document_depth: None,
+            deadline: None,
         };
         match &self.session {
             Some(session_id) => {
@@ -285,6 +285,7 @@ impl CollectionOrDatabaseOptions {
             read_concern: self.read_concern.clone(),
             selection_criteria: self.selection_criteria.clone(),
             write_concern: self.write_concern.clone(),
+            server_api: None,
         }
     }
 
@@ -293,6 +294,7 @@ impl CollectionOrDatabaseOptions {
             read_concern: self.read_concern.clone(),
             selection_criteria: self.selection_criteria.clone(),
             write_concern: self.write_concern.clone(),
+            server_api: None,
         }
     }
 }
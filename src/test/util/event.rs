@@ -32,12 +32,14 @@ use crate::{
             PoolReadyEvent,
         },
         command::{
+            CommandEvent,
             CommandEventHandler,
             CommandFailedEvent,
             CommandStartedEvent,
             CommandSucceededEvent,
         },
         sdam::{
+            CompatibilityWarningEvent,
             SdamEventHandler,
             ServerClosedEvent,
             ServerDescriptionChangedEvent,
@@ -95,6 +97,7 @@ pub(crate) enum SdamEvent {
     ServerHeartbeatStarted(ServerHeartbeatStartedEvent),
     ServerHeartbeatSucceeded(ServerHeartbeatSucceededEvent),
     ServerHeartbeatFailed(ServerHeartbeatFailedEvent),
+    CompatibilityWarning(CompatibilityWarningEvent),
 }
 
 impl SdamEvent {
@@ -109,19 +112,11 @@ impl SdamEvent {
             Self::ServerHeartbeatStarted(_) => "ServerHeartbeatStartedEvent",
             Self::ServerHeartbeatSucceeded(_) => "ServerHeartbeatSucceededEvent",
             Self::ServerHeartbeatFailed(_) => "ServerHeartbeatFailedEvent",
+            Self::CompatibilityWarning(_) => "CompatibilityWarningEvent",
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
-#[allow(clippy::large_enum_variant)]
-#[serde(untagged)]
-pub(crate) enum CommandEvent {
-    Started(CommandStartedEvent),
-    Succeeded(CommandSucceededEvent),
-    Failed(CommandFailedEvent),
-}
-
 impl CommandEvent {
     pub fn name(&self) -> &str {
         match self {
@@ -442,6 +437,12 @@ impl SdamEventHandler for EventHandler {
         self.handle(event.clone());
         add_event_to_queue(&self.sdam_events, event);
     }
+
+    fn handle_compatibility_warning_event(&self, event: CompatibilityWarningEvent) {
+        let event = SdamEvent::CompatibilityWarning(event);
+        self.handle(event.clone());
+        add_event_to_queue(&self.sdam_events, event);
+    }
 }
 
 impl CommandEventHandler for EventHandler {
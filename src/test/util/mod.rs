@@ -4,7 +4,7 @@ mod lock;
 mod matchable;
 
 pub(crate) use self::{
-    event::{CmapEvent, CommandEvent, Event, EventClient, EventHandler, SdamEvent},
+    event::{CmapEvent, Event, EventClient, EventHandler, SdamEvent},
     failpoint::{FailCommandOptions, FailPoint, FailPointGuard, FailPointMode},
     lock::TestLock,
     matchable::{assert_matches, eq_matches, MatchErrExt, Matchable},
@@ -12,6 +12,8 @@ pub(crate) use self::{
 
 use std::{fmt::Debug, sync::Arc, time::Duration};
 
+pub(crate) use crate::event::command::CommandEvent;
+
 use crate::{
     bson::{doc, Bson},
     hello::{hello_command, HelloCommandResponse},